@@ -17,6 +17,7 @@
 //! Polkadot CLI library.
 
 use clap::Parser;
+use std::path::PathBuf;
 
 #[allow(missing_docs)]
 #[derive(Debug, Parser)]
@@ -73,6 +74,12 @@ pub enum Subcommand {
 
 	/// Db meta columns information.
 	ChainInfo(sc_cli::ChainInfoCmd),
+
+	/// Inspect or repair the parachains database of a stopped node.
+	ParachainsDb(ParachainsDbCmd),
+
+	/// Validate a candidate again and report the result and the resources used by every phase.
+	ReplayPvf(ReplayPvfCmd),
 }
 
 #[allow(missing_docs)]
@@ -82,6 +89,171 @@ pub struct ValidationWorkerCommand {
 	pub socket_path: String,
 }
 
+#[allow(missing_docs)]
+#[derive(Debug, Parser)]
+pub struct ParachainsDbCmd {
+	#[clap(subcommand)]
+	pub action: ParachainsDbAction,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+}
+
+/// The operations supported on the parachains database.
+#[derive(Debug, Parser)]
+pub enum ParachainsDbAction {
+	/// Print the number of keys and their total size for every column.
+	Stats,
+
+	/// Decode the entries of a column and print them as JSON.
+	Dump {
+		/// The column to decode, e.g. `availability-meta` or `dispute-coordinator`.
+		#[clap(long)]
+		column: String,
+	},
+
+	/// Remove all entries of a single column.
+	Prune {
+		/// The column to clear.
+		#[clap(long)]
+		column: String,
+
+		/// Skip the interactive confirmation.
+		#[clap(short = 'y')]
+		yes: bool,
+	},
+
+	/// Export the disputes known to the dispute coordinator with all votes.
+	ExportDisputes {
+		/// The first session to export, defaults to the oldest one stored.
+		#[clap(long)]
+		from_session: Option<u32>,
+
+		/// The last session to export, defaults to the newest one stored.
+		#[clap(long)]
+		to_session: Option<u32>,
+
+		/// The output format.
+		#[clap(long, default_value = "json", possible_values = &["json", "csv"])]
+		format: String,
+
+		/// A validator operated by this node, SS58 or hex encoded. Can be given multiple times.
+		#[clap(long = "validator")]
+		validators: Vec<String>,
+	},
+
+	/// Report the votes against the supermajority of all concluded disputes.
+	AuditDisputes {
+		/// The first session to audit, defaults to the oldest one stored.
+		#[clap(long)]
+		from_session: Option<u32>,
+
+		/// The last session to audit, defaults to the newest one stored.
+		#[clap(long)]
+		to_session: Option<u32>,
+
+		/// The output format.
+		#[clap(long, default_value = "json", possible_values = &["json", "csv"])]
+		format: String,
+
+		/// Only report votes of this validator, SS58 or hex encoded. Can be given multiple times.
+		#[clap(long = "validator")]
+		validators: Vec<String>,
+	},
+
+	/// Print the unfinalized blocks known to chain selection with their viability, approval
+	/// and stagnation state as JSON.
+	ChainSelectionTree,
+
+	/// Override the chain selection state of an unfinalized block.
+	///
+	/// The override is recorded in the override log of the chain selection column.
+	ChainSelectionOverride {
+		/// The hex encoded hash of the block.
+		#[clap(long)]
+		block: String,
+
+		/// The override to apply, `revert` or `unstagnate`.
+		#[clap(long)]
+		action: String,
+
+		/// Skip the interactive confirmation.
+		#[clap(short = 'y')]
+		yes: bool,
+	},
+
+	/// Print the log of chain selection overrides as JSON, oldest first.
+	ChainSelectionOverrides,
+}
+
+impl sc_cli::CliConfiguration for ParachainsDbCmd {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&sc_cli::DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+#[allow(missing_docs)]
+#[derive(Debug, Parser)]
+pub struct ReplayPvfCmd {
+	/// The file holding the validation code, either compressed or plain wasm.
+	#[clap(long)]
+	pub code: PathBuf,
+
+	/// The file holding the SCALE encoded `PersistedValidationData` of the candidate.
+	#[clap(long, requires = "pov", conflicts_with = "candidate_hash")]
+	pub persisted_validation_data: Option<PathBuf>,
+
+	/// The file holding the SCALE encoded `PoV` of the candidate.
+	#[clap(long, requires = "persisted_validation_data", conflicts_with = "candidate_hash")]
+	pub pov: Option<PathBuf>,
+
+	/// Load the persisted validation data and the PoV of this candidate from the local
+	/// availability store instead of files.
+	#[clap(long, required_unless_present = "persisted_validation_data")]
+	pub candidate_hash: Option<String>,
+
+	/// The time the execution may take, in seconds.
+	#[clap(long, default_value = "6")]
+	pub execution_timeout: u64,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: sc_cli::SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: sc_cli::DatabaseParams,
+}
+
+impl sc_cli::CliConfiguration for ReplayPvfCmd {
+	fn shared_params(&self) -> &sc_cli::SharedParams {
+		&self.shared_params
+	}
+
+	fn database_params(&self) -> Option<&sc_cli::DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// What finality votes fall back to if the dispute coordinator does not respond in time.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq)]
+#[clap(rename_all = "kebab-case")]
+pub enum DisputeCoordinatorFallback {
+	/// Do not advance the vote.
+	Target,
+	/// Vote for the highest approved block regardless of disputes.
+	ApprovedAncestor,
+}
+
 #[allow(missing_docs)]
 #[derive(Debug, Parser)]
 #[cfg_attr(feature = "malus", derive(Clone))]
@@ -122,6 +294,23 @@ pub struct RunCmd {
 	#[clap(long)]
 	pub jaeger_agent: Option<String>,
 
+	/// Export spans to an OpenTelemetry collector instead of a jaeger agent.
+	///
+	/// Must be a valid URL, commonly `http://127.0.0.1:4317` for gRPC
+	/// or `http://127.0.0.1:4318/v1/traces` for HTTP.
+	#[clap(long, conflicts_with = "jaeger_agent")]
+	pub jaeger_otlp_endpoint: Option<String>,
+
+	/// The protocol used to reach the OpenTelemetry collector.
+	#[clap(long, default_value = "grpc", possible_values = &["grpc", "http"])]
+	pub jaeger_otlp_protocol: String,
+
+	/// The share of traces to record, between `0` and `1`.
+	///
+	/// Traces of disputed candidates are always recorded.
+	#[clap(long, default_value = "1")]
+	pub jaeger_sampling_rate: f64,
+
 	/// Add the destination address to the `pyroscope` agent.
 	///
 	/// Must be valid socket address, of format `IP:Port`
@@ -144,6 +333,78 @@ pub struct RunCmd {
 	/// **Dangerous!** Do not touch unless explicitly adviced to.
 	#[clap(long)]
 	pub overseer_channel_capacity_override: Option<usize>,
+
+	/// The number of PVF preparation workers kept for non-critical jobs.
+	#[clap(long)]
+	pub pvf_prepare_workers_soft_max: Option<usize>,
+
+	/// The maximum number of PVF preparation workers, including the ones spawned for critical jobs.
+	///
+	/// Must not be lower than `--pvf-prepare-workers-soft-max`.
+	#[clap(long)]
+	pub pvf_prepare_workers_hard_max: Option<usize>,
+
+	/// The number of PVF execution workers.
+	#[clap(long)]
+	pub pvf_execute_workers: Option<usize>,
+
+	/// Prevent PVF jobs from accessing the file system and the network. Only supported on Linux.
+	///
+	/// The restriction can't be lifted from a worker, so every job spawns a fresh worker, which
+	/// delays every validation by the time it takes to spawn one.
+	#[clap(long)]
+	pub pvf_forbid_job_syscalls: bool,
+
+	/// Memory budget of the runtime API cache in MiB, shared by all kinds of requests.
+	#[clap(long)]
+	pub runtime_api_cache_size: Option<usize>,
+
+	/// Record all messages and signals delivered to subsystems to the given file.
+	///
+	/// Only meant for debugging, the log grows quickly.
+	#[clap(long)]
+	pub overseer_message_log: Option<PathBuf>,
+
+	/// Collect per-subsystem poll time metrics, counting polls longer than the given
+	/// number of milliseconds as slow.
+	///
+	/// Allocations are attributed to subsystems as well when running with jemalloc.
+	#[clap(long)]
+	pub subsystem_slow_poll_threshold_ms: Option<u64>,
+
+	/// A JSON file with the collators allowed and denied to collate for each para.
+	///
+	/// The file holds an array of objects with the `paraId` and the `allow` and `deny`
+	/// lists of SS58 encoded collator ids. If the `allow` list of a para is not empty,
+	/// only the collators on it are accepted.
+	#[clap(long)]
+	pub collator_lists: Option<PathBuf>,
+
+	/// Stop advancing finality votes once more than the given number of blocks are unapproved
+	/// or disputed.
+	///
+	/// By default, votes keep advancing along the approved and undisputed chain. Must be between
+	/// 10 and the depth at which finality is forced regardless of approvals and disputes.
+	#[clap(long)]
+	pub finality_hold_back_depth: Option<u32>,
+
+	/// How long finality votes wait for the dispute coordinator, in milliseconds.
+	///
+	/// By default, votes wait until the dispute coordinator responds.
+	#[clap(long)]
+	pub dispute_coordinator_timeout_ms: Option<u64>,
+
+	/// What finality votes fall back to if the dispute coordinator does not respond in time.
+	///
+	/// `target` does not advance the vote. `approved-ancestor` votes for the highest approved
+	/// block regardless of disputes, which risks finalizing a block with a disputed candidate.
+	#[clap(long, arg_enum, default_value_t = DisputeCoordinatorFallback::Target)]
+	pub dispute_coordinator_fallback: DisputeCoordinatorFallback,
+
+	/// Warn when the best block is more than the given number of blocks ahead of the block
+	/// finality votes build on.
+	#[clap(long)]
+	pub finality_lag_alert: Option<u32>,
 }
 
 #[allow(missing_docs)]
@@ -14,7 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{Cli, Subcommand};
+use crate::cli::{
+	Cli, DisputeCoordinatorFallback, ParachainsDbAction, ReplayPvfCmd, RunCmd, Subcommand,
+};
 use frame_benchmarking_cli::{BenchmarkCmd, ExtrinsicFactory, SUBSTRATE_REFERENCE_HARDWARE};
 use futures::future::TryFutureExt;
 use log::info;
@@ -25,7 +27,7 @@ use sc_cli::{RuntimeVersion, SubstrateCli};
 use service::{self, HeaderBackend, IdentifyVariant};
 use sp_core::crypto::Ss58AddressFormatRegistry;
 use sp_keyring::Sr25519Keyring;
-use std::net::ToSocketAddrs;
+use std::{io::Write, net::ToSocketAddrs};
 
 pub use crate::{error::Error, service::BlockId};
 pub use polkadot_performance_test::PerfCheckError;
@@ -264,6 +266,256 @@ fn host_perf_check() -> Result<()> {
 	}
 }
 
+/// Runs an offline operation on the parachains database.
+fn parachains_db(action: &ParachainsDbAction, db_source: &service::DatabaseSource) -> Result<()> {
+	use service::parachains_db::{chain_selection, disputes, inspect, open_for_inspection};
+
+	fn parse_validators(
+		validators: &[String],
+	) -> Result<Vec<polkadot_primitives::v2::ValidatorId>> {
+		validators
+			.iter()
+			.map(|v| disputes::parse_validator(v).map_err(Error::Other))
+			.collect()
+	}
+
+	let db = open_for_inspection(db_source)?;
+	match action {
+		ParachainsDbAction::Stats => {
+			let stats = db.column_stats().map_err(service::Error::Io)?;
+			let json = serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?;
+			println!("{}", json);
+		},
+		ParachainsDbAction::Dump { column } => {
+			let column = inspect::column_by_name(column).map_err(service::Error::Io)?;
+			let records = db.decode_column(column).map_err(service::Error::Io)?;
+			let json = serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?;
+			println!("{}", json);
+		},
+		ParachainsDbAction::Prune { column, yes } => {
+			let col = inspect::column_by_name(column).map_err(service::Error::Io)?;
+			if !yes {
+				print!("Are you sure to remove all entries of the {:?} column? [y/N]: ", column);
+				std::io::stdout().flush().map_err(service::Error::Io)?;
+
+				let mut input = String::new();
+				std::io::stdin().read_line(&mut input).map_err(service::Error::Io)?;
+				if !matches!(input.trim(), "y" | "Y") {
+					println!("Aborted");
+					return Ok(())
+				}
+			}
+
+			db.clear_column(col).map_err(service::Error::Io)?;
+			println!("{:?} column cleared", column);
+		},
+		ParachainsDbAction::ExportDisputes { from_session, to_session, format, validators } => {
+			let validators = parse_validators(validators)?;
+			let sessions = from_session.unwrap_or(0)..=to_session.unwrap_or(u32::MAX);
+			let disputes = db.export_disputes(sessions, &validators).map_err(service::Error::Io)?;
+			if format == "csv" {
+				print!("{}", disputes::disputes_to_csv(&disputes));
+			} else {
+				let json = serde_json::to_string_pretty(&disputes).map_err(|e| e.to_string())?;
+				println!("{}", json);
+			}
+		},
+		ParachainsDbAction::AuditDisputes { from_session, to_session, format, validators } => {
+			let validators = parse_validators(validators)?;
+			let sessions = from_session.unwrap_or(0)..=to_session.unwrap_or(u32::MAX);
+			let disputes = db.export_disputes(sessions, &validators).map_err(service::Error::Io)?;
+			let entries = disputes::audit(&disputes, &validators);
+			if format == "csv" {
+				print!("{}", disputes::audit_to_csv(&entries));
+			} else {
+				let json = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+				println!("{}", json);
+			}
+		},
+		ParachainsDbAction::ChainSelectionTree => {
+			let blocks = db.chain_selection_tree().map_err(service::Error::Io)?;
+			let json = serde_json::to_string_pretty(&blocks).map_err(|e| e.to_string())?;
+			println!("{}", json);
+		},
+		ParachainsDbAction::ChainSelectionOverride { block, action, yes } => {
+			let hash = chain_selection::parse_block_hash(block).map_err(Error::Other)?;
+			let kind = chain_selection::parse_override(action).map_err(Error::Other)?;
+			if !yes {
+				print!("Are you sure to {} block {:?}? [y/N]: ", kind.as_str(), hash);
+				std::io::stdout().flush().map_err(service::Error::Io)?;
+
+				let mut input = String::new();
+				std::io::stdin().read_line(&mut input).map_err(service::Error::Io)?;
+				if !matches!(input.trim(), "y" | "Y") {
+					println!("Aborted");
+					return Ok(())
+				}
+			}
+
+			match db.override_chain_selection(hash, kind).map_err(service::Error::Io)? {
+				Some(block) => {
+					let json = serde_json::to_string_pretty(&block).map_err(|e| e.to_string())?;
+					println!("{}", json);
+				},
+				None => return Err(Error::Other(format!("Block {:?} is not known", hash))),
+			}
+		},
+		ParachainsDbAction::ChainSelectionOverrides => {
+			let records = db.chain_selection_overrides().map_err(service::Error::Io)?;
+			let json = serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?;
+			println!("{}", json);
+		},
+	}
+
+	Ok(())
+}
+
+/// Validates a candidate once in process and once through a validation host, printing the
+/// outcome and the resources used.
+fn replay_pvf(cmd: &ReplayPvfCmd, db_source: &service::DatabaseSource) -> Result<()> {
+	use futures::future::Either;
+	use parity_scale_codec::{Decode, Encode};
+	use polkadot_node_core_pvf::{replay_in_process, replay_via_host, sp_maybe_compressed_blob};
+	use polkadot_node_primitives::{BlockData, PoV, POV_BOMB_LIMIT, VALIDATION_CODE_BOMB_LIMIT};
+	use polkadot_parachain::primitives::ValidationParams;
+	use polkadot_primitives::v2::{CandidateHash, PersistedValidationData};
+	use std::time::Duration;
+
+	fn decode_file<T: Decode>(path: &std::path::Path) -> Result<T> {
+		let bytes = std::fs::read(path).map_err(service::Error::Io)?;
+		T::decode(&mut &bytes[..])
+			.map_err(|e| Error::Other(format!("Cannot decode {:?}: {}", path, e)))
+	}
+
+	let code = std::fs::read(&cmd.code).map_err(service::Error::Io)?;
+	let code = sp_maybe_compressed_blob::decompress(&code, VALIDATION_CODE_BOMB_LIMIT)
+		.map_err(|e| Error::Other(format!("Cannot decompress the code: {:?}", e)))?
+		.to_vec();
+
+	let (validation_data, pov): (PersistedValidationData, PoV) =
+		match (&cmd.candidate_hash, &cmd.persisted_validation_data, &cmd.pov) {
+			(Some(candidate_hash), _, _) => {
+				let candidate_hash = candidate_hash.parse().map(CandidateHash).map_err(|_| {
+					Error::Other(format!("Invalid candidate hash {:?}", candidate_hash))
+				})?;
+				let available_data = service::parachains_db::open_for_inspection(db_source)?
+					.available_data(&candidate_hash)
+					.map_err(service::Error::Io)?
+					.ok_or_else(|| {
+						Error::Other(format!("No PoV stored for candidate {:?}", candidate_hash))
+					})?;
+				(available_data.validation_data, (*available_data.pov).clone())
+			},
+			(None, Some(validation_data), Some(pov)) =>
+				(decode_file(validation_data)?, decode_file(pov)?),
+			_ =>
+				return Err(Error::Other(
+					"Either a candidate hash or both the validation data and the PoV are required"
+						.into(),
+				)),
+		};
+
+	let block_data = sp_maybe_compressed_blob::decompress(&pov.block_data.0, POV_BOMB_LIMIT)
+		.map_err(|e| Error::Other(format!("Cannot decompress the PoV: {:?}", e)))?;
+	let params = ValidationParams {
+		parent_head: validation_data.parent_head,
+		block_data: BlockData(block_data.to_vec()),
+		relay_parent_number: validation_data.relay_parent_number,
+		relay_parent_storage_root: validation_data.relay_parent_storage_root,
+	}
+	.encode();
+
+	let report = replay_in_process(code.clone(), params.clone());
+	for phase in &report.phases {
+		println!(
+			"{}: {:?} wall time, {} ms cpu time, {} KiB peak memory",
+			phase.name,
+			phase.duration,
+			phase.cpu_time_ms,
+			phase.peak_memory / 1024,
+		);
+	}
+	println!("In process result: {:?}", report.result);
+
+	let cache_dir = tempfile::tempdir().map_err(service::Error::Io)?;
+	let program_path = std::env::current_exe().map_err(service::Error::Io)?;
+	let (mut host, run_host) = polkadot_node_core_pvf::start(
+		polkadot_node_core_pvf::Config::new(cache_dir.path().to_owned(), program_path),
+		Default::default(),
+	);
+	let replay =
+		replay_via_host(&mut host, code, params, Duration::from_secs(cmd.execution_timeout));
+	let host_report = match futures::executor::block_on(futures::future::select(
+		Box::pin(run_host),
+		Box::pin(replay),
+	)) {
+		Either::Left(_) => return Err(Error::Other("The validation host exited".into())),
+		Either::Right((host_report, _)) => host_report,
+	};
+
+	println!("Validation host preparation: {:?}", host_report.preparation_time);
+	if let Some(execution_time) = host_report.execution_time {
+		println!("Validation host execution: {:?}", execution_time);
+	}
+	println!("Validation host result: {:?}", host_report.result);
+
+	Ok(())
+}
+
+/// Collects the PVF worker counts from the command line, using the defaults for the ones not
+/// given.
+fn pvf_worker_counts(run: &RunCmd) -> Result<service::PvfWorkerCounts> {
+	let defaults = service::PvfWorkerCounts::default();
+	let prepare_soft_max = run.pvf_prepare_workers_soft_max.unwrap_or(defaults.prepare_soft_max);
+	let counts = service::PvfWorkerCounts {
+		prepare_soft_max,
+		prepare_hard_max: run
+			.pvf_prepare_workers_hard_max
+			.unwrap_or(defaults.prepare_hard_max.max(prepare_soft_max)),
+		execute_max: run.pvf_execute_workers.unwrap_or(defaults.execute_max),
+	};
+
+	if counts.prepare_soft_max == 0 || counts.execute_max == 0 {
+		return Err(Error::Other("The number of PVF workers must be at least 1".into()))
+	}
+	if counts.prepare_soft_max > counts.prepare_hard_max {
+		return Err(Error::Other(
+			"--pvf-prepare-workers-soft-max must not exceed --pvf-prepare-workers-hard-max".into(),
+		))
+	}
+
+	Ok(counts)
+}
+
+/// Collects the safety policy of finality votes from the command line, using the defaults for the
+/// options not given.
+fn finality_policy(run: &RunCmd) -> Result<service::FinalityPolicy> {
+	let policy = service::FinalityPolicy {
+		hold_back_depth: run.finality_hold_back_depth,
+		dispute_coordinator_timeout: run
+			.dispute_coordinator_timeout_ms
+			.map(std::time::Duration::from_millis),
+		dispute_coordinator_fallback: match run.dispute_coordinator_fallback {
+			DisputeCoordinatorFallback::Target => service::DisputeCoordinatorFallback::Target,
+			DisputeCoordinatorFallback::ApprovedAncestor =>
+				service::DisputeCoordinatorFallback::ApprovedAncestor,
+		},
+		finality_lag_alert: run.finality_lag_alert,
+	};
+
+	if let Some(depth) = policy.hold_back_depth {
+		if !service::HOLD_BACK_DEPTH_RANGE.contains(&depth) {
+			return Err(Error::Other(format!(
+				"--finality-hold-back-depth must be between {} and {}",
+				service::HOLD_BACK_DEPTH_RANGE.start(),
+				service::HOLD_BACK_DEPTH_RANGE.end(),
+			)))
+		}
+	}
+
+	Ok(policy)
+}
+
 /// Launch a node, accepting arguments just like a regular node,
 /// accepts an alternative overseer generator, to adjust behavior
 /// for integration tests as needed.
@@ -315,17 +567,31 @@ where
 		info!("----------------------------");
 	}
 
-	let jaeger_agent = if let Some(ref jaeger_agent) = cli.run.jaeger_agent {
-		Some(
+	let pvf_workers = pvf_worker_counts(&cli.run)?;
+	let finality_policy = finality_policy(&cli.run)?;
+
+	let jaeger_exporter = if let Some(ref jaeger_agent) = cli.run.jaeger_agent {
+		Some(service::jaeger::JaegerExporter::Agent(
 			jaeger_agent
 				.to_socket_addrs()
 				.map_err(Error::AddressResolutionFailure)?
 				.next()
 				.ok_or_else(|| Error::AddressResolutionMissing)?,
-		)
+		))
+	} else if let Some(ref endpoint) = cli.run.jaeger_otlp_endpoint {
+		let protocol = match cli.run.jaeger_otlp_protocol.as_str() {
+			"http" => service::jaeger::OtlpProtocol::Http,
+			_ => service::jaeger::OtlpProtocol::Grpc,
+		};
+		Some(service::jaeger::JaegerExporter::Otlp { endpoint: endpoint.clone(), protocol })
 	} else {
 		None
 	};
+	let jaeger_config = jaeger_exporter.map(|exporter| {
+		service::jaeger::JaegerConfig::builder()
+			.exporter(exporter)
+			.sampling_rate(cli.run.jaeger_sampling_rate)
+	});
 
 	runner.run_node_until_exit(move |config| async move {
 		let hwbench = if !cli.run.no_hardware_benchmarks {
@@ -342,13 +608,28 @@ where
 			service::IsCollator::No,
 			grandpa_pause,
 			cli.run.beefy,
-			jaeger_agent,
+			jaeger_config,
 			None,
 			false,
 			overseer_gen,
 			cli.run.overseer_channel_capacity_override,
 			maybe_malus_finality_delay,
 			hwbench,
+			service::NewFullParams {
+				pvf_workers,
+				pvf_forbid_job_syscalls: cli.run.pvf_forbid_job_syscalls,
+				runtime_api_cache_budget: cli
+					.run
+					.runtime_api_cache_size
+					.map(|mib| mib * 1024 * 1024),
+				overseer_message_log: cli.run.overseer_message_log,
+				subsystem_slow_poll_threshold: cli
+					.run
+					.subsystem_slow_poll_threshold_ms
+					.map(std::time::Duration::from_millis),
+				collator_lists: cli.run.collator_lists,
+				finality_policy,
+			},
 		)
 		.map(|full| full.task_manager)
 		.map_err(Into::into)
@@ -681,6 +962,14 @@ pub fn run() -> Result<()> {
 			let runner = cli.create_runner(cmd)?;
 			Ok(runner.sync_run(|config| cmd.run::<service::Block>(&config))?)
 		},
+		Some(Subcommand::ParachainsDb(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| parachains_db(&cmd.action, &config.database))
+		},
+		Some(Subcommand::ReplayPvf(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| replay_pvf(cmd, &config.database))
+		},
 	}?;
 
 	#[cfg(feature = "pyroscope")]
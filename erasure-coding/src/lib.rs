@@ -25,7 +25,7 @@
 //! The data is coded so any f+1 chunks can be used to reconstruct the full data.
 
 use parity_scale_codec::{Decode, Encode};
-use polkadot_node_primitives::{AvailableData, Proof};
+use polkadot_node_primitives::{AvailableData, MultiProof, Proof};
 use polkadot_primitives::v2::{BlakeTwo256, Hash as H256, HashT};
 use sp_core::Blake2Hasher;
 use sp_trie::{
@@ -36,6 +36,12 @@ use thiserror::Error;
 
 use novelpoly::{CodeParams, WrappedShard};
 
+mod stream;
+
+pub use stream::{
+	obtain_chunks_streaming, reconstruct_streaming, StreamingDecoder, StreamingEncoder,
+};
+
 // we are limited to the field order of GF(2^16), which is 65536
 const MAX_VALIDATORS: usize = novelpoly::f2e16::FIELD_SIZE;
 
@@ -113,6 +119,19 @@ fn code_params(n_validators: usize) -> Result<CodeParams, Error> {
 	})
 }
 
+/// Obtain the number of systematic chunks, i.e. the chunks which hold the encoded data as is.
+///
+/// These are the chunks with the lowest indices. Once all of them are present, the data can be
+/// recovered with [`reconstruct_from_systematic_chunks`], which does not need any decoding.
+pub fn systematic_recovery_threshold(n_validators: usize) -> Result<usize, Error> {
+	// Fails for the same validator counts as encoding does.
+	code_params(n_validators)?;
+	// The code parameters round the recovery threshold down to a power of two, which is `k`.
+	let threshold = recovery_threshold(n_validators)?;
+
+	Ok(1 << (usize::BITS - 1 - threshold.leading_zeros()))
+}
+
 /// Obtain erasure-coded chunks for v1 `AvailableData`, one for each validator.
 ///
 /// Works only up to 65536 validators, and `n_validators` must be non-zero.
@@ -185,21 +204,93 @@ where
 		received_shards[chunk_idx] = Some(WrappedShard::new(chunk_data.to_vec()));
 	}
 
-	let res = params.make_encoder().reconstruct(received_shards);
-
-	let payload_bytes = match res {
-		Err(e) => match e {
-			novelpoly::Error::NeedMoreShards { .. } => return Err(Error::NotEnoughChunks),
-			novelpoly::Error::ParamterMustBePowerOf2 { .. } => return Err(Error::UnevenLength),
-			novelpoly::Error::WantedShardCountTooHigh(_) => return Err(Error::TooManyValidators),
-			novelpoly::Error::WantedShardCountTooLow(_) => return Err(Error::NotEnoughValidators),
-			novelpoly::Error::PayloadSizeIsZero { .. } => return Err(Error::BadPayload),
-			novelpoly::Error::InconsistentShardLengths { .. } =>
-				return Err(Error::NonUniformChunks),
-			_ => return Err(Error::UnknownReconstruction),
-		},
-		Ok(payload_bytes) => payload_bytes,
-	};
+	let payload_bytes = params
+		.make_encoder()
+		.reconstruct(received_shards)
+		.map_err(reconstruction_error)?;
+
+	Decode::decode(&mut &payload_bytes[..]).or_else(|_e| Err(Error::BadPayload))
+}
+
+fn reconstruction_error(e: novelpoly::Error) -> Error {
+	match e {
+		novelpoly::Error::NeedMoreShards { .. } => Error::NotEnoughChunks,
+		novelpoly::Error::ParamterMustBePowerOf2 { .. } => Error::UnevenLength,
+		novelpoly::Error::WantedShardCountTooHigh(_) => Error::TooManyValidators,
+		novelpoly::Error::WantedShardCountTooLow(_) => Error::NotEnoughValidators,
+		novelpoly::Error::PayloadSizeIsZero { .. } => Error::BadPayload,
+		novelpoly::Error::InconsistentShardLengths { .. } => Error::NonUniformChunks,
+		_ => Error::UnknownReconstruction,
+	}
+}
+
+/// Reconstruct the v1 available data from the systematic chunks.
+///
+/// Provide an iterator containing chunk data and the corresponding index. All of the first
+/// [`systematic_recovery_threshold`] chunks must be present, any others are ignored.
+///
+/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+pub fn reconstruct_from_systematic_chunks_v1<'a, I: 'a>(
+	n_validators: usize,
+	chunks: I,
+) -> Result<AvailableData, Error>
+where
+	I: IntoIterator<Item = (&'a [u8], usize)>,
+{
+	reconstruct_from_systematic_chunks(n_validators, chunks)
+}
+
+/// Reconstruct decodable data from the systematic chunks.
+///
+/// Provide an iterator containing chunk data and the corresponding index. All of the first
+/// [`systematic_recovery_threshold`] chunks must be present, any others are ignored. As these
+/// chunks hold the data itself, it is recovered by just putting their symbols back in order.
+///
+/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+pub fn reconstruct_from_systematic_chunks<'a, I: 'a, T: Decode>(
+	n_validators: usize,
+	chunks: I,
+) -> Result<T, Error>
+where
+	I: IntoIterator<Item = (&'a [u8], usize)>,
+{
+	let k = systematic_recovery_threshold(n_validators)?;
+	let mut systematic_chunks: Vec<Option<&[u8]>> = vec![None; k];
+	let mut shard_len = None;
+	for (chunk_data, chunk_idx) in chunks {
+		if chunk_idx >= n_validators {
+			return Err(Error::ChunkIndexOutOfBounds { chunk_index: chunk_idx, n_validators })
+		}
+		if chunk_idx >= k {
+			continue
+		}
+
+		let shard_len = shard_len.get_or_insert_with(|| chunk_data.len());
+
+		if *shard_len % 2 != 0 {
+			return Err(Error::UnevenLength)
+		}
+
+		if *shard_len != chunk_data.len() || *shard_len == 0 {
+			return Err(Error::NonUniformChunks)
+		}
+
+		systematic_chunks[chunk_idx] = Some(chunk_data);
+	}
+
+	let systematic_chunks = systematic_chunks
+		.into_iter()
+		.collect::<Option<Vec<_>>>()
+		.ok_or(Error::NotEnoughChunks)?;
+	let shard_len = shard_len.unwrap_or_default();
+
+	// The `i`-th symbol of the `j`-th systematic chunk is the `j`-th symbol of the `i`-th run.
+	let mut payload_bytes = Vec::with_capacity(shard_len * k);
+	for pos in (0..shard_len).step_by(2) {
+		for chunk in &systematic_chunks {
+			payload_bytes.extend_from_slice(&chunk[pos..pos + 2]);
+		}
+	}
 
 	Decode::decode(&mut &payload_bytes[..]).or_else(|_e| Err(Error::BadPayload))
 }
@@ -218,6 +309,37 @@ impl<'a, I: AsRef<[u8]>> Branches<'a, I> {
 	pub fn root(&self) -> H256 {
 		self.root.clone()
 	}
+
+	/// Get a single proof covering the merkle branches of all chunks at the given indices.
+	///
+	/// Fails with `BranchOutOfBounds` if any of the indices has no chunk.
+	pub fn multi_proof(&self, indices: &[usize]) -> Result<MultiProof, Error> {
+		use sp_trie::Recorder;
+
+		let mut recorder = Recorder::<LayoutV0<Blake2Hasher>>::new();
+		{
+			let trie = TrieDBBuilder::new(&self.trie_storage, &self.root)
+				.with_recorder(&mut recorder)
+				.build();
+
+			for index in indices {
+				let res = (*index as u32).using_encoded(|s| trie.get(s));
+				if res.expect("all nodes in trie present; qed").is_none() {
+					return Err(Error::BranchOutOfBounds)
+				}
+			}
+		}
+
+		// Nodes close to the root are visited by every lookup, only keep them once.
+		let mut seen = std::collections::HashSet::new();
+		let nodes: Vec<Vec<u8>> = recorder
+			.drain()
+			.into_iter()
+			.filter(|r| seen.insert(r.hash))
+			.map(|r| r.data)
+			.collect();
+		MultiProof::try_from(nodes).map_err(|_| Error::InvalidBranchProof)
+	}
 }
 
 impl<'a, I: AsRef<[u8]>> Iterator for Branches<'a, I> {
@@ -276,12 +398,34 @@ where
 /// Verify a merkle branch, yielding the chunk hash meant to be present at that
 /// index.
 pub fn branch_hash(root: &H256, branch_nodes: &Proof, index: usize) -> Result<H256, Error> {
+	let trie_storage = proof_storage(branch_nodes.iter());
+	let trie = TrieDBBuilder::new(&trie_storage, &root).build();
+	chunk_hash_at(&trie, index)
+}
+
+/// Verify a multi proof, yielding the chunk hashes meant to be present at the given indices, in
+/// the same order.
+///
+/// Fails if the proof does not cover the branch of any of the indices.
+pub fn branch_hashes(
+	root: &H256,
+	proof: &MultiProof,
+	indices: &[usize],
+) -> Result<Vec<H256>, Error> {
+	let trie_storage = proof_storage(proof.iter());
+	let trie = TrieDBBuilder::new(&trie_storage, &root).build();
+	indices.iter().map(|index| chunk_hash_at(&trie, *index)).collect()
+}
+
+fn proof_storage<'a>(nodes: impl Iterator<Item = &'a [u8]>) -> MemoryDB<Blake2Hasher> {
 	let mut trie_storage: MemoryDB<Blake2Hasher> = MemoryDB::default();
-	for node in branch_nodes.iter() {
+	for node in nodes {
 		(&mut trie_storage as &mut sp_trie::HashDB<_>).insert(EMPTY_PREFIX, node);
 	}
+	trie_storage
+}
 
-	let trie = TrieDBBuilder::new(&trie_storage, &root).build();
+fn chunk_hash_at(trie: &impl Trie<LayoutV0<Blake2Hasher>>, index: usize) -> Result<H256, Error> {
 	let res = (index as u32).using_encoded(|key| {
 		trie.get_with(key, |raw_hash: &[u8]| H256::decode(&mut &raw_hash[..]))
 	});
@@ -379,6 +523,39 @@ mod tests {
 		assert_eq!(reconstructed, available_data);
 	}
 
+	#[test]
+	fn systematic_recovery_threshold_is_the_power_of_two_below_the_threshold() {
+		for (n_validators, k) in [(2, 1), (3, 1), (10, 4), (100, 32), (300, 64), (1000, 256)] {
+			assert_eq!(systematic_recovery_threshold(n_validators), Ok(k));
+		}
+		assert_eq!(systematic_recovery_threshold(1), Err(Error::NotEnoughValidators));
+	}
+
+	#[test]
+	fn systematic_round_trip_works() {
+		let pov = PoV { block_data: BlockData((0..255).cycle().take(10_000).collect()) };
+		let available_data = AvailableData { pov: pov.into(), validation_data: Default::default() };
+
+		for n_validators in [2, 3, 10, 100, 1000] {
+			let k = systematic_recovery_threshold(n_validators).unwrap();
+			assert!(k <= recovery_threshold(n_validators).unwrap());
+
+			let chunks = obtain_chunks(n_validators, &available_data).unwrap();
+			let reconstructed: AvailableData = reconstruct_from_systematic_chunks(
+				n_validators,
+				chunks.iter().enumerate().rev().map(|(i, c)| (&c[..], i)),
+			)
+			.unwrap();
+			assert_eq!(reconstructed, available_data);
+
+			let missing_one = reconstruct_from_systematic_chunks_v1(
+				n_validators,
+				chunks.iter().enumerate().skip(1).map(|(i, c)| (&c[..], i)),
+			);
+			assert_eq!(missing_one, Err(Error::NotEnoughChunks));
+		}
+	}
+
 	#[test]
 	fn reconstruct_does_not_panic_on_low_validator_count() {
 		let reconstructed = reconstruct_v1(1, [].iter().cloned());
@@ -416,4 +593,52 @@ mod tests {
 			generate_trie_and_generate_proofs(i);
 		}
 	}
+
+	#[test]
+	fn multi_proof_covers_requested_branches() {
+		let pov = PoV { block_data: BlockData((0..255).cycle().take(10_000).collect()) };
+		let available_data = AvailableData { pov: pov.into(), validation_data: Default::default() };
+		let chunks = obtain_chunks(100, &available_data).unwrap();
+
+		let branches = branches(chunks.as_ref());
+		let root = branches.root();
+		let indices = [0, 3, 17, 42, 99];
+
+		let proof = branches.multi_proof(&indices).unwrap();
+		let encode = Encode::encode(&proof);
+		let decode: MultiProof = Decode::decode(&mut &encode[..]).unwrap();
+		assert_eq!(proof, decode);
+
+		let hashes = branch_hashes(&root, &decode, &indices).unwrap();
+		let expected: Vec<_> = indices.iter().map(|i| BlakeTwo256::hash(&chunks[*i])).collect();
+		assert_eq!(hashes, expected);
+
+		// The batched proof is smaller than the single proofs combined.
+		let single: Vec<_> = branches.map(|(proof, _)| proof).collect();
+		let single_len: usize = indices.iter().map(|i| single[*i].encode().len()).sum();
+		assert!(encode.len() < single_len);
+
+		// Merging the single proofs yields an equally usable proof.
+		let merged = MultiProof::from_proofs(indices.iter().map(|i| &single[*i])).unwrap();
+		assert_eq!(branch_hashes(&root, &merged, &indices).unwrap(), expected);
+	}
+
+	#[test]
+	fn multi_proof_does_not_cover_other_branches() {
+		let pov = PoV { block_data: BlockData((0..255).cycle().take(10_000).collect()) };
+		let available_data = AvailableData { pov: pov.into(), validation_data: Default::default() };
+		let chunks = obtain_chunks(100, &available_data).unwrap();
+
+		let branches = branches(chunks.as_ref());
+		let root = branches.root();
+
+		assert_eq!(branches.multi_proof(&[1, 100]), Err(Error::BranchOutOfBounds));
+
+		let proof = branches.multi_proof(&[1, 2]).unwrap();
+		assert!(branch_hashes(&root, &proof, &[1, 2, 50]).is_err());
+		assert_eq!(
+			branch_hashes(&H256::repeat_byte(1), &proof, &[1]),
+			Err(Error::InvalidBranchProof)
+		);
+	}
 }
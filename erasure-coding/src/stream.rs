@@ -0,0 +1,325 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Streaming erasure coding.
+//!
+//! The payload is encoded in runs of `2k` bytes, each of which becomes a single 16 bit symbol of
+//! every chunk. The runs do not depend on each other, so the payload can be cut into segments of
+//! whole runs which are encoded independently on the rayon thread pool, and the pieces of every
+//! chunk concatenated afterwards. The resulting chunks are identical to the ones returned by
+//! [`obtain_chunks`](crate::obtain_chunks). Likewise, the chunks can be cut into pieces which are
+//! reconstructed independently.
+
+use crate::{
+	code_params, reconstruction_error, recovery_threshold, systematic_recovery_threshold, Error,
+};
+use novelpoly::{CodeParams, WrappedShard};
+use parity_scale_codec::{Decode, Encode, Output};
+use rayon::prelude::*;
+
+/// The number of bytes of every chunk produced by encoding a single segment of the payload.
+const SEGMENT_CHUNK_LEN: usize = 2048;
+
+/// Erasure codes a payload which is passed in piece by piece, one chunk for each validator.
+///
+/// The payload is encoded in segments as soon as enough of it is present to keep all threads of
+/// the rayon thread pool busy, so it never has to be kept in memory as a whole. As this implements
+/// [`Output`], values can be encoded into it directly using [`Encode::encode_to`].
+pub struct StreamingEncoder {
+	params: CodeParams,
+	/// The number of payload bytes in a segment.
+	segment_len: usize,
+	/// The payload which has not been encoded yet.
+	pending: Vec<u8>,
+	payload_len: usize,
+	chunks: Vec<Vec<u8>>,
+}
+
+impl StreamingEncoder {
+	/// Create an encoder for the given number of validators.
+	///
+	/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+	pub fn new(n_validators: usize) -> Result<Self, Error> {
+		// Every symbol of a chunk encodes a run of `2k` bytes of the payload.
+		let segment_len = systematic_recovery_threshold(n_validators)? * SEGMENT_CHUNK_LEN;
+
+		Ok(StreamingEncoder {
+			params: code_params(n_validators)?,
+			segment_len,
+			pending: Vec::new(),
+			payload_len: 0,
+			chunks: vec![Vec::new(); n_validators],
+		})
+	}
+
+	/// Append bytes to the payload.
+	pub fn push(&mut self, bytes: &[u8]) {
+		self.pending.extend_from_slice(bytes);
+		self.payload_len += bytes.len();
+
+		if self.pending.len() >= self.segment_len * rayon::current_num_threads() {
+			let rest =
+				self.pending.split_off(self.pending.len() / self.segment_len * self.segment_len);
+			let segments = std::mem::replace(&mut self.pending, rest);
+			self.encode(segments);
+		}
+	}
+
+	/// Encode the rest of the payload and return the chunks.
+	pub fn finish(mut self) -> Result<Vec<Vec<u8>>, Error> {
+		if self.payload_len == 0 {
+			return Err(Error::BadPayload)
+		}
+
+		let rest = std::mem::take(&mut self.pending);
+		if !rest.is_empty() {
+			self.encode(rest);
+		}
+
+		Ok(self.chunks)
+	}
+
+	/// Encode the payload, which is made up of whole segments except possibly the last one.
+	fn encode(&mut self, payload: Vec<u8>) {
+		let params = self.params;
+		let encoded = payload
+			.par_chunks(self.segment_len)
+			.map(|segment| {
+				params.make_encoder().encode::<WrappedShard>(segment).expect(
+					"Segment non-empty, shard sizes are uniform, and validator numbers checked; qed",
+				)
+			})
+			.collect::<Vec<_>>();
+
+		for shards in encoded {
+			for (chunk, shard) in self.chunks.iter_mut().zip(shards) {
+				chunk.extend_from_slice(&shard.into_inner());
+			}
+		}
+	}
+}
+
+impl Output for StreamingEncoder {
+	fn write(&mut self, bytes: &[u8]) {
+		self.push(bytes)
+	}
+}
+
+/// Obtain erasure-coded chunks, one for each validator, encoding segments of the payload in
+/// parallel.
+///
+/// The chunks are the same as the ones returned by [`obtain_chunks`](crate::obtain_chunks).
+pub fn obtain_chunks_streaming<T: Encode>(
+	n_validators: usize,
+	data: &T,
+) -> Result<Vec<Vec<u8>>, Error> {
+	let mut encoder = StreamingEncoder::new(n_validators)?;
+	data.encode_to(&mut encoder);
+	encoder.finish()
+}
+
+/// Collects chunks as they arrive until there are enough of them to reconstruct the data.
+pub struct StreamingDecoder {
+	params: CodeParams,
+	threshold: usize,
+	chunks: Vec<Option<Vec<u8>>>,
+	received: usize,
+	chunk_len: Option<usize>,
+}
+
+impl StreamingDecoder {
+	/// Create a decoder for the given number of validators.
+	///
+	/// Works only up to 65536 validators, and `n_validators` must be non-zero.
+	pub fn new(n_validators: usize) -> Result<Self, Error> {
+		Ok(StreamingDecoder {
+			params: code_params(n_validators)?,
+			threshold: recovery_threshold(n_validators)?,
+			chunks: vec![None; n_validators],
+			received: 0,
+			chunk_len: None,
+		})
+	}
+
+	/// Add the chunk with the given index.
+	///
+	/// Returns whether there are enough chunks to reconstruct the data. Chunks which are already
+	/// present and chunks arriving after that point are ignored.
+	pub fn push(&mut self, chunk_index: usize, chunk: Vec<u8>) -> Result<bool, Error> {
+		let n_validators = self.chunks.len();
+		if chunk_index >= n_validators {
+			return Err(Error::ChunkIndexOutOfBounds { chunk_index, n_validators })
+		}
+
+		if self.has_enough() || self.chunks[chunk_index].is_some() {
+			return Ok(self.has_enough())
+		}
+
+		let chunk_len = *self.chunk_len.get_or_insert(chunk.len());
+		if chunk_len % 2 != 0 {
+			return Err(Error::UnevenLength)
+		}
+		if chunk_len != chunk.len() || chunk_len == 0 {
+			return Err(Error::NonUniformChunks)
+		}
+
+		self.chunks[chunk_index] = Some(chunk);
+		self.received += 1;
+
+		Ok(self.has_enough())
+	}
+
+	/// Whether there are enough chunks to reconstruct the data.
+	pub fn has_enough(&self) -> bool {
+		self.received >= self.threshold
+	}
+
+	/// Reconstruct the data from the chunks added so far.
+	pub fn reconstruct<T: Decode>(self) -> Result<T, Error> {
+		if !self.has_enough() {
+			return Err(Error::NotEnoughChunks)
+		}
+
+		let chunk_len = self.chunk_len.unwrap_or_default();
+		let params = self.params;
+		let chunks = &self.chunks;
+		let reconstructed = (0..(chunk_len + SEGMENT_CHUNK_LEN - 1) / SEGMENT_CHUNK_LEN)
+			.into_par_iter()
+			.map(|segment| {
+				let start = segment * SEGMENT_CHUNK_LEN;
+				let end = std::cmp::min(start + SEGMENT_CHUNK_LEN, chunk_len);
+				let shards = chunks
+					.iter()
+					.map(|chunk| chunk.as_ref().map(|c| WrappedShard::new(c[start..end].to_vec())))
+					.collect();
+				params.make_encoder().reconstruct(shards)
+			})
+			.collect::<Vec<_>>();
+
+		let mut payload_bytes = Vec::new();
+		for segment in reconstructed {
+			payload_bytes.extend(segment.map_err(reconstruction_error)?);
+		}
+
+		Decode::decode(&mut &payload_bytes[..]).or_else(|_e| Err(Error::BadPayload))
+	}
+}
+
+/// Reconstruct decodable data from chunks, reconstructing segments of the payload in parallel.
+///
+/// The chunks are taken from the iterator only until there are enough of them, so it may wait for
+/// chunks to arrive. The result is the same as the one of [`reconstruct`](crate::reconstruct).
+pub fn reconstruct_streaming<I, T: Decode>(n_validators: usize, chunks: I) -> Result<T, Error>
+where
+	I: IntoIterator<Item = (Vec<u8>, usize)>,
+{
+	let mut decoder = StreamingDecoder::new(n_validators)?;
+	for (chunk_data, chunk_idx) in chunks {
+		if decoder.push(chunk_idx, chunk_data)? {
+			break
+		}
+	}
+
+	decoder.reconstruct()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{branches, obtain_chunks};
+	use polkadot_node_primitives::{AvailableData, BlockData, PoV};
+
+	fn available_data(len: usize) -> AvailableData {
+		let pov = PoV { block_data: BlockData((0..len).map(|i| (i % 251) as u8).collect()) };
+		AvailableData { pov: pov.into(), validation_data: Default::default() }
+	}
+
+	#[test]
+	fn chunks_match_obtain_chunks() {
+		for n_validators in [2, 10, 100] {
+			let segment_len = StreamingEncoder::new(n_validators).unwrap().segment_len;
+			for len in [0, 1, 255, segment_len - 70, segment_len, 5 * segment_len + 3] {
+				let data = available_data(len);
+				let expected = obtain_chunks(n_validators, &data).unwrap();
+
+				let chunks = obtain_chunks_streaming(n_validators, &data).unwrap();
+				assert_eq!(chunks, expected, "n_validators: {}, len: {}", n_validators, len);
+				assert_eq!(branches(&chunks).root(), branches(&expected).root());
+			}
+		}
+	}
+
+	#[test]
+	fn payload_can_be_pushed_in_pieces() {
+		let data = available_data(100_000).encode();
+		let mut encoder = StreamingEncoder::new(10).unwrap();
+		for piece in data.chunks(777) {
+			encoder.push(piece);
+		}
+
+		assert_eq!(encoder.finish().unwrap(), obtain_chunks(10, &available_data(100_000)).unwrap());
+	}
+
+	#[test]
+	fn empty_payload_is_rejected() {
+		let encoder = StreamingEncoder::new(10).unwrap();
+		assert_eq!(encoder.finish(), Err(Error::BadPayload));
+	}
+
+	#[test]
+	fn reconstructs_once_enough_chunks_arrived() {
+		let data = available_data(100_000);
+		let chunks = obtain_chunks(10, &data).unwrap();
+
+		let mut decoder = StreamingDecoder::new(10).unwrap();
+		assert_eq!(decoder.push(9, chunks[9].clone()), Ok(false));
+		assert_eq!(decoder.push(9, chunks[9].clone()), Ok(false));
+		assert_eq!(decoder.push(2, chunks[2].clone()), Ok(false));
+		assert_eq!(decoder.push(5, chunks[5].clone()), Ok(false));
+		assert_eq!(decoder.push(7, chunks[7].clone()), Ok(true));
+
+		assert_eq!(decoder.reconstruct::<AvailableData>().unwrap(), data);
+	}
+
+	#[test]
+	fn stops_taking_chunks_once_enough_arrived() {
+		let data = available_data(3000);
+		let chunks = obtain_chunks(10, &data).unwrap();
+		let mut taken = 0;
+
+		let reconstructed: AvailableData =
+			reconstruct_streaming(10, chunks.iter().cloned().zip(0..).inspect(|_| taken += 1))
+				.unwrap();
+
+		assert_eq!(reconstructed, data);
+		assert_eq!(taken, recovery_threshold(10).unwrap());
+	}
+
+	#[test]
+	fn decoder_rejects_bad_chunks() {
+		let mut decoder = StreamingDecoder::new(10).unwrap();
+		assert_eq!(
+			decoder.push(10, vec![0; 2]),
+			Err(Error::ChunkIndexOutOfBounds { chunk_index: 10, n_validators: 10 }),
+		);
+		assert_eq!(decoder.push(0, vec![0; 2]), Ok(false));
+		assert_eq!(decoder.push(1, vec![0; 4]), Err(Error::NonUniformChunks));
+		assert_eq!(
+			StreamingDecoder::new(10).unwrap().reconstruct::<AvailableData>(),
+			Err(Error::NotEnoughChunks),
+		);
+	}
+}
@@ -19,7 +19,7 @@
 use parity_scale_codec::{Decode, Encode};
 use polkadot_node_primitives::approval::{AssignmentCert, DelayTranche};
 use polkadot_node_subsystem::{SubsystemError, SubsystemResult};
-use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_node_subsystem_util::database::{DBTransaction, Database, DecodedEntry};
 use polkadot_primitives::v2::{
 	BlockNumber, CandidateHash, CandidateReceipt, CoreIndex, GroupIndex, Hash, SessionIndex,
 	ValidatorIndex, ValidatorSignature,
@@ -27,7 +27,10 @@ use polkadot_primitives::v2::{
 use sp_consensus_slots::Slot;
 
 use bitvec::{order::Lsb0 as BitOrderLsb0, vec::BitVec};
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+	collections::{BTreeMap, HashSet},
+	sync::Arc,
+};
 
 use crate::{
 	backend::{Backend, BackendWriteOp},
@@ -339,3 +342,60 @@ pub fn load_candidate_entry(
 		.map(|u: Option<CandidateEntry>| u.map(|v| v.into()))
 		.map_err(|e| SubsystemError::with_origin("approval-voting", e))
 }
+
+/// Decode every entry reachable from the stored block range.
+///
+/// The entries are found by walking the block range rather than by iterating the column, as the
+/// column is not required to support ordered iteration.
+pub fn inspect_entries(
+	store: &dyn Database,
+	config: &Config,
+) -> SubsystemResult<Vec<DecodedEntry>> {
+	let mut entries = Vec::new();
+	let stored_blocks = match load_stored_blocks(store, config)? {
+		None => return Ok(entries),
+		Some(stored_blocks) => stored_blocks,
+	};
+
+	entries.push(DecodedEntry::new(STORED_BLOCKS_KEY, "stored_blocks", stored_blocks.clone()));
+
+	let mut seen_candidates = HashSet::new();
+	for height in stored_blocks.0..stored_blocks.1 {
+		let blocks = load_blocks_at_height(store, config, &height)?;
+		if blocks.is_empty() {
+			continue
+		}
+
+		entries.push(DecodedEntry::new(blocks_at_height_key(height), "blocks_at_height", &blocks));
+
+		for block_hash in blocks {
+			let block_entry = match load_block_entry(store, config, &block_hash)? {
+				None => continue,
+				Some(block_entry) => block_entry,
+			};
+
+			for (_, candidate_hash) in &block_entry.candidates {
+				if !seen_candidates.insert(*candidate_hash) {
+					continue
+				}
+
+				if let Some(candidate_entry) = load_candidate_entry(store, config, candidate_hash)?
+				{
+					entries.push(DecodedEntry::new(
+						candidate_entry_key(candidate_hash),
+						"candidate_entry",
+						candidate_entry,
+					));
+				}
+			}
+
+			entries.push(DecodedEntry::new(
+				block_entry_key(&block_hash),
+				"block_entry",
+				block_entry,
+			));
+		}
+	}
+
+	Ok(entries)
+}
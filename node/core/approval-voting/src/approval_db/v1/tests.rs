@@ -566,3 +566,37 @@ fn load_all_blocks_works() {
 		vec![block_hash_a, block_hash_b, block_hash_c],
 	)
 }
+
+#[test]
+fn inspect_entries_walks_stored_blocks() {
+	let (mut db, store) = make_db();
+
+	let block_hash = Hash::repeat_byte(1);
+	let candidate = make_candidate(ParaId::from(1_u32), block_hash);
+	let candidate_hash = candidate.hash();
+
+	let candidate_entry = CandidateEntry {
+		candidate,
+		session: 1,
+		block_assignments: Default::default(),
+		approvals: Default::default(),
+	};
+
+	let mut overlay_db = OverlayedBackend::new(&db);
+	overlay_db.write_stored_block_range(StoredBlockRange(1, 2));
+	overlay_db.write_blocks_at_height(1, vec![block_hash]);
+	overlay_db.write_block_entry(
+		make_block_entry(block_hash, Hash::repeat_byte(0), 1, vec![(CoreIndex(0), candidate_hash)])
+			.into(),
+	);
+	overlay_db.write_candidate_entry(candidate_entry.into());
+	db.write(overlay_db.into_write_ops()).unwrap();
+
+	let kinds = inspect_entries(store.as_ref(), &TEST_CONFIG)
+		.unwrap()
+		.into_iter()
+		.map(|e| e.kind)
+		.collect::<Vec<_>>();
+
+	assert_eq!(kinds, vec!["stored_blocks", "blocks_at_height", "candidate_entry", "block_entry"]);
+}
@@ -0,0 +1,193 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Batch verification of assignment certs and approval votes.
+//!
+//! Verifying many VRF proofs or signatures at once is considerably cheaper than verifying them
+//! one by one. A batch only tells whether all of its items are valid though, so a failed batch
+//! is split in halves until the invalid items are found.
+
+use polkadot_primitives::v2::{
+	ApprovalVote, CandidateHash, SessionIndex, ValidatorId, ValidatorSignature,
+};
+use sp_application_crypto::ByteArray;
+
+/// The signing context of sr25519 signatures, see `sp_core::sr25519`.
+const SIGNING_CONTEXT: &[u8] = b"substrate";
+
+/// Verify the items in batches with `verify`, which has to fail unless all items of the batch
+/// are valid and otherwise returns an output per item.
+///
+/// Returns the output of every valid item and `None` for the invalid ones, in order.
+pub(crate) fn verify_bisecting<T, R>(
+	items: &[T],
+	verify: &impl Fn(&[T]) -> Option<Vec<R>>,
+) -> Vec<Option<R>> {
+	if items.is_empty() {
+		return Vec::new()
+	}
+
+	if let Some(outputs) = verify(items) {
+		return outputs.into_iter().map(Some).collect()
+	}
+
+	if items.len() == 1 {
+		return vec![None]
+	}
+
+	let (left, right) = items.split_at(items.len() / 2);
+	let mut outputs = verify_bisecting(left, verify);
+	outputs.extend(verify_bisecting(right, verify));
+	outputs
+}
+
+/// The signature of an approval vote to check.
+pub(crate) struct ApprovalSignature {
+	public: ValidatorId,
+	payload: Vec<u8>,
+	signature: ValidatorSignature,
+}
+
+impl ApprovalSignature {
+	pub(crate) fn new(
+		public: ValidatorId,
+		candidate_hash: CandidateHash,
+		session: SessionIndex,
+		signature: ValidatorSignature,
+	) -> Self {
+		ApprovalSignature {
+			public,
+			payload: ApprovalVote(candidate_hash).signing_payload(session),
+			signature,
+		}
+	}
+}
+
+/// Check the signatures of approval votes, returning whether each of them is valid.
+pub(crate) fn check_approval_signatures(signatures: &[ApprovalSignature]) -> Vec<bool> {
+	// Keys and signatures which can't even be decoded are invalid without any further checks.
+	let decoded = signatures
+		.iter()
+		.enumerate()
+		.filter_map(|(i, s)| {
+			let public = schnorrkel::PublicKey::from_bytes(s.public.as_slice()).ok()?;
+			let signature = schnorrkel::Signature::from_bytes(s.signature.as_ref()).ok()?;
+			Some((i, public, signature))
+		})
+		.collect::<Vec<_>>();
+
+	let verified = verify_bisecting(&decoded, &|batch| {
+		let transcripts = batch.iter().map(|(i, _, _)| {
+			schnorrkel::signing_context(SIGNING_CONTEXT).bytes(&signatures[*i].payload)
+		});
+		let publics = batch.iter().map(|(_, public, _)| *public).collect::<Vec<_>>();
+		let signatures = batch.iter().map(|(_, _, signature)| *signature).collect::<Vec<_>>();
+
+		schnorrkel::verify_batch(transcripts, &signatures, &publics, false)
+			.ok()
+			.map(|()| vec![(); batch.len()])
+	});
+
+	let mut valid = vec![false; signatures.len()];
+	for ((i, _, _), verified) in decoded.iter().zip(verified) {
+		valid[*i] = verified.is_some();
+	}
+	valid
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use polkadot_primitives::v2::Hash;
+	use sp_keyring::sr25519::Keyring as Sr25519Keyring;
+	use std::cell::Cell;
+
+	fn approval_signature(
+		key: Sr25519Keyring,
+		candidate_hash: CandidateHash,
+		signed_session: SessionIndex,
+	) -> ApprovalSignature {
+		let signature = key.sign(&ApprovalVote(candidate_hash).signing_payload(signed_session));
+		ApprovalSignature::new(key.public().into(), candidate_hash, 1, signature.into())
+	}
+
+	#[test]
+	fn bisecting_finds_all_invalid_items() {
+		let items = (0..13u32).collect::<Vec<_>>();
+		let calls = Cell::new(0);
+		let outputs = verify_bisecting(&items, &|batch| {
+			calls.set(calls.get() + 1);
+			if batch.iter().any(|i| i % 5 == 3) {
+				None
+			} else {
+				Some(batch.iter().map(|i| i * 2).collect())
+			}
+		});
+
+		let expected = items
+			.iter()
+			.map(|i| if i % 5 == 3 { None } else { Some(i * 2) })
+			.collect::<Vec<_>>();
+		assert_eq!(outputs, expected);
+		assert!(calls.get() < items.len() * 2);
+	}
+
+	#[test]
+	fn bisecting_verifies_a_valid_batch_once() {
+		let calls = Cell::new(0);
+		let outputs = verify_bisecting(&[1, 2, 3], &|batch| {
+			calls.set(calls.get() + 1);
+			Some(batch.to_vec())
+		});
+
+		assert_eq!(outputs, vec![Some(1), Some(2), Some(3)]);
+		assert_eq!(calls.get(), 1);
+	}
+
+	#[test]
+	fn invalid_approval_signatures_are_found() {
+		let keys = [
+			Sr25519Keyring::Alice,
+			Sr25519Keyring::Bob,
+			Sr25519Keyring::Charlie,
+			Sr25519Keyring::Dave,
+			Sr25519Keyring::Eve,
+		];
+		let signatures = keys
+			.iter()
+			.enumerate()
+			.map(|(i, key)| {
+				let candidate_hash = CandidateHash(Hash::repeat_byte(i as u8));
+				// The votes of Bob and Eve are signed for the wrong session.
+				let session = if i == 1 || i == 4 { 2 } else { 1 };
+				approval_signature(*key, candidate_hash, session)
+			})
+			.collect::<Vec<_>>();
+
+		assert_eq!(check_approval_signatures(&signatures), vec![true, false, true, true, false],);
+	}
+
+	#[test]
+	fn valid_approval_signatures_pass() {
+		let signatures = [Sr25519Keyring::Alice, Sr25519Keyring::Bob]
+			.iter()
+			.map(|key| approval_signature(*key, CandidateHash(Hash::repeat_byte(1)), 1))
+			.collect::<Vec<_>>();
+
+		assert_eq!(check_approval_signatures(&signatures), vec![true, true]);
+		assert!(check_approval_signatures(&[]).is_empty());
+	}
+}
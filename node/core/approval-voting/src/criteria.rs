@@ -26,12 +26,13 @@ use polkadot_primitives::v2::{
 use sc_keystore::LocalKeystore;
 use sp_application_crypto::ByteArray;
 
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::VartimeMultiscalarMul};
 use merlin::Transcript;
-use schnorrkel::vrf::VRFInOut;
+use schnorrkel::vrf::{VRFInOut, VRFProof, VRFProofBatchable};
 
 use std::collections::{hash_map::Entry, HashMap};
 
-use super::LOG_TARGET;
+use super::{batch, LOG_TARGET};
 
 /// Details pertaining to our assignment on a block.
 #[derive(Debug, Clone, Encode, Decode, PartialEq)]
@@ -181,6 +182,36 @@ pub(crate) trait AssignmentCriteria {
 		assignment: &AssignmentCert,
 		backing_group: GroupIndex,
 	) -> Result<DelayTranche, InvalidAssignment>;
+
+	/// Check a batch of assignment certs, returning the results in order.
+	fn check_assignment_certs(
+		&self,
+		checks: &[AssignmentCertCheck],
+	) -> Vec<Result<DelayTranche, InvalidAssignment>> {
+		checks
+			.iter()
+			.map(|check| {
+				self.check_assignment_cert(
+					check.claimed_core_index,
+					check.validator_index,
+					&check.config,
+					check.relay_vrf_story.clone(),
+					&check.cert,
+					check.backing_group,
+				)
+			})
+			.collect()
+	}
+}
+
+/// The arguments of `check_assignment_cert` for checking a batch of certs.
+pub(crate) struct AssignmentCertCheck {
+	pub(crate) claimed_core_index: CoreIndex,
+	pub(crate) validator_index: ValidatorIndex,
+	pub(crate) config: Config,
+	pub(crate) relay_vrf_story: RelayVRFStory,
+	pub(crate) cert: AssignmentCert,
+	pub(crate) backing_group: GroupIndex,
 }
 
 pub(crate) struct RealAssignmentCriteria;
@@ -214,6 +245,13 @@ impl AssignmentCriteria for RealAssignmentCriteria {
 			backing_group,
 		)
 	}
+
+	fn check_assignment_certs(
+		&self,
+		checks: &[AssignmentCertCheck],
+	) -> Vec<Result<DelayTranche, InvalidAssignment>> {
+		check_assignment_certs(checks)
+	}
 }
 
 /// Compute the assignments for a given block. Returns a map containing all assignments to cores in
@@ -472,6 +510,139 @@ pub(crate) fn check_assignment_cert(
 	assignment: &AssignmentCert,
 	backing_group: GroupIndex,
 ) -> Result<DelayTranche, InvalidAssignment> {
+	let public = precheck_assignment_cert(
+		claimed_core_index,
+		validator_index,
+		config,
+		assignment,
+		backing_group,
+	)?;
+
+	check_assignment_vrf(&public, claimed_core_index, config, relay_vrf_story, assignment)
+}
+
+/// Check a batch of assignment certs, with the same failure conditions as
+/// `check_assignment_cert`.
+///
+/// The VRFs of `RelayVRFDelay` certs are verified together, after expanding their proofs
+/// into batchable ones. The proofs of `RelayVRFModulo` certs also cover the claimed core,
+/// which batch verification doesn't support, so they are verified one by one.
+pub(crate) fn check_assignment_certs(
+	checks: &[AssignmentCertCheck],
+) -> Vec<Result<DelayTranche, InvalidAssignment>> {
+	let mut results = Vec::with_capacity(checks.len());
+	let mut delay_certs = Vec::new();
+	for (i, check) in checks.iter().enumerate() {
+		let public = match precheck_assignment_cert(
+			check.claimed_core_index,
+			check.validator_index,
+			&check.config,
+			&check.cert,
+			check.backing_group,
+		) {
+			Ok(public) => public,
+			Err(e) => {
+				results.push(Err(e));
+				continue
+			},
+		};
+
+		match check.cert.kind {
+			AssignmentCertKind::RelayVRFModulo { .. } => results.push(check_assignment_vrf(
+				&public,
+				check.claimed_core_index,
+				&check.config,
+				check.relay_vrf_story.clone(),
+				&check.cert,
+			)),
+			AssignmentCertKind::RelayVRFDelay { core_index } => {
+				let proof = batchable_vrf_proof(
+					&public,
+					relay_vrf_delay_transcript(check.relay_vrf_story.clone(), core_index),
+					&check.cert.vrf.0 .0,
+					&check.cert.vrf.1 .0,
+				);
+				// Overwritten below if the VRF is valid.
+				results
+					.push(Err(InvalidAssignment(InvalidAssignmentReason::VRFDelayOutputMismatch)));
+				if let Some(proof) = proof {
+					delay_certs.push((i, public, core_index, proof));
+				}
+			},
+		}
+	}
+
+	let verified = batch::verify_bisecting(&delay_certs, &|batch| {
+		let transcripts = batch.iter().map(|(i, _, core_index, _)| {
+			relay_vrf_delay_transcript(checks[*i].relay_vrf_story.clone(), *core_index)
+		});
+		let outputs = batch.iter().map(|(i, _, _, _)| checks[*i].cert.vrf.0 .0).collect::<Vec<_>>();
+		let proofs = batch.iter().map(|(_, _, _, proof)| proof.clone()).collect::<Vec<_>>();
+		let publics = batch.iter().map(|(_, public, _, _)| *public).collect::<Vec<_>>();
+
+		schnorrkel::vrf::vrf_verify_batch(transcripts, &outputs, &proofs, &publics)
+			.ok()
+			.map(|vrf_in_outs| vrf_in_outs.into_vec())
+	});
+
+	for ((i, _, _, _), vrf_in_out) in delay_certs.iter().zip(verified) {
+		if let Some(vrf_in_out) = vrf_in_out {
+			let config = &checks[*i].config;
+			results[*i] = Ok(relay_vrf_delay_tranche(
+				&vrf_in_out,
+				config.n_delay_tranches,
+				config.zeroth_delay_tranche_width,
+			));
+		}
+	}
+
+	results
+}
+
+/// Expand a VRF proof as sent over the wire into the batchable form, which carries the
+/// commitments `R = g^r` and `Hr = h^r` instead of the challenge.
+///
+/// Both are recovered from the challenge `c` and the response `s` as `g^s * pk^c` and
+/// `h^s * out^c`. A proof which doesn't verify yields commitments which don't match the
+/// challenge and thus fails batch verification. Returns `None` if the proof or the output
+/// can't be decoded.
+fn batchable_vrf_proof(
+	public: &schnorrkel::PublicKey,
+	transcript: Transcript,
+	output: &schnorrkel::vrf::VRFPreOut,
+	proof: &VRFProof,
+) -> Option<VRFProofBatchable> {
+	let vrf_in_out = output.attach_input_hash(public, transcript).ok()?;
+
+	let proof = proof.to_bytes();
+	let mut c = [0u8; 32];
+	let mut s = [0u8; 32];
+	c.copy_from_slice(&proof[..32]);
+	s.copy_from_slice(&proof[32..]);
+	let c = Scalar::from_canonical_bytes(c)?;
+	let s = Scalar::from_canonical_bytes(s)?;
+
+	let r = RistrettoPoint::vartime_double_scalar_mul_basepoint(&c, public.as_point(), &s);
+	let hr = RistrettoPoint::vartime_multiscalar_mul(
+		&[s, c],
+		&[*vrf_in_out.input.as_point(), *vrf_in_out.output.as_point()],
+	);
+
+	let mut batchable = [0u8; schnorrkel::vrf::VRF_PROOF_BATCHABLE_LENGTH];
+	batchable[..32].copy_from_slice(r.compress().as_bytes());
+	batchable[32..64].copy_from_slice(hr.compress().as_bytes());
+	batchable[64..].copy_from_slice(&proof[32..]);
+	VRFProofBatchable::from_bytes(&batchable).ok()
+}
+
+/// All checks of an assignment cert but the VRF ones, returning the key of the validator.
+fn precheck_assignment_cert(
+	claimed_core_index: CoreIndex,
+	validator_index: ValidatorIndex,
+	config: &Config,
+	assignment: &AssignmentCert,
+	backing_group: GroupIndex,
+) -> Result<schnorrkel::PublicKey, InvalidAssignment> {
 	use InvalidAssignmentReason as Reason;
 
 	let validator_public = config
@@ -495,13 +666,33 @@ pub(crate) fn check_assignment_cert(
 		return Err(InvalidAssignment(Reason::IsInBackingGroup))
 	}
 
-	let &(ref vrf_output, ref vrf_proof) = &assignment.vrf;
 	match assignment.kind {
-		AssignmentCertKind::RelayVRFModulo { sample } => {
+		AssignmentCertKind::RelayVRFModulo { sample } =>
 			if sample >= config.relay_vrf_modulo_samples {
 				return Err(InvalidAssignment(Reason::SampleOutOfBounds))
-			}
+			},
+		AssignmentCertKind::RelayVRFDelay { core_index } =>
+			if core_index != claimed_core_index {
+				return Err(InvalidAssignment(Reason::VRFDelayCoreIndexMismatch))
+			},
+	}
+
+	Ok(public)
+}
+
+/// The VRF checks of an assignment cert which passed `precheck_assignment_cert`.
+fn check_assignment_vrf(
+	public: &schnorrkel::PublicKey,
+	claimed_core_index: CoreIndex,
+	config: &Config,
+	relay_vrf_story: RelayVRFStory,
+	assignment: &AssignmentCert,
+) -> Result<DelayTranche, InvalidAssignment> {
+	use InvalidAssignmentReason as Reason;
 
+	let &(ref vrf_output, ref vrf_proof) = &assignment.vrf;
+	match assignment.kind {
+		AssignmentCertKind::RelayVRFModulo { sample } => {
 			let (vrf_in_out, _) = public
 				.vrf_verify_extra(
 					relay_vrf_modulo_transcript(relay_vrf_story, sample),
@@ -519,10 +710,6 @@ pub(crate) fn check_assignment_cert(
 			}
 		},
 		AssignmentCertKind::RelayVRFDelay { core_index } => {
-			if core_index != claimed_core_index {
-				return Err(InvalidAssignment(Reason::VRFDelayCoreIndexMismatch))
-			}
-
 			let (vrf_in_out, _) = public
 				.vrf_verify(
 					relay_vrf_delay_transcript(relay_vrf_story, core_index),
@@ -757,6 +944,8 @@ mod tests {
 		);
 
 		let mut counted = 0;
+		let mut checks = Vec::new();
+		let mut single_results = Vec::new();
 		for (core, assignment) in assignments {
 			let mut mutated = MutatedAssignment {
 				core,
@@ -774,20 +963,32 @@ mod tests {
 
 			counted += 1;
 
-			let is_good = check_assignment_cert(
+			let result = check_assignment_cert(
 				mutated.core,
 				mutated.val_index,
 				&mutated.config,
 				relay_vrf_story.clone(),
 				&mutated.cert,
 				mutated.group,
-			)
-			.is_ok();
+			);
+
+			assert_eq!(expected, result.is_ok());
 
-			assert_eq!(expected, is_good)
+			single_results.push(result);
+			checks.push(AssignmentCertCheck {
+				claimed_core_index: mutated.core,
+				validator_index: mutated.val_index,
+				config: mutated.config,
+				relay_vrf_story: relay_vrf_story.clone(),
+				cert: mutated.cert,
+				backing_group: mutated.group,
+			});
 		}
 
 		assert!(counted > 0);
+
+		// Checking all of them as a batch gives the same results.
+		assert_eq!(check_assignment_certs(&checks), single_results);
 	}
 
 	#[test]
@@ -819,6 +1020,20 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn check_rejects_only_bad_vrfs_of_batch() {
+		let counter = std::cell::Cell::new(0);
+		check_mutated_assignments(40, 10, 8, |m| {
+			counter.set(counter.get() + 1);
+			if counter.get() % 3 == 0 {
+				m.cert.vrf = garbage_vrf();
+				Some(false)
+			} else {
+				Some(true)
+			}
+		});
+	}
+
 	#[test]
 	fn check_rejects_delay_bad_vrf() {
 		check_mutated_assignments(40, 10, 8, |m| {
@@ -633,9 +633,11 @@ pub(crate) mod tests {
 	use crate::{
 		approval_db::v1::Config as DatabaseConfig, criteria, BlockEntry, APPROVAL_SESSIONS,
 	};
+	use polkadot_node_subsystem_util::rolling_session_window::DatabaseParams;
 
 	const DATA_COL: u32 = 0;
-	const NUM_COLUMNS: u32 = 1;
+	const SESSION_DATA_COL: u32 = 1;
+	const NUM_COLUMNS: u32 = 2;
 
 	const TEST_CONFIG: DatabaseConfig = DatabaseConfig { col_data: DATA_COL };
 	#[derive(Default)]
@@ -652,8 +654,11 @@ pub(crate) mod tests {
 	}
 
 	fn blank_state() -> State {
+		let db = kvdb_memorydb::create(NUM_COLUMNS);
+		let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(db, &[]);
 		State {
 			session_window: None,
+			session_window_db: DatabaseParams { db: Arc::new(db), db_column: SESSION_DATA_COL },
 			keystore: Arc::new(LocalKeystore::in_memory()),
 			slot_duration_millis: 6_000,
 			clock: Box::new(MockClock::default()),
@@ -41,18 +41,17 @@ use polkadot_node_subsystem::{
 	SubsystemSender,
 };
 use polkadot_node_subsystem_util::{
-	database::Database,
+	database::{Database, DecodedEntry},
 	metrics::{self, prometheus},
 	rolling_session_window::{
-		new_session_window_size, RollingSessionWindow, SessionWindowSize, SessionWindowUpdate,
-		SessionsUnavailable,
+		new_session_window_size, DatabaseParams, RollingSessionWindow, SessionWindowSize,
+		SessionWindowUpdate, SessionsUnavailable,
 	},
 	TimeoutExt,
 };
 use polkadot_primitives::v2::{
-	ApprovalVote, BlockNumber, CandidateHash, CandidateIndex, CandidateReceipt, DisputeStatement,
-	GroupIndex, Hash, SessionIndex, SessionInfo, ValidDisputeStatementKind, ValidatorId,
-	ValidatorIndex, ValidatorPair, ValidatorSignature,
+	ApprovalVote, BlockNumber, CandidateHash, CandidateIndex, CandidateReceipt, GroupIndex, Hash,
+	SessionIndex, SessionInfo, ValidatorId, ValidatorIndex, ValidatorPair, ValidatorSignature,
 };
 use sc_keystore::LocalKeystore;
 use sp_application_crypto::Pair;
@@ -82,10 +81,13 @@ use time::{slot_number_to_tick, Clock, ClockExt, SystemClock, Tick};
 mod approval_checking;
 mod approval_db;
 mod backend;
+mod batch;
 mod criteria;
 mod import;
 mod ops;
 mod persisted_entries;
+#[cfg(feature = "simulation")]
+pub mod simulation;
 mod time;
 
 use crate::{
@@ -114,6 +116,8 @@ const LOG_TARGET: &str = "parachain::approval-voting";
 pub struct Config {
 	/// The column family in the DB where approval-voting data is stored.
 	pub col_data: u32,
+	/// The column family in the DB where the session window is stored.
+	pub col_session_data: u32,
 	/// The slot duration of the consensus algorithm, in milliseconds. Should be evenly
 	/// divisible by 500.
 	pub slot_duration_millis: u64,
@@ -140,6 +144,7 @@ pub struct ApprovalVotingSubsystem {
 	/// We do a lot of VRF signing and need the keys to have low latency.
 	keystore: Arc<LocalKeystore>,
 	db_config: DatabaseConfig,
+	col_session_data: u32,
 	slot_duration_millis: u64,
 	db: Arc<dyn Database>,
 	mode: Mode,
@@ -354,6 +359,7 @@ impl ApprovalVotingSubsystem {
 			slot_duration_millis: config.slot_duration_millis,
 			db,
 			db_config: DatabaseConfig { col_data: config.col_data },
+			col_session_data: config.col_session_data,
 			mode: Mode::Syncing(sync_oracle),
 			metrics,
 		}
@@ -371,6 +377,12 @@ impl ApprovalVotingSubsystem {
 		let ops = overlay.into_write_ops();
 		backend.write(ops)
 	}
+
+	/// Decode all block and candidate entries stored in the database.
+	pub fn inspect_db(&self) -> Result<Vec<DecodedEntry>, SubsystemError> {
+		let config = approval_db::v1::Config { col_data: self.db_config.col_data };
+		approval_db::v1::inspect_entries(&*self.db, &config)
+	}
 }
 
 #[overseer::subsystem(ApprovalVoting, error = SubsystemError, prefix = self::overseer)]
@@ -606,6 +618,7 @@ impl CurrentlyCheckingSet {
 
 struct State {
 	session_window: Option<RollingSessionWindow>,
+	session_window_db: DatabaseParams,
 	keystore: Arc<LocalKeystore>,
 	slot_duration_millis: u64,
 	clock: Box<dyn Clock + Send + Sync>,
@@ -631,8 +644,15 @@ impl State {
 		match session_window {
 			None => {
 				let sender = ctx.sender().clone();
-				self.session_window =
-					Some(RollingSessionWindow::new(sender, APPROVAL_SESSIONS, head).await?);
+				self.session_window = Some(
+					RollingSessionWindow::new(
+						sender,
+						APPROVAL_SESSIONS,
+						head,
+						self.session_window_db.clone(),
+					)
+					.await?,
+				);
 				Ok(None)
 			},
 			Some(mut session_window) => {
@@ -729,6 +749,10 @@ where
 {
 	let mut state = State {
 		session_window: None,
+		session_window_db: DatabaseParams {
+			db: subsystem.db.clone(),
+			db_column: subsystem.col_session_data,
+		},
 		keystore: subsystem.keystore,
 		slot_duration_millis: subsystem.slot_duration_millis,
 		clock,
@@ -1175,6 +1199,18 @@ async fn handle_from_overseer<Context>(
 					let _ = res.send(r);
 				})?
 				.0,
+			ApprovalVotingMessage::CheckAndImportAssignments(assignments, res) => {
+				let (check_outcomes, actions) =
+					check_and_import_assignments(state, db, assignments)?;
+				let _ = res.send(check_outcomes);
+
+				actions
+			},
+			ApprovalVotingMessage::CheckAndImportApprovals(approvals, res) =>
+				check_and_import_approvals(state, db, metrics, approvals, |r| {
+					let _ = res.send(r);
+				})?
+				.0,
 			ApprovalVotingMessage::ApprovedAncestor(target, lower_bound, res) => {
 				match handle_approved_ancestor(ctx, db, target, lower_bound, wakeups).await {
 					Ok(v) => {
@@ -1616,86 +1652,205 @@ fn check_and_import_assignment(
 	assignment: IndirectAssignmentCert,
 	candidate_index: CandidateIndex,
 ) -> SubsystemResult<(AssignmentCheckResult, Vec<Action>)> {
+	let (mut check_outcomes, actions) =
+		check_and_import_assignments(state, db, vec![(assignment, candidate_index)])?;
+	let check_outcome = check_outcomes.pop().expect("one outcome per assignment; qed");
+
+	Ok((check_outcome, actions))
+}
+
+/// Check and import assignments in order, verifying the certs of all of them together.
+fn check_and_import_assignments(
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	assignments: Vec<(IndirectAssignmentCert, CandidateIndex)>,
+) -> SubsystemResult<(Vec<AssignmentCheckResult>, Vec<Action>)> {
 	let tick_now = state.clock.tick_now();
 
-	let block_entry = match db.load_block_entry(&assignment.block_hash)? {
-		Some(b) => b,
-		None =>
-			return Ok((
-				AssignmentCheckResult::Bad(AssignmentCheckError::UnknownBlock(
+	// Assignments of a batch may target the same candidate, so the candidate entries are shared
+	// by all of them and only written once all are imported.
+	let mut block_entries = HashMap::new();
+	let mut candidate_entries = HashMap::new();
+
+	let mut prepared = Vec::with_capacity(assignments.len());
+	let mut cert_checks = Vec::new();
+	for (assignment, candidate_index) in &assignments {
+		match prepare_assignment_check(
+			state,
+			db,
+			&mut block_entries,
+			&mut candidate_entries,
+			assignment,
+			*candidate_index,
+		)? {
+			Ok((cert_check, assigned_candidate_hash)) => {
+				cert_checks.push(cert_check);
+				prepared.push(Ok(assigned_candidate_hash));
+			},
+			Err(check_outcome) => prepared.push(Err(check_outcome)),
+		}
+	}
+
+	let mut cert_results =
+		state.assignment_criteria.check_assignment_certs(&cert_checks).into_iter();
+
+	let mut check_outcomes = Vec::with_capacity(assignments.len());
+	let mut actions = Vec::new();
+	let mut imported = HashSet::new();
+	for ((assignment, _), prepared) in assignments.into_iter().zip(prepared) {
+		let assigned_candidate_hash = match prepared {
+			Ok(assigned_candidate_hash) => assigned_candidate_hash,
+			Err(check_outcome) => {
+				check_outcomes.push(check_outcome);
+				continue
+			},
+		};
+
+		let cert_result = cert_results.next().expect("one cert check per prepared assignment; qed");
+		let block_entry = block_entries
+			.get(&assignment.block_hash)
+			.expect("block entries of prepared assignments are loaded; qed");
+		let candidate_entry = candidate_entries
+			.get_mut(&assigned_candidate_hash)
+			.expect("candidate entries of prepared assignments are loaded; qed");
+
+		let (check_outcome, import_actions) = import_checked_assignment(
+			state,
+			block_entry,
+			candidate_entry,
+			assigned_candidate_hash,
+			assignment,
+			cert_result,
+			tick_now,
+		);
+		if import_actions.is_some() {
+			imported.insert(assigned_candidate_hash);
+		}
+		check_outcomes.push(check_outcome);
+		actions.extend(import_actions.into_iter().flatten());
+	}
+
+	// We also write the candidate entries as they now contain the new assignments.
+	for (candidate_hash, candidate_entry) in candidate_entries {
+		if imported.contains(&candidate_hash) {
+			db.write_candidate_entry(candidate_entry.into());
+		}
+	}
+
+	Ok((check_outcomes, actions))
+}
+
+/// Load everything needed to check the cert of an assignment.
+///
+/// Returns the cert check and the hash of the assigned candidate or the outcome if the
+/// assignment is bad regardless of its cert.
+fn prepare_assignment_check(
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	block_entries: &mut HashMap<Hash, BlockEntry>,
+	candidate_entries: &mut HashMap<CandidateHash, CandidateEntry>,
+	assignment: &IndirectAssignmentCert,
+	candidate_index: CandidateIndex,
+) -> SubsystemResult<Result<(criteria::AssignmentCertCheck, CandidateHash), AssignmentCheckResult>>
+{
+	let block_entry = match block_entries.entry(assignment.block_hash) {
+		HMEntry::Occupied(e) => e.into_mut(),
+		HMEntry::Vacant(e) => match db.load_block_entry(&assignment.block_hash)? {
+			Some(b) => e.insert(b),
+			None =>
+				return Ok(Err(AssignmentCheckResult::Bad(AssignmentCheckError::UnknownBlock(
 					assignment.block_hash,
-				)),
-				Vec::new(),
-			)),
+				)))),
+		},
 	};
 
 	let session_info = match state.session_info(block_entry.session()) {
 		Some(s) => s,
 		None =>
-			return Ok((
-				AssignmentCheckResult::Bad(AssignmentCheckError::UnknownSessionIndex(
-					block_entry.session(),
-				)),
-				Vec::new(),
-			)),
+			return Ok(Err(AssignmentCheckResult::Bad(AssignmentCheckError::UnknownSessionIndex(
+				block_entry.session(),
+			)))),
 	};
 
 	let (claimed_core_index, assigned_candidate_hash) =
 		match block_entry.candidate(candidate_index as usize) {
 			Some((c, h)) => (*c, *h),
 			None =>
-				return Ok((
-					AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCandidateIndex(
-						candidate_index,
-					)),
-					Vec::new(),
-				)), // no candidate at core.
+				return Ok(Err(AssignmentCheckResult::Bad(
+					AssignmentCheckError::InvalidCandidateIndex(candidate_index),
+				))), // no candidate at core.
 		};
 
-	let mut candidate_entry = match db.load_candidate_entry(&assigned_candidate_hash)? {
-		Some(c) => c,
-		None =>
-			return Ok((
-				AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCandidate(
+	let candidate_entry = match candidate_entries.entry(assigned_candidate_hash) {
+		HMEntry::Occupied(e) => e.into_mut(),
+		HMEntry::Vacant(e) => match db.load_candidate_entry(&assigned_candidate_hash)? {
+			Some(c) => e.insert(c),
+			None =>
+				return Ok(Err(AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCandidate(
 					candidate_index,
 					assigned_candidate_hash,
-				)),
-				Vec::new(),
-			)),
+				)))),
+		},
 	};
 
+	let backing_group = match candidate_entry.approval_entry(&assignment.block_hash) {
+		Some(a) => a.backing_group(),
+		None =>
+			return Ok(Err(AssignmentCheckResult::Bad(AssignmentCheckError::Internal(
+				assignment.block_hash,
+				assigned_candidate_hash,
+			)))),
+	};
+
+	let cert_check = criteria::AssignmentCertCheck {
+		claimed_core_index,
+		validator_index: assignment.validator,
+		config: criteria::Config::from(session_info),
+		relay_vrf_story: block_entry.relay_vrf_story(),
+		cert: assignment.cert.clone(),
+		backing_group,
+	};
+
+	Ok(Ok((cert_check, assigned_candidate_hash)))
+}
+
+/// Import an assignment whose cert has been checked into the candidate entry.
+///
+/// The actions are `None` if the assignment wasn't imported.
+fn import_checked_assignment(
+	state: &State,
+	block_entry: &BlockEntry,
+	candidate_entry: &mut CandidateEntry,
+	assigned_candidate_hash: CandidateHash,
+	assignment: IndirectAssignmentCert,
+	cert_result: Result<DelayTranche, criteria::InvalidAssignment>,
+	tick_now: Tick,
+) -> (AssignmentCheckResult, Option<Vec<Action>>) {
+	let para_id = candidate_entry.candidate_receipt().descriptor.para_id;
+
 	let res = {
 		// import the assignment.
 		let approval_entry = match candidate_entry.approval_entry_mut(&assignment.block_hash) {
 			Some(a) => a,
 			None =>
-				return Ok((
+				return (
 					AssignmentCheckResult::Bad(AssignmentCheckError::Internal(
 						assignment.block_hash,
 						assigned_candidate_hash,
 					)),
-					Vec::new(),
-				)),
+					None,
+				),
 		};
 
-		let res = state.assignment_criteria.check_assignment_cert(
-			claimed_core_index,
-			assignment.validator,
-			&criteria::Config::from(session_info),
-			block_entry.relay_vrf_story(),
-			&assignment.cert,
-			approval_entry.backing_group(),
-		);
-
-		let tranche = match res {
+		let tranche = match cert_result {
 			Err(crate::criteria::InvalidAssignment(reason)) =>
-				return Ok((
+				return (
 					AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCert(
 						assignment.validator,
 						format!("{:?}", reason),
 					)),
-					Vec::new(),
-				)),
+					None,
+				),
 			Ok(tranche) => {
 				let current_tranche =
 					state.clock.tranche_now(state.slot_duration_millis, block_entry.slot());
@@ -1703,7 +1858,7 @@ fn check_and_import_assignment(
 				let too_far_in_future = current_tranche + TICK_TOO_FAR_IN_FUTURE as DelayTranche;
 
 				if tranche >= too_far_in_future {
-					return Ok((AssignmentCheckResult::TooFarInFuture, Vec::new()))
+					return (AssignmentCheckResult::TooFarInFuture, None)
 				}
 
 				tranche
@@ -1720,7 +1875,7 @@ fn check_and_import_assignment(
 				target: LOG_TARGET,
 				validator = assignment.validator.0,
 				candidate_hash = ?assigned_candidate_hash,
-				para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
+				?para_id,
 				"Imported assignment.",
 			);
 
@@ -1731,7 +1886,7 @@ fn check_and_import_assignment(
 	let mut actions = Vec::new();
 
 	// We've imported a new approval, so we need to schedule a wake-up for when that might no-show.
-	if let Some((approval_entry, status)) = state.approval_status(&block_entry, &candidate_entry) {
+	if let Some((approval_entry, status)) = state.approval_status(block_entry, candidate_entry) {
 		actions.extend(schedule_wakeup_action(
 			approval_entry,
 			block_entry.block_hash(),
@@ -1743,10 +1898,7 @@ fn check_and_import_assignment(
 		));
 	}
 
-	// We also write the candidate entry as it now contains the new candidate.
-	db.write_candidate_entry(candidate_entry.into());
-
-	Ok((res, actions))
+	(res, Some(actions))
 }
 
 fn check_and_import_approval<T>(
@@ -1756,107 +1908,190 @@ fn check_and_import_approval<T>(
 	approval: IndirectSignedApprovalVote,
 	with_response: impl FnOnce(ApprovalCheckResult) -> T,
 ) -> SubsystemResult<(Vec<Action>, T)> {
-	macro_rules! respond_early {
-		($e: expr) => {{
-			let t = with_response($e);
-			return Ok((Vec::new(), t))
-		}};
+	check_and_import_approvals(state, db, metrics, vec![approval], |mut results| {
+		with_response(results.pop().expect("one result per approval; qed"))
+	})
+}
+
+/// Check and import approval votes in order, verifying the signatures of all of them together.
+///
+/// The results are handed to `with_response` once all votes are checked, but before importing
+/// them, as importing can be heavy.
+fn check_and_import_approvals<T>(
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	metrics: &Metrics,
+	approvals: Vec<IndirectSignedApprovalVote>,
+	with_response: impl FnOnce(Vec<ApprovalCheckResult>) -> T,
+) -> SubsystemResult<(Vec<Action>, T)> {
+	let mut block_entries = HashMap::new();
+	let mut prepared = Vec::with_capacity(approvals.len());
+	let mut signatures = Vec::new();
+	for approval in &approvals {
+		match prepare_approval_check(state, db, &mut block_entries, approval)? {
+			Ok((signature, approved_candidate_hash)) => {
+				signatures.push(signature);
+				prepared.push(Ok(approved_candidate_hash));
+			},
+			Err(result) => prepared.push(Err(result)),
+		}
 	}
 
-	let block_entry = match db.load_block_entry(&approval.block_hash)? {
-		Some(b) => b,
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::UnknownBlock(
-				approval.block_hash
-			),))
+	let mut valid_signatures = batch::check_approval_signatures(&signatures).into_iter();
+
+	// Only load each candidate entry once, votes of a batch may well approve the same candidate.
+	let mut candidate_entries = HashMap::new();
+	let mut results = Vec::with_capacity(approvals.len());
+	let mut accepted = Vec::new();
+	for (approval, prepared) in approvals.into_iter().zip(prepared) {
+		let approved_candidate_hash = match prepared {
+			Ok(approved_candidate_hash) => approved_candidate_hash,
+			Err(result) => {
+				results.push(result);
+				continue
+			},
+		};
+
+		if !valid_signatures.next().expect("one signature per prepared approval; qed") {
+			results.push(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(
+				approval.validator,
+			)));
+			continue
+		}
+
+		let candidate_entry = match candidate_entries.entry(approved_candidate_hash) {
+			HMEntry::Occupied(e) => e.into_mut(),
+			HMEntry::Vacant(e) => match db.load_candidate_entry(&approved_candidate_hash)? {
+				Some(c) => e.insert(c),
+				None => {
+					results.push(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidCandidate(
+						approval.candidate_index,
+						approved_candidate_hash,
+					)));
+					continue
+				},
+			},
+		};
+
+		// Don't accept approvals until assignment.
+		match candidate_entry.approval_entry(&approval.block_hash) {
+			None => {
+				results.push(ApprovalCheckResult::Bad(ApprovalCheckError::Internal(
+					approval.block_hash,
+					approved_candidate_hash,
+				)));
+				continue
+			},
+			Some(e) if !e.is_assigned(approval.validator) => {
+				results.push(ApprovalCheckResult::Bad(ApprovalCheckError::NoAssignment(
+					approval.validator,
+				)));
+				continue
+			},
+			_ => {},
+		}
+
+		results.push(ApprovalCheckResult::Accepted);
+		accepted.push((approval, approved_candidate_hash));
+	}
+
+	// importing the approvals can be heavy as it may trigger acceptance for a series of blocks.
+	let t = with_response(results);
+
+	let mut actions = Vec::new();
+	for (approval, approved_candidate_hash) in accepted {
+		// Importing may write the block and the candidate entry, so later votes for the same ones
+		// have to load them again.
+		let block_entry = match block_entries.remove(&approval.block_hash) {
+			Some(b) => b,
+			None => match db.load_block_entry(&approval.block_hash)? {
+				Some(b) => b,
+				None => continue,
+			},
+		};
+		let candidate_entry = match candidate_entries.remove(&approved_candidate_hash) {
+			Some(c) => c,
+			None => match db.load_candidate_entry(&approved_candidate_hash)? {
+				Some(c) => c,
+				None => continue,
+			},
+		};
+
+		gum::trace!(
+			target: LOG_TARGET,
+			validator_index = approval.validator.0,
+			candidate_hash = ?approved_candidate_hash,
+			para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
+			"Importing approval vote",
+		);
+
+		actions.extend(advance_approval_state(
+			state,
+			db,
+			&metrics,
+			block_entry,
+			approved_candidate_hash,
+			candidate_entry,
+			ApprovalStateTransition::RemoteApproval(approval.validator),
+		));
+	}
+
+	Ok((actions, t))
+}
+
+/// Load everything needed to check the signature of an approval vote.
+///
+/// Returns the signature check and the hash of the approved candidate or the result if the vote
+/// is bad regardless of its signature.
+fn prepare_approval_check(
+	state: &State,
+	db: &mut OverlayedBackend<'_, impl Backend>,
+	block_entries: &mut HashMap<Hash, BlockEntry>,
+	approval: &IndirectSignedApprovalVote,
+) -> SubsystemResult<Result<(batch::ApprovalSignature, CandidateHash), ApprovalCheckResult>> {
+	let block_entry = match block_entries.entry(approval.block_hash) {
+		HMEntry::Occupied(e) => e.into_mut(),
+		HMEntry::Vacant(e) => match db.load_block_entry(&approval.block_hash)? {
+			Some(b) => e.insert(b),
+			None =>
+				return Ok(Err(ApprovalCheckResult::Bad(ApprovalCheckError::UnknownBlock(
+					approval.block_hash,
+				)))),
 		},
 	};
 
 	let session_info = match state.session_info(block_entry.session()) {
 		Some(s) => s,
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::UnknownSessionIndex(
-				block_entry.session()
-			),))
-		},
+		None =>
+			return Ok(Err(ApprovalCheckResult::Bad(ApprovalCheckError::UnknownSessionIndex(
+				block_entry.session(),
+			)))),
 	};
 
 	let approved_candidate_hash = match block_entry.candidate(approval.candidate_index as usize) {
 		Some((_, h)) => *h,
-		None => respond_early!(ApprovalCheckResult::Bad(
-			ApprovalCheckError::InvalidCandidateIndex(approval.candidate_index),
-		)),
+		None =>
+			return Ok(Err(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidCandidateIndex(
+				approval.candidate_index,
+			)))),
 	};
 
 	let pubkey = match session_info.validators.get(approval.validator.0 as usize) {
 		Some(k) => k,
-		None => respond_early!(ApprovalCheckResult::Bad(
-			ApprovalCheckError::InvalidValidatorIndex(approval.validator),
-		)),
+		None =>
+			return Ok(Err(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidValidatorIndex(
+				approval.validator,
+			)))),
 	};
 
-	// Signature check:
-	match DisputeStatement::Valid(ValidDisputeStatementKind::ApprovalChecking).check_signature(
-		&pubkey,
+	let signature = batch::ApprovalSignature::new(
+		pubkey.clone(),
 		approved_candidate_hash,
 		block_entry.session(),
-		&approval.signature,
-	) {
-		Err(_) => respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(
-			approval.validator
-		),)),
-		Ok(()) => {},
-	};
-
-	let candidate_entry = match db.load_candidate_entry(&approved_candidate_hash)? {
-		Some(c) => c,
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::InvalidCandidate(
-				approval.candidate_index,
-				approved_candidate_hash
-			),))
-		},
-	};
-
-	// Don't accept approvals until assignment.
-	match candidate_entry.approval_entry(&approval.block_hash) {
-		None => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::Internal(
-				approval.block_hash,
-				approved_candidate_hash
-			),))
-		},
-		Some(e) if !e.is_assigned(approval.validator) => {
-			respond_early!(ApprovalCheckResult::Bad(ApprovalCheckError::NoAssignment(
-				approval.validator
-			),))
-		},
-		_ => {},
-	}
-
-	// importing the approval can be heavy as it may trigger acceptance for a series of blocks.
-	let t = with_response(ApprovalCheckResult::Accepted);
-
-	gum::trace!(
-		target: LOG_TARGET,
-		validator_index = approval.validator.0,
-		validator = ?pubkey,
-		candidate_hash = ?approved_candidate_hash,
-		para_id = ?candidate_entry.candidate_receipt().descriptor.para_id,
-		"Importing approval vote",
-	);
-
-	let actions = advance_approval_state(
-		state,
-		db,
-		&metrics,
-		block_entry,
-		approved_candidate_hash,
-		candidate_entry,
-		ApprovalStateTransition::RemoteApproval(approval.validator),
+		approval.signature.clone(),
 	);
 
-	Ok((actions, t))
+	Ok(Ok((signature, approved_candidate_hash)))
 }
 
 #[derive(Debug)]
@@ -2032,26 +2267,42 @@ fn should_trigger_assignment(
 	match approval_entry.our_assignment() {
 		None => false,
 		Some(ref assignment) if assignment.triggered() => false,
-		Some(ref assignment) if assignment.tranche() == 0 => true,
-		Some(ref assignment) => {
-			match required_tranches {
-				RequiredTranches::All => !approval_checking::check_approval(
-					&candidate_entry,
-					&approval_entry,
-					RequiredTranches::All,
-				)
-				.is_approved(Tick::max_value()), // when all are required, we are just waiting for the first 1/3+
-				RequiredTranches::Pending { maximum_broadcast, clock_drift, .. } => {
-					let drifted_tranche_now =
-						tranche_now.saturating_sub(clock_drift as DelayTranche);
-					assignment.tranche() <= maximum_broadcast &&
-						assignment.tranche() <= drifted_tranche_now
-				},
-				RequiredTranches::Exact { .. } => {
-					// indicates that no new assignments are needed at the moment.
-					false
-				},
-			}
+		Some(ref assignment) => should_trigger_tranche(
+			assignment.tranche(),
+			approval_entry,
+			candidate_entry,
+			required_tranches,
+			tranche_now,
+		),
+	}
+}
+
+/// Whether an untriggered assignment of the given tranche should be triggered now.
+fn should_trigger_tranche(
+	our_tranche: DelayTranche,
+	approval_entry: &ApprovalEntry,
+	candidate_entry: &CandidateEntry,
+	required_tranches: RequiredTranches,
+	tranche_now: DelayTranche,
+) -> bool {
+	if our_tranche == 0 {
+		return true
+	}
+
+	match required_tranches {
+		RequiredTranches::All => !approval_checking::check_approval(
+			&candidate_entry,
+			&approval_entry,
+			RequiredTranches::All,
+		)
+		.is_approved(Tick::max_value()), // when all are required, we are just waiting for the first 1/3+
+		RequiredTranches::Pending { maximum_broadcast, clock_drift, .. } => {
+			let drifted_tranche_now = tranche_now.saturating_sub(clock_drift as DelayTranche);
+			our_tranche <= maximum_broadcast && our_tranche <= drifted_tranche_now
+		},
+		RequiredTranches::Exact { .. } => {
+			// indicates that no new assignments are needed at the moment.
+			false
 		},
 	}
 }
@@ -0,0 +1,521 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Simulation of approval checking with a synthetic validator set.
+//!
+//! Meant for studying the approval related fields of the host configuration, e.g. when reviewing
+//! proposals to change them. Assignments are computed with the real assignment criteria from a
+//! keystore per synthetic validator, and the required tranches and approval of candidates are
+//! determined by the same logic the subsystem uses.
+//!
+//! The network is modelled by delaying every assignment and approval by a random amount of time,
+//! and by validators not approving after triggering their assignment, i.e. no-shows. All
+//! validators share a single view of the assignments and approvals of a candidate, as observed by
+//! one node.
+
+use bitvec::{bitvec, order::Lsb0 as BitOrderLsb0};
+use polkadot_node_primitives::approval::{DelayTranche, RelayVRFStory};
+use polkadot_primitives::v2::{
+	AssignmentId, CandidateDescriptor, CandidateReceipt, CoreIndex, GroupIndex, Hash, Id as ParaId,
+	SessionInfo, ValidatorId, ValidatorIndex, ASSIGNMENT_KEY_TYPE_ID,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sc_keystore::LocalKeystore;
+use serde::Serialize;
+use sp_application_crypto::sr25519;
+use sp_consensus_slots::Slot;
+use sp_keystore::SyncCryptoStore;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+	approval_checking::{check_approval, tranches_to_approve},
+	criteria::{compute_assignments, Config},
+	persisted_entries::{ApprovalEntry, CandidateEntry},
+	should_trigger_tranche,
+	time::{slot_number_to_tick, Tick, TICK_DURATION_MILLIS},
+	APPROVAL_DELAY,
+};
+
+/// Parameters of a simulation.
+#[derive(Debug, Clone)]
+pub struct SimulationParams {
+	/// Number of validators.
+	pub n_validators: u32,
+	/// Number of availability cores, all of them are occupied in every block.
+	pub n_cores: u32,
+	/// `HostConfiguration::n_delay_tranches`.
+	pub n_delay_tranches: u32,
+	/// `HostConfiguration::zeroth_delay_tranche_width`.
+	pub zeroth_delay_tranche_width: u32,
+	/// `HostConfiguration::needed_approvals`.
+	pub needed_approvals: u32,
+	/// `HostConfiguration::relay_vrf_modulo_samples`.
+	pub relay_vrf_modulo_samples: u32,
+	/// `HostConfiguration::no_show_slots`.
+	pub no_show_slots: u32,
+	/// Duration of a relay chain slot.
+	pub slot_duration_millis: u64,
+	/// Number of relay chain blocks to simulate.
+	pub n_blocks: u32,
+	/// Probability of a validator not approving a candidate after triggering its assignment.
+	pub no_show_probability: f64,
+	/// Lower bound of the time it takes for an assignment or an approval to arrive.
+	pub min_network_delay_millis: u64,
+	/// Upper bound of the time it takes for an assignment or an approval to arrive.
+	pub max_network_delay_millis: u64,
+	/// Time it takes to recover and check a candidate.
+	pub approval_check_millis: u64,
+	/// Candidates not approved within this time after inclusion are reported as unapproved.
+	pub timeout_millis: u64,
+	/// Seed of the keys and of all random choices, equal seeds yield equal reports.
+	pub seed: u64,
+}
+
+/// Errors of a simulation.
+#[derive(Debug, thiserror::Error)]
+pub enum SimulationError {
+	/// The parameters describe no meaningful simulation.
+	#[error("Invalid simulation parameters: {0}")]
+	InvalidParams(&'static str),
+	/// Creating the keys of the synthetic validators failed.
+	#[error("Failed to create validator keys: {0}")]
+	Keystore(String),
+}
+
+/// Distribution of a measured quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Distribution {
+	/// The smallest sample.
+	pub min: u64,
+	/// The arithmetic mean of all samples.
+	pub mean: f64,
+	/// The median.
+	pub p50: u64,
+	/// The 90th percentile.
+	pub p90: u64,
+	/// The 99th percentile.
+	pub p99: u64,
+	/// The largest sample.
+	pub max: u64,
+}
+
+impl Distribution {
+	/// Compute the distribution of the given samples, `None` if there are none.
+	pub fn from_samples(mut samples: Vec<u64>) -> Option<Self> {
+		if samples.is_empty() {
+			return None
+		}
+		samples.sort_unstable();
+
+		// Nearest-rank percentile.
+		let percentile = |p: usize| samples[(samples.len() * p + 99) / 100 - 1];
+		Some(Distribution {
+			min: samples[0],
+			mean: samples.iter().sum::<u64>() as f64 / samples.len() as f64,
+			p50: percentile(50),
+			p90: percentile(90),
+			p99: percentile(99),
+			max: samples[samples.len() - 1],
+		})
+	}
+}
+
+/// The outcome of a simulation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+	/// Number of simulated candidates.
+	pub candidates: usize,
+	/// Number of candidates not approved within the timeout.
+	pub unapproved: usize,
+	/// Time from the inclusion of an approved candidate to its approval, in milliseconds.
+	pub time_to_approval_millis: Option<Distribution>,
+	/// Number of validators checking a candidate.
+	pub checks_per_candidate: Option<Distribution>,
+	/// Number of no-shows of a candidate.
+	pub no_shows_per_candidate: Option<Distribution>,
+	/// The highest tranche of a candidate with triggered assignments.
+	pub highest_tranche: Option<Distribution>,
+	/// Number of candidates a validator checked over all simulated blocks.
+	pub validator_load: Option<Distribution>,
+}
+
+#[derive(Debug)]
+enum Event {
+	Assignment(ValidatorIndex, DelayTranche),
+	Approval(ValidatorIndex),
+}
+
+struct CandidateOutcome {
+	approved_at: Option<Tick>,
+	checks: u64,
+	no_shows: u64,
+	highest_tranche: DelayTranche,
+}
+
+/// Run a simulation.
+pub fn simulate(params: &SimulationParams) -> Result<Report, SimulationError> {
+	validate(params)?;
+
+	let mut rng = ChaCha8Rng::seed_from_u64(params.seed);
+	let (keystores, assignment_keys) = synthetic_validators(&mut rng, params.n_validators)?;
+	let session_info = session_info(params, assignment_keys);
+	let config = Config::from(&session_info);
+
+	let mut load = vec![0u64; params.n_validators as usize];
+	let mut outcomes = Vec::new();
+	for _ in 0..params.n_blocks {
+		let relay_vrf_story = RelayVRFStory(rng.gen());
+		let relay_parent = Hash::from(rng.gen::<[u8; 32]>());
+		let leaving_cores = (0..params.n_cores)
+			.map(|core| {
+				let receipt = candidate_receipt(relay_parent, ParaId::from(core));
+				(receipt.hash(), CoreIndex(core), GroupIndex(core))
+			})
+			.collect::<Vec<_>>();
+
+		let mut assignments: HashMap<CoreIndex, Vec<(ValidatorIndex, DelayTranche)>> =
+			HashMap::new();
+		for keystore in &keystores {
+			let ours = compute_assignments(
+				keystore,
+				relay_vrf_story.clone(),
+				&config,
+				leaving_cores.clone(),
+			);
+			for (core, assignment) in ours {
+				assignments
+					.entry(core)
+					.or_default()
+					.push((assignment.validator_index(), assignment.tranche()));
+			}
+		}
+
+		for (_, core, backing_group) in leaving_cores {
+			let mut assignments = assignments.remove(&core).unwrap_or_default();
+			assignments.sort_by_key(|&(validator, tranche)| (tranche, validator));
+			let receipt = candidate_receipt(relay_parent, ParaId::from(core.0));
+			outcomes.push(simulate_candidate(
+				&mut rng,
+				params,
+				&session_info,
+				receipt,
+				backing_group,
+				assignments,
+				&mut load,
+			));
+		}
+	}
+
+	let approved = outcomes.iter().filter_map(|o| o.approved_at).collect::<Vec<_>>();
+	Ok(Report {
+		candidates: outcomes.len(),
+		unapproved: outcomes.len() - approved.len(),
+		time_to_approval_millis: Distribution::from_samples(
+			approved.into_iter().map(|tick| tick * TICK_DURATION_MILLIS).collect(),
+		),
+		checks_per_candidate: Distribution::from_samples(
+			outcomes.iter().map(|o| o.checks).collect(),
+		),
+		no_shows_per_candidate: Distribution::from_samples(
+			outcomes.iter().map(|o| o.no_shows).collect(),
+		),
+		highest_tranche: Distribution::from_samples(
+			outcomes.iter().map(|o| o.highest_tranche as u64).collect(),
+		),
+		validator_load: Distribution::from_samples(load),
+	})
+}
+
+fn validate(params: &SimulationParams) -> Result<(), SimulationError> {
+	if params.n_validators == 0 || params.n_cores == 0 {
+		return Err(SimulationError::InvalidParams("validators and cores must not be zero"))
+	}
+	if params.n_cores > params.n_validators {
+		return Err(SimulationError::InvalidParams("every core needs a backing group"))
+	}
+	if params.needed_approvals > params.n_validators {
+		return Err(SimulationError::InvalidParams("more approvals needed than validators"))
+	}
+	if !(0.0..=1.0).contains(&params.no_show_probability) {
+		return Err(SimulationError::InvalidParams("no-show probability must be within 0 and 1"))
+	}
+	if params.min_network_delay_millis > params.max_network_delay_millis {
+		return Err(SimulationError::InvalidParams("minimum network delay exceeds the maximum"))
+	}
+	if params.slot_duration_millis == 0 || params.slot_duration_millis % TICK_DURATION_MILLIS != 0 {
+		return Err(SimulationError::InvalidParams("slot duration must be a multiple of a tick"))
+	}
+	Ok(())
+}
+
+/// Create a keystore holding the assignment key of each validator.
+fn synthetic_validators(
+	rng: &mut impl Rng,
+	n_validators: u32,
+) -> Result<(Vec<LocalKeystore>, Vec<AssignmentId>), SimulationError> {
+	(0..n_validators)
+		.map(|_| {
+			let seed = rng
+				.gen::<[u8; 32]>()
+				.iter()
+				.map(|byte| format!("{:02x}", byte))
+				.collect::<String>();
+			let keystore = LocalKeystore::in_memory();
+			let public = SyncCryptoStore::sr25519_generate_new(
+				&keystore,
+				ASSIGNMENT_KEY_TYPE_ID,
+				Some(&format!("0x{}", seed)),
+			)
+			.map_err(|err| SimulationError::Keystore(err.to_string()))?;
+			Ok((keystore, AssignmentId::from(public)))
+		})
+		.collect::<Result<Vec<_>, _>>()
+		.map(|validators| validators.into_iter().unzip())
+}
+
+fn session_info(params: &SimulationParams, assignment_keys: Vec<AssignmentId>) -> SessionInfo {
+	// One backing group per core, validators are spread over them round-robin.
+	let n_groups = params.n_cores as usize;
+	let mut validator_groups = vec![Vec::new(); n_groups];
+	for validator in 0..params.n_validators {
+		validator_groups[validator as usize % n_groups].push(ValidatorIndex(validator));
+	}
+
+	SessionInfo {
+		active_validator_indices: (0..params.n_validators).map(ValidatorIndex).collect(),
+		random_seed: [0u8; 32],
+		dispute_period: 6,
+		validators: assignment_keys
+			.iter()
+			.map(|key| ValidatorId::from(sr25519::Public::from(key.clone())))
+			.collect(),
+		discovery_keys: Vec::new(),
+		assignment_keys,
+		validator_groups,
+		n_cores: params.n_cores,
+		zeroth_delay_tranche_width: params.zeroth_delay_tranche_width,
+		relay_vrf_modulo_samples: params.relay_vrf_modulo_samples,
+		n_delay_tranches: params.n_delay_tranches,
+		no_show_slots: params.no_show_slots,
+		needed_approvals: params.needed_approvals,
+	}
+}
+
+fn candidate_receipt(relay_parent: Hash, para_id: ParaId) -> CandidateReceipt {
+	CandidateReceipt {
+		descriptor: CandidateDescriptor {
+			para_id,
+			relay_parent,
+			collator: sr25519::Public::from_raw([0; 32]).into(),
+			persisted_validation_data_hash: Hash::zero(),
+			pov_hash: Hash::zero(),
+			erasure_root: Hash::zero(),
+			signature: sr25519::Signature::from_raw([0; 64]).into(),
+			para_head: Hash::zero(),
+			validation_code_hash: Hash::zero().into(),
+		},
+		commitments_hash: Hash::zero(),
+	}
+}
+
+/// Follow the approval of a single candidate included at tick `0`.
+fn simulate_candidate(
+	rng: &mut impl Rng,
+	params: &SimulationParams,
+	session_info: &SessionInfo,
+	receipt: CandidateReceipt,
+	backing_group: GroupIndex,
+	mut untriggered: Vec<(ValidatorIndex, DelayTranche)>,
+	load: &mut [u64],
+) -> CandidateOutcome {
+	let n_validators = params.n_validators as usize;
+	let no_show_duration = slot_number_to_tick(
+		params.slot_duration_millis,
+		Slot::from(u64::from(params.no_show_slots)),
+	);
+	let check_ticks = to_ticks(params.approval_check_millis);
+	let timeout = to_ticks(params.timeout_millis);
+
+	let mut approval_entry = ApprovalEntry::new(
+		Vec::new(),
+		backing_group,
+		None,
+		None,
+		bitvec![u8, BitOrderLsb0; 0; n_validators],
+		false,
+	);
+	let mut candidate_entry = CandidateEntry {
+		candidate: receipt,
+		session: 0,
+		block_assignments: BTreeMap::new(),
+		approvals: bitvec![u8, BitOrderLsb0; 0; n_validators],
+	};
+
+	let mut outcome =
+		CandidateOutcome { approved_at: None, checks: 0, no_shows: 0, highest_tranche: 0 };
+	let mut events: BTreeMap<Tick, Vec<Event>> = BTreeMap::new();
+	for tick in 0..=timeout {
+		// Events without delay arrive within the tick they were sent in.
+		let later = events.split_off(&(tick + 1));
+		for event in std::mem::replace(&mut events, later).into_values().flatten() {
+			match event {
+				Event::Assignment(validator, tranche) =>
+					approval_entry.import_assignment(tranche, validator, tick),
+				Event::Approval(validator) => {
+					candidate_entry.mark_approval(validator);
+				},
+			}
+		}
+
+		let tranche_now = tick as DelayTranche;
+		let required = tranches_to_approve(
+			&approval_entry,
+			candidate_entry.approvals(),
+			tranche_now,
+			0,
+			no_show_duration,
+			session_info.needed_approvals as usize,
+		);
+		let check = check_approval(&candidate_entry, &approval_entry, required.clone());
+		if check.is_approved(tick.saturating_sub(APPROVAL_DELAY)) {
+			outcome.approved_at = Some(tick);
+			break
+		}
+
+		// Validators wake up at their tranche and then whenever the required tranches change.
+		let mut i = 0;
+		while i < untriggered.len() {
+			let (validator, tranche) = untriggered[i];
+			if tranche > tranche_now {
+				break
+			}
+			if !should_trigger_tranche(
+				tranche,
+				&approval_entry,
+				&candidate_entry,
+				required.clone(),
+				tranche_now,
+			) {
+				i += 1;
+				continue
+			}
+
+			untriggered.remove(i);
+			load[validator.0 as usize] += 1;
+			outcome.checks += 1;
+			outcome.highest_tranche = outcome.highest_tranche.max(tranche);
+
+			let assignment_arrival = tick + network_delay(rng, params);
+			events
+				.entry(assignment_arrival)
+				.or_default()
+				.push(Event::Assignment(validator, tranche));
+			if rng.gen_bool(params.no_show_probability) {
+				outcome.no_shows += 1;
+			} else {
+				let approval_arrival =
+					(tick + check_ticks + network_delay(rng, params)).max(assignment_arrival);
+				events.entry(approval_arrival).or_default().push(Event::Approval(validator));
+			}
+		}
+	}
+
+	outcome
+}
+
+fn network_delay(rng: &mut impl Rng, params: &SimulationParams) -> Tick {
+	to_ticks(rng.gen_range(params.min_network_delay_millis..=params.max_network_delay_millis))
+}
+
+fn to_ticks(millis: u64) -> Tick {
+	(millis + TICK_DURATION_MILLIS - 1) / TICK_DURATION_MILLIS
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn params() -> SimulationParams {
+		SimulationParams {
+			n_validators: 20,
+			n_cores: 4,
+			n_delay_tranches: 20,
+			zeroth_delay_tranche_width: 0,
+			needed_approvals: 3,
+			relay_vrf_modulo_samples: 2,
+			no_show_slots: 2,
+			slot_duration_millis: 6_000,
+			n_blocks: 2,
+			no_show_probability: 0.0,
+			min_network_delay_millis: 0,
+			max_network_delay_millis: 500,
+			approval_check_millis: 1_000,
+			timeout_millis: 120_000,
+			seed: 42,
+		}
+	}
+
+	#[test]
+	fn honest_validators_approve_all_candidates() {
+		let report = simulate(&params()).unwrap();
+
+		assert_eq!(report.candidates, 8);
+		assert_eq!(report.unapproved, 0);
+		assert!(report.checks_per_candidate.unwrap().min >= 3);
+		assert_eq!(report.no_shows_per_candidate.unwrap().max, 0);
+		assert!(report.time_to_approval_millis.unwrap().max <= 120_000);
+	}
+
+	#[test]
+	fn candidates_are_not_approved_without_approvals() {
+		let report = simulate(&SimulationParams { no_show_probability: 1.0, ..params() }).unwrap();
+
+		assert_eq!(report.unapproved, report.candidates);
+		assert_eq!(report.time_to_approval_millis, None);
+		// No-shows are covered by further checks, up to all validators not backing the candidate.
+		let checks = report.checks_per_candidate.unwrap();
+		assert!(checks.min > 3);
+		assert!(checks.max <= 15);
+	}
+
+	#[test]
+	fn equal_seeds_yield_equal_reports() {
+		let params = SimulationParams { no_show_probability: 0.2, ..params() };
+
+		assert_eq!(simulate(&params).unwrap(), simulate(&params).unwrap());
+	}
+
+	#[test]
+	fn more_cores_than_validators_are_rejected() {
+		assert!(matches!(
+			simulate(&SimulationParams { n_cores: 21, ..params() }),
+			Err(SimulationError::InvalidParams(_)),
+		));
+	}
+
+	#[test]
+	fn distribution_uses_nearest_rank_percentiles() {
+		let distribution = Distribution::from_samples((1..=200).rev().collect()).unwrap();
+
+		assert_eq!(
+			distribution,
+			Distribution { min: 1, mean: 100.5, p50: 100, p90: 180, p99: 198, max: 200 },
+		);
+		assert_eq!(Distribution::from_samples(Vec::new()), None);
+	}
+}
@@ -111,7 +111,8 @@ fn make_sync_oracle(val: bool) -> (Box<dyn SyncOracle + Send>, TestSyncOracleHan
 pub mod test_constants {
 	use crate::approval_db::v1::Config as DatabaseConfig;
 	const DATA_COL: u32 = 0;
-	pub(crate) const NUM_COLUMNS: u32 = 1;
+	pub(crate) const SESSION_DATA_COL: u32 = 1;
+	pub(crate) const NUM_COLUMNS: u32 = 2;
 
 	pub(crate) const TEST_CONFIG: DatabaseConfig = DatabaseConfig { col_data: DATA_COL };
 }
@@ -488,6 +489,7 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 		ApprovalVotingSubsystem::with_config(
 			Config {
 				col_data: test_constants::TEST_CONFIG.col_data,
+				col_session_data: test_constants::SESSION_DATA_COL,
 				slot_duration_millis: SLOT_DURATION_MILLIS,
 			},
 			Arc::new(db),
@@ -631,6 +633,28 @@ async fn check_and_import_assignment(
 	rx
 }
 
+async fn check_and_import_assignments(
+	overseer: &mut VirtualOverseer,
+	assignments: Vec<(Hash, CandidateIndex, ValidatorIndex)>,
+) -> oneshot::Receiver<Vec<AssignmentCheckResult>> {
+	let (tx, rx) = oneshot::channel();
+	let assignments = assignments
+		.into_iter()
+		.map(|(block_hash, candidate_index, validator)| {
+			let cert = garbage_assignment_cert(AssignmentCertKind::RelayVRFModulo { sample: 0 });
+			(IndirectAssignmentCert { block_hash, validator, cert }, candidate_index)
+		})
+		.collect();
+	overseer_send(
+		overseer,
+		FromOrchestra::Communication {
+			msg: ApprovalVotingMessage::CheckAndImportAssignments(assignments, tx),
+		},
+	)
+	.await;
+	rx
+}
+
 struct BlockConfig {
 	slot: Slot,
 	candidates: Option<Vec<(CandidateReceipt, CoreIndex, GroupIndex)>>,
@@ -1482,6 +1506,144 @@ fn subsystem_accepts_and_imports_approval_after_assignment() {
 	});
 }
 
+#[test]
+fn subsystem_checks_and_imports_batch_of_assignments() {
+	test_harness(HarnessConfig::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer, sync_oracle_handle: _sync_oracle_handle, .. } =
+			test_harness;
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(rx)) => {
+				rx.send(Ok(0)).unwrap();
+			}
+		);
+
+		let block_hash = Hash::repeat_byte(0x01);
+		let unknown_block_hash = Hash::repeat_byte(0x02);
+
+		ChainBuilder::new()
+			.add_block(
+				block_hash,
+				ChainBuilder::GENESIS_HASH,
+				1,
+				BlockConfig { slot: Slot::from(1), candidates: None, session_info: None },
+			)
+			.build(&mut virtual_overseer)
+			.await;
+
+		let rx = check_and_import_assignments(
+			&mut virtual_overseer,
+			vec![
+				(block_hash, 0, ValidatorIndex(0)),
+				(block_hash, 0, ValidatorIndex(0)),
+				(unknown_block_hash, 0, ValidatorIndex(0)),
+				(block_hash, 7, ValidatorIndex(1)),
+				(block_hash, 0, ValidatorIndex(1)),
+			],
+		)
+		.await;
+
+		assert_eq!(
+			rx.await,
+			Ok(vec![
+				AssignmentCheckResult::Accepted,
+				AssignmentCheckResult::AcceptedDuplicate,
+				AssignmentCheckResult::Bad(AssignmentCheckError::UnknownBlock(unknown_block_hash)),
+				AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCandidateIndex(7)),
+				AssignmentCheckResult::Accepted,
+			]),
+		);
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn subsystem_rejects_only_bad_signatures_of_approval_batch() {
+	test_harness(HarnessConfig::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer, sync_oracle_handle: _sync_oracle_handle, .. } =
+			test_harness;
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainApi(ChainApiMessage::FinalizedBlockNumber(rx)) => {
+				rx.send(Ok(0)).unwrap();
+			}
+		);
+
+		let block_hash = Hash::repeat_byte(0x01);
+
+		let candidate_hash = {
+			let mut candidate_receipt =
+				dummy_candidate_receipt_bad_sig(block_hash, Some(Default::default()));
+			candidate_receipt.descriptor.para_id = ParaId::from(0_u32);
+			candidate_receipt.descriptor.relay_parent = block_hash;
+			candidate_receipt.hash()
+		};
+		let session_index = 1;
+
+		ChainBuilder::new()
+			.add_block(
+				block_hash,
+				ChainBuilder::GENESIS_HASH,
+				1,
+				BlockConfig { slot: Slot::from(1), candidates: None, session_info: None },
+			)
+			.build(&mut virtual_overseer)
+			.await;
+
+		let rx = check_and_import_assignments(
+			&mut virtual_overseer,
+			vec![(block_hash, 0, ValidatorIndex(0)), (block_hash, 0, ValidatorIndex(1))],
+		)
+		.await;
+
+		assert_eq!(
+			rx.await,
+			Ok(vec![AssignmentCheckResult::Accepted, AssignmentCheckResult::Accepted]),
+		);
+
+		// The vote of Bob is signed by Alice.
+		let approvals = [
+			(ValidatorIndex(0), Sr25519Keyring::Alice),
+			(ValidatorIndex(1), Sr25519Keyring::Alice),
+		]
+		.into_iter()
+		.map(|(validator, key)| IndirectSignedApprovalVote {
+			block_hash,
+			candidate_index: 0,
+			validator,
+			signature: sign_approval(key, candidate_hash, session_index),
+		})
+		.collect();
+
+		let (tx, rx) = oneshot::channel();
+		overseer_send(
+			&mut virtual_overseer,
+			FromOrchestra::Communication {
+				msg: ApprovalVotingMessage::CheckAndImportApprovals(approvals, tx),
+			},
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainSelection(ChainSelectionMessage::Approved(b_hash)) => {
+				assert_eq!(b_hash, block_hash);
+			}
+		);
+
+		assert_eq!(
+			rx.await,
+			Ok(vec![
+				ApprovalCheckResult::Accepted,
+				ApprovalCheckResult::Bad(ApprovalCheckError::InvalidSignature(ValidatorIndex(1))),
+			]),
+		);
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn subsystem_second_approval_import_only_schedules_wakeups() {
 	test_harness(HarnessConfig::default(), |test_harness| async move {
@@ -29,7 +29,7 @@ use std::{
 use futures::{channel::oneshot, future, select, FutureExt};
 use futures_timer::Delay;
 use parity_scale_codec::{Decode, Encode, Error as CodecError, Input};
-use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_node_subsystem_util::database::{DBTransaction, Database, DecodedEntry};
 
 use bitvec::{order::Lsb0 as BitOrderLsb0, vec::BitVec};
 use polkadot_node_primitives::{AvailableData, ErasureChunk};
@@ -483,6 +483,61 @@ impl AvailabilityStoreSubsystem {
 			finalized_number: None,
 		}
 	}
+
+	/// Decode every entry of the meta column for offline inspection.
+	///
+	/// Available data and erasure chunks are not decoded.
+	pub fn inspect_meta(&self) -> Result<Vec<DecodedEntry>, Error> {
+		let mut entries = Vec::new();
+		for (key, value) in self.db.iter(self.config.col_meta) {
+			let entry = if key.starts_with(META_PREFIX) {
+				let candidate_hash = CandidateHash::decode(&mut &key[META_PREFIX.len()..])?;
+				let meta = CandidateMeta::decode(&mut &value[..])?;
+				DecodedEntry::new(key, "candidate_meta", (candidate_hash, meta))
+			} else if key.starts_with(UNFINALIZED_PREFIX) {
+				let decoded = decode_unfinalized_key(&key)?;
+				DecodedEntry::new(key, "unfinalized", decoded)
+			} else if key.starts_with(PRUNE_BY_TIME_PREFIX) {
+				let decoded = decode_pruning_key(&key)?;
+				DecodedEntry::new(key, "prune_by_time", decoded)
+			} else {
+				DecodedEntry::new(key, "unknown", value)
+			};
+			entries.push(entry);
+		}
+
+		Ok(entries)
+	}
+
+	/// The keys of all available data and erasure chunks recorded in the meta column.
+	///
+	/// The data column is not required to support iteration, so the keys are reconstructed from
+	/// the candidate metadata instead. Meant for offline tooling, like database migrations.
+	pub fn data_keys(&self) -> Result<Vec<Vec<u8>>, Error> {
+		let mut keys = Vec::new();
+		for (key, value) in self.db.iter_with_prefix(self.config.col_meta, META_PREFIX) {
+			let candidate_hash = CandidateHash::decode(&mut &key[META_PREFIX.len()..])?;
+			let meta = CandidateMeta::decode(&mut &value[..])?;
+			if meta.data_available {
+				keys.push((AVAILABLE_PREFIX, candidate_hash).encode());
+			}
+			for (index, _) in meta.chunks_stored.iter().enumerate().filter(|(_, b)| **b) {
+				keys.push((CHUNK_PREFIX, candidate_hash, ValidatorIndex(index as _)).encode());
+			}
+		}
+
+		Ok(keys)
+	}
+
+	/// Load the available data of the given candidate, if it is stored.
+	///
+	/// Meant for offline tooling, the subsystem does not need to be running.
+	pub fn available_data(
+		&self,
+		candidate_hash: &CandidateHash,
+	) -> Result<Option<AvailableData>, Error> {
+		load_available_data(&self.db, &self.config, candidate_hash)
+	}
 }
 
 /// We keep the hashes and numbers of all unfinalized
@@ -1099,3 +1099,98 @@ async fn import_leaf(
 
 	new_leaf
 }
+
+#[test]
+fn inspect_meta_decodes_all_entries() {
+	let store = test_store();
+	let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+	let block_hash = Hash::repeat_byte(2);
+
+	with_tx(&store, |tx| {
+		write_meta(
+			tx,
+			&TEST_CONFIG,
+			&candidate_hash,
+			&CandidateMeta {
+				data_available: false,
+				chunks_stored: bitvec::bitvec![u8, BitOrderLsb0; 0; 10],
+				state: State::Unavailable(BETimestamp(5)),
+			},
+		);
+		write_unfinalized_block_contains(tx, &TEST_CONFIG, 10, &block_hash, &candidate_hash);
+		write_pruning_key(tx, &TEST_CONFIG, Duration::from_secs(7), &candidate_hash);
+	});
+
+	let subsystem = AvailabilityStoreSubsystem::new(store, TEST_CONFIG, Metrics::default());
+	let entries = subsystem.inspect_meta().unwrap();
+
+	let mut kinds = entries.iter().map(|e| e.kind).collect::<Vec<_>>();
+	kinds.sort();
+	assert_eq!(kinds, vec!["candidate_meta", "prune_by_time", "unfinalized"]);
+	assert!(entries
+		.iter()
+		.find(|e| e.kind == "candidate_meta")
+		.unwrap()
+		.value
+		.to_string()
+		.contains("Unavailable"));
+}
+
+#[test]
+fn available_data_is_loaded_offline() {
+	let store = test_store();
+	let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+	let available_data = AvailableData {
+		pov: Arc::new(PoV { block_data: BlockData(vec![4, 2]) }),
+		validation_data: PersistedValidationData::default(),
+	};
+
+	with_tx(&store, |tx| write_available_data(tx, &TEST_CONFIG, &candidate_hash, &available_data));
+
+	let subsystem = AvailabilityStoreSubsystem::new(store, TEST_CONFIG, Metrics::default());
+	assert_eq!(subsystem.available_data(&candidate_hash).unwrap(), Some(available_data));
+	assert_eq!(subsystem.available_data(&CandidateHash(Hash::repeat_byte(2))).unwrap(), None);
+}
+
+#[test]
+fn data_keys_are_reconstructed_from_meta() {
+	let store = test_store();
+	let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+	let mut chunks_stored = bitvec::bitvec![u8, BitOrderLsb0; 0; 5];
+	chunks_stored.set(1, true);
+	chunks_stored.set(3, true);
+
+	with_tx(&store, |tx| {
+		write_meta(
+			tx,
+			&TEST_CONFIG,
+			&candidate_hash,
+			&CandidateMeta {
+				data_available: true,
+				chunks_stored,
+				state: State::Unavailable(BETimestamp(5)),
+			},
+		);
+		write_meta(
+			tx,
+			&TEST_CONFIG,
+			&CandidateHash(Hash::repeat_byte(2)),
+			&CandidateMeta {
+				data_available: false,
+				chunks_stored: bitvec::bitvec![u8, BitOrderLsb0; 0; 5],
+				state: State::Unavailable(BETimestamp(5)),
+			},
+		);
+		write_pruning_key(tx, &TEST_CONFIG, Duration::from_secs(7), &candidate_hash);
+	});
+
+	let subsystem = AvailabilityStoreSubsystem::new(store, TEST_CONFIG, Metrics::default());
+	assert_eq!(
+		subsystem.data_keys().unwrap(),
+		vec![
+			(AVAILABLE_PREFIX, candidate_hash).encode(),
+			(CHUNK_PREFIX, candidate_hash, ValidatorIndex(1)).encode(),
+			(CHUNK_PREFIX, candidate_hash, ValidatorIndex(3)).encode(),
+		],
+	);
+}
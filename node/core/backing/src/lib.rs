@@ -982,6 +982,13 @@ impl<Context> CandidateBackingJob<Context> {
 		// collect the misbehaviors to avoid double mutable self borrow issues
 		let misbehaviors: Vec<_> = self.table.drain_misbehaviors().collect();
 		for (validator_id, report) in misbehaviors {
+			gum::debug!(
+				target: LOG_TARGET,
+				?validator_id,
+				relay_parent = ?self.parent,
+				"Reporting backing misbehavior of validator",
+			);
+
 			// The provisioner waits on candidate-backing, which means
 			// that we need to send unbounded messages to avoid cycles.
 			//
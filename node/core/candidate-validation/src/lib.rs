@@ -68,6 +68,35 @@ pub struct Config {
 	/// The path to the executable which can be used for spawning PVF compilation & validation
 	/// workers.
 	pub program_path: PathBuf,
+	/// The number of workers the validation host may spawn.
+	pub pvf_workers: PvfWorkerCounts,
+	/// Whether PVF jobs are prevented from accessing the file system and the network, at the cost
+	/// of a fresh worker for every job.
+	pub forbid_job_syscalls: bool,
+}
+
+/// The number of PVF preparation and execution workers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PvfWorkerCounts {
+	/// The number of preparation workers the host tries to keep.
+	pub prepare_soft_max: usize,
+	/// The number of preparation workers the host never exceeds, even for critical jobs.
+	///
+	/// Must not be lower than `prepare_soft_max`.
+	pub prepare_hard_max: usize,
+	/// The number of execution workers.
+	pub execute_max: usize,
+}
+
+impl Default for PvfWorkerCounts {
+	fn default() -> Self {
+		let pvf_config = polkadot_node_core_pvf::Config::new(PathBuf::new(), PathBuf::new());
+		Self {
+			prepare_soft_max: pvf_config.prepare_workers_soft_max_num,
+			prepare_hard_max: pvf_config.prepare_workers_hard_max_num,
+			execute_max: pvf_config.execute_workers_max_num,
+		}
+	}
 }
 
 /// The candidate validation subsystem.
@@ -102,6 +131,8 @@ impl<Context> CandidateValidationSubsystem {
 			self.pvf_metrics,
 			self.config.artifacts_cache_path,
 			self.config.program_path,
+			self.config.pvf_workers,
+			self.config.forbid_job_syscalls,
 		)
 		.map_err(|e| SubsystemError::with_origin("candidate-validation", e))
 		.boxed();
@@ -116,11 +147,16 @@ async fn run<Context>(
 	pvf_metrics: polkadot_node_core_pvf::Metrics,
 	cache_path: PathBuf,
 	program_path: PathBuf,
+	pvf_workers: PvfWorkerCounts,
+	forbid_job_syscalls: bool,
 ) -> SubsystemResult<()> {
-	let (validation_host, task) = polkadot_node_core_pvf::start(
-		polkadot_node_core_pvf::Config::new(cache_path, program_path),
-		pvf_metrics,
-	);
+	let mut pvf_config = polkadot_node_core_pvf::Config::new(cache_path, program_path);
+	pvf_config.prepare_workers_soft_max_num = pvf_workers.prepare_soft_max;
+	pvf_config.prepare_workers_hard_max_num = pvf_workers.prepare_hard_max;
+	pvf_config.execute_workers_max_num = pvf_workers.execute_max;
+	pvf_config.forbid_job_syscalls = forbid_job_syscalls;
+
+	let (validation_host, task) = polkadot_node_core_pvf::start(pvf_config, pvf_metrics);
 	ctx.spawn_blocking("pvf-validation-host", task.boxed())?;
 
 	loop {
@@ -318,7 +354,12 @@ where
 			PrepareError::Prevalidation(_) |
 			PrepareError::Preparation(_) |
 			PrepareError::Panic(_) => PreCheckOutcome::Invalid,
-			PrepareError::TimedOut | PrepareError::DidNotMakeIt => PreCheckOutcome::Failed,
+			// The memory limit and the syscall filter are configured by the node and cover the
+			// code of the worker as well, so violating them is not attributed to the PVF.
+			PrepareError::TimedOut |
+			PrepareError::DidNotMakeIt |
+			PrepareError::MemoryLimitExceeded |
+			PrepareError::ForbiddenSyscall => PreCheckOutcome::Failed,
 		},
 	}
 }
@@ -926,4 +926,5 @@ fn precheck_properly_classifies_outcomes() {
 
 	inner(Err(PrepareError::TimedOut), PreCheckOutcome::Failed);
 	inner(Err(PrepareError::DidNotMakeIt), PreCheckOutcome::Failed);
+	inner(Err(PrepareError::MemoryLimitExceeded), PreCheckOutcome::Failed);
 }
@@ -21,6 +21,7 @@
 //! [`Backend`], maintaining consistency between queries and temporary writes,
 //! before any commit to the underlying storage is made.
 
+use polkadot_node_subsystem::messages::ChainSelectionOverrideRecord;
 use polkadot_primitives::v2::{BlockNumber, Hash};
 
 use std::collections::HashMap;
@@ -35,6 +36,7 @@ pub(super) enum BackendWriteOp {
 	DeleteBlocksByNumber(BlockNumber),
 	DeleteBlockEntry(Hash),
 	DeleteStagnantAt(Timestamp),
+	WriteOverrideRecord(ChainSelectionOverrideRecord),
 }
 
 /// An abstraction over backend storage for the logic of this subsystem.
@@ -56,6 +58,8 @@ pub(super) trait Backend {
 	fn load_first_block_number(&self) -> Result<Option<BlockNumber>, Error>;
 	/// Load blocks by number.
 	fn load_blocks_by_number(&self, number: BlockNumber) -> Result<Vec<Hash>, Error>;
+	/// Load all operator overrides in ascending order by timestamp.
+	fn load_override_log(&self) -> Result<Vec<ChainSelectionOverrideRecord>, Error>;
 
 	/// Atomically write the list of operations, with later operations taking precedence over prior.
 	fn write<I>(&mut self, ops: I) -> Result<(), Error>
@@ -79,6 +83,8 @@ pub(super) struct OverlayedBackend<'a, B: 'a> {
 	stagnant_at: HashMap<Timestamp, Option<Vec<Hash>>>,
 	// 'None' means query inner.
 	leaves: Option<LeafEntrySet>,
+	// Records to append to the override log.
+	override_records: Vec<ChainSelectionOverrideRecord>,
 }
 
 impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
@@ -89,6 +95,7 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 			blocks_by_number: HashMap::new(),
 			stagnant_at: HashMap::new(),
 			leaves: None,
+			override_records: Vec::new(),
 		}
 	}
 
@@ -156,6 +163,10 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 		self.stagnant_at.insert(timestamp, None);
 	}
 
+	pub(super) fn write_override_record(&mut self, record: ChainSelectionOverrideRecord) {
+		self.override_records.push(record);
+	}
+
 	/// Transform this backend into a set of write-ops to be written to the
 	/// inner backend.
 	pub(super) fn into_write_ops(self) -> impl Iterator<Item = BackendWriteOp> {
@@ -176,10 +187,14 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 			None => BackendWriteOp::DeleteStagnantAt(n),
 		});
 
+		let override_ops =
+			self.override_records.into_iter().map(BackendWriteOp::WriteOverrideRecord);
+
 		block_entry_ops
 			.chain(blocks_by_number_ops)
 			.chain(leaf_ops)
 			.chain(stagnant_at_ops)
+			.chain(override_ops)
 	}
 }
 
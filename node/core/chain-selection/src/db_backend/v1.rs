@@ -23,11 +23,12 @@
 //! ("CS_block_height", BigEndianBlockNumber) -> Vec<Hash>;
 //! ("CS_stagnant_at", BigEndianTimestamp) -> Vec<Hash>;
 //! ("CS_leaves") -> LeafEntrySet;
+//! ("CS_override", BigEndianTimestamp, Hash, OverrideKind) -> OverrideRecord;
 //! ```
 //!
 //! The big-endian encoding is used for creating iterators over the key-value DB which are
 //! accessible by prefix, to find the earliest block number stored as well as the all stagnant
-//! blocks.
+//! blocks and operator overrides.
 //!
 //! The `Vec`s stored are always non-empty. Empty `Vec`s are not stored on disk so there is no
 //! semantic difference between `None` and an empty `Vec`.
@@ -38,10 +39,11 @@ use crate::{
 };
 
 use polkadot_node_primitives::BlockWeight;
+use polkadot_node_subsystem::messages::{ChainSelectionOverride, ChainSelectionOverrideRecord};
 use polkadot_primitives::v2::{BlockNumber, Hash};
 
 use parity_scale_codec::{Decode, Encode};
-use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_node_subsystem_util::database::{DBTransaction, Database, DecodedEntry};
 
 use std::sync::Arc;
 
@@ -49,6 +51,7 @@ const BLOCK_ENTRY_PREFIX: &[u8; 14] = b"CS_block_entry";
 const BLOCK_HEIGHT_PREFIX: &[u8; 15] = b"CS_block_height";
 const STAGNANT_AT_PREFIX: &[u8; 14] = b"CS_stagnant_at";
 const LEAVES_KEY: &[u8; 9] = b"CS_leaves";
+const OVERRIDE_PREFIX: &[u8; 11] = b"CS_override";
 
 type Timestamp = u64;
 
@@ -109,7 +112,7 @@ impl From<ViabilityCriteria> for crate::ViabilityCriteria {
 	}
 }
 
-#[derive(Encode, Decode)]
+#[derive(Debug, Encode, Decode)]
 struct LeafEntry {
 	weight: BlockWeight,
 	block_number: BlockNumber,
@@ -132,7 +135,7 @@ impl From<LeafEntry> for crate::LeafEntry {
 	}
 }
 
-#[derive(Encode, Decode)]
+#[derive(Debug, Encode, Decode)]
 struct LeafEntrySet {
 	inner: Vec<LeafEntry>,
 }
@@ -185,6 +188,62 @@ impl From<BlockEntry> for crate::BlockEntry {
 	}
 }
 
+#[derive(Debug, Encode, Decode, Clone, Copy, PartialEq)]
+enum OverrideKind {
+	#[codec(index = 0)]
+	Revert,
+	#[codec(index = 1)]
+	Unstagnate,
+}
+
+impl From<ChainSelectionOverride> for OverrideKind {
+	fn from(x: ChainSelectionOverride) -> Self {
+		match x {
+			ChainSelectionOverride::Revert => OverrideKind::Revert,
+			ChainSelectionOverride::Unstagnate => OverrideKind::Unstagnate,
+		}
+	}
+}
+
+impl From<OverrideKind> for ChainSelectionOverride {
+	fn from(x: OverrideKind) -> ChainSelectionOverride {
+		match x {
+			OverrideKind::Revert => ChainSelectionOverride::Revert,
+			OverrideKind::Unstagnate => ChainSelectionOverride::Unstagnate,
+		}
+	}
+}
+
+#[derive(Debug, Encode, Decode, Clone, PartialEq)]
+struct OverrideRecord {
+	timestamp: Timestamp,
+	block_hash: Hash,
+	block_number: BlockNumber,
+	kind: OverrideKind,
+}
+
+impl From<ChainSelectionOverrideRecord> for OverrideRecord {
+	fn from(x: ChainSelectionOverrideRecord) -> Self {
+		OverrideRecord {
+			timestamp: x.timestamp,
+			block_hash: x.block_hash,
+			block_number: x.block_number,
+			kind: x.kind.into(),
+		}
+	}
+}
+
+impl From<OverrideRecord> for ChainSelectionOverrideRecord {
+	fn from(x: OverrideRecord) -> ChainSelectionOverrideRecord {
+		ChainSelectionOverrideRecord {
+			timestamp: x.timestamp,
+			block_hash: x.block_hash,
+			block_number: x.block_number,
+			kind: x.kind.into(),
+		}
+	}
+}
+
 /// Configuration for the database backend.
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
@@ -204,6 +263,34 @@ impl DbBackend {
 	pub fn new(db: Arc<dyn Database>, config: Config) -> Self {
 		DbBackend { inner: db, config }
 	}
+
+	/// Decode every entry stored in the chain-selection column.
+	pub fn inspect_entries(&self) -> Result<Vec<DecodedEntry>, Error> {
+		let mut entries = Vec::new();
+		for (key, value) in self.inner.iter(self.config.col_data) {
+			let entry = if key.starts_with(BLOCK_ENTRY_PREFIX) {
+				let block_entry = BlockEntry::decode(&mut &value[..])?;
+				DecodedEntry::new(key, "block_entry", block_entry)
+			} else if let Some(number) = decode_block_height_key(&key) {
+				let hashes = <Vec<Hash>>::decode(&mut &value[..])?;
+				DecodedEntry::new(key, "block_height", (number, hashes))
+			} else if let Some(timestamp) = decode_stagnant_at_key(&key) {
+				let hashes = <Vec<Hash>>::decode(&mut &value[..])?;
+				DecodedEntry::new(key, "stagnant_at", (timestamp, hashes))
+			} else if &key[..] == &LEAVES_KEY[..] {
+				let leaves = LeafEntrySet::decode(&mut &value[..])?;
+				DecodedEntry::new(key, "leaves", leaves)
+			} else if key.starts_with(OVERRIDE_PREFIX) {
+				let record = OverrideRecord::decode(&mut &value[..])?;
+				DecodedEntry::new(key, "override", record)
+			} else {
+				DecodedEntry::new(key, "unknown", value)
+			};
+			entries.push(entry);
+		}
+
+		Ok(entries)
+	}
 }
 
 impl Backend for DbBackend {
@@ -265,6 +352,13 @@ impl Backend for DbBackend {
 			.map(|o| o.unwrap_or_default())
 	}
 
+	fn load_override_log(&self) -> Result<Vec<ChainSelectionOverrideRecord>, Error> {
+		self.inner
+			.iter_with_prefix(self.config.col_data, &OVERRIDE_PREFIX[..])
+			.map(|(_, v)| Ok(OverrideRecord::decode(&mut &v[..])?.into()))
+			.collect()
+	}
+
 	/// Atomically write the list of operations, with later operations taking precedence over prior.
 	fn write<I>(&mut self, ops: I) -> Result<(), Error>
 	where
@@ -321,6 +415,10 @@ impl Backend for DbBackend {
 					let timestamp: Timestamp = timestamp.into();
 					tx.delete(self.config.col_data, &stagnant_at_key(timestamp));
 				},
+				BackendWriteOp::WriteOverrideRecord(record) => {
+					let record: OverrideRecord = record.into();
+					tx.put_vec(self.config.col_data, &override_key(&record), record.encode());
+				},
 			}
 		}
 
@@ -360,6 +458,15 @@ fn stagnant_at_key(timestamp: Timestamp) -> [u8; 14 + 8] {
 	key
 }
 
+fn override_key(record: &OverrideRecord) -> [u8; 11 + 8 + 32 + 1] {
+	let mut key = [0; 11 + 8 + 32 + 1];
+	key[..11].copy_from_slice(OVERRIDE_PREFIX);
+	key[11..19].copy_from_slice(&record.timestamp.to_be_bytes());
+	record.block_hash.using_encoded(|s| key[19..51].copy_from_slice(s));
+	record.kind.using_encoded(|s| key[51..].copy_from_slice(s));
+	key
+}
+
 fn decode_block_height_key(key: &[u8]) -> Option<BlockNumber> {
 	if key.len() != 15 + 4 {
 		return None
@@ -619,4 +726,93 @@ mod tests {
 
 		assert_eq!(backend.load_blocks_by_number(10).unwrap(), vec![Hash::repeat_byte(3)]);
 	}
+
+	#[test]
+	fn override_log_in_order_of_timestamps() {
+		let db = test_db();
+		let config = Config { col_data: 0 };
+
+		let mut backend = DbBackend::new(db, config);
+
+		let record = |timestamp, kind| ChainSelectionOverrideRecord {
+			timestamp,
+			block_hash: Hash::repeat_byte(1),
+			block_number: 1,
+			kind,
+		};
+
+		backend
+			.write(vec![BackendWriteOp::WriteOverrideRecord(record(
+				300,
+				ChainSelectionOverride::Revert,
+			))])
+			.unwrap();
+
+		backend
+			.write(vec![
+				BackendWriteOp::WriteOverrideRecord(record(2, ChainSelectionOverride::Revert)),
+				BackendWriteOp::WriteOverrideRecord(record(2, ChainSelectionOverride::Unstagnate)),
+			])
+			.unwrap();
+
+		assert_eq!(
+			backend.load_override_log().unwrap(),
+			vec![
+				record(2, ChainSelectionOverride::Revert),
+				record(2, ChainSelectionOverride::Unstagnate),
+				record(300, ChainSelectionOverride::Revert),
+			],
+		);
+	}
+
+	#[test]
+	fn inspect_entries_decodes_all_kinds() {
+		let db = test_db();
+		let config = Config { col_data: 0 };
+
+		let mut backend = DbBackend::new(db, config);
+
+		let block_entry = BlockEntry {
+			block_hash: Hash::repeat_byte(1),
+			block_number: 1,
+			parent_hash: Hash::repeat_byte(0),
+			children: vec![],
+			viability: ViabilityCriteria {
+				earliest_unviable_ancestor: None,
+				explicitly_reverted: false,
+				approval: Approval::Stagnant,
+			},
+			weight: 100,
+		};
+
+		backend
+			.write(vec![
+				BackendWriteOp::WriteBlockEntry(block_entry.clone().into()),
+				BackendWriteOp::WriteBlocksByNumber(1, vec![block_entry.block_hash]),
+				BackendWriteOp::WriteStagnantAt(5, vec![block_entry.block_hash]),
+				BackendWriteOp::WriteOverrideRecord(ChainSelectionOverrideRecord {
+					timestamp: 7,
+					block_hash: block_entry.block_hash,
+					block_number: 1,
+					kind: ChainSelectionOverride::Unstagnate,
+				}),
+				BackendWriteOp::WriteViableLeaves(
+					LeafEntrySet {
+						inner: vec![LeafEntry {
+							weight: 100,
+							block_number: 1,
+							block_hash: block_entry.block_hash,
+						}],
+					}
+					.into(),
+				),
+			])
+			.unwrap();
+
+		let entries = backend.inspect_entries().unwrap();
+		let kinds = entries.iter().map(|e| e.kind).collect::<Vec<_>>();
+
+		assert_eq!(kinds, vec!["block_entry", "block_height", "leaves", "override", "stagnant_at"]);
+		assert!(entries[0].value.contains("Stagnant"));
+	}
 }
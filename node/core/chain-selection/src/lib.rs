@@ -19,17 +19,21 @@
 use polkadot_node_primitives::BlockWeight;
 use polkadot_node_subsystem::{
 	errors::ChainApiError,
-	messages::{ChainApiMessage, ChainSelectionMessage},
+	messages::{
+		ChainApiMessage, ChainSelectionApproval, ChainSelectionBlock, ChainSelectionMessage,
+		ChainSelectionOverride, ChainSelectionOverrideRecord,
+	},
 	overseer::{self, SubsystemSender},
 	FromOrchestra, OverseerSignal, SpawnedSubsystem, SubsystemError,
 };
-use polkadot_node_subsystem_util::database::Database;
+use polkadot_node_subsystem_util::database::{Database, DecodedEntry};
 use polkadot_primitives::v2::{BlockNumber, ConsensusLog, Hash, Header};
 
 use futures::{channel::oneshot, future::Either, prelude::*};
 use parity_scale_codec::Error as CodecError;
 
 use std::{
+	collections::{HashMap, HashSet, VecDeque},
 	sync::Arc,
 	time::{Duration, SystemTime, UNIX_EPOCH},
 };
@@ -71,6 +75,16 @@ impl Approval {
 	}
 }
 
+impl From<Approval> for ChainSelectionApproval {
+	fn from(approval: Approval) -> Self {
+		match approval {
+			Approval::Approved => ChainSelectionApproval::Approved,
+			Approval::Unapproved => ChainSelectionApproval::Unapproved,
+			Approval::Stagnant => ChainSelectionApproval::Stagnant,
+		}
+	}
+}
+
 #[derive(Debug, Clone)]
 struct ViabilityCriteria {
 	// Whether this block has been explicitly reverted by one of its descendants.
@@ -180,6 +194,25 @@ impl BlockEntry {
 		}
 	}
 
+	fn chain_selection_block(
+		&self,
+		viable_leaf: bool,
+		stagnant_at: Option<Timestamp>,
+	) -> ChainSelectionBlock {
+		ChainSelectionBlock {
+			hash: self.block_hash,
+			number: self.block_number,
+			parent_hash: self.parent_hash,
+			weight: self.weight,
+			approval: self.viability.approval.clone().into(),
+			stagnant_at,
+			explicitly_reverted: self.viability.explicitly_reverted,
+			earliest_unviable_ancestor: self.viability.earliest_unviable_ancestor,
+			viable: self.viability.is_viable(),
+			viable_leaf,
+		}
+	}
+
 	fn non_viable_ancestor_for_child(&self) -> Option<Hash> {
 		if self.viability.is_viable() {
 			None
@@ -346,6 +379,39 @@ impl ChainSelectionSubsystem {
 
 		backend.write(ops)
 	}
+
+	/// Decode all entries stored in the database.
+	pub fn inspect_db(&self) -> Result<Vec<DecodedEntry>, Error> {
+		let config = db_backend::v1::Config { col_data: self.config.col_data };
+		db_backend::v1::DbBackend::new(self.db.clone(), config).inspect_entries()
+	}
+
+	/// Load all unfinalized blocks stored in the database in ascending order by number.
+	pub fn block_tree(&self) -> Result<Vec<ChainSelectionBlock>, Error> {
+		let config = db_backend::v1::Config { col_data: self.config.col_data };
+		load_block_tree(&db_backend::v1::DbBackend::new(self.db.clone(), config))
+	}
+
+	/// Apply an operator override to the block corresponding to the specified `hash` and
+	/// record it in the override log.
+	///
+	/// Returns the updated block, or `None` if the block is unknown.
+	pub fn apply_override(
+		&self,
+		hash: Hash,
+		kind: ChainSelectionOverride,
+	) -> Result<Option<ChainSelectionBlock>, Error> {
+		let config = db_backend::v1::Config { col_data: self.config.col_data };
+		let mut backend = db_backend::v1::DbBackend::new(self.db.clone(), config);
+
+		handle_override(&mut backend, hash, kind, SystemClock.timestamp_now())
+	}
+
+	/// Load all operator overrides stored in the database, oldest first.
+	pub fn override_log(&self) -> Result<Vec<ChainSelectionOverrideRecord>, Error> {
+		let config = db_backend::v1::Config { col_data: self.config.col_data };
+		db_backend::v1::DbBackend::new(self.db.clone(), config).load_override_log()
+	}
 }
 
 #[overseer::subsystem(ChainSelection, error = SubsystemError, prefix = self::overseer)]
@@ -465,6 +531,21 @@ where
 
 							let _ = tx.send(best_containing);
 						}
+						ChainSelectionMessage::BlockTree(tx) => {
+							let _ = tx.send(load_block_tree(&*backend)?);
+						}
+						ChainSelectionMessage::Override(hash, kind, tx) => {
+							let block = handle_override(
+								backend,
+								hash,
+								kind,
+								clock.timestamp_now(),
+							)?;
+							let _ = tx.send(block);
+						}
+						ChainSelectionMessage::OverrideLog(tx) => {
+							let _ = tx.send(backend.load_override_log()?);
+						}
 					}
 				}
 			}
@@ -677,6 +758,103 @@ fn handle_approved_block(backend: &mut impl Backend, approved_block: Hash) -> Re
 	backend.write(ops)
 }
 
+// Handle an operator override, recording it in the override log if it changed the block.
+fn handle_override(
+	backend: &mut impl Backend,
+	hash: Hash,
+	kind: ChainSelectionOverride,
+	now: Timestamp,
+) -> Result<Option<ChainSelectionBlock>, Error> {
+	let ops = {
+		let mut overlay = OverlayedBackend::new(&*backend);
+
+		let changed = match kind {
+			ChainSelectionOverride::Revert => tree::revert_block(&mut overlay, hash)?,
+			ChainSelectionOverride::Unstagnate =>
+				tree::unstagnate_block(&mut overlay, hash, now + STAGNANT_TIMEOUT)?,
+		};
+
+		if changed {
+			let block_number = overlay
+				.load_block_entry(&hash)?
+				.map(|entry| entry.block_number)
+				.expect("overrides only change known blocks; qed");
+			overlay.write_override_record(ChainSelectionOverrideRecord {
+				timestamp: now,
+				block_hash: hash,
+				block_number,
+				kind,
+			});
+		}
+
+		overlay.into_write_ops()
+	};
+
+	backend.write(ops)?;
+
+	load_block(&*backend, hash)
+}
+
+fn load_stagnant_timestamps(backend: &impl Backend) -> Result<HashMap<Hash, Timestamp>, Error> {
+	Ok(backend
+		.load_stagnant_at_up_to(Timestamp::MAX, usize::MAX)?
+		.into_iter()
+		.flat_map(|(at, hashes)| hashes.into_iter().map(move |hash| (hash, at)))
+		.collect())
+}
+
+// Load a single block with its viability, approval and stagnation state.
+fn load_block(backend: &impl Backend, hash: Hash) -> Result<Option<ChainSelectionBlock>, Error> {
+	let entry = match backend.load_block_entry(&hash)? {
+		None => return Ok(None),
+		Some(entry) => entry,
+	};
+
+	let viable_leaf = backend.load_leaves()?.into_hashes_descending().any(|h| h == hash);
+	let stagnant_at = load_stagnant_timestamps(backend)?.get(&hash).copied();
+
+	Ok(Some(entry.chain_selection_block(viable_leaf, stagnant_at)))
+}
+
+// Load all unfinalized blocks with their viability, approval and stagnation state,
+// in ascending order by number.
+fn load_block_tree(backend: &impl Backend) -> Result<Vec<ChainSelectionBlock>, Error> {
+	let first_number = match backend.load_first_block_number()? {
+		None => return Ok(Vec::new()),
+		Some(number) => number,
+	};
+
+	let viable_leaves = backend.load_leaves()?.into_hashes_descending().collect::<HashSet<_>>();
+	let stagnant_at = load_stagnant_timestamps(backend)?;
+
+	// The blocks of the first number are the roots of all sub-trees, visiting them
+	// breadth-first yields the blocks in ascending order by number.
+	let mut blocks = Vec::new();
+	let mut frontier = backend
+		.load_blocks_by_number(first_number)?
+		.into_iter()
+		.collect::<VecDeque<_>>();
+	while let Some(hash) = frontier.pop_front() {
+		let entry = match backend.load_block_entry(&hash)? {
+			None => {
+				gum::warn!(target: LOG_TARGET, block_hash = ?hash, "Missing expected block entry");
+				continue
+			},
+			Some(entry) => entry,
+		};
+
+		frontier.extend(entry.children.iter().cloned());
+		blocks.push(
+			entry.chain_selection_block(
+				viable_leaves.contains(&hash),
+				stagnant_at.get(&hash).copied(),
+			),
+		);
+	}
+
+	Ok(blocks)
+}
+
 fn detect_stagnant(
 	backend: &mut impl Backend,
 	now: Timestamp,
@@ -47,6 +47,7 @@ struct TestBackendInner {
 	block_entries: HashMap<Hash, BlockEntry>,
 	blocks_by_number: BTreeMap<BlockNumber, Vec<Hash>>,
 	stagnant_at: BTreeMap<Timestamp, Vec<Hash>>,
+	override_log: Vec<ChainSelectionOverrideRecord>,
 	// earlier wakers at the back.
 	write_wakers: Vec<oneshot::Sender<()>>,
 }
@@ -162,6 +163,9 @@ impl Backend for TestBackend {
 			.get(&number)
 			.map_or(Vec::new(), |v| v.clone()))
 	}
+	fn load_override_log(&self) -> Result<Vec<ChainSelectionOverrideRecord>, Error> {
+		Ok(self.inner.lock().override_log.clone())
+	}
 
 	fn write<I>(&mut self, ops: I) -> Result<(), Error>
 	where
@@ -199,6 +203,9 @@ impl Backend for TestBackend {
 				BackendWriteOp::DeleteStagnantAt(time) => {
 					inner.stagnant_at.remove(&time);
 				},
+				BackendWriteOp::WriteOverrideRecord(record) => {
+					inner.override_log.push(record);
+				},
 			}
 		}
 
@@ -2014,3 +2021,248 @@ fn stagnant_makes_childless_parent_leaf() {
 		virtual_overseer
 	})
 }
+
+async fn block_tree(virtual_overseer: &mut VirtualOverseer) -> Vec<ChainSelectionBlock> {
+	let (tx, rx) = oneshot::channel();
+	virtual_overseer
+		.send(FromOrchestra::Communication { msg: ChainSelectionMessage::BlockTree(tx) })
+		.await;
+
+	rx.await.unwrap()
+}
+
+async fn override_block(
+	virtual_overseer: &mut VirtualOverseer,
+	hash: Hash,
+	kind: ChainSelectionOverride,
+) -> Option<ChainSelectionBlock> {
+	let (tx, rx) = oneshot::channel();
+	virtual_overseer
+		.send(FromOrchestra::Communication { msg: ChainSelectionMessage::Override(hash, kind, tx) })
+		.await;
+
+	rx.await.unwrap()
+}
+
+async fn override_log(virtual_overseer: &mut VirtualOverseer) -> Vec<ChainSelectionOverrideRecord> {
+	let (tx, rx) = oneshot::channel();
+	virtual_overseer
+		.send(FromOrchestra::Communication { msg: ChainSelectionMessage::OverrideLog(tx) })
+		.await;
+
+	rx.await.unwrap()
+}
+
+#[test]
+fn block_tree_reports_stagnation() {
+	test_harness(|backend, clock, mut virtual_overseer| async move {
+		let finalized_number = 0;
+		let finalized_hash = Hash::repeat_byte(0);
+
+		// F <- A1 <- A2
+
+		let (a1_hash, chain_a) =
+			construct_chain_on_base(vec![1], finalized_number, finalized_hash, |h| {
+				salt_header(h, b"a");
+			});
+
+		let (a2_hash, chain_a_ext) = construct_chain_on_base(vec![1], 1, a1_hash, |h| {
+			salt_header(h, b"a");
+		});
+
+		import_chains_into_empty(
+			&mut virtual_overseer,
+			&backend,
+			finalized_number,
+			finalized_hash,
+			vec![chain_a.clone()],
+		)
+		.await;
+
+		clock.inc_by(1);
+
+		import_blocks_into(&mut virtual_overseer, &backend, None, chain_a_ext.clone()).await;
+
+		let tree = block_tree(&mut virtual_overseer).await;
+		assert_eq!(tree.iter().map(|b| b.hash).collect::<Vec<_>>(), vec![a1_hash, a2_hash]);
+		assert_eq!(tree[0].stagnant_at, Some(STAGNANT_TIMEOUT));
+		assert!(tree[1].viable_leaf);
+
+		{
+			let (_, write_rx) = backend.await_next_write();
+			clock.inc_by(STAGNANT_TIMEOUT - 1);
+
+			write_rx.await.unwrap();
+		}
+
+		let tree = block_tree(&mut virtual_overseer).await;
+		assert_eq!(
+			tree[0],
+			ChainSelectionBlock {
+				hash: a1_hash,
+				number: 1,
+				parent_hash: finalized_hash,
+				weight: 1,
+				approval: ChainSelectionApproval::Stagnant,
+				stagnant_at: None,
+				explicitly_reverted: false,
+				earliest_unviable_ancestor: None,
+				viable: false,
+				viable_leaf: false,
+			},
+		);
+		assert_eq!(
+			tree[1],
+			ChainSelectionBlock {
+				hash: a2_hash,
+				number: 2,
+				parent_hash: a1_hash,
+				weight: 1,
+				approval: ChainSelectionApproval::Unapproved,
+				stagnant_at: Some(STAGNANT_TIMEOUT + 1),
+				explicitly_reverted: false,
+				earliest_unviable_ancestor: Some(a1_hash),
+				viable: false,
+				viable_leaf: false,
+			},
+		);
+
+		virtual_overseer
+	})
+}
+
+#[test]
+fn operator_unstagnate_restores_viability() {
+	test_harness(|backend, clock, mut virtual_overseer| async move {
+		let finalized_number = 0;
+		let finalized_hash = Hash::repeat_byte(0);
+
+		// F <- A1 <- A2
+
+		let (a2_hash, chain_a) =
+			construct_chain_on_base(vec![1, 2], finalized_number, finalized_hash, |h| {
+				salt_header(h, b"a");
+			});
+
+		let (_, a1_hash, _) = extract_info_from_chain(0, &chain_a);
+
+		import_chains_into_empty(
+			&mut virtual_overseer,
+			&backend,
+			finalized_number,
+			finalized_hash,
+			vec![chain_a.clone()],
+		)
+		.await;
+
+		approve_block(&mut virtual_overseer, &backend, a1_hash).await;
+
+		{
+			let (_, write_rx) = backend.await_next_write();
+			clock.inc_by(STAGNANT_TIMEOUT);
+
+			write_rx.await.unwrap();
+		}
+
+		assert_leaves(&backend, vec![a1_hash]);
+
+		let block =
+			override_block(&mut virtual_overseer, a2_hash, ChainSelectionOverride::Unstagnate)
+				.await
+				.unwrap();
+		assert_eq!(block.approval, ChainSelectionApproval::Unapproved);
+		assert_eq!(block.stagnant_at, Some(2 * STAGNANT_TIMEOUT));
+		assert!(block.viable_leaf);
+		assert_leaves(&backend, vec![a2_hash]);
+
+		// Overriding a block which is not stagnant has no effect.
+		let block =
+			override_block(&mut virtual_overseer, a1_hash, ChainSelectionOverride::Unstagnate)
+				.await
+				.unwrap();
+		assert_eq!(block.approval, ChainSelectionApproval::Approved);
+
+		assert_eq!(
+			override_log(&mut virtual_overseer).await,
+			vec![ChainSelectionOverrideRecord {
+				timestamp: STAGNANT_TIMEOUT,
+				block_hash: a2_hash,
+				block_number: 2,
+				kind: ChainSelectionOverride::Unstagnate,
+			}],
+		);
+
+		virtual_overseer
+	})
+}
+
+#[test]
+fn operator_revert_removes_viability() {
+	test_harness(|backend, clock, mut virtual_overseer| async move {
+		let finalized_number = 0;
+		let finalized_hash = Hash::repeat_byte(0);
+
+		// F <- A1 <- A2
+		// F <- B1
+
+		let (a2_hash, chain_a) =
+			construct_chain_on_base(vec![1, 2], finalized_number, finalized_hash, |h| {
+				salt_header(h, b"a");
+			});
+
+		let (b1_hash, chain_b) =
+			construct_chain_on_base(vec![1], finalized_number, finalized_hash, |h| {
+				salt_header(h, b"b");
+			});
+
+		let (_, a1_hash, _) = extract_info_from_chain(0, &chain_a);
+
+		import_chains_into_empty(
+			&mut virtual_overseer,
+			&backend,
+			finalized_number,
+			finalized_hash,
+			vec![chain_a.clone(), chain_b.clone()],
+		)
+		.await;
+
+		assert_leaves(&backend, vec![a2_hash, b1_hash]);
+
+		clock.inc_by(5);
+
+		let block = override_block(&mut virtual_overseer, a1_hash, ChainSelectionOverride::Revert)
+			.await
+			.unwrap();
+		assert!(block.explicitly_reverted);
+		assert!(!block.viable);
+		assert_leaves(&backend, vec![b1_hash]);
+
+		let tree = block_tree(&mut virtual_overseer).await;
+		let a2 = tree.iter().find(|b| b.hash == a2_hash).unwrap();
+		assert_eq!(a2.earliest_unviable_ancestor, Some(a1_hash));
+
+		// Reverting again or reverting an unknown block has no effect.
+		assert!(override_block(&mut virtual_overseer, a1_hash, ChainSelectionOverride::Revert)
+			.await
+			.is_some());
+		assert!(override_block(
+			&mut virtual_overseer,
+			Hash::repeat_byte(0xAA),
+			ChainSelectionOverride::Revert,
+		)
+		.await
+		.is_none());
+
+		assert_eq!(
+			override_log(&mut virtual_overseer).await,
+			vec![ChainSelectionOverrideRecord {
+				timestamp: 5,
+				block_hash: a1_hash,
+				block_number: 1,
+				kind: ChainSelectionOverride::Revert,
+			}],
+		);
+
+		virtual_overseer
+	})
+}
@@ -526,6 +526,80 @@ pub(super) fn approve_block(
 	Ok(())
 }
 
+/// Mark a block as reverted on behalf of the operator and update the viability
+/// of its descendants accordingly.
+///
+/// Returns `false` if the block is unknown or already reverted.
+pub(super) fn revert_block(
+	backend: &mut OverlayedBackend<impl Backend>,
+	block_hash: Hash,
+) -> Result<bool, Error> {
+	let mut entry = match backend.load_block_entry(&block_hash)? {
+		None => return Ok(false),
+		Some(entry) => entry,
+	};
+
+	if entry.viability.explicitly_reverted {
+		return Ok(false)
+	}
+
+	gum::info!(
+		target: LOG_TARGET,
+		?block_hash,
+		block_number = entry.block_number,
+		"Reverting block on behalf of the operator",
+	);
+
+	entry.viability.explicitly_reverted = true;
+	propagate_viability_update(backend, entry)?;
+
+	Ok(true)
+}
+
+/// Undo the stagnation of a block on behalf of the operator, scheduling another
+/// stagnation check at `stagnant_at`, and update the viability of itself and its
+/// descendants accordingly.
+///
+/// Returns `false` if the block is unknown or not stagnant.
+pub(super) fn unstagnate_block(
+	backend: &mut OverlayedBackend<impl Backend>,
+	block_hash: Hash,
+	stagnant_at: Timestamp,
+) -> Result<bool, Error> {
+	let mut entry = match backend.load_block_entry(&block_hash)? {
+		None => return Ok(false),
+		Some(entry) => entry,
+	};
+
+	if !entry.viability.approval.is_stagnant() {
+		return Ok(false)
+	}
+
+	gum::info!(
+		target: LOG_TARGET,
+		?block_hash,
+		block_number = entry.block_number,
+		"Undoing stagnation of block on behalf of the operator",
+	);
+
+	let was_viable = entry.viability.is_viable();
+	entry.viability.approval = Approval::Unapproved;
+	let is_viable = entry.viability.is_viable();
+
+	let mut stagnant_at_list = backend.load_stagnant_at(stagnant_at)?;
+	stagnant_at_list.push(block_hash);
+	backend.write_stagnant_at(stagnant_at, stagnant_at_list);
+
+	// The block may still be unviable because it is reverted.
+	if !was_viable && is_viable {
+		propagate_viability_update(backend, entry)?;
+	} else {
+		backend.write_block_entry(entry);
+	}
+
+	Ok(true)
+}
+
 /// Check whether any blocks up to the given timestamp are stagnant and update
 /// accordingly.
 ///
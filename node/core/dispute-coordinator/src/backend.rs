@@ -26,7 +26,7 @@ use polkadot_primitives::v2::{CandidateHash, SessionIndex};
 
 use std::collections::HashMap;
 
-use super::db::v1::{CandidateVotes, RecentDisputes};
+use super::db::v1::{CandidateVotes, QueuedParticipation, RecentDisputes, SpamRecords};
 use crate::error::FatalResult;
 
 #[derive(Debug)]
@@ -35,6 +35,9 @@ pub enum BackendWriteOp {
 	WriteRecentDisputes(RecentDisputes),
 	WriteCandidateVotes(SessionIndex, CandidateHash, CandidateVotes),
 	DeleteCandidateVotes(SessionIndex, CandidateHash),
+	WriteSpamRecords(SpamRecords),
+	WriteQueuedParticipation(QueuedParticipation),
+	DeleteQueuedParticipation(CandidateHash),
 }
 
 /// An abstraction over backend storage for the logic of this subsystem.
@@ -52,6 +55,12 @@ pub trait Backend {
 		candidate_hash: &CandidateHash,
 	) -> SubsystemResult<Option<CandidateVotes>>;
 
+	/// Load the spam records of all validators, if any.
+	fn load_spam_records(&self) -> SubsystemResult<Option<SpamRecords>>;
+
+	/// Load all queued participation requests.
+	fn load_queued_participations(&self) -> SubsystemResult<Vec<QueuedParticipation>>;
+
 	/// Atomically writes the list of operations, with later operations taking precedence over
 	/// prior.
 	fn write<I>(&mut self, ops: I) -> FatalResult<()>
@@ -73,6 +82,10 @@ pub struct OverlayedBackend<'a, B: 'a> {
 	recent_disputes: Option<RecentDisputes>,
 	// `None` means deleted, missing means query inner.
 	candidate_votes: HashMap<(SessionIndex, CandidateHash), Option<CandidateVotes>>,
+	// `None` means unchanged.
+	spam_records: Option<SpamRecords>,
+	// `None` means deleted, missing means query inner.
+	queued_participations: HashMap<CandidateHash, Option<QueuedParticipation>>,
 }
 
 impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
@@ -82,6 +95,8 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 			earliest_session: None,
 			recent_disputes: None,
 			candidate_votes: HashMap::new(),
+			spam_records: None,
+			queued_participations: HashMap::new(),
 		}
 	}
 
@@ -89,7 +104,9 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 	pub fn is_empty(&self) -> bool {
 		self.earliest_session.is_none() &&
 			self.recent_disputes.is_none() &&
-			self.candidate_votes.is_empty()
+			self.candidate_votes.is_empty() &&
+			self.spam_records.is_none() &&
+			self.queued_participations.is_empty()
 	}
 
 	/// Load the earliest session, if any.
@@ -123,6 +140,27 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 		self.inner.load_candidate_votes(session, candidate_hash)
 	}
 
+	/// Load the spam records of all validators, if any.
+	pub fn load_spam_records(&self) -> SubsystemResult<Option<SpamRecords>> {
+		if let Some(val) = &self.spam_records {
+			return Ok(Some(val.clone()))
+		}
+
+		self.inner.load_spam_records()
+	}
+
+	/// Load all queued participation requests.
+	pub fn load_queued_participations(&self) -> SubsystemResult<Vec<QueuedParticipation>> {
+		let mut queued = self
+			.inner
+			.load_queued_participations()?
+			.into_iter()
+			.filter(|q| !self.queued_participations.contains_key(&q.candidate_receipt.hash()))
+			.collect::<Vec<_>>();
+		queued.extend(self.queued_participations.values().flatten().cloned());
+		Ok(queued)
+	}
+
 	/// Prepare a write to the "earliest session" field of the DB.
 	///
 	/// Later calls to this function will override earlier ones.
@@ -149,6 +187,25 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 		self.candidate_votes.insert((session, candidate_hash), Some(votes));
 	}
 
+	/// Prepare a write of the spam records of all validators.
+	///
+	/// Later calls to this function will override earlier ones.
+	pub fn write_spam_records(&mut self, spam_records: SpamRecords) {
+		self.spam_records = Some(spam_records)
+	}
+
+	/// Prepare a write of a queued participation request.
+	///
+	/// Later calls to this function for the same candidate will override earlier ones.
+	pub fn write_queued_participation(&mut self, queued: QueuedParticipation) {
+		self.queued_participations.insert(queued.candidate_receipt.hash(), Some(queued));
+	}
+
+	/// Prepare the deletion of a queued participation request.
+	pub fn delete_queued_participation(&mut self, candidate_hash: CandidateHash) {
+		self.queued_participations.insert(candidate_hash, None);
+	}
+
 	/// Transform this backend into a set of write-ops to be written to the inner backend.
 	pub fn into_write_ops(self) -> impl Iterator<Item = BackendWriteOp> {
 		let earliest_session_ops = self
@@ -167,6 +224,20 @@ impl<'a, B: 'a + Backend> OverlayedBackend<'a, B> {
 					None => BackendWriteOp::DeleteCandidateVotes(session, candidate),
 				});
 
-		earliest_session_ops.chain(recent_dispute_ops).chain(candidate_vote_ops)
+		let spam_record_ops = self.spam_records.map(BackendWriteOp::WriteSpamRecords).into_iter();
+
+		let participation_ops =
+			self.queued_participations
+				.into_iter()
+				.map(|(candidate_hash, queued)| match queued {
+					Some(queued) => BackendWriteOp::WriteQueuedParticipation(queued),
+					None => BackendWriteOp::DeleteQueuedParticipation(candidate_hash),
+				});
+
+		earliest_session_ops
+			.chain(recent_dispute_ops)
+			.chain(candidate_vote_ops)
+			.chain(spam_record_ops)
+			.chain(participation_ops)
 	}
 }
@@ -17,13 +17,13 @@
 //! `V1` database for the dispute coordinator.
 
 use polkadot_node_subsystem::{SubsystemError, SubsystemResult};
-use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_node_subsystem_util::database::{DBTransaction, Database, DecodedEntry};
 use polkadot_primitives::v2::{
 	CandidateHash, CandidateReceipt, Hash, InvalidDisputeStatementKind, SessionIndex,
-	ValidDisputeStatementKind, ValidatorIndex, ValidatorSignature,
+	ValidDisputeStatementKind, ValidatorId, ValidatorIndex, ValidatorSignature,
 };
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use parity_scale_codec::{Decode, Encode};
 
@@ -31,13 +31,15 @@ use crate::{
 	backend::{Backend, BackendWriteOp, OverlayedBackend},
 	error::{FatalError, FatalResult},
 	metrics::Metrics,
-	status::DisputeStatus,
+	status::{DisputeStatus, Timestamp},
 	DISPUTE_WINDOW, LOG_TARGET,
 };
 
 const RECENT_DISPUTES_KEY: &[u8; 15] = b"recent-disputes";
 const EARLIEST_SESSION_KEY: &[u8; 16] = b"earliest-session";
 const CANDIDATE_VOTES_SUBKEY: &[u8; 15] = b"candidate-votes";
+const SPAM_RECORDS_KEY: &[u8; 12] = b"spam-records";
+const PARTICIPATION_SUBKEY: &[u8; 13] = b"participation";
 /// Until what session have votes been cleaned up already?
 const CLEANED_VOTES_WATERMARK_KEY: &[u8; 23] = b"cleaned-votes-watermark";
 
@@ -127,6 +129,16 @@ impl Backend for DbBackend {
 		load_candidate_votes(&*self.inner, &self.config, session, candidate_hash)
 	}
 
+	/// Load the spam records of all validators, if any.
+	fn load_spam_records(&self) -> SubsystemResult<Option<SpamRecords>> {
+		load_spam_records(&*self.inner, &self.config)
+	}
+
+	/// Load all queued participation requests.
+	fn load_queued_participations(&self) -> SubsystemResult<Vec<QueuedParticipation>> {
+		load_queued_participations(&*self.inner, &self.config)
+	}
+
 	/// Atomically writes the list of operations, with later operations taking precedence over
 	/// prior.
 	///
@@ -164,6 +176,16 @@ impl Backend for DbBackend {
 				BackendWriteOp::DeleteCandidateVotes(session, candidate_hash) => {
 					tx.delete(self.config.col_data, &candidate_votes_key(session, &candidate_hash));
 				},
+				BackendWriteOp::WriteSpamRecords(spam_records) => {
+					tx.put_vec(self.config.col_data, SPAM_RECORDS_KEY, spam_records.encode());
+				},
+				BackendWriteOp::WriteQueuedParticipation(queued) => {
+					let key = participation_key(&queued.candidate_receipt.hash());
+					tx.put_vec(self.config.col_data, &key, queued.encode());
+				},
+				BackendWriteOp::DeleteQueuedParticipation(candidate_hash) => {
+					tx.delete(self.config.col_data, &participation_key(&candidate_hash));
+				},
 			}
 		}
 
@@ -182,6 +204,14 @@ fn candidate_votes_key(session: SessionIndex, candidate_hash: &CandidateHash) ->
 	buf
 }
 
+fn participation_key(candidate_hash: &CandidateHash) -> [u8; 13 + 32] {
+	let mut buf = [0u8; 13 + 32];
+	buf[..13].copy_from_slice(PARTICIPATION_SUBKEY);
+	candidate_hash.using_encoded(|s| buf[13..].copy_from_slice(s));
+
+	buf
+}
+
 fn candidate_votes_session_prefix(session: SessionIndex) -> [u8; 15 + 4] {
 	let mut buf = [0u8; 15 + 4];
 	buf[..15].copy_from_slice(CANDIDATE_VOTES_SUBKEY);
@@ -236,6 +266,45 @@ impl From<polkadot_node_primitives::CandidateVotes> for CandidateVotes {
 /// The mapping for recent disputes; any which have not yet been pruned for being ancient.
 pub type RecentDisputes = std::collections::BTreeMap<(SessionIndex, CandidateHash), DisputeStatus>;
 
+/// Decaying history of the unconfirmed disputes raised by a validator.
+///
+/// Counts are fixed point numbers, see `spam_slots::SCORE_UNIT`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct SpamRecord {
+	/// Disputes which never got confirmed.
+	pub unconfirmed: u32,
+	/// Disputes which got confirmed and concluded in favour of the validator.
+	pub confirmed: u32,
+}
+
+/// Spam records of all validators which raised unconfirmed disputes recently.
+///
+/// Keyed by public key, as validator indices are only meaningful within a session.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SpamRecords {
+	/// The session the records were last decayed in.
+	pub session: SessionIndex,
+	/// The record per validator.
+	pub records: BTreeMap<ValidatorId, SpamRecord>,
+}
+
+/// A participation request, which was queued when last persisted.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct QueuedParticipation {
+	/// The candidate to participate in the dispute of.
+	pub candidate_receipt: CandidateReceipt,
+	/// The session the candidate appeared in.
+	pub session: SessionIndex,
+	/// The number of validators in the session.
+	pub n_validators: u32,
+	/// Whether the request was on the priority queue.
+	pub priority: bool,
+	/// How often the request was added to the best effort queue.
+	pub added_count: u32,
+	/// When the request was queued first.
+	pub queued_at: Timestamp,
+}
+
 /// Errors while accessing things from the DB.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -284,6 +353,28 @@ pub(crate) fn load_earliest_session(
 		.map_err(|e| SubsystemError::with_origin("dispute-coordinator", e))
 }
 
+/// Load the spam records of all validators, if any.
+pub(crate) fn load_spam_records(
+	db: &dyn Database,
+	config: &ColumnConfiguration,
+) -> SubsystemResult<Option<SpamRecords>> {
+	load_decode(db, config.col_data, SPAM_RECORDS_KEY)
+		.map_err(|e| SubsystemError::with_origin("dispute-coordinator", e))
+}
+
+/// Load all queued participation requests.
+pub(crate) fn load_queued_participations(
+	db: &dyn Database,
+	config: &ColumnConfiguration,
+) -> SubsystemResult<Vec<QueuedParticipation>> {
+	db.iter_with_prefix(config.col_data, PARTICIPATION_SUBKEY)
+		.map(|(_, value)| {
+			QueuedParticipation::decode(&mut &value[..])
+				.map_err(|e| SubsystemError::with_origin("dispute-coordinator", Error::from(e)))
+		})
+		.collect()
+}
+
 /// Load the recent disputes, if any.
 pub(crate) fn load_recent_disputes(
 	db: &dyn Database,
@@ -339,6 +430,49 @@ pub(crate) fn note_current_session(
 	Ok(())
 }
 
+/// Decode every entry stored in the dispute coordinator column.
+pub(crate) fn inspect_entries(
+	db: &dyn Database,
+	config: &ColumnConfiguration,
+) -> Result<Vec<DecodedEntry>> {
+	let mut entries = Vec::new();
+	for (key, value) in db.iter(config.col_data) {
+		let entry = if key.starts_with(CANDIDATE_VOTES_SUBKEY) &&
+			key.len() == CANDIDATE_VOTES_SUBKEY.len() + 4 + 32
+		{
+			let mut session = [0u8; 4];
+			session.copy_from_slice(&key[CANDIDATE_VOTES_SUBKEY.len()..][..4]);
+			let session = SessionIndex::from_be_bytes(session);
+			let candidate_hash =
+				CandidateHash::decode(&mut &key[CANDIDATE_VOTES_SUBKEY.len() + 4..])?;
+			let votes = CandidateVotes::decode(&mut &value[..])?;
+			DecodedEntry::new(key, "candidate_votes", (session, candidate_hash, votes))
+		} else if &key[..] == &RECENT_DISPUTES_KEY[..] {
+			let recent_disputes = RecentDisputes::decode(&mut &value[..])?;
+			DecodedEntry::new(key, "recent_disputes", recent_disputes)
+		} else if &key[..] == &EARLIEST_SESSION_KEY[..] {
+			let session = SessionIndex::decode(&mut &value[..])?;
+			DecodedEntry::new(key, "earliest_session", session)
+		} else if &key[..] == &CLEANED_VOTES_WATERMARK_KEY[..] {
+			let session = SessionIndex::decode(&mut &value[..])?;
+			DecodedEntry::new(key, "cleaned_votes_watermark", session)
+		} else if key.starts_with(PARTICIPATION_SUBKEY) &&
+			key.len() == PARTICIPATION_SUBKEY.len() + 32
+		{
+			let queued = QueuedParticipation::decode(&mut &value[..])?;
+			DecodedEntry::new(key, "queued_participation", queued)
+		} else if &key[..] == &SPAM_RECORDS_KEY[..] {
+			let spam_records = SpamRecords::decode(&mut &value[..])?;
+			DecodedEntry::new(key, "spam_records", spam_records)
+		} else {
+			DecodedEntry::new(key, "unknown", value)
+		};
+		entries.push(entry);
+	}
+
+	Ok(entries)
+}
+
 /// Until what session votes have been cleaned up already.
 ///
 /// That is the db has already been purged of votes for sessions older than the returned
@@ -663,4 +797,69 @@ mod tests {
 			.is_some());
 		assert!(overlay_db.load_candidate_votes(very_recent, &hash_d).unwrap().is_some());
 	}
+
+	#[test]
+	fn inspect_entries_decodes_votes_and_metadata() {
+		let mut backend = make_db();
+
+		let mut overlay_db = OverlayedBackend::new(&backend);
+		let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+
+		overlay_db.write_earliest_session(1);
+		overlay_db.write_candidate_votes(
+			1,
+			candidate_hash,
+			CandidateVotes {
+				candidate_receipt: dummy_candidate_receipt(dummy_hash()),
+				valid: Vec::new(),
+				invalid: Vec::new(),
+			},
+		);
+
+		let write_ops = overlay_db.into_write_ops();
+		backend.write(write_ops).unwrap();
+
+		let entries = inspect_entries(&*backend.inner, &backend.config).unwrap();
+		let kinds = entries.iter().map(|e| e.kind).collect::<Vec<_>>();
+
+		assert_eq!(kinds, vec!["candidate_votes", "cleaned_votes_watermark", "earliest_session"]);
+	}
+	#[test]
+	fn queued_participations_round_trip() {
+		let mut backend = make_db();
+
+		let queued = |para_id: u32| {
+			let mut candidate_receipt = dummy_candidate_receipt(dummy_hash());
+			candidate_receipt.descriptor.para_id = ParaId::from(para_id);
+			QueuedParticipation {
+				candidate_receipt,
+				session: 1,
+				n_validators: 10,
+				priority: para_id % 2 == 0,
+				added_count: 1,
+				queued_at: 100,
+			}
+		};
+		let a = queued(1);
+		let b = queued(2);
+
+		let mut overlay_db = OverlayedBackend::new(&backend);
+		overlay_db.write_queued_participation(a.clone());
+		overlay_db.write_queued_participation(b.clone());
+		let write_ops = overlay_db.into_write_ops();
+		backend.write(write_ops).unwrap();
+
+		let mut loaded = backend.load_queued_participations().unwrap();
+		loaded.sort_by_key(|q| q.candidate_receipt.descriptor.para_id);
+		assert_eq!(loaded, vec![a.clone(), b.clone()]);
+
+		// Deletions in the overlay are visible before they get written:
+		let mut overlay_db = OverlayedBackend::new(&backend);
+		overlay_db.delete_queued_participation(a.candidate_receipt.hash());
+		assert_eq!(overlay_db.load_queued_participations().unwrap(), vec![b.clone()]);
+
+		let write_ops = overlay_db.into_write_ops();
+		backend.write(write_ops).unwrap();
+		assert_eq!(backend.load_queued_participations().unwrap(), vec![b]);
+	}
 }
@@ -30,6 +30,7 @@ use polkadot_node_primitives::{
 	DISPUTE_WINDOW,
 };
 use polkadot_node_subsystem::{
+	jaeger,
 	messages::{
 		ApprovalVotingMessage, BlockDescription, DisputeCoordinatorMessage,
 		DisputeDistributionMessage, ImportStatementsResult,
@@ -55,7 +56,7 @@ use crate::{
 
 use super::{
 	backend::Backend,
-	db,
+	db::{self, v1::QueuedParticipation},
 	participation::{
 		self, Participation, ParticipationPriority, ParticipationRequest, ParticipationStatement,
 		WorkerMessageReceiver,
@@ -93,10 +94,11 @@ impl Initialized {
 		spam_slots: SpamSlots,
 		scraper: ChainScraper,
 	) -> Self {
-		let DisputeCoordinatorSubsystem { config: _, store: _, keystore, metrics } = subsystem;
+		let DisputeCoordinatorSubsystem { config, store: _, keystore, metrics } = subsystem;
 
 		let (participation_sender, participation_receiver) = mpsc::channel(1);
-		let participation = Participation::new(participation_sender);
+		let participation =
+			Participation::new(participation_sender, config.participation_queue, metrics.clone());
 		let highest_session = rolling_session_window.latest_session();
 
 		Self {
@@ -120,6 +122,7 @@ impl Initialized {
 		mut ctx: Context,
 		mut backend: B,
 		mut participations: Vec<(ParticipationPriority, ParticipationRequest)>,
+		mut queued_participations: Vec<QueuedParticipation>,
 		mut votes: Vec<ScrapedOnChainVotes>,
 		mut first_leaf: Option<ActivatedLeaf>,
 		clock: Box<dyn Clock>,
@@ -133,6 +136,7 @@ impl Initialized {
 					&mut ctx,
 					&mut backend,
 					&mut participations,
+					&mut queued_participations,
 					&mut votes,
 					&mut first_leaf,
 					&*clock,
@@ -156,6 +160,7 @@ impl Initialized {
 		ctx: &mut Context,
 		backend: &mut B,
 		participations: &mut Vec<(ParticipationPriority, ParticipationRequest)>,
+		queued_participations: &mut Vec<QueuedParticipation>,
 		on_chain_votes: &mut Vec<ScrapedOnChainVotes>,
 		first_leaf: &mut Option<ActivatedLeaf>,
 		clock: &dyn Clock,
@@ -163,8 +168,13 @@ impl Initialized {
 	where
 		B: Backend,
 	{
+		self.participation
+			.restore_queued(ctx, std::mem::take(queued_participations))
+			.await?;
 		for (priority, request) in participations.drain(..) {
-			self.participation.queue_participation(ctx, priority, request).await?;
+			self.participation
+				.queue_participation(ctx, priority, request, clock.now())
+				.await?;
 		}
 
 		{
@@ -181,19 +191,23 @@ impl Initialized {
 						);
 					});
 			}
+			if let Some(first_leaf) = first_leaf.take() {
+				// Also provide first leaf to participation for good measure.
+				self.participation
+					.process_active_leaves_update(
+						ctx,
+						&ActiveLeavesUpdate::start_work(first_leaf),
+						clock.now(),
+					)
+					.await?;
+			}
+			self.persist_participation_queue(&mut overlay_db);
 			if !overlay_db.is_empty() {
 				let ops = overlay_db.into_write_ops();
 				backend.write(ops)?;
 			}
 		}
 
-		if let Some(first_leaf) = first_leaf.take() {
-			// Also provide first leaf to participation for good measure.
-			self.participation
-				.process_active_leaves_update(ctx, &ActiveLeavesUpdate::start_work(first_leaf))
-				.await?;
-		}
-
 		loop {
 			gum::trace!(target: LOG_TARGET, "Waiting for message");
 			let mut overlay_db = OverlayedBackend::new(backend);
@@ -207,7 +221,7 @@ impl Initialized {
 							candidate_hash,
 							candidate_receipt,
 							outcome,
-						} = self.participation.get_participation_result(ctx, msg).await?;
+						} = self.participation.get_participation_result(ctx, msg, clock.now()).await?;
 						if let Some(valid) = outcome.validity() {
 							gum::trace!(
 								target: LOG_TARGET,
@@ -252,6 +266,7 @@ impl Initialized {
 					},
 				};
 
+			self.persist_participation_queue(&mut overlay_db);
 			if !overlay_db.is_empty() {
 				let ops = overlay_db.into_write_ops();
 				backend.write(ops)?;
@@ -271,7 +286,7 @@ impl Initialized {
 	) -> Result<()> {
 		let on_chain_votes =
 			self.scraper.process_active_leaves_update(ctx.sender(), &update).await?;
-		self.participation.process_active_leaves_update(ctx, &update).await?;
+		self.participation.process_active_leaves_update(ctx, &update, now).await?;
 
 		if let Some(new_leaf) = update.activated {
 			match self
@@ -300,7 +315,8 @@ impl Initialized {
 						self.highest_session = session;
 
 						db::v1::note_current_session(overlay_db, session)?;
-						self.spam_slots.prune_old(new_window_start);
+						self.spam_slots.prune_old(new_window_start, session);
+						note_spam_slots(&mut self.spam_slots, &self.metrics, overlay_db);
 					}
 				},
 				Ok(SessionWindowUpdate::Unchanged) => {},
@@ -680,11 +696,25 @@ impl Initialized {
 
 				let _ = tx.send(undisputed_chain);
 			},
+			DisputeCoordinatorMessage::SpamSlots(tx) => {
+				gum::trace!(target: LOG_TARGET, "DisputeCoordinatorMessage::SpamSlots");
+				let _ = tx.send(self.spam_slots.usage());
+			},
 		}
 
 		Ok(Box::new(|| Ok(())))
 	}
 
+	// Write changes to the participation queues to the database.
+	fn persist_participation_queue(&mut self, overlay_db: &mut OverlayedBackend<'_, impl Backend>) {
+		for (candidate_hash, queued) in self.participation.take_queue_changes() {
+			match queued {
+				Some(queued) => overlay_db.write_queued_participation(queued),
+				None => overlay_db.delete_queued_participation(candidate_hash),
+			}
+		}
+	}
+
 	// Helper function for checking subsystem errors in message processing.
 	fn ensure_available_session_info(&self) -> Result<()> {
 		if let Some(subsystem_error) = self.error.clone() {
@@ -841,6 +871,7 @@ impl Initialized {
 		if !potential_spam {
 			// Former spammers have not been spammers after all:
 			self.spam_slots.clear(&(session, candidate_hash));
+			note_spam_slots(&mut self.spam_slots, &self.metrics, overlay_db);
 
 		// Potential spam:
 		} else if !import_result.new_invalid_voters().is_empty() {
@@ -852,10 +883,18 @@ impl Initialized {
 				// need to increase spam slots on invalid votes. (If we did not, we would also
 				// increase spam slots for backing validators for example - as validators have to
 				// provide some opposing vote for dispute-distribution).
-				free_spam_slots_available |=
-					self.spam_slots.add_unconfirmed(session, candidate_hash, *index);
+				if let Some(validator_id) = env.validators().get(index.0 as usize) {
+					free_spam_slots_available |= self.spam_slots.add_unconfirmed(
+						session,
+						candidate_hash,
+						*index,
+						validator_id,
+					);
+				}
 			}
+			note_spam_slots(&mut self.spam_slots, &self.metrics, overlay_db);
 			if !free_spam_slots_available {
+				self.metrics.on_spam_rejected();
 				gum::debug!(
 					target: LOG_TARGET,
 					?candidate_hash,
@@ -895,6 +934,7 @@ impl Initialized {
 						session,
 						env.validators().len(),
 					),
+					now,
 				)
 				.await;
 			log_error(r)?;
@@ -995,6 +1035,8 @@ impl Initialized {
 		// Update metrics:
 		if import_result.is_freshly_disputed() {
 			self.metrics.on_open();
+			// Keep recording spans of the candidate, regardless of the sampling rate.
+			jaeger::note_disputed_candidate(candidate_hash);
 		}
 		self.metrics.on_valid_votes(import_result.imported_valid_votes());
 		self.metrics.on_invalid_votes(import_result.imported_invalid_votes());
@@ -1012,6 +1054,13 @@ impl Initialized {
 		);
 
 		self.metrics.on_approval_votes(import_result.imported_approval_votes());
+		if import_result.is_freshly_concluded() {
+			self.spam_slots.concluded(
+				&(session, candidate_hash),
+				import_result.is_freshly_concluded_invalid(),
+			);
+			note_spam_slots(&mut self.spam_slots, &self.metrics, overlay_db);
+		}
 		if import_result.is_freshly_concluded_valid() {
 			gum::info!(
 				target: LOG_TARGET,
@@ -1314,3 +1363,15 @@ fn determine_undisputed_chain(
 
 	Ok(last)
 }
+
+// Persist changed spam records and update the spam slot metrics.
+fn note_spam_slots(
+	spam_slots: &mut SpamSlots,
+	metrics: &Metrics,
+	overlay_db: &mut OverlayedBackend<'_, impl Backend>,
+) {
+	if let Some(spam_records) = spam_slots.take_changed_records() {
+		overlay_db.write_spam_records(spam_records);
+	}
+	metrics.on_spam_slots(spam_slots.occupied(), spam_slots.restricted_validators());
+}
@@ -24,7 +24,7 @@
 //! validation results as well as a sink for votes received by other subsystems. When importing a dispute vote from
 //! another node, this will trigger dispute participation to recover and validate the block.
 
-use std::sync::Arc;
+use std::{ops::RangeInclusive, sync::Arc};
 
 use futures::FutureExt;
 
@@ -35,9 +35,12 @@ use polkadot_node_subsystem::{
 	overseer, ActivatedLeaf, FromOrchestra, OverseerSignal, SpawnedSubsystem, SubsystemError,
 };
 use polkadot_node_subsystem_util::{
-	database::Database, rolling_session_window::RollingSessionWindow,
+	database::{Database, DecodedEntry},
+	rolling_session_window::{DatabaseParams, RollingSessionWindow},
+};
+use polkadot_primitives::v2::{
+	CandidateHash, ScrapedOnChainVotes, SessionIndex, ValidatorIndex, ValidatorPair,
 };
-use polkadot_primitives::v2::{ScrapedOnChainVotes, ValidatorIndex, ValidatorPair};
 
 use crate::{
 	error::{FatalResult, JfyiError, Result},
@@ -45,7 +48,7 @@ use crate::{
 	status::{get_active_with_status, SystemClock},
 };
 use backend::{Backend, OverlayedBackend};
-use db::v1::DbBackend;
+use db::v1::{DbBackend, QueuedParticipation};
 use fatality::Split;
 
 use self::{
@@ -88,6 +91,7 @@ mod spam_slots;
 /// first and more importantly it will order requests in a way so disputes will get resolved, even
 /// if there are lots of them.
 pub(crate) mod participation;
+pub use participation::QueueConfig;
 
 /// Pure processing of vote imports.
 pub(crate) mod import;
@@ -97,6 +101,7 @@ mod metrics;
 
 /// Status tracking of disputes (`DisputeStatus`).
 mod status;
+pub use status::{DisputeStatus, Timestamp};
 
 use crate::status::Clock;
 
@@ -113,11 +118,28 @@ pub struct DisputeCoordinatorSubsystem {
 	metrics: Metrics,
 }
 
+/// A dispute as stored in the database, together with all votes on the candidate.
+#[derive(Debug, Clone)]
+pub struct StoredDispute {
+	/// The session the candidate was disputed in.
+	pub session: SessionIndex,
+	/// The disputed candidate.
+	pub candidate_hash: CandidateHash,
+	/// The status of the dispute.
+	pub status: DisputeStatus,
+	/// All votes known for the candidate.
+	pub votes: CandidateVotes,
+}
+
 /// Configuration for the dispute coordinator subsystem.
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
 	/// The data column in the store to use for dispute data.
 	pub col_data: u32,
+	/// The column in the store to persist the session window in.
+	pub col_session_data: u32,
+	/// Sizes of the participation queues.
+	pub participation_queue: QueueConfig,
 }
 
 impl Config {
@@ -157,6 +179,42 @@ impl DisputeCoordinatorSubsystem {
 		Self { store, config, keystore, metrics }
 	}
 
+	/// Decode all votes and dispute metadata stored in the database.
+	pub fn inspect_db(&self) -> std::result::Result<Vec<DecodedEntry>, SubsystemError> {
+		db::v1::inspect_entries(&*self.store, &self.config.column_config())
+			.map_err(|e| SubsystemError::with_origin("dispute-coordinator", e))
+	}
+
+	/// Load all disputes of the given sessions still stored in the database, ordered by session.
+	pub fn load_disputes(
+		&self,
+		sessions: RangeInclusive<SessionIndex>,
+	) -> std::result::Result<Vec<StoredDispute>, SubsystemError> {
+		let config = self.config.column_config();
+		let recent_disputes =
+			db::v1::load_recent_disputes(&*self.store, &config)?.unwrap_or_default();
+
+		let mut disputes = Vec::new();
+		for ((session, candidate_hash), status) in recent_disputes {
+			if !sessions.contains(&session) {
+				continue
+			}
+			// Votes are pruned independently of the dispute metadata.
+			if let Some(votes) =
+				db::v1::load_candidate_votes(&*self.store, &config, session, &candidate_hash)?
+			{
+				disputes.push(StoredDispute {
+					session,
+					candidate_hash,
+					status,
+					votes: votes.into(),
+				});
+			}
+		}
+
+		Ok(disputes)
+	}
+
 	/// Initialize and afterwards run `Initialized::run`.
 	async fn run<B, Context>(
 		self,
@@ -169,14 +227,23 @@ impl DisputeCoordinatorSubsystem {
 	{
 		let res = self.initialize(&mut ctx, backend, &*clock).await?;
 
-		let (participations, votes, first_leaf, initialized, backend) = match res {
-			// Concluded:
-			None => return Ok(()),
-			Some(r) => r,
-		};
+		let (participations, queued_participations, votes, first_leaf, initialized, backend) =
+			match res {
+				// Concluded:
+				None => return Ok(()),
+				Some(r) => r,
+			};
 
 		initialized
-			.run(ctx, backend, participations, votes, Some(first_leaf), clock)
+			.run(
+				ctx,
+				backend,
+				participations,
+				queued_participations,
+				votes,
+				Some(first_leaf),
+				clock,
+			)
 			.await
 	}
 
@@ -189,6 +256,7 @@ impl DisputeCoordinatorSubsystem {
 	) -> FatalResult<
 		Option<(
 			Vec<(ParticipationPriority, ParticipationRequest)>,
+			Vec<QueuedParticipation>,
 			Vec<ScrapedOnChainVotes>,
 			ActivatedLeaf,
 			Initialized,
@@ -199,35 +267,39 @@ impl DisputeCoordinatorSubsystem {
 		B: Backend + 'static,
 	{
 		loop {
-			let (first_leaf, rolling_session_window) = match get_rolling_session_window(ctx).await {
-				Ok(Some(update)) => update,
-				Ok(None) => {
-					gum::info!(target: LOG_TARGET, "received `Conclude` signal, exiting");
-					return Ok(None)
-				},
-				Err(e) => {
-					e.split()?.log();
-					continue
-				},
-			};
+			let db_params =
+				DatabaseParams { db: self.store.clone(), db_column: self.config.col_session_data };
+			let (first_leaf, rolling_session_window) =
+				match get_rolling_session_window(ctx, db_params).await {
+					Ok(Some(update)) => update,
+					Ok(None) => {
+						gum::info!(target: LOG_TARGET, "received `Conclude` signal, exiting");
+						return Ok(None)
+					},
+					Err(e) => {
+						e.split()?.log();
+						continue
+					},
+				};
 
 			let mut overlay_db = OverlayedBackend::new(&mut backend);
-			let (participations, votes, spam_slots, ordering_provider) = match self
-				.handle_startup(
-					ctx,
-					first_leaf.clone(),
-					&rolling_session_window,
-					&mut overlay_db,
-					clock,
-				)
-				.await
-			{
-				Ok(v) => v,
-				Err(e) => {
-					e.split()?.log();
-					continue
-				},
-			};
+			let (participations, queued_participations, votes, spam_slots, ordering_provider) =
+				match self
+					.handle_startup(
+						ctx,
+						first_leaf.clone(),
+						&rolling_session_window,
+						&mut overlay_db,
+						clock,
+					)
+					.await
+				{
+					Ok(v) => v,
+					Err(e) => {
+						e.split()?.log();
+						continue
+					},
+				};
 			if !overlay_db.is_empty() {
 				let ops = overlay_db.into_write_ops();
 				backend.write(ops)?;
@@ -235,6 +307,7 @@ impl DisputeCoordinatorSubsystem {
 
 			return Ok(Some((
 				participations,
+				queued_participations,
 				votes,
 				first_leaf,
 				Initialized::new(self, rolling_session_window, spam_slots, ordering_provider),
@@ -247,6 +320,7 @@ impl DisputeCoordinatorSubsystem {
 	//
 	// - Prune any old disputes.
 	// - Find disputes we need to participate in.
+	// - Restore the persisted participation queue, dropping requests no longer needed.
 	// - Initialize spam slots & OrderingProvider.
 	async fn handle_startup<Context>(
 		&self,
@@ -257,6 +331,7 @@ impl DisputeCoordinatorSubsystem {
 		clock: &dyn Clock,
 	) -> Result<(
 		Vec<(ParticipationPriority, ParticipationRequest)>,
+		Vec<QueuedParticipation>,
 		Vec<ScrapedOnChainVotes>,
 		SpamSlots,
 		ChainScraper,
@@ -344,12 +419,35 @@ impl DisputeCoordinatorSubsystem {
 			}
 		}
 
-		Ok((
-			participation_requests,
-			votes,
-			SpamSlots::recover_from_state(unconfirmed_disputes),
-			scraper,
-		))
+		// Requests still needed are restored, keeping their position. The others are queued fresh.
+		let mut queued_participations = Vec::new();
+		for mut queued in overlay_db.load_queued_participations()? {
+			let candidate_hash = queued.candidate_receipt.hash();
+			match participation_requests
+				.iter()
+				.find(|(_, req)| req.candidate_hash() == &candidate_hash)
+			{
+				Some((priority, _)) => {
+					// Candidates might have been included since they got queued.
+					queued.priority |= priority.is_priority();
+					queued_participations.push(queued);
+				},
+				None => overlay_db.delete_queued_participation(candidate_hash),
+			}
+		}
+		participation_requests.retain(|(_, req)| {
+			!queued_participations
+				.iter()
+				.any(|queued| &queued.candidate_receipt.hash() == req.candidate_hash())
+		});
+
+		let spam_records = overlay_db.load_spam_records()?.unwrap_or_default();
+		let spam_slots =
+			SpamSlots::recover_from_state(unconfirmed_disputes, spam_records, |session| {
+				rolling_session_window.session_info(session).map(|info| &info.validators[..])
+			});
+
+		Ok((participation_requests, queued_participations, votes, spam_slots, scraper))
 	}
 }
 
@@ -357,12 +455,13 @@ impl DisputeCoordinatorSubsystem {
 #[overseer::contextbounds(DisputeCoordinator, prefix = self::overseer)]
 async fn get_rolling_session_window<Context>(
 	ctx: &mut Context,
+	db_params: DatabaseParams,
 ) -> Result<Option<(ActivatedLeaf, RollingSessionWindow)>> {
 	if let Some(leaf) = { wait_for_first_leaf(ctx) }.await? {
 		let sender = ctx.sender().clone();
 		Ok(Some((
 			leaf.clone(),
-			RollingSessionWindow::new(sender, DISPUTE_WINDOW, leaf.hash)
+			RollingSessionWindow::new(sender, DISPUTE_WINDOW, leaf.hash, db_params)
 				.await
 				.map_err(JfyiError::RollingSessionWindow)?,
 		)))
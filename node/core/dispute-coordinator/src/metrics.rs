@@ -30,6 +30,18 @@ struct MetricsInner {
 	queued_participations: prometheus::CounterVec<prometheus::U64>,
 	/// How long vote cleanup batches take.
 	vote_cleanup_time: prometheus::Histogram,
+	/// Occupied spam slots across all validators.
+	spam_slots_occupied: prometheus::Gauge<prometheus::U64>,
+	/// Validators with fewer spam slots because of unconfirmed disputes.
+	spam_restricted_validators: prometheus::Gauge<prometheus::U64>,
+	/// Imports rejected because of full spam slots.
+	spam_rejected: prometheus::Counter<prometheus::U64>,
+	/// Time participation requests spent queued before being dequeued.
+	participation_queue_age: prometheus::HistogramVec,
+	/// Participation requests dropped, by reason.
+	participation_drops: prometheus::CounterVec<prometheus::U64>,
+	/// Current size of the participation queues.
+	participation_queue_size: prometheus::GaugeVec<prometheus::U64>,
 }
 
 /// Candidate validation metrics.
@@ -85,11 +97,60 @@ impl Metrics {
 		}
 	}
 
+	pub(crate) fn on_spam_slots(&self, occupied: u64, restricted_validators: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.spam_slots_occupied.set(occupied);
+			metrics.spam_restricted_validators.set(restricted_validators as _);
+		}
+	}
+
+	pub(crate) fn on_spam_rejected(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.spam_rejected.inc();
+		}
+	}
+
+	pub(crate) fn on_participation_dequeued(&self, priority: bool, age_secs: u64) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.participation_queue_age
+				.with_label_values(&[queue_label(priority)])
+				.observe(age_secs as f64);
+		}
+	}
+
+	pub(crate) fn on_participation_dropped(&self, reason: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.participation_drops.with_label_values(&[reason]).inc();
+		}
+	}
+
+	pub(crate) fn on_participation_queue_sizes(&self, priority: usize, best_effort: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.participation_queue_size
+				.with_label_values(&[queue_label(true)])
+				.set(priority as _);
+			metrics
+				.participation_queue_size
+				.with_label_values(&[queue_label(false)])
+				.set(best_effort as _);
+		}
+	}
+
 	pub(crate) fn time_vote_cleanup(&self) -> Option<prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.vote_cleanup_time.start_timer())
 	}
 }
 
+fn queue_label(priority: bool) -> &'static str {
+	if priority {
+		"priority"
+	} else {
+		"best-effort"
+	}
+}
+
 impl metrics::Metrics for Metrics {
 	fn try_register(registry: &prometheus::Registry) -> Result<Self, prometheus::PrometheusError> {
 		let metrics = MetricsInner {
@@ -147,6 +208,60 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			spam_slots_occupied: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_dispute_spam_slots_occupied",
+					"Number of unconfirmed disputes currently occupying spam slots.",
+				)?,
+				registry,
+			)?,
+			spam_restricted_validators: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_dispute_spam_restricted_validators",
+					"Number of validators with fewer spam slots because their disputes did not get confirmed.",
+				)?,
+				registry,
+			)?,
+			spam_rejected: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_dispute_spam_rejected_total",
+					"Number of imports rejected because all invalid voters exhausted their spam slots.",
+				)?,
+				registry,
+			)?,
+			participation_queue_age: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_dispute_participation_queue_age",
+						"Seconds a participation request spent queued, grouped by queue.",
+					)
+					.buckets(
+						[1.0, 6.0, 30.0, 60.0, 300.0, 600.0, 1800.0, 3600.0, 7200.0].into(),
+					),
+					&["queue"],
+				)?,
+				registry,
+			)?,
+			participation_drops: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_dispute_participation_drops_total",
+						"Participation requests dropped because a queue was full, grouped by reason.",
+					),
+					&["reason"],
+				)?,
+				registry,
+			)?,
+			participation_queue_size: prometheus::register(
+				prometheus::GaugeVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_dispute_participation_queue_size",
+						"Number of participation requests currently queued, grouped by queue.",
+					),
+					&["queue"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 #[cfg(test)]
 use std::time::Duration;
 
@@ -33,7 +33,7 @@ use polkadot_node_subsystem::{
 use polkadot_node_subsystem_util::runtime::get_validation_code_by_hash;
 use polkadot_primitives::v2::{BlockNumber, CandidateHash, CandidateReceipt, Hash, SessionIndex};
 
-use crate::LOG_TARGET;
+use crate::{db::v1::QueuedParticipation, metrics::Metrics, status::Timestamp, LOG_TARGET};
 
 use crate::error::{FatalError, FatalResult, Result};
 
@@ -44,7 +44,7 @@ pub use tests::{participation_full_happy_path, participation_missing_availabilit
 
 mod queues;
 use queues::Queues;
-pub use queues::{ParticipationPriority, ParticipationRequest, QueueError};
+pub use queues::{ParticipationPriority, ParticipationRequest, QueueConfig, QueueError};
 
 /// How many participation processes do we want to run in parallel the most.
 ///
@@ -130,10 +130,10 @@ impl Participation {
 	/// The passed in sender will be used by background workers to communicate back their results.
 	/// The calling context should make sure to call `Participation::on_worker_message()` for the
 	/// received messages.
-	pub fn new(sender: WorkerMessageSender) -> Self {
+	pub fn new(sender: WorkerMessageSender, config: QueueConfig, metrics: Metrics) -> Self {
 		Self {
 			running_participations: HashSet::new(),
-			queue: Queues::new(),
+			queue: Queues::new(config, metrics),
 			worker_sender: sender,
 			recent_block: None,
 		}
@@ -150,6 +150,7 @@ impl Participation {
 		ctx: &mut Context,
 		priority: ParticipationPriority,
 		req: ParticipationRequest,
+		now: Timestamp,
 	) -> Result<()> {
 		// Participation already running - we can ignore that request:
 		if self.running_participations.contains(req.candidate_hash()) {
@@ -163,7 +164,36 @@ impl Participation {
 			}
 		}
 		// Out of capacity/no recent block yet - queue:
-		self.queue.queue(ctx.sender(), priority, req).await
+		self.queue.queue(ctx.sender(), priority, req, now).await
+	}
+
+	/// Restore participation requests persisted before a restart.
+	///
+	/// They are put back on their queues, keeping how long they have been queued already.
+	pub async fn restore_queued<Context>(
+		&mut self,
+		ctx: &mut Context,
+		queued: Vec<QueuedParticipation>,
+	) -> Result<()> {
+		for queued in queued {
+			let candidate_hash = queued.candidate_receipt.hash();
+			if let Err(err) = self.queue.restore(ctx.sender(), queued).await {
+				gum::debug!(
+					target: LOG_TARGET,
+					?candidate_hash,
+					?err,
+					"Could not restore queued participation"
+				);
+			}
+		}
+		Ok(())
+	}
+
+	/// Changes to the queues since the last call, to be persisted.
+	///
+	/// `None` means the request is no longer queued.
+	pub fn take_queue_changes(&mut self) -> HashMap<CandidateHash, Option<QueuedParticipation>> {
+		self.queue.take_changes()
 	}
 
 	/// Message from a worker task was received - get the outcome.
@@ -179,11 +209,12 @@ impl Participation {
 		&mut self,
 		ctx: &mut Context,
 		msg: WorkerMessage,
+		now: Timestamp,
 	) -> FatalResult<ParticipationStatement> {
 		let WorkerMessage(statement) = msg;
 		self.running_participations.remove(&statement.candidate_hash);
 		let recent_block = self.recent_block.expect("We never ever reset recent_block to `None` and we already received a result, so it must have been set before. qed.");
-		self.dequeue_until_capacity(ctx, recent_block.1).await?;
+		self.dequeue_until_capacity(ctx, recent_block.1, now).await?;
 		Ok(statement)
 	}
 
@@ -195,13 +226,14 @@ impl Participation {
 		&mut self,
 		ctx: &mut Context,
 		update: &ActiveLeavesUpdate,
+		now: Timestamp,
 	) -> FatalResult<()> {
 		if let Some(activated) = &update.activated {
 			match self.recent_block {
 				None => {
 					self.recent_block = Some((activated.number, activated.hash));
 					// Work got potentially unblocked:
-					self.dequeue_until_capacity(ctx, activated.hash).await?;
+					self.dequeue_until_capacity(ctx, activated.hash, now).await?;
 				},
 				Some((number, _)) if activated.number > number => {
 					self.recent_block = Some((activated.number, activated.hash));
@@ -218,9 +250,10 @@ impl Participation {
 		&mut self,
 		ctx: &mut Context,
 		recent_head: Hash,
+		now: Timestamp,
 	) -> FatalResult<()> {
 		while self.running_participations.len() < MAX_PARALLEL_PARTICIPATIONS {
-			if let Some(req) = self.queue.dequeue() {
+			if let Some(req) = self.queue.dequeue(now) {
 				self.fork_participation(ctx, req, recent_head)?;
 			} else {
 				break
@@ -21,10 +21,15 @@ use std::{
 
 use futures::channel::oneshot;
 use polkadot_node_subsystem::{messages::ChainApiMessage, overseer};
-use polkadot_primitives::v2::{BlockNumber, CandidateHash, CandidateReceipt, Hash, SessionIndex};
+use polkadot_primitives::v2::{
+	BlockNumber, CandidateHash, CandidateReceipt, Hash, Id as ParaId, SessionIndex,
+};
 
 use crate::{
+	db::v1::QueuedParticipation,
 	error::{FatalError, FatalResult, Result},
+	metrics::Metrics,
+	status::Timestamp,
 	LOG_TARGET,
 };
 
@@ -37,6 +42,12 @@ const BEST_EFFORT_QUEUE_SIZE: usize = 100;
 #[cfg(test)]
 const BEST_EFFORT_QUEUE_SIZE: usize = 3;
 
+/// How many potential garbage disputes of a single para we want to queue.
+#[cfg(not(test))]
+const BEST_EFFORT_QUEUE_SIZE_PER_PARA: usize = 10;
+#[cfg(test)]
+const BEST_EFFORT_QUEUE_SIZE_PER_PARA: usize = BEST_EFFORT_QUEUE_SIZE;
+
 /// How many priority disputes can be queued.
 ///
 /// Once the queue exceeds that size, we will start to drop the newest participation requests in
@@ -50,26 +61,78 @@ const PRIORITY_QUEUE_SIZE: usize = 20_000;
 #[cfg(test)]
 const PRIORITY_QUEUE_SIZE: usize = 2;
 
+/// How many priority disputes of a single para can be queued.
+///
+/// Keeps a single para from crowding out the disputes of all others.
+#[cfg(not(test))]
+const PRIORITY_QUEUE_SIZE_PER_PARA: usize = 2_000;
+#[cfg(test)]
+const PRIORITY_QUEUE_SIZE_PER_PARA: usize = PRIORITY_QUEUE_SIZE;
+
 /// Type for counting how often a candidate was added to the best effort queue.
 type BestEffortCount = u32;
 
+/// Sizes of the participation queues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueConfig {
+	/// How many priority disputes can be queued.
+	pub priority_size: usize,
+	/// How many priority disputes of a single para can be queued.
+	pub priority_size_per_para: usize,
+	/// How many best effort disputes can be queued.
+	pub best_effort_size: usize,
+	/// How many best effort disputes of a single para can be queued.
+	pub best_effort_size_per_para: usize,
+}
+
+impl Default for QueueConfig {
+	fn default() -> Self {
+		Self {
+			priority_size: PRIORITY_QUEUE_SIZE,
+			priority_size_per_para: PRIORITY_QUEUE_SIZE_PER_PARA,
+			best_effort_size: BEST_EFFORT_QUEUE_SIZE,
+			best_effort_size_per_para: BEST_EFFORT_QUEUE_SIZE_PER_PARA,
+		}
+	}
+}
+
 /// Queues for dispute participation.
 pub struct Queues {
 	/// Set of best effort participation requests.
 	///
-	/// Note that as size is limited to `BEST_EFFORT_QUEUE_SIZE` we simply do a linear search for
+	/// Note that as size is limited to `best_effort_size` we simply do a linear search for
 	/// the entry with the highest `added_count` to determine what dispute to participate next in.
 	///
 	/// This mechanism leads to an amplifying effect - the more validators already participated,
 	/// the more likely it becomes that more validators will participate soon, which should lead to
 	/// a quick resolution of disputes, even in the best effort queue.
+	///
+	/// Ties are broken in favour of the para served least recently.
 	best_effort: HashMap<CandidateHash, BestEffortEntry>,
 
 	/// Priority queue.
 	///
 	/// In the priority queue, we have a strict ordering of candidates and participation will
 	/// happen in that order.
-	priority: BTreeMap<CandidateComparator, ParticipationRequest>,
+	priority: BTreeMap<CandidateComparator, PriorityEntry>,
+
+	/// Comparators of all requests in the priority queue.
+	priority_index: HashMap<CandidateHash, CandidateComparator>,
+
+	/// Number of queued requests per para, priority and best effort.
+	per_para: HashMap<ParaId, (usize, usize)>,
+
+	/// When a para was served last from the best effort queue, in dequeued requests.
+	last_served: HashMap<ParaId, u64>,
+
+	/// Number of requests dequeued from the best effort queue.
+	served: u64,
+
+	/// Changes to persist, `None` means removed from the queues.
+	changes: HashMap<CandidateHash, Option<QueuedParticipation>>,
+
+	config: QueueConfig,
+	metrics: Metrics,
 }
 
 /// A dispute participation request that can be queued.
@@ -118,6 +181,8 @@ pub enum QueueError {
 	BestEffortFull,
 	#[error("Request could not be queued, because priority queue was already full.")]
 	PriorityFull,
+	#[error("Request could not be queued, because para {0} already used its share of the queue.")]
+	ParaFull(ParaId),
 }
 
 impl ParticipationRequest {
@@ -146,12 +211,25 @@ impl ParticipationRequest {
 		let Self { candidate_hash, candidate_receipt, .. } = self;
 		(candidate_hash, candidate_receipt)
 	}
+	fn para_id(&self) -> ParaId {
+		self.candidate_receipt.descriptor.para_id
+	}
 }
 
 impl Queues {
 	/// Create new `Queues`.
-	pub fn new() -> Self {
-		Self { best_effort: HashMap::new(), priority: BTreeMap::new() }
+	pub fn new(config: QueueConfig, metrics: Metrics) -> Self {
+		Self {
+			best_effort: HashMap::new(),
+			priority: BTreeMap::new(),
+			priority_index: HashMap::new(),
+			per_para: HashMap::new(),
+			last_served: HashMap::new(),
+			served: 0,
+			changes: HashMap::new(),
+			config,
+			metrics,
+		}
 	}
 
 	/// Will put message in queue, either priority or best effort depending on priority.
@@ -166,13 +244,37 @@ impl Queues {
 		sender: &mut impl overseer::DisputeCoordinatorSenderTrait,
 		priority: ParticipationPriority,
 		req: ParticipationRequest,
+		now: Timestamp,
 	) -> Result<()> {
 		let comparator = match priority {
 			ParticipationPriority::BestEffort => None,
 			ParticipationPriority::Priority =>
 				CandidateComparator::new(sender, &req.candidate_receipt).await?,
 		};
-		self.queue_with_comparator(comparator, req)?;
+		self.queue_with_comparator(comparator, req, now)?;
+		Ok(())
+	}
+
+	/// Restore a request persisted before a restart.
+	///
+	/// Keeps the time it got queued at and how often it was added. Priority requests fall back to
+	/// best effort, if their relay parent is no longer known.
+	pub async fn restore(
+		&mut self,
+		sender: &mut impl overseer::DisputeCoordinatorSenderTrait,
+		stored: QueuedParticipation,
+	) -> Result<()> {
+		let req = ParticipationRequest::new(
+			stored.candidate_receipt,
+			stored.session,
+			stored.n_validators as usize,
+		);
+		let comparator = if stored.priority {
+			CandidateComparator::new(sender, &req.candidate_receipt).await?
+		} else {
+			None
+		};
+		self.insert(comparator, req, stored.added_count, stored.queued_at)?;
 		Ok(())
 	}
 
@@ -180,79 +282,197 @@ impl Queues {
 	///
 	/// if any.  Priority queue is always considered first, then the best effort queue based on
 	/// `added_count`.
-	pub fn dequeue(&mut self) -> Option<ParticipationRequest> {
-		if let Some(req) = self.pop_priority() {
-			// In case a candidate became best effort over time, we might have it also queued in
-			// the best effort queue - get rid of any such entry:
-			self.best_effort.remove(req.candidate_hash());
-			return Some(req)
+	pub fn dequeue(&mut self, now: Timestamp) -> Option<ParticipationRequest> {
+		if let Some(entry) = self.pop_priority() {
+			self.metrics
+				.on_participation_dequeued(true, now.saturating_sub(entry.queued_at));
+			return Some(entry.req)
 		}
-		self.pop_best_effort()
+		let entry = self.pop_best_effort()?;
+		self.metrics
+			.on_participation_dequeued(false, now.saturating_sub(entry.queued_at));
+		Some(entry.req)
+	}
+
+	/// Changes to the queues since the last call, to be persisted.
+	///
+	/// `None` means the request is no longer queued.
+	pub fn take_changes(&mut self) -> HashMap<CandidateHash, Option<QueuedParticipation>> {
+		std::mem::take(&mut self.changes)
 	}
 
 	fn queue_with_comparator(
 		&mut self,
 		comparator: Option<CandidateComparator>,
 		req: ParticipationRequest,
+		now: Timestamp,
 	) -> std::result::Result<(), QueueError> {
-		if let Some(comparator) = comparator {
-			if self.priority.len() >= PRIORITY_QUEUE_SIZE {
-				return Err(QueueError::PriorityFull)
+		// Keep the original queuing time, if the request was queued already:
+		let queued_at = self
+			.best_effort
+			.get(&req.candidate_hash)
+			.map(|e| e.queued_at)
+			.or_else(|| {
+				let comparator = self.priority_index.get(&req.candidate_hash)?;
+				self.priority.get(comparator).map(|e| e.queued_at)
+			})
+			.unwrap_or(now);
+		self.insert(comparator, req, 1, queued_at)
+	}
+
+	fn insert(
+		&mut self,
+		comparator: Option<CandidateComparator>,
+		req: ParticipationRequest,
+		added_count: BestEffortCount,
+		queued_at: Timestamp,
+	) -> std::result::Result<(), QueueError> {
+		let para_id = req.para_id();
+		let (para_priority, para_best_effort) =
+			self.per_para.get(&para_id).copied().unwrap_or_default();
+		// Requests are only ever on one queue, priority taking precedence:
+		if self.priority_index.contains_key(&req.candidate_hash) {
+			return Ok(())
+		}
+		let result = if let Some(comparator) = comparator {
+			if self.priority.len() >= self.config.priority_size {
+				Err(QueueError::PriorityFull)
+			} else if para_priority >= self.config.priority_size_per_para {
+				Err(QueueError::ParaFull(para_id))
+			} else {
+				// Remove any best effort entry:
+				self.remove_best_effort(&req.candidate_hash);
+				self.note_queued(&req, true, 0, queued_at);
+				self.per_para.entry(para_id).or_default().0 += 1;
+				self.priority_index.insert(req.candidate_hash, comparator);
+				self.priority.insert(comparator, PriorityEntry { req, queued_at });
+				Ok(())
 			}
-			// Remove any best effort entry:
-			self.best_effort.remove(&req.candidate_hash);
-			self.priority.insert(comparator, req);
+		} else if let Some(entry) = self.best_effort.get_mut(&req.candidate_hash) {
+			entry.added_count += added_count;
+			let (req, added_count, queued_at) =
+				(entry.req.clone(), entry.added_count, entry.queued_at);
+			self.note_queued(&req, false, added_count, queued_at);
+			Ok(())
+		} else if self.best_effort.len() >= self.config.best_effort_size {
+			Err(QueueError::BestEffortFull)
+		} else if para_best_effort >= self.config.best_effort_size_per_para {
+			Err(QueueError::ParaFull(para_id))
 		} else {
-			if self.best_effort.len() >= BEST_EFFORT_QUEUE_SIZE {
-				return Err(QueueError::BestEffortFull)
-			}
-			// Note: The request might have been added to priority in a previous call already, we
-			// take care of that case in `dequeue` (more efficient).
+			self.note_queued(&req, false, added_count, queued_at);
+			self.per_para.entry(para_id).or_default().1 += 1;
 			self.best_effort
-				.entry(req.candidate_hash)
-				.or_insert(BestEffortEntry { req, added_count: 0 })
-				.added_count += 1;
+				.insert(req.candidate_hash, BestEffortEntry { req, added_count, queued_at });
+			Ok(())
+		};
+
+		match &result {
+			Ok(()) => self.update_size_metrics(),
+			Err(QueueError::PriorityFull) => self.metrics.on_participation_dropped("priority-full"),
+			Err(QueueError::BestEffortFull) =>
+				self.metrics.on_participation_dropped("best-effort-full"),
+			Err(QueueError::ParaFull(_)) => self.metrics.on_participation_dropped("para-full"),
 		}
-		Ok(())
+		result
 	}
 
 	/// Get the next best from the best effort queue.
 	///
-	/// If there are multiple best - just pick one.
-	fn pop_best_effort(&mut self) -> Option<ParticipationRequest> {
-		let best = self.best_effort.iter().reduce(|(hash1, entry1), (hash2, entry2)| {
-			if entry1.added_count > entry2.added_count {
-				(hash1, entry1)
-			} else {
-				(hash2, entry2)
-			}
-		});
-		if let Some((best_hash, _)) = best {
-			let best_hash = best_hash.clone();
-			self.best_effort.remove(&best_hash).map(|e| e.req)
-		} else {
-			None
-		}
+	/// If there are multiple best - pick the one of the para served least recently.
+	fn pop_best_effort(&mut self) -> Option<BestEffortEntry> {
+		let last_served = &self.last_served;
+		let rank = |entry: &BestEffortEntry| {
+			let served = last_served.get(&entry.req.para_id()).copied().unwrap_or_default();
+			(entry.added_count, std::cmp::Reverse(served))
+		};
+		let best_hash = self
+			.best_effort
+			.iter()
+			.max_by(|(hash1, entry1), (hash2, entry2)| {
+				// Candidate hashes make the choice deterministic.
+				rank(entry1).cmp(&rank(entry2)).then_with(|| hash2.cmp(hash1))
+			})
+			.map(|(hash, _)| *hash)?;
+
+		let entry = self.remove_best_effort(&best_hash)?;
+		self.served += 1;
+		self.last_served.insert(entry.req.para_id(), self.served);
+		Some(entry)
 	}
 
 	/// Get best priority queue entry.
-	fn pop_priority(&mut self) -> Option<ParticipationRequest> {
+	fn pop_priority(&mut self) -> Option<PriorityEntry> {
 		// Once https://github.com/rust-lang/rust/issues/62924 is there, we can use a simple:
 		// priority.pop_first().
-		if let Some((comparator, _)) = self.priority.iter().next() {
-			let comparator = comparator.clone();
-			self.priority.remove(&comparator)
-		} else {
-			None
+		let comparator = *self.priority.keys().next()?;
+		let entry = self.priority.remove(&comparator)?;
+		self.priority_index.remove(&entry.req.candidate_hash);
+		self.note_removed(&entry.req, true);
+		Some(entry)
+	}
+
+	fn remove_best_effort(&mut self, candidate_hash: &CandidateHash) -> Option<BestEffortEntry> {
+		let entry = self.best_effort.remove(candidate_hash)?;
+		self.note_removed(&entry.req, false);
+		Some(entry)
+	}
+
+	fn note_queued(
+		&mut self,
+		req: &ParticipationRequest,
+		priority: bool,
+		added_count: BestEffortCount,
+		queued_at: Timestamp,
+	) {
+		self.changes.insert(
+			req.candidate_hash,
+			Some(QueuedParticipation {
+				candidate_receipt: req.candidate_receipt.clone(),
+				session: req.session,
+				n_validators: req.n_validators as u32,
+				priority,
+				added_count,
+				queued_at,
+			}),
+		);
+	}
+
+	fn note_removed(&mut self, req: &ParticipationRequest, priority: bool) {
+		let para_id = req.para_id();
+		if let Some(counts) = self.per_para.get_mut(&para_id) {
+			if priority {
+				counts.0 = counts.0.saturating_sub(1);
+			} else {
+				counts.1 = counts.1.saturating_sub(1);
+			}
+			if *counts == (0, 0) {
+				self.per_para.remove(&para_id);
+			}
 		}
+		self.changes.insert(req.candidate_hash, None);
+		self.update_size_metrics();
+	}
+
+	fn update_size_metrics(&self) {
+		self.metrics
+			.on_participation_queue_sizes(self.priority.len(), self.best_effort.len());
 	}
 }
 
+/// Entry for the priority queue.
+struct PriorityEntry {
+	req: ParticipationRequest,
+	/// When the request was queued first.
+	queued_at: Timestamp,
+}
+
 /// Entry for the best effort queue.
 struct BestEffortEntry {
 	req: ParticipationRequest,
 	/// How often was the above request added to the queue.
 	added_count: BestEffortCount,
+	/// When the request was queued first.
+	queued_at: Timestamp,
 }
 
 /// `Comparator` for ordering of disputes for candidates.
@@ -18,7 +18,9 @@ use ::test_helpers::{dummy_candidate_receipt, dummy_hash};
 use assert_matches::assert_matches;
 use polkadot_primitives::v2::{BlockNumber, Hash};
 
-use super::{CandidateComparator, ParticipationRequest, QueueError, Queues};
+use polkadot_primitives::v2::Id as ParaId;
+
+use super::{CandidateComparator, ParticipationRequest, QueueConfig, QueueError, Queues};
 
 /// Make a `ParticipationRequest` based on the given commitments hash.
 fn make_participation_request(hash: Hash) -> ParticipationRequest {
@@ -28,6 +30,14 @@ fn make_participation_request(hash: Hash) -> ParticipationRequest {
 	ParticipationRequest::new(receipt, 1, 100)
 }
 
+/// Make a `ParticipationRequest` for a candidate of the given para.
+fn make_para_request(hash: Hash, para_id: u32) -> ParticipationRequest {
+	let mut receipt = dummy_candidate_receipt(dummy_hash());
+	receipt.commitments_hash = hash;
+	receipt.descriptor.para_id = ParaId::from(para_id);
+	ParticipationRequest::new(receipt, 1, 100)
+}
+
 /// Make dummy comparator for request, based on the given block number.
 fn make_dummy_comparator(
 	req: &ParticipationRequest,
@@ -42,7 +52,7 @@ fn make_dummy_comparator(
 /// processed in order. Best effort items, based on how often they have been added.
 #[test]
 fn ordering_works_as_expected() {
-	let mut queue = Queues::new();
+	let mut queue = Queues::new(Default::default(), Default::default());
 	let req1 = make_participation_request(Hash::repeat_byte(0x01));
 	let req_prio = make_participation_request(Hash::repeat_byte(0x02));
 	let req3 = make_participation_request(Hash::repeat_byte(0x03));
@@ -50,81 +60,184 @@ fn ordering_works_as_expected() {
 	let req5 = make_participation_request(Hash::repeat_byte(0x05));
 	let req_full = make_participation_request(Hash::repeat_byte(0x06));
 	let req_prio_full = make_participation_request(Hash::repeat_byte(0x07));
-	queue.queue_with_comparator(None, req1.clone()).unwrap();
+	queue.queue_with_comparator(None, req1.clone(), 0).unwrap();
 	queue
-		.queue_with_comparator(Some(make_dummy_comparator(&req_prio, 1)), req_prio.clone())
+		.queue_with_comparator(Some(make_dummy_comparator(&req_prio, 1)), req_prio.clone(), 0)
 		.unwrap();
-	queue.queue_with_comparator(None, req3.clone()).unwrap();
+	queue.queue_with_comparator(None, req3.clone(), 0).unwrap();
 	queue
-		.queue_with_comparator(Some(make_dummy_comparator(&req_prio_2, 2)), req_prio_2.clone())
+		.queue_with_comparator(Some(make_dummy_comparator(&req_prio_2, 2)), req_prio_2.clone(), 0)
 		.unwrap();
-	queue.queue_with_comparator(None, req3.clone()).unwrap();
-	queue.queue_with_comparator(None, req5.clone()).unwrap();
+	queue.queue_with_comparator(None, req3.clone(), 0).unwrap();
+	queue.queue_with_comparator(None, req5.clone(), 0).unwrap();
 	assert_matches!(
-		queue.queue_with_comparator(Some(make_dummy_comparator(&req_prio_full, 3)), req_prio_full),
+		queue.queue_with_comparator(
+			Some(make_dummy_comparator(&req_prio_full, 3)),
+			req_prio_full,
+			0
+		),
 		Err(QueueError::PriorityFull)
 	);
-	assert_matches!(queue.queue_with_comparator(None, req_full), Err(QueueError::BestEffortFull));
+	assert_matches!(
+		queue.queue_with_comparator(None, req_full, 0),
+		Err(QueueError::BestEffortFull)
+	);
 
-	assert_eq!(queue.dequeue(), Some(req_prio));
-	assert_eq!(queue.dequeue(), Some(req_prio_2));
-	assert_eq!(queue.dequeue(), Some(req3));
+	assert_eq!(queue.dequeue(0), Some(req_prio));
+	assert_eq!(queue.dequeue(0), Some(req_prio_2));
+	assert_eq!(queue.dequeue(0), Some(req3));
 	assert_matches!(
-		queue.dequeue(),
+		queue.dequeue(0),
 		Some(r) => { assert!(r == req1 || r == req5) }
 	);
 	assert_matches!(
-		queue.dequeue(),
+		queue.dequeue(0),
 		Some(r) => { assert!(r == req1 || r == req5) }
 	);
-	assert_matches!(queue.dequeue(), None);
+	assert_matches!(queue.dequeue(0), None);
 }
 
 /// No matter how often a candidate gets queued, it should only ever get dequeued once.
 #[test]
 fn candidate_is_only_dequeued_once() {
-	let mut queue = Queues::new();
+	let mut queue = Queues::new(Default::default(), Default::default());
 	let req1 = make_participation_request(Hash::repeat_byte(0x01));
 	let req_prio = make_participation_request(Hash::repeat_byte(0x02));
 	let req_best_effort_then_prio = make_participation_request(Hash::repeat_byte(0x03));
 	let req_prio_then_best_effort = make_participation_request(Hash::repeat_byte(0x04));
 
-	queue.queue_with_comparator(None, req1.clone()).unwrap();
+	queue.queue_with_comparator(None, req1.clone(), 0).unwrap();
 	queue
-		.queue_with_comparator(Some(make_dummy_comparator(&req_prio, 1)), req_prio.clone())
+		.queue_with_comparator(Some(make_dummy_comparator(&req_prio, 1)), req_prio.clone(), 0)
 		.unwrap();
 	// Insert same best effort again:
-	queue.queue_with_comparator(None, req1.clone()).unwrap();
+	queue.queue_with_comparator(None, req1.clone(), 0).unwrap();
 	// insert same prio again:
 	queue
-		.queue_with_comparator(Some(make_dummy_comparator(&req_prio, 1)), req_prio.clone())
+		.queue_with_comparator(Some(make_dummy_comparator(&req_prio, 1)), req_prio.clone(), 0)
 		.unwrap();
 
 	// Insert first as best effort:
-	queue.queue_with_comparator(None, req_best_effort_then_prio.clone()).unwrap();
+	queue.queue_with_comparator(None, req_best_effort_then_prio.clone(), 0).unwrap();
 	// Then as prio:
 	queue
 		.queue_with_comparator(
 			Some(make_dummy_comparator(&req_best_effort_then_prio, 2)),
 			req_best_effort_then_prio.clone(),
+			0,
 		)
 		.unwrap();
 
 	// Make space in prio:
-	assert_eq!(queue.dequeue(), Some(req_prio));
+	assert_eq!(queue.dequeue(0), Some(req_prio));
 
 	// Insert first as prio:
 	queue
 		.queue_with_comparator(
 			Some(make_dummy_comparator(&req_prio_then_best_effort, 3)),
 			req_prio_then_best_effort.clone(),
+			0,
 		)
 		.unwrap();
 	// Then as best effort:
-	queue.queue_with_comparator(None, req_prio_then_best_effort.clone()).unwrap();
+	queue.queue_with_comparator(None, req_prio_then_best_effort.clone(), 0).unwrap();
+
+	assert_eq!(queue.dequeue(0), Some(req_best_effort_then_prio));
+	assert_eq!(queue.dequeue(0), Some(req_prio_then_best_effort));
+	assert_eq!(queue.dequeue(0), Some(req1));
+	assert_eq!(queue.dequeue(0), None);
+}
+
+/// A single para can only take its share of a queue.
+#[test]
+fn para_share_is_limited() {
+	let config = QueueConfig {
+		priority_size: 3,
+		priority_size_per_para: 1,
+		best_effort_size: 3,
+		best_effort_size_per_para: 2,
+	};
+	let mut queue = Queues::new(config, Default::default());
+	let req1 = make_para_request(Hash::repeat_byte(0x01), 1);
+	let req2 = make_para_request(Hash::repeat_byte(0x02), 1);
+	let req3 = make_para_request(Hash::repeat_byte(0x03), 1);
+	let req_other = make_para_request(Hash::repeat_byte(0x04), 2);
+
+	queue
+		.queue_with_comparator(Some(make_dummy_comparator(&req1, 1)), req1.clone(), 0)
+		.unwrap();
+	assert_matches!(
+		queue.queue_with_comparator(Some(make_dummy_comparator(&req2, 1)), req2.clone(), 0),
+		Err(QueueError::ParaFull(para)) if para == ParaId::from(1)
+	);
+	queue.queue_with_comparator(None, req2.clone(), 0).unwrap();
+	queue.queue_with_comparator(None, req3.clone(), 0).unwrap();
+	assert_matches!(
+		queue.queue_with_comparator(None, make_para_request(Hash::repeat_byte(0x05), 1), 0),
+		Err(QueueError::ParaFull(_))
+	);
+	queue.queue_with_comparator(None, req_other.clone(), 0).unwrap();
+
+	// Dequeuing frees the share again:
+	assert_eq!(queue.dequeue(0), Some(req1));
+	queue
+		.queue_with_comparator(Some(make_dummy_comparator(&req2, 1)), req2.clone(), 0)
+		.unwrap();
+	assert_eq!(queue.dequeue(0), Some(req2));
+}
+
+/// Equally requested best effort disputes are served round robin between paras.
+#[test]
+fn best_effort_is_fair_between_paras() {
+	let config = QueueConfig { best_effort_size: 10, ..Default::default() };
+	let mut queue = Queues::new(config, Default::default());
+	let para1 = (1..=3).map(|i| make_para_request(Hash::repeat_byte(i), 1)).collect::<Vec<_>>();
+	let para2 = make_para_request(Hash::repeat_byte(0x10), 2);
+	for req in &para1 {
+		queue.queue_with_comparator(None, req.clone(), 0).unwrap();
+	}
+	queue.queue_with_comparator(None, para2.clone(), 0).unwrap();
+
+	let first = queue.dequeue(0).unwrap();
+	let second = queue.dequeue(0).unwrap();
+	let paras = [
+		first.candidate_receipt().descriptor.para_id,
+		second.candidate_receipt().descriptor.para_id,
+	];
+	assert!(paras.contains(&ParaId::from(1)));
+	assert!(paras.contains(&ParaId::from(2)));
+}
+
+/// Changes to persist reflect queuing and dequeuing, keeping age and added count.
+#[test]
+fn changes_track_queued_requests() {
+	let mut queue = Queues::new(Default::default(), Default::default());
+	let req1 = make_participation_request(Hash::repeat_byte(0x01));
+	let req_prio = make_participation_request(Hash::repeat_byte(0x02));
+
+	queue.queue_with_comparator(None, req1.clone(), 10).unwrap();
+	queue.queue_with_comparator(None, req1.clone(), 20).unwrap();
+	queue
+		.queue_with_comparator(Some(make_dummy_comparator(&req_prio, 1)), req_prio.clone(), 30)
+		.unwrap();
+
+	let changes = queue.take_changes();
+	assert_eq!(changes.len(), 2);
+	let stored = changes[req1.candidate_hash()].clone().unwrap();
+	assert_eq!((stored.priority, stored.added_count, stored.queued_at), (false, 2, 10));
+	let stored = changes[req_prio.candidate_hash()].clone().unwrap();
+	assert_eq!((stored.priority, stored.queued_at), (true, 30));
+	assert!(queue.take_changes().is_empty());
+
+	assert_eq!(queue.dequeue(40), Some(req_prio.clone()));
+	let changes = queue.take_changes();
+	assert_eq!(changes.len(), 1);
+	assert_eq!(changes[req_prio.candidate_hash()], None);
 
-	assert_eq!(queue.dequeue(), Some(req_best_effort_then_prio));
-	assert_eq!(queue.dequeue(), Some(req_prio_then_best_effort));
-	assert_eq!(queue.dequeue(), Some(req1));
-	assert_eq!(queue.dequeue(), None);
+	// A restored request keeps its count:
+	let mut restored = Queues::new(Default::default(), Default::default());
+	restored.insert(None, req1.clone(), 2, 10).unwrap();
+	restored.queue_with_comparator(None, req1.clone(), 50).unwrap();
+	let stored = restored.take_changes()[req1.candidate_hash()].clone().unwrap();
+	assert_eq!((stored.added_count, stored.queued_at), (3, 10));
 }
@@ -76,7 +76,7 @@ async fn participate_with_commitments_hash<Context>(
 	let req = ParticipationRequest::new(candidate_receipt, session, n_validators);
 
 	participation
-		.queue_participation(ctx, ParticipationPriority::BestEffort, req)
+		.queue_participation(ctx, ParticipationPriority::BestEffort, req, 0)
 		.await
 }
 
@@ -105,6 +105,7 @@ async fn activate_leaf<Context>(
 				number: block_number,
 				status: LeafStatus::Fresh,
 			}),
+			0,
 		)
 		.await
 }
@@ -205,7 +206,7 @@ fn same_req_wont_get_queued_if_participation_is_already_running() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 		for _ in 0..MAX_PARALLEL_PARTICIPATIONS {
@@ -223,7 +224,7 @@ fn same_req_wont_get_queued_if_participation_is_already_running() {
 		);
 
 		let result = participation
-			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 			.await
 			.unwrap();
 
@@ -244,7 +245,7 @@ fn reqs_get_queued_when_out_of_capacity() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 		for i in 0..MAX_PARALLEL_PARTICIPATIONS {
@@ -269,7 +270,7 @@ fn reqs_get_queued_when_out_of_capacity() {
 			);
 
 			let result = participation
-				.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+				.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 				.await
 				.unwrap();
 
@@ -291,7 +292,7 @@ fn reqs_get_queued_on_no_recent_block() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, _worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		participate(&mut ctx, &mut participation).await.unwrap();
 		assert!(ctx_handle.recv().timeout(Duration::from_millis(10)).await.is_none());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
@@ -316,7 +317,7 @@ fn cannot_participate_if_cannot_recover_available_data() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 
@@ -330,7 +331,7 @@ fn cannot_participate_if_cannot_recover_available_data() {
 			"overseer did not receive recover available data message",
 		);
 		let result = participation
-			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 			.await
 			.unwrap();
 		assert_matches!(
@@ -346,7 +347,7 @@ fn cannot_participate_if_cannot_recover_validation_code() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 
@@ -367,7 +368,7 @@ fn cannot_participate_if_cannot_recover_validation_code() {
 		);
 
 		let result = participation
-			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 			.await
 			.unwrap();
 		assert_matches!(
@@ -383,7 +384,7 @@ fn cast_invalid_vote_if_available_data_is_invalid() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 
@@ -398,7 +399,7 @@ fn cast_invalid_vote_if_available_data_is_invalid() {
 		);
 
 		let result = participation
-			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 			.await
 			.unwrap();
 		assert_matches!(
@@ -414,7 +415,7 @@ fn cast_invalid_vote_if_validation_fails_or_is_invalid() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 
@@ -436,7 +437,7 @@ fn cast_invalid_vote_if_validation_fails_or_is_invalid() {
 		);
 
 		let result = participation
-			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 			.await
 			.unwrap();
 		assert_matches!(
@@ -452,7 +453,7 @@ fn cast_invalid_vote_if_commitments_dont_match() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 
@@ -474,7 +475,7 @@ fn cast_invalid_vote_if_commitments_dont_match() {
 		);
 
 		let result = participation
-			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 			.await
 			.unwrap();
 		assert_matches!(
@@ -490,7 +491,7 @@ fn cast_valid_vote_if_validation_passes() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 
@@ -512,7 +513,7 @@ fn cast_valid_vote_if_validation_passes() {
 		);
 
 		let result = participation
-			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 			.await
 			.unwrap();
 		assert_matches!(
@@ -528,7 +529,7 @@ fn failure_to_store_available_data_does_not_preclude_participation() {
 		let (mut ctx, mut ctx_handle) = make_our_subsystem_context(TaskExecutor::new());
 
 		let (sender, mut worker_receiver) = mpsc::channel(1);
-		let mut participation = Participation::new(sender);
+		let mut participation = Participation::new(sender, Default::default(), Default::default());
 		activate_leaf(&mut ctx, &mut participation, 10).await.unwrap();
 		participate(&mut ctx, &mut participation).await.unwrap();
 
@@ -551,7 +552,7 @@ fn failure_to_store_available_data_does_not_preclude_participation() {
 		);
 
 		let result = participation
-			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap())
+			.get_participation_result(&mut ctx, worker_receiver.next().await.unwrap(), 0)
 			.await
 			.unwrap();
 		assert_matches!(
@@ -16,14 +16,18 @@
 
 use std::collections::{BTreeSet, HashMap};
 
-use polkadot_primitives::v2::{CandidateHash, SessionIndex, ValidatorIndex};
+use polkadot_node_subsystem::messages::SpamSlotUsage;
+use polkadot_primitives::v2::{CandidateHash, SessionIndex, ValidatorId, ValidatorIndex};
 
-use crate::LOG_TARGET;
+use crate::{
+	db::v1::{SpamRecord, SpamRecords},
+	LOG_TARGET,
+};
 
 /// Type used for counting potential spam votes.
 type SpamCount = u32;
 
-/// How many unconfirmed disputes a validator is allowed to import (per session).
+/// How many unconfirmed disputes a validator without any record is allowed to import (per session).
 ///
 /// Unconfirmed means: Node has not seen the candidate be included on any chain, it has not cast a
 /// vote itself on that dispute, the dispute has not yet reached more than a third of
@@ -42,15 +46,58 @@ const MAX_SPAM_VOTES: SpamCount = 50;
 #[cfg(test)]
 const MAX_SPAM_VOTES: SpamCount = 1;
 
+/// The number of spam slots repeat offenders are left with.
+#[cfg(not(test))]
+const MIN_SPAM_VOTES: SpamCount = 5;
+#[cfg(test)]
+const MIN_SPAM_VOTES: SpamCount = 0;
+
+/// The number of spam slots validators whose disputes usually get confirmed can earn.
+const MAX_RELAXED_SPAM_VOTES: SpamCount = 2 * MAX_SPAM_VOTES;
+
+/// The amount a single dispute adds to a `SpamRecord`.
+///
+/// Records are fixed point numbers, so they can decay gradually.
+pub const SCORE_UNIT: u32 = 1000;
+
+/// Records are multiplied by `SCORE_DECAY_PER_SESSION / SCORE_UNIT` on every new session.
+///
+/// This halves a record within roughly seven sessions.
+const SCORE_DECAY_PER_SESSION: u32 = 900;
+
+/// Sessions after which a record decayed to zero anyway.
+const MAX_DECAY_SESSIONS: u32 = 100;
+
+/// Occupied spam slots of a validator in a session.
+struct SlotUse {
+	validator: ValidatorId,
+	count: SpamCount,
+}
+
 /// Spam slots for raised disputes concerning unknown candidates.
+///
+/// The number of slots of a validator depends on its record: Validators whose unconfirmed
+/// disputes never got confirmed get fewer slots, validators whose disputes usually concluded
+/// invalid get more. Records decay over sessions, so validators recover from past incidents.
 pub struct SpamSlots {
 	/// Counts per validator and session.
 	///
-	/// Must not exceed `MAX_SPAM_VOTES`.
-	slots: HashMap<(SessionIndex, ValidatorIndex), SpamCount>,
+	/// Must not exceed the limit given by the validator's record.
+	slots: HashMap<(SessionIndex, ValidatorIndex), SlotUse>,
 
 	/// All unconfirmed candidates we are aware of right now.
 	unconfirmed: UnconfirmedDisputes,
+
+	/// Validators which raised confirmed disputes, until those disputes conclude.
+	///
+	/// Only disputes concluding in favour of the raising validators get credited to their records.
+	awaiting_conclusion: HashMap<(SessionIndex, CandidateHash), Vec<ValidatorId>>,
+
+	/// Records of all validators, persisted in the DB.
+	records: SpamRecords,
+
+	/// Whether `records` changed since the last call to `take_changed_records`.
+	records_changed: bool,
 }
 
 /// Unconfirmed disputes to be passed at initialization.
@@ -59,26 +106,48 @@ pub type UnconfirmedDisputes = HashMap<(SessionIndex, CandidateHash), BTreeSet<V
 impl SpamSlots {
 	/// Recover `SpamSlots` from state on startup.
 	///
-	/// Initialize based on already existing active disputes.
-	pub fn recover_from_state(unconfirmed_disputes: UnconfirmedDisputes) -> Self {
-		let mut slots: HashMap<(SessionIndex, ValidatorIndex), SpamCount> = HashMap::new();
-		for ((session, _), validators) in unconfirmed_disputes.iter() {
-			for validator in validators {
-				let spam_vote_count = slots.entry((*session, *validator)).or_default();
-				*spam_vote_count += 1;
-				if *spam_vote_count > MAX_SPAM_VOTES {
+	/// Initialize based on already existing active disputes and the stored records. `validators`
+	/// gives the validators of a session, disputes of unknown sessions are ignored.
+	pub fn recover_from_state<'a>(
+		unconfirmed_disputes: UnconfirmedDisputes,
+		records: SpamRecords,
+		validators: impl Fn(SessionIndex) -> Option<&'a [ValidatorId]>,
+	) -> Self {
+		let mut spam_slots = Self {
+			slots: HashMap::new(),
+			unconfirmed: UnconfirmedDisputes::new(),
+			awaiting_conclusion: HashMap::new(),
+			records,
+			records_changed: false,
+		};
+		for ((session, candidate), validator_indices) in unconfirmed_disputes {
+			let session_validators = match validators(session) {
+				Some(v) => v,
+				None => continue,
+			};
+			for validator in &validator_indices {
+				let validator_id = match session_validators.get(validator.0 as usize) {
+					Some(id) => id,
+					None => continue,
+				};
+				let limit = spam_slots.limit(validator_id);
+				let slot = slot_use(&mut spam_slots.slots, session, *validator, validator_id);
+				slot.count += 1;
+				if slot.count > limit {
 					gum::debug!(
 						target: LOG_TARGET,
 						?session,
 						?validator,
-						count = ?spam_vote_count,
+						count = ?slot.count,
+						?limit,
 						"Import exceeded spam slot for validator"
 					);
 				}
 			}
+			spam_slots.unconfirmed.insert((session, candidate), validator_indices);
 		}
 
-		Self { slots, unconfirmed: unconfirmed_disputes }
+		spam_slots
 	}
 
 	/// Increase a "voting invalid" validator's spam slot.
@@ -92,9 +161,11 @@ impl SpamSlots {
 		session: SessionIndex,
 		candidate: CandidateHash,
 		validator: ValidatorIndex,
+		validator_id: &ValidatorId,
 	) -> bool {
-		let spam_vote_count = self.slots.entry((session, validator)).or_default();
-		if *spam_vote_count >= MAX_SPAM_VOTES {
+		let limit = self.limit(validator_id);
+		let slot = slot_use(&mut self.slots, session, validator, validator_id);
+		if slot.count >= limit {
 			return false
 		}
 		let validators = self.unconfirmed.entry((session, candidate)).or_default();
@@ -103,7 +174,7 @@ impl SpamSlots {
 			// We only increment spam slots once per candidate, as each validator has to provide an
 			// opposing vote for sending out its own vote. Therefore, receiving multiple votes for
 			// a single candidate is expected and should not get punished here.
-			*spam_vote_count += 1;
+			slot.count += 1;
 		}
 
 		true
@@ -112,24 +183,246 @@ impl SpamSlots {
 	/// Clear out spam slots for a given candidate in a session.
 	///
 	/// This effectively reduces the spam slot count for all validators participating in a dispute
-	/// for that candidate. You should call this function once a dispute became obsolete or got
-	/// confirmed and thus votes for it should no longer be treated as potential spam.
+	/// for that candidate. You should call this function once a dispute got confirmed and thus
+	/// votes for it should no longer be treated as potential spam. Whether the dispute gets
+	/// credited to the records of the validators depends on its outcome, see `concluded`.
 	pub fn clear(&mut self, key: &(SessionIndex, CandidateHash)) {
 		if let Some(validators) = self.unconfirmed.remove(key) {
 			let (session, _) = key;
 			for validator in validators {
-				if let Some(spam_vote_count) = self.slots.remove(&(*session, validator)) {
-					let new = spam_vote_count - 1;
-					if new > 0 {
-						self.slots.insert((*session, validator), new);
+				if let Some(mut slot) = self.slots.remove(&(*session, validator)) {
+					self.awaiting_conclusion.entry(*key).or_default().push(slot.validator.clone());
+
+					slot.count = slot.count.saturating_sub(1);
+					if slot.count > 0 {
+						self.slots.insert((*session, validator), slot);
 					}
 				}
 			}
 		}
 	}
+
+	/// Note the conclusion of a dispute previously passed to `clear`.
+	///
+	/// The validators which raised the dispute get it credited to their records, if it concluded
+	/// in their favour, i.e. the candidate was found invalid.
+	pub fn concluded(&mut self, key: &(SessionIndex, CandidateHash), concluded_invalid: bool) {
+		let validators = match self.awaiting_conclusion.remove(key) {
+			Some(validators) => validators,
+			None => return,
+		};
+		if !concluded_invalid {
+			return
+		}
+		for validator in validators {
+			self.records.records.entry(validator).or_default().confirmed += SCORE_UNIT;
+			self.records_changed = true;
+		}
+	}
+
 	/// Prune all spam slots for sessions older than the given index.
-	pub fn prune_old(&mut self, oldest_index: SessionIndex) {
-		self.unconfirmed.retain(|(session, _), _| *session >= oldest_index);
+	///
+	/// Disputes still unconfirmed by then are held against the validators which raised them.
+	/// Records are decayed up to `current_session`.
+	pub fn prune_old(&mut self, oldest_index: SessionIndex, current_session: SessionIndex) {
+		let slots = &self.slots;
+		let records = &mut self.records.records;
+		let mut records_changed = false;
+		self.unconfirmed.retain(|(session, _), validators| {
+			if *session >= oldest_index {
+				return true
+			}
+			for validator in validators.iter() {
+				if let Some(slot) = slots.get(&(*session, *validator)) {
+					records.entry(slot.validator.clone()).or_default().unconfirmed += SCORE_UNIT;
+					records_changed = true;
+				}
+			}
+			false
+		});
+		self.records_changed |= records_changed;
 		self.slots.retain(|(session, _), _| *session >= oldest_index);
+		self.awaiting_conclusion.retain(|(session, _), _| *session >= oldest_index);
+		self.decay(current_session);
+	}
+
+	/// The records of all validators, if they changed since the last call.
+	pub fn take_changed_records(&mut self) -> Option<SpamRecords> {
+		std::mem::take(&mut self.records_changed).then(|| self.records.clone())
+	}
+
+	/// Current spam slot use of all validators with unconfirmed disputes.
+	pub fn usage(&self) -> Vec<SpamSlotUsage> {
+		let mut usage = self
+			.slots
+			.iter()
+			.map(|((session, validator_index), slot)| {
+				let record = self.records.records.get(&slot.validator).copied().unwrap_or_default();
+				SpamSlotUsage {
+					session: *session,
+					validator_index: *validator_index,
+					validator: slot.validator.clone(),
+					used: slot.count,
+					limit: slot_limit(&record),
+					unconfirmed_score: record.unconfirmed,
+					confirmed_score: record.confirmed,
+				}
+			})
+			.collect::<Vec<_>>();
+		usage.sort_by_key(|u| (u.session, u.validator_index));
+		usage
+	}
+
+	/// Number of occupied slots across all validators and sessions.
+	pub fn occupied(&self) -> u64 {
+		self.slots.values().map(|slot| u64::from(slot.count)).sum()
+	}
+
+	/// Number of validators which got fewer slots than `MAX_SPAM_VOTES` because of their record.
+	pub fn restricted_validators(&self) -> usize {
+		self.records
+			.records
+			.values()
+			.filter(|record| slot_limit(record) < MAX_SPAM_VOTES)
+			.count()
+	}
+
+	fn limit(&self, validator_id: &ValidatorId) -> SpamCount {
+		self.records.records.get(validator_id).map_or(MAX_SPAM_VOTES, slot_limit)
+	}
+
+	fn decay(&mut self, current_session: SessionIndex) {
+		let elapsed = current_session.saturating_sub(self.records.session);
+		if elapsed == 0 {
+			return
+		}
+		self.records.session = current_session;
+		self.records_changed = true;
+
+		let elapsed = elapsed.min(MAX_DECAY_SESSIONS);
+		let decay = |value: u32| {
+			(0..elapsed).fold(value, |value, _| {
+				(u64::from(value) * u64::from(SCORE_DECAY_PER_SESSION) / u64::from(SCORE_UNIT))
+					as u32
+			})
+		};
+		self.records.records.retain(|_, record| {
+			record.unconfirmed = decay(record.unconfirmed);
+			record.confirmed = decay(record.confirmed);
+			record.unconfirmed > 0 || record.confirmed > 0
+		});
+	}
+}
+
+fn slot_use<'a>(
+	slots: &'a mut HashMap<(SessionIndex, ValidatorIndex), SlotUse>,
+	session: SessionIndex,
+	validator: ValidatorIndex,
+	validator_id: &ValidatorId,
+) -> &'a mut SlotUse {
+	slots
+		.entry((session, validator))
+		.or_insert_with(|| SlotUse { validator: validator_id.clone(), count: 0 })
+}
+
+/// The number of spam slots for a validator with the given record.
+///
+/// Scales `MAX_SPAM_VOTES` by the ratio of confirmed to unconfirmed disputes, both offset by a
+/// single dispute so validators without history keep the default.
+fn slot_limit(record: &SpamRecord) -> SpamCount {
+	let unit = u64::from(SCORE_UNIT);
+	let limit = u64::from(MAX_SPAM_VOTES) * (u64::from(record.confirmed) + unit) /
+		(u64::from(record.unconfirmed) + unit);
+	limit.clamp(u64::from(MIN_SPAM_VOTES), u64::from(MAX_RELAXED_SPAM_VOTES)) as SpamCount
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::Sr25519Keyring;
+
+	fn candidate(n: u8) -> CandidateHash {
+		CandidateHash([n; 32].into())
+	}
+
+	fn alice() -> ValidatorId {
+		Sr25519Keyring::Alice.public().into()
+	}
+
+	fn spam_slots() -> SpamSlots {
+		SpamSlots::recover_from_state(Default::default(), Default::default(), |_| None)
+	}
+
+	#[test]
+	fn unconfirmed_disputes_tighten_limit() {
+		let mut spam_slots = spam_slots();
+		assert!(spam_slots.add_unconfirmed(1, candidate(1), ValidatorIndex(0), &alice()));
+		assert!(!spam_slots.add_unconfirmed(1, candidate(2), ValidatorIndex(0), &alice()));
+		assert!(spam_slots.take_changed_records().is_none());
+
+		// The dispute never got confirmed:
+		spam_slots.prune_old(2, 2);
+		let records = spam_slots.take_changed_records().unwrap();
+		assert_eq!(records.session, 2);
+		assert!(records.records[&alice()].unconfirmed > 0);
+		assert!(spam_slots.take_changed_records().is_none());
+
+		assert_eq!(spam_slots.restricted_validators(), 1);
+		assert!(!spam_slots.add_unconfirmed(2, candidate(3), ValidatorIndex(0), &alice()));
+	}
+
+	#[test]
+	fn confirmed_disputes_relax_limit() {
+		let mut spam_slots = spam_slots();
+		assert!(spam_slots.add_unconfirmed(1, candidate(1), ValidatorIndex(0), &alice()));
+		spam_slots.clear(&(1, candidate(1)));
+		assert_eq!(spam_slots.occupied(), 0);
+		assert!(spam_slots.take_changed_records().is_none());
+		spam_slots.concluded(&(1, candidate(1)), true);
+		assert_eq!(spam_slots.take_changed_records().unwrap().records[&alice()].confirmed, 1000);
+
+		assert!(spam_slots.add_unconfirmed(1, candidate(2), ValidatorIndex(0), &alice()));
+		assert!(spam_slots.add_unconfirmed(1, candidate(3), ValidatorIndex(0), &alice()));
+		assert!(!spam_slots.add_unconfirmed(1, candidate(4), ValidatorIndex(0), &alice()));
+
+		let usage = spam_slots.usage();
+		assert_eq!(usage.len(), 1);
+		assert_eq!((usage[0].used, usage[0].limit), (2, MAX_RELAXED_SPAM_VOTES));
+	}
+
+	#[test]
+	fn lost_disputes_are_not_credited() {
+		let mut spam_slots = spam_slots();
+		assert!(spam_slots.add_unconfirmed(1, candidate(1), ValidatorIndex(0), &alice()));
+		spam_slots.clear(&(1, candidate(1)));
+		assert_eq!(spam_slots.occupied(), 0);
+
+		// The candidate was found valid, alice raised the dispute for nothing:
+		spam_slots.concluded(&(1, candidate(1)), false);
+		assert!(spam_slots.take_changed_records().is_none());
+
+		// Concluding again has no effect either:
+		spam_slots.concluded(&(1, candidate(1)), true);
+		assert!(spam_slots.take_changed_records().is_none());
+
+		assert!(spam_slots.add_unconfirmed(1, candidate(2), ValidatorIndex(0), &alice()));
+		assert!(!spam_slots.add_unconfirmed(1, candidate(3), ValidatorIndex(0), &alice()));
+	}
+
+	#[test]
+	fn records_decay_over_sessions() {
+		let mut records = SpamRecords::default();
+		records
+			.records
+			.insert(alice(), SpamRecord { unconfirmed: SCORE_UNIT, confirmed: 0 });
+		let mut spam_slots = SpamSlots::recover_from_state(Default::default(), records, |_| None);
+
+		spam_slots.prune_old(0, 7);
+		let unconfirmed = spam_slots.take_changed_records().unwrap().records[&alice()].unconfirmed;
+		assert!(unconfirmed < SCORE_UNIT / 2 && unconfirmed > 0);
+
+		spam_slots.prune_old(0, 7 + MAX_DECAY_SESSIONS);
+		assert!(spam_slots.take_changed_records().unwrap().records.is_empty());
+		assert_eq!(spam_slots.restricted_validators(), 0);
 	}
 }
@@ -172,10 +172,11 @@ impl Default for TestState {
 		let subsystem_keystore =
 			make_keystore(vec![Sr25519Keyring::Alice.to_seed()].into_iter()).into();
 
-		let db = kvdb_memorydb::create(1);
-		let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(db, &[]);
+		let db = kvdb_memorydb::create(2);
+		let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(db, &[0]);
 		let db = Arc::new(db);
-		let config = Config { col_data: 0 };
+		let config =
+			Config { col_data: 0, col_session_data: 1, participation_queue: Default::default() };
 
 		let genesis_header = Header {
 			parent_hash: Hash::zero(),
@@ -458,11 +459,11 @@ impl TestState {
 		)
 	}
 
-	fn resume<F>(mut self, test: F) -> Self
+	fn resume<F>(self, test: F) -> Self
 	where
 		F: FnOnce(TestState, VirtualOverseer) -> BoxFuture<'static, TestState>,
 	{
-		self.known_session = None;
+		// `known_session` is kept, the session window is persisted in the database.
 		let (ctx, ctx_handle) = make_subsystem_context(TaskExecutor::new());
 		let subsystem = DisputeCoordinatorSubsystem::new(
 			self.db.clone(),
@@ -0,0 +1,75 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Derives the version of prepared artifacts from the crate version and the locked versions of
+//! the crates which produce the compiled code.
+
+use std::{env, fs, path::PathBuf};
+
+/// The crates whose version determines whether a prepared artifact can be reused.
+const COMPILER_CRATES: &[&str] = &["wasmtime", "sc-executor-wasmtime"];
+
+fn main() {
+	let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("set by cargo; qed"));
+	let lock_file = manifest_dir.ancestors().map(|dir| dir.join("Cargo.lock")).find(|p| p.exists());
+
+	// The crate version covers changes to the executor configuration, which is part of this
+	// crate.
+	let crate_version = env::var("CARGO_PKG_VERSION").expect("set by cargo; qed");
+	let version = match lock_file {
+		Some(lock_file) => {
+			println!("cargo:rerun-if-changed={}", lock_file.display());
+			let lock = fs::read_to_string(&lock_file).expect("Cargo.lock is readable; qed");
+			let compilers = COMPILER_CRATES
+				.iter()
+				.map(|name| format!("{} {}", name, locked_version(&lock, name)))
+				.collect::<Vec<_>>()
+				.join(", ");
+			format!("{} ({})", crate_version, compilers)
+		},
+		// Without a lock file, e.g. when built as a dependency of another workspace, only the
+		// crate version is known.
+		None => crate_version,
+	};
+
+	println!("cargo:rustc-env=PVF_ARTIFACT_VERSION={}", version);
+}
+
+/// The version and source of the given package in the lock file.
+///
+/// The source includes the git commit for git dependencies, whose version alone does not change
+/// between commits.
+fn locked_version(lock: &str, name: &str) -> String {
+	let mut versions = lock
+		.split("[[package]]")
+		.filter(|package| package.lines().any(|line| line == format!("name = \"{}\"", name)))
+		.map(|package| {
+			package
+				.lines()
+				.filter_map(|line| {
+					line.strip_prefix("version = ").or_else(|| line.strip_prefix("source = "))
+				})
+				.map(|value| value.trim_matches('"'))
+				.collect::<Vec<_>>()
+				.join(" ")
+		})
+		.collect::<Vec<_>>();
+
+	// Several versions may be locked, only one of which is used by the executor. Any change to
+	// either of them invalidates the artifacts, which is the safe direction to err in.
+	versions.sort();
+	versions.join(" | ")
+}
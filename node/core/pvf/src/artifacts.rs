@@ -14,15 +14,30 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{error::PrepareError, host::PrepareResultSender};
+use crate::{error::PrepareError, host::PrepareResultSender, LOG_TARGET};
 use always_assert::always;
 use async_std::path::{Path, PathBuf};
+use futures::StreamExt as _;
+use parity_scale_codec::{Decode, Encode};
 use polkadot_parachain::primitives::ValidationCodeHash;
+use sp_core::blake2_256;
 use std::{
 	collections::HashMap,
+	io,
 	time::{Duration, SystemTime},
 };
 
+/// The build that prepared an artifact.
+///
+/// Compiled code is only valid for the wasmtime version and the executor configuration it was
+/// produced with, so artifacts prepared by any other build are discarded on startup. The version
+/// is derived from the crate version and, if known, the locked versions of wasmtime and the
+/// executor by the build script.
+const ARTIFACT_VERSION: &str = env!("PVF_ARTIFACT_VERSION");
+
+/// The suffix appended to the artifact path to get the path of its header.
+const HEADER_SUFFIX: &str = ".header";
+
 pub struct CompiledArtifact(Vec<u8>);
 
 impl CompiledArtifact {
@@ -37,6 +52,32 @@ impl AsRef<[u8]> for CompiledArtifact {
 	}
 }
 
+/// Metadata stored next to every prepared artifact.
+///
+/// The executor maps the artifact file directly, so the header can not be prepended to the
+/// compiled code and is kept in a separate file instead, see [`header_path`].
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct ArtifactHeader {
+	/// The build that prepared the artifact.
+	pub version: String,
+	/// The blake2-256 hash of the compiled artifact.
+	pub checksum: [u8; 32],
+}
+
+impl ArtifactHeader {
+	/// Creates the header for the given compiled artifact, prepared by this build.
+	pub fn new(compiled_artifact: &[u8]) -> Self {
+		Self { version: ARTIFACT_VERSION.to_string(), checksum: blake2_256(compiled_artifact) }
+	}
+}
+
+/// Returns the path of the header belonging to the artifact at the given path.
+pub fn header_path(artifact_path: &Path) -> PathBuf {
+	let mut path = artifact_path.as_os_str().to_owned();
+	path.push(HEADER_SUFFIX);
+	path.into()
+}
+
 /// Identifier of an artifact. Right now it only encodes a code hash of the PVF. But if we get to
 /// multiple engine implementations the artifact ID should include the engine type as well.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -53,7 +94,6 @@ impl ArtifactId {
 	}
 
 	/// Tries to recover the artifact id from the given file name.
-	#[cfg(test)]
 	pub fn from_file_name(file_name: &str) -> Option<Self> {
 		use polkadot_core_primitives::Hash;
 		use std::str::FromStr as _;
@@ -115,17 +155,39 @@ pub struct Artifacts {
 }
 
 impl Artifacts {
-	/// Initialize a blank cache at the given path. This will clear everything present at the
-	/// given path, to be populated over time.
+	/// Initialize the cache at the given path, picking up the artifacts prepared before a restart.
 	///
 	/// The recognized artifacts will be filled in the table and unrecognized will be removed.
+	/// Artifacts which were prepared by another build or do not match the checksum of their
+	/// header are removed as well, so that they get prepared again once needed.
 	pub async fn new(cache_path: &Path) -> Self {
 		// Make sure that the cache path directory and all it's parents are created.
-		// First delete the entire cache. Nodes are long-running so this should populate shortly.
-		let _ = async_std::fs::remove_dir_all(cache_path).await;
 		let _ = async_std::fs::create_dir_all(cache_path).await;
 
-		Self { artifacts: HashMap::new() }
+		let artifacts = match scan_cache(cache_path).await {
+			Ok(artifacts) => artifacts,
+			Err(err) => {
+				// Start with a blank cache. Nodes are long-running so this should populate shortly.
+				gum::warn!(
+					target: LOG_TARGET,
+					"failed to scan the artifacts cache at {}: {:?}",
+					cache_path.display(),
+					err,
+				);
+				let _ = async_std::fs::remove_dir_all(cache_path).await;
+				let _ = async_std::fs::create_dir_all(cache_path).await;
+				HashMap::new()
+			},
+		};
+
+		gum::debug!(
+			target: LOG_TARGET,
+			"recovered {} prepared artifacts from {}",
+			artifacts.len(),
+			cache_path.display(),
+		);
+
+		Self { artifacts }
 	}
 
 	#[cfg(test)]
@@ -192,10 +254,95 @@ impl Artifacts {
 	}
 }
 
+/// Walks the cache directory and collects the artifacts which can be used as is, removing
+/// everything else.
+async fn scan_cache(cache_path: &Path) -> io::Result<HashMap<ArtifactId, ArtifactState>> {
+	let now = SystemTime::now();
+	let mut artifacts = HashMap::new();
+
+	let mut entries = async_std::fs::read_dir(cache_path).await?;
+	while let Some(entry) = entries.next().await {
+		let path = entry?.path();
+		let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+
+		// Headers are checked together with their artifacts, only orphans are removed here.
+		if let Some(artifact_file_name) = file_name.strip_suffix(HEADER_SUFFIX) {
+			if !cache_path.join(artifact_file_name).exists().await {
+				remove_file(&path).await;
+			}
+			continue
+		}
+
+		let artifact_id = match ArtifactId::from_file_name(file_name) {
+			Some(artifact_id) => artifact_id,
+			None => {
+				// Leftovers like the temporary files of interrupted preparations.
+				remove_file(&path).await;
+				continue
+			},
+		};
+
+		if is_valid_artifact(&path).await {
+			artifacts.insert(artifact_id, ArtifactState::Prepared { last_time_needed: now });
+		} else {
+			gum::debug!(
+				target: LOG_TARGET,
+				validation_code_hash = ?artifact_id.code_hash,
+				"discarding stale or corrupted artifact {}",
+				path.display(),
+			);
+			remove_file(&path).await;
+			remove_file(&header_path(&path)).await;
+		}
+	}
+
+	Ok(artifacts)
+}
+
+/// Checks that the artifact at the given path was prepared by this build and is intact.
+async fn is_valid_artifact(artifact_path: &Path) -> bool {
+	let header = match async_std::fs::read(header_path(artifact_path)).await {
+		Ok(bytes) => match ArtifactHeader::decode(&mut bytes.as_slice()) {
+			Ok(header) => header,
+			Err(_) => return false,
+		},
+		Err(_) => return false,
+	};
+
+	if header.version != ARTIFACT_VERSION {
+		return false
+	}
+
+	match async_std::fs::read(artifact_path).await {
+		Ok(compiled_artifact) => header == ArtifactHeader::new(&compiled_artifact),
+		Err(_) => false,
+	}
+}
+
+async fn remove_file(path: &Path) {
+	match async_std::fs::remove_file(path).await {
+		Ok(()) => (),
+		Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+		Err(err) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				"failed to remove {} from the artifacts cache: {:?}",
+				path.display(),
+				err,
+			);
+		},
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{ArtifactId, Artifacts};
+	use super::{
+		header_path, ArtifactHeader, ArtifactId, ArtifactState, Artifacts, ARTIFACT_VERSION,
+		HEADER_SUFFIX,
+	};
+	use assert_matches::assert_matches;
 	use async_std::path::Path;
+	use parity_scale_codec::Encode;
 	use sp_core::H256;
 	use std::str::FromStr;
 
@@ -233,28 +380,96 @@ mod tests {
 		);
 	}
 
+	fn write_artifact(
+		cache_path: &Path,
+		file_name: &str,
+		code: &[u8],
+		header: Option<ArtifactHeader>,
+	) {
+		let artifact_path = cache_path.join(file_name);
+		std::fs::write(&artifact_path, code).unwrap();
+		if let Some(header) = header {
+			std::fs::write(header_path(&artifact_path), header.encode()).unwrap();
+		}
+	}
+
+	#[test]
+	fn artifact_version_follows_the_node() {
+		assert!(ARTIFACT_VERSION.starts_with(env!("CARGO_PKG_VERSION")));
+	}
+
 	#[test]
-	fn artifacts_removes_cache_on_startup() {
+	fn artifacts_of_other_compiler_versions_are_discarded() {
 		let fake_cache_path = async_std::task::block_on(async move {
 			crate::worker_common::tmpfile("test-cache").await.unwrap()
 		});
-		let fake_artifact_path = {
-			let mut p = fake_cache_path.clone();
-			p.push("wasmtime_0x1234567890123456789012345678901234567890123456789012345678901234");
-			p
+		std::fs::create_dir_all(&fake_cache_path).unwrap();
+
+		// Prepared by the same node version, but with another wasmtime.
+		let name = "wasmtime_0x1234567890123456789012345678901234567890123456789012345678901234";
+		let header = ArtifactHeader {
+			version: format!("{} (wasmtime 0.0.1)", env!("CARGO_PKG_VERSION")),
+			..ArtifactHeader::new(b"code")
 		};
+		write_artifact(&fake_cache_path, name, b"code", Some(header));
+
+		let p = &fake_cache_path;
+		let mut artifacts = async_std::task::block_on(async { Artifacts::new(p).await });
+
+		assert!(artifacts
+			.artifact_state_mut(&ArtifactId::from_file_name(name).unwrap())
+			.is_none());
+		assert_eq!(std::fs::read_dir(&fake_cache_path).unwrap().count(), 0);
 
-		// create a tmp cache with 1 artifact.
+		std::fs::remove_dir_all(fake_cache_path).unwrap();
+	}
 
+	#[test]
+	fn artifacts_recovers_cache_on_startup() {
+		let fake_cache_path = async_std::task::block_on(async move {
+			crate::worker_common::tmpfile("test-cache").await.unwrap()
+		});
 		std::fs::create_dir_all(&fake_cache_path).unwrap();
-		std::fs::File::create(fake_artifact_path).unwrap();
 
-		// this should remove it and re-create.
+		let valid = "wasmtime_0x1234567890123456789012345678901234567890123456789012345678901234";
+		let corrupted =
+			"wasmtime_0x2234567890123456789012345678901234567890123456789012345678901234";
+		let stale = "wasmtime_0x3234567890123456789012345678901234567890123456789012345678901234";
+		let headerless =
+			"wasmtime_0x4234567890123456789012345678901234567890123456789012345678901234";
+
+		write_artifact(&fake_cache_path, valid, b"code", Some(ArtifactHeader::new(b"code")));
+		write_artifact(&fake_cache_path, corrupted, b"c0de", Some(ArtifactHeader::new(b"code")));
+		write_artifact(
+			&fake_cache_path,
+			stale,
+			b"code",
+			Some(ArtifactHeader { version: "0.0.0".into(), ..ArtifactHeader::new(b"code") }),
+		);
+		write_artifact(&fake_cache_path, headerless, b"code", None);
+		write_artifact(&fake_cache_path, "prepare-artifact-abcdefghij", b"code", None);
+		std::fs::write(fake_cache_path.join("wasmtime_0x42.header"), b"").unwrap();
 
 		let p = &fake_cache_path;
-		async_std::task::block_on(async { Artifacts::new(p).await });
+		let mut artifacts = async_std::task::block_on(async { Artifacts::new(p).await });
 
-		assert_eq!(std::fs::read_dir(&fake_cache_path).unwrap().count(), 0);
+		assert_matches!(
+			artifacts.artifact_state_mut(&ArtifactId::from_file_name(valid).unwrap()),
+			Some(ArtifactState::Prepared { .. })
+		);
+		for name in [corrupted, stale, headerless] {
+			assert!(artifacts
+				.artifact_state_mut(&ArtifactId::from_file_name(name).unwrap())
+				.is_none());
+		}
+
+		// Only the valid artifact and its header are kept.
+		let mut remaining = std::fs::read_dir(&fake_cache_path)
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+			.collect::<Vec<_>>();
+		remaining.sort();
+		assert_eq!(remaining, vec![valid.to_string(), format!("{}{}", valid, HEADER_SUFFIX)]);
 
 		std::fs::remove_dir_all(fake_cache_path).unwrap();
 	}
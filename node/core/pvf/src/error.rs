@@ -31,6 +31,12 @@ pub enum PrepareError {
 	Panic(String),
 	/// Failed to prepare the PVF due to the time limit.
 	TimedOut,
+	/// The preparation used more memory than allowed for a single job. The limit is configured by
+	/// the node rather than the runtime, so this is not attributed to the PVF.
+	MemoryLimitExceeded,
+	/// The preparation attempted a filesystem or network syscall. The filter also covers the code
+	/// of the worker itself, so this is not attributed to the PVF.
+	ForbiddenSyscall,
 	/// This state indicates that the process assigned to prepare the artifact wasn't responsible
 	/// or were killed. This state is reported by the validation host (not by the worker).
 	DidNotMakeIt,
@@ -103,6 +109,10 @@ impl From<PrepareError> for ValidationError {
 			PrepareError::Panic(err) => ValidationError::InvalidCandidate(
 				InvalidCandidate::PrepareError(format!("panic: {}", err)),
 			),
+			PrepareError::MemoryLimitExceeded =>
+				ValidationError::InternalError("prepare: memory limit exceeded".to_owned()),
+			PrepareError::ForbiddenSyscall =>
+				ValidationError::InternalError("prepare: forbidden syscall".to_owned()),
 			PrepareError::TimedOut => ValidationError::InternalError("prepare: timeout".to_owned()),
 			PrepareError::DidNotMakeIt =>
 				ValidationError::InternalError("prepare: did not make it".to_owned()),
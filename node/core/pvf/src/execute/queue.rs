@@ -21,8 +21,9 @@ use crate::{
 	artifacts::{ArtifactId, ArtifactPathId},
 	host::ResultSender,
 	metrics::Metrics,
+	sandbox::JobLimits,
 	worker_common::{IdleWorker, WorkerHandle},
-	InvalidCandidate, ValidationError, LOG_TARGET,
+	InvalidCandidate, Priority, ValidationError, LOG_TARGET,
 };
 use async_std::path::PathBuf;
 use futures::{
@@ -32,7 +33,11 @@ use futures::{
 	Future, FutureExt,
 };
 use slotmap::HopSlotMap;
-use std::{collections::VecDeque, fmt, time::Duration};
+use std::{
+	collections::VecDeque,
+	fmt,
+	time::{Duration, Instant},
+};
 
 slotmap::new_key_type! { struct Worker; }
 
@@ -42,6 +47,7 @@ pub enum ToQueue {
 		artifact: ArtifactPathId,
 		execution_timeout: Duration,
 		params: Vec<u8>,
+		priority: Priority,
 		result_tx: ResultSender,
 	},
 }
@@ -50,9 +56,73 @@ struct ExecuteJob {
 	artifact: ArtifactPathId,
 	execution_timeout: Duration,
 	params: Vec<u8>,
+	priority: Priority,
+	/// When the job started to wait for a worker.
+	waiting_since: Instant,
 	result_tx: ResultSender,
 }
 
+/// The jobs waiting for a worker, one queue per priority. Higher priorities are always served
+/// first.
+struct Unscheduled {
+	background: VecDeque<ExecuteJob>,
+	normal: VecDeque<ExecuteJob>,
+	critical: VecDeque<ExecuteJob>,
+	metrics: Metrics,
+}
+
+impl Unscheduled {
+	fn new(metrics: Metrics) -> Self {
+		Self {
+			background: VecDeque::new(),
+			normal: VecDeque::new(),
+			critical: VecDeque::new(),
+			metrics,
+		}
+	}
+
+	fn queue_mut(&mut self, prio: Priority) -> &mut VecDeque<ExecuteJob> {
+		match prio {
+			Priority::Background => &mut self.background,
+			Priority::Normal => &mut self.normal,
+			Priority::Critical => &mut self.critical,
+		}
+	}
+
+	fn add(&mut self, job: ExecuteJob) {
+		let prio = job.priority;
+		self.queue_mut(prio).push_back(job);
+		self.note_depth(prio);
+	}
+
+	fn is_empty(&self) -> bool {
+		self.background.is_empty() && self.normal.is_empty() && self.critical.is_empty()
+	}
+
+	fn next(&mut self) -> Option<ExecuteJob> {
+		let prio = Priority::ALL
+			.iter()
+			.rev()
+			.copied()
+			.find(|prio| !self.queue_mut(*prio).is_empty())?;
+		let job = self.queue_mut(prio).pop_front();
+		self.note_depth(prio);
+
+		for lower in Priority::ALL.iter().copied().filter(|lower| *lower < prio) {
+			if !self.queue_mut(lower).is_empty() {
+				self.metrics.execute_queue().on_preempted(lower);
+			}
+		}
+
+		job
+	}
+
+	fn note_depth(&mut self, prio: Priority) {
+		let depth = self.queue_mut(prio).len();
+		self.metrics.execute_queue().on_depth_changed(prio, depth);
+	}
+}
+
 struct WorkerData {
 	idle: Option<IdleWorker>,
 	handle: WorkerHandle,
@@ -109,9 +179,10 @@ struct Queue {
 
 	program_path: PathBuf,
 	spawn_timeout: Duration,
+	job_limits: JobLimits,
 
-	/// The queue of jobs that are waiting for a worker to pick up.
-	queue: VecDeque<ExecuteJob>,
+	/// The queues of jobs that are waiting for a worker to pick up.
+	unscheduled: Unscheduled,
 	workers: Workers,
 	mux: Mux,
 }
@@ -122,14 +193,16 @@ impl Queue {
 		program_path: PathBuf,
 		worker_capacity: usize,
 		spawn_timeout: Duration,
+		job_limits: JobLimits,
 		to_queue_rx: mpsc::Receiver<ToQueue>,
 	) -> Self {
 		Self {
 			metrics,
 			program_path,
 			spawn_timeout,
+			job_limits,
 			to_queue_rx,
-			queue: VecDeque::new(),
+			unscheduled: Unscheduled::new(metrics.clone()),
 			mux: Mux::new(),
 			workers: Workers {
 				running: HopSlotMap::with_capacity_and_key(10),
@@ -173,14 +246,22 @@ async fn purge_dead(metrics: &Metrics, workers: &mut Workers) {
 }
 
 fn handle_to_queue(queue: &mut Queue, to_queue: ToQueue) {
-	let ToQueue::Enqueue { artifact, execution_timeout, params, result_tx } = to_queue;
+	let ToQueue::Enqueue { artifact, execution_timeout, params, priority, result_tx } = to_queue;
 	gum::debug!(
 		target: LOG_TARGET,
 		validation_code_hash = ?artifact.id.code_hash,
+		?priority,
 		"enqueueing an artifact for execution",
 	);
 	queue.metrics.execute_enqueued();
-	let job = ExecuteJob { artifact, execution_timeout, params, result_tx };
+	let job = ExecuteJob {
+		artifact,
+		execution_timeout,
+		params,
+		priority,
+		waiting_since: Instant::now(),
+		result_tx,
+	};
 
 	if let Some(available) = queue.workers.find_available() {
 		assign(queue, available, job);
@@ -188,7 +269,7 @@ fn handle_to_queue(queue: &mut Queue, to_queue: ToQueue) {
 		if queue.workers.can_afford_one_more() {
 			spawn_extra_worker(queue);
 		}
-		queue.queue.push_back(job);
+		queue.unscheduled.add(job);
 	}
 }
 
@@ -210,7 +291,7 @@ fn handle_worker_spawned(queue: &mut Queue, idle: IdleWorker, handle: WorkerHand
 
 	gum::debug!(target: LOG_TARGET, ?worker, "execute worker spawned");
 
-	if let Some(job) = queue.queue.pop_front() {
+	if let Some(job) = queue.unscheduled.next() {
 		assign(queue, worker, job);
 	}
 }
@@ -225,22 +306,36 @@ fn handle_job_finish(
 	result_tx: ResultSender,
 ) {
 	let (idle_worker, result) = match outcome {
-		Outcome::Ok { result_descriptor, duration_ms, idle_worker } => {
+		Outcome::Ok { result_descriptor, duration_ms, usage, idle_worker } => {
 			// TODO: propagate the soft timeout
 			drop(duration_ms);
+			queue.metrics.execute_worker().on_job_usage(&usage);
 
-			(Some(idle_worker), Ok(result_descriptor))
+			(idle_worker, Ok(result_descriptor))
 		},
 		Outcome::InvalidCandidate { err, idle_worker } => (
-			Some(idle_worker),
+			idle_worker,
 			Err(ValidationError::InvalidCandidate(InvalidCandidate::WorkerReportedError(err))),
 		),
 		Outcome::InternalError { err, idle_worker } =>
-			(Some(idle_worker), Err(ValidationError::InternalError(err))),
+			(idle_worker, Err(ValidationError::InternalError(err))),
 		Outcome::HardTimeout =>
 			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::HardTimeout))),
 		Outcome::IoErr =>
 			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::AmbiguousWorkerDeath))),
+		Outcome::MemoryLimitExceeded { usage } => {
+			queue.metrics.execute_worker().on_job_usage(&usage);
+			queue.metrics.execute_worker().on_sandbox_violation("memory");
+			// The limit is set by the node rather than the runtime, so the candidate can't be
+			// blamed for exceeding it.
+			(None, Err(ValidationError::InternalError("execution: memory limit exceeded".into())))
+		},
+		Outcome::ForbiddenSyscall => {
+			queue.metrics.execute_worker().on_sandbox_violation("syscall");
+			// The filter also covers the code of the worker itself, e.g. the host functions, so
+			// the candidate can't be blamed for the violation.
+			(None, Err(ValidationError::InternalError("execution: forbidden syscall".into())))
+		},
 	};
 
 	queue.metrics.execute_finished();
@@ -267,7 +362,7 @@ fn handle_job_finish(
 		if let Some(data) = queue.workers.running.get_mut(worker) {
 			data.idle = Some(idle_worker);
 
-			if let Some(job) = queue.queue.pop_front() {
+			if let Some(job) = queue.unscheduled.next() {
 				assign(queue, worker, job);
 			}
 		}
@@ -277,7 +372,7 @@ fn handle_job_finish(
 			queue.metrics.execute_worker().on_retired();
 		}
 
-		if !queue.queue.is_empty() {
+		if !queue.unscheduled.is_empty() {
 			// The worker has died and we still have work we have to do. Request an extra worker.
 			//
 			// That can potentially overshoot, but that should be OK.
@@ -290,19 +385,28 @@ fn spawn_extra_worker(queue: &mut Queue) {
 	queue.metrics.execute_worker().on_begin_spawn();
 	gum::debug!(target: LOG_TARGET, "spawning an extra worker");
 
-	queue
-		.mux
-		.push(spawn_worker_task(queue.program_path.clone(), queue.spawn_timeout).boxed());
+	queue.mux.push(
+		spawn_worker_task(queue.program_path.clone(), queue.spawn_timeout, queue.metrics.clone())
+			.boxed(),
+	);
 	queue.workers.spawn_inflight += 1;
 }
 
-async fn spawn_worker_task(program_path: PathBuf, spawn_timeout: Duration) -> QueueEvent {
+async fn spawn_worker_task(
+	program_path: PathBuf,
+	spawn_timeout: Duration,
+	metrics: Metrics,
+) -> QueueEvent {
 	use futures_timer::Delay;
 
 	loop {
+		let spawn_timer = metrics.execute_worker().time_spawn();
 		match super::worker::spawn(&program_path, spawn_timeout).await {
 			Ok((idle, handle)) => break QueueEvent::Spawn(idle, handle),
 			Err(err) => {
+				if let Some(timer) = spawn_timer {
+					timer.stop_and_discard();
+				}
 				gum::warn!(target: LOG_TARGET, "failed to spawn an execute worker: {:?}", err);
 
 				// Assume that the failure intermittent and retry after a delay.
@@ -328,7 +432,12 @@ fn assign(queue: &mut Queue, worker: Worker, job: ExecuteJob) {
 			thus claim_idle cannot return None;
 			qed.",
 	);
+	queue
+		.metrics
+		.execute_queue()
+		.on_dispatched(job.priority, job.waiting_since.elapsed());
 	let execution_timer = queue.metrics.time_execution();
+	let job_limits = queue.job_limits;
 	queue.mux.push(
 		async move {
 			let _timer = execution_timer;
@@ -337,6 +446,7 @@ fn assign(queue: &mut Queue, worker: Worker, job: ExecuteJob) {
 				job.artifact.clone(),
 				job.execution_timeout,
 				job.params,
+				job_limits,
 			)
 			.await;
 			QueueEvent::StartWork(worker, outcome, job.artifact.id, job.result_tx)
@@ -350,8 +460,11 @@ pub fn start(
 	program_path: PathBuf,
 	worker_capacity: usize,
 	spawn_timeout: Duration,
+	job_limits: JobLimits,
 ) -> (mpsc::Sender<ToQueue>, impl Future<Output = ()>) {
 	let (to_queue_tx, to_queue_rx) = mpsc::channel(20);
-	let run = Queue::new(metrics, program_path, worker_capacity, spawn_timeout, to_queue_rx).run();
+	let run =
+		Queue::new(metrics, program_path, worker_capacity, spawn_timeout, job_limits, to_queue_rx)
+			.run();
 	(to_queue_tx, run)
 }
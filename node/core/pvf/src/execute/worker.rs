@@ -16,9 +16,10 @@
 
 use crate::{
 	artifacts::ArtifactPathId,
-	executor_intf::Executor,
+	executor_intf::{Executor, EXECUTE_THREAD_STACK_SIZE},
+	sandbox::{self, JobLimits, JobOutcome, JobUsage},
 	worker_common::{
-		bytes_to_path, framed_recv, framed_send, path_to_bytes, spawn_with_program_path,
+		bytes_to_path, frame, framed_recv, framed_send, path_to_bytes, spawn_with_program_path,
 		worker_event_loop, IdleWorker, SpawnErr, WorkerHandle,
 	},
 	LOG_TARGET,
@@ -32,7 +33,10 @@ use futures::FutureExt;
 use futures_timer::Delay;
 use parity_scale_codec::{Decode, Encode};
 use polkadot_parachain::primitives::ValidationResult;
-use std::time::{Duration, Instant};
+use std::{
+	os::unix::io::AsRawFd,
+	time::{Duration, Instant},
+};
 
 /// Spawns a new worker with the given program path that acts as the worker and the spawn timeout.
 ///
@@ -45,18 +49,30 @@ pub async fn spawn(
 }
 
 /// Outcome of PVF execution.
+///
+/// The idle worker is only returned if the job did not forbid syscalls, otherwise the worker
+/// exits after reporting the outcome.
 pub enum Outcome {
 	/// PVF execution completed successfully and the result is returned. The worker is ready for
 	/// another job.
-	Ok { result_descriptor: ValidationResult, duration_ms: u64, idle_worker: IdleWorker },
+	Ok {
+		result_descriptor: ValidationResult,
+		duration_ms: u64,
+		usage: JobUsage,
+		idle_worker: Option<IdleWorker>,
+	},
 	/// The candidate validation failed. It may be for example because the wasm execution triggered a trap.
 	/// Errors related to the preparation process are not expected to be encountered by the execution workers.
-	InvalidCandidate { err: String, idle_worker: IdleWorker },
+	InvalidCandidate { err: String, idle_worker: Option<IdleWorker> },
 	/// An internal error happened during the validation. Such an error is most likely related to
 	/// some transient glitch.
-	InternalError { err: String, idle_worker: IdleWorker },
+	InternalError { err: String, idle_worker: Option<IdleWorker> },
 	/// The execution time exceeded the hard limit. The worker is terminated.
 	HardTimeout,
+	/// The execution exceeded the memory limit of the job. The worker is terminated.
+	MemoryLimitExceeded { usage: JobUsage },
+	/// The PVF attempted a syscall that is forbidden for the jobs. The worker is terminated.
+	ForbiddenSyscall,
 	/// An I/O error happened during communication with the worker. This may mean that the worker
 	/// process already died. The token is not returned in any case.
 	IoErr,
@@ -69,6 +85,7 @@ pub async fn start_work(
 	artifact: ArtifactPathId,
 	execution_timeout: Duration,
 	validation_params: Vec<u8>,
+	limits: JobLimits,
 ) -> Outcome {
 	let IdleWorker { mut stream, pid } = worker;
 
@@ -80,7 +97,8 @@ pub async fn start_work(
 		artifact.path.display(),
 	);
 
-	if let Err(error) = send_request(&mut stream, &artifact.path, &validation_params).await {
+	if let Err(error) = send_request(&mut stream, &artifact.path, &validation_params, limits).await
+	{
 		gum::warn!(
 			target: LOG_TARGET,
 			worker_pid = %pid,
@@ -118,13 +136,31 @@ pub async fn start_work(
 		},
 	};
 
+	let idle_worker = (!limits.forbid_syscalls).then(|| IdleWorker { stream, pid });
 	match response {
-		Response::Ok { result_descriptor, duration_ms } =>
-			Outcome::Ok { result_descriptor, duration_ms, idle_worker: IdleWorker { stream, pid } },
-		Response::InvalidCandidate(err) =>
-			Outcome::InvalidCandidate { err, idle_worker: IdleWorker { stream, pid } },
-		Response::InternalError(err) =>
-			Outcome::InternalError { err, idle_worker: IdleWorker { stream, pid } },
+		Response::Ok { result_descriptor, duration_ms, usage } =>
+			Outcome::Ok { result_descriptor, duration_ms, usage, idle_worker },
+		Response::InvalidCandidate(err) => Outcome::InvalidCandidate { err, idle_worker },
+		Response::InternalError(err) => Outcome::InternalError { err, idle_worker },
+		Response::MemoryLimitExceeded(usage) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				worker_pid = %pid,
+				validation_code_hash = ?artifact.id.code_hash,
+				peak_memory = usage.peak_memory,
+				"execution exceeded the memory limit",
+			);
+			Outcome::MemoryLimitExceeded { usage }
+		},
+		Response::ForbiddenSyscall => {
+			gum::warn!(
+				target: LOG_TARGET,
+				worker_pid = %pid,
+				validation_code_hash = ?artifact.id.code_hash,
+				"execution attempted a forbidden syscall",
+			);
+			Outcome::ForbiddenSyscall
+		},
 	}
 }
 
@@ -132,12 +168,14 @@ async fn send_request(
 	stream: &mut UnixStream,
 	artifact_path: &Path,
 	validation_params: &[u8],
+	limits: JobLimits,
 ) -> io::Result<()> {
 	framed_send(stream, path_to_bytes(artifact_path)).await?;
-	framed_send(stream, validation_params).await
+	framed_send(stream, validation_params).await?;
+	framed_send(stream, &limits.encode()).await
 }
 
-async fn recv_request(stream: &mut UnixStream) -> io::Result<(PathBuf, Vec<u8>)> {
+async fn recv_request(stream: &mut UnixStream) -> io::Result<(PathBuf, Vec<u8>, JobLimits)> {
 	let artifact_path = framed_recv(stream).await?;
 	let artifact_path = bytes_to_path(&artifact_path).ok_or_else(|| {
 		io::Error::new(
@@ -146,7 +184,14 @@ async fn recv_request(stream: &mut UnixStream) -> io::Result<(PathBuf, Vec<u8>)>
 		)
 	})?;
 	let params = framed_recv(stream).await?;
-	Ok((artifact_path, params))
+	let limits = framed_recv(stream).await?;
+	let limits = JobLimits::decode(&mut &limits[..]).map_err(|e| {
+		io::Error::new(
+			io::ErrorKind::Other,
+			format!("execute pvf recv_request: failed to decode job limits: {:?}", e),
+		)
+	})?;
+	Ok((artifact_path, params, limits))
 }
 
 async fn send_response(stream: &mut UnixStream, response: Response) -> io::Result<()> {
@@ -165,9 +210,11 @@ async fn recv_response(stream: &mut UnixStream) -> io::Result<Response> {
 
 #[derive(Encode, Decode)]
 enum Response {
-	Ok { result_descriptor: ValidationResult, duration_ms: u64 },
+	Ok { result_descriptor: ValidationResult, duration_ms: u64, usage: JobUsage },
 	InvalidCandidate(String),
 	InternalError(String),
+	MemoryLimitExceeded(JobUsage),
+	ForbiddenSyscall,
 }
 
 impl Response {
@@ -188,32 +235,77 @@ pub fn worker_entrypoint(socket_path: &str) {
 			io::Error::new(io::ErrorKind::Other, format!("cannot create executor: {}", e))
 		})?;
 		loop {
-			let (artifact_path, params) = recv_request(&mut stream).await?;
+			let (artifact_path, params, limits) = recv_request(&mut stream).await?;
 			gum::debug!(
 				target: LOG_TARGET,
 				worker_pid = %std::process::id(),
 				"worker: validating artifact {}",
 				artifact_path.display(),
 			);
-			let response = validate_using_artifact(&artifact_path, &params, &executor).await;
+
+			if limits.forbid_syscalls {
+				let report = frame(&Response::ForbiddenSyscall.encode());
+				sandbox::set_violation_report(stream.as_raw_fd(), &report)?;
+			}
+
+			let executor = executor.clone();
+			let job_outcome = sandbox::run_job(&limits, EXECUTE_THREAD_STACK_SIZE, move || {
+				validate_using_artifact(artifact_path, params, limits, executor)
+			});
+
+			let response = match job_outcome {
+				Ok(JobOutcome::Finished(response, usage)) => match response {
+					Response::Ok { result_descriptor, duration_ms, .. } =>
+						Response::Ok { result_descriptor, duration_ms, usage },
+					response => response,
+				},
+				Ok(JobOutcome::Panicked(err)) => Response::format_invalid("execute", &err),
+				Ok(JobOutcome::MemoryLimitExceeded(usage)) => {
+					send_response(&mut stream, Response::MemoryLimitExceeded(usage)).await?;
+					// The job can't be stopped, so the whole worker has to go.
+					std::process::exit(1)
+				},
+				Err(err) => Response::InternalError(format!("cannot run the job: {}", err)),
+			};
 			send_response(&mut stream, response).await?;
+
+			if limits.forbid_syscalls {
+				// The syscall filter can't be lifted, so the next job needs a fresh worker.
+				std::process::exit(0)
+			}
 		}
 	});
 }
 
-async fn validate_using_artifact(
-	artifact_path: &Path,
-	params: &[u8],
-	executor: &Executor,
+fn validate_using_artifact(
+	artifact_path: PathBuf,
+	params: Vec<u8>,
+	limits: JobLimits,
+	executor: Executor,
 ) -> Response {
 	let validation_started_at = Instant::now();
+	let after_load = || {
+		if limits.forbid_syscalls {
+			// The artifact is mapped at this point, the PVF itself has no business touching
+			// the file system or the network.
+			if let Err(err) = sandbox::forbid_syscalls() {
+				gum::warn!(
+					target: LOG_TARGET,
+					worker_pid = %std::process::id(),
+					"worker: cannot install the syscall filter: {:?}",
+					err,
+				);
+			}
+		}
+		Ok(())
+	};
 	let descriptor_bytes = match unsafe {
 		// SAFETY: this should be safe since the compiled artifact passed here comes from the
 		//         file created by the prepare workers. These files are obtained by calling
 		//         [`executor_intf::prepare`].
-		executor.execute(artifact_path.as_ref(), params)
+		executor.execute(artifact_path.as_ref(), &params, after_load)
 	} {
-		Err(err) => return Response::format_invalid("execute", &err.to_string()),
+		Err(err) => return Response::format_invalid("execute", &err),
 		Ok(d) => d,
 	};
 
@@ -225,5 +317,6 @@ async fn validate_using_artifact(
 		Ok(r) => r,
 	};
 
-	Response::Ok { result_descriptor, duration_ms }
+	// The usage is filled in once the job thread is done.
+	Response::Ok { result_descriptor, duration_ms, usage: JobUsage::default() }
 }
@@ -101,65 +101,64 @@ pub fn prepare(blob: RuntimeBlob) -> Result<Vec<u8>, sc_executor_common::error::
 	sc_executor_wasmtime::prepare_runtime_artifact(blob, &CONFIG.semantics)
 }
 
+/// The stack size of the thread executing a PVF.
+///
+/// Wasmtime powers the Substrate Executor. It compiles the wasm bytecode into native code.
+/// That native code does not create any stacks and just reuses the stack of the thread that
+/// wasmtime was invoked from.
+///
+/// Also, we configure the executor to provide the deterministic stack and that requires
+/// supplying the amount of the native stack space that wasm is allowed to use. This is
+/// realized by supplying the limit into `wasmtime::Config::max_wasm_stack`.
+///
+/// There are quirks to that configuration knob:
+///
+/// 1. It only limits the amount of stack space consumed by wasm but does not ensure nor check
+///    that the stack space is actually available.
+///
+///    That means, if the calling thread has 1 MiB of stack space left and the wasm code consumes
+///    more, then the wasmtime limit will **not** trigger. Instead, the wasm code will hit the
+///    guard page and the Rust stack overflow handler will be triggered. That leads to an
+///    **abort**.
+///
+/// 2. It cannot and does not limit the stack space consumed by Rust code.
+///
+///    Meaning that if the wasm code leaves no stack space for Rust code, then the Rust code
+///    and that will abort the process as well.
+///
+/// Typically on Linux the main thread gets the stack size specified by the `ulimit` and
+/// typically it's configured to 8 MiB. Rust's spawned threads are 2 MiB. OTOH, the
+/// NATIVE_STACK_MAX is set to 256 MiB. Not nearly enough.
+///
+/// Hence the execution has to happen on a thread spawned with this stack size. A fresh thread is
+/// used for every execution, since the syscall filter installed after loading the artifact can't
+/// be lifted again.
+///
+/// The reasoning why we pick this particular size is:
+///
+/// The default Rust thread stack limit 2 MiB + 256 MiB wasm stack.
+pub const EXECUTE_THREAD_STACK_SIZE: usize = 2 * 1024 * 1024 + NATIVE_STACK_MAX as usize;
+
+#[derive(Clone)]
 pub struct Executor {
-	thread_pool: rayon::ThreadPool,
 	spawner: TaskSpawner,
 }
 
 impl Executor {
 	pub fn new() -> Result<Self, String> {
-		// Wasmtime powers the Substrate Executor. It compiles the wasm bytecode into native code.
-		// That native code does not create any stacks and just reuses the stack of the thread that
-		// wasmtime was invoked from.
-		//
-		// Also, we configure the executor to provide the deterministic stack and that requires
-		// supplying the amount of the native stack space that wasm is allowed to use. This is
-		// realized by supplying the limit into `wasmtime::Config::max_wasm_stack`.
-		//
-		// There are quirks to that configuration knob:
-		//
-		// 1. It only limits the amount of stack space consumed by wasm but does not ensure nor check
-		//    that the stack space is actually available.
-		//
-		//    That means, if the calling thread has 1 MiB of stack space left and the wasm code consumes
-		//    more, then the wasmtime limit will **not** trigger. Instead, the wasm code will hit the
-		//    guard page and the Rust stack overflow handler will be triggered. That leads to an
-		//    **abort**.
-		//
-		// 2. It cannot and does not limit the stack space consumed by Rust code.
-		//
-		//    Meaning that if the wasm code leaves no stack space for Rust code, then the Rust code
-		//    and that will abort the process as well.
-		//
-		// Typically on Linux the main thread gets the stack size specified by the `ulimit` and
-		// typically it's configured to 8 MiB. Rust's spawned threads are 2 MiB. OTOH, the
-		// NATIVE_STACK_MAX is set to 256 MiB. Not nearly enough.
-		//
-		// Hence we need to increase it.
-		//
-		// The simplest way to fix that is to spawn a thread with the desired stack limit. In order
-		// to avoid costs of creating a thread, we use a thread pool. The execution is
-		// single-threaded hence the thread pool has only one thread.
-		//
-		// The reasoning why we pick this particular size is:
-		//
-		// The default Rust thread stack limit 2 MiB + 256 MiB wasm stack.
-		let thread_stack_size = 2 * 1024 * 1024 + NATIVE_STACK_MAX as usize;
-		let thread_pool = rayon::ThreadPoolBuilder::new()
-			.num_threads(1)
-			.stack_size(thread_stack_size)
-			.build()
-			.map_err(|e| format!("Failed to create thread pool: {:?}", e))?;
-
 		let spawner =
 			TaskSpawner::new().map_err(|e| format!("cannot create task spawner: {}", e))?;
 
-		Ok(Self { thread_pool, spawner })
+		Ok(Self { spawner })
 	}
 
 	/// Executes the given PVF in the form of a compiled artifact and returns the result of execution
 	/// upon success.
 	///
+	/// `after_load` is called once the artifact is loaded, before the PVF is invoked.
+	///
+	/// Must be called on a thread with a stack of at least [`EXECUTE_THREAD_STACK_SIZE`].
+	///
 	/// # Safety
 	///
 	/// The caller must ensure that the compiled artifact passed here was:
@@ -174,30 +173,18 @@ impl Executor {
 		&self,
 		compiled_artifact_path: &Path,
 		params: &[u8],
+		after_load: impl FnOnce() -> Result<(), String>,
 	) -> Result<Vec<u8>, String> {
-		let spawner = self.spawner.clone();
-		let mut result = None;
-		self.thread_pool.scope({
-			let result = &mut result;
-			move |s| {
-				s.spawn(move |_| {
-					// spawn does not return a value, so we need to use a variable to pass the result.
-					*result = Some(
-						do_execute(compiled_artifact_path, params, spawner)
-							.map_err(|err| format!("execute error: {:?}", err)),
-					);
-				});
-			}
-		});
-		result.unwrap_or_else(|| Err("rayon thread pool spawn failed".to_string()))
+		do_execute(compiled_artifact_path, params, after_load, self.spawner.clone())
 	}
 }
 
 unsafe fn do_execute(
 	compiled_artifact_path: &Path,
 	params: &[u8],
+	after_load: impl FnOnce() -> Result<(), String>,
 	spawner: impl sp_core::traits::SpawnNamed + 'static,
-) -> Result<Vec<u8>, sc_executor_common::error::Error> {
+) -> Result<Vec<u8>, String> {
 	let mut extensions = sp_externalities::Extensions::new();
 
 	extensions.register(sp_core::traits::TaskExecutorExt::new(spawner));
@@ -205,13 +192,19 @@ unsafe fn do_execute(
 
 	let mut ext = ValidationExternalities(extensions);
 
+	let runtime = sc_executor_wasmtime::create_runtime_from_artifact::<HostFunctions>(
+		compiled_artifact_path,
+		CONFIG,
+	)
+	.map_err(|err| format!("execute error: {:?}", err))?;
+
+	after_load()?;
+
 	sc_executor::with_externalities_safe(&mut ext, || {
-		let runtime = sc_executor_wasmtime::create_runtime_from_artifact::<HostFunctions>(
-			compiled_artifact_path,
-			CONFIG,
-		)?;
 		runtime.new_instance()?.call(InvokeMethod::Export("validate_block"), params)
-	})?
+	})
+	.and_then(|result| result)
+	.map_err(|err| format!("execute error: {:?}", err))
 }
 
 type HostFunctions = (
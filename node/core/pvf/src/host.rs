@@ -21,10 +21,12 @@
 //! [`ValidationHost`], that allows communication with that event-loop.
 
 use crate::{
-	artifacts::{ArtifactId, ArtifactPathId, ArtifactState, Artifacts},
+	artifacts::{header_path, ArtifactId, ArtifactPathId, ArtifactState, Artifacts},
 	execute,
 	metrics::Metrics,
-	prepare, PrepareResult, Priority, Pvf, ValidationError, LOG_TARGET,
+	prepare,
+	sandbox::JobLimits,
+	PrepareResult, Priority, Pvf, ValidationError, LOG_TARGET,
 };
 use always_assert::never;
 use async_std::path::{Path, PathBuf};
@@ -140,6 +142,16 @@ pub struct Config {
 	pub execute_worker_spawn_timeout: Duration,
 	/// The maximum number of execute workers that can run at the same time.
 	pub execute_workers_max_num: usize,
+	/// The memory a single preparation job may use, in bytes. `None` means unlimited.
+	pub prepare_job_max_memory: Option<u64>,
+	/// The memory a single execution job may use, in bytes. `None` means unlimited.
+	pub execute_job_max_memory: Option<u64>,
+	/// Whether the jobs are prevented from accessing the file system and the network once the
+	/// code is loaded. Only supported on Linux.
+	///
+	/// The restriction can't be lifted from a worker again, so every job gets a fresh worker, which
+	/// adds the spawn time of a worker to every job. Hence disabled by default.
+	pub forbid_job_syscalls: bool,
 }
 
 impl Config {
@@ -158,6 +170,9 @@ impl Config {
 			execute_worker_program_path: program_path,
 			execute_worker_spawn_timeout: Duration::from_secs(3),
 			execute_workers_max_num: 2,
+			prepare_job_max_memory: Some(2 * 1024 * 1024 * 1024),
+			execute_job_max_memory: Some(1024 * 1024 * 1024),
+			forbid_job_syscalls: false,
 		}
 	}
 }
@@ -180,6 +195,10 @@ pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<O
 		config.prepare_worker_program_path.clone(),
 		config.cache_path.clone(),
 		config.prepare_worker_spawn_timeout,
+		JobLimits {
+			max_memory: config.prepare_job_max_memory,
+			forbid_syscalls: config.forbid_job_syscalls,
+		},
 	);
 
 	let (to_prepare_queue_tx, from_prepare_queue_rx, run_prepare_queue) = prepare::start_queue(
@@ -196,6 +215,10 @@ pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<O
 		config.execute_worker_program_path.to_owned(),
 		config.execute_workers_max_num,
 		config.execute_worker_spawn_timeout,
+		JobLimits {
+			max_memory: config.execute_job_max_memory,
+			forbid_syscalls: config.forbid_job_syscalls,
+		},
 	);
 
 	let (to_sweeper_tx, to_sweeper_rx) = mpsc::channel(100);
@@ -239,6 +262,7 @@ pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<O
 struct PendingExecutionRequest {
 	execution_timeout: Duration,
 	params: Vec<u8>,
+	priority: Priority,
 	result_tx: ResultSender,
 }
 
@@ -253,11 +277,13 @@ impl AwaitingPrepare {
 		artifact_id: ArtifactId,
 		execution_timeout: Duration,
 		params: Vec<u8>,
+		priority: Priority,
 		result_tx: ResultSender,
 	) {
 		self.0.entry(artifact_id).or_default().push(PendingExecutionRequest {
 			execution_timeout,
 			params,
+			priority,
 			result_tx,
 		});
 	}
@@ -439,9 +465,14 @@ async fn handle_precheck_pvf(
 			},
 		}
 	} else {
+		// Pre-checking is not time critical, so it should not delay the preparation of artifacts
+		// that are needed for backing or approval.
 		artifacts.insert_preparing(artifact_id, vec![result_sender]);
-		send_prepare(prepare_queue, prepare::ToQueue::Enqueue { priority: Priority::Normal, pvf })
-			.await?;
+		send_prepare(
+			prepare_queue,
+			prepare::ToQueue::Enqueue { priority: Priority::Background, pvf },
+		)
+		.await?;
 	}
 	Ok(())
 }
@@ -471,13 +502,20 @@ async fn handle_execute_pvf(
 						artifact: ArtifactPathId::new(artifact_id, cache_path),
 						execution_timeout,
 						params,
+						priority,
 						result_tx,
 					},
 				)
 				.await?;
 			},
 			ArtifactState::Preparing { waiting_for_response: _ } => {
-				awaiting_prepare.add(artifact_id, execution_timeout, params, result_tx);
+				// The preparation may have been enqueued for pre-checking with a lower priority.
+				send_prepare(
+					prepare_queue,
+					prepare::ToQueue::Amend { priority, artifact_id: artifact_id.clone() },
+				)
+				.await?;
+				awaiting_prepare.add(artifact_id, execution_timeout, params, priority, result_tx);
 			},
 			ArtifactState::FailedToProcess(error) => {
 				let _ = result_tx.send(Err(ValidationError::from(error.clone())));
@@ -489,7 +527,7 @@ async fn handle_execute_pvf(
 		artifacts.insert_preparing(artifact_id.clone(), Vec::new());
 		send_prepare(prepare_queue, prepare::ToQueue::Enqueue { priority, pvf }).await?;
 
-		awaiting_prepare.add(artifact_id, execution_timeout, params, result_tx);
+		awaiting_prepare.add(artifact_id, execution_timeout, params, priority, result_tx);
 	}
 
 	return Ok(())
@@ -510,7 +548,16 @@ async fn handle_heads_up(
 					*last_time_needed = now;
 				},
 				ArtifactState::Preparing { waiting_for_response: _ } => {
-					// The artifact is already being prepared, so we don't need to do anything.
+					// The artifact is already being prepared, but it may have been enqueued for
+					// pre-checking with a lower priority.
+					send_prepare(
+						prepare_queue,
+						prepare::ToQueue::Amend {
+							priority: Priority::Normal,
+							artifact_id: artifact_id.clone(),
+						},
+					)
+					.await?;
 				},
 				ArtifactState::FailedToProcess(_) => {},
 			}
@@ -576,7 +623,9 @@ async fn handle_prepare_done(
 	// It's finally time to dispatch all the execution requests that were waiting for this artifact
 	// to be prepared.
 	let pending_requests = awaiting_prepare.take(&artifact_id);
-	for PendingExecutionRequest { execution_timeout, params, result_tx } in pending_requests {
+	for PendingExecutionRequest { execution_timeout, params, priority, result_tx } in
+		pending_requests
+	{
 		if result_tx.is_canceled() {
 			// Preparation could've taken quite a bit of time and the requester may be not interested
 			// in execution anymore, in which case we just skip the request.
@@ -595,6 +644,7 @@ async fn handle_prepare_done(
 				artifact: ArtifactPathId::new(artifact_id.clone(), cache_path),
 				execution_timeout,
 				params,
+				priority,
 				result_tx,
 			},
 		)
@@ -642,7 +692,9 @@ async fn handle_cleanup_pulse(
 			"pruning artifact",
 		);
 		let artifact_path = artifact_id.path(cache_path);
+		let header_path = header_path(&artifact_path);
 		sweeper_tx.send(artifact_path).await.map_err(|_| Fatal)?;
+		sweeper_tx.send(header_path).await.map_err(|_| Fatal)?;
 	}
 
 	Ok(())
@@ -888,6 +940,7 @@ mod tests {
 			&mut test.run,
 			async {
 				assert_eq!(to_sweeper_rx.next().await.unwrap(), artifact_path(2));
+				assert_eq!(to_sweeper_rx.next().await.unwrap(), header_path(&artifact_path(2)));
 			}
 			.boxed(),
 		)
@@ -941,6 +994,11 @@ mod tests {
 			test.poll_and_recv_to_prepare_queue().await,
 			prepare::ToQueue::Enqueue { .. }
 		);
+		// The critical request raises the priority of the preparation.
+		assert_matches!(
+			test.poll_and_recv_to_prepare_queue().await,
+			prepare::ToQueue::Amend { priority: Priority::Critical, .. }
+		);
 		assert_matches!(
 			test.poll_and_recv_to_prepare_queue().await,
 			prepare::ToQueue::Enqueue { .. }
@@ -1107,10 +1165,14 @@ mod tests {
 		)
 		.await
 		.unwrap();
-		// Received prepare request.
+		// Received prepare request, which is amended by the execution.
 		assert_matches!(
 			test.poll_and_recv_to_prepare_queue().await,
-			prepare::ToQueue::Enqueue { .. }
+			prepare::ToQueue::Enqueue { priority: Priority::Background, .. }
+		);
+		assert_matches!(
+			test.poll_and_recv_to_prepare_queue().await,
+			prepare::ToQueue::Amend { priority: Priority::Critical, .. }
 		);
 		test.from_prepare_queue_tx
 			.send(prepare::FromQueue { artifact_id: artifact_id(2), result: Ok(()) })
@@ -1127,6 +1189,46 @@ mod tests {
 		}
 	}
 
+	#[async_std::test]
+	async fn precheck_priority_is_raised() {
+		let mut test = Builder::default().build();
+		let mut host = test.host_handle();
+
+		let (result_tx, _result_rx) = oneshot::channel();
+		host.precheck_pvf(Pvf::from_discriminator(1), result_tx).await.unwrap();
+		assert_matches!(
+			test.poll_and_recv_to_prepare_queue().await,
+			prepare::ToQueue::Enqueue { priority: Priority::Background, .. }
+		);
+
+		// The PVF becomes active while the pre-check is still waiting.
+		host.heads_up(vec![Pvf::from_discriminator(1)]).await.unwrap();
+		assert_matches!(
+			test.poll_and_recv_to_prepare_queue().await,
+			prepare::ToQueue::Amend { priority: Priority::Normal, artifact_id: id } => {
+				assert_eq!(id, artifact_id(1));
+			}
+		);
+
+		// And is needed for backing.
+		let (result_tx, _result_rx) = oneshot::channel();
+		host.execute_pvf(
+			Pvf::from_discriminator(1),
+			TEST_EXECUTION_TIMEOUT,
+			b"pvf1".to_vec(),
+			Priority::Critical,
+			result_tx,
+		)
+		.await
+		.unwrap();
+		assert_matches!(
+			test.poll_and_recv_to_prepare_queue().await,
+			prepare::ToQueue::Amend { priority: Priority::Critical, artifact_id: id } => {
+				assert_eq!(id, artifact_id(1));
+			}
+		);
+	}
+
 	#[async_std::test]
 	async fn cancellation() {
 		let mut test = Builder::default().build();
@@ -46,6 +46,10 @@
 //!
 //! Priority can never go down, only up.
 //!
+//! Pre-checking a PVF is done with the background priority. Both queues keep a separate queue per
+//! priority and always pick a job of the highest priority waiting, so pre-checking never delays
+//! the jobs needed for backing or approval.
+//!
 //! # Under the hood
 //!
 //! Under the hood, the validation host is built using a bunch of communicating processes, not
@@ -67,11 +71,18 @@
 //!
 //! The artifact is saved on disk and is also tracked by an in memory table. This in memory table
 //! doesn't contain the artifact contents though, only a flag that the given artifact is compiled.
+//! On startup the table is rebuilt from the artifacts found on disk. Every artifact has a header
+//! recording the build that prepared it and a checksum; artifacts failing either check are removed
+//! and prepared again when needed.
 //!
 //! The execute workers will be fed by the requests from the execution queue, which is basically a
 //! combination of a path to the compiled artifact and the
 //! [`params`][`polkadot_parachain::primitives::ValidationParams`].
 //!
+//! Both kinds of workers run each job under a memory ceiling and, on Linux, with the filesystem and
+//! network syscalls banned once the code is loaded. A job violating either is reported with a
+//! dedicated error and the worker is killed. See [`Config`] for the knobs.
+//!
 //! Each fixed interval of time a pruning task will run. This task will remove all artifacts that
 //! weren't used or received a heads up signal for a while.
 
@@ -84,6 +95,8 @@ mod metrics;
 mod prepare;
 mod priority;
 mod pvf;
+mod replay;
+mod sandbox;
 mod worker_common;
 
 #[doc(hidden)]
@@ -95,6 +108,7 @@ pub use sp_tracing;
 pub use error::{InvalidCandidate, PrepareError, PrepareResult, ValidationError};
 pub use priority::Priority;
 pub use pvf::Pvf;
+pub use replay::{replay_in_process, replay_via_host, HostReplayReport, PhaseReport, ReplayReport};
 
 pub use host::{start, Config, ValidationHost};
 pub use metrics::Metrics;
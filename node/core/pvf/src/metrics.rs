@@ -16,7 +16,9 @@
 
 //! Prometheus metrics related to the validation host.
 
+use crate::{sandbox::JobUsage, Priority};
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
+use std::time::Duration;
 
 /// Validation host metrics.
 #[derive(Default, Clone)]
@@ -33,6 +35,16 @@ impl Metrics {
 		WorkerRelatedMetrics { metrics: self, flavor: WorkerFlavor::Execute }
 	}
 
+	/// Returns a handle to submit metrics of the prepare queue.
+	pub(crate) fn prepare_queue(&'_ self) -> QueueRelatedMetrics<'_> {
+		QueueRelatedMetrics { metrics: self, flavor: WorkerFlavor::Prepare }
+	}
+
+	/// Returns a handle to submit metrics of the execute queue.
+	pub(crate) fn execute_queue(&'_ self) -> QueueRelatedMetrics<'_> {
+		QueueRelatedMetrics { metrics: self, flavor: WorkerFlavor::Execute }
+	}
+
 	/// When preparation pipeline had a new item enqueued.
 	pub(crate) fn prepare_enqueued(&self) {
 		if let Some(metrics) = &self.0 {
@@ -79,12 +91,19 @@ struct MetricsInner {
 	worker_spawning: prometheus::CounterVec<prometheus::U64>,
 	worker_spawned: prometheus::CounterVec<prometheus::U64>,
 	worker_retired: prometheus::CounterVec<prometheus::U64>,
+	worker_spawn_time: prometheus::HistogramVec,
 	prepare_enqueued: prometheus::Counter<prometheus::U64>,
 	prepare_concluded: prometheus::Counter<prometheus::U64>,
 	execute_enqueued: prometheus::Counter<prometheus::U64>,
 	execute_finished: prometheus::Counter<prometheus::U64>,
 	preparation_time: prometheus::Histogram,
 	execution_time: prometheus::Histogram,
+	job_cpu_time: prometheus::HistogramVec,
+	job_peak_memory: prometheus::HistogramVec,
+	sandbox_violations: prometheus::CounterVec<prometheus::U64>,
+	queue_depth: prometheus::GaugeVec<prometheus::U64>,
+	queue_wait_time: prometheus::HistogramVec,
+	queue_preemptions: prometheus::CounterVec<prometheus::U64>,
 }
 
 impl metrics::Metrics for Metrics {
@@ -120,6 +139,17 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			worker_spawn_time: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_pvf_worker_spawn_time",
+						"Time it took to spawn a worker until it was ready for jobs in seconds",
+					)
+					.buckets(vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 3.0, 5.0]),
+					&["flavor"],
+				)?,
+				registry,
+			)?,
 			prepare_enqueued: prometheus::register(
 				prometheus::Counter::new(
 					"polkadot_pvf_prepare_enqueued",
@@ -191,6 +221,76 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			job_cpu_time: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_pvf_job_cpu_time",
+						"CPU time spent by the workers on a single job in seconds",
+					)
+					.buckets(vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0]),
+					&["flavor"],
+				)?,
+				registry,
+			)?,
+			job_peak_memory: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_pvf_job_peak_memory",
+						"Highest resident memory of a worker while running a single job in bytes",
+					)
+					.buckets(
+						[32, 64, 128, 256, 512, 1024, 2048, 4096]
+							.iter()
+							.map(|mib| (mib * 1024 * 1024) as f64)
+							.collect(),
+					),
+					&["flavor"],
+				)?,
+				registry,
+			)?,
+			sandbox_violations: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_pvf_sandbox_violations",
+						"The total number of jobs which exceeded the limits of the worker sandbox",
+					),
+					&["flavor", "kind"],
+				)?,
+				registry,
+			)?,
+			queue_depth: prometheus::register(
+				prometheus::GaugeVec::new(
+					prometheus::Opts::new(
+						"polkadot_pvf_queue_depth",
+						"The number of jobs waiting for a worker",
+					),
+					&["flavor", "priority"],
+				)?,
+				registry,
+			)?,
+			queue_wait_time: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_pvf_queue_wait_time",
+						"Time a job spent waiting for a worker in seconds",
+					)
+					.buckets(vec![
+						0.001, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0,
+					]),
+					&["flavor", "priority"],
+				)?,
+				registry,
+			)?,
+			queue_preemptions: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_pvf_queue_preemptions",
+						"Number of times waiting jobs were overtaken by a job of a higher priority",
+					),
+					&["flavor", "priority"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(inner)))
 	}
@@ -230,10 +330,81 @@ impl<'a> WorkerRelatedMetrics<'a> {
 		}
 	}
 
+	/// Time between starting to spawn a worker and the worker being ready for jobs.
+	pub(crate) fn time_spawn(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
+		self.metrics.0.as_ref().map(|metrics| {
+			metrics
+				.worker_spawn_time
+				.with_label_values(&[self.flavor.as_label()])
+				.start_timer()
+		})
+	}
+
 	/// When the worker was killed or died.
 	pub(crate) fn on_retired(&self) {
 		if let Some(metrics) = &self.metrics.0 {
 			metrics.worker_retired.with_label_values(&[self.flavor.as_label()]).inc();
 		}
 	}
+
+	/// When the worker finished a job, with the resources consumed by it.
+	pub(crate) fn on_job_usage(&self, usage: &JobUsage) {
+		if let Some(metrics) = &self.metrics.0 {
+			metrics
+				.job_cpu_time
+				.with_label_values(&[self.flavor.as_label()])
+				.observe(usage.cpu_time_ms as f64 / 1000.0);
+			metrics
+				.job_peak_memory
+				.with_label_values(&[self.flavor.as_label()])
+				.observe(usage.peak_memory as f64);
+		}
+	}
+
+	/// When a job exceeded the limits of the sandbox, either `"memory"` or `"syscall"`.
+	pub(crate) fn on_sandbox_violation(&self, kind: &'static str) {
+		if let Some(metrics) = &self.metrics.0 {
+			metrics
+				.sandbox_violations
+				.with_label_values(&[self.flavor.as_label(), kind])
+				.inc();
+		}
+	}
+}
+
+pub(crate) struct QueueRelatedMetrics<'a> {
+	metrics: &'a Metrics,
+	flavor: WorkerFlavor,
+}
+
+impl<'a> QueueRelatedMetrics<'a> {
+	/// When the number of jobs of the given priority waiting for a worker changed.
+	pub(crate) fn on_depth_changed(&self, priority: Priority, depth: usize) {
+		if let Some(metrics) = &self.metrics.0 {
+			metrics
+				.queue_depth
+				.with_label_values(&[self.flavor.as_label(), priority.as_label()])
+				.set(depth as u64);
+		}
+	}
+
+	/// When a job was handed to a worker after waiting for the given time.
+	pub(crate) fn on_dispatched(&self, priority: Priority, waited: Duration) {
+		if let Some(metrics) = &self.metrics.0 {
+			metrics
+				.queue_wait_time
+				.with_label_values(&[self.flavor.as_label(), priority.as_label()])
+				.observe(waited.as_secs_f64());
+		}
+	}
+
+	/// When the waiting jobs of the given priority were overtaken by a job of a higher one.
+	pub(crate) fn on_preempted(&self, priority: Priority) {
+		if let Some(metrics) = &self.metrics.0 {
+			metrics
+				.queue_preemptions
+				.with_label_values(&[self.flavor.as_label(), priority.as_label()])
+				.inc();
+		}
+	}
 }
@@ -18,6 +18,7 @@ use super::worker::{self, Outcome};
 use crate::{
 	error::{PrepareError, PrepareResult},
 	metrics::Metrics,
+	sandbox::JobLimits,
 	worker_common::{IdleWorker, WorkerHandle},
 	LOG_TARGET,
 };
@@ -107,6 +108,7 @@ struct Pool {
 	program_path: PathBuf,
 	cache_path: PathBuf,
 	spawn_timeout: Duration,
+	job_limits: JobLimits,
 	to_pool: mpsc::Receiver<ToPool>,
 	from_pool: mpsc::UnboundedSender<FromPool>,
 	spawned: HopSlotMap<Worker, WorkerData>,
@@ -122,6 +124,7 @@ async fn run(
 		program_path,
 		cache_path,
 		spawn_timeout,
+		job_limits,
 		to_pool,
 		mut from_pool,
 		mut spawned,
@@ -149,6 +152,7 @@ async fn run(
 					&program_path,
 					&cache_path,
 					spawn_timeout,
+					job_limits,
 					&mut spawned,
 					&mut mux,
 					to_pool,
@@ -195,6 +199,7 @@ fn handle_to_pool(
 	program_path: &Path,
 	cache_path: &Path,
 	spawn_timeout: Duration,
+	job_limits: JobLimits,
 	spawned: &mut HopSlotMap<Worker, WorkerData>,
 	mux: &mut Mux,
 	to_pool: ToPool,
@@ -203,7 +208,9 @@ fn handle_to_pool(
 		ToPool::Spawn => {
 			gum::debug!(target: LOG_TARGET, "spawning a new prepare worker");
 			metrics.prepare_worker().on_begin_spawn();
-			mux.push(spawn_worker_task(program_path.to_owned(), spawn_timeout).boxed());
+			mux.push(
+				spawn_worker_task(program_path.to_owned(), spawn_timeout, metrics.clone()).boxed(),
+			);
 		},
 		ToPool::StartWork { worker, code, artifact_path } => {
 			if let Some(data) = spawned.get_mut(worker) {
@@ -216,6 +223,7 @@ fn handle_to_pool(
 							code,
 							cache_path.to_owned(),
 							artifact_path,
+							job_limits,
 							preparation_timer,
 						)
 						.boxed(),
@@ -241,13 +249,21 @@ fn handle_to_pool(
 	}
 }
 
-async fn spawn_worker_task(program_path: PathBuf, spawn_timeout: Duration) -> PoolEvent {
+async fn spawn_worker_task(
+	program_path: PathBuf,
+	spawn_timeout: Duration,
+	metrics: Metrics,
+) -> PoolEvent {
 	use futures_timer::Delay;
 
 	loop {
+		let spawn_timer = metrics.prepare_worker().time_spawn();
 		match worker::spawn(&program_path, spawn_timeout).await {
 			Ok((idle, handle)) => break PoolEvent::Spawn(idle, handle),
 			Err(err) => {
+				if let Some(timer) = spawn_timer {
+					timer.stop_and_discard();
+				}
 				gum::warn!(target: LOG_TARGET, "failed to spawn a prepare worker: {:?}", err);
 
 				// Assume that the failure intermittent and retry after a delay.
@@ -263,9 +279,10 @@ async fn start_work_task<Timer>(
 	code: Arc<Vec<u8>>,
 	cache_path: PathBuf,
 	artifact_path: PathBuf,
+	job_limits: JobLimits,
 	_preparation_timer: Option<Timer>,
 ) -> PoolEvent {
-	let outcome = worker::start_work(idle, code, &cache_path, artifact_path).await;
+	let outcome = worker::start_work(idle, code, &cache_path, artifact_path, job_limits).await;
 	PoolEvent::StartWork(worker, outcome)
 }
 
@@ -287,7 +304,19 @@ fn handle_mux(
 		},
 		PoolEvent::StartWork(worker, outcome) => {
 			match outcome {
-				Outcome::Concluded { worker: idle, result } => {
+				Outcome::Concluded { worker: None, result, usage } => {
+					metrics.prepare_worker().on_job_usage(&usage);
+
+					// The worker exited after the job.
+					if attempt_retire(metrics, spawned, worker) {
+						reply(from_pool, FromPool::Concluded { worker, rip: true, result })?;
+					}
+
+					Ok(())
+				},
+				Outcome::Concluded { worker: Some(idle), result, usage } => {
+					metrics.prepare_worker().on_job_usage(&usage);
+
 					let data = match spawned.get_mut(worker) {
 						None => {
 							// Perhaps the worker was killed meanwhile and the result is no longer
@@ -306,6 +335,21 @@ fn handle_mux(
 
 					Ok(())
 				},
+				Outcome::SandboxViolation(err) => {
+					let kind = match err {
+						PrepareError::MemoryLimitExceeded => "memory",
+						_ => "syscall",
+					};
+					metrics.prepare_worker().on_sandbox_violation(kind);
+					if attempt_retire(metrics, spawned, worker) {
+						reply(
+							from_pool,
+							FromPool::Concluded { worker, rip: true, result: Err(err) },
+						)?;
+					}
+
+					Ok(())
+				},
 				Outcome::Unreachable => {
 					if attempt_retire(metrics, spawned, worker) {
 						reply(from_pool, FromPool::Rip(worker))?;
@@ -375,6 +419,7 @@ pub fn start(
 	program_path: PathBuf,
 	cache_path: PathBuf,
 	spawn_timeout: Duration,
+	job_limits: JobLimits,
 ) -> (mpsc::Sender<ToPool>, mpsc::UnboundedReceiver<FromPool>, impl Future<Output = ()>) {
 	let (to_pool_tx, to_pool_rx) = mpsc::channel(10);
 	let (from_pool_tx, from_pool_rx) = mpsc::unbounded();
@@ -384,6 +429,7 @@ pub fn start(
 		program_path,
 		cache_path,
 		spawn_timeout,
+		job_limits,
 		to_pool: to_pool_rx,
 		from_pool: from_pool_tx,
 		spawned: HopSlotMap::with_capacity_and_key(20),
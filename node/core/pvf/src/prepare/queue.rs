@@ -21,7 +21,10 @@ use crate::{artifacts::ArtifactId, metrics::Metrics, PrepareResult, Priority, Pv
 use always_assert::{always, never};
 use async_std::path::PathBuf;
 use futures::{channel::mpsc, stream::StreamExt as _, Future, SinkExt};
-use std::collections::{HashMap, VecDeque};
+use std::{
+	collections::{HashMap, VecDeque},
+	time::Instant,
+};
 
 /// A request to pool.
 #[derive(Debug)]
@@ -31,6 +34,11 @@ pub enum ToQueue {
 	/// Note that it is incorrect to enqueue the same PVF again without first receiving the
 	/// [`FromQueue`] response.
 	Enqueue { priority: Priority, pvf: Pvf },
+	/// Raises the priority of an enqueued PVF, e.g. once it is needed for execution while its
+	/// pre-checking is still waiting for a worker.
+	///
+	/// Has no effect if the preparation already started or the priority is not higher.
+	Amend { priority: Priority, artifact_id: ArtifactId },
 }
 
 /// A response from queue.
@@ -77,6 +85,8 @@ struct JobData {
 	priority: Priority,
 	pvf: Pvf,
 	worker: Option<Worker>,
+	/// When the job started to wait for a worker.
+	waiting_since: Instant,
 }
 
 #[derive(Default)]
@@ -92,15 +102,26 @@ impl WorkerData {
 
 /// A queue structured like this is prone to starving, however, we don't care that much since we expect
 ///  there is going to be a limited number of critical jobs and we don't really care if background starve.
-#[derive(Default)]
 struct Unscheduled {
+	background: VecDeque<Job>,
 	normal: VecDeque<Job>,
 	critical: VecDeque<Job>,
+	metrics: Metrics,
 }
 
 impl Unscheduled {
+	fn new(metrics: Metrics) -> Self {
+		Self {
+			background: VecDeque::new(),
+			normal: VecDeque::new(),
+			critical: VecDeque::new(),
+			metrics,
+		}
+	}
+
 	fn queue_mut(&mut self, prio: Priority) -> &mut VecDeque<Job> {
 		match prio {
+			Priority::Background => &mut self.background,
 			Priority::Normal => &mut self.normal,
 			Priority::Critical => &mut self.critical,
 		}
@@ -108,19 +129,52 @@ impl Unscheduled {
 
 	fn add(&mut self, prio: Priority, job: Job) {
 		self.queue_mut(prio).push_back(job);
+		self.note_depth(prio);
 	}
 
 	fn readd(&mut self, prio: Priority, job: Job) {
 		self.queue_mut(prio).push_front(job);
+		self.note_depth(prio);
+	}
+
+	/// Removes the job from the queue of the given priority, returns `false` if it wasn't there.
+	fn remove(&mut self, prio: Priority, job: Job) -> bool {
+		let queue = self.queue_mut(prio);
+		match queue.iter().position(|j| *j == job) {
+			Some(index) => {
+				let _ = queue.remove(index);
+				self.note_depth(prio);
+				true
+			},
+			None => false,
+		}
 	}
 
 	fn is_empty(&self) -> bool {
-		self.normal.is_empty() && self.critical.is_empty()
+		self.background.is_empty() && self.normal.is_empty() && self.critical.is_empty()
 	}
 
 	fn next(&mut self) -> Option<Job> {
-		let mut check = |prio: Priority| self.queue_mut(prio).pop_front();
-		check(Priority::Critical).or_else(|| check(Priority::Normal))
+		let prio = Priority::ALL
+			.iter()
+			.rev()
+			.copied()
+			.find(|prio| !self.queue_mut(*prio).is_empty())?;
+		let job = self.queue_mut(prio).pop_front();
+		self.note_depth(prio);
+
+		for lower in Priority::ALL.iter().copied().filter(|lower| *lower < prio) {
+			if !self.queue_mut(lower).is_empty() {
+				self.metrics.prepare_queue().on_preempted(lower);
+			}
+		}
+
+		job
+	}
+
+	fn note_depth(&mut self, prio: Priority) {
+		let depth = self.queue_mut(prio).len();
+		self.metrics.prepare_queue().on_depth_changed(prio, depth);
 	}
 }
 
@@ -165,6 +219,7 @@ impl Queue {
 		from_pool_rx: mpsc::UnboundedReceiver<pool::FromPool>,
 	) -> Self {
 		Self {
+			unscheduled: Unscheduled::new(metrics.clone()),
 			metrics,
 			to_queue_rx,
 			from_queue_tx,
@@ -174,7 +229,6 @@ impl Queue {
 			spawn_inflight: 0,
 			limits: Limits { hard_capacity, soft_capacity },
 			jobs: slotmap::SlotMap::with_key(),
-			unscheduled: Unscheduled::default(),
 			artifact_id_to_job: HashMap::new(),
 			workers: slotmap::SparseSecondaryMap::new(),
 		}
@@ -206,6 +260,9 @@ async fn handle_to_queue(queue: &mut Queue, to_queue: ToQueue) -> Result<(), Fat
 		ToQueue::Enqueue { priority, pvf } => {
 			handle_enqueue(queue, priority, pvf).await?;
 		},
+		ToQueue::Amend { priority, artifact_id } => {
+			handle_amend(queue, priority, artifact_id).await?;
+		},
 	}
 	Ok(())
 }
@@ -236,7 +293,10 @@ async fn handle_enqueue(queue: &mut Queue, priority: Priority, pvf: Pvf) -> Resu
 		return Ok(())
 	}
 
-	let job = queue.jobs.insert(JobData { priority, pvf, worker: None });
+	let job =
+		queue
+			.jobs
+			.insert(JobData { priority, pvf, worker: None, waiting_since: Instant::now() });
 	queue.artifact_id_to_job.insert(artifact_id, job);
 
 	if let Some(available) = find_idle_worker(queue) {
@@ -251,6 +311,39 @@ async fn handle_enqueue(queue: &mut Queue, priority: Priority, pvf: Pvf) -> Resu
 	Ok(())
 }
 
+async fn handle_amend(
+	queue: &mut Queue,
+	priority: Priority,
+	artifact_id: ArtifactId,
+) -> Result<(), Fatal> {
+	let job = match queue.artifact_id_to_job.get(&artifact_id) {
+		Some(job) => *job,
+		// The preparation has concluded meanwhile.
+		None => return Ok(()),
+	};
+
+	let job_data = &mut queue.jobs[job];
+	if job_data.worker.is_some() || job_data.priority >= priority {
+		return Ok(())
+	}
+
+	gum::debug!(
+		target: LOG_TARGET,
+		validation_code_hash = ?job_data.pvf.code_hash,
+		old_priority = ?job_data.priority,
+		?priority,
+		"PVF preparation priority is raised.",
+	);
+
+	let old_priority = std::mem::replace(&mut job_data.priority, priority);
+	if queue.unscheduled.remove(old_priority, job) {
+		spawn_extra_worker(queue, priority.is_critical()).await?;
+		queue.unscheduled.add(priority, job);
+	}
+
+	Ok(())
+}
+
 fn find_idle_worker(queue: &mut Queue) -> Option<Worker> {
 	queue.workers.iter().filter(|(_, data)| data.is_idle()).map(|(k, _)| k).next()
 }
@@ -376,7 +469,11 @@ async fn handle_worker_rip(queue: &mut Queue, worker: Worker) -> Result<(), Fata
 	if let Some(WorkerData { job: Some(job), .. }) = worker_data {
 		// This is an edge case where the worker ripped after we sent assignment but before it
 		// was received by the pool.
-		let priority = queue.jobs.get(job).map(|data| data.priority).unwrap_or_else(|| {
+		let priority = queue.jobs.get_mut(job).map(|data| {
+			data.waiting_since = Instant::now();
+			data.priority
+		});
+		let priority = priority.unwrap_or_else(|| {
 			// job is inserted upon enqueue and removed on concluded signal;
 			// this is enclosed in the if statement that narrows the situation to before
 			// conclusion;
@@ -418,6 +515,11 @@ async fn assign(queue: &mut Queue, worker: Worker, job: Job) -> Result<(), Fatal
 	let artifact_id = job_data.pvf.as_artifact_id();
 	let artifact_path = artifact_id.path(&queue.cache_path);
 
+	queue
+		.metrics
+		.prepare_queue()
+		.on_dispatched(job_data.priority, job_data.waiting_since.elapsed());
+
 	job_data.worker = Some(worker);
 
 	queue.workers[worker].job = Some(job);
@@ -638,6 +740,66 @@ mod tests {
 		assert_eq!(test.poll_and_recv_to_pool().await, pool::ToPool::Spawn);
 	}
 
+	#[async_std::test]
+	async fn background_jobs_are_overtaken() {
+		let mut test = Test::new(1, 1);
+
+		test.send_queue(ToQueue::Enqueue { priority: Priority::Background, pvf: pvf(1) });
+		test.send_queue(ToQueue::Enqueue { priority: Priority::Normal, pvf: pvf(2) });
+		assert_eq!(test.poll_and_recv_to_pool().await, pool::ToPool::Spawn);
+
+		let w1 = test.workers.insert(());
+		test.send_from_pool(pool::FromPool::Spawned(w1));
+
+		// The normal job goes first, even though it was enqueued later.
+		let code = assert_matches!(
+			test.poll_and_recv_to_pool().await,
+			pool::ToPool::StartWork { code, .. } => code
+		);
+		assert_eq!(code, pvf(2).code);
+
+		test.send_from_pool(pool::FromPool::Concluded { worker: w1, rip: false, result: Ok(()) });
+		let code = assert_matches!(
+			test.poll_and_recv_to_pool().await,
+			pool::ToPool::StartWork { code, .. } => code
+		);
+		assert_eq!(code, pvf(1).code);
+	}
+
+	#[async_std::test]
+	async fn amended_jobs_overtake_background_jobs() {
+		let mut test = Test::new(1, 1);
+
+		test.send_queue(ToQueue::Enqueue { priority: Priority::Background, pvf: pvf(1) });
+		test.send_queue(ToQueue::Enqueue { priority: Priority::Background, pvf: pvf(2) });
+		test.send_queue(ToQueue::Amend {
+			priority: Priority::Normal,
+			artifact_id: pvf(2).as_artifact_id(),
+		});
+		// Lowering the priority has no effect.
+		test.send_queue(ToQueue::Amend {
+			priority: Priority::Background,
+			artifact_id: pvf(2).as_artifact_id(),
+		});
+		assert_eq!(test.poll_and_recv_to_pool().await, pool::ToPool::Spawn);
+
+		let w1 = test.workers.insert(());
+		test.send_from_pool(pool::FromPool::Spawned(w1));
+
+		let code = assert_matches!(
+			test.poll_and_recv_to_pool().await,
+			pool::ToPool::StartWork { code, .. } => code
+		);
+		assert_eq!(code, pvf(2).code);
+
+		test.send_from_pool(pool::FromPool::Concluded { worker: w1, rip: false, result: Ok(()) });
+		let code = assert_matches!(
+			test.poll_and_recv_to_pool().await,
+			pool::ToPool::StartWork { code, .. } => code
+		);
+		assert_eq!(code, pvf(1).code);
+	}
+
 	#[async_std::test]
 	async fn cull_unwanted() {
 		let mut test = Test::new(1, 2);
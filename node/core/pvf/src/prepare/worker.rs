@@ -15,10 +15,11 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-	artifacts::CompiledArtifact,
+	artifacts::{header_path, ArtifactHeader, CompiledArtifact},
 	error::{PrepareError, PrepareResult},
+	sandbox::{self, JobLimits, JobOutcome, JobUsage},
 	worker_common::{
-		bytes_to_path, framed_recv, framed_send, path_to_bytes, spawn_with_program_path,
+		bytes_to_path, frame, framed_recv, framed_send, path_to_bytes, spawn_with_program_path,
 		tmpfile_in, worker_event_loop, IdleWorker, SpawnErr, WorkerHandle,
 	},
 	LOG_TARGET,
@@ -30,12 +31,15 @@ use async_std::{
 };
 use parity_scale_codec::{Decode, Encode};
 use sp_core::hexdisplay::HexDisplay;
-use std::{panic, sync::Arc, time::Duration};
+use std::{io::Write as _, os::unix::io::AsRawFd, panic, sync::Arc, time::Duration};
 
 /// The time period after which the preparation worker is considered unresponsive and will be killed.
 // NOTE: If you change this make sure to fix the buckets of `pvf_preparation_time` metric.
 const COMPILATION_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// The stack size of the thread compiling a PVF. The same as the main thread typically gets.
+const PREPARE_THREAD_STACK_SIZE: usize = 8 * 1024 * 1024;
+
 /// Spawns a new worker with the given program path that acts as the worker and the spawn timeout.
 ///
 /// The program should be able to handle `<program-path> prepare-worker <socket-path>` invocation.
@@ -48,7 +52,13 @@ pub async fn spawn(
 
 pub enum Outcome {
 	/// The worker has finished the work assigned to it.
-	Concluded { worker: IdleWorker, result: PrepareResult },
+	///
+	/// The idle worker is only returned if the job did not forbid syscalls, otherwise the worker
+	/// exits after reporting the result.
+	Concluded { worker: Option<IdleWorker>, result: PrepareResult, usage: JobUsage },
+	/// The job exceeded the limits of the sandbox. The worker has terminated itself and is no
+	/// longer usable.
+	SandboxViolation(PrepareError),
 	/// The host tried to reach the worker but failed. This is most likely because the worked was
 	/// killed by the system.
 	Unreachable,
@@ -69,6 +79,7 @@ pub async fn start_work(
 	code: Arc<Vec<u8>>,
 	cache_path: &Path,
 	artifact_path: PathBuf,
+	limits: JobLimits,
 ) -> Outcome {
 	let IdleWorker { mut stream, pid } = worker;
 
@@ -80,7 +91,7 @@ pub async fn start_work(
 	);
 
 	with_tmp_file(pid, cache_path, |tmp_file| async move {
-		if let Err(err) = send_request(&mut stream, code, &tmp_file).await {
+		if let Err(err) = send_request(&mut stream, code, &tmp_file, limits).await {
 			gum::warn!(
 				target: LOG_TARGET,
 				worker_pid = %pid,
@@ -97,7 +108,8 @@ pub async fn start_work(
 
 		#[derive(Debug)]
 		enum Selected {
-			Done(PrepareResult),
+			Done(PrepareResult, JobUsage),
+			Violation(PrepareError),
 			IoErr,
 			Deadline,
 		}
@@ -106,9 +118,17 @@ pub async fn start_work(
 			match async_std::future::timeout(COMPILATION_TIMEOUT, framed_recv(&mut stream)).await {
 				Ok(Ok(response_bytes)) => {
 					// Received bytes from worker within the time limit.
-					// By convention we expect encoded `PrepareResult`.
-					if let Ok(result) = PrepareResult::decode(&mut response_bytes.as_slice()) {
-						if result.is_ok() {
+					// By convention we expect encoded `PrepareResult` along with `JobUsage`.
+					if let Ok((result, usage)) =
+						<(PrepareResult, JobUsage)>::decode(&mut response_bytes.as_slice())
+					{
+						if let Err(
+							err @ (PrepareError::MemoryLimitExceeded |
+							PrepareError::ForbiddenSyscall),
+						) = result
+						{
+							Selected::Violation(err)
+						} else if result.is_ok() {
 							gum::debug!(
 								target: LOG_TARGET,
 								worker_pid = %pid,
@@ -117,9 +137,9 @@ pub async fn start_work(
 								artifact_path.display(),
 							);
 
-							async_std::fs::rename(&tmp_file, &artifact_path)
+							promote_artifact(&tmp_file, &artifact_path)
 								.await
-								.map(|_| Selected::Done(result))
+								.map(|_| Selected::Done(result, usage))
 								.unwrap_or_else(|err| {
 									gum::warn!(
 										target: LOG_TARGET,
@@ -132,7 +152,7 @@ pub async fn start_work(
 									Selected::IoErr
 								})
 						} else {
-							Selected::Done(result)
+							Selected::Done(result, usage)
 						}
 					} else {
 						// We received invalid bytes from the worker.
@@ -163,8 +183,20 @@ pub async fn start_work(
 			};
 
 		match selected {
-			Selected::Done(result) =>
-				Outcome::Concluded { worker: IdleWorker { stream, pid }, result },
+			Selected::Done(result, usage) => Outcome::Concluded {
+				worker: (!limits.forbid_syscalls).then(|| IdleWorker { stream, pid }),
+				result,
+				usage,
+			},
+			Selected::Violation(err) => {
+				gum::warn!(
+					target: LOG_TARGET,
+					worker_pid = %pid,
+					"prepare job violated the sandbox: {:?}",
+					err,
+				);
+				Outcome::SandboxViolation(err)
+			},
 			Selected::Deadline => Outcome::TimedOut,
 			Selected::IoErr => Outcome::DidNotMakeIt,
 		}
@@ -172,6 +204,15 @@ pub async fn start_work(
 	.await
 }
 
+/// Move the artifact and its header from the temporary location to the final one.
+///
+/// The header goes first: if we are interrupted in between, the artifact either doesn't exist
+/// or doesn't match the header and will be discarded on the next start.
+async fn promote_artifact(tmp_file: &Path, artifact_path: &Path) -> io::Result<()> {
+	async_std::fs::rename(header_path(tmp_file), header_path(artifact_path)).await?;
+	async_std::fs::rename(tmp_file, artifact_path).await
+}
+
 /// Create a temporary file for an artifact at the given cache path and execute the given
 /// future/closure passing the file path in.
 ///
@@ -199,19 +240,21 @@ where
 	// The function called above is expected to move `tmp_file` to a new location upon success. However,
 	// the function may as well fail and in that case we should remove the tmp file here.
 	//
-	// In any case, we try to remove the file here so that there are no leftovers. We only report
-	// errors that are different from the `NotFound`.
-	match async_std::fs::remove_file(tmp_file).await {
-		Ok(()) => (),
-		Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
-		Err(err) => {
-			gum::warn!(
-				target: LOG_TARGET,
-				worker_pid = %pid,
-				"failed to remove the tmp file: {:?}",
-				err,
-			);
-		},
+	// In any case, we try to remove the file and its header here so that there are no leftovers.
+	// We only report errors that are different from the `NotFound`.
+	for file in [header_path(&tmp_file), tmp_file] {
+		match async_std::fs::remove_file(file).await {
+			Ok(()) => (),
+			Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+			Err(err) => {
+				gum::warn!(
+					target: LOG_TARGET,
+					worker_pid = %pid,
+					"failed to remove the tmp file: {:?}",
+					err,
+				);
+			},
+		}
 	}
 
 	outcome
@@ -221,13 +264,15 @@ async fn send_request(
 	stream: &mut UnixStream,
 	code: Arc<Vec<u8>>,
 	tmp_file: &Path,
+	limits: JobLimits,
 ) -> io::Result<()> {
 	framed_send(stream, &*code).await?;
 	framed_send(stream, path_to_bytes(tmp_file)).await?;
+	framed_send(stream, &limits.encode()).await?;
 	Ok(())
 }
 
-async fn recv_request(stream: &mut UnixStream) -> io::Result<(Vec<u8>, PathBuf)> {
+async fn recv_request(stream: &mut UnixStream) -> io::Result<(Vec<u8>, PathBuf, JobLimits)> {
 	let code = framed_recv(stream).await?;
 	let tmp_file = framed_recv(stream).await?;
 	let tmp_file = bytes_to_path(&tmp_file).ok_or_else(|| {
@@ -236,7 +281,14 @@ async fn recv_request(stream: &mut UnixStream) -> io::Result<(Vec<u8>, PathBuf)>
 			"prepare pvf recv_request: non utf-8 artifact path".to_string(),
 		)
 	})?;
-	Ok((code, tmp_file))
+	let limits = framed_recv(stream).await?;
+	let limits = JobLimits::decode(&mut &limits[..]).map_err(|e| {
+		io::Error::new(
+			io::ErrorKind::Other,
+			format!("prepare pvf recv_request: failed to decode job limits: {:?}", e),
+		)
+	})?;
+	Ok((code, tmp_file, limits))
 }
 
 /// The entrypoint that the spawned prepare worker should start with. The `socket_path` specifies
@@ -244,7 +296,7 @@ async fn recv_request(stream: &mut UnixStream) -> io::Result<(Vec<u8>, PathBuf)>
 pub fn worker_entrypoint(socket_path: &str) {
 	worker_event_loop("prepare", socket_path, |mut stream| async move {
 		loop {
-			let (code, dest) = recv_request(&mut stream).await?;
+			let (code, dest, limits) = recv_request(&mut stream).await?;
 
 			gum::debug!(
 				target: LOG_TARGET,
@@ -252,13 +304,49 @@ pub fn worker_entrypoint(socket_path: &str) {
 				"worker: preparing artifact",
 			);
 
-			let result = match prepare_artifact(&code) {
+			if limits.forbid_syscalls {
+				let report = (Err::<(), _>(PrepareError::ForbiddenSyscall), JobUsage::default());
+				sandbox::set_violation_report(stream.as_raw_fd(), &frame(&report.encode()))?;
+			}
+
+			// The files are created before the job starts, as it may forbid opening them. The
+			// host removes them again if the preparation fails.
+			let mut artifact_file = std::fs::File::create(&dest)?;
+			let mut header_file = std::fs::File::create(header_path(&dest))?;
+
+			let job_outcome = sandbox::run_job(&limits, PREPARE_THREAD_STACK_SIZE, move || {
+				if limits.forbid_syscalls {
+					// The code is in memory already, compilation doesn't need anything else.
+					if let Err(err) = sandbox::forbid_syscalls() {
+						gum::warn!(
+							target: LOG_TARGET,
+							worker_pid = %std::process::id(),
+							"worker: cannot install the syscall filter: {:?}",
+							err,
+						);
+					}
+				}
+				prepare_artifact(&code)
+			})?;
+
+			let (result, usage) = match job_outcome {
+				JobOutcome::Finished(result, usage) => (result, usage),
+				JobOutcome::Panicked(err) => (Err(PrepareError::Panic(err)), JobUsage::default()),
+				JobOutcome::MemoryLimitExceeded(usage) => {
+					let report = (Err::<(), _>(PrepareError::MemoryLimitExceeded), usage);
+					framed_send(&mut stream, &report.encode()).await?;
+					// The job can't be stopped, so the whole worker has to go.
+					std::process::exit(1)
+				},
+			};
+
+			let result = match result {
 				Err(err) => {
 					// Serialized error will be written into the socket.
 					Err(err)
 				},
 				Ok(compiled_artifact) => {
-					// Write the serialized artifact into a temp file.
+					// Write the serialized artifact and its header into temp files.
 					// PVF host only keeps artifacts statuses in its memory,
 					// successfully compiled code gets stored on the disk (and
 					// consequently deserialized by execute-workers). The prepare
@@ -271,13 +359,20 @@ pub fn worker_entrypoint(socket_path: &str) {
 						"worker: writing artifact to {}",
 						dest.display(),
 					);
-					async_std::fs::write(&dest, &compiled_artifact).await?;
+					artifact_file.write_all(compiled_artifact.as_ref())?;
+					header_file
+						.write_all(&ArtifactHeader::new(compiled_artifact.as_ref()).encode())?;
 
 					Ok(())
 				},
 			};
 
-			framed_send(&mut stream, result.encode().as_slice()).await?;
+			framed_send(&mut stream, (result, usage).encode().as_slice()).await?;
+
+			if limits.forbid_syscalls {
+				// The syscall filter can't be lifted, so the next job needs a fresh worker.
+				std::process::exit(0)
+			}
 		}
 	});
 }
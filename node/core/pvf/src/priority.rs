@@ -17,6 +17,9 @@
 /// A priority assigned to execution of a PVF.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Priority {
+	/// Jobs nobody is waiting on in the critical path, such as pre-checking a PVF. These are only
+	/// picked up when there is nothing more urgent.
+	Background,
 	/// Normal priority for things that do not require immediate response, but still need to be
 	/// done pretty quick.
 	///
@@ -33,4 +36,17 @@ impl Priority {
 	pub fn is_critical(self) -> bool {
 		self == Priority::Critical
 	}
+
+	/// All priorities, from the lowest to the highest.
+	pub(crate) const ALL: [Priority; 3] =
+		[Priority::Background, Priority::Normal, Priority::Critical];
+
+	/// Returns the label used for the priority in the metrics.
+	pub(crate) fn as_label(self) -> &'static str {
+		match self {
+			Priority::Background => "background",
+			Priority::Normal => "normal",
+			Priority::Critical => "critical",
+		}
+	}
 }
@@ -0,0 +1,196 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Replaying the validation of a single candidate, e.g. to reproduce what the node computed for a
+//! candidate that was disputed.
+//!
+//! [`replay_in_process`] runs the same prevalidation, preparation and execution steps as the
+//! workers, one after another in the current process, measuring each of them.
+//! [`replay_via_host`] sends the candidate through a [`ValidationHost`], i.e. the exact path the
+//! candidate validation subsystem takes. Only the durations are known for the latter, since the
+//! work happens in the worker processes.
+
+use crate::{
+	executor_intf::{prepare, prevalidate, Executor, EXECUTE_THREAD_STACK_SIZE},
+	sandbox::{self, JobLimits, JobOutcome, JobUsage},
+	Priority, Pvf, ValidationError, ValidationHost,
+};
+use futures::channel::oneshot;
+use parity_scale_codec::Decode;
+use polkadot_parachain::primitives::ValidationResult;
+use std::time::{Duration, Instant};
+
+/// The stack size of the threads running the phases before the execution.
+const PHASE_THREAD_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// The resources consumed by a single phase of the validation.
+#[derive(Debug, Clone)]
+pub struct PhaseReport {
+	/// The name of the phase.
+	pub name: &'static str,
+	/// The wall clock time the phase took.
+	pub duration: Duration,
+	/// The CPU time spent by the phase, in milliseconds.
+	pub cpu_time_ms: u64,
+	/// The peak resident memory of the process while the phase was running, in bytes.
+	pub peak_memory: u64,
+}
+
+/// The outcome of [`replay_in_process`].
+#[derive(Debug)]
+pub struct ReplayReport {
+	/// The phases which were run, in order. Phases after a failed one are not run.
+	pub phases: Vec<PhaseReport>,
+	/// The validation result or the error the validation stopped with.
+	pub result: Result<ValidationResult, String>,
+}
+
+/// The outcome of [`replay_via_host`].
+#[derive(Debug)]
+pub struct HostReplayReport {
+	/// The time it took the host to prepare the artifact.
+	pub preparation_time: Duration,
+	/// The time it took the host to execute the candidate, if it got that far.
+	pub execution_time: Option<Duration>,
+	/// The result as it would be reported to the candidate validation subsystem.
+	pub result: Result<ValidationResult, ValidationError>,
+}
+
+/// Validates the candidate in the current process, phase by phase.
+///
+/// `code` is the uncompressed validation code and `params` the encoded
+/// [`ValidationParams`][`polkadot_parachain::primitives::ValidationParams`].
+pub fn replay_in_process(code: Vec<u8>, params: Vec<u8>) -> ReplayReport {
+	let mut phases = Vec::new();
+	let result = run_phases(&mut phases, code, params);
+	ReplayReport { phases, result }
+}
+
+fn run_phases(
+	phases: &mut Vec<PhaseReport>,
+	code: Vec<u8>,
+	params: Vec<u8>,
+) -> Result<ValidationResult, String> {
+	let blob = run_phase(phases, "prevalidation", PHASE_THREAD_STACK_SIZE, move || {
+		prevalidate(&code).map_err(|e| format!("{:?}", e))
+	})?;
+	let artifact = run_phase(phases, "preparation", PHASE_THREAD_STACK_SIZE, move || {
+		prepare(blob).map_err(|e| format!("{:?}", e))
+	})?;
+
+	let tmpdir = tempfile::tempdir().map_err(|e| format!("cannot create a temp dir: {}", e))?;
+	let artifact_path = tmpdir.path().join("artifact");
+	std::fs::write(&artifact_path, &artifact)
+		.map_err(|e| format!("cannot write the artifact: {}", e))?;
+
+	let executor = Executor::new()?;
+	let descriptor_bytes = run_phase(phases, "execution", EXECUTE_THREAD_STACK_SIZE, move || {
+		unsafe {
+			// SAFETY: the artifact was just obtained by calling `prepare` and was written into a
+			//         temporary directory in an unmodified state.
+			executor.execute(&artifact_path, &params, || Ok(()))
+		}
+	})?;
+
+	ValidationResult::decode(&mut &descriptor_bytes[..])
+		.map_err(|e| format!("validation result decoding failed: {}", e))
+}
+
+fn run_phase<T, F>(
+	phases: &mut Vec<PhaseReport>,
+	name: &'static str,
+	stack_size: usize,
+	job: F,
+) -> Result<T, String>
+where
+	T: Send + 'static,
+	F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+	let started_at = Instant::now();
+	let outcome = sandbox::run_job(&JobLimits::default(), stack_size, job)
+		.map_err(|e| format!("{}: cannot run the phase: {}", name, e))?;
+	let duration = started_at.elapsed();
+
+	let (result, usage) = match outcome {
+		JobOutcome::Finished(result, usage) => (result, usage),
+		JobOutcome::Panicked(err) => (Err(format!("panic: {}", err)), JobUsage::default()),
+		JobOutcome::MemoryLimitExceeded(usage) => (Err("memory limit exceeded".into()), usage),
+	};
+	phases.push(PhaseReport {
+		name,
+		duration,
+		cpu_time_ms: usage.cpu_time_ms,
+		peak_memory: usage.peak_memory,
+	});
+
+	result.map_err(|e| format!("{}: {}", name, e))
+}
+
+/// Validates the candidate using the given validation host.
+///
+/// The artifact is prepared first, so that the preparation and the execution can be timed
+/// separately. `code` is the uncompressed validation code and `params` the encoded
+/// [`ValidationParams`][`polkadot_parachain::primitives::ValidationParams`].
+pub async fn replay_via_host(
+	host: &mut ValidationHost,
+	code: Vec<u8>,
+	params: Vec<u8>,
+	execution_timeout: Duration,
+) -> HostReplayReport {
+	let pvf = Pvf::from_code(code);
+
+	let started_at = Instant::now();
+	let (prepare_tx, prepare_rx) = oneshot::channel();
+	let prepared = match host.precheck_pvf(pvf.clone(), prepare_tx).await {
+		Ok(()) => prepare_rx
+			.await
+			.unwrap_or_else(|_| Err(crate::PrepareError::DidNotMakeIt))
+			.map_err(ValidationError::from),
+		Err(err) => Err(ValidationError::InternalError(err)),
+	};
+	let preparation_time = started_at.elapsed();
+
+	if let Err(err) = prepared {
+		return HostReplayReport { preparation_time, execution_time: None, result: Err(err) }
+	}
+
+	let started_at = Instant::now();
+	let (result_tx, result_rx) = oneshot::channel();
+	let result = match host
+		.execute_pvf(pvf, execution_timeout, params, Priority::Critical, result_tx)
+		.await
+	{
+		Ok(()) => result_rx.await.unwrap_or_else(|_| {
+			Err(ValidationError::InternalError("the validation host hung up".into()))
+		}),
+		Err(err) => Err(ValidationError::InternalError(err)),
+	};
+
+	HostReplayReport { preparation_time, execution_time: Some(started_at.elapsed()), result }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn stops_at_the_failing_phase() {
+		let report = replay_in_process(b"not wasm".to_vec(), Vec::new());
+
+		assert_eq!(report.phases.len(), 1);
+		assert!(report.result.unwrap_err().starts_with("prevalidation: "));
+	}
+}
@@ -0,0 +1,473 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Resource limits and syscall filtering for the jobs run by the workers.
+//!
+//! Every job runs on a fresh thread of the worker process. Meanwhile the main thread of the worker
+//! samples the resident memory of the process and gives up on the job once it exceeds the
+//! configured ceiling. On top of that, a `RLIMIT_DATA` with some headroom is set as a hard
+//! backstop against allocations growing faster than the sampling can notice. Hitting the backstop
+//! aborts the worker, which the host can only report as an ambiguous worker death. Memory limits
+//! via cgroup v2 are out of scope: they need a delegated cgroup subtree per worker, which the node
+//! can not assume to have, as the workers share the cgroup of the node.
+//!
+//! If enabled by the node, the job additionally installs a seccomp filter which bans filesystem and
+//! network syscalls. A violation raises `SIGSYS`, whose handler writes a pre-encoded report to the
+//! host socket and terminates the worker. The filter is applied to every thread of the worker, as
+//! the threads share their memory and a job which took over its thread could otherwise get another
+//! one to do the syscalls for it. The filter also covers the code of the worker itself, so a
+//! violation is not attributed to the PVF. The filter is never lifted again, so a worker exits once
+//! it reported the result of such a job. Everything the worker needs after the job starts, like the
+//! process statistics or the files the result is written to, has to be opened beforehand.
+//!
+//! The CPU time of the job thread is accounted and reported back to the host with the result.
+
+use parity_scale_codec::{Decode, Encode};
+use std::{
+	io,
+	sync::mpsc,
+	thread,
+	time::{Duration, Instant},
+};
+
+/// How often the memory consumption of a running job is sampled.
+const MEMORY_SAMPLING_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The allowance on top of the memory ceiling and the job stack before the hard limit is hit.
+const HARD_LIMIT_HEADROOM: u64 = 512 * 1024 * 1024;
+
+/// Limits enforced on a single job. Sent by the host along with every request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Encode, Decode)]
+pub struct JobLimits {
+	/// The maximum resident memory of the worker process while running the job, in bytes.
+	pub max_memory: Option<u64>,
+	/// Whether filesystem and network syscalls are forbidden to the job.
+	pub forbid_syscalls: bool,
+}
+
+/// Resources consumed by a single job.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Encode, Decode)]
+pub struct JobUsage {
+	/// The CPU time spent by the job thread, in milliseconds.
+	pub cpu_time_ms: u64,
+	/// The highest resident memory of the worker process sampled while running the job, in bytes.
+	pub peak_memory: u64,
+}
+
+/// The outcome of [`run_job`].
+#[derive(Debug)]
+pub enum JobOutcome<T> {
+	/// The job ran to completion.
+	Finished(T, JobUsage),
+	/// The job exceeded the memory ceiling and was abandoned. It is still running, so the worker
+	/// must report the violation and exit.
+	MemoryLimitExceeded(JobUsage),
+	/// The job panicked.
+	Panicked(String),
+}
+
+/// Runs the job on a fresh thread with the given stack size, enforcing the memory ceiling of the
+/// given limits.
+///
+/// The syscall filter is not installed here, since only the job knows the point after which it
+/// doesn't need the filesystem anymore. The job should call [`forbid_syscalls`] at that point.
+pub fn run_job<T, F>(limits: &JobLimits, stack_size: usize, job: F) -> io::Result<JobOutcome<T>>
+where
+	T: Send + 'static,
+	F: FnOnce() -> T + Send + 'static,
+{
+	if let Some(max_memory) = limits.max_memory {
+		set_hard_memory_limit(max_memory + stack_size as u64 + HARD_LIMIT_HEADROOM)?;
+	}
+
+	// Opened before the job starts, as the job may forbid opening files.
+	let memory = ResidentMemory::open();
+	let (result_tx, result_rx) = mpsc::channel();
+	let _ =
+		thread::Builder::new()
+			.name("pvf-job".into())
+			.stack_size(stack_size)
+			.spawn(move || {
+				let started_at = thread_cpu_time();
+				let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job));
+				let cpu_time = thread_cpu_time().saturating_sub(started_at);
+				let _ = result_tx.send((result, cpu_time));
+			})?;
+
+	let mut peak_memory = 0;
+	let started_at = Instant::now();
+	loop {
+		match result_rx.recv_timeout(MEMORY_SAMPLING_INTERVAL) {
+			Ok((result, cpu_time)) => {
+				peak_memory = peak_memory.max(memory.sample().unwrap_or(0));
+				let usage = JobUsage { cpu_time_ms: cpu_time.as_millis() as u64, peak_memory };
+				return Ok(match result {
+					Ok(result) => JobOutcome::Finished(result, usage),
+					Err(payload) =>
+						JobOutcome::Panicked(crate::error::stringify_panic_payload(payload)),
+				})
+			},
+			Err(mpsc::RecvTimeoutError::Timeout) => {
+				peak_memory = peak_memory.max(memory.sample().unwrap_or(0));
+				if limits.max_memory.map_or(false, |max_memory| peak_memory > max_memory) {
+					// The job thread can not be interrupted, so its CPU time is approximated by
+					// the wall clock time.
+					let cpu_time_ms = started_at.elapsed().as_millis() as u64;
+					return Ok(JobOutcome::MemoryLimitExceeded(JobUsage {
+						cpu_time_ms,
+						peak_memory,
+					}))
+				}
+			},
+			Err(mpsc::RecvTimeoutError::Disconnected) =>
+				return Ok(JobOutcome::Panicked("the job thread died".into())),
+		}
+	}
+}
+
+/// Sets the report written to the given socket if the job performs a forbidden syscall.
+///
+/// Must be called before the job is started. Fails if the report is longer than 64 bytes.
+pub fn set_violation_report(socket_fd: i32, report: &[u8]) -> io::Result<()> {
+	imp::set_violation_report(socket_fd, report)
+}
+
+/// Bans filesystem and network syscalls for all threads of the worker. A violation terminates the
+/// worker after the report set by [`set_violation_report`] was written.
+///
+/// The filter can't be lifted, so the worker has to exit once the result of the job is reported.
+///
+/// Only supported on Linux on `x86_64` and `aarch64`; a no-op elsewhere.
+pub fn forbid_syscalls() -> io::Result<()> {
+	imp::forbid_syscalls()
+}
+
+fn set_hard_memory_limit(limit: u64) -> io::Result<()> {
+	imp::set_hard_memory_limit(limit)
+}
+
+use imp::ResidentMemory;
+
+fn thread_cpu_time() -> Duration {
+	imp::thread_cpu_time()
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+	use std::{
+		fs::File,
+		io,
+		os::unix::fs::FileExt,
+		sync::atomic::{AtomicI32, AtomicUsize, Ordering},
+		time::Duration,
+	};
+
+	/// The maximum length of the violation report.
+	const MAX_REPORT_LEN: usize = 64;
+
+	// Read by the `SIGSYS` handler, so these can't be protected by a lock. The report is only
+	// written before a job starts, while no filter is installed.
+	static mut REPORT: [u8; MAX_REPORT_LEN] = [0; MAX_REPORT_LEN];
+	static REPORT_LEN: AtomicUsize = AtomicUsize::new(0);
+	static REPORT_FD: AtomicI32 = AtomicI32::new(-1);
+
+	pub fn set_violation_report(socket_fd: i32, report: &[u8]) -> io::Result<()> {
+		if report.len() > MAX_REPORT_LEN {
+			return Err(io::Error::new(io::ErrorKind::Other, "violation report is too large"))
+		}
+
+		// SAFETY: no job is running, so the signal handler can't be reading the report.
+		unsafe {
+			REPORT[..report.len()].copy_from_slice(report);
+		}
+		REPORT_LEN.store(report.len(), Ordering::SeqCst);
+		REPORT_FD.store(socket_fd, Ordering::SeqCst);
+
+		let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+		action.sa_sigaction = on_sigsys as usize;
+		// SAFETY: the handler only calls async-signal-safe functions.
+		if unsafe { libc::sigaction(libc::SIGSYS, &action, std::ptr::null_mut()) } != 0 {
+			return Err(io::Error::last_os_error())
+		}
+
+		Ok(())
+	}
+
+	extern "C" fn on_sigsys(_signal: libc::c_int) {
+		let fd = REPORT_FD.load(Ordering::SeqCst);
+		let len = REPORT_LEN.load(Ordering::SeqCst);
+		// SAFETY: `write` and `_exit` are async-signal-safe and the report isn't modified while a
+		// job is running.
+		unsafe {
+			let _ = libc::write(fd, std::ptr::addr_of!(REPORT) as *const libc::c_void, len);
+			libc::_exit(1);
+		}
+	}
+
+	#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+	pub fn forbid_syscalls() -> io::Result<()> {
+		#[cfg(target_arch = "x86_64")]
+		const AUDIT_ARCH: u32 = 0xC000_003E;
+		#[cfg(target_arch = "aarch64")]
+		const AUDIT_ARCH: u32 = 0xC000_00B7;
+
+		const BPF_LD_W_ABS: u16 = 0x00 | 0x00 | 0x20;
+		const BPF_JMP_JEQ_K: u16 = 0x05 | 0x10 | 0x00;
+		const BPF_RET_K: u16 = 0x06 | 0x00;
+
+		const SECCOMP_SET_MODE_FILTER: libc::c_ulong = 1;
+		const SECCOMP_FILTER_FLAG_TSYNC: libc::c_ulong = 1;
+		const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+		const SECCOMP_RET_TRAP: u32 = 0x0003_0000;
+		const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+
+		// Offsets into `struct seccomp_data`.
+		const SYSCALL_NR_OFFSET: u32 = 0;
+		const ARCH_OFFSET: u32 = 4;
+
+		#[repr(C)]
+		struct SockFilter {
+			code: u16,
+			jt: u8,
+			jf: u8,
+			k: u32,
+		}
+
+		#[repr(C)]
+		struct SockFprog {
+			len: libc::c_ushort,
+			filter: *const SockFilter,
+		}
+
+		fn stmt(code: u16, k: u32) -> SockFilter {
+			SockFilter { code, jt: 0, jf: 0, k }
+		}
+
+		let mut forbidden = vec![
+			libc::SYS_openat,
+			libc::SYS_mkdirat,
+			libc::SYS_unlinkat,
+			libc::SYS_renameat,
+			libc::SYS_linkat,
+			libc::SYS_symlinkat,
+			libc::SYS_truncate,
+			libc::SYS_execve,
+			libc::SYS_execveat,
+			libc::SYS_socket,
+			libc::SYS_socketpair,
+			libc::SYS_connect,
+			libc::SYS_bind,
+			libc::SYS_listen,
+			libc::SYS_accept,
+			libc::SYS_accept4,
+		];
+		#[cfg(target_arch = "x86_64")]
+		forbidden.extend([
+			libc::SYS_open,
+			libc::SYS_creat,
+			libc::SYS_mkdir,
+			libc::SYS_rmdir,
+			libc::SYS_unlink,
+			libc::SYS_rename,
+			libc::SYS_link,
+			libc::SYS_symlink,
+		]);
+
+		let mut program = vec![
+			stmt(BPF_LD_W_ABS, ARCH_OFFSET),
+			SockFilter { code: BPF_JMP_JEQ_K, jt: 1, jf: 0, k: AUDIT_ARCH },
+			stmt(BPF_RET_K, SECCOMP_RET_KILL_PROCESS),
+			stmt(BPF_LD_W_ABS, SYSCALL_NR_OFFSET),
+		];
+		for syscall in forbidden {
+			program.push(SockFilter { code: BPF_JMP_JEQ_K, jt: 0, jf: 1, k: syscall as u32 });
+			program.push(stmt(BPF_RET_K, SECCOMP_RET_TRAP));
+		}
+		program.push(stmt(BPF_RET_K, SECCOMP_RET_ALLOW));
+
+		let fprog = SockFprog { len: program.len() as libc::c_ushort, filter: program.as_ptr() };
+
+		// SAFETY: `fprog` points to a valid filter program which outlives the calls. With the
+		// `SECCOMP_FILTER_FLAG_TSYNC` flag the filter applies to all threads of the process.
+		unsafe {
+			if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+				return Err(io::Error::last_os_error())
+			}
+			match libc::syscall(
+				libc::SYS_seccomp,
+				SECCOMP_SET_MODE_FILTER,
+				SECCOMP_FILTER_FLAG_TSYNC,
+				&fprog as *const SockFprog,
+			) {
+				0 => Ok(()),
+				// The id of a thread which could not be synchronized.
+				tid if tid > 0 => Err(io::Error::new(
+					io::ErrorKind::Other,
+					format!("cannot apply the syscall filter to thread {}", tid),
+				)),
+				_ => Err(io::Error::last_os_error()),
+			}
+		}
+	}
+
+	#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+	pub fn forbid_syscalls() -> io::Result<()> {
+		Ok(())
+	}
+
+	pub fn set_hard_memory_limit(limit: u64) -> io::Result<()> {
+		let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+		// SAFETY: `current` is a valid `rlimit` to write to.
+		if unsafe { libc::getrlimit(libc::RLIMIT_DATA, &mut current) } != 0 {
+			return Err(io::Error::last_os_error())
+		}
+
+		// Only the soft limit is changed, so it can be raised again for the next job.
+		let limit = libc::rlimit { rlim_cur: limit.min(current.rlim_max), ..current };
+		// SAFETY: `limit` is a valid `rlimit`.
+		if unsafe { libc::setrlimit(libc::RLIMIT_DATA, &limit) } != 0 {
+			return Err(io::Error::last_os_error())
+		}
+
+		Ok(())
+	}
+
+	/// The resident memory of the worker process, read from the kept open `statm` file.
+	pub struct ResidentMemory(Option<File>);
+
+	impl ResidentMemory {
+		pub fn open() -> Self {
+			Self(File::open("/proc/self/statm").ok())
+		}
+
+		pub fn sample(&self) -> Option<u64> {
+			let mut buf = [0u8; 128];
+			let len = self.0.as_ref()?.read_at(&mut buf, 0).ok()?;
+			// The second field of `statm` is the number of resident pages.
+			let statm = std::str::from_utf8(&buf[..len]).ok()?;
+			let pages = statm.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+			// SAFETY: `sysconf` has no preconditions.
+			let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+			Some(pages * page_size as u64)
+		}
+	}
+
+	pub fn thread_cpu_time() -> Duration {
+		let mut time = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+		// SAFETY: `time` is a valid `timespec` to write to.
+		if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) } != 0 {
+			return Duration::ZERO
+		}
+		Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+	use std::{io, time::Duration};
+
+	pub fn set_violation_report(_socket_fd: i32, _report: &[u8]) -> io::Result<()> {
+		Ok(())
+	}
+
+	pub fn forbid_syscalls() -> io::Result<()> {
+		Ok(())
+	}
+
+	pub fn set_hard_memory_limit(_limit: u64) -> io::Result<()> {
+		Ok(())
+	}
+
+	pub struct ResidentMemory;
+
+	impl ResidentMemory {
+		pub fn open() -> Self {
+			Self
+		}
+
+		pub fn sample(&self) -> Option<u64> {
+			None
+		}
+	}
+
+	pub fn thread_cpu_time() -> Duration {
+		Duration::ZERO
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const STACK_SIZE: usize = 2 * 1024 * 1024;
+
+	#[test]
+	fn finished_job_reports_usage() {
+		let outcome = run_job(&JobLimits::default(), STACK_SIZE, || {
+			let started_at = Instant::now();
+			// Busy loop, so that some CPU time is accounted.
+			while started_at.elapsed() < Duration::from_millis(50) {}
+			42
+		})
+		.unwrap();
+
+		match outcome {
+			JobOutcome::Finished(result, usage) => {
+				assert_eq!(result, 42);
+				#[cfg(target_os = "linux")]
+				assert!(usage.cpu_time_ms >= 10 && usage.peak_memory > 0, "{:?}", usage);
+			},
+			other => panic!("unexpected outcome: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn panicking_job_is_reported() {
+		let outcome = run_job::<(), _>(&JobLimits::default(), STACK_SIZE, || panic!("boom"));
+		assert!(matches!(outcome.unwrap(), JobOutcome::Panicked(msg) if msg == "boom"));
+	}
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn memory_hog_is_abandoned() {
+		// The limits apply to the whole process, which the test can't share with the others. Hence
+		// it re-runs itself in a child process.
+		const CHILD_ENV: &str = "PVF_SANDBOX_TEST_CHILD";
+		if std::env::var_os(CHILD_ENV).is_none() {
+			let status = std::process::Command::new(std::env::current_exe().unwrap())
+				.args(["--exact", "sandbox::tests::memory_hog_is_abandoned", "--test-threads=1"])
+				.env(CHILD_ENV, "1")
+				.status()
+				.unwrap();
+			assert!(status.success(), "{:?}", status);
+			return
+		}
+
+		let limits = JobLimits {
+			max_memory: Some(ResidentMemory::open().sample().unwrap() + 32 * 1024 * 1024),
+			..Default::default()
+		};
+		let outcome = run_job(&limits, STACK_SIZE, || {
+			// Touch every page, so that the memory actually becomes resident.
+			let hog = vec![1u8; 256 * 1024 * 1024];
+			std::thread::sleep(Duration::from_secs(1));
+			hog.len()
+		})
+		.unwrap();
+
+		assert!(matches!(outcome, JobOutcome::MemoryLimitExceeded(_)), "{:?}", outcome);
+	}
+}
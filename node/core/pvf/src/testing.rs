@@ -29,7 +29,7 @@ pub fn validate_candidate(
 	code: &[u8],
 	params: &[u8],
 ) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-	use crate::executor_intf::{prepare, prevalidate, Executor};
+	use crate::executor_intf::{prepare, prevalidate, Executor, EXECUTE_THREAD_STACK_SIZE};
 
 	let code = sp_maybe_compressed_blob::decompress(code, 10 * 1024 * 1024)
 		.expect("Decompressing code failed");
@@ -41,11 +41,16 @@ pub fn validate_candidate(
 	std::fs::write(&artifact_path, &artifact)?;
 
 	let executor = Executor::new()?;
-	let result = unsafe {
-		// SAFETY: This is trivially safe since the artifact is obtained by calling `prepare`
-		//         and is written into a temporary directory in an unmodified state.
-		executor.execute(&artifact_path, params)?
-	};
+	let params = params.to_vec();
+	let result = std::thread::Builder::new()
+		.stack_size(EXECUTE_THREAD_STACK_SIZE)
+		.spawn(move || unsafe {
+			// SAFETY: This is trivially safe since the artifact is obtained by calling `prepare`
+			//         and is written into a temporary directory in an unmodified state.
+			executor.execute(&artifact_path, &params, || Ok(()))
+		})?
+		.join()
+		.map_err(|_| "the execution thread panicked")??;
 
 	Ok(result)
 }
@@ -298,6 +298,13 @@ pub fn bytes_to_path(bytes: &[u8]) -> Option<PathBuf> {
 	std::str::from_utf8(bytes).ok().map(PathBuf::from)
 }
 
+/// Encodes the given buffer the same way [`framed_send`] puts it on the wire.
+pub fn frame(buf: &[u8]) -> Vec<u8> {
+	let mut framed = buf.len().to_le_bytes().to_vec();
+	framed.extend_from_slice(buf);
+	framed
+}
+
 pub async fn framed_send(w: &mut (impl AsyncWrite + Unpin), buf: &[u8]) -> io::Result<()> {
 	let len_buf = buf.len().to_le_bytes();
 	w.write_all(&len_buf).await?;
@@ -14,9 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::TestHost;
+use super::{TestHost, TEST_EXECUTION_TIMEOUT};
 use adder::{hash_state, BlockData, HeadData};
 use parity_scale_codec::{Decode, Encode};
+use polkadot_node_core_pvf::{replay_in_process, replay_via_host};
 use polkadot_parachain::primitives::{
 	BlockData as GenericBlockData, HeadData as GenericHeadData, RelayChainBlockNumber,
 	ValidationParams,
@@ -142,3 +143,32 @@ async fn stress_spawn() {
 
 	futures::future::join_all((0..100).map(|_| execute(host.clone()))).await;
 }
+
+#[async_std::test]
+async fn replay_agrees_with_host() {
+	let parent_head = HeadData { number: 0, parent_hash: [0; 32], post_state: hash_state(0) };
+	let block_data = BlockData { state: 0, add: 512 };
+	let params = ValidationParams {
+		parent_head: GenericHeadData(parent_head.encode()),
+		block_data: GenericBlockData(block_data.encode()),
+		relay_parent_number: 1,
+		relay_parent_storage_root: Default::default(),
+	}
+	.encode();
+	let code = sp_maybe_compressed_blob::decompress(adder::wasm_binary_unwrap(), 16 * 1024 * 1024)
+		.unwrap()
+		.to_vec();
+
+	let report = replay_in_process(code.clone(), params.clone());
+	let names = report.phases.iter().map(|p| p.name).collect::<Vec<_>>();
+	assert_eq!(names, vec!["prevalidation", "preparation", "execution"]);
+
+	let host = TestHost::new();
+	let host_report =
+		replay_via_host(&mut *host.host.lock().await, code, params, TEST_EXECUTION_TIMEOUT).await;
+	assert!(host_report.execution_time.is_some());
+
+	let new_head = HeadData::decode(&mut &report.result.unwrap().head_data.0[..]).unwrap();
+	assert_eq!(new_head.post_state, hash_state(512));
+	assert_eq!(host_report.result.unwrap().head_data, GenericHeadData(new_head.encode()));
+}
@@ -99,6 +99,30 @@ async fn terminates_on_timeout() {
 	}
 }
 
+#[cfg(target_os = "linux")]
+#[async_std::test]
+async fn terminates_on_memory_limit() {
+	// Any PVF exceeds a single byte of memory, the looping one just makes sure it is noticed.
+	let host = TestHost::new_with_config(|cfg| cfg.execute_job_max_memory = Some(1));
+
+	let result = host
+		.validate_candidate(
+			halt::wasm_binary_unwrap(),
+			ValidationParams {
+				block_data: BlockData(Vec::new()),
+				parent_head: Default::default(),
+				relay_parent_number: 1,
+				relay_parent_storage_root: Default::default(),
+			},
+		)
+		.await;
+
+	match result {
+		Err(ValidationError::InternalError(err)) if err.contains("memory limit") => {},
+		r => panic!("{:?}", r),
+	}
+}
+
 #[async_std::test]
 async fn parallel_execution() {
 	let host = TestHost::new();
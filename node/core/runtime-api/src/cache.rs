@@ -16,7 +16,8 @@
 
 use std::collections::btree_map::BTreeMap;
 
-use memory_lru::{MemoryLruCache, ResidentSize};
+use lru::LruCache;
+use memory_lru::ResidentSize;
 use parity_util_mem::{MallocSizeOf, MallocSizeOfExt};
 use sp_consensus_babe::Epoch;
 
@@ -28,26 +29,15 @@ use polkadot_primitives::v2::{
 	ValidationCodeHash, ValidatorId, ValidatorIndex, ValidatorSignature,
 };
 
-const AUTHORITIES_CACHE_SIZE: usize = 128 * 1024;
-const VALIDATORS_CACHE_SIZE: usize = 64 * 1024;
-const VALIDATOR_GROUPS_CACHE_SIZE: usize = 64 * 1024;
-const AVAILABILITY_CORES_CACHE_SIZE: usize = 64 * 1024;
-const PERSISTED_VALIDATION_DATA_CACHE_SIZE: usize = 64 * 1024;
-const ASSUMED_VALIDATION_DATA_CACHE_SIZE: usize = 64 * 1024;
-const CHECK_VALIDATION_OUTPUTS_CACHE_SIZE: usize = 64 * 1024;
-const SESSION_INDEX_FOR_CHILD_CACHE_SIZE: usize = 64 * 1024;
-const VALIDATION_CODE_CACHE_SIZE: usize = 10 * 1024 * 1024;
-const CANDIDATE_PENDING_AVAILABILITY_CACHE_SIZE: usize = 64 * 1024;
-const CANDIDATE_EVENTS_CACHE_SIZE: usize = 64 * 1024;
-const SESSION_INFO_CACHE_SIZE: usize = 64 * 1024;
-const DMQ_CONTENTS_CACHE_SIZE: usize = 64 * 1024;
-const INBOUND_HRMP_CHANNELS_CACHE_SIZE: usize = 64 * 1024;
-const CURRENT_BABE_EPOCH_CACHE_SIZE: usize = 64 * 1024;
-const ON_CHAIN_VOTES_CACHE_SIZE: usize = 3 * 1024;
-const PVFS_REQUIRE_PRECHECK_SIZE: usize = 1024;
-const VALIDATION_CODE_HASH_CACHE_SIZE: usize = 64 * 1024;
-const VERSION_CACHE_SIZE: usize = 4 * 1024;
-const DISPUTES_CACHE_SIZE: usize = 64 * 1024;
+use crate::metrics::Metrics;
+
+/// The default memory budget of the cache, in bytes.
+///
+/// Roughly what the former per-request caches added up to.
+pub const DEFAULT_CACHE_BUDGET: usize = 24 * 1024 * 1024;
+
+/// Returned for cached requests of validation code, which did not exist.
+static NO_VALIDATION_CODE: Option<ValidationCode> = None;
 
 struct ResidentSizeOf<T>(T);
 
@@ -75,89 +65,151 @@ impl<T> ResidentSize for VecOfDoesNotAllocate<T> {
 	}
 }
 
-pub(crate) struct RequestResultCache {
-	authorities: MemoryLruCache<Hash, VecOfDoesNotAllocate<AuthorityDiscoveryId>>,
-	validators: MemoryLruCache<Hash, ResidentSizeOf<Vec<ValidatorId>>>,
-	validator_groups:
-		MemoryLruCache<Hash, ResidentSizeOf<(Vec<Vec<ValidatorIndex>>, GroupRotationInfo)>>,
-	availability_cores: MemoryLruCache<Hash, ResidentSizeOf<Vec<CoreState>>>,
-	persisted_validation_data: MemoryLruCache<
-		(Hash, ParaId, OccupiedCoreAssumption),
-		ResidentSizeOf<Option<PersistedValidationData>>,
-	>,
-	assumed_validation_data: MemoryLruCache<
-		(ParaId, Hash),
+macro_rules! cached_requests {
+	($($variant:ident($key:ty) => $value:ty, $label:literal;)*) => {
+		#[derive(Clone, PartialEq, Eq, Hash)]
+		enum CacheKey {
+			$($variant($key),)*
+		}
+
+		enum CacheValue {
+			$($variant($value),)*
+		}
+
+		impl CacheKey {
+			/// The request kind, as used for metrics.
+			fn request(&self) -> &'static str {
+				match self {
+					$(Self::$variant(_) => $label,)*
+				}
+			}
+		}
+
+		impl ResidentSize for CacheValue {
+			fn resident_size(&self) -> usize {
+				match self {
+					$(Self::$variant(value) => value.resident_size(),)*
+				}
+			}
+		}
+	};
+}
+
+cached_requests! {
+	Authorities(Hash) => VecOfDoesNotAllocate<AuthorityDiscoveryId>, "authorities";
+	Validators(Hash) => ResidentSizeOf<Vec<ValidatorId>>, "validators";
+	ValidatorGroups(Hash) =>
+		ResidentSizeOf<(Vec<Vec<ValidatorIndex>>, GroupRotationInfo)>, "validator_groups";
+	AvailabilityCores(Hash) => ResidentSizeOf<Vec<CoreState>>, "availability_cores";
+	PersistedValidationData((Hash, ParaId, OccupiedCoreAssumption)) =>
+		ResidentSizeOf<Option<PersistedValidationData>>, "persisted_validation_data";
+	AssumedValidationData((ParaId, Hash)) =>
 		ResidentSizeOf<Option<(PersistedValidationData, ValidationCodeHash)>>,
-	>,
-	check_validation_outputs:
-		MemoryLruCache<(Hash, ParaId, CandidateCommitments), ResidentSizeOf<bool>>,
-	session_index_for_child: MemoryLruCache<Hash, ResidentSizeOf<SessionIndex>>,
-	validation_code: MemoryLruCache<
-		(Hash, ParaId, OccupiedCoreAssumption),
-		ResidentSizeOf<Option<ValidationCode>>,
-	>,
-	validation_code_by_hash:
-		MemoryLruCache<ValidationCodeHash, ResidentSizeOf<Option<ValidationCode>>>,
-	candidate_pending_availability:
-		MemoryLruCache<(Hash, ParaId), ResidentSizeOf<Option<CommittedCandidateReceipt>>>,
-	candidate_events: MemoryLruCache<Hash, ResidentSizeOf<Vec<CandidateEvent>>>,
-	session_info: MemoryLruCache<SessionIndex, ResidentSizeOf<SessionInfo>>,
-	dmq_contents:
-		MemoryLruCache<(Hash, ParaId), ResidentSizeOf<Vec<InboundDownwardMessage<BlockNumber>>>>,
-	inbound_hrmp_channels_contents: MemoryLruCache<
-		(Hash, ParaId),
+		"assumed_validation_data";
+	CheckValidationOutputs((Hash, ParaId, CandidateCommitments)) =>
+		ResidentSizeOf<bool>, "check_validation_outputs";
+	SessionIndexForChild(Hash) => ResidentSizeOf<SessionIndex>, "session_index_for_child";
+	// Only the hash, the code itself is kept in `ValidationCodeByHash`.
+	ValidationCode((Hash, ParaId, OccupiedCoreAssumption)) =>
+		ResidentSizeOf<Option<ValidationCodeHash>>, "validation_code";
+	ValidationCodeByHash(ValidationCodeHash) =>
+		ResidentSizeOf<Option<ValidationCode>>, "validation_code_by_hash";
+	CandidatePendingAvailability((Hash, ParaId)) =>
+		ResidentSizeOf<Option<CommittedCandidateReceipt>>, "candidate_pending_availability";
+	CandidateEvents(Hash) => ResidentSizeOf<Vec<CandidateEvent>>, "candidate_events";
+	SessionInfo(SessionIndex) => ResidentSizeOf<SessionInfo>, "session_info";
+	DmqContents((Hash, ParaId)) =>
+		ResidentSizeOf<Vec<InboundDownwardMessage<BlockNumber>>>, "dmq_contents";
+	InboundHrmpChannelsContents((Hash, ParaId)) =>
 		ResidentSizeOf<BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>>,
-	>,
-	current_babe_epoch: MemoryLruCache<Hash, DoesNotAllocate<Epoch>>,
-	on_chain_votes: MemoryLruCache<Hash, ResidentSizeOf<Option<ScrapedOnChainVotes>>>,
-	pvfs_require_precheck: MemoryLruCache<Hash, ResidentSizeOf<Vec<ValidationCodeHash>>>,
-	validation_code_hash: MemoryLruCache<
-		(Hash, ParaId, OccupiedCoreAssumption),
-		ResidentSizeOf<Option<ValidationCodeHash>>,
-	>,
-	version: MemoryLruCache<Hash, ResidentSizeOf<u32>>,
-	disputes: MemoryLruCache<
-		Hash,
-		ResidentSizeOf<Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>>,
-	>,
+		"inbound_hrmp_channels_contents";
+	CurrentBabeEpoch(Hash) => DoesNotAllocate<Epoch>, "current_babe_epoch";
+	OnChainVotes(Hash) => ResidentSizeOf<Option<ScrapedOnChainVotes>>, "on_chain_votes";
+	PvfsRequirePrecheck(Hash) => ResidentSizeOf<Vec<ValidationCodeHash>>, "pvfs_require_precheck";
+	ValidationCodeHash((Hash, ParaId, OccupiedCoreAssumption)) =>
+		ResidentSizeOf<Option<ValidationCodeHash>>, "validation_code_hash";
+	Version(Hash) => ResidentSizeOf<u32>, "version";
+	Disputes(Hash) =>
+		ResidentSizeOf<Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>>, "disputes";
+}
+
+/// Memory used by a cache entry, including its key.
+fn entry_size(value: &CacheValue) -> usize {
+	std::mem::size_of::<CacheKey>() + value.resident_size()
 }
 
-impl Default for RequestResultCache {
-	fn default() -> Self {
-		Self {
-			authorities: MemoryLruCache::new(AUTHORITIES_CACHE_SIZE),
-			validators: MemoryLruCache::new(VALIDATORS_CACHE_SIZE),
-			validator_groups: MemoryLruCache::new(VALIDATOR_GROUPS_CACHE_SIZE),
-			availability_cores: MemoryLruCache::new(AVAILABILITY_CORES_CACHE_SIZE),
-			persisted_validation_data: MemoryLruCache::new(PERSISTED_VALIDATION_DATA_CACHE_SIZE),
-			assumed_validation_data: MemoryLruCache::new(ASSUMED_VALIDATION_DATA_CACHE_SIZE),
-			check_validation_outputs: MemoryLruCache::new(CHECK_VALIDATION_OUTPUTS_CACHE_SIZE),
-			session_index_for_child: MemoryLruCache::new(SESSION_INDEX_FOR_CHILD_CACHE_SIZE),
-			validation_code: MemoryLruCache::new(VALIDATION_CODE_CACHE_SIZE),
-			validation_code_by_hash: MemoryLruCache::new(VALIDATION_CODE_CACHE_SIZE),
-			candidate_pending_availability: MemoryLruCache::new(
-				CANDIDATE_PENDING_AVAILABILITY_CACHE_SIZE,
-			),
-			candidate_events: MemoryLruCache::new(CANDIDATE_EVENTS_CACHE_SIZE),
-			session_info: MemoryLruCache::new(SESSION_INFO_CACHE_SIZE),
-			dmq_contents: MemoryLruCache::new(DMQ_CONTENTS_CACHE_SIZE),
-			inbound_hrmp_channels_contents: MemoryLruCache::new(INBOUND_HRMP_CHANNELS_CACHE_SIZE),
-			current_babe_epoch: MemoryLruCache::new(CURRENT_BABE_EPOCH_CACHE_SIZE),
-			on_chain_votes: MemoryLruCache::new(ON_CHAIN_VOTES_CACHE_SIZE),
-			pvfs_require_precheck: MemoryLruCache::new(PVFS_REQUIRE_PRECHECK_SIZE),
-			validation_code_hash: MemoryLruCache::new(VALIDATION_CODE_HASH_CACHE_SIZE),
-			version: MemoryLruCache::new(VERSION_CACHE_SIZE),
-			disputes: MemoryLruCache::new(DISPUTES_CACHE_SIZE),
+// Look up a cached result, unwrapping it from its `CacheValue` variant.
+macro_rules! lookup {
+	($cache:expr, $variant:ident($key:expr)) => {
+		match $cache.get(CacheKey::$variant($key)) {
+			Some(CacheValue::$variant(value)) => Some(&value.0),
+			_ => None,
 		}
-	}
+	};
+}
+
+/// Cache of runtime API results.
+///
+/// All kinds of requests share a single memory budget, least recently used entries get evicted
+/// first. Results which do not depend on the relay parent, like session info and validation code,
+/// are cached once and served for all relay parents.
+pub(crate) struct RequestResultCache {
+	entries: LruCache<CacheKey, CacheValue>,
+	/// Memory budget in bytes.
+	budget: usize,
+	/// Memory currently used by `entries`, as estimated by `entry_size`.
+	used: usize,
+	metrics: Metrics,
 }
 
 impl RequestResultCache {
+	/// Create a new cache using at most `budget` bytes.
+	pub(crate) fn new(budget: usize, metrics: Metrics) -> Self {
+		Self { entries: LruCache::unbounded(), budget, used: 0, metrics }
+	}
+
+	fn get(&mut self, key: CacheKey) -> Option<&CacheValue> {
+		let request = key.request();
+		let value = self.entries.get(&key);
+		self.metrics.on_cache_lookup(request, value.is_some());
+		value
+	}
+
+	fn insert(&mut self, key: CacheKey, value: CacheValue) {
+		let size = entry_size(&value);
+		if size > self.budget {
+			// Would evict everything else, and itself right away.
+			return
+		}
+
+		self.used += size;
+		if let Some(old) = self.entries.put(key, value) {
+			self.used -= entry_size(&old);
+		}
+		// The new entry is the most recently used one, so it is never evicted here.
+		while self.used > self.budget {
+			match self.entries.pop_lru() {
+				Some((key, value)) => {
+					self.used -= entry_size(&value);
+					self.metrics.on_cache_eviction(key.request());
+				},
+				None => break,
+			}
+		}
+		self.metrics.on_cache_size(self.used);
+	}
+
+	/// Memory currently used by cached results, in bytes.
+	#[cfg(test)]
+	pub(crate) fn used(&self) -> usize {
+		self.used
+	}
+
 	pub(crate) fn authorities(
 		&mut self,
 		relay_parent: &Hash,
 	) -> Option<&Vec<AuthorityDiscoveryId>> {
-		self.authorities.get(relay_parent).map(|v| &v.0)
+		lookup!(self, Authorities(*relay_parent))
 	}
 
 	pub(crate) fn cache_authorities(
@@ -165,22 +217,28 @@ impl RequestResultCache {
 		relay_parent: Hash,
 		authorities: Vec<AuthorityDiscoveryId>,
 	) {
-		self.authorities.insert(relay_parent, VecOfDoesNotAllocate(authorities));
+		self.insert(
+			CacheKey::Authorities(relay_parent),
+			CacheValue::Authorities(VecOfDoesNotAllocate(authorities)),
+		);
 	}
 
 	pub(crate) fn validators(&mut self, relay_parent: &Hash) -> Option<&Vec<ValidatorId>> {
-		self.validators.get(relay_parent).map(|v| &v.0)
+		lookup!(self, Validators(*relay_parent))
 	}
 
 	pub(crate) fn cache_validators(&mut self, relay_parent: Hash, validators: Vec<ValidatorId>) {
-		self.validators.insert(relay_parent, ResidentSizeOf(validators));
+		self.insert(
+			CacheKey::Validators(relay_parent),
+			CacheValue::Validators(ResidentSizeOf(validators)),
+		);
 	}
 
 	pub(crate) fn validator_groups(
 		&mut self,
 		relay_parent: &Hash,
 	) -> Option<&(Vec<Vec<ValidatorIndex>>, GroupRotationInfo)> {
-		self.validator_groups.get(relay_parent).map(|v| &v.0)
+		lookup!(self, ValidatorGroups(*relay_parent))
 	}
 
 	pub(crate) fn cache_validator_groups(
@@ -188,22 +246,28 @@ impl RequestResultCache {
 		relay_parent: Hash,
 		groups: (Vec<Vec<ValidatorIndex>>, GroupRotationInfo),
 	) {
-		self.validator_groups.insert(relay_parent, ResidentSizeOf(groups));
+		self.insert(
+			CacheKey::ValidatorGroups(relay_parent),
+			CacheValue::ValidatorGroups(ResidentSizeOf(groups)),
+		);
 	}
 
 	pub(crate) fn availability_cores(&mut self, relay_parent: &Hash) -> Option<&Vec<CoreState>> {
-		self.availability_cores.get(relay_parent).map(|v| &v.0)
+		lookup!(self, AvailabilityCores(*relay_parent))
 	}
 
 	pub(crate) fn cache_availability_cores(&mut self, relay_parent: Hash, cores: Vec<CoreState>) {
-		self.availability_cores.insert(relay_parent, ResidentSizeOf(cores));
+		self.insert(
+			CacheKey::AvailabilityCores(relay_parent),
+			CacheValue::AvailabilityCores(ResidentSizeOf(cores)),
+		);
 	}
 
 	pub(crate) fn persisted_validation_data(
 		&mut self,
 		key: (Hash, ParaId, OccupiedCoreAssumption),
 	) -> Option<&Option<PersistedValidationData>> {
-		self.persisted_validation_data.get(&key).map(|v| &v.0)
+		lookup!(self, PersistedValidationData(key))
 	}
 
 	pub(crate) fn cache_persisted_validation_data(
@@ -211,14 +275,17 @@ impl RequestResultCache {
 		key: (Hash, ParaId, OccupiedCoreAssumption),
 		data: Option<PersistedValidationData>,
 	) {
-		self.persisted_validation_data.insert(key, ResidentSizeOf(data));
+		self.insert(
+			CacheKey::PersistedValidationData(key),
+			CacheValue::PersistedValidationData(ResidentSizeOf(data)),
+		);
 	}
 
 	pub(crate) fn assumed_validation_data(
 		&mut self,
 		key: (Hash, ParaId, Hash),
 	) -> Option<&Option<(PersistedValidationData, ValidationCodeHash)>> {
-		self.assumed_validation_data.get(&(key.1, key.2)).map(|v| &v.0)
+		lookup!(self, AssumedValidationData((key.1, key.2)))
 	}
 
 	pub(crate) fn cache_assumed_validation_data(
@@ -226,14 +293,17 @@ impl RequestResultCache {
 		key: (ParaId, Hash),
 		data: Option<(PersistedValidationData, ValidationCodeHash)>,
 	) {
-		self.assumed_validation_data.insert(key, ResidentSizeOf(data));
+		self.insert(
+			CacheKey::AssumedValidationData(key),
+			CacheValue::AssumedValidationData(ResidentSizeOf(data)),
+		);
 	}
 
 	pub(crate) fn check_validation_outputs(
 		&mut self,
 		key: (Hash, ParaId, CandidateCommitments),
 	) -> Option<&bool> {
-		self.check_validation_outputs.get(&key).map(|v| &v.0)
+		lookup!(self, CheckValidationOutputs(key))
 	}
 
 	pub(crate) fn cache_check_validation_outputs(
@@ -241,11 +311,14 @@ impl RequestResultCache {
 		key: (Hash, ParaId, CandidateCommitments),
 		value: bool,
 	) {
-		self.check_validation_outputs.insert(key, ResidentSizeOf(value));
+		self.insert(
+			CacheKey::CheckValidationOutputs(key),
+			CacheValue::CheckValidationOutputs(ResidentSizeOf(value)),
+		);
 	}
 
 	pub(crate) fn session_index_for_child(&mut self, relay_parent: &Hash) -> Option<&SessionIndex> {
-		self.session_index_for_child.get(relay_parent).map(|v| &v.0)
+		lookup!(self, SessionIndexForChild(*relay_parent))
 	}
 
 	pub(crate) fn cache_session_index_for_child(
@@ -253,14 +326,33 @@ impl RequestResultCache {
 		relay_parent: Hash,
 		index: SessionIndex,
 	) {
-		self.session_index_for_child.insert(relay_parent, ResidentSizeOf(index));
+		self.insert(
+			CacheKey::SessionIndexForChild(relay_parent),
+			CacheValue::SessionIndexForChild(ResidentSizeOf(index)),
+		);
 	}
 
+	// The code is looked up by its hash, so it is shared with `validation_code_by_hash` and
+	// between relay parents. This is only a hit, if the code itself is still cached.
 	pub(crate) fn validation_code(
 		&mut self,
 		key: (Hash, ParaId, OccupiedCoreAssumption),
 	) -> Option<&Option<ValidationCode>> {
-		self.validation_code.get(&key).map(|v| &v.0)
+		let code_hash = match self.entries.get(&CacheKey::ValidationCode(key)) {
+			Some(CacheValue::ValidationCode(code_hash)) => Some(code_hash.0),
+			_ => None,
+		};
+		let code = match code_hash {
+			None => None,
+			Some(None) => Some(&NO_VALIDATION_CODE),
+			Some(Some(code_hash)) =>
+				match self.entries.get(&CacheKey::ValidationCodeByHash(code_hash)) {
+					Some(CacheValue::ValidationCodeByHash(code)) => Some(&code.0),
+					_ => None,
+				},
+		};
+		self.metrics.on_cache_lookup("validation_code", code.is_some());
+		code
 	}
 
 	pub(crate) fn cache_validation_code(
@@ -268,7 +360,14 @@ impl RequestResultCache {
 		key: (Hash, ParaId, OccupiedCoreAssumption),
 		value: Option<ValidationCode>,
 	) {
-		self.validation_code.insert(key, ResidentSizeOf(value));
+		let code_hash = value.as_ref().map(|code| code.hash());
+		if let Some(code_hash) = code_hash {
+			self.cache_validation_code_by_hash(code_hash, value);
+		}
+		self.insert(
+			CacheKey::ValidationCode(key),
+			CacheValue::ValidationCode(ResidentSizeOf(code_hash)),
+		);
 	}
 
 	// the actual key is `ValidationCodeHash` (`Hash` is ignored),
@@ -277,7 +376,7 @@ impl RequestResultCache {
 		&mut self,
 		key: (Hash, ValidationCodeHash),
 	) -> Option<&Option<ValidationCode>> {
-		self.validation_code_by_hash.get(&key.1).map(|v| &v.0)
+		lookup!(self, ValidationCodeByHash(key.1))
 	}
 
 	pub(crate) fn cache_validation_code_by_hash(
@@ -285,14 +384,17 @@ impl RequestResultCache {
 		key: ValidationCodeHash,
 		value: Option<ValidationCode>,
 	) {
-		self.validation_code_by_hash.insert(key, ResidentSizeOf(value));
+		self.insert(
+			CacheKey::ValidationCodeByHash(key),
+			CacheValue::ValidationCodeByHash(ResidentSizeOf(value)),
+		);
 	}
 
 	pub(crate) fn candidate_pending_availability(
 		&mut self,
 		key: (Hash, ParaId),
 	) -> Option<&Option<CommittedCandidateReceipt>> {
-		self.candidate_pending_availability.get(&key).map(|v| &v.0)
+		lookup!(self, CandidatePendingAvailability(key))
 	}
 
 	pub(crate) fn cache_candidate_pending_availability(
@@ -300,11 +402,14 @@ impl RequestResultCache {
 		key: (Hash, ParaId),
 		value: Option<CommittedCandidateReceipt>,
 	) {
-		self.candidate_pending_availability.insert(key, ResidentSizeOf(value));
+		self.insert(
+			CacheKey::CandidatePendingAvailability(key),
+			CacheValue::CandidatePendingAvailability(ResidentSizeOf(value)),
+		);
 	}
 
 	pub(crate) fn candidate_events(&mut self, relay_parent: &Hash) -> Option<&Vec<CandidateEvent>> {
-		self.candidate_events.get(relay_parent).map(|v| &v.0)
+		lookup!(self, CandidateEvents(*relay_parent))
 	}
 
 	pub(crate) fn cache_candidate_events(
@@ -312,22 +417,25 @@ impl RequestResultCache {
 		relay_parent: Hash,
 		events: Vec<CandidateEvent>,
 	) {
-		self.candidate_events.insert(relay_parent, ResidentSizeOf(events));
+		self.insert(
+			CacheKey::CandidateEvents(relay_parent),
+			CacheValue::CandidateEvents(ResidentSizeOf(events)),
+		);
 	}
 
 	pub(crate) fn session_info(&mut self, key: SessionIndex) -> Option<&SessionInfo> {
-		self.session_info.get(&key).map(|v| &v.0)
+		lookup!(self, SessionInfo(key))
 	}
 
 	pub(crate) fn cache_session_info(&mut self, key: SessionIndex, value: SessionInfo) {
-		self.session_info.insert(key, ResidentSizeOf(value));
+		self.insert(CacheKey::SessionInfo(key), CacheValue::SessionInfo(ResidentSizeOf(value)));
 	}
 
 	pub(crate) fn dmq_contents(
 		&mut self,
 		key: (Hash, ParaId),
 	) -> Option<&Vec<InboundDownwardMessage<BlockNumber>>> {
-		self.dmq_contents.get(&key).map(|v| &v.0)
+		lookup!(self, DmqContents(key))
 	}
 
 	pub(crate) fn cache_dmq_contents(
@@ -335,14 +443,14 @@ impl RequestResultCache {
 		key: (Hash, ParaId),
 		value: Vec<InboundDownwardMessage<BlockNumber>>,
 	) {
-		self.dmq_contents.insert(key, ResidentSizeOf(value));
+		self.insert(CacheKey::DmqContents(key), CacheValue::DmqContents(ResidentSizeOf(value)));
 	}
 
 	pub(crate) fn inbound_hrmp_channels_contents(
 		&mut self,
 		key: (Hash, ParaId),
 	) -> Option<&BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>> {
-		self.inbound_hrmp_channels_contents.get(&key).map(|v| &v.0)
+		lookup!(self, InboundHrmpChannelsContents(key))
 	}
 
 	pub(crate) fn cache_inbound_hrmp_channel_contents(
@@ -350,22 +458,28 @@ impl RequestResultCache {
 		key: (Hash, ParaId),
 		value: BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>,
 	) {
-		self.inbound_hrmp_channels_contents.insert(key, ResidentSizeOf(value));
+		self.insert(
+			CacheKey::InboundHrmpChannelsContents(key),
+			CacheValue::InboundHrmpChannelsContents(ResidentSizeOf(value)),
+		);
 	}
 
 	pub(crate) fn current_babe_epoch(&mut self, relay_parent: &Hash) -> Option<&Epoch> {
-		self.current_babe_epoch.get(relay_parent).map(|v| &v.0)
+		lookup!(self, CurrentBabeEpoch(*relay_parent))
 	}
 
 	pub(crate) fn cache_current_babe_epoch(&mut self, relay_parent: Hash, epoch: Epoch) {
-		self.current_babe_epoch.insert(relay_parent, DoesNotAllocate(epoch));
+		self.insert(
+			CacheKey::CurrentBabeEpoch(relay_parent),
+			CacheValue::CurrentBabeEpoch(DoesNotAllocate(epoch)),
+		);
 	}
 
 	pub(crate) fn on_chain_votes(
 		&mut self,
 		relay_parent: &Hash,
 	) -> Option<&Option<ScrapedOnChainVotes>> {
-		self.on_chain_votes.get(relay_parent).map(|v| &v.0)
+		lookup!(self, OnChainVotes(*relay_parent))
 	}
 
 	pub(crate) fn cache_on_chain_votes(
@@ -373,14 +487,17 @@ impl RequestResultCache {
 		relay_parent: Hash,
 		scraped: Option<ScrapedOnChainVotes>,
 	) {
-		self.on_chain_votes.insert(relay_parent, ResidentSizeOf(scraped));
+		self.insert(
+			CacheKey::OnChainVotes(relay_parent),
+			CacheValue::OnChainVotes(ResidentSizeOf(scraped)),
+		);
 	}
 
 	pub(crate) fn pvfs_require_precheck(
 		&mut self,
 		relay_parent: &Hash,
 	) -> Option<&Vec<ValidationCodeHash>> {
-		self.pvfs_require_precheck.get(relay_parent).map(|v| &v.0)
+		lookup!(self, PvfsRequirePrecheck(*relay_parent))
 	}
 
 	pub(crate) fn cache_pvfs_require_precheck(
@@ -388,14 +505,17 @@ impl RequestResultCache {
 		relay_parent: Hash,
 		pvfs: Vec<ValidationCodeHash>,
 	) {
-		self.pvfs_require_precheck.insert(relay_parent, ResidentSizeOf(pvfs))
+		self.insert(
+			CacheKey::PvfsRequirePrecheck(relay_parent),
+			CacheValue::PvfsRequirePrecheck(ResidentSizeOf(pvfs)),
+		)
 	}
 
 	pub(crate) fn validation_code_hash(
 		&mut self,
 		key: (Hash, ParaId, OccupiedCoreAssumption),
 	) -> Option<&Option<ValidationCodeHash>> {
-		self.validation_code_hash.get(&key).map(|v| &v.0)
+		lookup!(self, ValidationCodeHash(key))
 	}
 
 	pub(crate) fn cache_validation_code_hash(
@@ -403,22 +523,25 @@ impl RequestResultCache {
 		key: (Hash, ParaId, OccupiedCoreAssumption),
 		value: Option<ValidationCodeHash>,
 	) {
-		self.validation_code_hash.insert(key, ResidentSizeOf(value));
+		self.insert(
+			CacheKey::ValidationCodeHash(key),
+			CacheValue::ValidationCodeHash(ResidentSizeOf(value)),
+		);
 	}
 
 	pub(crate) fn version(&mut self, relay_parent: &Hash) -> Option<&u32> {
-		self.version.get(&relay_parent).map(|v| &v.0)
+		lookup!(self, Version(*relay_parent))
 	}
 
 	pub(crate) fn cache_version(&mut self, key: Hash, value: u32) {
-		self.version.insert(key, ResidentSizeOf(value));
+		self.insert(CacheKey::Version(key), CacheValue::Version(ResidentSizeOf(value)));
 	}
 
 	pub(crate) fn disputes(
 		&mut self,
 		relay_parent: &Hash,
 	) -> Option<&Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>> {
-		self.disputes.get(relay_parent).map(|v| &v.0)
+		lookup!(self, Disputes(*relay_parent))
 	}
 
 	pub(crate) fn cache_disputes(
@@ -426,7 +549,7 @@ impl RequestResultCache {
 		relay_parent: Hash,
 		value: Vec<(SessionIndex, CandidateHash, DisputeState<BlockNumber>)>,
 	) {
-		self.disputes.insert(relay_parent, ResidentSizeOf(value));
+		self.insert(CacheKey::Disputes(relay_parent), CacheValue::Disputes(ResidentSizeOf(value)));
 	}
 }
 
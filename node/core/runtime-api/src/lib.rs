@@ -35,6 +35,7 @@ use futures::{channel::oneshot, prelude::*, select, stream::FuturesUnordered};
 use std::sync::Arc;
 
 mod cache;
+pub use cache::DEFAULT_CACHE_BUDGET;
 
 mod metrics;
 use self::metrics::Metrics;
@@ -64,17 +65,29 @@ pub struct RuntimeApiSubsystem<Client> {
 
 impl<Client> RuntimeApiSubsystem<Client> {
 	/// Create a new Runtime API subsystem wrapping the given client and metrics.
+	///
+	/// Results are cached within [`DEFAULT_CACHE_BUDGET`].
 	pub fn new(
 		client: Arc<Client>,
 		metrics: Metrics,
 		spawner: impl overseer::gen::Spawner + 'static,
+	) -> Self {
+		Self::with_cache_budget(client, metrics, spawner, DEFAULT_CACHE_BUDGET)
+	}
+
+	/// Create a new Runtime API subsystem, caching results in at most `cache_budget` bytes.
+	pub fn with_cache_budget(
+		client: Arc<Client>,
+		metrics: Metrics,
+		spawner: impl overseer::gen::Spawner + 'static,
+		cache_budget: usize,
 	) -> Self {
 		RuntimeApiSubsystem {
 			client,
+			requests_cache: RequestResultCache::new(cache_budget, metrics.clone()),
 			metrics,
 			spawn_handle: Box::new(spawner),
 			active_requests: Default::default(),
-			requests_cache: RequestResultCache::default(),
 		}
 	}
 }
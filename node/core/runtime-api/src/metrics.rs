@@ -20,6 +20,9 @@ use polkadot_node_subsystem_util::metrics::{self, prometheus};
 pub(crate) struct MetricsInner {
 	pub(crate) chain_api_requests: prometheus::CounterVec<prometheus::U64>,
 	pub(crate) make_runtime_api_request: prometheus::Histogram,
+	pub(crate) cache_lookups: prometheus::CounterVec<prometheus::U64>,
+	pub(crate) cache_evictions: prometheus::CounterVec<prometheus::U64>,
+	pub(crate) cache_size: prometheus::Gauge<prometheus::U64>,
 }
 
 /// Runtime API metrics.
@@ -43,6 +46,28 @@ impl Metrics {
 			.map(|metrics| metrics.chain_api_requests.with_label_values(&["cached"]).inc());
 	}
 
+	/// A lookup of a `request` in the cache, which was either a hit or a miss.
+	pub(crate) fn on_cache_lookup(&self, request: &'static str, hit: bool) {
+		if let Some(metrics) = &self.0 {
+			let result = if hit { "hit" } else { "miss" };
+			metrics.cache_lookups.with_label_values(&[request, result]).inc();
+		}
+	}
+
+	/// A cached result of a `request` got evicted to stay within the memory budget.
+	pub(crate) fn on_cache_eviction(&self, request: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.cache_evictions.with_label_values(&[request]).inc();
+		}
+	}
+
+	/// Memory used by the cache, in bytes.
+	pub(crate) fn on_cache_size(&self, bytes: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.cache_size.set(bytes as u64);
+		}
+	}
+
 	/// Provide a timer for `make_runtime_api_request` which observes on drop.
 	pub fn time_make_runtime_api_request(
 		&self,
@@ -71,6 +96,33 @@ impl metrics::Metrics for Metrics {
 				))?,
 				registry,
 			)?,
+			cache_lookups: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_runtime_api_cache_lookups_total",
+						"Number of Runtime API cache lookups, by request and whether they hit.",
+					),
+					&["request", "result"],
+				)?,
+				registry,
+			)?,
+			cache_evictions: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_runtime_api_cache_evictions_total",
+						"Number of Runtime API results evicted from the cache, by request.",
+					),
+					&["request"],
+				)?,
+				registry,
+			)?,
+			cache_size: prometheus::register(
+				prometheus::Gauge::new(
+					"polkadot_parachain_runtime_api_cache_size_bytes",
+					"Memory used by cached Runtime API results, in bytes.",
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
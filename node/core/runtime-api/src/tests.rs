@@ -1082,3 +1082,63 @@ fn requests_validation_code_hash() {
 
 	futures::executor::block_on(future::join(subsystem_task, test_task));
 }
+
+#[test]
+fn cache_stays_within_budget() {
+	let code = |byte: u8| ValidationCode(vec![byte; 16 * 1024]);
+	// Enough for two, but not three entries:
+	let mut cache = cache::RequestResultCache::new(40 * 1024, Metrics(None));
+
+	cache.cache_validation_code_by_hash(code(1).hash(), Some(code(1)));
+	cache.cache_validation_code_by_hash(code(2).hash(), Some(code(2)));
+	assert!(cache.validation_code_by_hash((Hash::zero(), code(1).hash())).is_some());
+
+	// `code(2)` is the least recently used one now:
+	cache.cache_validation_code_by_hash(code(3).hash(), Some(code(3)));
+	assert!(cache.used() <= 40 * 1024);
+	assert!(cache.validation_code_by_hash((Hash::zero(), code(1).hash())).is_some());
+	assert!(cache.validation_code_by_hash((Hash::zero(), code(2).hash())).is_none());
+	assert!(cache.validation_code_by_hash((Hash::zero(), code(3).hash())).is_some());
+
+	// Entries larger than the whole budget are not cached at all:
+	let huge = ValidationCode(vec![4; 64 * 1024]);
+	cache.cache_validation_code_by_hash(huge.hash(), Some(huge.clone()));
+	assert!(cache.validation_code_by_hash((Hash::zero(), huge.hash())).is_none());
+	assert!(cache.validation_code_by_hash((Hash::zero(), code(3).hash())).is_some());
+}
+
+#[test]
+fn validation_code_is_shared_between_relay_parents() {
+	let code = ValidationCode(vec![1; 16 * 1024]);
+	let para = ParaId::from(5_u32);
+	let mut cache = cache::RequestResultCache::new(cache::DEFAULT_CACHE_BUDGET, Metrics(None));
+
+	let relay_parent_a: Hash = [1; 32].into();
+	let relay_parent_b: Hash = [2; 32].into();
+	cache.cache_validation_code(
+		(relay_parent_a, para, OccupiedCoreAssumption::Included),
+		Some(code.clone()),
+	);
+	let used = cache.used();
+	cache.cache_validation_code(
+		(relay_parent_b, para, OccupiedCoreAssumption::Included),
+		Some(code.clone()),
+	);
+	// The code itself is only stored once:
+	assert!(cache.used() - used < code.0.len());
+
+	assert_eq!(
+		cache.validation_code((relay_parent_b, para, OccupiedCoreAssumption::Included)),
+		Some(&Some(code.clone())),
+	);
+	assert_eq!(
+		cache.validation_code_by_hash((relay_parent_b, code.hash())),
+		Some(&Some(code.clone())),
+	);
+
+	cache.cache_validation_code((relay_parent_a, para, OccupiedCoreAssumption::TimedOut), None);
+	assert_eq!(
+		cache.validation_code((relay_parent_a, para, OccupiedCoreAssumption::TimedOut)),
+		Some(&None),
+	);
+}
@@ -16,20 +16,48 @@
 
 //! Polkadot Jaeger configuration.
 
+/// Destination of the collected spans.
+#[derive(Clone, Debug)]
+pub enum JaegerExporter {
+	/// A jaeger agent, receiving spans via UDP.
+	Agent(std::net::SocketAddr),
+	/// An OpenTelemetry collector, receiving spans via OTLP.
+	Otlp {
+		/// Where to reach the collector, i.e. `http://127.0.0.1:4317` for gRPC or
+		/// `http://127.0.0.1:4318/v1/traces` for HTTP.
+		endpoint: String,
+		/// The transport to use.
+		protocol: OtlpProtocol,
+	},
+}
+
+/// Transport of the OTLP exporter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OtlpProtocol {
+	/// gRPC, usually on port 4317.
+	Grpc,
+	/// Protobuf encoded HTTP requests, usually on port 4318.
+	Http,
+}
+
 /// Configuration for the jaeger tracing.
 #[derive(Clone)]
 pub struct JaegerConfig {
 	pub(crate) node_name: String,
-	pub(crate) agent_addr: std::net::SocketAddr,
+	pub(crate) exporter: JaegerExporter,
+	pub(crate) sampling_rate: f64,
 }
 
 impl std::default::Default for JaegerConfig {
 	fn default() -> Self {
 		Self {
 			node_name: "unknown_".to_owned(),
-			agent_addr: "127.0.0.1:6831"
-				.parse()
-				.expect(r#"Static "127.0.0.1:6831" is a valid socket address string. qed"#),
+			exporter: JaegerExporter::Agent(
+				"127.0.0.1:6831"
+					.parse()
+					.expect(r#"Static "127.0.0.1:6831" is a valid socket address string. qed"#),
+			),
+			sampling_rate: 1.0,
 		}
 	}
 }
@@ -62,7 +90,31 @@ impl JaegerConfigBuilder {
 	where
 		U: Into<std::net::SocketAddr>,
 	{
-		self.inner.agent_addr = addr.into();
+		self.inner.exporter = JaegerExporter::Agent(addr.into());
+		self
+	}
+
+	/// Export the collected spans to an OpenTelemetry collector instead of a jaeger agent.
+	pub fn otlp<S>(mut self, endpoint: S, protocol: OtlpProtocol) -> Self
+	where
+		S: Into<String>,
+	{
+		self.inner.exporter = JaegerExporter::Otlp { endpoint: endpoint.into(), protocol };
+		self
+	}
+
+	/// Set the destination of the collected spans.
+	pub fn exporter(mut self, exporter: JaegerExporter) -> Self {
+		self.inner.exporter = exporter;
+		self
+	}
+
+	/// Set the share of traces to record, between `0.0` and `1.0`.
+	///
+	/// Traces of candidates involved in disputes are always recorded,
+	/// see [`note_disputed_candidate`](crate::note_disputed_candidate).
+	pub fn sampling_rate(mut self, rate: f64) -> Self {
+		self.inner.sampling_rate = rate.clamp(0.0, 1.0);
 		self
 	}
 
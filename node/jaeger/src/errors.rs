@@ -25,4 +25,7 @@ pub enum JaegerError {
 
 	#[error("Missing jaeger configuration")]
 	MissingConfiguration,
+
+	#[error("Failed to create the OTLP exporter")]
+	Otlp(#[from] opentelemetry::trace::TraceError),
 }
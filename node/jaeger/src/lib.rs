@@ -43,20 +43,27 @@
 //!  -p 9411:9411 \
 //!  docker.io/jaegertracing/all-in-one:1.21
 //! ```
+//!
+//! Alternatively, spans can be exported to an OpenTelemetry collector via OTLP,
+//! see [`JaegerConfigBuilder::otlp`].
 
 #![forbid(unused_imports)]
 
 mod config;
 mod errors;
+mod otlp;
+mod sampling;
 mod spans;
 
 pub use self::{
-	config::{JaegerConfig, JaegerConfigBuilder},
+	config::{JaegerConfig, JaegerConfigBuilder, JaegerExporter, OtlpProtocol},
 	errors::JaegerError,
 	spans::{hash_to_trace_identifier, PerLeafSpan, Span, Stage},
 };
 
-use self::spans::TraceIdentifier;
+use self::{sampling::Sampler, spans::TraceIdentifier};
+
+use polkadot_primitives::v2::CandidateHash;
 
 use sp_core::traits::SpawnNamed;
 
@@ -67,12 +74,22 @@ lazy_static::lazy_static! {
 	static ref INSTANCE: RwLock<Jaeger> = RwLock::new(Jaeger::None);
 }
 
-/// Stateful convenience wrapper around [`mick_jaeger`].
+/// Backend recording the spans.
+pub enum Tracer {
+	/// [`mick_jaeger`] provided API to record spans to, sent to a jaeger agent.
+	Agent(Arc<mick_jaeger::TracesIn>),
+	/// OpenTelemetry SDK provided API to record spans to, exported via OTLP.
+	Otlp(otlp::Tracer),
+}
+
+/// Stateful convenience wrapper around [`mick_jaeger`] and the OTLP exporter.
 pub enum Jaeger {
 	/// Launched and operational state.
 	Launched {
-		/// [`mick_jaeger`] provided API to record spans to.
-		traces_in: Arc<mick_jaeger::TracesIn>,
+		/// Backend to record spans to.
+		tracer: Tracer,
+		/// Decides which traces to record.
+		sampler: Sampler,
 	},
 	/// Preparation state with the necessary config to launch the collector.
 	Prep(JaegerConfig),
@@ -102,12 +119,14 @@ impl Jaeger {
 				let (traces_in, _traces_out) = mick_jaeger::init(mick_jaeger::Config {
 					service_name: "polkadot-jaeger-test".to_owned(),
 				});
-				*instance = Self::Launched { traces_in };
+				*instance =
+					Self::Launched { tracer: Tracer::Agent(traces_in), sampler: Sampler::new(1.0) };
 			},
 		}
 	}
 
-	/// Spawn the background task in order to send the tracing information out via UDP
+	/// Spawn the background task in order to send the tracing information out
+	/// via UDP or OTLP.
 	#[cfg(not(target_os = "unknown"))]
 	pub fn launch<S: SpawnNamed>(self, spawner: S) -> result::Result<(), JaegerError> {
 		let cfg = match self {
@@ -116,13 +135,23 @@ impl Jaeger {
 			Self::None => Err(JaegerError::MissingConfiguration),
 		}?;
 
-		let jaeger_agent = cfg.agent_addr;
+		let service_name = format!("polkadot-{}", cfg.node_name);
+		let sampler = Sampler::new(cfg.sampling_rate);
+
+		let jaeger_agent = match cfg.exporter {
+			JaegerExporter::Agent(addr) => addr,
+			JaegerExporter::Otlp { endpoint, protocol } => {
+				log::info!("🐹 Exporting jaeger spans to {} via OTLP", &endpoint);
+
+				let tracer = otlp::Tracer::launch(spawner, service_name, endpoint, protocol)?;
+				*INSTANCE.write() = Self::Launched { tracer: Tracer::Otlp(tracer), sampler };
+				return Ok(())
+			},
+		};
 
 		log::info!("🐹 Collecting jaeger spans for {:?}", &jaeger_agent);
 
-		let (traces_in, mut traces_out) = mick_jaeger::init(mick_jaeger::Config {
-			service_name: format!("polkadot-{}", cfg.node_name),
-		});
+		let (traces_in, mut traces_out) = mick_jaeger::init(mick_jaeger::Config { service_name });
 
 		// Spawn a background task that pulls span information and sends them on the network.
 		spawner.spawn(
@@ -144,7 +173,7 @@ impl Jaeger {
 			}),
 		);
 
-		*INSTANCE.write() = Self::Launched { traces_in };
+		*INSTANCE.write() = Self::Launched { tracer: Tracer::Agent(traces_in), sampler };
 		Ok(())
 	}
 
@@ -152,16 +181,32 @@ impl Jaeger {
 	///
 	/// The deferral allows to avoid the additional CPU runtime cost in case of
 	/// items that are not a pre-computed hash by themselves.
-	pub(crate) fn span<F>(&self, lazy_hash: F, span_name: &'static str) -> Option<mick_jaeger::Span>
+	pub(crate) fn span<F>(&self, lazy_hash: F, span_name: &'static str) -> Span
 	where
 		F: Fn() -> TraceIdentifier,
 	{
-		if let Self::Launched { traces_in, .. } = self {
+		if let Self::Launched { tracer, sampler } = self {
 			let ident = lazy_hash();
-			let trace_id = std::num::NonZeroU128::new(ident)?;
-			Some(traces_in.span(trace_id, span_name))
+			let trace_id = match std::num::NonZeroU128::new(ident) {
+				Some(trace_id) if sampler.should_sample(ident) => trace_id,
+				_ => return Span::Disabled,
+			};
+			match tracer {
+				Tracer::Agent(traces_in) => Span::Enabled(traces_in.span(trace_id, span_name)),
+				Tracer::Otlp(tracer) => Span::Otlp(tracer.span(trace_id, span_name)),
+			}
 		} else {
-			None
+			Span::Disabled
 		}
 	}
 }
+
+/// Record all spans of the candidate from now on, regardless of the sampling rate.
+///
+/// Only affects spans identified by the candidate hash, i.e. created with
+/// `Span::new(candidate_hash, ..)`.
+pub fn note_disputed_candidate(candidate_hash: CandidateHash) {
+	if let Jaeger::Launched { sampler, .. } = &*INSTANCE.read_recursive() {
+		sampler.note_disputed(hash_to_trace_identifier(candidate_hash.0));
+	}
+}
@@ -0,0 +1,173 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Polkadot Jaeger OTLP exporter.
+//!
+//! Spans are recorded with the OpenTelemetry SDK, finished spans are queued up
+//! and exported in batches by a background task.
+
+use opentelemetry::{
+	sdk::{
+		self,
+		export::trace::{SpanData, SpanExporter as _},
+	},
+	trace::{Span as _, TraceContextExt, TraceId, TraceResult, Tracer as _, TracerProvider as _},
+	Context, KeyValue,
+};
+use opentelemetry_otlp::{SpanExporterBuilder, WithExportConfig};
+use sp_core::traits::SpawnNamed;
+
+use std::{num::NonZeroU128, result};
+
+use super::{JaegerError, OtlpProtocol, TraceIdentifier};
+
+/// Maximum number of finished spans waiting to be exported.
+const SPAN_QUEUE_SIZE: usize = 4096;
+
+/// Maximum number of spans exported with a single request.
+const MAX_EXPORT_BATCH_SIZE: usize = 512;
+
+/// Records spans to be exported via OTLP.
+#[derive(Clone)]
+pub struct Tracer {
+	// Tracers only keep a weak reference to their provider.
+	_provider: sdk::trace::TracerProvider,
+	tracer: sdk::trace::Tracer,
+}
+
+impl Tracer {
+	/// Create a tracer and spawn the task exporting its spans to `endpoint`.
+	pub(crate) fn launch<S: SpawnNamed>(
+		spawner: S,
+		service_name: String,
+		endpoint: String,
+		protocol: OtlpProtocol,
+	) -> result::Result<Self, JaegerError> {
+		let exporter = match protocol {
+			OtlpProtocol::Grpc => SpanExporterBuilder::from(
+				opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint),
+			),
+			OtlpProtocol::Http => SpanExporterBuilder::from(
+				opentelemetry_otlp::new_exporter().http().with_endpoint(endpoint),
+			),
+		};
+		let mut exporter = exporter.build_span_exporter()?;
+
+		let (tx, rx) = async_std::channel::bounded(SPAN_QUEUE_SIZE);
+
+		spawner.spawn(
+			"jaeger-collector",
+			Some("jaeger"),
+			Box::pin(async move {
+				while let Ok(span) = rx.recv().await {
+					let mut batch = vec![span];
+					while batch.len() < MAX_EXPORT_BATCH_SIZE {
+						match rx.try_recv() {
+							Ok(span) => batch.push(span),
+							Err(_) => break,
+						}
+					}
+					if let Err(e) = exporter.export(batch).await {
+						log::debug!(target: "jaeger", "OTLP export error: {}", e);
+					}
+				}
+			}),
+		);
+
+		// Sampling happens before the spans are created, see `Sampler`.
+		let config = sdk::trace::config()
+			.with_sampler(sdk::trace::Sampler::AlwaysOn)
+			.with_resource(sdk::Resource::new(vec![KeyValue::new("service.name", service_name)]));
+		let provider = sdk::trace::TracerProvider::builder()
+			.with_span_processor(QueueSpans(tx))
+			.with_config(config)
+			.build();
+		let tracer = provider.tracer("polkadot-jaeger");
+
+		Ok(Self { _provider: provider, tracer })
+	}
+
+	/// Create a root span of the given trace.
+	pub(crate) fn span(&self, trace_id: NonZeroU128, span_name: &'static str) -> Span {
+		let span = self
+			.tracer
+			.span_builder(span_name)
+			.with_trace_id(TraceId::from_bytes(trace_id.get().to_be_bytes()))
+			.start_with_context(&self.tracer, &Context::new());
+		Span { span, tracer: self.tracer.clone() }
+	}
+}
+
+/// Hands finished spans over to the export task.
+#[derive(Debug)]
+struct QueueSpans(async_std::channel::Sender<SpanData>);
+
+impl sdk::trace::SpanProcessor for QueueSpans {
+	fn on_start(&self, _span: &mut sdk::trace::Span, _cx: &Context) {}
+
+	fn on_end(&self, span: SpanData) {
+		if self.0.try_send(span).is_err() {
+			log::debug!(target: "jaeger", "OTLP export is lagging behind, dropping span");
+		}
+	}
+
+	fn force_flush(&self) -> TraceResult<()> {
+		Ok(())
+	}
+
+	fn shutdown(&mut self) -> TraceResult<()> {
+		self.0.close();
+		Ok(())
+	}
+}
+
+/// A span recorded with the OpenTelemetry SDK, ends when dropped.
+pub struct Span {
+	span: sdk::trace::Span,
+	tracer: sdk::trace::Tracer,
+}
+
+impl Span {
+	/// Derive a child span from `self`.
+	pub(crate) fn child(&self, name: &'static str) -> Self {
+		let parent = Context::new().with_remote_span_context(self.span.span_context().clone());
+		Self { span: self.tracer.start_with_context(name, &parent), tracer: self.tracer.clone() }
+	}
+
+	pub(crate) fn add_string_tag(&mut self, tag: &'static str, value: &str) {
+		self.span.set_attribute(KeyValue::new(tag, value.to_owned()));
+	}
+
+	pub(crate) fn add_int_tag(&mut self, tag: &'static str, value: i64) {
+		self.span.set_attribute(KeyValue::new(tag, value));
+	}
+
+	/// Links can only be added when creating a span, note the relationship as an event instead.
+	pub(crate) fn add_follows_from(&mut self, other: &Self) {
+		let other = other.span.span_context();
+		self.span.add_event(
+			"follows-from",
+			vec![
+				KeyValue::new("trace-id", other.trace_id().to_string()),
+				KeyValue::new("span-id", other.span_id().to_string()),
+			],
+		);
+	}
+
+	pub(crate) fn trace_id(&self) -> TraceIdentifier {
+		u128::from_be_bytes(self.span.span_context().trace_id().to_bytes())
+	}
+}
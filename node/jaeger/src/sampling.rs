@@ -0,0 +1,108 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Polkadot Jaeger sampling.
+//!
+//! The decision is made once per root span, all children of a span share its fate.
+//! Since it only depends on the trace identifier, all root spans of one candidate
+//! are either recorded or not.
+
+use parking_lot::RwLock;
+use std::collections::{HashSet, VecDeque};
+
+use super::spans::TraceIdentifier;
+
+/// Number of disputed candidates remembered, the oldest are forgotten first.
+const MAX_DISPUTED_TRACES: usize = 1024;
+
+/// Decides which traces are recorded.
+pub struct Sampler {
+	// Traces with a lower 64 bit value of the identifier are recorded,
+	// `None` if all are.
+	threshold: Option<u64>,
+	disputed: RwLock<DisputedTraces>,
+}
+
+#[derive(Default)]
+struct DisputedTraces {
+	traces: HashSet<TraceIdentifier>,
+	order: VecDeque<TraceIdentifier>,
+}
+
+impl Sampler {
+	/// Record the given share of traces, between `0.0` and `1.0`.
+	pub(crate) fn new(rate: f64) -> Self {
+		let threshold = if rate >= 1.0 { None } else { Some((rate * u64::MAX as f64) as u64) };
+		Self { threshold, disputed: RwLock::new(DisputedTraces::default()) }
+	}
+
+	/// Whether spans of the given trace are recorded.
+	pub(crate) fn should_sample(&self, trace_id: TraceIdentifier) -> bool {
+		match self.threshold {
+			None => true,
+			Some(threshold) =>
+				(trace_id as u64) < threshold || self.disputed.read().traces.contains(&trace_id),
+		}
+	}
+
+	/// Record the given trace from now on, regardless of the sampling rate.
+	pub(crate) fn note_disputed(&self, trace_id: TraceIdentifier) {
+		let mut disputed = self.disputed.write();
+		if !disputed.traces.insert(trace_id) {
+			return
+		}
+		disputed.order.push_back(trace_id);
+		if disputed.order.len() > MAX_DISPUTED_TRACES {
+			if let Some(oldest) = disputed.order.pop_front() {
+				disputed.traces.remove(&oldest);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sampling_rate_is_respected() {
+		let all = Sampler::new(1.0);
+		let none = Sampler::new(0.0);
+		let half = Sampler::new(0.5);
+
+		let low = 1u128 << 64 | 7;
+		let high = 7u128 << 64 | u64::MAX as u128;
+
+		assert!(all.should_sample(low) && all.should_sample(high));
+		assert!(!none.should_sample(low) && !none.should_sample(high));
+		assert!(half.should_sample(low));
+		assert!(!half.should_sample(high));
+	}
+
+	#[test]
+	fn disputed_traces_are_always_sampled() {
+		let sampler = Sampler::new(0.0);
+		sampler.note_disputed(42);
+		assert!(sampler.should_sample(42));
+		assert!(!sampler.should_sample(43));
+
+		for trace_id in 0..MAX_DISPUTED_TRACES as u128 {
+			sampler.note_disputed(1000 + trace_id);
+		}
+		assert!(!sampler.should_sample(42));
+		assert!(sampler.should_sample(1000));
+	}
+}
@@ -92,7 +92,7 @@ use sc_network::PeerId;
 
 use std::{fmt, sync::Arc};
 
-use super::INSTANCE;
+use super::{otlp, INSTANCE};
 
 /// A special "per leaf span".
 ///
@@ -164,6 +164,8 @@ pub enum Stage {
 pub enum Span {
 	/// Running with jaeger being enabled.
 	Enabled(mick_jaeger::Span),
+	/// Running with the OTLP exporter being enabled.
+	Otlp(otlp::Span),
 	/// Running with jaeger disabled.
 	Disabled,
 }
@@ -256,8 +258,7 @@ impl Span {
 	pub fn new<I: LazyIdent>(identifier: I, span_name: &'static str) -> Span {
 		let mut span = INSTANCE
 			.read_recursive()
-			.span(|| <I as LazyIdent>::eval(&identifier), span_name)
-			.into();
+			.span(|| <I as LazyIdent>::eval(&identifier), span_name);
 		<I as LazyIdent>::extra_tags(&identifier, &mut span);
 		span
 	}
@@ -265,22 +266,20 @@ impl Span {
 	/// Creates a new span builder based on an encodable type.
 	/// The encoded bytes are then used to derive the true trace identifier.
 	pub fn from_encodable<I: Encode>(identifier: I, span_name: &'static str) -> Span {
-		INSTANCE
-			.read_recursive()
-			.span(
-				move || {
-					let bytes = identifier.encode();
-					LazyIdent::eval(&bytes.as_slice())
-				},
-				span_name,
-			)
-			.into()
+		INSTANCE.read_recursive().span(
+			move || {
+				let bytes = identifier.encode();
+				LazyIdent::eval(&bytes.as_slice())
+			},
+			span_name,
+		)
 	}
 
 	/// Derive a child span from `self`.
 	pub fn child(&self, name: &'static str) -> Self {
 		match self {
 			Self::Enabled(inner) => Self::Enabled(inner.child(name)),
+			Self::Otlp(inner) => Self::Otlp(inner.child(name)),
 			Self::Disabled => Self::Disabled,
 		}
 	}
@@ -368,6 +367,8 @@ impl Span {
 		match (self, other) {
 			(Self::Enabled(ref mut inner), Self::Enabled(ref other_inner)) =>
 				inner.add_follows_from(&other_inner),
+			(Self::Otlp(ref mut inner), Self::Otlp(ref other_inner)) =>
+				inner.add_follows_from(other_inner),
 			_ => {},
 		}
 	}
@@ -390,6 +391,7 @@ impl Span {
 	pub fn add_string_tag<V: ToString>(&mut self, tag: &'static str, val: V) {
 		match self {
 			Self::Enabled(ref mut inner) => inner.add_string_tag(tag, val.to_string().as_str()),
+			Self::Otlp(ref mut inner) => inner.add_string_tag(tag, val.to_string().as_str()),
 			Self::Disabled => {},
 		}
 	}
@@ -399,6 +401,7 @@ impl Span {
 		match self {
 			Self::Enabled(ref mut inner) =>
 				inner.add_string_tag(tag, format!("{:?}", val).as_str()),
+			Self::Otlp(ref mut inner) => inner.add_string_tag(tag, format!("{:?}", val).as_str()),
 			Self::Disabled => {},
 		}
 	}
@@ -406,6 +409,7 @@ impl Span {
 	pub fn add_int_tag(&mut self, tag: &'static str, value: i64) {
 		match self {
 			Self::Enabled(ref mut inner) => inner.add_int_tag(tag, value),
+			Self::Otlp(ref mut inner) => inner.add_int_tag(tag, value),
 			Self::Disabled => {},
 		}
 	}
@@ -413,6 +417,7 @@ impl Span {
 	pub fn add_uint_tag(&mut self, tag: &'static str, value: u64) {
 		match self {
 			Self::Enabled(ref mut inner) => inner.add_int_tag(tag, value as i64),
+			Self::Otlp(ref mut inner) => inner.add_int_tag(tag, value as i64),
 			Self::Disabled => {},
 		}
 	}
@@ -421,7 +426,7 @@ impl Span {
 	/// in order to avoid computational overhead.
 	pub const fn is_enabled(&self) -> bool {
 		match self {
-			Span::Enabled(_) => true,
+			Span::Enabled(_) | Span::Otlp(_) => true,
 			_ => false,
 		}
 	}
@@ -430,6 +435,7 @@ impl Span {
 	pub fn trace_id(&self) -> Option<TraceIdentifier> {
 		match self {
 			Span::Enabled(inner) => Some(inner.trace_id().get()),
+			Span::Otlp(inner) => Some(inner.trace_id()),
 			_ => None,
 		}
 	}
@@ -0,0 +1,122 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Export of spans to a mock OpenTelemetry collector.
+
+use futures::future::BoxFuture;
+use hyper::{
+	service::{make_service_fn, service_fn},
+	Body, Request, Response, Server,
+};
+use polkadot_node_jaeger::{note_disputed_candidate, Jaeger, JaegerConfig, OtlpProtocol, Span};
+use polkadot_primitives::v2::{CandidateHash, Hash, Id as ParaId};
+use sp_core::traits::SpawnNamed;
+use std::{convert::Infallible, net::SocketAddr, time::Duration};
+use tokio::sync::mpsc;
+
+/// The OTLP HTTP client requires a tokio runtime.
+#[derive(Clone)]
+struct TokioSpawner;
+
+impl SpawnNamed for TokioSpawner {
+	fn spawn_blocking(
+		&self,
+		_name: &'static str,
+		_group: Option<&'static str>,
+		future: BoxFuture<'static, ()>,
+	) {
+		tokio::task::spawn_blocking(move || futures::executor::block_on(future));
+	}
+
+	fn spawn(
+		&self,
+		_name: &'static str,
+		_group: Option<&'static str>,
+		future: BoxFuture<'static, ()>,
+	) {
+		tokio::spawn(future);
+	}
+}
+
+/// Accepts any request, forwarding its path and body.
+fn mock_collector() -> (SocketAddr, mpsc::UnboundedReceiver<(String, Vec<u8>)>) {
+	let (tx, rx) = mpsc::unbounded_channel();
+	let make_service = make_service_fn(move |_| {
+		let tx = tx.clone();
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+				let tx = tx.clone();
+				async move {
+					let path = request.uri().path().to_owned();
+					let body = hyper::body::to_bytes(request.into_body())
+						.await
+						.expect("Request body is sent in full. qed");
+					let _ = tx.send((path, body.to_vec()));
+					Ok::<_, Infallible>(Response::new(Body::empty()))
+				}
+			}))
+		}
+	});
+
+	let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+	let addr = server.local_addr();
+	tokio::spawn(server);
+	(addr, rx)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+	haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn disputed_candidate_spans_are_exported() {
+	let (addr, mut requests) = mock_collector();
+
+	let cfg = JaegerConfig::builder()
+		.named("otlp-test")
+		.otlp(format!("http://{}/v1/traces", addr), OtlpProtocol::Http)
+		.sampling_rate(0.0)
+		.build();
+	Jaeger::new(cfg).launch(TokioSpawner).unwrap();
+
+	let candidate = CandidateHash(Hash::repeat_byte(1));
+	let disputed_candidate = CandidateHash(Hash::repeat_byte(2));
+	note_disputed_candidate(disputed_candidate);
+
+	let span = Span::new(candidate, "sampled-out-span");
+	assert!(!span.is_enabled());
+	drop(span);
+
+	{
+		let span = Span::new(disputed_candidate, "disputed-span").with_para_id(ParaId::from(7));
+		assert!(span.is_enabled());
+		let _child = span.child("disputed-child-span");
+	}
+
+	let mut exported = Vec::new();
+	while !contains(&exported, b"disputed-span") || !contains(&exported, b"disputed-child-span") {
+		let (path, body) = tokio::time::timeout(Duration::from_secs(10), requests.recv())
+			.await
+			.expect("Spans are exported in time")
+			.expect("Collector keeps running");
+		assert_eq!(path, "/v1/traces");
+		exported.extend(body);
+	}
+
+	assert!(contains(&exported, b"polkadot-otlp-test"));
+	assert!(contains(&exported, b"para-id"));
+	assert!(!contains(&exported, b"sampled-out-span"));
+}
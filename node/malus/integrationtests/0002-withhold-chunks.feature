@@ -0,0 +1,27 @@
+Description: Availability with 1/3 of the validators withholding their chunks
+Network: ./0002-withhold-chunks.toml
+Creds: config
+
+honest-validator-0: is up
+honest-validator-1: is up
+honest-validator-2: is up
+malus-validator-0: is up
+
+# Check authority status.
+honest-validator-0: reports node_roles is 4
+honest-validator-1: reports node_roles is 4
+honest-validator-2: reports node_roles is 4
+malus-validator-0: reports node_roles is 4
+
+# Parachains should be making progress even if we have up to 1/3 malicious validators.
+honest-validator-0: parachain 2000 block height is at least 2 within 180 seconds
+honest-validator-1: parachain 2001 block height is at least 2 within 180 seconds
+honest-validator-2: parachain 2002 block height is at least 2 within 180 seconds
+
+# The malicious validator refuses to serve any chunk it holds.
+malus-validator-0: reports polkadot_parachain_served_chunks_total{success="not-found"} is at least 1 within 180 seconds
+
+# Parachains keep making progress.
+honest-validator-0: parachain 2000 block height is at least 10 within 300 seconds
+honest-validator-1: parachain 2001 block height is at least 10 within 300 seconds
+honest-validator-2: parachain 2002 block height is at least 10 within 300 seconds
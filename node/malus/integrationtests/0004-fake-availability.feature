@@ -0,0 +1,29 @@
+Description: Availability with 1/3 of the validators claiming chunks they do not check for
+Network: ./0004-fake-availability.toml
+Creds: config
+
+honest-validator-0: is up
+honest-validator-1: is up
+honest-validator-2: is up
+malus-validator-0: is up
+
+# Check authority status.
+honest-validator-0: reports node_roles is 4
+honest-validator-1: reports node_roles is 4
+honest-validator-2: reports node_roles is 4
+malus-validator-0: reports node_roles is 4
+
+# Parachains should be making progress even if we have up to 1/3 malicious validators.
+honest-validator-0: parachain 2000 block height is at least 2 within 180 seconds
+honest-validator-1: parachain 2001 block height is at least 2 within 180 seconds
+honest-validator-2: parachain 2002 block height is at least 2 within 180 seconds
+
+# The malicious validator signs bitfields without consulting its availability store.
+malus-validator-0: log line contains "Claiming availability of chunk without checking the store" within 180 seconds
+malus-validator-0: reports polkadot_parachain_bitfields_signed_total is at least 1
+
+# Parachains keep making progress and no candidate becomes unrecoverable.
+honest-validator-0: parachain 2000 block height is at least 10 within 300 seconds
+honest-validator-1: parachain 2001 block height is at least 10 within 300 seconds
+honest-validator-2: parachain 2002 block height is at least 10 within 300 seconds
+honest-validator-0: reports parachain_candidate_disputes_total is 0
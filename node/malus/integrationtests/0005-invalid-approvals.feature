@@ -0,0 +1,30 @@
+Description: Approval voting with 1/3 of the validators voting invalid without checking candidates
+Network: ./0005-invalid-approvals.toml
+Creds: config
+
+honest-validator-0: is up
+honest-validator-1: is up
+honest-validator-2: is up
+malus-validator-0: is up
+
+# Check authority status.
+honest-validator-0: reports node_roles is 4
+honest-validator-1: reports node_roles is 4
+honest-validator-2: reports node_roles is 4
+malus-validator-0: reports node_roles is 4
+
+# Parachains should be making progress even if we have up to 1/3 malicious validators.
+honest-validator-0: parachain 2000 block height is at least 2 within 180 seconds
+honest-validator-1: parachain 2001 block height is at least 2 within 180 seconds
+honest-validator-2: parachain 2002 block height is at least 2 within 180 seconds
+
+# The malicious validator raises disputes on valid candidates.
+honest-validator-0: reports parachain_candidate_disputes_total is at least 1 within 250 seconds
+honest-validator-1: reports parachain_candidate_disputes_total is at least 1 within 90 seconds
+honest-validator-2: reports parachain_candidate_disputes_total is at least 1 within 90 seconds
+
+# Disputes should always end as "valid".
+honest-validator-0: reports parachain_candidate_dispute_concluded{validity="valid"} is at least 1 within 90 seconds
+honest-validator-0: reports parachain_candidate_dispute_concluded{validity="invalid"} is 0 within 90 seconds
+honest-validator-1: reports parachain_candidate_dispute_concluded{validity="valid"} is at least 1 within 90 seconds
+honest-validator-2: reports parachain_candidate_dispute_concluded{validity="valid"} is at least 1 within 90 seconds
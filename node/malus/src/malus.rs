@@ -38,6 +38,14 @@ enum NemesisVariant {
 	BackGarbageCandidate(RunCmd),
 	/// Delayed disputing of ancestors that are perfectly fine.
 	DisputeAncestor(DisputeAncestorOptions),
+	/// Store availability chunks, but refuse to serve them to anyone.
+	WithholdChunks(RunCmd),
+	/// Second a conflicting candidate for every candidate seconded.
+	EquivocateStatements(RunCmd),
+	/// Sign availability bitfields claiming chunks that were never fetched.
+	FakeAvailability(RunCmd),
+	/// Vote invalid on all candidates assigned in approval voting, without checking them.
+	InvalidApprovals(RunCmd),
 
 	#[allow(missing_docs)]
 	#[clap(name = "prepare-worker", hide = true)]
@@ -75,6 +83,14 @@ impl MalusCli {
 				DisputeValidCandidates::new(opts),
 				finality_delay,
 			)?,
+			NemesisVariant::WithholdChunks(cmd) =>
+				polkadot_cli::run_node(run_cmd(cmd), WithholdChunks, finality_delay)?,
+			NemesisVariant::EquivocateStatements(cmd) =>
+				polkadot_cli::run_node(run_cmd(cmd), EquivocateStatements, finality_delay)?,
+			NemesisVariant::FakeAvailability(cmd) =>
+				polkadot_cli::run_node(run_cmd(cmd), FakeAvailability, finality_delay)?,
+			NemesisVariant::InvalidApprovals(cmd) =>
+				polkadot_cli::run_node(run_cmd(cmd), InvalidApprovals, finality_delay)?,
 			NemesisVariant::PvfPrepareWorker(cmd) => {
 				#[cfg(target_os = "android")]
 				{
@@ -129,4 +145,30 @@ mod tests {
 			assert!(run.cmd.base.bob);
 		});
 	}
+
+	#[test]
+	fn adversary_subcommands_work() {
+		let parse = |subcommand| {
+			MalusCli::try_parse_from(IntoIterator::into_iter(["malus", subcommand, "--bob"]))
+				.unwrap()
+				.variant
+		};
+
+		assert_matches::assert_matches!(
+			parse("withhold-chunks"),
+			NemesisVariant::WithholdChunks(run) => assert!(run.base.bob)
+		);
+		assert_matches::assert_matches!(
+			parse("equivocate-statements"),
+			NemesisVariant::EquivocateStatements(_)
+		);
+		assert_matches::assert_matches!(
+			parse("fake-availability"),
+			NemesisVariant::FakeAvailability(_)
+		);
+		assert_matches::assert_matches!(
+			parse("invalid-approvals"),
+			NemesisVariant::InvalidApprovals(_)
+		);
+	}
 }
@@ -0,0 +1,184 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node that double-votes in backing: whenever it seconds a candidate, it also signs
+//! a `Seconded` statement for a conflicting candidate at the same relay parent and distributes
+//! both. Honest validators are expected to detect the equivocation and report the misbehavior.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use polkadot_cli::{
+	prepared_overseer_builder,
+	service::{
+		AuthorityDiscoveryApi, AuxStore, BabeApi, Block, Error, HeaderBackend, Overseer,
+		OverseerConnector, OverseerGen, OverseerGenArgs, OverseerHandle, ParachainHost,
+		ProvideRuntimeApi,
+	},
+};
+use polkadot_node_primitives::{SignedFullStatement, Statement};
+use polkadot_node_subsystem::SpawnGlue;
+use polkadot_node_subsystem_util::{
+	request_session_index_for_child, request_validators, signing_key_and_index,
+};
+use polkadot_primitives::v2::{
+	CandidateCommitments, CandidateReceipt, CommittedCandidateReceipt, Hash, HeadData,
+	SigningContext,
+};
+use sp_core::traits::SpawnNamed;
+use sp_keystore::SyncCryptoStorePtr;
+
+use crate::{
+	interceptor::*,
+	shared::{MALICIOUS_POV, MALUS},
+};
+
+use std::sync::Arc;
+
+/// Sign and share a conflicting `Seconded` statement for every candidate we are asked to second.
+#[derive(Clone)]
+struct SecondConflictingCandidate<Spawner> {
+	keystore: SyncCryptoStorePtr,
+	spawner: Spawner,
+}
+
+impl<Sender, Spawner> MessageInterceptor<Sender> for SecondConflictingCandidate<Spawner>
+where
+	Sender: overseer::CandidateBackingSenderTrait + Clone + Send + 'static,
+	Spawner: overseer::gen::Spawner + Clone + 'static,
+{
+	type Message = CandidateBackingMessage;
+
+	fn intercept_incoming(
+		&self,
+		subsystem_sender: &mut Sender,
+		msg: FromOrchestra<Self::Message>,
+	) -> Option<FromOrchestra<Self::Message>> {
+		if let FromOrchestra::Communication {
+			msg: CandidateBackingMessage::Second(relay_parent, ref candidate, _),
+		} = msg
+		{
+			let sender = subsystem_sender.clone();
+			let keystore = self.keystore.clone();
+			let candidate = candidate.clone();
+			self.spawner.spawn(
+				"malus-second-conflicting-candidate",
+				Some("malus"),
+				Box::pin(share_conflicting_statement(sender, keystore, relay_parent, candidate)),
+			);
+		}
+
+		// The honest candidate is seconded as usual.
+		Some(msg)
+	}
+}
+
+/// A candidate with the same descriptor as the given one, but different commitments.
+fn conflicting_candidate(candidate: &CandidateReceipt) -> CommittedCandidateReceipt {
+	let commitments =
+		CandidateCommitments { head_data: HeadData(MALICIOUS_POV.to_vec()), ..Default::default() };
+	CommittedCandidateReceipt { descriptor: candidate.descriptor.clone(), commitments }
+}
+
+async fn share_conflicting_statement<Sender>(
+	mut sender: Sender,
+	keystore: SyncCryptoStorePtr,
+	relay_parent: Hash,
+	candidate: CandidateReceipt,
+) where
+	Sender: overseer::CandidateBackingSenderTrait,
+{
+	let session_index = match request_session_index_for_child(relay_parent, &mut sender).await.await
+	{
+		Ok(Ok(session_index)) => session_index,
+		_ => {
+			gum::warn!(target: MALUS, ?relay_parent, "Unable to fetch session index");
+			return
+		},
+	};
+	let validators = match request_validators(relay_parent, &mut sender).await.await {
+		Ok(Ok(validators)) => validators,
+		_ => {
+			gum::warn!(target: MALUS, ?relay_parent, "Unable to fetch validators");
+			return
+		},
+	};
+	let (key, validator_index) = match signing_key_and_index(&validators, &keystore).await {
+		Some(key_and_index) => key_and_index,
+		None => {
+			gum::warn!(target: MALUS, ?relay_parent, "Not a validator, cannot equivocate");
+			return
+		},
+	};
+
+	let conflicting = conflicting_candidate(&candidate);
+	let conflicting_hash = conflicting.hash();
+	let context = SigningContext { session_index, parent_hash: relay_parent };
+	let statement = match SignedFullStatement::sign(
+		&keystore,
+		Statement::Seconded(conflicting),
+		&context,
+		validator_index,
+		&key,
+	)
+	.await
+	{
+		Ok(Some(statement)) => statement,
+		_ => {
+			gum::warn!(target: MALUS, ?relay_parent, "Signing conflicting statement failed");
+			return
+		},
+	};
+
+	gum::debug!(
+		target: MALUS,
+		candidate_hash = ?candidate.hash(),
+		?conflicting_hash,
+		?relay_parent,
+		"Sharing conflicting seconded statement"
+	);
+
+	sender
+		.send_message(StatementDistributionMessage::Share(relay_parent, statement))
+		.await;
+}
+
+/// Generates an overseer whose candidate backing seconds a conflicting candidate alongside every
+/// honest one.
+pub(crate) struct EquivocateStatements;
+
+impl OverseerGen for EquivocateStatements {
+	fn generate<'a, Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'a, Spawner, RuntimeClient>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + AuxStore,
+		RuntimeClient::Api: ParachainHost<Block> + BabeApi<Block> + AuthorityDiscoveryApi<Block>,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		let keystore: SyncCryptoStorePtr = args.keystore.clone();
+		let second_conflicting =
+			SecondConflictingCandidate { keystore, spawner: SpawnGlue(args.spawner.clone()) };
+
+		prepared_overseer_builder(args)?
+			.replace_candidate_backing(move |cb| InterceptedSubsystem::new(cb, second_conflicting))
+			.build_with_connector(connector)
+			.map_err(|e| e.into())
+	}
+}
@@ -0,0 +1,96 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node that signs availability bitfields claiming to hold the chunks of all
+//! candidates pending availability, whether it has fetched them or not. Candidates can thus be
+//! considered available with fewer chunks actually being around than expected.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use polkadot_cli::{
+	prepared_overseer_builder,
+	service::{
+		AuthorityDiscoveryApi, AuxStore, BabeApi, Block, Error, HeaderBackend, Overseer,
+		OverseerConnector, OverseerGen, OverseerGenArgs, OverseerHandle, ParachainHost,
+		ProvideRuntimeApi,
+	},
+};
+use polkadot_node_subsystem::SpawnGlue;
+use sp_core::traits::SpawnNamed;
+
+use crate::{interceptor::*, shared::MALUS};
+
+use std::sync::Arc;
+
+/// Answer all chunk availability queries of the bitfield signing subsystem with "available",
+/// without asking the availability store.
+#[derive(Clone, Debug)]
+struct ClaimAllChunks;
+
+impl<Sender> MessageInterceptor<Sender> for ClaimAllChunks
+where
+	Sender: overseer::BitfieldSigningSenderTrait + Clone + Send + 'static,
+{
+	type Message = BitfieldSigningMessage;
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::BitfieldSigningOutgoingMessages,
+	) -> Option<overseer::BitfieldSigningOutgoingMessages> {
+		match msg {
+			overseer::BitfieldSigningOutgoingMessages::AvailabilityStoreMessage(
+				AvailabilityStoreMessage::QueryChunkAvailability(
+					candidate_hash,
+					validator_index,
+					tx,
+				),
+			) => {
+				gum::debug!(
+					target: MALUS,
+					?candidate_hash,
+					?validator_index,
+					"Claiming availability of chunk without checking the store"
+				);
+				let _ = tx.send(true);
+				None
+			},
+			msg => Some(msg),
+		}
+	}
+}
+
+/// Generates an overseer whose bitfield signing claims availability for every pending candidate.
+pub(crate) struct FakeAvailability;
+
+impl OverseerGen for FakeAvailability {
+	fn generate<'a, Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'a, Spawner, RuntimeClient>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + AuxStore,
+		RuntimeClient::Api: ParachainHost<Block> + BabeApi<Block> + AuthorityDiscoveryApi<Block>,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		prepared_overseer_builder(args)?
+			.replace_bitfield_signing(move |bs| InterceptedSubsystem::new(bs, ClaimAllChunks))
+			.build_with_connector(connector)
+			.map_err(|e| e.into())
+	}
+}
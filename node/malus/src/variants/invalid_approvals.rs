@@ -0,0 +1,91 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node that votes against every candidate it is assigned to check in approval
+//! voting, without recovering the candidate's data or validating it. Each such vote raises a
+//! dispute, which the honest validators are expected to conclude as valid.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use polkadot_cli::{
+	prepared_overseer_builder,
+	service::{
+		AuthorityDiscoveryApi, AuxStore, BabeApi, Block, Error, HeaderBackend, Overseer,
+		OverseerConnector, OverseerGen, OverseerGenArgs, OverseerHandle, ParachainHost,
+		ProvideRuntimeApi,
+	},
+};
+use polkadot_node_subsystem::{RecoveryError, SpawnGlue};
+use sp_core::traits::SpawnNamed;
+
+use crate::{interceptor::*, shared::MALUS};
+
+use std::sync::Arc;
+
+/// Fail every data recovery of approval voting as invalid, without ever asking availability
+/// recovery. Approval voting then issues an invalid vote for the candidate.
+#[derive(Clone, Debug)]
+struct FailRecoveryAsInvalid;
+
+impl<Sender> MessageInterceptor<Sender> for FailRecoveryAsInvalid
+where
+	Sender: overseer::ApprovalVotingSenderTrait + Clone + Send + 'static,
+{
+	type Message = ApprovalVotingMessage;
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::ApprovalVotingOutgoingMessages,
+	) -> Option<overseer::ApprovalVotingOutgoingMessages> {
+		match msg {
+			overseer::ApprovalVotingOutgoingMessages::AvailabilityRecoveryMessage(
+				AvailabilityRecoveryMessage::RecoverAvailableData(receipt, _, _, tx),
+			) => {
+				gum::debug!(
+					target: MALUS,
+					candidate_hash = ?receipt.hash(),
+					"Voting invalid in approvals without checking the candidate"
+				);
+				let _ = tx.send(Err(RecoveryError::Invalid));
+				None
+			},
+			msg => Some(msg),
+		}
+	}
+}
+
+/// Generates an overseer whose approval voting votes invalid on all assigned candidates.
+pub(crate) struct InvalidApprovals;
+
+impl OverseerGen for InvalidApprovals {
+	fn generate<'a, Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'a, Spawner, RuntimeClient>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + AuxStore,
+		RuntimeClient::Api: ParachainHost<Block> + BabeApi<Block> + AuthorityDiscoveryApi<Block>,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		prepared_overseer_builder(args)?
+			.replace_approval_voting(move |av| InterceptedSubsystem::new(av, FailRecoveryAsInvalid))
+			.build_with_connector(connector)
+			.map_err(|e| e.into())
+	}
+}
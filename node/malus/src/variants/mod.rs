@@ -19,11 +19,19 @@
 mod back_garbage_candidate;
 mod common;
 mod dispute_valid_candidates;
+mod equivocate_statements;
+mod fake_availability;
+mod invalid_approvals;
 mod suggest_garbage_candidate;
+mod withhold_chunks;
 
 pub(crate) use self::{
 	back_garbage_candidate::BackGarbageCandidate,
 	dispute_valid_candidates::{DisputeAncestorOptions, DisputeValidCandidates},
+	equivocate_statements::EquivocateStatements,
+	fake_availability::FakeAvailability,
+	invalid_approvals::InvalidApprovals,
 	suggest_garbage_candidate::BackGarbageCandidateWrapper,
+	withhold_chunks::WithholdChunks,
 };
 pub(crate) use common::*;
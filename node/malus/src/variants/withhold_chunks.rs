@@ -0,0 +1,93 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node that fetches and stores its availability chunks like everybody else, but
+//! refuses to serve them to other validators. Availability and recovery have to cope with the
+//! chunks of up to a third of the validators missing.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use polkadot_cli::{
+	prepared_overseer_builder,
+	service::{
+		AuthorityDiscoveryApi, AuxStore, BabeApi, Block, Error, HeaderBackend, Overseer,
+		OverseerConnector, OverseerGen, OverseerGenArgs, OverseerHandle, ParachainHost,
+		ProvideRuntimeApi,
+	},
+};
+use polkadot_node_subsystem::SpawnGlue;
+use sp_core::traits::SpawnNamed;
+
+use crate::{interceptor::*, shared::MALUS};
+
+use std::sync::Arc;
+
+/// Answer all chunk queries of the availability distribution responders with "no such chunk".
+#[derive(Clone, Debug)]
+struct WithholdChunkQueries;
+
+impl<Sender> MessageInterceptor<Sender> for WithholdChunkQueries
+where
+	Sender: overseer::AvailabilityDistributionSenderTrait + Clone + Send + 'static,
+{
+	type Message = AvailabilityDistributionMessage;
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::AvailabilityDistributionOutgoingMessages,
+	) -> Option<overseer::AvailabilityDistributionOutgoingMessages> {
+		match msg {
+			overseer::AvailabilityDistributionOutgoingMessages::AvailabilityStoreMessage(
+				AvailabilityStoreMessage::QueryChunk(candidate_hash, validator_index, tx),
+			) => {
+				gum::debug!(
+					target: MALUS,
+					?candidate_hash,
+					?validator_index,
+					"Withholding availability chunk"
+				);
+				let _ = tx.send(None);
+				None
+			},
+			msg => Some(msg),
+		}
+	}
+}
+
+/// Generates an overseer whose availability distribution never serves any chunks.
+pub(crate) struct WithholdChunks;
+
+impl OverseerGen for WithholdChunks {
+	fn generate<'a, Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'a, Spawner, RuntimeClient>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + AuxStore,
+		RuntimeClient::Api: ParachainHost<Block> + BabeApi<Block> + AuthorityDiscoveryApi<Block>,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		prepared_overseer_builder(args)?
+			.replace_availability_distribution(move |ad| {
+				InterceptedSubsystem::new(ad, WithholdChunkQueries)
+			})
+			.build_with_connector(connector)
+			.map_err(|e| e.into())
+	}
+}
@@ -25,42 +25,66 @@ use futures::{
 
 use std::{pin::Pin, result};
 
-use super::{measure_tof_check, CoarseInstant, MaybeTimeOfFlight, Meter};
+use super::{
+	measure_tof_check,
+	queue::{self, QueueReceiver, QueueSender},
+	CoarseInstant, Lane, MaybeTimeOfFlight, Meter, OverflowPolicy,
+};
 
 /// Create a wrapped `mpsc::channel` pair of `MeteredSender` and `MeteredReceiver`.
 pub fn channel<T>(capacity: usize) -> (MeteredSender<T>, MeteredReceiver<T>) {
-	let (tx, rx) = mpsc::channel::<MaybeTimeOfFlight<T>>(capacity);
+	channel_with_policy(capacity, OverflowPolicy::Block, |_| Lane::Normal)
+}
+
+/// Create a pair of `MeteredSender` and `MeteredReceiver`, handling messages sent while
+/// `capacity` messages are queued up according to `policy`.
+///
+/// With any policy but `OverflowPolicy::Block`, sending never waits and messages go into
+/// the lane picked by `lane`, or the urgent one if sent with `send_urgent`. Only droppable
+/// messages are dropped, all others are queued even beyond `capacity`.
+pub fn channel_with_policy<T>(
+	capacity: usize,
+	policy: OverflowPolicy,
+	lane: fn(&T) -> Lane,
+) -> (MeteredSender<T>, MeteredReceiver<T>) {
+	let (tx, rx) = match policy {
+		OverflowPolicy::Block => {
+			let (tx, rx) = mpsc::channel::<MaybeTimeOfFlight<T>>(capacity);
+			(SenderInner::Channel(tx), ReceiverInner::Channel(rx))
+		},
+		OverflowPolicy::DropOldest | OverflowPolicy::DropNewest => {
+			let (tx, rx) = queue::queue(capacity, policy, lane);
+			(SenderInner::Queue(tx), ReceiverInner::Queue(rx))
+		},
+	};
 	let shared_meter = Meter::default();
 	let tx = MeteredSender { meter: shared_meter.clone(), inner: tx };
 	let rx = MeteredReceiver { meter: shared_meter, inner: rx };
 	(tx, rx)
 }
 
+#[derive(Debug)]
+enum ReceiverInner<T> {
+	Channel(mpsc::Receiver<MaybeTimeOfFlight<T>>),
+	Queue(QueueReceiver<T>),
+}
+
 /// A receiver tracking the messages consumed by itself.
 #[derive(Debug)]
 pub struct MeteredReceiver<T> {
 	// count currently contained messages
 	meter: Meter,
-	inner: mpsc::Receiver<MaybeTimeOfFlight<T>>,
-}
-
-impl<T> std::ops::Deref for MeteredReceiver<T> {
-	type Target = mpsc::Receiver<MaybeTimeOfFlight<T>>;
-	fn deref(&self) -> &Self::Target {
-		&self.inner
-	}
-}
-
-impl<T> std::ops::DerefMut for MeteredReceiver<T> {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.inner
-	}
+	inner: ReceiverInner<T>,
 }
 
 impl<T> Stream for MeteredReceiver<T> {
 	type Item = T;
 	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-		match mpsc::Receiver::poll_next(Pin::new(&mut self.inner), cx) {
+		let polled = match self.inner {
+			ReceiverInner::Channel(ref mut rx) => mpsc::Receiver::poll_next(Pin::new(rx), cx),
+			ReceiverInner::Queue(ref mut rx) => rx.poll_next(cx),
+		};
+		match polled {
 			Poll::Ready(maybe_value) => Poll::Ready(self.maybe_meter_tof(maybe_value)),
 			Poll::Pending => Poll::Pending,
 		}
@@ -68,7 +92,10 @@ impl<T> Stream for MeteredReceiver<T> {
 
 	/// Don't rely on the unreliable size hint.
 	fn size_hint(&self) -> (usize, Option<usize>) {
-		self.inner.size_hint()
+		match self.inner {
+			ReceiverInner::Channel(ref rx) => rx.size_hint(),
+			ReceiverInner::Queue(ref rx) => (rx.len(), None),
+		}
 	}
 }
 
@@ -97,7 +124,11 @@ impl<T> MeteredReceiver<T> {
 
 	/// Attempt to receive the next item.
 	pub fn try_next(&mut self) -> Result<Option<T>, mpsc::TryRecvError> {
-		match self.inner.try_next()? {
+		let next = match self.inner {
+			ReceiverInner::Channel(ref mut rx) => rx.try_next()?,
+			ReceiverInner::Queue(ref mut rx) => rx.try_next()?,
+		};
+		match next {
 			Some(value) => Ok(self.maybe_meter_tof(Some(value))),
 			None => Ok(None),
 		}
@@ -106,7 +137,25 @@ impl<T> MeteredReceiver<T> {
 
 impl<T> futures::stream::FusedStream for MeteredReceiver<T> {
 	fn is_terminated(&self) -> bool {
-		self.inner.is_terminated()
+		match self.inner {
+			ReceiverInner::Channel(ref rx) => rx.is_terminated(),
+			ReceiverInner::Queue(ref rx) => rx.is_terminated(),
+		}
+	}
+}
+
+#[derive(Debug)]
+enum SenderInner<T> {
+	Channel(mpsc::Sender<MaybeTimeOfFlight<T>>),
+	Queue(QueueSender<T>),
+}
+
+impl<T> Clone for SenderInner<T> {
+	fn clone(&self) -> Self {
+		match self {
+			Self::Channel(tx) => Self::Channel(tx.clone()),
+			Self::Queue(tx) => Self::Queue(tx.clone()),
+		}
 	}
 }
 
@@ -115,7 +164,7 @@ impl<T> futures::stream::FusedStream for MeteredReceiver<T> {
 #[derive(Debug)]
 pub struct MeteredSender<T> {
 	meter: Meter,
-	inner: mpsc::Sender<MaybeTimeOfFlight<T>>,
+	inner: SenderInner<T>,
 }
 
 impl<T> Clone for MeteredSender<T> {
@@ -124,19 +173,6 @@ impl<T> Clone for MeteredSender<T> {
 	}
 }
 
-impl<T> std::ops::Deref for MeteredSender<T> {
-	type Target = mpsc::Sender<MaybeTimeOfFlight<T>>;
-	fn deref(&self) -> &Self::Target {
-		&self.inner
-	}
-}
-
-impl<T> std::ops::DerefMut for MeteredSender<T> {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.inner
-	}
-}
-
 impl<T> MeteredSender<T> {
 	fn prepare_with_tof(&self, item: T) -> MaybeTimeOfFlight<T> {
 		let previous = self.meter.note_sent();
@@ -166,12 +202,18 @@ impl<T> MeteredSender<T> {
 
 				let msg = send_err.into_inner();
 				self.meter.note_sent();
-				let fut = self.inner.send(msg);
+				let tx = match self.inner {
+					SenderInner::Channel(ref mut tx) => tx,
+					SenderInner::Queue(_) => unreachable!("Queues never report being full. qed"),
+				};
+				let fut = tx.send(msg);
 				futures::pin_mut!(fut);
 				fut.await.map_err(|e| {
 					self.meter.retract_sent();
 					e
-				})
+				})?;
+				self.meter.note_queued();
+				Ok(())
 			},
 			_ => Ok(()),
 		}
@@ -182,14 +224,59 @@ impl<T> MeteredSender<T> {
 		&mut self,
 		msg: T,
 	) -> result::Result<(), mpsc::TrySendError<MaybeTimeOfFlight<T>>> {
+		self.try_send_inner(msg, false)
+	}
+
+	/// Send a message ahead of all normal ones.
+	///
+	/// Behaves like `send` for channels with `OverflowPolicy::Block`, which have no lanes.
+	pub async fn send_urgent(&mut self, msg: T) -> result::Result<(), mpsc::SendError>
+	where
+		Self: Unpin,
+	{
+		match self.inner {
+			SenderInner::Channel(_) => self.send(msg).await,
+			SenderInner::Queue(_) =>
+				self.try_send_inner(msg, true).map_err(|e| e.into_send_error()),
+		}
+	}
+
+	/// Attempt to send a message ahead of all normal ones or fail immediately.
+	pub fn try_send_urgent(
+		&mut self,
+		msg: T,
+	) -> result::Result<(), mpsc::TrySendError<MaybeTimeOfFlight<T>>> {
+		self.try_send_inner(msg, true)
+	}
+
+	fn try_send_inner(
+		&mut self,
+		msg: T,
+		urgent: bool,
+	) -> result::Result<(), mpsc::TrySendError<MaybeTimeOfFlight<T>>> {
+		let lane = match self.inner {
+			SenderInner::Queue(_) if urgent => Lane::Urgent,
+			SenderInner::Queue(ref tx) => tx.lane(&msg),
+			SenderInner::Channel(_) => Lane::Normal,
+		};
 		let msg = self.prepare_with_tof(msg);
-		self.inner.try_send(msg).map_err(|e| {
-			if e.is_full() {
-				// Count bounded channel sends that block.
-				self.meter.note_blocked();
-			}
-			self.meter.retract_sent();
-			e
-		})
+		match self.inner {
+			SenderInner::Channel(ref mut tx) => {
+				tx.try_send(msg).map_err(|e| {
+					if e.is_full() {
+						// Count bounded channel sends that block.
+						self.meter.note_blocked();
+					}
+					self.meter.retract_sent();
+					e
+				})?;
+				self.meter.note_queued();
+				Ok(())
+			},
+			SenderInner::Queue(ref tx) => tx.push(&self.meter, msg, lane).map_err(|msg| {
+				self.meter.retract_sent();
+				queue::disconnected_error(msg)
+			}),
+		}
 	}
 }
@@ -25,6 +25,7 @@ use derive_more::Display;
 
 mod bounded;
 pub mod oneshot;
+mod queue;
 mod unbounded;
 
 pub use self::{bounded::*, unbounded::*};
@@ -44,6 +45,10 @@ pub struct Meter {
 	received: Arc<AtomicUsize>,
 	// Number of times senders blocked while sending messages to a subsystem.
 	blocked: Arc<AtomicUsize>,
+	// Number of messages dropped due to the overflow policy of the channel.
+	dropped: Arc<AtomicUsize>,
+	// Largest number of messages ever queued up at once.
+	high_water_mark: Arc<AtomicUsize>,
 	// Atomic ringbuffer of the last 50 time of flight values
 	tof: Arc<crossbeam_queue::ArrayQueue<CoarseDuration>>,
 }
@@ -54,6 +59,8 @@ impl std::default::Default for Meter {
 			sent: Arc::new(AtomicUsize::new(0)),
 			received: Arc::new(AtomicUsize::new(0)),
 			blocked: Arc::new(AtomicUsize::new(0)),
+			dropped: Arc::new(AtomicUsize::new(0)),
+			high_water_mark: Arc::new(AtomicUsize::new(0)),
 			tof: Arc::new(crossbeam_queue::ArrayQueue::new(100)),
 		}
 	}
@@ -70,6 +77,10 @@ pub struct Readout {
 	pub received: usize,
 	/// How many times the caller blocked when sending messages.
 	pub blocked: usize,
+	/// The amount of messages dropped due to the overflow policy, in aggregate.
+	pub dropped: usize,
+	/// The largest amount of messages queued up at once.
+	pub high_water_mark: usize,
 	/// Time of flight in micro seconds (us)
 	pub tof: Vec<CoarseDuration>,
 }
@@ -83,6 +94,8 @@ impl Meter {
 			sent: self.sent.load(Ordering::Relaxed),
 			received: self.received.load(Ordering::Relaxed),
 			blocked: self.blocked.load(Ordering::Relaxed),
+			dropped: self.dropped.load(Ordering::Relaxed),
+			high_water_mark: self.high_water_mark.load(Ordering::Relaxed),
 			tof: {
 				let mut acc = Vec::with_capacity(self.tof.len());
 				while let Some(value) = self.tof.pop() {
@@ -97,6 +110,14 @@ impl Meter {
 		self.sent.fetch_add(1, Ordering::Relaxed)
 	}
 
+	// Must be called after a message was queued up, messages sent but neither received
+	// nor dropped are the ones still queued.
+	fn note_queued(&self) {
+		let sent = self.sent.load(Ordering::Relaxed);
+		let gone = self.received.load(Ordering::Relaxed) + self.dropped.load(Ordering::Relaxed);
+		self.high_water_mark.fetch_max(sent.saturating_sub(gone), Ordering::Relaxed);
+	}
+
 	fn retract_sent(&self) {
 		self.sent.fetch_sub(1, Ordering::Relaxed);
 	}
@@ -109,11 +130,43 @@ impl Meter {
 		self.blocked.fetch_add(1, Ordering::Relaxed);
 	}
 
+	fn note_dropped(&self) {
+		self.dropped.fetch_add(1, Ordering::Relaxed);
+	}
+
 	fn note_time_of_flight(&self, tof: CoarseDuration) {
 		let _ = self.tof.force_push(tof);
 	}
 }
 
+/// What to do with a message sent to a channel which is at capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+	/// Wait for capacity, or grow without limit for unbounded channels.
+	Block,
+	/// Drop the oldest queued droppable message to make room.
+	DropOldest,
+	/// Drop the message being sent, if it is droppable.
+	DropNewest,
+}
+
+impl Default for OverflowPolicy {
+	fn default() -> Self {
+		Self::Block
+	}
+}
+
+/// The lane of a message sent to a channel with any policy but `OverflowPolicy::Block`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lane {
+	/// Received before all other messages, never dropped.
+	Urgent,
+	/// Received in the order sent, never dropped.
+	Normal,
+	/// Received in the order sent along with normal messages, dropped on overflow.
+	Droppable,
+}
+
 /// Determine if this instance shall be measured
 #[inline(always)]
 fn measure_tof_check(nth: usize) -> bool {
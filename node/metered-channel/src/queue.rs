@@ -0,0 +1,272 @@
+// Copyright 2017-2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Queue backing the channels which drop messages on overflow instead of blocking.
+//!
+//! Senders and the receiver share the lanes behind a mutex. Urgent messages are received
+//! before all others, normal and droppable ones in the order they were sent. Only droppable
+//! messages are ever dropped, all others are queued even beyond capacity.
+
+use futures::{
+	channel::mpsc,
+	task::{Context, Poll, Waker},
+};
+
+use std::{
+	collections::VecDeque,
+	fmt,
+	sync::{Arc, Mutex, MutexGuard},
+};
+
+use super::{Lane, MaybeTimeOfFlight, Meter, OverflowPolicy};
+
+struct State<T> {
+	urgent: VecDeque<MaybeTimeOfFlight<T>>,
+	// Normal and droppable messages are kept apart, so the oldest droppable one is at hand.
+	// The sequence numbers restore the order they were sent in.
+	normal: VecDeque<(u64, MaybeTimeOfFlight<T>)>,
+	droppable: VecDeque<(u64, MaybeTimeOfFlight<T>)>,
+	next_sequence: u64,
+	receiver_waker: Option<Waker>,
+	senders: usize,
+	receiver_alive: bool,
+}
+
+impl<T> State<T> {
+	fn len(&self) -> usize {
+		self.urgent.len() + self.normal.len() + self.droppable.len()
+	}
+
+	fn pop_front(&mut self) -> Option<MaybeTimeOfFlight<T>> {
+		if let Some(msg) = self.urgent.pop_front() {
+			return Some(msg)
+		}
+		let lane = match (self.normal.front(), self.droppable.front()) {
+			(Some((normal, _)), Some((droppable, _))) if droppable < normal => &mut self.droppable,
+			(Some(_), _) => &mut self.normal,
+			(None, _) => &mut self.droppable,
+		};
+		lane.pop_front().map(|(_, msg)| msg)
+	}
+
+	fn clear(&mut self) {
+		self.urgent.clear();
+		self.normal.clear();
+		self.droppable.clear();
+	}
+}
+
+struct Shared<T> {
+	state: Mutex<State<T>>,
+	capacity: usize,
+	policy: OverflowPolicy,
+	lane: fn(&T) -> Lane,
+}
+
+impl<T> Shared<T> {
+	fn lock(&self) -> MutexGuard<'_, State<T>> {
+		// No code path panics while holding the lock, but don't let
+		// a poisoned lock take down all senders either.
+		self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+/// Create a queue holding at most `capacity` messages, putting messages into the lane picked
+/// by `lane` and dropping droppable ones on overflow as instructed by `policy`.
+pub(crate) fn queue<T>(
+	capacity: usize,
+	policy: OverflowPolicy,
+	lane: fn(&T) -> Lane,
+) -> (QueueSender<T>, QueueReceiver<T>) {
+	debug_assert!(policy != OverflowPolicy::Block, "Blocking channels are backed by `mpsc`. qed");
+
+	let shared = Arc::new(Shared {
+		state: Mutex::new(State {
+			urgent: VecDeque::new(),
+			normal: VecDeque::new(),
+			droppable: VecDeque::new(),
+			next_sequence: 0,
+			receiver_waker: None,
+			senders: 1,
+			receiver_alive: true,
+		}),
+		capacity: capacity.max(1),
+		policy,
+		lane,
+	});
+	(QueueSender(shared.clone()), QueueReceiver { shared, terminated: false })
+}
+
+/// Sending half of a queue.
+pub(crate) struct QueueSender<T>(Arc<Shared<T>>);
+
+impl<T> fmt::Debug for QueueSender<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("QueueSender")
+			.field("capacity", &self.0.capacity)
+			.field("policy", &self.0.policy)
+			.finish()
+	}
+}
+
+impl<T> Clone for QueueSender<T> {
+	fn clone(&self) -> Self {
+		self.0.lock().senders += 1;
+		Self(self.0.clone())
+	}
+}
+
+impl<T> Drop for QueueSender<T> {
+	fn drop(&mut self) {
+		let mut state = self.0.lock();
+		state.senders -= 1;
+		if state.senders == 0 {
+			if let Some(waker) = state.receiver_waker.take() {
+				waker.wake();
+			}
+		}
+	}
+}
+
+impl<T> QueueSender<T> {
+	/// The lane the message is put into, unless sent as urgent.
+	pub(crate) fn lane(&self, msg: &T) -> Lane {
+		(self.0.lane)(msg)
+	}
+
+	/// Queue a message into the given lane, dropping a droppable message if the queue is
+	/// full and the message is droppable itself.
+	///
+	/// Urgent and normal messages are queued regardless of capacity.
+	/// Fails only if the receiver is gone.
+	pub(crate) fn push(
+		&self,
+		meter: &Meter,
+		msg: MaybeTimeOfFlight<T>,
+		lane: Lane,
+	) -> Result<(), MaybeTimeOfFlight<T>> {
+		let mut state = self.0.lock();
+		if !state.receiver_alive {
+			return Err(msg)
+		}
+
+		if lane == Lane::Droppable && state.len() >= self.0.capacity {
+			meter.note_dropped();
+			let evicted = match self.0.policy {
+				OverflowPolicy::DropOldest => state.droppable.pop_front(),
+				OverflowPolicy::DropNewest | OverflowPolicy::Block => None,
+			};
+			if evicted.is_none() {
+				return Ok(())
+			}
+		}
+
+		let sequence = state.next_sequence;
+		match lane {
+			Lane::Urgent => state.urgent.push_back(msg),
+			Lane::Normal => {
+				state.normal.push_back((sequence, msg));
+				state.next_sequence += 1;
+			},
+			Lane::Droppable => {
+				state.droppable.push_back((sequence, msg));
+				state.next_sequence += 1;
+			},
+		}
+		meter.note_queued();
+		if let Some(waker) = state.receiver_waker.take() {
+			waker.wake();
+		}
+		Ok(())
+	}
+}
+
+/// Receiving half of a queue.
+pub(crate) struct QueueReceiver<T> {
+	shared: Arc<Shared<T>>,
+	terminated: bool,
+}
+
+impl<T> fmt::Debug for QueueReceiver<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("QueueReceiver")
+			.field("capacity", &self.shared.capacity)
+			.field("policy", &self.shared.policy)
+			.finish()
+	}
+}
+
+impl<T> Drop for QueueReceiver<T> {
+	fn drop(&mut self) {
+		let mut state = self.shared.lock();
+		state.receiver_alive = false;
+		state.clear();
+	}
+}
+
+impl<T> QueueReceiver<T> {
+	/// Take the next message, `None` if all senders are gone and the queue is drained.
+	pub(crate) fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<MaybeTimeOfFlight<T>>> {
+		let mut state = self.shared.lock();
+		if let Some(msg) = state.pop_front() {
+			return Poll::Ready(Some(msg))
+		}
+		if state.senders == 0 {
+			self.terminated = true;
+			return Poll::Ready(None)
+		}
+		state.receiver_waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+
+	/// Take the next message without waiting, see `mpsc::Receiver::try_next`.
+	pub(crate) fn try_next(&mut self) -> Result<Option<MaybeTimeOfFlight<T>>, mpsc::TryRecvError> {
+		let mut state = self.shared.lock();
+		if let Some(msg) = state.pop_front() {
+			return Ok(Some(msg))
+		}
+		if state.senders == 0 {
+			self.terminated = true;
+			return Ok(None)
+		}
+		Err(empty_error())
+	}
+
+	/// Number of queued messages.
+	pub(crate) fn len(&self) -> usize {
+		self.shared.lock().len()
+	}
+
+	/// Whether the stream of messages ended.
+	pub(crate) fn is_terminated(&self) -> bool {
+		self.terminated
+	}
+}
+
+// The error types of `mpsc` can not be constructed outside of `futures`,
+// obtain them from a channel in the required state instead.
+
+/// The error of receiving from an empty, but open channel.
+fn empty_error() -> mpsc::TryRecvError {
+	let (_tx, mut rx) = mpsc::unbounded::<()>();
+	rx.try_next().expect_err("Channel is empty and the sender alive. qed")
+}
+
+/// The error of sending to a channel without receiver.
+pub(crate) fn disconnected_error<T>(msg: T) -> mpsc::TrySendError<T> {
+	let (tx, _) = mpsc::unbounded();
+	tx.unbounded_send(msg).expect_err("The receiver was dropped. qed")
+}
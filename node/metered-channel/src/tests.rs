@@ -39,12 +39,12 @@ fn try_send_try_next() {
 		assert_matches!(rx.meter().read(), Readout { sent: 4, received: 1, .. });
 		rx.try_next().unwrap();
 		rx.try_next().unwrap();
-		assert_matches!(tx.meter().read(), Readout { sent: 4, received: 3, blocked: 0, tof } => {
+		assert_matches!(tx.meter().read(), Readout { sent: 4, received: 3, blocked: 0, tof, .. } => {
 			// every second in test, consumed before
 			assert_eq!(dbg!(tof).len(), 1);
 		});
 		rx.try_next().unwrap();
-		assert_matches!(rx.meter().read(), Readout { sent: 4, received: 4, blocked: 0, tof } => {
+		assert_matches!(rx.meter().read(), Readout { sent: 4, received: 4, blocked: 0, tof, .. } => {
 			// every second in test, consumed before
 			assert_eq!(dbg!(tof).len(), 0);
 		});
@@ -148,3 +148,183 @@ fn blocked_send_is_metered() {
 		);
 	});
 }
+
+#[test]
+fn drop_oldest_keeps_newest_messages() {
+	let (mut tx, mut rx) =
+		channel_with_policy::<Msg>(2, OverflowPolicy::DropOldest, |_| Lane::Droppable);
+
+	block_on(async move {
+		for val in 0..4 {
+			assert!(tx.send(Msg { val }).await.is_ok());
+		}
+		assert_matches!(
+			tx.meter().read(),
+			Readout { sent: 4, received: 0, dropped: 2, high_water_mark: 2, .. }
+		);
+		assert_eq!(rx.try_next().unwrap().unwrap().val, 2);
+		assert_eq!(rx.try_next().unwrap().unwrap().val, 3);
+		assert!(rx.try_next().is_err());
+	});
+}
+
+#[test]
+fn drop_newest_keeps_oldest_messages() {
+	let (tx, mut rx) =
+		unbounded_with_policy::<Msg>(2, OverflowPolicy::DropNewest, |_| Lane::Droppable);
+
+	for val in 0..4 {
+		assert!(tx.unbounded_send(Msg { val }).is_ok());
+	}
+	assert_matches!(
+		tx.meter().read(),
+		Readout { sent: 4, received: 0, dropped: 2, high_water_mark: 2, .. }
+	);
+	assert_eq!(rx.try_next().unwrap().unwrap().val, 0);
+	assert_eq!(rx.try_next().unwrap().unwrap().val, 1);
+
+	drop(tx);
+	assert_matches!(rx.try_next(), Ok(None));
+}
+
+fn droppable_below_10(msg: &Msg) -> Lane {
+	if msg.val < 10 {
+		Lane::Droppable
+	} else {
+		Lane::Normal
+	}
+}
+
+fn urgent_above_20(msg: &Msg) -> Lane {
+	if msg.val > 20 {
+		Lane::Urgent
+	} else {
+		droppable_below_10(msg)
+	}
+}
+
+#[test]
+fn only_droppable_messages_are_dropped() {
+	let (mut tx, mut rx) =
+		channel_with_policy::<Msg>(2, OverflowPolicy::DropOldest, droppable_below_10);
+
+	block_on(async move {
+		tx.try_send(Msg { val: 0 }).unwrap();
+		tx.try_send(Msg { val: 10 }).unwrap();
+		// Exceeds the capacity, but must not be dropped.
+		tx.send(Msg { val: 11 }).await.unwrap();
+		// Each evicts the oldest droppable message.
+		tx.try_send(Msg { val: 1 }).unwrap();
+		tx.try_send(Msg { val: 2 }).unwrap();
+
+		assert_matches!(tx.meter().read(), Readout { sent: 5, dropped: 2, .. });
+		assert_eq!(rx.next().await.unwrap().val, 10);
+		assert_eq!(rx.next().await.unwrap().val, 11);
+		assert_eq!(rx.next().await.unwrap().val, 2);
+
+		drop(tx);
+		assert!(rx.next().await.is_none());
+	});
+}
+
+#[test]
+fn non_droppable_messages_exceed_limit() {
+	let (tx, mut rx) =
+		unbounded_with_policy::<Msg>(1, OverflowPolicy::DropNewest, droppable_below_10);
+
+	for val in [10, 0, 11, 1] {
+		assert!(tx.unbounded_send(Msg { val }).is_ok());
+	}
+	assert_matches!(tx.meter().read(), Readout { sent: 4, dropped: 2, .. });
+	assert_eq!(rx.try_next().unwrap().unwrap().val, 10);
+	assert_eq!(rx.try_next().unwrap().unwrap().val, 11);
+	assert!(rx.try_next().is_err());
+}
+
+#[test]
+fn normal_and_droppable_messages_keep_their_order() {
+	let (tx, mut rx) =
+		unbounded_with_policy::<Msg>(10, OverflowPolicy::DropOldest, droppable_below_10);
+
+	for val in [0, 10, 1, 11, 12, 2] {
+		tx.unbounded_send(Msg { val }).unwrap();
+	}
+	for val in [0, 10, 1, 11, 12, 2] {
+		assert_eq!(rx.try_next().unwrap().unwrap().val, val);
+	}
+	assert!(rx.try_next().is_err());
+}
+
+#[test]
+fn urgent_messages_overtake_and_are_never_dropped() {
+	let (mut tx, mut rx) =
+		channel_with_policy::<Msg>(2, OverflowPolicy::DropNewest, |_| Lane::Droppable);
+
+	block_on(async move {
+		tx.try_send(Msg { val: 0 }).unwrap();
+		tx.try_send(Msg { val: 1 }).unwrap();
+		tx.send_urgent(Msg { val: 2 }).await.unwrap();
+		tx.try_send_urgent(Msg { val: 3 }).unwrap();
+		// The queue is full, so the droppable message is dropped.
+		tx.try_send(Msg { val: 4 }).unwrap();
+
+		assert_matches!(tx.meter().read(), Readout { sent: 5, dropped: 1, .. });
+		for val in [2, 3, 0, 1] {
+			assert_eq!(rx.next().await.unwrap().val, val);
+		}
+
+		drop(tx);
+		assert!(rx.next().await.is_none());
+	});
+}
+
+#[test]
+fn classifier_picks_the_urgent_lane() {
+	let (tx, mut rx) = unbounded_with_policy::<Msg>(2, OverflowPolicy::DropOldest, urgent_above_20);
+
+	for val in [0, 10, 21, 1, 22] {
+		tx.unbounded_send(Msg { val }).unwrap();
+	}
+	tx.unbounded_send_urgent(Msg { val: 2 }).unwrap();
+
+	assert_matches!(tx.meter().read(), Readout { sent: 6, dropped: 1, .. });
+	for val in [21, 22, 2, 10, 1] {
+		assert_eq!(rx.try_next().unwrap().unwrap().val, val);
+	}
+	assert!(rx.try_next().is_err());
+}
+
+#[test]
+fn send_to_dropped_queue_fails() {
+	let (mut bounded, _) =
+		channel_with_policy::<Msg>(5, OverflowPolicy::DropOldest, |_| Lane::Droppable);
+	let (unbounded, _) =
+		unbounded_with_policy::<Msg>(5, OverflowPolicy::DropNewest, |_| Lane::Droppable);
+
+	block_on(async move {
+		assert!(bounded.send(Msg::default()).await.is_err());
+		assert!(bounded.try_send(Msg::default()).is_err());
+		assert_matches!(bounded.meter().read(), Readout { sent: 0, dropped: 0, .. });
+
+		assert!(unbounded.unbounded_send(Msg::default()).unwrap_err().is_disconnected());
+		assert_matches!(unbounded.meter().read(), Readout { sent: 0, dropped: 0, .. });
+	});
+}
+
+#[test]
+fn queue_wakes_up_receiver() {
+	let (mut tx, mut rx) =
+		channel_with_policy::<Msg>(5, OverflowPolicy::DropOldest, |_| Lane::Droppable);
+
+	let pool = futures::executor::ThreadPool::new().unwrap();
+	pool.spawn_ok(async move {
+		Delay::new(Duration::from_millis(50)).await;
+		tx.send(Msg { val: 7 }).await.unwrap();
+	});
+
+	block_on(async move {
+		assert_eq!(rx.next().await.unwrap().val, 7);
+		assert!(rx.next().await.is_none());
+		assert!(futures::stream::FusedStream::is_terminated(&rx));
+	});
+}
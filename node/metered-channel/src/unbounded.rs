@@ -24,42 +24,68 @@ use futures::{
 
 use std::{pin::Pin, result};
 
-use super::{measure_tof_check, CoarseInstant, MaybeTimeOfFlight, Meter};
+use super::{
+	measure_tof_check,
+	queue::{self, QueueReceiver, QueueSender},
+	CoarseInstant, Lane, MaybeTimeOfFlight, Meter, OverflowPolicy,
+};
 
 /// Create a wrapped `mpsc::channel` pair of `MeteredSender` and `MeteredReceiver`.
 pub fn unbounded<T>() -> (UnboundedMeteredSender<T>, UnboundedMeteredReceiver<T>) {
-	let (tx, rx) = mpsc::unbounded::<MaybeTimeOfFlight<T>>();
+	unbounded_with_policy(usize::MAX, OverflowPolicy::Block, |_| Lane::Normal)
+}
+
+/// Create a pair of `UnboundedMeteredSender` and `UnboundedMeteredReceiver`, handling
+/// messages sent while `limit` messages are queued up according to `policy`.
+///
+/// `OverflowPolicy::Block` ignores the `limit`, the channel grows without bounds as
+/// the one returned by `unbounded`. With any other policy, messages go into the lane
+/// picked by `lane`, or the urgent one if sent with `unbounded_send_urgent`. Only
+/// droppable messages are dropped, all others are queued even beyond `limit`.
+pub fn unbounded_with_policy<T>(
+	limit: usize,
+	policy: OverflowPolicy,
+	lane: fn(&T) -> Lane,
+) -> (UnboundedMeteredSender<T>, UnboundedMeteredReceiver<T>) {
+	let (tx, rx) = match policy {
+		OverflowPolicy::Block => {
+			let (tx, rx) = mpsc::unbounded::<MaybeTimeOfFlight<T>>();
+			(SenderInner::Channel(tx), ReceiverInner::Channel(rx))
+		},
+		OverflowPolicy::DropOldest | OverflowPolicy::DropNewest => {
+			let (tx, rx) = queue::queue(limit, policy, lane);
+			(SenderInner::Queue(tx), ReceiverInner::Queue(rx))
+		},
+	};
 	let shared_meter = Meter::default();
 	let tx = UnboundedMeteredSender { meter: shared_meter.clone(), inner: tx };
 	let rx = UnboundedMeteredReceiver { meter: shared_meter, inner: rx };
 	(tx, rx)
 }
 
+#[derive(Debug)]
+enum ReceiverInner<T> {
+	Channel(mpsc::UnboundedReceiver<MaybeTimeOfFlight<T>>),
+	Queue(QueueReceiver<T>),
+}
+
 /// A receiver tracking the messages consumed by itself.
 #[derive(Debug)]
 pub struct UnboundedMeteredReceiver<T> {
 	// count currently contained messages
 	meter: Meter,
-	inner: mpsc::UnboundedReceiver<MaybeTimeOfFlight<T>>,
-}
-
-impl<T> std::ops::Deref for UnboundedMeteredReceiver<T> {
-	type Target = mpsc::UnboundedReceiver<MaybeTimeOfFlight<T>>;
-	fn deref(&self) -> &Self::Target {
-		&self.inner
-	}
-}
-
-impl<T> std::ops::DerefMut for UnboundedMeteredReceiver<T> {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.inner
-	}
+	inner: ReceiverInner<T>,
 }
 
 impl<T> Stream for UnboundedMeteredReceiver<T> {
 	type Item = T;
 	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-		match mpsc::UnboundedReceiver::poll_next(Pin::new(&mut self.inner), cx) {
+		let polled = match self.inner {
+			ReceiverInner::Channel(ref mut rx) =>
+				mpsc::UnboundedReceiver::poll_next(Pin::new(rx), cx),
+			ReceiverInner::Queue(ref mut rx) => rx.poll_next(cx),
+		};
+		match polled {
 			Poll::Ready(maybe_value) => Poll::Ready(self.maybe_meter_tof(maybe_value)),
 			Poll::Pending => Poll::Pending,
 		}
@@ -67,7 +93,10 @@ impl<T> Stream for UnboundedMeteredReceiver<T> {
 
 	/// Don't rely on the unreliable size hint.
 	fn size_hint(&self) -> (usize, Option<usize>) {
-		self.inner.size_hint()
+		match self.inner {
+			ReceiverInner::Channel(ref rx) => rx.size_hint(),
+			ReceiverInner::Queue(ref rx) => (rx.len(), None),
+		}
 	}
 }
 
@@ -96,7 +125,11 @@ impl<T> UnboundedMeteredReceiver<T> {
 
 	/// Attempt to receive the next item.
 	pub fn try_next(&mut self) -> Result<Option<T>, mpsc::TryRecvError> {
-		match self.inner.try_next()? {
+		let next = match self.inner {
+			ReceiverInner::Channel(ref mut rx) => rx.try_next()?,
+			ReceiverInner::Queue(ref mut rx) => rx.try_next()?,
+		};
+		match next {
 			Some(value) => Ok(self.maybe_meter_tof(Some(value))),
 			None => Ok(None),
 		}
@@ -105,7 +138,25 @@ impl<T> UnboundedMeteredReceiver<T> {
 
 impl<T> futures::stream::FusedStream for UnboundedMeteredReceiver<T> {
 	fn is_terminated(&self) -> bool {
-		self.inner.is_terminated()
+		match self.inner {
+			ReceiverInner::Channel(ref rx) => rx.is_terminated(),
+			ReceiverInner::Queue(ref rx) => rx.is_terminated(),
+		}
+	}
+}
+
+#[derive(Debug)]
+enum SenderInner<T> {
+	Channel(mpsc::UnboundedSender<MaybeTimeOfFlight<T>>),
+	Queue(QueueSender<T>),
+}
+
+impl<T> Clone for SenderInner<T> {
+	fn clone(&self) -> Self {
+		match self {
+			Self::Channel(tx) => Self::Channel(tx.clone()),
+			Self::Queue(tx) => Self::Queue(tx.clone()),
+		}
 	}
 }
 
@@ -114,7 +165,7 @@ impl<T> futures::stream::FusedStream for UnboundedMeteredReceiver<T> {
 #[derive(Debug)]
 pub struct UnboundedMeteredSender<T> {
 	meter: Meter,
-	inner: mpsc::UnboundedSender<MaybeTimeOfFlight<T>>,
+	inner: SenderInner<T>,
 }
 
 impl<T> Clone for UnboundedMeteredSender<T> {
@@ -123,19 +174,6 @@ impl<T> Clone for UnboundedMeteredSender<T> {
 	}
 }
 
-impl<T> std::ops::Deref for UnboundedMeteredSender<T> {
-	type Target = mpsc::UnboundedSender<MaybeTimeOfFlight<T>>;
-	fn deref(&self) -> &Self::Target {
-		&self.inner
-	}
-}
-
-impl<T> std::ops::DerefMut for UnboundedMeteredSender<T> {
-	fn deref_mut(&mut self) -> &mut Self::Target {
-		&mut self.inner
-	}
-}
-
 impl<T> UnboundedMeteredSender<T> {
 	fn prepare_with_tof(&self, item: T) -> MaybeTimeOfFlight<T> {
 		let previous = self.meter.note_sent();
@@ -157,10 +195,44 @@ impl<T> UnboundedMeteredSender<T> {
 		&self,
 		msg: T,
 	) -> result::Result<(), mpsc::TrySendError<MaybeTimeOfFlight<T>>> {
+		self.send_inner(msg, false)
+	}
+
+	/// Attempt to send a message ahead of all normal ones or fail immediately.
+	///
+	/// Behaves like `unbounded_send` for channels with `OverflowPolicy::Block`,
+	/// which have no lanes.
+	pub fn unbounded_send_urgent(
+		&self,
+		msg: T,
+	) -> result::Result<(), mpsc::TrySendError<MaybeTimeOfFlight<T>>> {
+		self.send_inner(msg, true)
+	}
+
+	fn send_inner(
+		&self,
+		msg: T,
+		urgent: bool,
+	) -> result::Result<(), mpsc::TrySendError<MaybeTimeOfFlight<T>>> {
+		let lane = match self.inner {
+			SenderInner::Queue(_) if urgent => Lane::Urgent,
+			SenderInner::Queue(ref tx) => tx.lane(&msg),
+			SenderInner::Channel(_) => Lane::Normal,
+		};
 		let msg = self.prepare_with_tof(msg);
-		self.inner.unbounded_send(msg).map_err(|e| {
-			self.meter.retract_sent();
-			e
-		})
+		match self.inner {
+			SenderInner::Channel(ref tx) => {
+				tx.unbounded_send(msg).map_err(|e| {
+					self.meter.retract_sent();
+					e
+				})?;
+				self.meter.note_queued();
+				Ok(())
+			},
+			SenderInner::Queue(ref tx) => tx.push(&self.meter, msg, lane).map_err(|msg| {
+				self.meter.retract_sent();
+				queue::disconnected_error(msg)
+			}),
+		}
 	}
 }
@@ -36,11 +36,15 @@ use polkadot_node_subsystem::{
 	},
 	overseer, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, SpawnedSubsystem, SubsystemError,
 };
+use polkadot_node_subsystem_util::TimeoutExt as _;
 use polkadot_primitives::v2::{
 	BlockNumber, CandidateIndex, Hash, SessionIndex, ValidatorIndex, ValidatorSignature,
 };
 use rand::{CryptoRng, Rng, SeedableRng};
-use std::collections::{hash_map, BTreeMap, HashMap, HashSet, VecDeque};
+use std::{
+	collections::{hash_map, BTreeMap, HashMap, HashSet, VecDeque},
+	time::{Duration, Instant},
+};
 
 use self::metrics::Metrics;
 
@@ -58,6 +62,13 @@ const COST_ASSIGNMENT_TOO_FAR_IN_THE_FUTURE: Rep =
 	Rep::CostMinor("The vote was valid but too far in the future");
 const COST_INVALID_MESSAGE: Rep = Rep::CostMajor("The vote was bad");
 
+/// How long assignments and approvals from peers are buffered to be checked as a batch.
+const IMPORT_BATCH_WINDOW: Duration = Duration::from_millis(10);
+
+/// The maximum number of buffered assignments and approvals from peers, reaching it imports them
+/// right away.
+const MAX_IMPORT_BATCH_SIZE: usize = 256;
+
 const BENEFIT_VALID_MESSAGE: Rep = Rep::BenefitMinor("Peer sent a valid message");
 const BENEFIT_VALID_MESSAGE_FIRST: Rep =
 	Rep::BenefitMinorFirst("Valid message with new information");
@@ -179,6 +190,39 @@ struct State {
 
 	/// Config for aggression.
 	aggression_config: AggressionConfig,
+
+	/// Assignments and approvals from peers waiting to be checked by approval voting as a batch.
+	import_batch: ImportBatch,
+}
+
+/// Assignments and approvals from peers, buffered for a short time so approval voting can check
+/// them together.
+#[derive(Default)]
+struct ImportBatch {
+	messages: Vec<(PeerId, PendingMessage)>,
+	/// When the first of the messages arrived.
+	started: Option<Instant>,
+}
+
+impl ImportBatch {
+	fn push(&mut self, peer_id: PeerId, message: PendingMessage) {
+		self.started.get_or_insert_with(Instant::now);
+		self.messages.push((peer_id, message));
+	}
+
+	fn is_full(&self) -> bool {
+		self.messages.len() >= MAX_IMPORT_BATCH_SIZE
+	}
+
+	/// When the messages have to be imported, if there are any.
+	fn deadline(&self) -> Option<Instant> {
+		self.started.map(|started| started + IMPORT_BATCH_WINDOW)
+	}
+
+	fn take(&mut self) -> Vec<(PeerId, PendingMessage)> {
+		self.started = None;
+		std::mem::take(&mut self.messages)
+	}
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -465,30 +509,7 @@ impl State {
 
 				let _timer = metrics.time_import_pending_now_known();
 
-				for (peer_id, message) in to_import {
-					match message {
-						PendingMessage::Assignment(assignment, claimed_index) => {
-							self.import_and_circulate_assignment(
-								ctx,
-								metrics,
-								MessageSource::Peer(peer_id),
-								assignment,
-								claimed_index,
-								rng,
-							)
-							.await;
-						},
-						PendingMessage::Approval(approval_vote) => {
-							self.import_and_circulate_approval(
-								ctx,
-								metrics,
-								MessageSource::Peer(peer_id),
-								approval_vote,
-							)
-							.await;
-						},
-					}
-				}
+				self.import_peer_messages(ctx, metrics, to_import, rng).await;
 			}
 		}
 
@@ -559,15 +580,10 @@ impl State {
 						continue
 					}
 
-					self.import_and_circulate_assignment(
-						ctx,
-						metrics,
-						MessageSource::Peer(peer_id.clone()),
-						assignment,
-						claimed_index,
-						rng,
-					)
-					.await;
+					self.import_batch.push(
+						peer_id.clone(),
+						PendingMessage::Assignment(assignment, claimed_index),
+					);
 				}
 			},
 			protocol_v1::ApprovalDistributionMessage::Approvals(approvals) => {
@@ -597,16 +613,76 @@ impl State {
 						continue
 					}
 
-					self.import_and_circulate_approval(
-						ctx,
-						metrics,
-						MessageSource::Peer(peer_id.clone()),
-						approval_vote,
-					)
-					.await;
+					self.import_batch
+						.push(peer_id.clone(), PendingMessage::Approval(approval_vote));
 				}
 			},
 		}
+
+		if self.import_batch.is_full() {
+			self.flush_import_batch(ctx, metrics, rng).await;
+		}
+	}
+
+	/// Import all assignments and approvals from peers buffered in the import batch.
+	async fn flush_import_batch<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		rng: &mut (impl CryptoRng + Rng),
+	) {
+		let messages = self.import_batch.take();
+		if !messages.is_empty() {
+			self.import_peer_messages(ctx, metrics, messages, rng).await;
+		}
+	}
+
+	/// Import assignments and approvals from peers in order, checking consecutive assignments
+	/// and consecutive approvals as a batch.
+	async fn import_peer_messages<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		messages: Vec<(PeerId, PendingMessage)>,
+		rng: &mut (impl CryptoRng + Rng),
+	) {
+		let mut assignments = Vec::new();
+		let mut approvals = Vec::new();
+		for (peer_id, message) in messages {
+			match message {
+				PendingMessage::Assignment(assignment, claimed_index) => {
+					// Approvals are only accepted after their assignments are imported.
+					if !approvals.is_empty() {
+						self.import_and_circulate_approvals(
+							ctx,
+							metrics,
+							std::mem::take(&mut approvals),
+						)
+						.await;
+					}
+					assignments.push((peer_id, assignment, claimed_index));
+				},
+				PendingMessage::Approval(approval_vote) => {
+					if !assignments.is_empty() {
+						self.import_and_circulate_assignments(
+							ctx,
+							metrics,
+							std::mem::take(&mut assignments),
+							rng,
+						)
+						.await;
+					}
+					approvals.push((peer_id, approval_vote));
+				},
+			}
+		}
+
+		if !assignments.is_empty() {
+			self.import_and_circulate_assignments(ctx, metrics, assignments, rng).await;
+		}
+		if !approvals.is_empty() {
+			self.import_and_circulate_approvals(ctx, metrics, approvals).await;
+		}
 	}
 
 	// handle a peer view change: requires that the peer is already connected
@@ -697,84 +773,99 @@ impl State {
 	) where
 		R: CryptoRng + Rng,
 	{
-		let block_hash = assignment.block_hash.clone();
-		let validator_index = assignment.validator;
+		if let MessageSource::Peer(peer_id) = source {
+			self.import_and_circulate_assignments(
+				ctx,
+				metrics,
+				vec![(peer_id, assignment, claimed_candidate_index)],
+				rng,
+			)
+			.await;
+			return
+		}
 
-		let entry = match self.blocks.get_mut(&block_hash) {
+		let entry = match self.blocks.get_mut(&assignment.block_hash) {
 			Some(entry) => entry,
-			None => {
-				if let Some(peer_id) = source.peer_id() {
-					gum::trace!(
-						target: LOG_TARGET,
-						?peer_id,
-						hash = ?block_hash,
-						?validator_index,
-						"Unexpected assignment",
-					);
-					if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
-						modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-					}
-				}
-				return
-			},
+			None => return,
 		};
 
-		// compute metadata on the assignment.
-		let message_subject = MessageSubject(block_hash, claimed_candidate_index, validator_index);
-		let message_kind = MessageKind::Assignment;
+		let message_subject =
+			MessageSubject(assignment.block_hash, claimed_candidate_index, assignment.validator);
+		if !entry.knowledge.insert(message_subject.clone(), MessageKind::Assignment) {
+			// if we already imported an assignment, there is no need to distribute it again
+			gum::warn!(
+				target: LOG_TARGET,
+				?message_subject,
+				"Importing locally an already known assignment",
+			);
+			return
+		} else {
+			gum::debug!(
+				target: LOG_TARGET,
+				?message_subject,
+				"Importing locally a new assignment",
+			);
+		}
 
-		if let Some(peer_id) = source.peer_id() {
-			// check if our knowledge of the peer already contains this assignment
-			match entry.known_by.entry(peer_id.clone()) {
-				hash_map::Entry::Occupied(mut peer_knowledge) => {
-					let peer_knowledge = peer_knowledge.get_mut();
-					if peer_knowledge.contains(&message_subject, message_kind) {
-						// wasn't included before
-						if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
-							gum::debug!(
-								target: LOG_TARGET,
-								?peer_id,
-								?message_subject,
-								"Duplicate assignment",
-							);
-							modify_reputation(ctx.sender(), peer_id, COST_DUPLICATE_MESSAGE).await;
-						}
-						return
-					}
-				},
-				hash_map::Entry::Vacant(_) => {
-					gum::debug!(
-						target: LOG_TARGET,
-						?peer_id,
-						?message_subject,
-						"Assignment from a peer is out of view",
-					);
-					modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
-				},
-			}
+		self.circulate_assignment(ctx, metrics, source, assignment, claimed_candidate_index, rng)
+			.await;
+	}
 
-			// if the assignment is known to be valid, reward the peer
-			if entry.knowledge.contains(&message_subject, message_kind) {
-				modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE).await;
-				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-					gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known assignment");
-					peer_knowledge.received.insert(message_subject, message_kind);
+	/// Import assignments from peers, having approval voting check them as a batch, and
+	/// circulate the valid ones.
+	async fn import_and_circulate_assignments<Context, R>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		mut assignments: Vec<(PeerId, IndirectAssignmentCert, CandidateIndex)>,
+		rng: &mut R,
+	) where
+		R: CryptoRng + Rng,
+	{
+		while !assignments.is_empty() {
+			// An assignment which is already part of the batch is imported after the batch, so it
+			// is treated just like if it had arrived after the first one was imported.
+			let mut deferred = Vec::new();
+			let mut queued = HashSet::new();
+			let mut to_check = Vec::new();
+			for (peer_id, assignment, claimed_candidate_index) in assignments {
+				let message_subject = MessageSubject(
+					assignment.block_hash,
+					claimed_candidate_index,
+					assignment.validator,
+				);
+				if queued.contains(&message_subject) {
+					deferred.push((peer_id, assignment, claimed_candidate_index));
+					continue
 				}
-				return
+
+				if self.precheck_peer_assignment(ctx, &peer_id, &message_subject).await {
+					queued.insert(message_subject);
+					to_check.push((peer_id, assignment, claimed_candidate_index));
+				}
+			}
+			assignments = deferred;
+
+			if to_check.is_empty() {
+				continue
 			}
 
 			let (tx, rx) = oneshot::channel();
 
-			ctx.send_message(ApprovalVotingMessage::CheckAndImportAssignment(
-				assignment.clone(),
-				claimed_candidate_index,
+			ctx.send_message(ApprovalVotingMessage::CheckAndImportAssignments(
+				to_check
+					.iter()
+					.map(|(_, assignment, claimed_candidate_index)| {
+						(assignment.clone(), *claimed_candidate_index)
+					})
+					.collect(),
 				tx,
 			))
 			.await;
 
 			let timer = metrics.time_awaiting_approval_voting();
-			let result = match rx.await {
-				Ok(result) => result,
+			let results = match rx.await {
+				Ok(results) => results,
 				Err(_) => {
 					gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
 					return
@@ -782,78 +873,205 @@ impl State {
 			};
 			drop(timer);
 
-			gum::trace!(
-				target: LOG_TARGET,
-				?source,
-				?message_subject,
-				?result,
-				"Checked assignment",
-			);
-			match result {
-				AssignmentCheckResult::Accepted => {
-					modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE_FIRST)
-						.await;
-					entry.knowledge.known_messages.insert(message_subject.clone(), message_kind);
-					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
-					}
-				},
-				AssignmentCheckResult::AcceptedDuplicate => {
-					// "duplicate" assignments aren't necessarily equal.
-					// There is more than one way each validator can be assigned to each core.
-					// cf. https://github.com/paritytech/polkadot/pull/2160#discussion_r557628699
-					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
-					}
-					gum::debug!(
-						target: LOG_TARGET,
-						hash = ?block_hash,
-						?peer_id,
-						"Got an `AcceptedDuplicate` assignment",
-					);
-					return
-				},
-				AssignmentCheckResult::TooFarInFuture => {
-					gum::debug!(
-						target: LOG_TARGET,
-						hash = ?block_hash,
-						?peer_id,
-						"Got an assignment too far in the future",
-					);
-					modify_reputation(ctx.sender(), peer_id, COST_ASSIGNMENT_TOO_FAR_IN_THE_FUTURE)
-						.await;
-					return
-				},
-				AssignmentCheckResult::Bad(error) => {
-					gum::info!(
-						target: LOG_TARGET,
-						hash = ?block_hash,
-						?peer_id,
-						%error,
-						"Got a bad assignment from peer",
-					);
-					modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
-					return
-				},
+			for ((peer_id, assignment, claimed_candidate_index), result) in
+				to_check.into_iter().zip(results)
+			{
+				self.handle_assignment_check_result(
+					ctx,
+					metrics,
+					peer_id,
+					assignment,
+					claimed_candidate_index,
+					result,
+					rng,
+				)
+				.await;
 			}
-		} else {
-			if !entry.knowledge.insert(message_subject.clone(), message_kind) {
-				// if we already imported an assignment, there is no need to distribute it again
-				gum::warn!(
+		}
+	}
+
+	/// Check an assignment from a peer against our knowledge, before approval voting checks it.
+	///
+	/// Returns whether approval voting has to check the assignment.
+	async fn precheck_peer_assignment<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: &PeerId,
+		message_subject: &MessageSubject,
+	) -> bool {
+		let MessageSubject(block_hash, _, validator_index) = message_subject;
+		let message_kind = MessageKind::Assignment;
+
+		let entry = match self.blocks.get_mut(block_hash) {
+			Some(entry) => entry,
+			None => {
+				gum::trace!(
 					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally an already known assignment",
+					?peer_id,
+					hash = ?block_hash,
+					?validator_index,
+					"Unexpected assignment",
 				);
-				return
-			} else {
+				if !self.recent_outdated_blocks.is_recent_outdated(block_hash) {
+					modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
+				}
+				return false
+			},
+		};
+
+		// check if our knowledge of the peer already contains this assignment
+		match entry.known_by.entry(peer_id.clone()) {
+			hash_map::Entry::Occupied(mut peer_knowledge) => {
+				let peer_knowledge = peer_knowledge.get_mut();
+				if peer_knowledge.contains(message_subject, message_kind) {
+					// wasn't included before
+					if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
+						gum::debug!(
+							target: LOG_TARGET,
+							?peer_id,
+							?message_subject,
+							"Duplicate assignment",
+						);
+						modify_reputation(ctx.sender(), peer_id.clone(), COST_DUPLICATE_MESSAGE)
+							.await;
+					}
+					return false
+				}
+			},
+			hash_map::Entry::Vacant(_) => {
 				gum::debug!(
 					target: LOG_TARGET,
+					?peer_id,
 					?message_subject,
-					"Importing locally a new assignment",
+					"Assignment from a peer is out of view",
 				);
+				modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
+			},
+		}
+
+		// if the assignment is known to be valid, reward the peer
+		if entry.knowledge.contains(message_subject, message_kind) {
+			modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE).await;
+			if let Some(peer_knowledge) = entry.known_by.get_mut(peer_id) {
+				gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known assignment");
+				peer_knowledge.received.insert(message_subject.clone(), message_kind);
 			}
+			return false
 		}
 
+		true
+	}
+
+	/// Apply the result of approval voting checking an assignment from a peer.
+	async fn handle_assignment_check_result<Context, R>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		peer_id: PeerId,
+		assignment: IndirectAssignmentCert,
+		claimed_candidate_index: CandidateIndex,
+		result: AssignmentCheckResult,
+		rng: &mut R,
+	) where
+		R: CryptoRng + Rng,
+	{
+		let block_hash = assignment.block_hash;
+		let message_subject =
+			MessageSubject(block_hash, claimed_candidate_index, assignment.validator);
+		let message_kind = MessageKind::Assignment;
+
+		let entry = match self.blocks.get_mut(&block_hash) {
+			Some(entry) => entry,
+			None => return,
+		};
+
+		gum::trace!(
+			target: LOG_TARGET,
+			?peer_id,
+			?message_subject,
+			?result,
+			"Checked assignment",
+		);
+		match result {
+			AssignmentCheckResult::Accepted => {
+				modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE_FIRST).await;
+				entry.knowledge.known_messages.insert(message_subject.clone(), message_kind);
+				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
+					peer_knowledge.received.insert(message_subject.clone(), message_kind);
+				}
+			},
+			AssignmentCheckResult::AcceptedDuplicate => {
+				// "duplicate" assignments aren't necessarily equal.
+				// There is more than one way each validator can be assigned to each core.
+				// cf. https://github.com/paritytech/polkadot/pull/2160#discussion_r557628699
+				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
+					peer_knowledge.received.insert(message_subject.clone(), message_kind);
+				}
+				gum::debug!(
+					target: LOG_TARGET,
+					hash = ?block_hash,
+					?peer_id,
+					"Got an `AcceptedDuplicate` assignment",
+				);
+				return
+			},
+			AssignmentCheckResult::TooFarInFuture => {
+				gum::debug!(
+					target: LOG_TARGET,
+					hash = ?block_hash,
+					?peer_id,
+					"Got an assignment too far in the future",
+				);
+				modify_reputation(ctx.sender(), peer_id, COST_ASSIGNMENT_TOO_FAR_IN_THE_FUTURE)
+					.await;
+				return
+			},
+			AssignmentCheckResult::Bad(error) => {
+				gum::info!(
+					target: LOG_TARGET,
+					hash = ?block_hash,
+					?peer_id,
+					%error,
+					"Got a bad assignment from peer",
+				);
+				modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
+				return
+			},
+		}
+
+		self.circulate_assignment(
+			ctx,
+			metrics,
+			MessageSource::Peer(peer_id),
+			assignment,
+			claimed_candidate_index,
+			rng,
+		)
+		.await;
+	}
+
+	/// Send a newly imported assignment to the peers which should receive it.
+	async fn circulate_assignment<Context, R>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		source: MessageSource,
+		assignment: IndirectAssignmentCert,
+		claimed_candidate_index: CandidateIndex,
+		rng: &mut R,
+	) where
+		R: CryptoRng + Rng,
+	{
+		let block_hash = assignment.block_hash.clone();
+		let validator_index = assignment.validator;
+		let message_subject = MessageSubject(block_hash, claimed_candidate_index, validator_index);
+		let message_kind = MessageKind::Assignment;
+
+		let entry = match self.blocks.get_mut(&block_hash) {
+			Some(entry) => entry,
+			None => return,
+		};
+
 		// Invariant: to our knowledge, none of the peers except for the `source` know about the assignment.
 		metrics.on_assignment_imported();
 
@@ -955,85 +1173,80 @@ impl State {
 		source: MessageSource,
 		vote: IndirectSignedApprovalVote,
 	) {
-		let block_hash = vote.block_hash.clone();
-		let validator_index = vote.validator;
-		let candidate_index = vote.candidate_index;
+		if let MessageSource::Peer(peer_id) = source {
+			self.import_and_circulate_approvals(ctx, metrics, vec![(peer_id, vote)]).await;
+			return
+		}
 
-		let entry = match self.blocks.get_mut(&block_hash) {
-			Some(entry) if entry.candidates.get(candidate_index as usize).is_some() => entry,
-			_ => {
-				if let Some(peer_id) = source.peer_id() {
-					if !self.recent_outdated_blocks.is_recent_outdated(&block_hash) {
-						modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-					}
-				}
-				return
-			},
+		let entry = match self.blocks.get_mut(&vote.block_hash) {
+			Some(entry) if entry.candidates.get(vote.candidate_index as usize).is_some() => entry,
+			_ => return,
 		};
 
-		// compute metadata on the assignment.
-		let message_subject = MessageSubject(block_hash, candidate_index, validator_index);
-		let message_kind = MessageKind::Approval;
+		let message_subject = MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+		if !entry.knowledge.insert(message_subject.clone(), MessageKind::Approval) {
+			// if we already imported an approval, there is no need to distribute it again
+			gum::warn!(
+				target: LOG_TARGET,
+				?message_subject,
+				"Importing locally an already known approval",
+			);
+			return
+		} else {
+			gum::debug!(
+				target: LOG_TARGET,
+				?message_subject,
+				"Importing locally a new approval",
+			);
+		}
 
-		if let Some(peer_id) = source.peer_id() {
-			if !entry.knowledge.contains(&message_subject, MessageKind::Assignment) {
-				gum::debug!(
-					target: LOG_TARGET,
-					?peer_id,
-					?message_subject,
-					"Unknown approval assignment",
-				);
-				modify_reputation(ctx.sender(), peer_id, COST_UNEXPECTED_MESSAGE).await;
-				return
-			}
+		self.circulate_approval(ctx, metrics, source, vote).await;
+	}
 
-			// check if our knowledge of the peer already contains this approval
-			match entry.known_by.entry(peer_id.clone()) {
-				hash_map::Entry::Occupied(mut knowledge) => {
-					let peer_knowledge = knowledge.get_mut();
-					if peer_knowledge.contains(&message_subject, message_kind) {
-						if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
-							gum::debug!(
-								target: LOG_TARGET,
-								?peer_id,
-								?message_subject,
-								"Duplicate approval",
-							);
-
-							modify_reputation(ctx.sender(), peer_id, COST_DUPLICATE_MESSAGE).await;
-						}
-						return
-					}
-				},
-				hash_map::Entry::Vacant(_) => {
-					gum::debug!(
-						target: LOG_TARGET,
-						?peer_id,
-						?message_subject,
-						"Approval from a peer is out of view",
-					);
-					modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
-				},
-			}
+	/// Import approvals from peers, having approval voting check them as a batch, and circulate
+	/// the valid ones.
+	async fn import_and_circulate_approvals<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		mut votes: Vec<(PeerId, IndirectSignedApprovalVote)>,
+	) {
+		while !votes.is_empty() {
+			// An approval which is already part of the batch is imported after the batch, so it is
+			// treated just like if it had arrived after the first one was imported.
+			let mut deferred = Vec::new();
+			let mut queued = HashSet::new();
+			let mut to_check = Vec::new();
+			for (peer_id, vote) in votes {
+				let message_subject =
+					MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+				if queued.contains(&message_subject) {
+					deferred.push((peer_id, vote));
+					continue
+				}
 
-			// if the approval is known to be valid, reward the peer
-			if entry.knowledge.contains(&message_subject, message_kind) {
-				gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known approval");
-				modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE).await;
-				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-					peer_knowledge.received.insert(message_subject.clone(), message_kind);
+				if self.precheck_peer_approval(ctx, &peer_id, &message_subject).await {
+					queued.insert(message_subject);
+					to_check.push((peer_id, vote));
 				}
-				return
+			}
+			votes = deferred;
+
+			if to_check.is_empty() {
+				continue
 			}
 
 			let (tx, rx) = oneshot::channel();
 
-			ctx.send_message(ApprovalVotingMessage::CheckAndImportApproval(vote.clone(), tx))
-				.await;
+			ctx.send_message(ApprovalVotingMessage::CheckAndImportApprovals(
+				to_check.iter().map(|(_, vote)| vote.clone()).collect(),
+				tx,
+			))
+			.await;
 
 			let timer = metrics.time_awaiting_approval_voting();
-			let result = match rx.await {
-				Ok(result) => result,
+			let results = match rx.await {
+				Ok(results) => results,
 				Err(_) => {
 					gum::debug!(target: LOG_TARGET, "The approval voting subsystem is down");
 					return
@@ -1041,52 +1254,155 @@ impl State {
 			};
 			drop(timer);
 
-			gum::trace!(
+			for ((peer_id, vote), result) in to_check.into_iter().zip(results) {
+				self.handle_approval_check_result(ctx, metrics, peer_id, vote, result).await;
+			}
+		}
+	}
+
+	/// Check an approval from a peer against our knowledge, before approval voting checks it.
+	///
+	/// Returns whether approval voting has to check the approval.
+	async fn precheck_peer_approval<Context>(
+		&mut self,
+		ctx: &mut Context,
+		peer_id: &PeerId,
+		message_subject: &MessageSubject,
+	) -> bool {
+		let MessageSubject(block_hash, candidate_index, _) = message_subject;
+		let message_kind = MessageKind::Approval;
+
+		let entry = match self.blocks.get_mut(block_hash) {
+			Some(entry) if entry.candidates.get(*candidate_index as usize).is_some() => entry,
+			_ => {
+				if !self.recent_outdated_blocks.is_recent_outdated(block_hash) {
+					modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
+				}
+				return false
+			},
+		};
+
+		if !entry.knowledge.contains(message_subject, MessageKind::Assignment) {
+			gum::debug!(
 				target: LOG_TARGET,
 				?peer_id,
 				?message_subject,
-				?result,
-				"Checked approval",
+				"Unknown approval assignment",
 			);
-			match result {
-				ApprovalCheckResult::Accepted => {
-					modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE_FIRST)
-						.await;
+			modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
+			return false
+		}
+
+		// check if our knowledge of the peer already contains this approval
+		match entry.known_by.entry(peer_id.clone()) {
+			hash_map::Entry::Occupied(mut knowledge) => {
+				let peer_knowledge = knowledge.get_mut();
+				if peer_knowledge.contains(message_subject, message_kind) {
+					if !peer_knowledge.received.insert(message_subject.clone(), message_kind) {
+						gum::debug!(
+							target: LOG_TARGET,
+							?peer_id,
+							?message_subject,
+							"Duplicate approval",
+						);
 
-					entry.knowledge.insert(message_subject.clone(), message_kind);
-					if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
-						peer_knowledge.received.insert(message_subject.clone(), message_kind);
+						modify_reputation(ctx.sender(), peer_id.clone(), COST_DUPLICATE_MESSAGE)
+							.await;
 					}
-				},
-				ApprovalCheckResult::Bad(error) => {
-					modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
-					gum::info!(
-						target: LOG_TARGET,
-						?peer_id,
-						%error,
-						"Got a bad approval from peer",
-					);
-					return
-				},
-			}
-		} else {
-			if !entry.knowledge.insert(message_subject.clone(), message_kind) {
-				// if we already imported an approval, there is no need to distribute it again
-				gum::warn!(
-					target: LOG_TARGET,
-					?message_subject,
-					"Importing locally an already known approval",
-				);
-				return
-			} else {
+					return false
+				}
+			},
+			hash_map::Entry::Vacant(_) => {
 				gum::debug!(
 					target: LOG_TARGET,
+					?peer_id,
 					?message_subject,
-					"Importing locally a new approval",
+					"Approval from a peer is out of view",
 				);
+				modify_reputation(ctx.sender(), peer_id.clone(), COST_UNEXPECTED_MESSAGE).await;
+			},
+		}
+
+		// if the approval is known to be valid, reward the peer
+		if entry.knowledge.contains(message_subject, message_kind) {
+			gum::trace!(target: LOG_TARGET, ?peer_id, ?message_subject, "Known approval");
+			modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE).await;
+			if let Some(peer_knowledge) = entry.known_by.get_mut(peer_id) {
+				peer_knowledge.received.insert(message_subject.clone(), message_kind);
 			}
+			return false
 		}
 
+		true
+	}
+
+	/// Apply the result of approval voting checking an approval from a peer.
+	async fn handle_approval_check_result<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		peer_id: PeerId,
+		vote: IndirectSignedApprovalVote,
+		result: ApprovalCheckResult,
+	) {
+		let message_subject = MessageSubject(vote.block_hash, vote.candidate_index, vote.validator);
+		let message_kind = MessageKind::Approval;
+
+		let entry = match self.blocks.get_mut(&vote.block_hash) {
+			Some(entry) => entry,
+			None => return,
+		};
+
+		gum::trace!(
+			target: LOG_TARGET,
+			?peer_id,
+			?message_subject,
+			?result,
+			"Checked approval",
+		);
+		match result {
+			ApprovalCheckResult::Accepted => {
+				modify_reputation(ctx.sender(), peer_id.clone(), BENEFIT_VALID_MESSAGE_FIRST).await;
+
+				entry.knowledge.insert(message_subject.clone(), message_kind);
+				if let Some(peer_knowledge) = entry.known_by.get_mut(&peer_id) {
+					peer_knowledge.received.insert(message_subject, message_kind);
+				}
+			},
+			ApprovalCheckResult::Bad(error) => {
+				modify_reputation(ctx.sender(), peer_id, COST_INVALID_MESSAGE).await;
+				gum::info!(
+					target: LOG_TARGET,
+					?peer_id,
+					%error,
+					"Got a bad approval from peer",
+				);
+				return
+			},
+		}
+
+		self.circulate_approval(ctx, metrics, MessageSource::Peer(peer_id), vote).await;
+	}
+
+	/// Send a newly imported approval to the peers which should receive it.
+	async fn circulate_approval<Context>(
+		&mut self,
+		ctx: &mut Context,
+		metrics: &Metrics,
+		source: MessageSource,
+		vote: IndirectSignedApprovalVote,
+	) {
+		let block_hash = vote.block_hash.clone();
+		let validator_index = vote.validator;
+		let candidate_index = vote.candidate_index;
+		let message_subject = MessageSubject(block_hash, candidate_index, validator_index);
+		let message_kind = MessageKind::Approval;
+
+		let entry = match self.blocks.get_mut(&block_hash) {
+			Some(entry) => entry,
+			None => return,
+		};
+
 		// Invariant: to our knowledge, none of the peers except for the `source` know about the approval.
 		metrics.on_approval_imported();
 
@@ -1652,13 +1968,43 @@ impl ApprovalDistribution {
 		rng: &mut (impl CryptoRng + Rng),
 	) {
 		loop {
-			let message = match ctx.recv().await {
+			let message = match state.import_batch.deadline() {
+				None => ctx.recv().await,
+				Some(deadline) => {
+					let message = ctx
+						.recv()
+						.timeout(deadline.saturating_duration_since(Instant::now()))
+						.await;
+					match message {
+						Some(message) => message,
+						None => {
+							state.flush_import_batch(&mut ctx, &self.metrics, rng).await;
+							continue
+						},
+					}
+				},
+			};
+			let message = match message {
 				Ok(message) => message,
 				Err(e) => {
 					gum::debug!(target: LOG_TARGET, err = ?e, "Failed to receive a message from Overseer, exiting");
 					return
 				},
 			};
+
+			// Everything but further assignments and approvals from peers may depend on the
+			// buffered ones being imported.
+			if !matches!(
+				message,
+				FromOrchestra::Communication {
+					msg: ApprovalDistributionMessage::NetworkBridgeUpdate(
+						NetworkBridgeEvent::PeerMessage(..)
+					)
+				}
+			) {
+				state.flush_import_batch(&mut ctx, &self.metrics, rng).await;
+			}
+
 			match message {
 				FromOrchestra::Communication { msg } =>
 					Self::handle_incoming(&mut ctx, state, msg, &self.metrics, rng).await,
@@ -21,7 +21,9 @@ use polkadot_node_network_protocol::{our_view, view, ObservedRole};
 use polkadot_node_primitives::approval::{
 	AssignmentCertKind, VRFOutput, VRFProof, RELAY_VRF_MODULO_CONTEXT,
 };
-use polkadot_node_subsystem::messages::{network_bridge_event, AllMessages, ApprovalCheckError};
+use polkadot_node_subsystem::messages::{
+	network_bridge_event, AllMessages, ApprovalCheckError, AssignmentCheckError,
+};
 use polkadot_node_subsystem_test_helpers as test_helpers;
 use polkadot_node_subsystem_util::TimeoutExt as _;
 use polkadot_primitives::v2::{AuthorityDiscoveryId, BlakeTwo256, HashT};
@@ -285,13 +287,12 @@ fn try_import_the_same_assignment() {
 		// send an `Accept` message from the Approval Voting subsystem
 		assert_matches!(
 			overseer_recv(overseer).await,
-			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-				assignment,
-				0u32,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				assignments,
 				tx,
 			)) => {
-				assert_eq!(assignment, cert);
-				tx.send(AssignmentCheckResult::Accepted).unwrap();
+				assert_eq!(assignments, vec![(cert.clone(), 0u32)]);
+				tx.send(vec![AssignmentCheckResult::Accepted]).unwrap();
 			}
 		);
 
@@ -325,6 +326,80 @@ fn try_import_the_same_assignment() {
 	});
 }
 
+/// Assignments from several peers arriving together are checked as one batch,
+/// but each peer is only held accountable for its own assignments.
+#[test]
+fn assignments_from_several_peers_are_checked_as_a_batch() {
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let parent_hash = Hash::repeat_byte(0xFF);
+	let hash = Hash::repeat_byte(0xAA);
+
+	let _ = test_harness(State::default(), |mut virtual_overseer| async move {
+		let overseer = &mut virtual_overseer;
+		setup_peer_with_view(overseer, &peer_a, view![hash]).await;
+		setup_peer_with_view(overseer, &peer_b, view![hash]).await;
+
+		// new block `hash` with 2 candidates
+		let meta = BlockApprovalMeta {
+			hash,
+			parent_hash,
+			number: 2,
+			candidates: vec![Default::default(); 2],
+			slot: 1.into(),
+			session: 1,
+		};
+		let msg = ApprovalDistributionMessage::NewBlocks(vec![meta]);
+		overseer_send(overseer, msg).await;
+
+		let cert_a = fake_assignment_cert(hash, ValidatorIndex(0));
+		let cert_b = fake_assignment_cert(hash, ValidatorIndex(1));
+
+		let msg = protocol_v1::ApprovalDistributionMessage::Assignments(vec![(cert_a.clone(), 0)]);
+		send_message_from_peer(overseer, &peer_a, msg).await;
+		let msg = protocol_v1::ApprovalDistributionMessage::Assignments(vec![(cert_b.clone(), 1)]);
+		send_message_from_peer(overseer, &peer_b, msg).await;
+
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				assignments,
+				tx,
+			)) => {
+				assert_eq!(assignments, vec![(cert_a.clone(), 0), (cert_b.clone(), 1)]);
+				tx.send(vec![
+					AssignmentCheckResult::Bad(AssignmentCheckError::InvalidCert(
+						ValidatorIndex(0),
+						String::from("bad cert"),
+					)),
+					AssignmentCheckResult::Accepted,
+				])
+				.unwrap();
+			}
+		);
+
+		expect_reputation_change(overseer, &peer_a, COST_INVALID_MESSAGE).await;
+		expect_reputation_change(overseer, &peer_b, BENEFIT_VALID_MESSAGE_FIRST).await;
+
+		// only the valid assignment is circulated
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendValidationMessage(
+				peers,
+				Versioned::V1(protocol_v1::ValidationProtocol::ApprovalDistribution(
+					protocol_v1::ApprovalDistributionMessage::Assignments(assignments)
+				))
+			)) => {
+				assert_eq!(peers, vec![peer_a.clone()]);
+				assert_eq!(assignments, vec![(cert_b, 1)]);
+			}
+		);
+
+		assert!(overseer.recv().timeout(TIMEOUT).await.is_none(), "no message should be sent");
+		virtual_overseer
+	});
+}
+
 /// <https://github.com/paritytech/polkadot/pull/2160#discussion_r547594835>
 ///
 /// 1. Send a view update that removes block B from their view.
@@ -369,22 +444,23 @@ fn spam_attack_results_in_negative_reputation_change() {
 		let msg = protocol_v1::ApprovalDistributionMessage::Assignments(assignments.clone());
 		send_message_from_peer(overseer, peer, msg.clone()).await;
 
-		for i in 0..candidates_count {
+		for _ in 0..candidates_count {
 			expect_reputation_change(overseer, peer, COST_UNEXPECTED_MESSAGE).await;
+		}
 
-			assert_matches!(
-				overseer_recv(overseer).await,
-				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-					assignment,
-					claimed_candidate_index,
-					tx,
-				)) => {
-					assert_eq!(assignment, assignments[i].0);
-					assert_eq!(claimed_candidate_index, assignments[i].1);
-					tx.send(AssignmentCheckResult::Accepted).unwrap();
-				}
-			);
+		// all of them are checked as a batch
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				checked_assignments,
+				tx,
+			)) => {
+				assert_eq!(checked_assignments, assignments);
+				tx.send(vec![AssignmentCheckResult::Accepted; candidates_count]).unwrap();
+			}
+		);
 
+		for _ in 0..candidates_count {
 			expect_reputation_change(overseer, peer, BENEFIT_VALID_MESSAGE_FIRST).await;
 		}
 
@@ -551,12 +627,12 @@ fn import_approval_happy_path() {
 
 		assert_matches!(
 			overseer_recv(overseer).await,
-			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApproval(
-				vote,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApprovals(
+				votes,
 				tx,
 			)) => {
-				assert_eq!(vote, approval);
-				tx.send(ApprovalCheckResult::Accepted).unwrap();
+				assert_eq!(votes, vec![approval.clone()]);
+				tx.send(vec![ApprovalCheckResult::Accepted]).unwrap();
 			}
 		);
 
@@ -626,14 +702,12 @@ fn import_approval_bad() {
 
 		assert_matches!(
 			overseer_recv(overseer).await,
-			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-				assignment,
-				i,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				assignments,
 				tx,
 			)) => {
-				assert_eq!(assignment, cert);
-				assert_eq!(i, candidate_index);
-				tx.send(AssignmentCheckResult::Accepted).unwrap();
+				assert_eq!(assignments, vec![(cert.clone(), candidate_index)]);
+				tx.send(vec![AssignmentCheckResult::Accepted]).unwrap();
 			}
 		);
 
@@ -645,12 +719,13 @@ fn import_approval_bad() {
 
 		assert_matches!(
 			overseer_recv(overseer).await,
-			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApproval(
-				vote,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApprovals(
+				votes,
 				tx,
 			)) => {
-				assert_eq!(vote, approval);
-				tx.send(ApprovalCheckResult::Bad(ApprovalCheckError::UnknownBlock(hash))).unwrap();
+				assert_eq!(votes, vec![approval.clone()]);
+				tx.send(vec![ApprovalCheckResult::Bad(ApprovalCheckError::UnknownBlock(hash))])
+					.unwrap();
 			}
 		);
 
@@ -922,14 +997,12 @@ fn import_remotely_then_locally() {
 		// send an `Accept` message from the Approval Voting subsystem
 		assert_matches!(
 			overseer_recv(overseer).await,
-			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-				assignment,
-				i,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				assignments,
 				tx,
 			)) => {
-				assert_eq!(assignment, cert);
-				assert_eq!(i, candidate_index);
-				tx.send(AssignmentCheckResult::Accepted).unwrap();
+				assert_eq!(assignments, vec![(cert.clone(), candidate_index)]);
+				tx.send(vec![AssignmentCheckResult::Accepted]).unwrap();
 			}
 		);
 
@@ -956,12 +1029,12 @@ fn import_remotely_then_locally() {
 
 		assert_matches!(
 			overseer_recv(overseer).await,
-			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApproval(
-				vote,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportApprovals(
+				votes,
 				tx,
 			)) => {
-				assert_eq!(vote, approval);
-				tx.send(ApprovalCheckResult::Accepted).unwrap();
+				assert_eq!(votes, vec![approval.clone()]);
+				tx.send(vec![ApprovalCheckResult::Accepted]).unwrap();
 			}
 		);
 		expect_reputation_change(overseer, peer, BENEFIT_VALID_MESSAGE_FIRST).await;
@@ -1109,24 +1182,22 @@ fn race_condition_in_local_vs_remote_view_update() {
 		let msg = ApprovalDistributionMessage::NewBlocks(vec![meta]);
 		overseer_send(overseer, msg).await;
 
-		for i in 0..candidates_count {
-			// Previously, this has caused out-of-view assignments/approvals
-			//expect_reputation_change(overseer, peer, COST_UNEXPECTED_MESSAGE).await;
+		// Previously, this has caused out-of-view assignments/approvals
+		//expect_reputation_change(overseer, peer, COST_UNEXPECTED_MESSAGE).await;
 
-			assert_matches!(
-				overseer_recv(overseer).await,
-				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-					assignment,
-					claimed_candidate_index,
-					tx,
-				)) => {
-					assert_eq!(assignment, assignments[i].0);
-					assert_eq!(claimed_candidate_index, assignments[i].1);
-					tx.send(AssignmentCheckResult::Accepted).unwrap();
-				}
-			);
+		assert_matches!(
+			overseer_recv(overseer).await,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
+				checked_assignments,
+				tx,
+			)) => {
+				assert_eq!(checked_assignments, assignments);
+				tx.send(vec![AssignmentCheckResult::Accepted; candidates_count]).unwrap();
+			}
+		);
 
-			// Since we have a valid statement pending, this should always occur
+		// Since we have a valid statement pending, this should always occur
+		for _ in 0..candidates_count {
 			expect_reputation_change(overseer, peer, BENEFIT_VALID_MESSAGE_FIRST).await;
 		}
 		virtual_overseer
@@ -1290,12 +1361,11 @@ fn propagates_assignments_along_unshared_dimension() {
 			send_message_from_peer(overseer, &peers[99].0, msg).await;
 			assert_matches!(
 				overseer_recv(overseer).await,
-				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-					_,
+				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
 					_,
 					tx,
 				)) => {
-					tx.send(AssignmentCheckResult::Accepted).unwrap();
+					tx.send(vec![AssignmentCheckResult::Accepted]).unwrap();
 				}
 			);
 			expect_reputation_change(overseer, &peers[99].0, BENEFIT_VALID_MESSAGE_FIRST).await;
@@ -1339,12 +1409,11 @@ fn propagates_assignments_along_unshared_dimension() {
 			send_message_from_peer(overseer, &peers[99].0, msg).await;
 			assert_matches!(
 				overseer_recv(overseer).await,
-				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-					_,
+				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
 					_,
 					tx,
 				)) => {
-					tx.send(AssignmentCheckResult::Accepted).unwrap();
+					tx.send(vec![AssignmentCheckResult::Accepted]).unwrap();
 				}
 			);
 			expect_reputation_change(overseer, &peers[99].0, BENEFIT_VALID_MESSAGE_FIRST).await;
@@ -1878,12 +1947,11 @@ fn non_originator_aggression_l1() {
 		send_message_from_peer(overseer, &peers[99].0, msg).await;
 		assert_matches!(
 			overseer_recv(overseer).await,
-			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-				_,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
 				_,
 				tx,
 			)) => {
-				tx.send(AssignmentCheckResult::Accepted).unwrap();
+				tx.send(vec![AssignmentCheckResult::Accepted]).unwrap();
 			}
 		);
 
@@ -1983,12 +2051,11 @@ fn non_originator_aggression_l2() {
 		send_message_from_peer(overseer, &peers[99].0, msg).await;
 		assert_matches!(
 			overseer_recv(overseer).await,
-			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-				_,
+			AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
 				_,
 				tx,
 			)) => {
-				tx.send(AssignmentCheckResult::Accepted).unwrap();
+				tx.send(vec![AssignmentCheckResult::Accepted]).unwrap();
 			}
 		);
 
@@ -2143,12 +2210,11 @@ fn resends_messages_periodically() {
 			send_message_from_peer(overseer, &peers[99].0, msg).await;
 			assert_matches!(
 				overseer_recv(overseer).await,
-				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignment(
-					_,
+				AllMessages::ApprovalVoting(ApprovalVotingMessage::CheckAndImportAssignments(
 					_,
 					tx,
 				)) => {
-					tx.send(AssignmentCheckResult::Accepted).unwrap();
+					tx.send(vec![AssignmentCheckResult::Accepted]).unwrap();
 				}
 			);
 			expect_reputation_change(overseer, &peers[99].0, BENEFIT_VALID_MESSAGE_FIRST).await;
@@ -18,7 +18,7 @@ use futures::{future::Either, FutureExt, StreamExt, TryFutureExt};
 
 use sp_keystore::SyncCryptoStorePtr;
 
-use polkadot_node_network_protocol::request_response::{v1, IncomingRequestReceiver};
+use polkadot_node_network_protocol::request_response::{v1, v2, IncomingRequestReceiver};
 use polkadot_node_subsystem::{
 	messages::AvailabilityDistributionMessage, overseer, FromOrchestra, OverseerSignal,
 	SpawnedSubsystem, SubsystemError,
@@ -39,7 +39,7 @@ mod pov_requester;
 
 /// Responding to erasure chunk requests:
 mod responder;
-use responder::{run_chunk_receiver, run_pov_receiver};
+use responder::{run_chunk_receiver, run_chunk_v2_receiver, run_pov_receiver};
 
 mod metrics;
 /// Prometheus `Metrics` for availability distribution.
@@ -66,6 +66,8 @@ pub struct IncomingRequestReceivers {
 	pub pov_req_receiver: IncomingRequestReceiver<v1::PoVFetchingRequest>,
 	/// Receiver for incoming availability chunk requests.
 	pub chunk_req_receiver: IncomingRequestReceiver<v1::ChunkFetchingRequest>,
+	/// Receiver for incoming requests of several availability chunks at once.
+	pub chunk_req_v2_receiver: IncomingRequestReceiver<v2::ChunkFetchingRequest>,
 }
 
 #[overseer::subsystem(AvailabilityDistribution, error=SubsystemError, prefix=self::overseer)]
@@ -96,7 +98,11 @@ impl AvailabilityDistributionSubsystem {
 	async fn run<Context>(self, mut ctx: Context) -> std::result::Result<(), FatalError> {
 		let Self { mut runtime, recvs, metrics } = self;
 
-		let IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver } = recvs;
+		let IncomingRequestReceivers {
+			pov_req_receiver,
+			chunk_req_receiver,
+			chunk_req_v2_receiver,
+		} = recvs;
 		let mut requester = Requester::new(metrics.clone()).fuse();
 
 		{
@@ -109,7 +115,13 @@ impl AvailabilityDistributionSubsystem {
 
 			ctx.spawn(
 				"chunk-receiver",
-				run_chunk_receiver(sender, chunk_req_receiver, metrics.clone()).boxed(),
+				run_chunk_receiver(sender.clone(), chunk_req_receiver, metrics.clone()).boxed(),
+			)
+			.map_err(FatalError::SpawnTask)?;
+
+			ctx.spawn(
+				"chunk-v2-receiver",
+				run_chunk_v2_receiver(sender, chunk_req_v2_receiver, metrics.clone()).boxed(),
 			)
 			.map_err(FatalError::SpawnTask)?;
 		}
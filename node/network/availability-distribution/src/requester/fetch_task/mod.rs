@@ -25,9 +25,10 @@ use futures::{
 use polkadot_erasure_coding::branch_hash;
 use polkadot_node_network_protocol::request_response::{
 	outgoing::{OutgoingRequest, Recipient, RequestError, Requests},
-	v1::{ChunkFetchingRequest, ChunkFetchingResponse},
+	v1::{self, ChunkFetchingRequest},
+	v2,
 };
-use polkadot_node_primitives::ErasureChunk;
+use polkadot_node_primitives::{ErasureChunk, Proof};
 use polkadot_node_subsystem::{
 	jaeger,
 	messages::{AvailabilityStoreMessage, IfDisconnected, NetworkBridgeTxMessage},
@@ -266,24 +267,9 @@ impl RunningTask {
 			count += 1;
 
 			// Send request:
-			let resp = match self.do_request(&validator).await {
-				Ok(resp) => resp,
-				Err(TaskError::ShuttingDown) => {
-					gum::info!(
-						target: LOG_TARGET,
-						"Node seems to be shutting down, canceling fetch task"
-					);
-					self.metrics.on_fetch(FAILED);
-					return
-				},
-				Err(TaskError::PeerError) => {
-					bad_validators.push(validator);
-					continue
-				},
-			};
-			let chunk = match resp {
-				ChunkFetchingResponse::Chunk(resp) => resp.recombine_into_chunk(&self.request),
-				ChunkFetchingResponse::NoSuchChunk => {
+			let chunk = match self.do_request(&validator).await {
+				Ok(Some(chunk)) => chunk,
+				Ok(None) => {
 					gum::debug!(
 						target: LOG_TARGET,
 						validator = ?validator,
@@ -297,6 +283,18 @@ impl RunningTask {
 					bad_validators.push(validator);
 					continue
 				},
+				Err(TaskError::ShuttingDown) => {
+					gum::info!(
+						target: LOG_TARGET,
+						"Node seems to be shutting down, canceling fetch task"
+					);
+					self.metrics.on_fetch(FAILED);
+					return
+				},
+				Err(TaskError::PeerError) => {
+					bad_validators.push(validator);
+					continue
+				},
 			};
 
 			// Data genuine?
@@ -321,29 +319,103 @@ impl RunningTask {
 		}
 	}
 
-	/// Do request and return response, if successful.
+	/// Do request and return the chunk, if successful.
+	///
+	/// `Ok(None)` means the validator did not have our chunk. The chunk is requested via the
+	/// second version of the chunk fetching protocol, falling back to the first version if the
+	/// validator does not support it yet.
 	async fn do_request(
 		&mut self,
 		validator: &AuthorityDiscoveryId,
-	) -> std::result::Result<ChunkFetchingResponse, TaskError> {
+	) -> std::result::Result<Option<ErasureChunk>, TaskError> {
+		let request = v2::ChunkFetchingRequest {
+			candidate_hash: self.request.candidate_hash,
+			indices: vec![self.request.index],
+		};
+		let (full_request, response_recv) =
+			OutgoingRequest::new(Recipient::Authority(validator.clone()), request.clone());
+		self.send_request(Requests::ChunkFetchingV2(full_request)).await?;
+
+		match response_recv.await {
+			Ok(v2::ChunkFetchingResponse::Chunks(resp)) =>
+				self.recombine_into_chunk(validator, resp, &request).map(Some),
+			Ok(v2::ChunkFetchingResponse::NoSuchChunk) => Ok(None),
+			Err(err) if err.is_unsupported_protocol() => {
+				gum::trace!(
+					target: LOG_TARGET,
+					origin = ?validator,
+					candidate_hash = ?self.request.candidate_hash,
+					"Validator does not support chunk fetching v2, falling back to v1"
+				);
+				self.do_request_v1(validator).await
+			},
+			Err(err) => Err(self.on_request_error(validator, err)),
+		}
+	}
+
+	/// Request the chunk via the first version of the chunk fetching protocol.
+	async fn do_request_v1(
+		&mut self,
+		validator: &AuthorityDiscoveryId,
+	) -> std::result::Result<Option<ErasureChunk>, TaskError> {
 		let (full_request, response_recv) =
 			OutgoingRequest::new(Recipient::Authority(validator.clone()), self.request);
-		let requests = Requests::ChunkFetchingV1(full_request);
+		self.send_request(Requests::ChunkFetchingV1(full_request)).await?;
+
+		match response_recv.await {
+			Ok(v1::ChunkFetchingResponse::Chunk(resp)) =>
+				Ok(Some(resp.recombine_into_chunk(&self.request))),
+			Ok(v1::ChunkFetchingResponse::NoSuchChunk) => Ok(None),
+			Err(err) => Err(self.on_request_error(validator, err)),
+		}
+	}
 
+	/// Send a request to the network bridge.
+	async fn send_request(&mut self, request: Requests) -> std::result::Result<(), TaskError> {
 		self.sender
 			.send(FromFetchTask::Message(
-				NetworkBridgeTxMessage::SendRequests(
-					vec![requests],
-					IfDisconnected::ImmediateError,
-				)
-				.into(),
+				NetworkBridgeTxMessage::SendRequests(vec![request], IfDisconnected::ImmediateError)
+					.into(),
 			))
 			.await
-			.map_err(|_| TaskError::ShuttingDown)?;
+			.map_err(|_| TaskError::ShuttingDown)
+	}
 
-		match response_recv.await {
-			Ok(resp) => Ok(resp),
-			Err(RequestError::InvalidResponse(err)) => {
+	/// Turn a response of the second version of the protocol into our chunk.
+	///
+	/// As only a single chunk was requested, the multi proof consists of exactly the nodes of its
+	/// branch.
+	fn recombine_into_chunk(
+		&self,
+		validator: &AuthorityDiscoveryId,
+		resp: v2::ChunksResponse,
+		request: &v2::ChunkFetchingRequest,
+	) -> std::result::Result<ErasureChunk, TaskError> {
+		let chunk = resp.recombine_into_chunks(request).and_then(|(mut chunks, proof)| {
+			let (index, chunk) = chunks.pop()?;
+			let proof = Proof::try_from(proof.iter().map(<[u8]>::to_vec).collect::<Vec<_>>());
+			Some(ErasureChunk { chunk, index, proof: proof.ok()? })
+		});
+
+		chunk.ok_or_else(|| {
+			gum::warn!(
+				target: LOG_TARGET,
+				origin = ?validator,
+				relay_parent = ?self.relay_parent,
+				group_index = ?self.group_index,
+				session_index = ?self.session_index,
+				chunk_index = ?self.request.index,
+				candidate_hash = ?self.request.candidate_hash,
+				"Peer sent us a malformed chunks response"
+			);
+			TaskError::PeerError
+		})
+	}
+
+	/// Log a failed request.
+	fn on_request_error(&self, validator: &AuthorityDiscoveryId, err: RequestError) -> TaskError {
+		match err {
+			RequestError::InvalidResponse(err) => {
 				gum::warn!(
 					target: LOG_TARGET,
 					origin= ?validator,
@@ -355,9 +427,8 @@ impl RunningTask {
 					err= ?err,
 					"Peer sent us invalid erasure chunk data"
 				);
-				Err(TaskError::PeerError)
 			},
-			Err(RequestError::NetworkError(err)) => {
+			RequestError::NetworkError(err) => {
 				gum::debug!(
 					target: LOG_TARGET,
 					origin= ?validator,
@@ -369,9 +440,8 @@ impl RunningTask {
 					err= ?err,
 					"Some network error occurred when fetching erasure chunk"
 				);
-				Err(TaskError::PeerError)
 			},
-			Err(RequestError::Canceled(oneshot::Canceled)) => {
+			RequestError::Canceled(oneshot::Canceled) => {
 				gum::debug!(
 					target: LOG_TARGET,
 					origin= ?validator,
@@ -382,9 +452,9 @@ impl RunningTask {
 					candidate_hash = ?self.request.candidate_hash,
 					"Erasure chunk request got canceled"
 				);
-				Err(TaskError::PeerError)
 			},
 		}
+		TaskError::PeerError
 	}
 
 	fn validate_chunk(&self, validator: &AuthorityDiscoveryId, chunk: &ErasureChunk) -> bool {
@@ -28,8 +28,8 @@ use futures::{
 use sc_network as network;
 use sp_keyring::Sr25519Keyring;
 
-use polkadot_node_network_protocol::request_response::{v1, Recipient};
-use polkadot_node_primitives::{BlockData, PoV, Proof};
+use polkadot_node_network_protocol::request_response::{v1, v2, Recipient};
+use polkadot_node_primitives::{BlockData, MultiProof, PoV, Proof};
 use polkadot_node_subsystem::messages::AllMessages;
 use polkadot_primitives::v2::{CandidateHash, ValidatorIndex};
 
@@ -59,13 +59,14 @@ fn task_does_not_accept_invalid_chunk() {
 			let mut m = HashMap::new();
 			m.insert(
 				Recipient::Authority(Sr25519Keyring::Alice.public().into()),
-				ChunkFetchingResponse::Chunk(v1::ChunkResponse {
+				v1::ChunkFetchingResponse::Chunk(v1::ChunkResponse {
 					chunk: vec![1, 2, 3],
 					proof: Proof::try_from(vec![vec![9, 8, 2], vec![2, 3, 4]]).unwrap(),
 				}),
 			);
 			m
 		},
+		chunk_v2_responses: HashMap::new(),
 		valid_chunks: HashSet::new(),
 	};
 	test.run(task, rx);
@@ -87,13 +88,14 @@ fn task_stores_valid_chunk() {
 			let mut m = HashMap::new();
 			m.insert(
 				Recipient::Authority(Sr25519Keyring::Alice.public().into()),
-				ChunkFetchingResponse::Chunk(v1::ChunkResponse {
+				v1::ChunkFetchingResponse::Chunk(v1::ChunkResponse {
 					chunk: chunk.chunk.clone(),
 					proof: chunk.proof,
 				}),
 			);
 			m
 		},
+		chunk_v2_responses: HashMap::new(),
 		valid_chunks: {
 			let mut s = HashSet::new();
 			s.insert(chunk.chunk);
@@ -119,13 +121,14 @@ fn task_does_not_accept_wrongly_indexed_chunk() {
 			let mut m = HashMap::new();
 			m.insert(
 				Recipient::Authority(Sr25519Keyring::Alice.public().into()),
-				ChunkFetchingResponse::Chunk(v1::ChunkResponse {
+				v1::ChunkFetchingResponse::Chunk(v1::ChunkResponse {
 					chunk: chunk.chunk.clone(),
 					proof: chunk.proof,
 				}),
 			);
 			m
 		},
+		chunk_v2_responses: HashMap::new(),
 		valid_chunks: HashSet::new(),
 	};
 	test.run(task, rx);
@@ -158,18 +161,18 @@ fn task_stores_valid_chunk_if_there_is_one() {
 			let mut m = HashMap::new();
 			m.insert(
 				Recipient::Authority(Sr25519Keyring::Alice.public().into()),
-				ChunkFetchingResponse::Chunk(v1::ChunkResponse {
+				v1::ChunkFetchingResponse::Chunk(v1::ChunkResponse {
 					chunk: chunk.chunk.clone(),
 					proof: chunk.proof,
 				}),
 			);
 			m.insert(
 				Recipient::Authority(Sr25519Keyring::Bob.public().into()),
-				ChunkFetchingResponse::NoSuchChunk,
+				v1::ChunkFetchingResponse::NoSuchChunk,
 			);
 			m.insert(
 				Recipient::Authority(Sr25519Keyring::Charlie.public().into()),
-				ChunkFetchingResponse::Chunk(v1::ChunkResponse {
+				v1::ChunkFetchingResponse::Chunk(v1::ChunkResponse {
 					chunk: vec![1, 2, 3],
 					proof: Proof::try_from(vec![vec![9, 8, 2], vec![2, 3, 4]]).unwrap(),
 				}),
@@ -177,6 +180,55 @@ fn task_stores_valid_chunk_if_there_is_one() {
 
 			m
 		},
+		chunk_v2_responses: HashMap::new(),
+		valid_chunks: {
+			let mut s = HashSet::new();
+			s.insert(chunk.chunk);
+			s
+		},
+	};
+	test.run(task, rx);
+}
+
+/// Task fetches the chunk via v2 from validators supporting it, falling back to v1 for the others.
+#[test]
+fn task_stores_valid_chunk_fetched_via_v2() {
+	let (mut task, rx) = get_test_running_task();
+	let pov = PoV { block_data: BlockData(vec![45, 46, 47]) };
+	let (root_hash, chunk) = get_valid_chunk_data(pov);
+	task.erasure_root = root_hash;
+	task.request.index = chunk.index;
+
+	let validators = [
+		// Alice only supports v2 and has the valid chunk, Bob does not know about v2.
+		Sr25519Keyring::Alice,
+		Sr25519Keyring::Bob,
+	]
+	.iter()
+	.map(|v| v.public().into())
+	.collect::<Vec<_>>();
+	task.group = validators;
+
+	let test = TestRun {
+		chunk_responses: {
+			let mut m = HashMap::new();
+			m.insert(
+				Recipient::Authority(Sr25519Keyring::Bob.public().into()),
+				v1::ChunkFetchingResponse::NoSuchChunk,
+			);
+			m
+		},
+		chunk_v2_responses: {
+			let mut m = HashMap::new();
+			m.insert(
+				Recipient::Authority(Sr25519Keyring::Alice.public().into()),
+				v2::ChunkFetchingResponse::Chunks(v2::ChunksResponse {
+					chunks: vec![chunk.chunk.clone()],
+					proof: MultiProof::from_proofs(std::iter::once(&chunk.proof)).unwrap(),
+				}),
+			);
+			m
+		},
 		valid_chunks: {
 			let mut s = HashSet::new();
 			s.insert(chunk.chunk);
@@ -189,7 +241,11 @@ fn task_stores_valid_chunk_if_there_is_one() {
 struct TestRun {
 	/// Response to deliver for a given validator index.
 	/// None means, answer with `NetworkError`.
-	chunk_responses: HashMap<Recipient, ChunkFetchingResponse>,
+	chunk_responses: HashMap<Recipient, v1::ChunkFetchingResponse>,
+	/// Responses to requests of the second protocol version.
+	///
+	/// Validators missing here don't support it.
+	chunk_v2_responses: HashMap<Recipient, v2::ChunkFetchingResponse>,
 	/// Set of chunks that should be considered valid:
 	valid_chunks: HashSet<Vec<u8>>,
 }
@@ -239,21 +295,40 @@ impl TestRun {
 			)) => {
 				let mut valid_responses = 0;
 				for req in reqs {
-					let req = match req {
-						Requests::ChunkFetchingV1(req) => req,
-						_ => panic!("Unexpected request"),
-					};
-					let response =
-						self.chunk_responses.get(&req.peer).ok_or(network::RequestFailure::Refused);
+					match req {
+						Requests::ChunkFetchingV1(req) => {
+							let response = self
+								.chunk_responses
+								.get(&req.peer)
+								.ok_or(network::RequestFailure::Refused);
 
-					if let Ok(ChunkFetchingResponse::Chunk(resp)) = &response {
-						if self.valid_chunks.contains(&resp.chunk) {
-							valid_responses += 1;
-						}
+							if let Ok(v1::ChunkFetchingResponse::Chunk(resp)) = &response {
+								if self.valid_chunks.contains(&resp.chunk) {
+									valid_responses += 1;
+								}
+							}
+							req.pending_response
+								.send(response.map(Encode::encode))
+								.expect("Sending response should succeed");
+						},
+						Requests::ChunkFetchingV2(req) => {
+							let response = self.chunk_v2_responses.get(&req.peer).ok_or(
+								network::RequestFailure::Network(
+									network::OutboundFailure::UnsupportedProtocols,
+								),
+							);
+
+							if let Ok(v2::ChunkFetchingResponse::Chunks(resp)) = &response {
+								if resp.chunks.iter().any(|c| self.valid_chunks.contains(c)) {
+									valid_responses += 1;
+								}
+							}
+							req.pending_response
+								.send(response.map(Encode::encode))
+								.expect("Sending response should succeed");
+						},
+						_ => panic!("Unexpected request"),
 					}
-					req.pending_response
-						.send(response.map(Encode::encode))
-						.expect("Sending response should succeed");
 				}
 				return (valid_responses == 0) && self.valid_chunks.is_empty()
 			},
@@ -22,10 +22,10 @@ use futures::channel::oneshot;
 
 use fatality::Nested;
 use polkadot_node_network_protocol::{
-	request_response::{v1, IncomingRequest, IncomingRequestReceiver},
+	request_response::{v1, v2, IncomingRequest, IncomingRequestReceiver},
 	UnifiedReputationChange as Rep,
 };
-use polkadot_node_primitives::{AvailableData, ErasureChunk};
+use polkadot_node_primitives::{AvailableData, ErasureChunk, MultiProof};
 use polkadot_node_subsystem::{jaeger, messages::AvailabilityStoreMessage, SubsystemSender};
 use polkadot_primitives::v2::{CandidateHash, ValidatorIndex};
 
@@ -97,6 +97,38 @@ pub async fn run_chunk_receiver<Sender>(
 	}
 }
 
+/// Receiver task to be forked as a separate task to handle requests for several chunks at once.
+pub async fn run_chunk_v2_receiver<Sender>(
+	mut sender: Sender,
+	mut receiver: IncomingRequestReceiver<v2::ChunkFetchingRequest>,
+	metrics: Metrics,
+) where
+	Sender: SubsystemSender<AvailabilityStoreMessage>,
+{
+	loop {
+		match receiver.recv(|| vec![COST_INVALID_REQUEST]).await.into_nested() {
+			Ok(Ok(msg)) => {
+				answer_chunks_request_log(&mut sender, msg, &metrics).await;
+			},
+			Err(fatal) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					error = ?fatal,
+					"Shutting down chunk v2 receiver."
+				);
+				return
+			},
+			Ok(Err(jfyi)) => {
+				gum::debug!(
+					target: LOG_TARGET,
+					error = ?jfyi,
+					"Error decoding incoming chunk v2 request."
+				);
+			},
+		}
+	}
+}
+
 /// Variant of `answer_pov_request` that does Prometheus metric and logging on errors.
 ///
 /// Any errors of `answer_pov_request` will simply be logged.
@@ -146,6 +178,30 @@ where
 	}
 }
 
+/// Variant of `answer_chunks_request` that does Prometheus metric and logging on errors.
+///
+/// Any errors of `answer_chunks_request` will simply be logged.
+pub async fn answer_chunks_request_log<Sender>(
+	sender: &mut Sender,
+	req: IncomingRequest<v2::ChunkFetchingRequest>,
+	metrics: &Metrics,
+) where
+	Sender: SubsystemSender<AvailabilityStoreMessage>,
+{
+	let res = answer_chunks_request(sender, req).await;
+	match res {
+		Ok(result) => metrics.on_served_chunk(if result { SUCCEEDED } else { NOT_FOUND }),
+		Err(err) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				err= ?err,
+				"Serving chunks failed with error"
+			);
+			metrics.on_served_chunk(FAILED);
+		},
+	}
+}
+
 /// Answer an incoming PoV fetch request by querying the av store.
 ///
 /// Returns: `Ok(true)` if chunk was found and served.
@@ -210,6 +266,54 @@ where
 	Ok(result)
 }
 
+/// Answer an incoming request for several chunks by querying the av store.
+///
+/// The proofs of the individual chunks are merged into a single multi proof. If any of the
+/// requested chunks is missing, `NoSuchChunk` is served.
+///
+/// Returns: `Ok(true)` if all chunks were found and served.
+pub async fn answer_chunks_request<Sender>(
+	sender: &mut Sender,
+	req: IncomingRequest<v2::ChunkFetchingRequest>,
+) -> Result<bool>
+where
+	Sender: SubsystemSender<AvailabilityStoreMessage>,
+{
+	let _span = jaeger::Span::new(req.payload.candidate_hash, "answer-chunks-request");
+
+	let mut chunks = Vec::with_capacity(req.payload.indices.len());
+	for index in &req.payload.indices {
+		match query_chunk(sender, req.payload.candidate_hash, *index).await? {
+			Some(chunk) => chunks.push(chunk),
+			None => break,
+		}
+	}
+
+	let response = if chunks.len() == req.payload.indices.len() {
+		MultiProof::from_proofs(chunks.iter().map(|chunk| &chunk.proof))
+			.ok()
+			.map(|proof| v2::ChunksResponse {
+				chunks: chunks.into_iter().map(|chunk| chunk.chunk).collect(),
+				proof,
+			})
+	} else {
+		None
+	};
+	let result = response.is_some();
+
+	gum::trace!(
+		target: LOG_TARGET,
+		hash = ?req.payload.candidate_hash,
+		indices = ?req.payload.indices,
+		peer = ?req.peer,
+		has_data = ?result,
+		"Serving chunks",
+	);
+
+	req.send_response(response.into()).map_err(|_| JfyiError::SendResponse)?;
+	Ok(result)
+}
+
 /// Query chunk from the availability store.
 async fn query_chunk<Sender>(
 	sender: &mut Sender,
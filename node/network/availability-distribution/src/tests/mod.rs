@@ -47,14 +47,22 @@ fn test_harness<T: Future<Output = ()>>(
 	let (pov_req_receiver, pov_req_cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	let (chunk_req_receiver, chunk_req_cfg) =
 		IncomingRequest::get_config_receiver(&req_protocol_names);
+	let (chunk_req_v2_receiver, chunk_req_v2_cfg) =
+		IncomingRequest::get_config_receiver(&req_protocol_names);
 	let subsystem = AvailabilityDistributionSubsystem::new(
 		keystore,
-		IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver },
+		IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver, chunk_req_v2_receiver },
 		Default::default(),
 	);
 	let subsystem = subsystem.run(context);
 
-	let test_fut = test_fx(TestHarness { virtual_overseer, pov_req_cfg, chunk_req_cfg, pool });
+	let test_fut = test_fx(TestHarness {
+		virtual_overseer,
+		pov_req_cfg,
+		chunk_req_cfg,
+		chunk_req_v2_cfg,
+		pool,
+	});
 
 	futures::pin_mut!(test_fut);
 	futures::pin_mut!(subsystem);
@@ -64,6 +72,7 @@ fn test_harness<T: Future<Output = ()>>(
 
 /// Simple basic check, whether the subsystem works as expected.
 ///
+/// Chunks are fetched and served via the second version of the chunk fetching protocol.
 /// Exceptional cases are tested as unit tests in `fetch_task`.
 #[test]
 fn check_basic() {
@@ -71,6 +80,15 @@ fn check_basic() {
 	test_harness(state.keystore.clone(), move |harness| state.run(harness));
 }
 
+/// Check whether chunks are still fetched from validators not supporting the second version of the
+/// chunk fetching protocol.
+#[test]
+fn check_fetch_falls_back_to_v1() {
+	let mut state = TestState::default();
+	state.chunk_fetching_v2 = false;
+	test_harness(state.keystore.clone(), move |harness| state.run(harness));
+}
+
 /// Check whether requester tries all validators in group.
 #[test]
 fn check_fetch_tries_all() {
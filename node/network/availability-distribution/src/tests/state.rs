@@ -34,9 +34,10 @@ use sc_network::{config as netconfig, config::RequestResponseConfig, IfDisconnec
 use sp_core::{testing::TaskExecutor, traits::SpawnNamed};
 use sp_keystore::SyncCryptoStorePtr;
 
+use parity_scale_codec::{Decode, Encode};
 use polkadot_node_network_protocol::{
 	jaeger,
-	request_response::{v1, IncomingRequest, OutgoingRequest, Requests},
+	request_response::{IncomingRequest, IsRequest, OutgoingRequest, Requests},
 };
 use polkadot_node_primitives::ErasureChunk;
 use polkadot_node_subsystem::{
@@ -61,6 +62,7 @@ pub struct TestHarness {
 	pub virtual_overseer: VirtualOverseer,
 	pub pov_req_cfg: RequestResponseConfig,
 	pub chunk_req_cfg: RequestResponseConfig,
+	pub chunk_req_v2_cfg: RequestResponseConfig,
 	pub pool: TaskExecutor,
 }
 
@@ -84,6 +86,9 @@ pub struct TestState {
 	/// Cores per relay chain block.
 	pub cores: HashMap<Hash, Vec<CoreState>>,
 	pub keystore: SyncCryptoStorePtr,
+	/// Whether the validators serving chunks support the second version of the chunk fetching
+	/// protocol.
+	pub chunk_fetching_v2: bool,
 }
 
 impl Default for TestState {
@@ -146,6 +151,7 @@ impl Default for TestState {
 			session_info,
 			cores,
 			keystore,
+			chunk_fetching_v2: true,
 		}
 	}
 }
@@ -220,15 +226,26 @@ impl TestState {
 				)) => {
 					for req in reqs {
 						// Forward requests:
-						let in_req = to_incoming_req(&harness.pool, req);
-						harness
-							.chunk_req_cfg
-							.inbound_queue
-							.as_mut()
-							.unwrap()
-							.send(in_req.into_raw())
-							.await
-							.unwrap();
+						let (cfg, in_req) = match req {
+							Requests::ChunkFetchingV1(req) => (
+								&mut harness.chunk_req_cfg,
+								to_incoming_req(&harness.pool, req).into_raw(),
+							),
+							Requests::ChunkFetchingV2(req) if self.chunk_fetching_v2 => (
+								&mut harness.chunk_req_v2_cfg,
+								to_incoming_req(&harness.pool, req).into_raw(),
+							),
+							Requests::ChunkFetchingV2(req) => {
+								req.pending_response
+									.send(Err(network::RequestFailure::Network(
+										network::OutboundFailure::UnsupportedProtocols,
+									)))
+									.expect("Sending response is expected to work");
+								continue
+							},
+							_ => panic!("Unexpected request!"),
+						};
+						cfg.inbound_queue.as_mut().unwrap().send(in_req).await.unwrap();
 					}
 				},
 				AllMessages::AvailabilityStore(AvailabilityStoreMessage::QueryChunk(
@@ -310,34 +327,34 @@ async fn overseer_recv(rx: &mut mpsc::UnboundedReceiver<AllMessages>) -> AllMess
 	rx.next().await.expect("Test subsystem no longer live")
 }
 
-fn to_incoming_req(
+fn to_incoming_req<Req>(
 	executor: &TaskExecutor,
-	outgoing: Requests,
-) -> IncomingRequest<v1::ChunkFetchingRequest> {
-	match outgoing {
-		Requests::ChunkFetchingV1(OutgoingRequest { payload, pending_response, .. }) => {
-			let (tx, rx): (oneshot::Sender<netconfig::OutgoingResponse>, oneshot::Receiver<_>) =
-				oneshot::channel();
-			executor.spawn(
-				"message-forwarding",
-				None,
-				async {
-					let response = rx.await;
-					let payload = response.expect("Unexpected canceled request").result;
-					pending_response
-						.send(payload.map_err(|_| network::RequestFailure::Refused))
-						.expect("Sending response is expected to work");
-				}
-				.boxed(),
-			);
+	outgoing: OutgoingRequest<Req>,
+) -> IncomingRequest<Req>
+where
+	Req: IsRequest + Decode + Encode,
+	Req::Response: Encode,
+{
+	let OutgoingRequest { payload, pending_response, .. } = outgoing;
+	let (tx, rx): (oneshot::Sender<netconfig::OutgoingResponse>, oneshot::Receiver<_>) =
+		oneshot::channel();
+	executor.spawn(
+		"message-forwarding",
+		None,
+		async {
+			let response = rx.await;
+			let payload = response.expect("Unexpected canceled request").result;
+			pending_response
+				.send(payload.map_err(|_| network::RequestFailure::Refused))
+				.expect("Sending response is expected to work");
+		}
+		.boxed(),
+	);
 
-			IncomingRequest::new(
-				// We don't really care:
-				network::PeerId::random(),
-				payload,
-				tx,
-			)
-		},
-		_ => panic!("Unexpected request!"),
-	}
+	IncomingRequest::new(
+		// We don't really care:
+		network::PeerId::random(),
+		payload,
+		tx,
+	)
 }
@@ -36,7 +36,10 @@ use lru::LruCache;
 use rand::seq::SliceRandom;
 
 use fatality::Nested;
-use polkadot_erasure_coding::{branch_hash, branches, obtain_chunks_v1, recovery_threshold};
+use polkadot_erasure_coding::{
+	branch_hash, branches, obtain_chunks_streaming, reconstruct_streaming, recovery_threshold,
+	systematic_recovery_threshold,
+};
 #[cfg(not(test))]
 use polkadot_node_network_protocol::request_response::CHUNK_REQUEST_TIMEOUT;
 use polkadot_node_network_protocol::{
@@ -98,6 +101,8 @@ const TIMEOUT_START_NEW_REQUESTS: Duration = Duration::from_millis(100);
 /// The Availability Recovery Subsystem.
 pub struct AvailabilityRecoverySubsystem {
 	fast_path: bool,
+	/// Whether to request the systematic chunks first when recovering from chunks.
+	systematic_chunks: bool,
 	/// Receiver for available data requests.
 	req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
 	/// Metrics for this subsystem.
@@ -123,6 +128,13 @@ struct RequestChunksFromValidators {
 	received_chunks: HashMap<ValidatorIndex, ErasureChunk>,
 	/// Pending chunk requests with soft timeout.
 	requesting_chunks: FuturesUndead<Result<Option<ErasureChunk>, (ValidatorIndex, RequestError)>>,
+	/// The number of systematic chunks, as long as only those are requested.
+	///
+	/// Once one of them cannot be fetched, this is reset to `None` and chunks are requested from
+	/// all validators.
+	systematic_threshold: Option<usize>,
+	/// The validators to request chunks from after falling back from the systematic chunks.
+	non_systematic: VecDeque<ValidatorIndex>,
 }
 
 struct RecoveryParams {
@@ -135,6 +147,9 @@ struct RecoveryParams {
 	/// The number of pieces needed.
 	threshold: usize,
 
+	/// The number of systematic chunks, if these should be requested first.
+	systematic_threshold: Option<usize>,
+
 	/// A hash of the relevant candidate.
 	candidate_hash: CandidateHash,
 
@@ -253,20 +268,79 @@ impl RequestChunksFromValidators {
 			shuffling: shuffling.into(),
 			received_chunks: HashMap::new(),
 			requesting_chunks: FuturesUndead::new(),
+			systematic_threshold: None,
+			non_systematic: VecDeque::new(),
+		}
+	}
+
+	/// Like `new`, but only requests the systematic chunks until one of them cannot be fetched.
+	///
+	/// With all of the systematic chunks the data can be recovered without decoding.
+	fn systematic_first(n_validators: u32, systematic_threshold: usize) -> Self {
+		let mut phase = Self::new(n_validators);
+		let (systematic, non_systematic) =
+			phase.shuffling.drain(..).partition(|i| (i.0 as usize) < systematic_threshold);
+		phase.shuffling = systematic;
+		phase.non_systematic = non_systematic;
+		phase.systematic_threshold = Some(systematic_threshold);
+		phase
+	}
+
+	fn for_params(params: &RecoveryParams) -> Self {
+		let n_validators = params.validators.len() as u32;
+		match params.systematic_threshold {
+			Some(systematic_threshold) =>
+				Self::systematic_first(n_validators, systematic_threshold),
+			None => Self::new(n_validators),
+		}
+	}
+
+	/// The number of chunks needed at this point.
+	fn threshold(&self, params: &RecoveryParams) -> usize {
+		self.systematic_threshold.unwrap_or(params.threshold)
+	}
+
+	/// The number of received chunks which count towards the threshold.
+	fn received_count(&self) -> usize {
+		match self.systematic_threshold {
+			Some(systematic_threshold) => self
+				.received_chunks
+				.keys()
+				.filter(|i| (i.0 as usize) < systematic_threshold)
+				.count(),
+			None => self.received_chunks.len(),
+		}
+	}
+
+	/// Stop requesting only the systematic chunks.
+	fn fall_back_to_all_chunks(&mut self) {
+		self.systematic_threshold = None;
+		for validator_index in self.non_systematic.drain(..) {
+			self.shuffling.push_front(validator_index);
+		}
+	}
+
+	/// Request the chunk of the given validator again later on.
+	fn retry_later(&mut self, validator_index: ValidatorIndex) {
+		if self.systematic_threshold.is_some() {
+			// Waiting for the chunk would hold up the recovery, so rather fall back to the others.
+			self.non_systematic.push_back(validator_index);
+		} else {
+			self.shuffling.push_front(validator_index);
 		}
 	}
 
 	fn is_unavailable(&self, params: &RecoveryParams) -> bool {
 		is_unavailable(
-			self.received_chunks.len(),
+			self.received_count(),
 			self.requesting_chunks.total_len(),
 			self.shuffling.len(),
-			params.threshold,
+			self.threshold(params),
 		)
 	}
 
 	fn can_conclude(&self, params: &RecoveryParams) -> bool {
-		self.received_chunks.len() >= params.threshold || self.is_unavailable(params)
+		self.received_count() >= self.threshold(params) || self.is_unavailable(params)
 	}
 
 	/// Desired number of parallel requests.
@@ -283,7 +357,7 @@ impl RequestChunksFromValidators {
 		// 4. We request more chunks to make up for it ...
 		let max_requests_boundary = std::cmp::min(N_PARALLEL, threshold);
 		// How many chunks are still needed?
-		let remaining_chunks = threshold.saturating_sub(self.received_chunks.len());
+		let remaining_chunks = threshold.saturating_sub(self.received_count());
 		// What is the current error rate, so we can make up for it?
 		let inv_error_rate =
 			self.total_received_responses.checked_div(self.error_count).unwrap_or(0);
@@ -301,7 +375,8 @@ impl RequestChunksFromValidators {
 	) where
 		Sender: overseer::AvailabilityRecoverySenderTrait,
 	{
-		let num_requests = self.get_desired_request_count(params.threshold);
+		let threshold = self.threshold(params);
+		let num_requests = self.get_desired_request_count(threshold);
 		let candidate_hash = &params.candidate_hash;
 		let already_requesting_count = self.requesting_chunks.len();
 
@@ -311,7 +386,7 @@ impl RequestChunksFromValidators {
 			?num_requests,
 			error_count= ?self.error_count,
 			total_received = ?self.total_received_responses,
-			threshold = ?params.threshold,
+			?threshold,
 			?already_requesting_count,
 			"Requesting availability chunks for a candidate",
 		);
@@ -455,12 +530,12 @@ impl RequestChunksFromValidators {
 								metrics.on_chunk_request_error();
 							}
 
-							self.shuffling.push_front(validator_index);
+							self.retry_later(validator_index);
 						},
 						RequestError::Canceled(_) => {
 							metrics.on_chunk_request_error();
 
-							self.shuffling.push_front(validator_index);
+							self.retry_later(validator_index);
 						},
 					}
 				},
@@ -474,7 +549,7 @@ impl RequestChunksFromValidators {
 					candidate_hash = ?params.candidate_hash,
 					received_chunks_count = ?self.received_chunks.len(),
 					requested_chunks_count = ?self.requesting_chunks.len(),
-					threshold = ?params.threshold,
+					threshold = ?self.threshold(params),
 					"Can conclude availability for a candidate",
 				);
 				break
@@ -505,6 +580,7 @@ impl RequestChunksFromValidators {
 					// we wouldn't have reached this stage.
 					let chunk_indices: Vec<_> = chunks.iter().map(|c| c.index).collect();
 					self.shuffling.retain(|i| !chunk_indices.contains(i));
+					self.non_systematic.retain(|i| !chunk_indices.contains(i));
 
 					for chunk in chunks {
 						self.received_chunks.insert(chunk.index, chunk);
@@ -523,6 +599,19 @@ impl RequestChunksFromValidators {
 		let _recovery_timer = metrics.time_full_recovery();
 
 		loop {
+			if self.is_unavailable(&params) && self.systematic_threshold.is_some() {
+				gum::debug!(
+					target: LOG_TARGET,
+					candidate_hash = ?params.candidate_hash,
+					received = %self.received_count(),
+					requesting = %self.requesting_chunks.len(),
+					"Systematic chunks are not available, requesting the others",
+				);
+
+				metrics.on_systematic_recovery_fallback();
+				self.fall_back_to_all_chunks();
+			}
+
 			if self.is_unavailable(&params) {
 				gum::debug!(
 					target: LOG_TARGET,
@@ -546,13 +635,26 @@ impl RequestChunksFromValidators {
 			// If received_chunks has more than threshold entries, attempt to recover the data.
 			// If that fails, or a re-encoding of it doesn't match the expected erasure root,
 			// return Err(RecoveryError::Invalid)
-			if self.received_chunks.len() >= params.threshold {
+			if self.received_count() >= self.threshold(params) {
 				let recovery_duration = metrics.time_erasure_recovery();
 
-				return match polkadot_erasure_coding::reconstruct_v1(
-					params.validators.len(),
-					self.received_chunks.values().map(|c| (&c.chunk[..], c.index.0 as usize)),
-				) {
+				let recovered = if self.systematic_threshold.is_some() {
+					metrics.on_systematic_recovery();
+					polkadot_erasure_coding::reconstruct_from_systematic_chunks_v1(
+						params.validators.len(),
+						self.received_chunks.values().map(|c| (&c.chunk[..], c.index.0 as usize)),
+					)
+				} else {
+					// The recovery ends either way, so the chunks are moved into the decoder.
+					reconstruct_streaming(
+						params.validators.len(),
+						std::mem::take(&mut self.received_chunks)
+							.into_values()
+							.map(|c| (c.chunk, c.index.0 as usize)),
+					)
+				};
+
+				return match recovered {
 					Ok(data) => {
 						if reconstructed_data_matches_root(
 							params.validators.len(),
@@ -629,7 +731,7 @@ fn reconstructed_data_matches_root(
 	expected_root: &Hash,
 	data: &AvailableData,
 ) -> bool {
-	let chunks = match obtain_chunks_v1(n_validators, data) {
+	let chunks = match obtain_chunks_streaming(n_validators, data) {
 		Ok(chunks) => chunks,
 		Err(e) => {
 			gum::debug!(
@@ -685,9 +787,9 @@ where
 						Ok(data) => break Ok(data),
 						Err(RecoveryError::Invalid) => break Err(RecoveryError::Invalid),
 						Err(RecoveryError::Unavailable) =>
-							self.source = Source::RequestChunks(RequestChunksFromValidators::new(
-								self.params.validators.len() as _,
-							)),
+							self.source = Source::RequestChunks(
+								RequestChunksFromValidators::for_params(&self.params),
+							),
 					}
 				},
 				Source::RequestChunks(ref mut from_all) =>
@@ -839,15 +941,22 @@ async fn launch_recovery_task<Context>(
 	session_info: SessionInfo,
 	receipt: CandidateReceipt,
 	backing_group: Option<GroupIndex>,
+	systematic_chunks: bool,
 	response_sender: oneshot::Sender<Result<AvailableData, RecoveryError>>,
 	metrics: &Metrics,
 ) -> error::Result<()> {
 	let candidate_hash = receipt.hash();
+	let n_validators = session_info.validators.len();
 
 	let params = RecoveryParams {
 		validator_authority_keys: session_info.discovery_keys.clone(),
 		validators: session_info.validators.clone(),
-		threshold: recovery_threshold(session_info.validators.len())?,
+		threshold: recovery_threshold(n_validators)?,
+		systematic_threshold: if systematic_chunks {
+			Some(systematic_recovery_threshold(n_validators)?)
+		} else {
+			None
+		},
 		candidate_hash,
 		erasure_root: receipt.descriptor.erasure_root,
 		metrics: metrics.clone(),
@@ -856,9 +965,7 @@ async fn launch_recovery_task<Context>(
 	let phase = backing_group
 		.and_then(|g| session_info.validator_groups.get(g.0 as usize))
 		.map(|group| Source::RequestFromBackers(RequestFromBackers::new(group.clone())))
-		.unwrap_or_else(|| {
-			Source::RequestChunks(RequestChunksFromValidators::new(params.validators.len() as _))
-		});
+		.unwrap_or_else(|| Source::RequestChunks(RequestChunksFromValidators::for_params(&params)));
 
 	let recovery_task = RecoveryTask { sender: ctx.sender().clone(), params, source: phase };
 
@@ -889,6 +996,7 @@ async fn handle_recover<Context>(
 	receipt: CandidateReceipt,
 	session_index: SessionIndex,
 	backing_group: Option<GroupIndex>,
+	systematic_chunks: bool,
 	response_sender: oneshot::Sender<Result<AvailableData, RecoveryError>>,
 	metrics: &Metrics,
 ) -> error::Result<()> {
@@ -932,6 +1040,7 @@ async fn handle_recover<Context>(
 				session_info,
 				receipt,
 				backing_group,
+				systematic_chunks,
 				response_sender,
 				metrics,
 			)
@@ -967,7 +1076,7 @@ impl AvailabilityRecoverySubsystem {
 		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
 		metrics: Metrics,
 	) -> Self {
-		Self { fast_path: true, req_receiver, metrics }
+		Self { fast_path: true, systematic_chunks: false, req_receiver, metrics }
 	}
 
 	/// Create a new instance of `AvailabilityRecoverySubsystem` which requests only chunks
@@ -975,12 +1084,22 @@ impl AvailabilityRecoverySubsystem {
 		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
 		metrics: Metrics,
 	) -> Self {
-		Self { fast_path: false, req_receiver, metrics }
+		Self { fast_path: false, systematic_chunks: false, req_receiver, metrics }
+	}
+
+	/// Create a new instance of `AvailabilityRecoverySubsystem` which requests only chunks,
+	/// starting with the systematic ones. These are enough to recover the data without the costly
+	/// decoding, so other chunks are only requested if some of the systematic ones are missing.
+	pub fn with_systematic_chunks(
+		req_receiver: IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
+		metrics: Metrics,
+	) -> Self {
+		Self { fast_path: false, systematic_chunks: true, req_receiver, metrics }
 	}
 
 	async fn run<Context>(self, mut ctx: Context) -> SubsystemResult<()> {
 		let mut state = State::default();
-		let Self { fast_path, mut req_receiver, metrics } = self;
+		let Self { fast_path, systematic_chunks, mut req_receiver, metrics } = self;
 
 		loop {
 			let recv_req = req_receiver.recv(|| vec![COST_INVALID_REQUEST]).fuse();
@@ -1008,6 +1127,7 @@ impl AvailabilityRecoverySubsystem {
 										receipt,
 										session_index,
 										maybe_backing_group.filter(|_| fast_path),
+										systematic_chunks,
 										response_sender,
 										&metrics,
 									).await {
@@ -58,6 +58,13 @@ struct MetricsInner {
 	/// Note: Those are only recoveries which could not get served locally already - so in other
 	/// words: Only real recoveries.
 	full_recoveries_started: Counter<U64>,
+
+	/// Number of recoveries from the systematic chunks.
+	///
+	/// Split by result:
+	/// - `attempted` ... all systematic chunks were fetched and recovery attempted.
+	/// - `fallback` ... some systematic chunk was missing, so the others were requested.
+	systematic_recoveries: CounterVec<U64>,
 }
 
 impl Metrics {
@@ -150,6 +157,20 @@ impl Metrics {
 			metrics.full_recoveries_started.inc()
 		}
 	}
+
+	/// All systematic chunks were fetched, recovering the data from them.
+	pub fn on_systematic_recovery(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.systematic_recoveries.with_label_values(&["attempted"]).inc()
+		}
+	}
+
+	/// A systematic chunk could not be fetched, falling back to the other chunks.
+	pub fn on_systematic_recovery_fallback(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.systematic_recoveries.with_label_values(&["fallback"]).inc()
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -210,6 +231,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			systematic_recoveries: prometheus::register(
+				CounterVec::new(
+					Opts::new(
+						"polkadot_parachain_availability_recovery_systematic_recoveries",
+						"Total number of recoveries which requested the systematic chunks first.",
+					),
+					&["result"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
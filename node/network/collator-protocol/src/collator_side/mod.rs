@@ -684,6 +684,10 @@ async fn process_msg<Context>(
 				);
 			}
 		},
+		CollatorReputations(_, tx) => {
+			// Collators don't keep track of other collators.
+			let _ = tx.send(Vec::new());
+		},
 		_ => {},
 	}
 
@@ -21,7 +21,7 @@
 #![deny(unused_crate_dependencies)]
 #![recursion_limit = "256"]
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use futures::{FutureExt, TryFutureExt};
 
@@ -36,12 +36,17 @@ use polkadot_primitives::v2::CollatorPair;
 use polkadot_node_subsystem::{
 	errors::SubsystemError, messages::NetworkBridgeTxMessage, overseer, SpawnedSubsystem,
 };
+use polkadot_node_subsystem_util::database::Database;
 
 mod error;
 
 mod collator_side;
 mod validator_side;
 
+pub use validator_side::{
+	inspect_collator_reputations, CollatorList, CollatorLists, ReputationConfig,
+};
+
 const LOG_TARGET: &'static str = "parachain::collator-protocol";
 
 /// A collator eviction policy - how fast to evict collators which are inactive.
@@ -72,6 +77,10 @@ pub enum ProtocolSide {
 		eviction_policy: CollatorEvictionPolicy,
 		/// Prometheus metrics for validators.
 		metrics: validator_side::Metrics,
+		/// The parachains DB, persisting the reputation of collators.
+		db: Arc<dyn Database>,
+		/// Where to persist the reputation of collators and the collators allowed by the operator.
+		reputation_config: ReputationConfig,
 	},
 	/// Collators operate on a parachain.
 	Collator(
@@ -99,8 +108,15 @@ impl CollatorProtocolSubsystem {
 
 	async fn run<Context>(self, ctx: Context) -> std::result::Result<(), error::FatalError> {
 		match self.protocol_side {
-			ProtocolSide::Validator { keystore, eviction_policy, metrics } =>
-				validator_side::run(ctx, keystore, eviction_policy, metrics).await,
+			ProtocolSide::Validator {
+				keystore,
+				eviction_policy,
+				metrics,
+				db,
+				reputation_config,
+			} =>
+				validator_side::run(ctx, keystore, eviction_policy, metrics, db, reputation_config)
+					.await,
 			ProtocolSide::Collator(local_peer_id, collator_pair, req_receiver, metrics) =>
 				collator_side::run(ctx, local_peer_id, collator_pair, req_receiver, metrics).await,
 		}
@@ -47,18 +47,26 @@ use polkadot_node_primitives::{PoV, SignedFullStatement};
 use polkadot_node_subsystem::{
 	jaeger,
 	messages::{
-		CandidateBackingMessage, CollatorProtocolMessage, IfDisconnected, NetworkBridgeEvent,
-		NetworkBridgeTxMessage, RuntimeApiMessage,
+		CandidateBackingMessage, CollatorListing, CollatorProtocolMessage, IfDisconnected,
+		NetworkBridgeEvent, NetworkBridgeTxMessage, RuntimeApiMessage,
 	},
 	overseer, FromOrchestra, OverseerSignal, PerLeafSpan, SubsystemSender,
 };
-use polkadot_node_subsystem_util::metrics::{self, prometheus};
+use polkadot_node_subsystem_util::{
+	database::Database,
+	metrics::{self, prometheus},
+};
 use polkadot_primitives::v2::{CandidateReceipt, CollatorId, Hash, Id as ParaId};
 
 use crate::error::Result;
 
 use super::{modify_reputation, LOG_TARGET};
 
+mod reputation;
+
+use reputation::ReputationTable;
+pub use reputation::{inspect_collator_reputations, CollatorList, CollatorLists, ReputationConfig};
+
 #[cfg(test)]
 mod tests;
 
@@ -71,6 +79,7 @@ const COST_INVALID_SIGNATURE: Rep = Rep::Malicious("Invalid network message sign
 const COST_REPORT_BAD: Rep = Rep::Malicious("A collator was reported by another subsystem");
 const COST_WRONG_PARA: Rep = Rep::Malicious("A collator provided a collation for the wrong para");
 const COST_UNNEEDED_COLLATOR: Rep = Rep::CostMinor("An unneeded collator connected");
+const COST_DENIED_COLLATOR: Rep = Rep::CostMinor("A collator denied by the operator connected");
 const BENEFIT_NOTIFY_GOOD: Rep =
 	Rep::BenefitMinor("A collator was noted good by another subsystem");
 
@@ -546,9 +555,13 @@ impl CollationsPerRelayParent {
 	///
 	/// Returns `Some(_)` if there is any collation to fetch, the `status` is not `Seconded` and
 	/// the passed in `finished_one` is the currently `waiting_collation`.
+	///
+	/// Collations of collators with a better reputation are fetched first, the most recently
+	/// advertised one if reputations are equal.
 	pub fn get_next_collation_to_fetch(
 		&mut self,
 		finished_one: Option<&CollatorId>,
+		reputation: &ReputationTable,
 	) -> Option<(PendingCollation, CollatorId)> {
 		// If finished one does not match waiting_collation, then we already dequeued another fetch
 		// to replace it.
@@ -567,7 +580,13 @@ impl CollationsPerRelayParent {
 			// We don't need to fetch any other collation when we already have seconded one.
 			CollationStatus::Seconded => None,
 			CollationStatus::Waiting => {
-				let next = self.unfetched_collations.pop();
+				let next = self
+					.unfetched_collations
+					.iter()
+					.enumerate()
+					.max_by_key(|(_, (collation, id))| reputation.score(collation.para_id, id))
+					.map(|(index, _)| index)
+					.map(|index| self.unfetched_collations.remove(index));
 				self.waiting_collation = next.as_ref().map(|(_, collator_id)| collator_id.clone());
 				next
 			},
@@ -617,6 +636,12 @@ struct State {
 
 	/// Keep track of all pending candidate collations
 	pending_candidates: HashMap<Hash, CollationEvent>,
+
+	/// The persistent scores of collators per para.
+	reputation: ReputationTable,
+
+	/// The collators allowed and denied by the operator.
+	collator_lists: CollatorLists,
 }
 
 // O(n) search for collator ID by iterating through the peers map. This should be fast enough
@@ -680,13 +705,41 @@ async fn fetch_collation(
 	state.collation_fetches.push(rx.map(|r| ((id, pc), r)).boxed());
 }
 
+/// Modify the reputation of a peer, as well as the score of the collator it declared to be for
+/// its para.
+async fn modify_collator_reputation(
+	sender: &mut impl overseer::CollatorProtocolSenderTrait,
+	peer_data: &HashMap<PeerId, PeerData>,
+	reputation: &mut ReputationTable,
+	peer_id: PeerId,
+	rep: Rep,
+) {
+	if let Some(data) = peer_data.get(&peer_id) {
+		if let (Some(id), Some(para_id)) = (data.collator_id(), data.collating_para()) {
+			reputation.modify(para_id, id, rep);
+		}
+	}
+	modify_reputation(sender, peer_id, rep).await;
+}
+
 /// Report a collator for some malicious actions.
+///
+/// The score of the collator is lowered for the given para, or the para it declared to be for.
 async fn report_collator(
 	sender: &mut impl overseer::CollatorProtocolSenderTrait,
 	peer_data: &HashMap<PeerId, PeerData>,
+	reputation: &mut ReputationTable,
 	id: CollatorId,
+	para_id: Option<ParaId>,
 ) {
-	if let Some(peer_id) = collator_peer_id(peer_data, &id) {
+	let peer_id = collator_peer_id(peer_data, &id);
+	// The collator might have disconnected already, its score is kept regardless.
+	let para_id = para_id
+		.or_else(|| peer_id.as_ref().and_then(|peer_id| peer_data.get(peer_id)?.collating_para()));
+	if let Some(para_id) = para_id {
+		reputation.modify(para_id, &id, COST_REPORT_BAD);
+	}
+	if let Some(peer_id) = peer_id {
 		modify_reputation(sender, peer_id, COST_REPORT_BAD).await;
 	}
 }
@@ -695,8 +748,11 @@ async fn report_collator(
 async fn note_good_collation(
 	sender: &mut impl overseer::CollatorProtocolSenderTrait,
 	peer_data: &HashMap<PeerId, PeerData>,
+	reputation: &mut ReputationTable,
 	id: CollatorId,
+	para_id: ParaId,
 ) {
+	reputation.modify(para_id, &id, BENEFIT_NOTIFY_GOOD);
 	if let Some(peer_id) = collator_peer_id(peer_data, &id) {
 		modify_reputation(sender, peer_id, BENEFIT_NOTIFY_GOOD).await;
 	}
@@ -860,6 +916,20 @@ async fn process_incoming_peer_message<Context>(
 				return
 			}
 
+			if state.collator_lists.listing(para_id, &collator_id) == CollatorListing::Denied {
+				gum::debug!(
+					target: LOG_TARGET,
+					peer_id = ?origin,
+					?collator_id,
+					?para_id,
+					"Declared as collator denied by the operator",
+				);
+
+				modify_reputation(ctx.sender(), origin.clone(), COST_DENIED_COLLATOR).await;
+				disconnect_peer(ctx.sender(), origin).await;
+				return
+			}
+
 			if state.active_paras.is_current(&para_id) {
 				gum::debug!(
 					target: LOG_TARGET,
@@ -1113,7 +1183,7 @@ async fn process_msg<Context>(
 			);
 		},
 		ReportCollator(id) => {
-			report_collator(ctx.sender(), &state.peer_data, id).await;
+			report_collator(ctx.sender(), &state.peer_data, &mut state.reputation, id, None).await;
 		},
 		NetworkBridgeUpdate(event) => {
 			if let Err(e) = handle_network_msg(ctx, state, keystore, event).await {
@@ -1127,8 +1197,15 @@ async fn process_msg<Context>(
 		Seconded(parent, stmt) => {
 			if let Some(collation_event) = state.pending_candidates.remove(&parent) {
 				let (collator_id, pending_collation) = collation_event;
-				let PendingCollation { relay_parent, peer_id, .. } = pending_collation;
-				note_good_collation(ctx.sender(), &state.peer_data, collator_id).await;
+				let PendingCollation { relay_parent, para_id, peer_id, .. } = pending_collation;
+				note_good_collation(
+					ctx.sender(),
+					&state.peer_data,
+					&mut state.reputation,
+					collator_id,
+					para_id,
+				)
+				.await;
 				notify_collation_seconded(ctx.sender(), peer_id, relay_parent, stmt).await;
 
 				if let Some(collations) = state.collations_per_relay_parent.get_mut(&parent) {
@@ -1160,10 +1237,20 @@ async fn process_msg<Context>(
 				Entry::Vacant(_) => return,
 			};
 
-			report_collator(ctx.sender(), &state.peer_data, id.clone()).await;
+			report_collator(
+				ctx.sender(),
+				&state.peer_data,
+				&mut state.reputation,
+				id.clone(),
+				Some(candidate_receipt.descriptor.para_id),
+			)
+			.await;
 
 			dequeue_next_collation_and_fetch(ctx, state, parent, id).await;
 		},
+		CollatorReputations(para_id, tx) => {
+			let _ = tx.send(state.reputation.reputations(&state.collator_lists, para_id));
+		},
 	}
 }
 
@@ -1193,8 +1280,12 @@ pub(crate) async fn run<Context>(
 	keystore: SyncCryptoStorePtr,
 	eviction_policy: crate::CollatorEvictionPolicy,
 	metrics: Metrics,
+	db: Arc<dyn Database>,
+	reputation_config: ReputationConfig,
 ) -> std::result::Result<(), crate::error::FatalError> {
-	let mut state = State { metrics, ..Default::default() };
+	let ReputationConfig { col_data, collator_lists } = reputation_config;
+	let reputation = ReputationTable::load(&*db, col_data);
+	let mut state = State { metrics, reputation, collator_lists, ..Default::default() };
 
 	let next_inactivity_stream = infinite_stream(ACTIVITY_POLL);
 	futures::pin_mut!(next_inactivity_stream);
@@ -1243,10 +1334,21 @@ pub(crate) async fn run<Context>(
 				).await;
 
 				for (peer_id, rep) in reputation_changes {
-					modify_reputation(ctx.sender(), peer_id, rep).await;
+					modify_collator_reputation(
+						ctx.sender(),
+						&state.peer_data,
+						&mut state.reputation,
+						peer_id,
+						rep,
+					)
+					.await;
 				}
 			},
 		}
+
+		if let Err(error) = state.reputation.write(&*db, col_data) {
+			gum::warn!(target: LOG_TARGET, ?error, "Failed to persist collator scores");
+		}
 	}
 
 	Ok(())
@@ -1288,7 +1390,7 @@ async fn dequeue_next_collation_and_fetch<Context>(
 	if let Some((next, id)) = state
 		.collations_per_relay_parent
 		.get_mut(&relay_parent)
-		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch)))
+		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch), &state.reputation))
 	{
 		gum::debug!(
 			target: LOG_TARGET,
@@ -0,0 +1,273 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistent collator reputation and the operator's allow and deny lists.
+//!
+//! The peer reputation of the network is forgotten on restart and shared by all paras. In
+//! addition, the score of a collator is kept per para in the parachains DB. It decides which of
+//! the advertisements for a relay parent is fetched next, so a collator spamming bad collations
+//! for one para can't push out the honest collators of another.
+
+use parity_scale_codec::{Decode, Encode};
+use serde::Deserialize;
+use std::{
+	collections::{HashMap, HashSet},
+	fs, io,
+	path::Path,
+};
+
+use polkadot_node_network_protocol::UnifiedReputationChange as Rep;
+use polkadot_node_subsystem::messages::{CollatorListing, CollatorReputation};
+use polkadot_node_subsystem_util::database::{DBTransaction, Database, DecodedEntry};
+use polkadot_primitives::v2::{CollatorId, Id as ParaId};
+
+use crate::LOG_TARGET;
+
+/// Prefix of the keys of all scores, followed by the para id and the collator id.
+const SCORE_PREFIX: &[u8] = b"CollatorScore";
+
+/// Scores are bounded, so misbehaving collators can earn back their reputation and good
+/// collators can't build up a lead which is never caught up with.
+const MAX_SCORE: Score = 1_000;
+const MIN_SCORE: Score = -1_000;
+
+/// Number of collators whose score is kept per para.
+///
+/// Once reached, the collator with the score closest to zero is forgotten.
+const MAX_COLLATORS_PER_PARA: usize = 1_000;
+
+/// The score of a collator for a para, collators without one have a score of `0`.
+pub type Score = i32;
+
+/// Configuration of the collator reputation on the validator side.
+#[derive(Debug, Clone)]
+pub struct ReputationConfig {
+	/// The column of the parachains DB scores are persisted in.
+	pub col_data: u32,
+	/// The collators allowed and denied by the operator.
+	pub collator_lists: CollatorLists,
+}
+
+/// Collators allowed and denied to collate for a para.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CollatorList {
+	/// If not empty, only these collators are accepted for the para.
+	#[serde(default)]
+	pub allow: HashSet<CollatorId>,
+	/// Collators never accepted for the para.
+	#[serde(default)]
+	pub deny: HashSet<CollatorId>,
+}
+
+/// The collators allowed and denied by the operator, per para.
+#[derive(Debug, Clone, Default)]
+pub struct CollatorLists(HashMap<ParaId, CollatorList>);
+
+/// An entry of the collator lists file.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CollatorListEntry {
+	para_id: ParaId,
+	#[serde(flatten)]
+	list: CollatorList,
+}
+
+impl CollatorLists {
+	/// Create the lists from the lists of all paras.
+	pub fn new(lists: HashMap<ParaId, CollatorList>) -> Self {
+		CollatorLists(lists)
+	}
+
+	/// Load the lists from a JSON file.
+	///
+	/// The file holds an array of objects with the `paraId` and optionally the `allow` and `deny`
+	/// lists of SS58 encoded collator ids.
+	pub fn from_file(path: &Path) -> io::Result<Self> {
+		let entries: Vec<CollatorListEntry> = serde_json::from_slice(&fs::read(path)?)
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+		let mut lists = HashMap::new();
+		for CollatorListEntry { para_id, list } in entries {
+			if lists.insert(para_id, list).is_some() {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					format!("Para {} is listed more than once", para_id),
+				))
+			}
+		}
+		Ok(CollatorLists(lists))
+	}
+
+	/// How the operator treats the collator for the para.
+	pub(crate) fn listing(&self, para_id: ParaId, collator_id: &CollatorId) -> CollatorListing {
+		match self.0.get(&para_id) {
+			None => CollatorListing::Unlisted,
+			Some(list) if list.deny.contains(collator_id) => CollatorListing::Denied,
+			Some(list) if list.allow.contains(collator_id) => CollatorListing::Allowed,
+			Some(list) if list.allow.is_empty() => CollatorListing::Unlisted,
+			Some(_) => CollatorListing::Denied,
+		}
+	}
+}
+
+/// The scores of collators per para.
+///
+/// Changes are kept in memory until written to the database with `write`.
+#[derive(Default)]
+pub(crate) struct ReputationTable {
+	scores: HashMap<ParaId, HashMap<CollatorId, Score>>,
+	// Scores changed since the last write.
+	dirty: HashSet<(ParaId, CollatorId)>,
+}
+
+impl ReputationTable {
+	/// Load all scores from the database.
+	pub(crate) fn load(db: &dyn Database, col_data: u32) -> Self {
+		let mut table = ReputationTable::default();
+		for (key, value) in db.iter_with_prefix(col_data, SCORE_PREFIX) {
+			match decode_score(&key, &value) {
+				Some((para_id, collator_id, score)) => {
+					table.scores.entry(para_id).or_default().insert(collator_id, score);
+				},
+				None => gum::warn!(
+					target: LOG_TARGET,
+					?key,
+					"Ignoring collator score which could not be decoded",
+				),
+			}
+		}
+		table
+	}
+
+	/// The score of the collator for the para.
+	pub(crate) fn score(&self, para_id: ParaId, collator_id: &CollatorId) -> Score {
+		self.scores
+			.get(&para_id)
+			.and_then(|scores| scores.get(collator_id))
+			.copied()
+			.unwrap_or(0)
+	}
+
+	/// Apply a reputation change of the collator to its score for the para.
+	pub(crate) fn modify(&mut self, para_id: ParaId, collator_id: &CollatorId, rep: Rep) {
+		let scores = self.scores.entry(para_id).or_default();
+		if !scores.contains_key(collator_id) && scores.len() >= MAX_COLLATORS_PER_PARA {
+			let least_significant = scores
+				.iter()
+				.min_by_key(|(_, score)| score.abs())
+				.map(|(collator_id, _)| collator_id.clone());
+			if let Some(evicted) = least_significant {
+				scores.remove(&evicted);
+				self.dirty.insert((para_id, evicted));
+			}
+		}
+
+		let score = scores.entry(collator_id.clone()).or_default();
+		*score = score.saturating_add(score_change(rep)).clamp(MIN_SCORE, MAX_SCORE);
+		if *score == 0 {
+			scores.remove(collator_id);
+		}
+		self.dirty.insert((para_id, collator_id.clone()));
+	}
+
+	/// Write all changed scores to the database.
+	pub(crate) fn write(&mut self, db: &dyn Database, col_data: u32) -> io::Result<()> {
+		if self.dirty.is_empty() {
+			return Ok(())
+		}
+
+		let mut tx = DBTransaction::new();
+		for (para_id, collator_id) in self.dirty.iter() {
+			let key = score_key(*para_id, collator_id);
+			match self.scores.get(para_id).and_then(|scores| scores.get(collator_id)) {
+				Some(score) => tx.put_vec(col_data, &key, score.encode()),
+				None => tx.delete(col_data, &key),
+			}
+		}
+		db.write(tx)?;
+		self.dirty.clear();
+		Ok(())
+	}
+
+	/// The reputations of all collators with a score or on the operator's lists, either of all
+	/// paras or only the given one.
+	pub(crate) fn reputations(
+		&self,
+		collator_lists: &CollatorLists,
+		para_id: Option<ParaId>,
+	) -> Vec<CollatorReputation> {
+		let scored = self
+			.scores
+			.iter()
+			.flat_map(|(para_id, scores)| scores.keys().map(move |id| (*para_id, id)));
+		let listed = collator_lists.0.iter().flat_map(|(para_id, list)| {
+			list.allow.iter().chain(&list.deny).map(move |id| (*para_id, id))
+		});
+
+		let collators = scored
+			.chain(listed)
+			.filter(|(p, _)| para_id.map_or(true, |para_id| para_id == *p))
+			.collect::<HashSet<_>>();
+
+		let mut reputations = collators
+			.into_iter()
+			.map(|(para_id, collator_id)| CollatorReputation {
+				para_id,
+				collator_id: collator_id.clone(),
+				score: self.score(para_id, collator_id),
+				listing: collator_lists.listing(para_id, collator_id),
+			})
+			.collect::<Vec<_>>();
+		reputations.sort_by(|a, b| a.para_id.cmp(&b.para_id).then(b.score.cmp(&a.score)));
+		reputations
+	}
+}
+
+/// Decode all scores of the given column, for offline inspection.
+pub fn inspect_collator_reputations(db: &dyn Database, col_data: u32) -> Vec<DecodedEntry> {
+	db.iter_with_prefix(col_data, SCORE_PREFIX)
+		.map(|(key, value)| match decode_score(&key, &value) {
+			Some((para_id, collator_id, score)) =>
+				DecodedEntry::new(key, "collator-score", (para_id, collator_id, score)),
+			None => DecodedEntry::new(key, "undecodable", value),
+		})
+		.collect()
+}
+
+/// How much a reputation change on the network changes the score of a collator.
+fn score_change(rep: Rep) -> Score {
+	match rep {
+		Rep::Malicious(_) => -200,
+		Rep::CostMajor(_) | Rep::CostMajorRepeated(_) => -50,
+		Rep::CostMinor(_) | Rep::CostMinorRepeated(_) => -10,
+		Rep::BenefitMajor(_) | Rep::BenefitMajorFirst(_) => 30,
+		Rep::BenefitMinor(_) | Rep::BenefitMinorFirst(_) => 10,
+	}
+}
+
+fn score_key(para_id: ParaId, collator_id: &CollatorId) -> Vec<u8> {
+	let mut key = SCORE_PREFIX.to_vec();
+	(para_id, collator_id).encode_to(&mut key);
+	key
+}
+
+fn decode_score(key: &[u8], value: &[u8]) -> Option<(ParaId, CollatorId, Score)> {
+	let (para_id, collator_id) =
+		<(ParaId, CollatorId)>::decode(&mut key.strip_prefix(SCORE_PREFIX)?).ok()?;
+	let score = Score::decode(&mut &value[..]).ok()?;
+	Some((para_id, collator_id, score))
+}
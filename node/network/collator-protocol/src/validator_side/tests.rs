@@ -28,9 +28,11 @@ use polkadot_node_network_protocol::{
 	ObservedRole,
 };
 use polkadot_node_primitives::BlockData;
-use polkadot_node_subsystem::messages::{AllMessages, RuntimeApiMessage, RuntimeApiRequest};
+use polkadot_node_subsystem::messages::{
+	AllMessages, CollatorReputation, RuntimeApiMessage, RuntimeApiRequest,
+};
 use polkadot_node_subsystem_test_helpers as test_helpers;
-use polkadot_node_subsystem_util::TimeoutExt;
+use polkadot_node_subsystem_util::{database::kvdb_impl::DbAdapter, TimeoutExt};
 use polkadot_primitives::v2::{
 	CollatorPair, CoreState, GroupIndex, GroupRotationInfo, OccupiedCore, ScheduledCore,
 	ValidatorId, ValidatorIndex,
@@ -42,6 +44,8 @@ use polkadot_primitives_test_helpers::{
 const ACTIVITY_TIMEOUT: Duration = Duration::from_millis(500);
 const DECLARE_TIMEOUT: Duration = Duration::from_millis(25);
 
+const COL_DATA: u32 = 0;
+
 #[derive(Clone)]
 struct TestState {
 	chain_ids: Vec<ParaId>,
@@ -118,7 +122,19 @@ struct TestHarness {
 	virtual_overseer: VirtualOverseer,
 }
 
+fn test_db() -> Arc<dyn Database> {
+	Arc::new(DbAdapter::new(kvdb_memorydb::create(1), &[COL_DATA]))
+}
+
 fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarness) -> T) {
+	test_harness_with(test_db(), CollatorLists::default(), test)
+}
+
+fn test_harness_with<T: Future<Output = VirtualOverseer>>(
+	db: Arc<dyn Database>,
+	collator_lists: CollatorLists,
+	test: impl FnOnce(TestHarness) -> T,
+) {
 	let _ = env_logger::builder()
 		.is_test(true)
 		.filter(Some("polkadot_collator_protocol"), log::LevelFilter::Trace)
@@ -145,6 +161,8 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(test: impl FnOnce(TestHarne
 			undeclared: DECLARE_TIMEOUT,
 		},
 		Metrics::default(),
+		db,
+		ReputationConfig { col_data: COL_DATA, collator_lists },
 	);
 
 	let test_fut = test(TestHarness { virtual_overseer });
@@ -1067,3 +1085,265 @@ fn view_change_clears_old_collators() {
 		virtual_overseer
 	})
 }
+
+/// Assert that a fetch collation request was sent, returning the peer it was sent to.
+async fn assert_fetch_collation_request_to(
+	virtual_overseer: &mut VirtualOverseer,
+	relay_parent: Hash,
+) -> (PeerId, ResponseSender) {
+	assert_matches!(
+		overseer_recv(virtual_overseer).await,
+		AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendRequests(reqs, IfDisconnected::ImmediateError)
+	) => {
+		let req = reqs.into_iter().next()
+			.expect("There should be exactly one request");
+		match req {
+			Requests::CollationFetchingV1(req) => {
+				assert_eq!(req.payload.relay_parent, relay_parent);
+				assert_matches!(req.peer, Recipient::Peer(peer) => (peer, req.pending_response))
+			}
+			_ => panic!("Unexpected request"),
+		}
+	})
+}
+
+async fn query_reputations(
+	virtual_overseer: &mut VirtualOverseer,
+	para_id: Option<ParaId>,
+) -> Vec<CollatorReputation> {
+	let (tx, rx) = oneshot::channel();
+	overseer_send(virtual_overseer, CollatorProtocolMessage::CollatorReputations(para_id, tx))
+		.await;
+	rx.timeout(TIMEOUT)
+		.await
+		.expect("Reputations are sent in time")
+		.expect("Reputations are sent")
+}
+
+#[test]
+fn collator_scores_persist_across_restarts() {
+	let test_state = TestState::default();
+	let db = test_db();
+
+	test_harness_with(db.clone(), CollatorLists::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_b.clone(),
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::ReportCollator(test_state.collators[0].public()),
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(peer, rep)) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_REPORT_BAD);
+			}
+		);
+
+		virtual_overseer
+	});
+
+	let test_state = TestState::default();
+	test_harness_with(db, CollatorLists::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		let reputations = query_reputations(&mut virtual_overseer, None).await;
+		assert_matches!(
+			&reputations[..],
+			[CollatorReputation { para_id, collator_id, score, listing }] => {
+				assert_eq!(*para_id, test_state.chain_ids[0]);
+				assert_eq!(*collator_id, test_state.collators[0].public());
+				assert!(*score < 0);
+				assert_eq!(*listing, CollatorListing::Unlisted);
+			}
+		);
+		assert!(query_reputations(&mut virtual_overseer, Some(test_state.chain_ids[1]))
+			.await
+			.is_empty());
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn collations_of_reputable_collators_are_fetched_first() {
+	let test_state = TestState::default();
+	let db = test_db();
+
+	// Make the collator advertising second the most reputable one.
+	let mut reputation = ReputationTable::default();
+	reputation.modify(
+		test_state.chain_ids[0],
+		&test_state.collators[1].public(),
+		BENEFIT_NOTIFY_GOOD,
+	);
+	reputation.write(&*db, COL_DATA).unwrap();
+
+	test_harness_with(db, CollatorLists::default(), |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peers = [PeerId::random(), PeerId::random(), PeerId::random()];
+		for (peer, collator) in peers.iter().zip(test_state.collators.iter()) {
+			connect_and_declare_collator(
+				&mut virtual_overseer,
+				peer.clone(),
+				collator.clone(),
+				test_state.chain_ids[0],
+			)
+			.await;
+		}
+		for peer in peers.iter() {
+			advertise_collation(&mut virtual_overseer, peer.clone(), test_state.relay_parent).await;
+		}
+
+		// The first advertisement is fetched right away.
+		let (peer, response_channel) =
+			assert_fetch_collation_request_to(&mut virtual_overseer, test_state.relay_parent).await;
+		assert_eq!(peer, peers[0]);
+
+		// Once it fails, the collation of the reputable collator is fetched, not the most
+		// recently advertised one.
+		drop(response_channel);
+		let (peer, _response_channel) =
+			assert_fetch_collation_request_to(&mut virtual_overseer, test_state.relay_parent).await;
+		assert_eq!(peer, peers[1]);
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn collators_not_allowed_by_the_operator_are_disconnected() {
+	let test_state = TestState::default();
+	let allowed = test_state.collators[1].public();
+	let collator_lists = CollatorLists::new(
+		vec![(
+			test_state.chain_ids[0],
+			CollatorList {
+				allow: vec![allowed.clone()].into_iter().collect(),
+				deny: HashSet::new(),
+			},
+		)]
+		.into_iter()
+		.collect(),
+	);
+
+	test_harness_with(test_db(), collator_lists, |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		let peer_c = PeerId::random();
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_b.clone(),
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(peer, rep)) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_DENIED_COLLATOR);
+			}
+		);
+		assert_collator_disconnect(&mut virtual_overseer, peer_b).await;
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_c.clone(),
+			test_state.collators[1].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+		advertise_collation(&mut virtual_overseer, peer_c.clone(), test_state.relay_parent).await;
+		let (peer, _response_channel) =
+			assert_fetch_collation_request_to(&mut virtual_overseer, test_state.relay_parent).await;
+		assert_eq!(peer, peer_c);
+
+		assert_eq!(
+			query_reputations(&mut virtual_overseer, Some(test_state.chain_ids[0])).await,
+			vec![CollatorReputation {
+				para_id: test_state.chain_ids[0],
+				collator_id: allowed,
+				score: 0,
+				listing: CollatorListing::Allowed,
+			}],
+		);
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn collator_lists_are_loaded_from_file() {
+	let chain_a = ParaId::from(1);
+	let chain_b = ParaId::from(2);
+	let allowed = Sr25519Keyring::Alice.public();
+	let denied = Sr25519Keyring::Bob.public();
+
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("collators.json");
+	std::fs::write(
+		&path,
+		format!(
+			r#"[{{ "paraId": 1, "allow": ["{}"] }}, {{ "paraId": 2, "deny": ["{}"] }}]"#,
+			allowed, denied,
+		),
+	)
+	.unwrap();
+
+	let lists = CollatorLists::from_file(&path).unwrap();
+	let (allowed, denied) = (CollatorId::from(allowed), CollatorId::from(denied));
+	assert_eq!(lists.listing(chain_a, &allowed), CollatorListing::Allowed);
+	assert_eq!(lists.listing(chain_a, &denied), CollatorListing::Denied);
+	assert_eq!(lists.listing(chain_b, &allowed), CollatorListing::Unlisted);
+	assert_eq!(lists.listing(chain_b, &denied), CollatorListing::Denied);
+	assert_eq!(lists.listing(ParaId::from(3), &denied), CollatorListing::Unlisted);
+
+	std::fs::write(&path, r#"[{ "paraId": 1 }, { "paraId": 1 }]"#).unwrap();
+	assert!(CollatorLists::from_file(&path).is_err());
+}
@@ -30,7 +30,7 @@
 //! `trait IsRequest` .... A trait describing a particular request. It is used for gathering meta
 //! data, like what is the corresponding response type.
 //!
-//!  Versioned (v1 module): The actual requests and responses as sent over the network.
+//!  Versioned (v1 and v2 modules): The actual requests and responses as sent over the network.
 
 use std::{borrow::Cow, collections::HashMap, time::Duration, u64};
 
@@ -55,12 +55,18 @@ pub use outgoing::{OutgoingRequest, OutgoingResult, Recipient, Requests, Respons
 /// Actual versioned requests and responses, that are sent over the wire.
 pub mod v1;
 
+/// Second version of requests and responses, for protocols that got one.
+pub mod v2;
+
 /// A protocol per subsystem seems to make the most sense, this way we don't need any dispatching
 /// within protocols.
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, EnumIter)]
 pub enum Protocol {
 	/// Protocol for chunk fetching, used by availability distribution and availability recovery.
 	ChunkFetchingV1,
+	/// Protocol for fetching several chunks of a candidate at once, with a single proof for all
+	/// of them.
+	ChunkFetchingV2,
 	/// Protocol for fetching collations from collators.
 	CollationFetchingV1,
 	/// Protocol for fetching seconded PoVs from validators of the same group.
@@ -143,6 +149,17 @@ impl Protocol {
 				request_timeout: CHUNK_REQUEST_TIMEOUT,
 				inbound_queue: Some(tx),
 			},
+			Protocol::ChunkFetchingV2 => RequestResponseConfig {
+				name,
+				fallback_names,
+				max_request_size: 1_000,
+				// Fetching all chunks amounts to three times the PoV size, plus the proof, which
+				// at most contains every node of the trie.
+				max_response_size: POV_RESPONSE_SIZE as u64 * 4,
+				// Responses are about the size of a PoV, if the systematic chunks are requested.
+				request_timeout: POV_REQUEST_TIMEOUT_CONNECTED,
+				inbound_queue: Some(tx),
+			},
 			Protocol::CollationFetchingV1 => RequestResponseConfig {
 				name,
 				fallback_names,
@@ -212,6 +229,9 @@ impl Protocol {
 			// assuming we can service requests relatively quickly, which would need to be measured
 			// as well.
 			Protocol::ChunkFetchingV1 => 100,
+			// Requests are fewer than for single chunks, but each of them is more expensive to
+			// serve.
+			Protocol::ChunkFetchingV2 => 50,
 			// 10 seems reasonable, considering group sizes of max 10 validators.
 			Protocol::CollationFetchingV1 => 10,
 			// 10 seems reasonable, considering group sizes of max 10 validators.
@@ -249,18 +269,21 @@ impl Protocol {
 
 	/// Fallback protocol names of this protocol, as understood by substrate networking.
 	fn get_fallback_names(self) -> Vec<Cow<'static, str>> {
-		std::iter::once(self.get_legacy_name().into()).collect()
+		self.get_legacy_name().into_iter().map(Into::into).collect()
 	}
 
 	/// Legacy protocol name associated with each peer set.
-	const fn get_legacy_name(self) -> &'static str {
+	///
+	/// Protocols introduced after the switch to genesis hash based names have none.
+	const fn get_legacy_name(self) -> Option<&'static str> {
 		match self {
-			Protocol::ChunkFetchingV1 => "/polkadot/req_chunk/1",
-			Protocol::CollationFetchingV1 => "/polkadot/req_collation/1",
-			Protocol::PoVFetchingV1 => "/polkadot/req_pov/1",
-			Protocol::AvailableDataFetchingV1 => "/polkadot/req_available_data/1",
-			Protocol::StatementFetchingV1 => "/polkadot/req_statement/1",
-			Protocol::DisputeSendingV1 => "/polkadot/send_dispute/1",
+			Protocol::ChunkFetchingV1 => Some("/polkadot/req_chunk/1"),
+			Protocol::ChunkFetchingV2 => None,
+			Protocol::CollationFetchingV1 => Some("/polkadot/req_collation/1"),
+			Protocol::PoVFetchingV1 => Some("/polkadot/req_pov/1"),
+			Protocol::AvailableDataFetchingV1 => Some("/polkadot/req_available_data/1"),
+			Protocol::StatementFetchingV1 => Some("/polkadot/req_statement/1"),
+			Protocol::DisputeSendingV1 => Some("/polkadot/send_dispute/1"),
 		}
 	}
 }
@@ -311,6 +334,7 @@ impl ReqProtocolNames {
 
 		let short_name = match protocol {
 			Protocol::ChunkFetchingV1 => "/req_chunk/1",
+			Protocol::ChunkFetchingV2 => "/req_chunk/2",
 			Protocol::CollationFetchingV1 => "/req_collation/1",
 			Protocol::PoVFetchingV1 => "/req_pov/1",
 			Protocol::AvailableDataFetchingV1 => "/req_available_data/1",
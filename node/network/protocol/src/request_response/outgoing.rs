@@ -23,13 +23,15 @@ use sc_network::PeerId;
 
 use polkadot_primitives::v2::AuthorityDiscoveryId;
 
-use super::{v1, IsRequest, Protocol};
+use super::{v1, v2, IsRequest, Protocol};
 
 /// All requests that can be sent to the network bridge via `NetworkBridgeTxMessage::SendRequest`.
 #[derive(Debug)]
 pub enum Requests {
 	/// Request an availability chunk from a node.
 	ChunkFetchingV1(OutgoingRequest<v1::ChunkFetchingRequest>),
+	/// Request several availability chunks at once from a node.
+	ChunkFetchingV2(OutgoingRequest<v2::ChunkFetchingRequest>),
 	/// Fetch a collation from a collator which previously announced it.
 	CollationFetchingV1(OutgoingRequest<v1::CollationFetchingRequest>),
 	/// Fetch a PoV from a validator which previously sent out a seconded statement.
@@ -47,6 +49,7 @@ impl Requests {
 	pub fn get_protocol(&self) -> Protocol {
 		match self {
 			Self::ChunkFetchingV1(_) => Protocol::ChunkFetchingV1,
+			Self::ChunkFetchingV2(_) => Protocol::ChunkFetchingV2,
 			Self::CollationFetchingV1(_) => Protocol::CollationFetchingV1,
 			Self::PoVFetchingV1(_) => Protocol::PoVFetchingV1,
 			Self::AvailableDataFetchingV1(_) => Protocol::AvailableDataFetchingV1,
@@ -65,6 +68,7 @@ impl Requests {
 	pub fn encode_request(self) -> (Protocol, OutgoingRequest<Vec<u8>>) {
 		match self {
 			Self::ChunkFetchingV1(r) => r.encode_request(),
+			Self::ChunkFetchingV2(r) => r.encode_request(),
 			Self::CollationFetchingV1(r) => r.encode_request(),
 			Self::PoVFetchingV1(r) => r.encode_request(),
 			Self::AvailableDataFetchingV1(r) => r.encode_request(),
@@ -105,6 +109,18 @@ impl RequestError {
 			_ => false,
 		}
 	}
+
+	/// Whether the peer does not support the protocol of the request.
+	///
+	/// Used for falling back to an older version of a protocol.
+	pub fn is_unsupported_protocol(&self) -> bool {
+		matches!(
+			self,
+			Self::NetworkError(network::RequestFailure::Network(
+				network::OutboundFailure::UnsupportedProtocols,
+			))
+		)
+	}
 }
 
 /// A request to be sent to the network bridge, including a sender for sending responses/failures.
@@ -0,0 +1,86 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Requests and responses as sent over the wire for the second version of protocols.
+
+use parity_scale_codec::{Decode, Encode};
+
+use polkadot_node_primitives::MultiProof;
+use polkadot_primitives::v2::{CandidateHash, ValidatorIndex};
+
+use super::{IsRequest, Protocol};
+
+/// Request several availability chunks of a candidate at once.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct ChunkFetchingRequest {
+	/// Hash of candidate we want chunks for.
+	pub candidate_hash: CandidateHash,
+	/// The indices of the chunks to fetch.
+	pub indices: Vec<ValidatorIndex>,
+}
+
+/// Receive the requested erasure chunks.
+#[derive(Debug, Clone, Encode, Decode)]
+pub enum ChunkFetchingResponse {
+	/// The requested chunks.
+	#[codec(index = 0)]
+	Chunks(ChunksResponse),
+	/// Node was not in possession of all of the requested chunks.
+	#[codec(index = 1)]
+	NoSuchChunk,
+}
+
+impl From<Option<ChunksResponse>> for ChunkFetchingResponse {
+	fn from(x: Option<ChunksResponse>) -> Self {
+		match x {
+			Some(c) => ChunkFetchingResponse::Chunks(c),
+			None => ChunkFetchingResponse::NoSuchChunk,
+		}
+	}
+}
+
+/// The chunks requested by a `ChunkFetchingRequest`, together with a single proof of all of their
+/// branches in the Merkle tree.
+///
+/// Like `v1::ChunkResponse`, this omits the chunk indices, chunks are in the order of the indices
+/// in the request.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct ChunksResponse {
+	/// The erasure-encoded chunks of data belonging to the candidate block.
+	pub chunks: Vec<Vec<u8>>,
+	/// Proof for the branches of all the chunks in the Merkle tree.
+	pub proof: MultiProof,
+}
+
+impl ChunksResponse {
+	/// Pair the received chunks with the indices from the request.
+	///
+	/// Returns `None` if the response does not contain exactly one chunk per requested index.
+	pub fn recombine_into_chunks(
+		self,
+		req: &ChunkFetchingRequest,
+	) -> Option<(Vec<(ValidatorIndex, Vec<u8>)>, MultiProof)> {
+		if self.chunks.len() != req.indices.len() {
+			return None
+		}
+		Some((req.indices.iter().copied().zip(self.chunks).collect(), self.proof))
+	}
+}
+
+impl IsRequest for ChunkFetchingRequest {
+	type Response = ChunkFetchingResponse;
+	const PROTOCOL: Protocol = Protocol::ChunkFetchingV2;
+}
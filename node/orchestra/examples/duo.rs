@@ -42,6 +42,13 @@ impl<Context> AwesomeSubSys {
 	}
 }
 
+// `sub0` drops the oldest of these once its channels are full.
+impl PickLane for MsgStrukt {
+	fn lane(&self) -> metered::Lane {
+		metered::Lane::Droppable
+	}
+}
+
 #[derive(Default)]
 pub struct Fortified;
 
@@ -62,7 +69,7 @@ impl<Context> Fortified {
 
 #[orchestra(signal=SigSigSig, event=EvX, error=Yikes, gen=AllMessages)]
 struct Duo<T, U, V, W> {
-	#[subsystem(consumes: MsgStrukt, sends: [Plinko])]
+	#[subsystem(consumes: MsgStrukt, sends: [Plinko], overflow: DropOldest)]
 	sub0: Awesome,
 
 	#[subsystem(blocking, consumes: Plinko, sends: [MsgStrukt])]
@@ -48,6 +48,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 	let channel_name_rx = &info.channel_names_without_wip("_rx");
 	let channel_name_unbounded_rx = &info.channel_names_without_wip("_unbounded_rx");
+	let overflow_policy = &info.overflow_policies_without_wip();
+	let lanes = &info.lanes_without_wip();
 
 	let baggage_name = &info.baggage_names();
 	let baggage_generic_ty = &info.baggage_generic_types();
@@ -65,6 +67,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 		.collect::<Vec<_>>();
 
 	let error_ty = &info.extern_error_ty;
+	let message_wrapper = &info.message_wrapper;
+	let signal = &info.extern_signal_ty;
 
 	let support_crate = info.support_crate_name();
 
@@ -166,6 +170,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 							channel_capacity: self.channel_capacity,
 							signal_capacity: self.signal_capacity,
+							message_observer: self.message_observer,
+							poll_observer: self.poll_observer,
 						}
 					}
 					/// Specify the the initialization function for a subsystem
@@ -191,6 +197,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 							channel_capacity: self.channel_capacity,
 							signal_capacity: self.signal_capacity,
+							message_observer: self.message_observer,
+							poll_observer: self.poll_observer,
 						}
 					}
 				}
@@ -230,6 +238,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 							channel_capacity: self.channel_capacity,
 							signal_capacity: self.signal_capacity,
+							message_observer: self.message_observer,
+							poll_observer: self.poll_observer,
 						}
 					}
 				}
@@ -275,6 +285,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 						channel_capacity: self.channel_capacity,
 						signal_capacity: self.signal_capacity,
+						message_observer: self.message_observer,
+						poll_observer: self.poll_observer,
 					}
 				}
 			}
@@ -296,6 +308,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 						channel_capacity: self.channel_capacity,
 						signal_capacity: self.signal_capacity,
+						message_observer: self.message_observer,
+						poll_observer: self.poll_observer,
 					}
 				}
 			}
@@ -443,6 +457,10 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 			// or the default value.
 			channel_capacity: Option<usize>,
 			signal_capacity: Option<usize>,
+			message_observer: Option<
+				::std::sync::Arc<dyn #support_crate ::MessageObserver< #message_wrapper, #signal >>
+			>,
+			poll_observer: Option<::std::sync::Arc<dyn #support_crate ::PollObserver>>,
 		}
 	});
 
@@ -467,6 +485,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 					channel_capacity: None,
 					signal_capacity: None,
+					message_observer: None,
+					poll_observer: None,
 				}
 			}
 		}
@@ -494,6 +514,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 
 					channel_capacity: self.channel_capacity,
 					signal_capacity: self.signal_capacity,
+					message_observer: self.message_observer,
+					poll_observer: self.poll_observer,
 				}
 			}
 		}
@@ -519,6 +541,26 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 				self.channel_capacity = Some(capacity);
 				self
 			}
+
+			/// Install an observer of all messages and signals delivered to subsystems.
+			pub fn message_observer(
+				mut self,
+				observer: ::std::sync::Arc<dyn #support_crate ::MessageObserver< #message_wrapper, #signal >>,
+			) -> Self
+			{
+				self.message_observer = Some(observer);
+				self
+			}
+
+			/// Install an observer of all polls of the subsystems' futures.
+			pub fn poll_observer(
+				mut self,
+				observer: ::std::sync::Arc<dyn #support_crate ::PollObserver>,
+			) -> Self
+			{
+				self.poll_observer = Some(observer);
+				self
+			}
 		}
 	});
 
@@ -565,18 +607,25 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 				#(
 					let (#channel_name_tx, #channel_name_rx)
 					=
-						#support_crate ::metered::channel::<
+						#support_crate ::metered::channel_with_policy::<
 							MessagePacket< #consumes >
 						>(
-							self.channel_capacity.unwrap_or(CHANNEL_CAPACITY)
+							self.channel_capacity.unwrap_or(CHANNEL_CAPACITY),
+							#overflow_policy,
+							#lanes,
 						);
 				)*
 
 				#(
 					let (#channel_name_unbounded_tx, #channel_name_unbounded_rx) =
-						#support_crate ::metered::unbounded::<
+						#support_crate ::metered::unbounded_with_policy::<
 							MessagePacket< #consumes >
-						>();
+						>(
+							self.channel_capacity.unwrap_or(CHANNEL_CAPACITY)
+								* UNBOUNDED_CHANNEL_CAPACITY_FACTOR,
+							#overflow_policy,
+							#lanes,
+						);
 				)*
 
 				let channels_out =
@@ -587,6 +636,7 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 						#(
 							#channel_name_unbounded: #channel_name_unbounded_tx,
 						)*
+						message_observer: self.message_observer.clone(),
 					};
 
 				let mut spawner = match self.spawner {
@@ -631,6 +681,7 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 							ctx,
 							#subsystem_name,
 							#subsystem_name_str_literal,
+							self.poll_observer.clone(),
 							&mut running_subsystems,
 						)?;
 				)*
@@ -654,6 +705,8 @@ pub(crate) fn impl_builder(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 					running_subsystems,
 					events_rx,
 					to_orchestra_rx,
+					message_observer: self.message_observer,
+					poll_observer: self.poll_observer,
 				};
 
 				Ok((orchestra, handle))
@@ -705,6 +758,7 @@ pub(crate) fn impl_task_kind(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 			ctx: Ctx,
 			s: SubSys,
 			subsystem_name: &'static str,
+			poll_observer: Option<::std::sync::Arc<dyn #support_crate ::PollObserver>>,
 			futures: &mut #support_crate ::FuturesUnordered<BoxFuture<'static, ::std::result::Result<(), #error_ty> >>,
 		) -> ::std::result::Result<OrchestratedSubsystem<M>, #error_ty >
 		where
@@ -727,6 +781,12 @@ pub(crate) fn impl_task_kind(info: &OrchestraInfo) -> proc_macro2::TokenStream {
 				}
 				let _ = tx.send(());
 			});
+			let fut: BoxFuture<'static, ()> = match poll_observer {
+				Some(observer) => Box::pin(
+					#support_crate ::ObservedPolls::new(fut, subsystem_name, name, observer)
+				),
+				None => fut,
+			};
 
 			<TK as TaskKind>::launch_task(spawner, name, subsystem_name, fut);
 
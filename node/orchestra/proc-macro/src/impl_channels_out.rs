@@ -31,6 +31,13 @@ pub(crate) fn impl_channels_out_struct(info: &OrchestraInfo) -> Result<proc_macr
 	let unconsumes_variant = &info.variant_names_only_wip();
 
 	let support_crate = info.support_crate_name();
+	let signal = &info.extern_signal_ty;
+
+	let subsystem_name_str_literal = info
+		.subsystem_names_without_wip()
+		.iter()
+		.map(|ident| proc_macro2::Literal::string(ident.to_string().replace("_", "-").as_str()))
+		.collect::<Vec<_>>();
 
 	let ts = quote! {
 		/// Collection of channels to the individual subsystems.
@@ -53,11 +60,36 @@ pub(crate) fn impl_channels_out_struct(info: &OrchestraInfo) -> Result<proc_macr
 						MessagePacket< #consumes >
 					>,
 			)*
+
+			/// Observer of all messages sent via these channels, if any.
+			pub message_observer: ::std::option::Option<
+				::std::sync::Arc<dyn #support_crate ::MessageObserver< #message_wrapper, #signal >>
+			>,
 		}
 
 		#[allow(unreachable_code)]
 		// when no defined messages in enum
 		impl ChannelsOut {
+			/// The name of the subsystem consuming the given message, if any.
+			pub fn destination(message: & #message_wrapper) -> ::std::option::Option<&'static str> {
+				match message {
+				#(
+					#message_wrapper :: #consumes_variant ( _ ) => Some( #subsystem_name_str_literal ),
+				)*
+					#[allow(unreachable_patterns)]
+					_ => None,
+				}
+			}
+
+			/// Pass a message to the message observer, if one is installed.
+			pub fn observe(&self, origin: &'static str, message: & #message_wrapper) {
+				if let Some(observer) = self.message_observer.as_ref() {
+					if let Some(destination) = Self::destination(message) {
+						observer.on_message(origin, destination, message);
+					}
+				}
+			}
+
 			/// Send a message via a bounded channel.
 			pub async fn send_and_log_error(
 				&mut self,
@@ -62,6 +62,10 @@ pub(crate) fn impl_orchestra_struct(info: &OrchestraInfo) -> proc_macro2::TokenS
 		/// Capacity of a signal channel between a subsystem and the orchestra.
 		const SIGNAL_CHANNEL_CAPACITY: usize = #signal_channel_capacity;
 
+		/// Number of messages an unbounded channel to a subsystem with an overflow policy
+		/// other than `Block` holds, as a multiple of the bounded channel capacity.
+		const UNBOUNDED_CHANNEL_CAPACITY_FACTOR: usize = 16;
+
 		/// The log target tag.
 		const LOG_TARGET: &'static str = #log_target;
 
@@ -93,6 +97,14 @@ pub(crate) fn impl_orchestra_struct(info: &OrchestraInfo) -> proc_macro2::TokenS
 
 			/// Events that are sent to the orchestra from the outside world.
 			events_rx: #support_crate ::metered::MeteredReceiver< #event_ty >,
+
+			/// Observer of all messages and signals delivered to subsystems, if any.
+			message_observer: ::std::option::Option<
+				::std::sync::Arc<dyn #support_crate ::MessageObserver< #message_wrapper, #signal_ty >>
+			>,
+
+			/// Observer of all polls of the subsystems' futures, if any.
+			poll_observer: ::std::option::Option<::std::sync::Arc<dyn #support_crate ::PollObserver>>,
 		}
 
 		impl #generics #orchestra_name #generics #where_clause {
@@ -102,6 +114,9 @@ pub(crate) fn impl_orchestra_struct(info: &OrchestraInfo) -> proc_macro2::TokenS
 			/// The definition of a termination signal is up to the user and
 			/// implementation specific.
 			pub async fn wait_terminate(&mut self, signal: #signal_ty, timeout: ::std::time::Duration) -> ::std::result::Result<(), #error_ty > {
+				if let Some(observer) = self.message_observer.as_ref() {
+					observer.on_signal(&signal);
+				}
 				#(
 					::std::mem::drop(self. #subsystem_name .send_signal(signal.clone()).await);
 				)*
@@ -127,6 +142,9 @@ pub(crate) fn impl_orchestra_struct(info: &OrchestraInfo) -> proc_macro2::TokenS
 
 			/// Broadcast a signal to all subsystems.
 			pub async fn broadcast_signal(&mut self, signal: #signal_ty) -> ::std::result::Result<(), #error_ty > {
+				if let Some(observer) = self.message_observer.as_ref() {
+					observer.on_signal(&signal);
+				}
 				#(
 					let _ = self. #subsystem_name .send_signal(signal.clone()).await;
 				)*
@@ -137,6 +155,11 @@ pub(crate) fn impl_orchestra_struct(info: &OrchestraInfo) -> proc_macro2::TokenS
 
 			/// Route a particular message to a subsystem that consumes the message.
 			pub async fn route_message(&mut self, message: #message_wrapper, origin: &'static str) -> ::std::result::Result<(), #error_ty > {
+				if let Some(observer) = self.message_observer.as_ref() {
+					if let Some(destination) = ChannelsOut::destination(&message) {
+						observer.on_message(origin, destination, &message);
+					}
+				}
 				match message {
 					#(
 						#message_wrapper :: #consumes_variant ( inner ) =>
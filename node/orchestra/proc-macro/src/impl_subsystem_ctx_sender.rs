@@ -222,6 +222,8 @@ pub(crate) fn impl_subsystem_sender(
 			channels: ChannelsOut,
 			/// Systemwide tick for which signals were received by all subsystems.
 			signals_received: SignalsReceived,
+			/// Name of the subsystem sending, reported to the message observer.
+			origin: &'static str,
 			/// Keep that marker around.
 			_phantom: ::core::marker::PhantomData< OutgoingWrapper >,
 		}
@@ -233,6 +235,7 @@ pub(crate) fn impl_subsystem_sender(
 				Self {
 					channels: self.channels.clone(),
 					signals_received: self.signals_received.clone(),
+					origin: self.origin,
 					_phantom: ::core::marker::PhantomData::default(),
 				}
 			}
@@ -254,11 +257,13 @@ pub(crate) fn impl_subsystem_sender(
 			{
 				async fn send_message(&mut self, msg: OutgoingMessage)
 				{
+					let msg = <#all_messages_wrapper as ::std::convert::From<_>> ::from (
+						<#outgoing_wrapper as ::std::convert::From<_>> :: from ( msg )
+					);
+					self.channels.observe(self.origin, &msg);
 					self.channels.send_and_log_error(
 						self.signals_received.load(),
-						<#all_messages_wrapper as ::std::convert::From<_>> ::from (
-							<#outgoing_wrapper as ::std::convert::From<_>> :: from ( msg )
-						)
+						msg,
 					).await;
 				}
 
@@ -274,11 +279,13 @@ pub(crate) fn impl_subsystem_sender(
 
 				fn send_unbounded_message(&mut self, msg: OutgoingMessage)
 				{
+					let msg = <#all_messages_wrapper as ::std::convert::From<_>> ::from (
+						<#outgoing_wrapper as ::std::convert::From<_>> :: from ( msg )
+					);
+					self.channels.observe(self.origin, &msg);
 					self.channels.send_unbounded_and_log_error(
 						self.signals_received.load(),
-						<#all_messages_wrapper as ::std::convert::From<_>> ::from (
-							<#outgoing_wrapper as ::std::convert::From<_>> :: from ( msg )
-						)
+						msg,
 					);
 				}
 			}
@@ -635,6 +642,7 @@ pub(crate) fn impl_subsystem_context(
 					to_subsystems: #subsystem_sender_name :: < <M as AssociateOutgoing>::OutgoingMessages > {
 						channels: to_subsystems,
 						signals_received: signals_received.clone(),
+						origin: name,
 						_phantom: ::core::marker::PhantomData::default(),
 					},
 					to_orchestra,
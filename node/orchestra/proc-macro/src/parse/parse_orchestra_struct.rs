@@ -33,6 +33,7 @@ mod kw {
 	syn::custom_keyword!(blocking);
 	syn::custom_keyword!(consumes);
 	syn::custom_keyword!(sends);
+	syn::custom_keyword!(overflow);
 }
 
 #[derive(Clone, Debug)]
@@ -47,6 +48,8 @@ pub(crate) enum SubSysAttrItem {
 	Sends(Sends),
 	/// Message to be consumed by this subsystem.
 	Consumes(Consumes),
+	/// How to handle messages sent while the channels of the subsystem are full.
+	Overflow(Overflow),
 }
 
 impl Parse for SubSysAttrItem {
@@ -58,6 +61,8 @@ impl Parse for SubSysAttrItem {
 			Self::Blocking(input.parse::<kw::blocking>()?)
 		} else if lookahead.peek(kw::sends) {
 			Self::Sends(input.parse::<Sends>()?)
+		} else if lookahead.peek(kw::overflow) {
+			Self::Overflow(input.parse::<Overflow>()?)
 		} else {
 			Self::Consumes(input.parse::<Consumes>()?)
 		})
@@ -79,6 +84,9 @@ impl ToTokens for SubSysAttrItem {
 			Self::Consumes(_) => {
 				quote! {}
 			},
+			Self::Overflow(_) => {
+				quote! {}
+			},
 		};
 		tokens.extend(ts.into_iter());
 	}
@@ -105,6 +113,9 @@ pub(crate) struct SubSysField {
 	/// Avoids dispatching `Wrapper` type messages, but generates the variants.
 	/// Does not require the subsystem to be instantiated with the builder pattern.
 	pub(crate) wip: bool,
+	/// The `OverflowPolicy` variant of the channels to the subsystem,
+	/// if they should drop messages rather than block or grow without bounds.
+	pub(crate) overflow: Option<Ident>,
 }
 
 // Converts a type enum to a path if this type is a TypePath
@@ -230,6 +241,31 @@ impl Parse for Sends {
 	}
 }
 
+/// Parses `overflow: DropOldest`.
+#[derive(Debug, Clone)]
+pub(crate) struct Overflow {
+	#[allow(dead_code)]
+	pub(crate) keyword_overflow: kw::overflow,
+	#[allow(dead_code)]
+	pub(crate) colon: Token![:],
+	pub(crate) policy: Ident,
+}
+
+impl Parse for Overflow {
+	fn parse(input: syn::parse::ParseStream) -> Result<Self> {
+		let keyword_overflow = input.parse()?;
+		let colon = input.parse()?;
+		let policy: Ident = input.parse()?;
+		if !["Block", "DropOldest", "DropNewest"].iter().any(|variant| policy == variant) {
+			return Err(Error::new(
+				policy.span(),
+				"Overflow policy must be one of `Block`, `DropOldest` and `DropNewest`.",
+			))
+		}
+		Ok(Self { keyword_overflow, colon, policy })
+	}
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Consumes {
 	#[allow(dead_code)]
@@ -267,6 +303,8 @@ pub(crate) struct SubSystemAttrItems {
 	/// The message type being consumed by the subsystem.
 	pub(crate) consumes: Option<Consumes>,
 	pub(crate) sends: Option<Sends>,
+	/// How messages sent to the full channels of the subsystem are handled.
+	pub(crate) overflow: Option<Overflow>,
 }
 
 impl Parse for SubSystemAttrItems {
@@ -306,8 +344,9 @@ impl Parse for SubSystemAttrItems {
 
 		let blocking = extract_variant!(unique, Blocking; default = false);
 		let wip = extract_variant!(unique, Wip; default = false);
+		let overflow = extract_variant!(unique, Overflow take);
 
-		Ok(Self { blocking, wip, sends, consumes })
+		Ok(Self { blocking, wip, sends, consumes, overflow })
 	}
 }
 
@@ -446,6 +485,39 @@ impl OrchestraInfo {
 			.map(|ssf| ssf.message_to_consume.clone())
 			.collect::<Vec<_>>()
 	}
+
+	pub(crate) fn overflow_policies_without_wip(&self) -> Vec<TokenStream> {
+		let support_crate = self.support_crate_name();
+		self.subsystems
+			.iter()
+			.filter(|ssf| !ssf.wip)
+			.map(|ssf| {
+				let policy =
+					ssf.overflow.clone().unwrap_or_else(|| Ident::new("Block", ssf.name.span()));
+				quote! { #support_crate ::metered::OverflowPolicy:: #policy }
+			})
+			.collect::<Vec<_>>()
+	}
+
+	/// Classifiers picking the lane of the messages sent to a subsystem, see `PickLane`.
+	pub(crate) fn lanes_without_wip(&self) -> Vec<TokenStream> {
+		let support_crate = self.support_crate_name();
+		self.subsystems
+			.iter()
+			.filter(|ssf| !ssf.wip)
+			.map(|ssf| {
+				let consumes = &ssf.message_to_consume;
+				match ssf.overflow {
+					Some(ref policy) if policy != "Block" => quote! {
+						|packet: &MessagePacket< #consumes >| {
+							#support_crate ::PickLane::lane(&packet.message)
+						}
+					},
+					_ => quote! { |_| #support_crate ::metered::Lane::Normal },
+				}
+			})
+			.collect::<Vec<_>>()
+	}
 }
 
 /// Internals of the orchestra.
@@ -520,7 +592,8 @@ impl OrchestraGuts {
 				}
 				unique_subsystem_idents.insert(generic.clone());
 
-				let SubSystemAttrItems { wip, blocking, consumes, sends, .. } = subsystem_attrs;
+				let SubSystemAttrItems { wip, blocking, consumes, sends, overflow, .. } =
+					subsystem_attrs;
 
 				// messages to be sent
 				let sends = if let Some(sends) = sends {
@@ -542,6 +615,7 @@ impl OrchestraGuts {
 					messages_to_send: sends,
 					wip,
 					blocking,
+					overflow: overflow.map(|overflow| overflow.policy),
 				});
 			} else {
 				let flattened = flatten_type(&ty, ident.span())?;
@@ -246,6 +246,26 @@ mod strukt {
 		});
 	}
 
+	#[test]
+	fn parse_subsystem_attributes_works_13_overflow() {
+		assert_matches!(
+		syn::parse2::<SubSystemAttrItems>(quote! {
+			(consumes: Foo, overflow: DropOldest)
+		}), Ok(SubSystemAttrItems { overflow: Some(overflow), .. }) => {
+			assert_eq!(overflow.policy, "DropOldest");
+		});
+	}
+
+	#[test]
+	fn parse_subsystem_attributes_works_14_unknown_overflow_policy() {
+		assert_matches!(
+		syn::parse2::<SubSystemAttrItems>(quote! {
+			(consumes: Foo, overflow: DropAll)
+		}), Err(e) => {
+			dbg!(e)
+		});
+	}
+
 	#[test]
 	fn struct_parse_baggage() {
 		let item: OrchestraGuts = parse_quote! {
@@ -162,6 +162,49 @@ impl fmt::Debug for ToOrchestra {
 	}
 }
 
+/// Observes every message and signal the orchestra delivers to its subsystems.
+///
+/// Installed via the generated builder's `message_observer`. The observer is invoked
+/// on the sending task before the message is put into the destination channel,
+/// hence implementations must not block.
+pub trait MessageObserver<Message, Signal>: fmt::Debug + Send + Sync {
+	/// A message sent by `origin` is about to be delivered to the subsystem `destination`.
+	fn on_message(&self, origin: &'static str, destination: &'static str, message: &Message);
+
+	/// A signal is about to be broadcast to all subsystems.
+	fn on_signal(&self, signal: &Signal);
+}
+
+/// Picks the lane of each message consumed by a subsystem with an `overflow` policy.
+///
+/// Urgent messages are received before all others. While the channels to such a subsystem
+/// are full, only droppable messages are dropped, all others are queued regardless of
+/// capacity and in order.
+pub trait PickLane {
+	/// The lane to put the message into.
+	fn lane(&self) -> metered::Lane;
+}
+
+/// Observes every poll of the futures the orchestra spawns for its subsystems.
+///
+/// Installed via the generated builder's `poll_observer`. Both methods run on the
+/// executor thread, right around the poll, and must be cheap.
+pub trait PollObserver: fmt::Debug + Send + Sync {
+	/// Called right before a poll, the returned value is passed on to `after_poll`.
+	///
+	/// Allows to capture per-thread state, i.e. the allocation counter of the thread.
+	fn before_poll(&self) -> u64;
+
+	/// The `task` of `subsystem` was polled for `elapsed`.
+	fn after_poll(
+		&self,
+		subsystem: &'static str,
+		task: &'static str,
+		elapsed: Duration,
+		before: u64,
+	);
+}
+
 /// A helper trait to map a subsystem to smth. else.
 pub trait MapSubsystem<T> {
 	/// The output type of the mapping.
@@ -547,3 +590,40 @@ where
 		Poll::Pending
 	}
 }
+
+/// A future that reports all of its polls to a [`PollObserver`].
+#[pin_project::pin_project]
+pub struct ObservedPolls<F> {
+	#[pin]
+	future: F,
+	subsystem: &'static str,
+	task: &'static str,
+	observer: Arc<dyn PollObserver>,
+}
+
+impl<F: Future> ObservedPolls<F> {
+	/// Observe the polls of the `task` of `subsystem`.
+	pub fn new(
+		future: F,
+		subsystem: &'static str,
+		task: &'static str,
+		observer: Arc<dyn PollObserver>,
+	) -> Self {
+		Self { future, subsystem, task, observer }
+	}
+}
+
+impl<F: Future> Future for ObservedPolls<F> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+		let this = self.project();
+
+		let before = this.observer.before_poll();
+		let start = std::time::Instant::now();
+		let res = this.future.poll(ctx);
+		this.observer.after_poll(this.subsystem, this.task, start.elapsed(), before);
+
+		res
+	}
+}
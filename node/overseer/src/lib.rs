@@ -89,12 +89,15 @@ pub use polkadot_node_subsystem_types::{
 };
 
 pub mod metrics;
-pub use self::metrics::Metrics as OverseerMetrics;
+pub use self::metrics::{Metrics as OverseerMetrics, PollMetrics};
 
 /// A dummy subsystem, mostly useful for placeholders and tests.
 pub mod dummy;
 pub use self::dummy::DummySubsystem;
 
+pub mod recorder;
+pub use self::recorder::MessageRecorder;
+
 pub use polkadot_node_metrics::{
 	metrics::{prometheus, Metrics as MetricsTrait},
 	Metronome,
@@ -104,10 +107,10 @@ use parity_util_mem::MemoryAllocationTracker;
 
 pub use orchestra as gen;
 pub use orchestra::{
-	contextbounds, orchestra, subsystem, FromOrchestra, MapSubsystem, MessagePacket,
-	OrchestraError as OverseerError, SignalsReceived, Spawner, Subsystem, SubsystemContext,
-	SubsystemIncomingMessages, SubsystemInstance, SubsystemMeterReadouts, SubsystemMeters,
-	SubsystemSender, TimeoutExt, ToOrchestra,
+	contextbounds, orchestra, subsystem, FromOrchestra, MapSubsystem, MessageObserver,
+	MessagePacket, ObservedPolls, OrchestraError as OverseerError, PollObserver, SignalsReceived,
+	Spawner, Subsystem, SubsystemContext, SubsystemIncomingMessages, SubsystemInstance,
+	SubsystemMeterReadouts, SubsystemMeters, SubsystemSender, TimeoutExt, ToOrchestra,
 };
 
 /// Store 2 days worth of blocks, not accounting for forks,
@@ -500,11 +503,14 @@ pub struct Overseer<SupportsParachains> {
 	])]
 	bitfield_signing: BitfieldSigning,
 
+	// Bitfields are re-gossiped for every relay parent, a lagging subsystem is better
+	// off with the most recent ones than with an ever growing queue. Only gossiped
+	// bitfields are dropped, see `PickLane for BitfieldDistributionMessage`.
 	#[subsystem(BitfieldDistributionMessage, sends: [
 		RuntimeApiMessage,
 		NetworkBridgeTxMessage,
 		ProvisionerMessage,
-	])]
+	], overflow: DropOldest)]
 	bitfield_distribution: BitfieldDistribution,
 
 	#[subsystem(ProvisionerMessage, sends: [
@@ -907,6 +913,7 @@ where
 		subsystem_name: Option<&'static str>,
 		j: BoxFuture<'static, ()>,
 	) {
+		let j = self.observe_polls(task_name, subsystem_name, j);
 		self.spawner.spawn(task_name, subsystem_name, j);
 	}
 
@@ -916,6 +923,24 @@ where
 		subsystem_name: Option<&'static str>,
 		j: BoxFuture<'static, ()>,
 	) {
+		let j = self.observe_polls(task_name, subsystem_name, j);
 		self.spawner.spawn_blocking(task_name, subsystem_name, j);
 	}
+
+	fn observe_polls(
+		&self,
+		task_name: &'static str,
+		subsystem_name: Option<&'static str>,
+		j: BoxFuture<'static, ()>,
+	) -> BoxFuture<'static, ()> {
+		match self.poll_observer.as_ref() {
+			Some(observer) => Box::pin(ObservedPolls::new(
+				j,
+				subsystem_name.unwrap_or("overseer"),
+				task_name,
+				observer.clone(),
+			)),
+			None => j,
+		}
+	}
 }
@@ -32,10 +32,14 @@ struct MetricsInner {
 	to_subsystem_bounded_sent: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_bounded_received: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_bounded_blocked: prometheus::GaugeVec<prometheus::U64>,
+	to_subsystem_bounded_dropped: prometheus::GaugeVec<prometheus::U64>,
+	to_subsystem_bounded_high_water_mark: prometheus::GaugeVec<prometheus::U64>,
 
 	to_subsystem_unbounded_tof: prometheus::HistogramVec,
 	to_subsystem_unbounded_sent: prometheus::GaugeVec<prometheus::U64>,
 	to_subsystem_unbounded_received: prometheus::GaugeVec<prometheus::U64>,
+	to_subsystem_unbounded_dropped: prometheus::GaugeVec<prometheus::U64>,
+	to_subsystem_unbounded_high_water_mark: prometheus::GaugeVec<prometheus::U64>,
 
 	signals_sent: prometheus::GaugeVec<prometheus::U64>,
 	signals_received: prometheus::GaugeVec<prometheus::U64>,
@@ -97,6 +101,16 @@ impl Metrics {
 						.with_label_values(&[name])
 						.set(readouts.bounded.blocked as u64);
 
+					metrics
+						.to_subsystem_bounded_dropped
+						.with_label_values(&[name])
+						.set(readouts.bounded.dropped as u64);
+
+					metrics
+						.to_subsystem_bounded_high_water_mark
+						.with_label_values(&[name])
+						.set(readouts.bounded.high_water_mark as u64);
+
 					metrics
 						.to_subsystem_unbounded_sent
 						.with_label_values(&[name])
@@ -107,6 +121,16 @@ impl Metrics {
 						.with_label_values(&[name])
 						.set(readouts.unbounded.received as u64);
 
+					metrics
+						.to_subsystem_unbounded_dropped
+						.with_label_values(&[name])
+						.set(readouts.unbounded.dropped as u64);
+
+					metrics
+						.to_subsystem_unbounded_high_water_mark
+						.with_label_values(&[name])
+						.set(readouts.unbounded.high_water_mark as u64);
+
 					metrics
 						.signals_sent
 						.with_label_values(&[name])
@@ -196,6 +220,26 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			to_subsystem_bounded_dropped: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_bounded_dropped",
+						"Number of elements dropped from subsystems' bounded queues on overflow",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			to_subsystem_bounded_high_water_mark: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_bounded_high_water_mark",
+						"Largest number of elements queued up in subsystems' bounded queues",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
 			to_subsystem_unbounded_tof: prometheus::register(
 				prometheus::HistogramVec::new(
 					prometheus::HistogramOpts::new(
@@ -226,6 +270,26 @@ impl MetricsTrait for Metrics {
 				)?,
 				registry,
 			)?,
+			to_subsystem_unbounded_dropped: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_unbounded_dropped",
+						"Number of elements dropped from subsystems' unbounded queues on overflow",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			to_subsystem_unbounded_high_water_mark: prometheus::register(
+				prometheus::GaugeVec::<prometheus::U64>::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_unbounded_high_water_mark",
+						"Largest number of elements queued up in subsystems' unbounded queues",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
 			signals_sent: prometheus::register(
 				prometheus::GaugeVec::<prometheus::U64>::new(
 					prometheus::Opts::new(
@@ -271,3 +335,111 @@ impl fmt::Debug for Metrics {
 		f.write_str("Metrics {{...}}")
 	}
 }
+
+/// Buckets of the poll duration histogram, from 10µs to about 0.6s.
+const POLL_DURATION_BUCKETS: [f64; 9] =
+	[0.00001, 0.00004, 0.00016, 0.00064, 0.00256, 0.01024, 0.04096, 0.16384, 0.65536];
+
+/// Poll time and allocation accounting per subsystem and task.
+///
+/// Installed as the overseer's [`PollObserver`](crate::PollObserver). Allocations are
+/// only attributed when built with the `jemalloc-allocator` feature.
+#[derive(Clone)]
+pub struct PollMetrics {
+	poll_duration: prometheus::HistogramVec,
+	slow_polls: prometheus::CounterVec<prometheus::U64>,
+	#[cfg(feature = "jemalloc-allocator")]
+	allocated_bytes: prometheus::CounterVec<prometheus::U64>,
+	slow_poll_threshold: Duration,
+}
+
+impl PollMetrics {
+	/// Register the metrics, counting polls longer than `slow_poll_threshold` as slow.
+	pub fn register(
+		registry: &prometheus::Registry,
+		slow_poll_threshold: Duration,
+	) -> Result<Self, prometheus::PrometheusError> {
+		Ok(PollMetrics {
+			poll_duration: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_subsystem_poll_duration",
+						"Time spent polling a task of a subsystem",
+					)
+					.buckets(POLL_DURATION_BUCKETS.into()),
+					&["subsystem_name", "task_name"],
+				)?,
+				registry,
+			)?,
+			slow_polls: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_slow_polls_total",
+						"Number of polls of a task of a subsystem exceeding the slow poll threshold",
+					),
+					&["subsystem_name", "task_name"],
+				)?,
+				registry,
+			)?,
+			#[cfg(feature = "jemalloc-allocator")]
+			allocated_bytes: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_subsystem_allocated_bytes_total",
+						"Bytes allocated while polling a task of a subsystem",
+					),
+					&["subsystem_name", "task_name"],
+				)?,
+				registry,
+			)?,
+			slow_poll_threshold,
+		})
+	}
+}
+
+impl PollObserver for PollMetrics {
+	fn before_poll(&self) -> u64 {
+		thread_allocated()
+	}
+
+	fn after_poll(
+		&self,
+		subsystem: &'static str,
+		task: &'static str,
+		elapsed: Duration,
+		_before: u64,
+	) {
+		let labels = [subsystem, task];
+		self.poll_duration.with_label_values(&labels).observe(elapsed.as_secs_f64());
+		if elapsed > self.slow_poll_threshold {
+			self.slow_polls.with_label_values(&labels).inc();
+		}
+
+		#[cfg(feature = "jemalloc-allocator")]
+		self.allocated_bytes
+			.with_label_values(&labels)
+			.inc_by(thread_allocated().saturating_sub(_before));
+	}
+}
+
+impl fmt::Debug for PollMetrics {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("PollMetrics {{...}}")
+	}
+}
+
+/// Bytes allocated by the current thread since it started.
+#[cfg(feature = "jemalloc-allocator")]
+fn thread_allocated() -> u64 {
+	thread_local! {
+		static ALLOCATED: Option<tikv_jemalloc_ctl::thread::ThreadLocal<u64>> =
+			tikv_jemalloc_ctl::thread::allocatedp::read().ok();
+	}
+
+	ALLOCATED.with(|allocated| allocated.as_ref().map_or(0, |allocated| allocated.get()))
+}
+
+#[cfg(not(feature = "jemalloc-allocator"))]
+fn thread_allocated() -> u64 {
+	0
+}
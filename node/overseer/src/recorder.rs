@@ -0,0 +1,630 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Recording of all messages and signals the overseer delivers to subsystems.
+//!
+//! The [`MessageRecorder`] is installed as the overseer's message observer and queues
+//! a [`Record`] per delivery. A [`RecordWriter`] running on its own thread drains the
+//! queue into a binary log, so the overseer never waits on the file system. Records are
+//! SCALE encoded and length prefixed, so a log cut short by a crash can still be read up
+//! to the last complete record with [`read_records`].
+//!
+//! The payload of every message consisting of encodable data is captured, see
+//! [`RecordedPayload`]. Messages carrying response channels or network peers are recorded
+//! with their origin and destination only, and the responses sent back over those
+//! channels are not part of the log.
+
+use std::{
+	fmt,
+	fs::File,
+	io::{self, BufWriter, Read, Write},
+	path::Path,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		mpsc::{self, Receiver, SyncSender, TrySendError},
+		Arc,
+	},
+	time::Instant,
+};
+
+use parity_scale_codec::{Decode, Encode};
+
+use polkadot_node_primitives::{
+	approval::{IndirectAssignmentCert, IndirectSignedApprovalVote},
+	PoV, UncheckedDisputeMessage, UncheckedSignedFullStatement,
+};
+use polkadot_node_subsystem_types::messages::{
+	ApprovalDistributionMessage, BitfieldDistributionMessage, CandidateBackingMessage,
+	ChainSelectionMessage, CollatorProtocolMessage, DisputeCoordinatorMessage,
+	DisputeDistributionMessage, ProvisionableData, ProvisionerMessage,
+	StatementDistributionMessage,
+};
+use polkadot_primitives::v2::{
+	BlockNumber, CandidateHash, CandidateIndex, CandidateReceipt, CollatorId, Hash, Id as ParaId,
+	SessionIndex, UncheckedSignedAvailabilityBitfield, ValidatorSignature,
+};
+
+use crate::{
+	jaeger, ActivatedLeaf, ActiveLeavesUpdate, AllMessages, LeafStatus, MessageObserver,
+	OverseerSignal,
+};
+
+const LOG_TARGET: &str = "parachain::overseer-recorder";
+
+/// Number of records queued for the [`RecordWriter`] before further ones get dropped.
+pub const RECORD_QUEUE_SIZE: usize = 16 * 1024;
+
+/// A single delivery recorded by the [`MessageRecorder`].
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct Record {
+	/// Microseconds since the recorder was created.
+	pub at: u64,
+	/// What was delivered.
+	pub event: RecordedEvent,
+}
+
+/// A recorded message or signal.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum RecordedEvent {
+	/// A message sent to a single subsystem.
+	#[codec(index = 0)]
+	Message {
+		/// Name of the sending subsystem, or of the external source.
+		origin: String,
+		/// Name of the receiving subsystem.
+		destination: String,
+		/// The message itself, if it could be captured.
+		payload: RecordedPayload,
+	},
+	/// A signal broadcast to all subsystems.
+	#[codec(index = 1)]
+	Signal(RecordedSignal),
+}
+
+/// A leaf activated by an [`OverseerSignal::ActiveLeaves`].
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct RecordedLeaf {
+	/// The block hash.
+	pub hash: Hash,
+	/// The block number.
+	pub number: BlockNumber,
+	/// Whether the leaf was encountered for the first time.
+	pub fresh: bool,
+}
+
+/// The encodable part of an [`OverseerSignal`].
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum RecordedSignal {
+	/// See [`OverseerSignal::ActiveLeaves`].
+	#[codec(index = 0)]
+	ActiveLeaves {
+		/// The newly activated leaf, if any.
+		activated: Option<RecordedLeaf>,
+		/// Leaves no longer of interest.
+		deactivated: Vec<Hash>,
+	},
+	/// See [`OverseerSignal::BlockFinalized`].
+	#[codec(index = 1)]
+	BlockFinalized(Hash, BlockNumber),
+	/// See [`OverseerSignal::Conclude`].
+	#[codec(index = 2)]
+	Conclude,
+}
+
+impl From<&OverseerSignal> for RecordedSignal {
+	fn from(signal: &OverseerSignal) -> Self {
+		match signal {
+			OverseerSignal::ActiveLeaves(update) => RecordedSignal::ActiveLeaves {
+				activated: update.activated.as_ref().map(|leaf| RecordedLeaf {
+					hash: leaf.hash,
+					number: leaf.number,
+					fresh: leaf.status.is_fresh(),
+				}),
+				deactivated: update.deactivated.to_vec(),
+			},
+			OverseerSignal::BlockFinalized(hash, number) =>
+				RecordedSignal::BlockFinalized(*hash, *number),
+			OverseerSignal::Conclude => RecordedSignal::Conclude,
+		}
+	}
+}
+
+impl RecordedSignal {
+	/// Recreate the signal.
+	///
+	/// Jaeger spans are not recorded, activated leaves carry a disabled span.
+	pub fn into_signal(self) -> OverseerSignal {
+		match self {
+			RecordedSignal::ActiveLeaves { activated, deactivated } =>
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+					activated: activated.map(|leaf| ActivatedLeaf {
+						hash: leaf.hash,
+						number: leaf.number,
+						status: if leaf.fresh { LeafStatus::Fresh } else { LeafStatus::Stale },
+						span: Arc::new(jaeger::Span::Disabled),
+					}),
+					deactivated: deactivated.into_iter().collect(),
+				}),
+			RecordedSignal::BlockFinalized(hash, number) =>
+				OverseerSignal::BlockFinalized(hash, number),
+			RecordedSignal::Conclude => OverseerSignal::Conclude,
+		}
+	}
+}
+
+/// The payload of a recorded message.
+///
+/// Every message consisting of encodable data is captured, everything else is recorded as
+/// `Opaque`. Signed statements, bitfields and disputes are captured unchecked; they can't be
+/// recreated without checking their signatures and are hence not replayed.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum RecordedPayload {
+	/// The message was not captured.
+	#[codec(index = 0)]
+	Opaque,
+	/// See [`ChainSelectionMessage::Approved`].
+	#[codec(index = 1)]
+	ChainSelectionApproved(Hash),
+	/// See [`ApprovalDistributionMessage::DistributeAssignment`].
+	#[codec(index = 2)]
+	DistributeAssignment(IndirectAssignmentCert, CandidateIndex),
+	/// See [`ApprovalDistributionMessage::DistributeApproval`].
+	#[codec(index = 3)]
+	DistributeApproval(IndirectSignedApprovalVote),
+	/// See [`CandidateBackingMessage::Second`].
+	#[codec(index = 4)]
+	Second(Hash, CandidateReceipt, PoV),
+	/// See [`CandidateBackingMessage::Statement`].
+	#[codec(index = 5)]
+	BackingStatement(Hash, UncheckedSignedFullStatement),
+	/// See [`CollatorProtocolMessage::CollateOn`].
+	#[codec(index = 6)]
+	CollateOn(ParaId),
+	/// See [`CollatorProtocolMessage::DistributeCollation`], sent without a result sender.
+	#[codec(index = 7)]
+	DistributeCollation(CandidateReceipt, PoV),
+	/// See [`CollatorProtocolMessage::ReportCollator`].
+	#[codec(index = 8)]
+	ReportCollator(CollatorId),
+	/// See [`CollatorProtocolMessage::Invalid`].
+	#[codec(index = 9)]
+	CollationInvalid(Hash, CandidateReceipt),
+	/// See [`CollatorProtocolMessage::Seconded`].
+	#[codec(index = 10)]
+	CollationSeconded(Hash, UncheckedSignedFullStatement),
+	/// See [`DisputeCoordinatorMessage::IssueLocalStatement`].
+	#[codec(index = 11)]
+	IssueLocalStatement(SessionIndex, CandidateHash, CandidateReceipt, bool),
+	/// See [`DisputeDistributionMessage::SendDispute`].
+	#[codec(index = 12)]
+	SendDispute(UncheckedDisputeMessage),
+	/// See [`BitfieldDistributionMessage::DistributeBitfield`].
+	#[codec(index = 13)]
+	DistributeBitfield(Hash, UncheckedSignedAvailabilityBitfield),
+	/// See [`StatementDistributionMessage::Share`].
+	#[codec(index = 14)]
+	ShareStatement(Hash, UncheckedSignedFullStatement),
+	/// See [`ProvisionableData::Bitfield`].
+	#[codec(index = 15)]
+	ProvisionBitfield(Hash, Hash, UncheckedSignedAvailabilityBitfield),
+	/// See [`ProvisionableData::BackedCandidate`].
+	#[codec(index = 16)]
+	ProvisionBackedCandidate(Hash, CandidateReceipt),
+	/// See [`ProvisionableData::Dispute`].
+	#[codec(index = 17)]
+	ProvisionDispute(Hash, Hash, ValidatorSignature),
+}
+
+impl RecordedPayload {
+	/// Capture the payload of a message, if it consists of encodable data.
+	pub fn capture(message: &AllMessages) -> Self {
+		match message {
+			AllMessages::ChainSelection(ChainSelectionMessage::Approved(hash)) =>
+				RecordedPayload::ChainSelectionApproved(*hash),
+			AllMessages::ApprovalDistribution(
+				ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index),
+			) => RecordedPayload::DistributeAssignment(cert.clone(), *candidate_index),
+			AllMessages::ApprovalDistribution(ApprovalDistributionMessage::DistributeApproval(
+				vote,
+			)) => RecordedPayload::DistributeApproval(vote.clone()),
+			AllMessages::CandidateBacking(CandidateBackingMessage::Second(
+				relay_parent,
+				candidate,
+				pov,
+			)) => RecordedPayload::Second(*relay_parent, candidate.clone(), pov.clone()),
+			AllMessages::CandidateBacking(CandidateBackingMessage::Statement(
+				relay_parent,
+				statement,
+			)) => RecordedPayload::BackingStatement(*relay_parent, statement.clone().into()),
+			AllMessages::CollatorProtocol(CollatorProtocolMessage::CollateOn(para_id)) =>
+				RecordedPayload::CollateOn(*para_id),
+			AllMessages::CollatorProtocol(CollatorProtocolMessage::DistributeCollation(
+				candidate,
+				pov,
+				None,
+			)) => RecordedPayload::DistributeCollation(candidate.clone(), pov.clone()),
+			AllMessages::CollatorProtocol(CollatorProtocolMessage::ReportCollator(collator)) =>
+				RecordedPayload::ReportCollator(collator.clone()),
+			AllMessages::CollatorProtocol(CollatorProtocolMessage::Invalid(
+				relay_parent,
+				candidate,
+			)) => RecordedPayload::CollationInvalid(*relay_parent, candidate.clone()),
+			AllMessages::CollatorProtocol(CollatorProtocolMessage::Seconded(
+				relay_parent,
+				statement,
+			)) => RecordedPayload::CollationSeconded(*relay_parent, statement.clone().into()),
+			AllMessages::DisputeCoordinator(DisputeCoordinatorMessage::IssueLocalStatement(
+				session,
+				candidate_hash,
+				candidate,
+				valid,
+			)) => RecordedPayload::IssueLocalStatement(
+				*session,
+				*candidate_hash,
+				candidate.clone(),
+				*valid,
+			),
+			AllMessages::DisputeDistribution(DisputeDistributionMessage::SendDispute(dispute)) =>
+				RecordedPayload::SendDispute(dispute.clone().into()),
+			AllMessages::BitfieldDistribution(BitfieldDistributionMessage::DistributeBitfield(
+				relay_parent,
+				bitfield,
+			)) => RecordedPayload::DistributeBitfield(*relay_parent, bitfield.clone().into()),
+			AllMessages::StatementDistribution(StatementDistributionMessage::Share(
+				relay_parent,
+				statement,
+			)) => RecordedPayload::ShareStatement(*relay_parent, statement.clone().into()),
+			AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(relay_parent, data)) =>
+				match data {
+					ProvisionableData::Bitfield(hash, bitfield) =>
+						RecordedPayload::ProvisionBitfield(
+							*relay_parent,
+							*hash,
+							bitfield.clone().into(),
+						),
+					ProvisionableData::BackedCandidate(candidate) =>
+						RecordedPayload::ProvisionBackedCandidate(*relay_parent, candidate.clone()),
+					ProvisionableData::Dispute(hash, signature) =>
+						RecordedPayload::ProvisionDispute(*relay_parent, *hash, signature.clone()),
+					ProvisionableData::MisbehaviorReport(..) => RecordedPayload::Opaque,
+				},
+			_ => RecordedPayload::Opaque,
+		}
+	}
+
+	/// Recreate the message, or `None` if the payload was not captured or carries unchecked
+	/// signatures.
+	pub fn into_message(self) -> Option<AllMessages> {
+		match self {
+			RecordedPayload::Opaque |
+			RecordedPayload::BackingStatement(..) |
+			RecordedPayload::CollationSeconded(..) |
+			RecordedPayload::SendDispute(_) |
+			RecordedPayload::DistributeBitfield(..) |
+			RecordedPayload::ShareStatement(..) |
+			RecordedPayload::ProvisionBitfield(..) => None,
+			RecordedPayload::ChainSelectionApproved(hash) =>
+				Some(ChainSelectionMessage::Approved(hash).into()),
+			RecordedPayload::DistributeAssignment(cert, candidate_index) => Some(
+				ApprovalDistributionMessage::DistributeAssignment(cert, candidate_index).into(),
+			),
+			RecordedPayload::DistributeApproval(vote) =>
+				Some(ApprovalDistributionMessage::DistributeApproval(vote).into()),
+			RecordedPayload::Second(relay_parent, candidate, pov) =>
+				Some(CandidateBackingMessage::Second(relay_parent, candidate, pov).into()),
+			RecordedPayload::CollateOn(para_id) =>
+				Some(CollatorProtocolMessage::CollateOn(para_id).into()),
+			RecordedPayload::DistributeCollation(candidate, pov) =>
+				Some(CollatorProtocolMessage::DistributeCollation(candidate, pov, None).into()),
+			RecordedPayload::ReportCollator(collator) =>
+				Some(CollatorProtocolMessage::ReportCollator(collator).into()),
+			RecordedPayload::CollationInvalid(relay_parent, candidate) =>
+				Some(CollatorProtocolMessage::Invalid(relay_parent, candidate).into()),
+			RecordedPayload::IssueLocalStatement(session, candidate_hash, candidate, valid) =>
+				Some(
+					DisputeCoordinatorMessage::IssueLocalStatement(
+						session,
+						candidate_hash,
+						candidate,
+						valid,
+					)
+					.into(),
+				),
+			RecordedPayload::ProvisionBackedCandidate(relay_parent, candidate) => Some(
+				ProvisionerMessage::ProvisionableData(
+					relay_parent,
+					ProvisionableData::BackedCandidate(candidate),
+				)
+				.into(),
+			),
+			RecordedPayload::ProvisionDispute(relay_parent, hash, signature) => Some(
+				ProvisionerMessage::ProvisionableData(
+					relay_parent,
+					ProvisionableData::Dispute(hash, signature),
+				)
+				.into(),
+			),
+		}
+	}
+}
+
+/// Records every message and signal delivered by the overseer.
+///
+/// Records are handed to the [`RecordWriter`] through a queue of [`RECORD_QUEUE_SIZE`]
+/// records. Delivery never waits for the writer; records arriving while the queue is full are
+/// dropped.
+pub struct MessageRecorder {
+	start: Instant,
+	records: SyncSender<Record>,
+	dropped: AtomicU64,
+}
+
+impl fmt::Debug for MessageRecorder {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("MessageRecorder")
+			.field("start", &self.start)
+			.field("dropped", &self.dropped)
+			.finish()
+	}
+}
+
+impl MessageRecorder {
+	/// Create a recorder appending to the given sink.
+	///
+	/// The returned writer has to be run for records to reach the sink.
+	pub fn new(sink: impl Write + Send + 'static) -> (Self, RecordWriter) {
+		let (tx, rx) = mpsc::sync_channel(RECORD_QUEUE_SIZE);
+		let recorder = Self { start: Instant::now(), records: tx, dropped: AtomicU64::new(0) };
+		(recorder, RecordWriter { records: rx, sink: Box::new(sink) })
+	}
+
+	/// Create a recorder writing to a new file at `path`, truncating any existing one.
+	pub fn create(path: &Path) -> io::Result<(Self, RecordWriter)> {
+		Ok(Self::new(BufWriter::new(File::create(path)?)))
+	}
+
+	/// Number of records dropped because the writer fell behind.
+	pub fn dropped(&self) -> u64 {
+		self.dropped.load(Ordering::Relaxed)
+	}
+
+	fn append(&self, event: RecordedEvent) {
+		let record = Record { at: self.start.elapsed().as_micros() as u64, event };
+		match self.records.try_send(record) {
+			Ok(()) => {},
+			Err(TrySendError::Full(_)) =>
+				if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+					gum::warn!(
+						target: LOG_TARGET,
+						"Message log writer fell behind, dropping records",
+					);
+				},
+			Err(TrySendError::Disconnected(_)) => {
+				gum::debug!(target: LOG_TARGET, "Message log writer is gone");
+			},
+		}
+	}
+}
+
+impl MessageObserver<AllMessages, OverseerSignal> for MessageRecorder {
+	fn on_message(&self, origin: &'static str, destination: &'static str, message: &AllMessages) {
+		self.append(RecordedEvent::Message {
+			origin: origin.to_owned(),
+			destination: destination.to_owned(),
+			payload: RecordedPayload::capture(message),
+		});
+	}
+
+	fn on_signal(&self, signal: &OverseerSignal) {
+		self.append(RecordedEvent::Signal(signal.into()));
+	}
+}
+
+/// Writes the records queued by a [`MessageRecorder`] to the sink.
+pub struct RecordWriter {
+	records: Receiver<Record>,
+	sink: Box<dyn Write + Send>,
+}
+
+impl RecordWriter {
+	/// Write records until the recorder is dropped.
+	///
+	/// Blocks on the queue and the sink, hence has to run on a dedicated thread. The sink is
+	/// flushed whenever the queue runs empty.
+	pub fn run(mut self) {
+		while let Ok(record) = self.records.recv() {
+			self.write(record);
+			while let Ok(record) = self.records.try_recv() {
+				self.write(record);
+			}
+			if let Err(err) = self.sink.flush() {
+				gum::warn!(target: LOG_TARGET, ?err, "Failed to flush the message log");
+			}
+		}
+	}
+
+	fn write(&mut self, record: Record) {
+		if let Err(err) = self.sink.write_all(&record.encode().encode()) {
+			gum::warn!(target: LOG_TARGET, ?err, "Failed to append to the message log");
+		}
+	}
+}
+
+/// Read all complete records from a log written by a [`MessageRecorder`].
+///
+/// A truncated last record is ignored, a corrupted one is an error.
+pub fn read_records(mut input: impl Read) -> io::Result<Vec<Record>> {
+	let mut bytes = Vec::new();
+	input.read_to_end(&mut bytes)?;
+
+	let mut remaining = &bytes[..];
+	let mut records = Vec::new();
+	while !remaining.is_empty() {
+		let encoded = match Vec::<u8>::decode(&mut remaining) {
+			Ok(encoded) => encoded,
+			Err(_) => break,
+		};
+		let record = Record::decode(&mut &encoded[..])
+			.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+		records.push(record);
+	}
+
+	Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ::test_helpers::{dummy_candidate_receipt, dummy_hash};
+	use parking_lot::Mutex;
+
+	#[derive(Clone, Default)]
+	struct SharedSink(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for SharedSink {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn records_round_trip() {
+		let sink = SharedSink::default();
+		let (recorder, writer) = MessageRecorder::new(sink.clone());
+
+		let leaf = Hash::repeat_byte(1);
+		recorder.on_signal(&OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(
+			ActivatedLeaf {
+				hash: leaf,
+				number: 1,
+				status: LeafStatus::Fresh,
+				span: Arc::new(jaeger::Span::Disabled),
+			},
+		)));
+		recorder.on_message(
+			"approval-voting",
+			"chain-selection",
+			&ChainSelectionMessage::Approved(leaf).into(),
+		);
+		recorder.on_message(
+			"collation-generation",
+			"collator-protocol",
+			&CollatorProtocolMessage::CollateOn(ParaId::from(1)).into(),
+		);
+		recorder.on_message(
+			"collation-generation",
+			"collator-protocol",
+			&CollatorProtocolMessage::DistributeCollation(
+				dummy_candidate_receipt(dummy_hash()),
+				PoV { block_data: Default::default() },
+				Some(futures::channel::oneshot::channel().0),
+			)
+			.into(),
+		);
+		recorder.on_signal(&OverseerSignal::Conclude);
+		drop(recorder);
+		writer.run();
+
+		let records = read_records(&sink.0.lock()[..]).unwrap();
+		let events = records.into_iter().map(|record| record.event).collect::<Vec<_>>();
+		assert_eq!(
+			events,
+			vec![
+				RecordedEvent::Signal(RecordedSignal::ActiveLeaves {
+					activated: Some(RecordedLeaf { hash: leaf, number: 1, fresh: true }),
+					deactivated: Vec::new(),
+				}),
+				RecordedEvent::Message {
+					origin: "approval-voting".to_owned(),
+					destination: "chain-selection".to_owned(),
+					payload: RecordedPayload::ChainSelectionApproved(leaf),
+				},
+				RecordedEvent::Message {
+					origin: "collation-generation".to_owned(),
+					destination: "collator-protocol".to_owned(),
+					payload: RecordedPayload::CollateOn(ParaId::from(1)),
+				},
+				RecordedEvent::Message {
+					origin: "collation-generation".to_owned(),
+					destination: "collator-protocol".to_owned(),
+					payload: RecordedPayload::Opaque,
+				},
+				RecordedEvent::Signal(RecordedSignal::Conclude),
+			],
+		);
+	}
+
+	#[test]
+	fn truncated_record_is_ignored() {
+		let sink = SharedSink::default();
+		let (recorder, writer) = MessageRecorder::new(sink.clone());
+		recorder.on_signal(&OverseerSignal::BlockFinalized(Hash::repeat_byte(2), 2));
+		recorder.on_signal(&OverseerSignal::Conclude);
+		drop(recorder);
+		writer.run();
+
+		let bytes = sink.0.lock().clone();
+		let records = read_records(&bytes[..bytes.len() - 1]).unwrap();
+		assert_eq!(records.len(), 1);
+		assert_eq!(
+			records[0].event,
+			RecordedEvent::Signal(RecordedSignal::BlockFinalized(Hash::repeat_byte(2), 2)),
+		);
+	}
+
+	#[test]
+	fn captured_payloads_recreate_messages() {
+		let candidate = dummy_candidate_receipt(dummy_hash());
+		let message = DisputeCoordinatorMessage::IssueLocalStatement(
+			1,
+			candidate.hash(),
+			candidate.clone(),
+			false,
+		)
+		.into();
+		let payload = RecordedPayload::capture(&message);
+		assert_eq!(
+			payload,
+			RecordedPayload::IssueLocalStatement(1, candidate.hash(), candidate.clone(), false),
+		);
+
+		let decoded = RecordedPayload::decode(&mut &payload.encode()[..]).unwrap();
+		assert!(matches!(
+			decoded.into_message(),
+			Some(AllMessages::DisputeCoordinator(DisputeCoordinatorMessage::IssueLocalStatement(
+				1, hash, _, false,
+			))) if hash == candidate.hash()
+		));
+	}
+
+	#[test]
+	fn records_are_dropped_when_the_writer_falls_behind() {
+		let sink = SharedSink::default();
+		let (recorder, writer) = MessageRecorder::new(sink.clone());
+		for _ in 0..=RECORD_QUEUE_SIZE {
+			recorder.on_signal(&OverseerSignal::BlockFinalized(Hash::repeat_byte(3), 3));
+		}
+		assert_eq!(recorder.dropped(), 1);
+
+		drop(recorder);
+		writer.run();
+		assert_eq!(read_records(&sink.0.lock()[..]).unwrap().len(), RECORD_QUEUE_SIZE);
+	}
+}
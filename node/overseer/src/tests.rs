@@ -265,6 +265,50 @@ fn overseer_metrics_work() {
 	});
 }
 
+#[test]
+fn overseer_reports_subsystem_polls() {
+	let spawner = sp_core::testing::TaskExecutor::new();
+
+	executor::block_on(async move {
+		let registry = prometheus::Registry::new();
+		let poll_metrics = PollMetrics::register(&registry, Duration::ZERO).unwrap();
+		let (overseer, handle) = dummy_overseer_builder(spawner, MockSupportsParachains, None)
+			.unwrap()
+			.leaves(Vec::new())
+			.poll_observer(Arc::new(poll_metrics))
+			.build()
+			.unwrap();
+
+		let mut handle = Handle::new(handle);
+		let overseer_fut = overseer.run().fuse();
+
+		pin_mut!(overseer_fut);
+
+		handle
+			.send_msg_anon(AllMessages::CandidateValidation(test_candidate_validation_msg()))
+			.await;
+		handle.stop().await;
+
+		select! {
+			res = overseer_fut => {
+				assert!(res.is_ok());
+				let gather = registry.gather();
+				let slow_polls = gather
+					.iter()
+					.find(|mf| mf.get_name() == "polkadot_parachain_subsystem_slow_polls_total")
+					.expect("Must contain the slow polls metric");
+				assert!(slow_polls.get_metric().iter().any(|metric| {
+					metric.get_label().iter().any(|label| {
+						label.get_name() == "subsystem_name" &&
+							label.get_value() == "candidate-validation"
+					}) && metric.get_counter().get_value() > 0.0
+				}));
+			},
+			complete => (),
+		}
+	});
+}
+
 fn extract_metrics(registry: &prometheus::Registry) -> HashMap<&'static str, u64> {
 	let gather = registry.gather();
 	assert!(!gather.is_empty(), "Gathered metrics are not empty. qed");
@@ -1137,6 +1181,8 @@ fn context_holds_onto_message_until_enough_signals_received() {
 		dispute_distribution_unbounded: dispute_distribution_unbounded_tx.clone(),
 		chain_selection_unbounded: chain_selection_unbounded_tx.clone(),
 		pvf_checker_unbounded: pvf_checker_unbounded_tx.clone(),
+
+		message_observer: None,
 	};
 
 	let (mut signal_tx, signal_rx) = metered::channel(CHANNEL_CAPACITY);
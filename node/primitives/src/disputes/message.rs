@@ -37,7 +37,7 @@ use polkadot_primitives::v2::{
 pub struct DisputeMessage(UncheckedDisputeMessage);
 
 /// A `DisputeMessage` where signatures of statements have not yet been checked.
-#[derive(Clone, Encode, Decode, Debug)]
+#[derive(Clone, PartialEq, Encode, Decode, Debug)]
 pub struct UncheckedDisputeMessage {
 	/// The candidate being disputed.
 	pub candidate_receipt: CandidateReceipt,
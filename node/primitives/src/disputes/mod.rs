@@ -211,7 +211,7 @@ impl SignedDisputeStatement {
 }
 
 /// Any invalid vote (currently only explicit).
-#[derive(Clone, Encode, Decode, Debug)]
+#[derive(Clone, PartialEq, Encode, Decode, Debug)]
 pub struct InvalidDisputeVote {
 	/// The voting validator index.
 	pub validator_index: ValidatorIndex,
@@ -225,7 +225,7 @@ pub struct InvalidDisputeVote {
 }
 
 /// Any valid vote (backing, approval, explicit).
-#[derive(Clone, Encode, Decode, Debug)]
+#[derive(Clone, PartialEq, Encode, Decode, Debug)]
 pub struct ValidDisputeVote {
 	/// The voting validator index.
 	pub validator_index: ValidatorIndex,
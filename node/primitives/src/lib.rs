@@ -56,6 +56,9 @@ pub use disputes::{
 const MERKLE_NODE_MAX_SIZE: usize = 512 + 100;
 // 16-ary Merkle Prefix Trie for 32-bit ValidatorIndex has depth at most 8.
 const MERKLE_PROOF_MAX_DEPTH: usize = 8;
+// A multi proof holds at most all nodes of the trie. Leaves hold the chunk hashes and every branch
+// node has at least two children, so 4096 nodes cover all branches of up to 2048 chunks.
+const MERKLE_MULTI_PROOF_MAX_NODES: usize = 4096;
 
 /// The bomb limit for decompressing code blobs.
 pub const VALIDATION_CODE_BOMB_LIMIT: usize = (MAX_CODE_SIZE * 4u32) as usize;
@@ -416,6 +419,14 @@ pub enum MerkleProofError {
 	#[error("Merkle node max size exceeded {0} > {} .", MERKLE_NODE_MAX_SIZE)]
 	/// This error signifies that a Proof node exceeds the 16-ary max node size
 	MerkleProofNodeSizeExceeded(usize),
+
+	#[error("Merkle multi proof max node count exceeded {0} > {} .", MERKLE_MULTI_PROOF_MAX_NODES)]
+	/// This error signifies that a [`MultiProof`] holds more nodes than any trie could have
+	MerkleMultiProofNodeCountExceeded(usize),
+
+	#[error("Merkle proof is empty.")]
+	/// This error signifies that a proof without any nodes was given
+	MerkleProofEmpty,
 }
 
 impl TryFrom<Vec<Vec<u8>>> for Proof {
@@ -484,6 +495,87 @@ impl<'de> Deserialize<'de> for Proof {
 	}
 }
 
+/// The nodes of the Merkle branches of several erasure chunks, batched into a single proof.
+///
+/// Nodes shared by several branches (e.g. the root) are only included once, so a multi proof for a
+/// set of chunks is considerably smaller than the individual [`Proof`]s of these chunks.
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+pub struct MultiProof(
+	BoundedVec<BoundedVec<u8, 1, MERKLE_NODE_MAX_SIZE>, 1, MERKLE_MULTI_PROOF_MAX_NODES>,
+);
+
+impl MultiProof {
+	/// Iterate over the distinct trie nodes of this proof.
+	pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+		self.0.iter().map(|v| v.as_slice())
+	}
+
+	/// Merge the proofs of single chunks into a multi proof.
+	///
+	/// All proofs are expected to be for the same root, nodes occurring in several of them are
+	/// only kept once.
+	pub fn from_proofs<'a>(
+		proofs: impl IntoIterator<Item = &'a Proof>,
+	) -> Result<Self, MerkleProofError> {
+		let mut seen = std::collections::HashSet::new();
+		let nodes = proofs
+			.into_iter()
+			.flat_map(|proof| proof.iter())
+			.filter(|node| seen.insert(*node))
+			.map(|node| node.to_vec())
+			.collect::<Vec<_>>();
+		Self::try_from(nodes)
+	}
+}
+
+impl TryFrom<Vec<Vec<u8>>> for MultiProof {
+	type Error = MerkleProofError;
+
+	fn try_from(input: Vec<Vec<u8>>) -> Result<Self, Self::Error> {
+		if input.is_empty() {
+			return Err(Self::Error::MerkleProofEmpty)
+		}
+		if input.len() > MERKLE_MULTI_PROOF_MAX_NODES {
+			return Err(Self::Error::MerkleMultiProofNodeCountExceeded(input.len()))
+		}
+		let mut out = Vec::with_capacity(input.len());
+		for element in input.into_iter() {
+			let length = element.len();
+			let data: BoundedVec<u8, 1, MERKLE_NODE_MAX_SIZE> = BoundedVec::from_vec(element)
+				.map_err(|_| Self::Error::MerkleProofNodeSizeExceeded(length))?;
+			out.push(data);
+		}
+		Ok(MultiProof(BoundedVec::from_vec(out).expect("Node count is checked above. qed")))
+	}
+}
+
+impl Decode for MultiProof {
+	fn decode<I: Input>(value: &mut I) -> Result<Self, CodecError> {
+		let temp: Vec<Vec<u8>> = Decode::decode(value)?;
+		let mut out = Vec::new();
+		for element in temp.into_iter() {
+			let bounded_temp: Result<BoundedVec<u8, 1, MERKLE_NODE_MAX_SIZE>, CodecError> =
+				BoundedVec::from_vec(element)
+					.map_err(|_| "Inner node exceeds maximum node size.".into());
+			out.push(bounded_temp?);
+		}
+		BoundedVec::from_vec(out)
+			.map(Self)
+			.map_err(|_| "Merkle multi proof node count is out of bounds".into())
+	}
+}
+
+impl Encode for MultiProof {
+	fn size_hint(&self) -> usize {
+		MERKLE_NODE_MAX_SIZE * self.0.len()
+	}
+
+	fn using_encoded<R, F: FnOnce(&[u8]) -> R>(&self, f: F) -> R {
+		let temp = self.0.iter().map(|v| v.as_vec()).collect::<Vec<_>>();
+		temp.using_encoded(f)
+	}
+}
+
 /// A chunk of erasure-encoded block data.
 #[derive(PartialEq, Eq, Clone, Encode, Decode, Serialize, Deserialize, Debug, Hash)]
 pub struct ErasureChunk {
@@ -20,7 +20,7 @@
 
 pub mod chain_spec;
 mod grandpa_support;
-mod parachains_db;
+pub mod parachains_db;
 mod relay_chain_selection;
 
 #[cfg(feature = "full-node")]
@@ -36,6 +36,7 @@ mod tests;
 use {
 	grandpa::{self, FinalityProofProvider as GrandpaFinalityProofProvider},
 	gum::info,
+	polkadot_collator_protocol::{CollatorLists, ReputationConfig as CollatorReputationConfig},
 	polkadot_node_core_approval_voting::{
 		self as approval_voting_subsystem, Config as ApprovalVotingConfig,
 	},
@@ -57,9 +58,12 @@ use polkadot_node_subsystem_util::database::Database;
 
 #[cfg(feature = "full-node")]
 pub use {
+	polkadot_node_core_candidate_validation::PvfWorkerCounts,
 	polkadot_overseer::{Handle, Overseer, OverseerConnector, OverseerHandle},
 	polkadot_primitives::runtime_api::ParachainHost,
-	relay_chain_selection::SelectRelayChain,
+	relay_chain_selection::{
+		DisputeCoordinatorFallback, FinalityPolicy, SelectRelayChain, HOLD_BACK_DEPTH_RANGE,
+	},
 	sc_client_api::AuxStore,
 	sp_authority_discovery::AuthorityDiscoveryApi,
 	sp_blockchain::{HeaderBackend, HeaderMetadata},
@@ -67,7 +71,7 @@ pub use {
 };
 
 #[cfg(feature = "full-node")]
-use polkadot_node_subsystem::jaeger;
+pub use polkadot_node_subsystem::jaeger;
 
 use std::{sync::Arc, time::Duration};
 
@@ -229,6 +233,10 @@ pub enum Error {
 	#[error(transparent)]
 	Availability(#[from] AvailabilityError),
 
+	#[cfg(feature = "full-node")]
+	#[error("Failed to load the collator lists: {0}")]
+	CollatorLists(std::io::Error),
+
 	#[error("Authorities require the real overseer implementation")]
 	AuthoritiesRequireRealOverseer,
 
@@ -291,47 +299,29 @@ impl IdentifyVariant for Box<dyn ChainSpec> {
 
 #[cfg(feature = "full-node")]
 fn open_database(db_source: &DatabaseSource) -> Result<Arc<dyn Database>, Error> {
-	let parachains_db = match db_source {
-		DatabaseSource::RocksDb { path, .. } => parachains_db::open_creating_rocksdb(
-			path.clone(),
-			parachains_db::CacheSizes::default(),
-		)?,
-		DatabaseSource::ParityDb { path, .. } => parachains_db::open_creating_paritydb(
-			path.parent().ok_or(Error::DatabasePathRequired)?.into(),
-			parachains_db::CacheSizes::default(),
-		)?,
-		DatabaseSource::Auto { paritydb_path, rocksdb_path, .. } =>
-			if paritydb_path.is_dir() && paritydb_path.exists() {
-				parachains_db::open_creating_paritydb(
-					paritydb_path.parent().ok_or(Error::DatabasePathRequired)?.into(),
-					parachains_db::CacheSizes::default(),
-				)?
-			} else {
-				parachains_db::open_creating_rocksdb(
-					rocksdb_path.clone(),
-					parachains_db::CacheSizes::default(),
-				)?
-			},
-		DatabaseSource::Custom { .. } => {
-			unimplemented!("No polkadot subsystem db for custom source.");
+	let parachains_db = match parachains_db::locate(db_source)? {
+		(root, parachains_db::DatabaseKind::RocksDB) => {
+			parachains_db::migrate_from_paritydb_if_needed(db_source, &root)?;
+			parachains_db::open_creating_rocksdb(root, parachains_db::CacheSizes::default())?
+		},
+		(root, parachains_db::DatabaseKind::ParityDB) => {
+			parachains_db::migrate_from_rocksdb_if_needed(db_source, &root)?;
+			parachains_db::open_creating_paritydb(root, parachains_db::CacheSizes::default())?
 		},
 	};
 	Ok(parachains_db)
 }
 
-/// Initialize the `Jeager` collector. The destination must listen
-/// on the given address and port for `UDP` packets.
+/// Initialize the `Jeager` collector. The destination is either a jaeger agent
+/// listening for `UDP` packets or an OpenTelemetry collector accepting `OTLP`.
 #[cfg(any(test, feature = "full-node"))]
-fn jaeger_launch_collector_with_agent(
+fn jaeger_launch_collector(
 	spawner: impl SpawnNamed,
 	config: &Configuration,
-	agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 ) -> Result<(), Error> {
-	if let Some(agent) = agent {
-		let cfg = jaeger::JaegerConfig::builder()
-			.agent(agent)
-			.named(&config.network.node_name)
-			.build();
+	if let Some(jaeger_config) = jaeger_config {
+		let cfg = jaeger_config.named(&config.network.node_name).build();
 
 		jaeger::Jaeger::new(cfg).launch(spawner)?;
 	}
@@ -378,7 +368,7 @@ where
 #[cfg(feature = "full-node")]
 fn new_partial_basics<RuntimeApi, ExecutorDispatch>(
 	config: &mut Configuration,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 ) -> Result<Basics<RuntimeApi, ExecutorDispatch>, Error>
 where
@@ -433,7 +423,7 @@ where
 		telemetry
 	});
 
-	jaeger_launch_collector_with_agent(task_manager.spawn_handle(), &*config, jaeger_agent)?;
+	jaeger_launch_collector(task_manager.spawn_handle(), &*config, jaeger_config)?;
 
 	Ok(Basics { task_manager, client, backend, keystore_container, telemetry })
 }
@@ -446,6 +436,7 @@ fn new_partial<RuntimeApi, ExecutorDispatch, ChainSelection>(
 		ExecutorDispatch,
 	>,
 	select_chain: ChainSelection,
+	overseer_handle: Option<Handle>,
 ) -> Result<
 	service::PartialComponents<
 		FullClient<RuntimeApi, ExecutorDispatch>,
@@ -593,8 +584,11 @@ where
 				beefy: polkadot_rpc::BeefyDeps {
 					beefy_finality_proof_stream: beefy_rpc_links.from_voter_justif_stream.clone(),
 					beefy_best_block_stream: beefy_rpc_links.from_voter_best_beefy_stream.clone(),
-					subscription_executor,
+					subscription_executor: subscription_executor.clone(),
 				},
+				parachain: overseer_handle.clone().map(|overseer_handle| {
+					polkadot_rpc::ParachainDeps { overseer_handle, subscription_executor }
+				}),
 			};
 
 			polkadot_rpc::create_full(deps, backend.clone()).map_err(Into::into)
@@ -638,6 +632,26 @@ impl<C> NewFull<C> {
 	}
 }
 
+/// Node settings of a full node beyond the `Configuration`, see [`new_full`].
+#[cfg(feature = "full-node")]
+#[derive(Debug, Clone, Default)]
+pub struct NewFullParams {
+	/// The number of PVF preparation and execution workers.
+	pub pvf_workers: PvfWorkerCounts,
+	/// Whether PVF jobs are prevented from accessing the file system and the network.
+	pub pvf_forbid_job_syscalls: bool,
+	/// The budget of the runtime API cache in bytes, `None` uses the default.
+	pub runtime_api_cache_budget: Option<usize>,
+	/// Record all messages passing the overseer to the file at the given path.
+	pub overseer_message_log: Option<std::path::PathBuf>,
+	/// Collect per-subsystem poll time metrics, counting longer polls as slow.
+	pub subsystem_slow_poll_threshold: Option<Duration>,
+	/// A JSON file with the collators allowed and denied to collate for each para.
+	pub collator_lists: Option<std::path::PathBuf>,
+	/// The safety policy applied to finality votes.
+	pub finality_policy: FinalityPolicy,
+}
+
 /// Is this node a collator?
 #[cfg(feature = "full-node")]
 #[derive(Clone)]
@@ -731,7 +745,7 @@ pub fn new_full<RuntimeApi, ExecutorDispatch, OverseerGenerator>(
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
 	enable_beefy: bool,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 	program_path: Option<std::path::PathBuf>,
 	overseer_enable_anyways: bool,
@@ -739,6 +753,7 @@ pub fn new_full<RuntimeApi, ExecutorDispatch, OverseerGenerator>(
 	overseer_message_channel_capacity_override: Option<usize>,
 	_malus_finality_delay: Option<u32>,
 	hwbench: Option<sc_sysinfo::HwBench>,
+	params: NewFullParams,
 ) -> Result<NewFull<Arc<FullClient<RuntimeApi, ExecutorDispatch>>>, Error>
 where
 	RuntimeApi: ConstructRuntimeApi<Block, FullClient<RuntimeApi, ExecutorDispatch>>
@@ -752,6 +767,16 @@ where
 {
 	use polkadot_node_network_protocol::request_response::IncomingRequest;
 
+	let NewFullParams {
+		pvf_workers,
+		pvf_forbid_job_syscalls,
+		runtime_api_cache_budget,
+		overseer_message_log,
+		subsystem_slow_poll_threshold,
+		collator_lists,
+		finality_policy,
+	} = params;
+
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks = {
@@ -784,7 +809,7 @@ where
 
 	let basics = new_partial_basics::<RuntimeApi, ExecutorDispatch>(
 		&mut config,
-		jaeger_agent,
+		jaeger_config,
 		telemetry_worker_handle,
 	)?;
 
@@ -798,6 +823,8 @@ where
 	let local_keystore = basics.keystore_container.local_keystore();
 	let auth_or_collator = role.is_authority() || is_collator.is_collator();
 	let requires_overseer_for_chain_sel = local_keystore.is_some() && auth_or_collator;
+	// Mirrors the conditions under which the overseer is spawned below.
+	let runs_overseer = local_keystore.is_some() && (auth_or_collator || overseer_enable_anyways);
 
 	let pvf_checker_enabled = !is_collator.is_collator() && chain_spec.is_versi();
 
@@ -809,6 +836,7 @@ where
 			basics.backend.clone(),
 			overseer_handle.clone(),
 			metrics,
+			finality_policy,
 		)
 	} else {
 		SelectRelayChain::new_longest_chain(basics.backend.clone())
@@ -827,6 +855,7 @@ where
 		&mut config,
 		basics,
 		select_chain,
+		runs_overseer.then(|| overseer_handle.clone()),
 	)?;
 
 	let shared_voter_state = rpc_setup;
@@ -864,6 +893,8 @@ where
 	config.network.request_response_protocols.push(cfg);
 	let (chunk_req_receiver, cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	config.network.request_response_protocols.push(cfg);
+	let (chunk_req_v2_receiver, cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
+	config.network.request_response_protocols.push(cfg);
 	let (collation_req_receiver, cfg) = IncomingRequest::get_config_receiver(&req_protocol_names);
 	config.network.request_response_protocols.push(cfg);
 	let (available_data_req_receiver, cfg) =
@@ -926,6 +957,7 @@ where
 
 	let approval_voting_config = ApprovalVotingConfig {
 		col_data: parachains_db::REAL_COLUMNS.col_approval_data,
+		col_session_data: parachains_db::REAL_COLUMNS.col_session_window_data,
 		slot_duration_millis: slot_duration.as_millis() as u64,
 	};
 
@@ -939,6 +971,8 @@ where
 			None => std::env::current_exe()?,
 			Some(p) => p,
 		},
+		pvf_workers,
+		forbid_job_syscalls: pvf_forbid_job_syscalls,
 	};
 
 	let chain_selection_config = ChainSelectionConfig {
@@ -949,6 +983,16 @@ where
 
 	let dispute_coordinator_config = DisputeCoordinatorConfig {
 		col_data: parachains_db::REAL_COLUMNS.col_dispute_coordinator_data,
+		col_session_data: parachains_db::REAL_COLUMNS.col_session_window_data,
+		participation_queue: Default::default(),
+	};
+
+	let collator_reputation_config = CollatorReputationConfig {
+		col_data: parachains_db::REAL_COLUMNS.col_collator_reputation_data,
+		collator_lists: match collator_lists {
+			Some(path) => CollatorLists::from_file(&path).map_err(Error::CollatorLists)?,
+			None => Default::default(),
+		},
 	};
 
 	let rpc_handlers = service::spawn_tasks(service::SpawnTasksParams {
@@ -1035,6 +1079,19 @@ where
 	let maybe_params =
 		local_keystore.and_then(move |k| authority_discovery_service.map(|a| (a, k)));
 
+	let overseer_message_recorder = match overseer_message_log {
+		Some(path) => {
+			let (recorder, writer) = polkadot_overseer::MessageRecorder::create(&path)?;
+			task_manager.spawn_handle().spawn_blocking(
+				"overseer-message-recorder",
+				None,
+				async move { writer.run() },
+			);
+			Some(Arc::new(recorder))
+		},
+		None => None,
+	};
+
 	let overseer_handle = if let Some((authority_discovery_service, keystore)) = maybe_params {
 		let (overseer, overseer_handle) = overseer_gen
 			.generate::<service::SpawnTaskHandle, FullClient<RuntimeApi, ExecutorDispatch>>(
@@ -1048,6 +1105,7 @@ where
 					authority_discovery_service,
 					pov_req_receiver,
 					chunk_req_receiver,
+					chunk_req_v2_receiver,
 					collation_req_receiver,
 					available_data_req_receiver,
 					statement_req_receiver,
@@ -1062,6 +1120,11 @@ where
 					dispute_coordinator_config,
 					pvf_checker_enabled,
 					overseer_message_channel_capacity_override,
+					runtime_api_cache_budget: runtime_api_cache_budget
+						.unwrap_or(polkadot_node_core_runtime_api::DEFAULT_CACHE_BUDGET),
+					overseer_message_recorder,
+					subsystem_slow_poll_threshold,
+					collator_reputation_config,
 					req_protocol_names,
 				},
 			)
@@ -1280,13 +1343,13 @@ where
 
 #[cfg(feature = "full-node")]
 macro_rules! chain_ops {
-	($config:expr, $jaeger_agent:expr, $telemetry_worker_handle:expr; $scope:ident, $executor:ident, $variant:ident) => {{
+	($config:expr, $jaeger_config:expr, $telemetry_worker_handle:expr; $scope:ident, $executor:ident, $variant:ident) => {{
 		let telemetry_worker_handle = $telemetry_worker_handle;
-		let jaeger_agent = $jaeger_agent;
+		let jaeger_config = $jaeger_config;
 		let mut config = $config;
 		let basics = new_partial_basics::<$scope::RuntimeApi, $executor>(
 			config,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 		)?;
 
@@ -1299,6 +1362,7 @@ macro_rules! chain_ops {
 				&mut config,
 				basics,
 				chain_selection,
+				None,
 			)?;
 		Ok((Arc::new(Client::$variant(client)), backend, import_queue, task_manager))
 	}};
@@ -1308,7 +1372,7 @@ macro_rules! chain_ops {
 #[cfg(feature = "full-node")]
 pub fn new_chain_ops(
 	mut config: &mut Configuration,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 ) -> Result<
 	(
 		Arc<Client>,
@@ -1327,22 +1391,22 @@ pub fn new_chain_ops(
 		config.chain_spec.is_wococo() ||
 		config.chain_spec.is_versi()
 	{
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; rococo_runtime, RococoExecutorDispatch, Rococo)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; rococo_runtime, RococoExecutorDispatch, Rococo)
 	}
 
 	#[cfg(feature = "kusama-native")]
 	if config.chain_spec.is_kusama() {
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; kusama_runtime, KusamaExecutorDispatch, Kusama)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; kusama_runtime, KusamaExecutorDispatch, Kusama)
 	}
 
 	#[cfg(feature = "westend-native")]
 	if config.chain_spec.is_westend() {
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; westend_runtime, WestendExecutorDispatch, Westend)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; westend_runtime, WestendExecutorDispatch, Westend)
 	}
 
 	#[cfg(feature = "polkadot-native")]
 	{
-		return chain_ops!(config, jaeger_agent, telemetry_worker_handle; polkadot_runtime, PolkadotExecutorDispatch, Polkadot)
+		return chain_ops!(config, jaeger_config, telemetry_worker_handle; polkadot_runtime, PolkadotExecutorDispatch, Polkadot)
 	}
 	#[cfg(not(feature = "polkadot-native"))]
 	Err(Error::NoRuntime)
@@ -1362,13 +1426,14 @@ pub fn build_full(
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
 	enable_beefy: bool,
-	jaeger_agent: Option<std::net::SocketAddr>,
+	jaeger_config: Option<jaeger::JaegerConfigBuilder>,
 	telemetry_worker_handle: Option<TelemetryWorkerHandle>,
 	overseer_enable_anyways: bool,
 	overseer_gen: impl OverseerGen,
 	overseer_message_channel_override: Option<usize>,
 	malus_finality_delay: Option<u32>,
 	hwbench: Option<sc_sysinfo::HwBench>,
+	params: NewFullParams,
 ) -> Result<NewFull<Client>, Error> {
 	#[cfg(feature = "rococo-native")]
 	if config.chain_spec.is_rococo() ||
@@ -1380,7 +1445,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			enable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_enable_anyways,
@@ -1388,6 +1453,7 @@ pub fn build_full(
 			overseer_message_channel_override,
 			malus_finality_delay,
 			hwbench,
+			params,
 		)
 		.map(|full| full.with_client(Client::Rococo))
 	}
@@ -1399,7 +1465,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			enable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_enable_anyways,
@@ -1407,6 +1473,7 @@ pub fn build_full(
 			overseer_message_channel_override,
 			malus_finality_delay,
 			hwbench,
+			params,
 		)
 		.map(|full| full.with_client(Client::Kusama))
 	}
@@ -1418,7 +1485,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			enable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_enable_anyways,
@@ -1426,6 +1493,7 @@ pub fn build_full(
 			overseer_message_channel_override,
 			malus_finality_delay,
 			hwbench,
+			params,
 		)
 		.map(|full| full.with_client(Client::Westend))
 	}
@@ -1437,7 +1505,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			enable_beefy,
-			jaeger_agent,
+			jaeger_config,
 			telemetry_worker_handle,
 			None,
 			overseer_enable_anyways,
@@ -1448,6 +1516,7 @@ pub fn build_full(
 			}),
 			malus_finality_delay,
 			hwbench,
+			params,
 		)
 		.map(|full| full.with_client(Client::Polkadot))
 	}
@@ -1513,6 +1582,7 @@ fn revert_chain_selection(db: Arc<dyn Database>, hash: Hash) -> sp_blockchain::R
 fn revert_approval_voting(db: Arc<dyn Database>, hash: Hash) -> sp_blockchain::Result<()> {
 	let config = approval_voting_subsystem::Config {
 		col_data: parachains_db::REAL_COLUMNS.col_approval_data,
+		col_session_data: parachains_db::REAL_COLUMNS.col_session_window_data,
 		slot_duration_millis: Default::default(),
 	};
 
@@ -19,13 +19,14 @@ use sp_core::traits::SpawnNamed;
 
 use lru::LruCache;
 use polkadot_availability_distribution::IncomingRequestReceivers;
+use polkadot_collator_protocol::ReputationConfig as CollatorReputationConfig;
 use polkadot_node_core_approval_voting::Config as ApprovalVotingConfig;
 use polkadot_node_core_av_store::Config as AvailabilityConfig;
 use polkadot_node_core_candidate_validation::Config as CandidateValidationConfig;
 use polkadot_node_core_chain_selection::Config as ChainSelectionConfig;
 use polkadot_node_core_dispute_coordinator::Config as DisputeCoordinatorConfig;
 use polkadot_node_network_protocol::request_response::{
-	v1 as request_v1, IncomingRequestReceiver, ReqProtocolNames,
+	v1 as request_v1, v2 as request_v2, IncomingRequestReceiver, ReqProtocolNames,
 };
 #[cfg(any(feature = "malus", test))]
 pub use polkadot_overseer::{
@@ -33,8 +34,8 @@ pub use polkadot_overseer::{
 	HeadSupportsParachains,
 };
 use polkadot_overseer::{
-	metrics::Metrics as OverseerMetrics, BlockInfo, InitializedOverseerBuilder, MetricsTrait,
-	Overseer, OverseerConnector, OverseerHandle, SpawnGlue,
+	metrics::Metrics as OverseerMetrics, BlockInfo, InitializedOverseerBuilder, MessageRecorder,
+	MetricsTrait, Overseer, OverseerConnector, OverseerHandle, PollMetrics, SpawnGlue,
 };
 
 use polkadot_primitives::runtime_api::ParachainHost;
@@ -45,7 +46,7 @@ use sc_network_common::service::NetworkStateInfo;
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_consensus_babe::BabeApi;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 pub use polkadot_approval_distribution::ApprovalDistribution as ApprovalDistributionSubsystem;
 pub use polkadot_availability_bitfield_distribution::BitfieldDistribution as BitfieldDistributionSubsystem;
@@ -95,6 +96,7 @@ where
 	/// POV request receiver
 	pub pov_req_receiver: IncomingRequestReceiver<request_v1::PoVFetchingRequest>,
 	pub chunk_req_receiver: IncomingRequestReceiver<request_v1::ChunkFetchingRequest>,
+	pub chunk_req_v2_receiver: IncomingRequestReceiver<request_v2::ChunkFetchingRequest>,
 	pub collation_req_receiver: IncomingRequestReceiver<request_v1::CollationFetchingRequest>,
 	pub available_data_req_receiver:
 		IncomingRequestReceiver<request_v1::AvailableDataFetchingRequest>,
@@ -120,6 +122,16 @@ where
 	pub pvf_checker_enabled: bool,
 	/// Overseer channel capacity override.
 	pub overseer_message_channel_capacity_override: Option<usize>,
+	/// Memory budget of the runtime API cache, in bytes.
+	pub runtime_api_cache_budget: usize,
+	/// Records all messages and signals delivered to subsystems, if set.
+	pub overseer_message_recorder: Option<Arc<MessageRecorder>>,
+	/// Polls of subsystem tasks taking longer are counted as slow.
+	///
+	/// Poll metrics are only collected if set.
+	pub subsystem_slow_poll_threshold: Option<Duration>,
+	/// Configuration of the collator reputation kept by validators.
+	pub collator_reputation_config: CollatorReputationConfig,
 	/// Request-response protocol names source.
 	pub req_protocol_names: ReqProtocolNames,
 }
@@ -136,6 +148,7 @@ pub fn prepared_overseer_builder<'a, Spawner, RuntimeClient>(
 		authority_discovery_service,
 		pov_req_receiver,
 		chunk_req_receiver,
+		chunk_req_v2_receiver,
 		collation_req_receiver,
 		available_data_req_receiver,
 		statement_req_receiver,
@@ -150,6 +163,10 @@ pub fn prepared_overseer_builder<'a, Spawner, RuntimeClient>(
 		dispute_coordinator_config,
 		pvf_checker_enabled,
 		overseer_message_channel_capacity_override,
+		runtime_api_cache_budget,
+		overseer_message_recorder,
+		subsystem_slow_poll_threshold,
+		collator_reputation_config,
 		req_protocol_names,
 	}: OverseerGenArgs<'a, Spawner, RuntimeClient>,
 ) -> Result<
@@ -215,10 +232,14 @@ where
 		))
 		.availability_distribution(AvailabilityDistributionSubsystem::new(
 			keystore.clone(),
-			IncomingRequestReceivers { pov_req_receiver, chunk_req_receiver },
+			IncomingRequestReceivers {
+				pov_req_receiver,
+				chunk_req_receiver,
+				chunk_req_v2_receiver,
+			},
 			Metrics::register(registry)?,
 		))
-		.availability_recovery(AvailabilityRecoverySubsystem::with_chunks_only(
+		.availability_recovery(AvailabilityRecoverySubsystem::with_systematic_chunks(
 			available_data_req_receiver,
 			Metrics::register(registry)?,
 		))
@@ -260,15 +281,18 @@ where
 					keystore: keystore.clone(),
 					eviction_policy: Default::default(),
 					metrics: Metrics::register(registry)?,
+					db: parachains_db.clone(),
+					reputation_config: collator_reputation_config,
 				},
 			};
 			CollatorProtocolSubsystem::new(side)
 		})
 		.provisioner(ProvisionerSubsystem::new(Metrics::register(registry)?))
-		.runtime_api(RuntimeApiSubsystem::new(
+		.runtime_api(RuntimeApiSubsystem::with_cache_budget(
 			runtime_client.clone(),
 			Metrics::register(registry)?,
 			spawner.clone(),
+			runtime_api_cache_budget,
 		))
 		.statement_distribution(StatementDistributionSubsystem::new(
 			keystore.clone(),
@@ -315,8 +339,20 @@ where
 		.metrics(metrics)
 		.spawner(spawner);
 
-	if let Some(capacity) = overseer_message_channel_capacity_override {
-		Ok(builder.message_channel_capacity(capacity))
+	let builder = if let Some(capacity) = overseer_message_channel_capacity_override {
+		builder.message_channel_capacity(capacity)
+	} else {
+		builder
+	};
+
+	let builder = match (registry, subsystem_slow_poll_threshold) {
+		(Some(registry), Some(threshold)) =>
+			builder.poll_observer(Arc::new(PollMetrics::register(registry, threshold)?)),
+		_ => builder,
+	};
+
+	if let Some(recorder) = overseer_message_recorder {
+		Ok(builder.message_observer(recorder))
 	} else {
 		Ok(builder)
 	}
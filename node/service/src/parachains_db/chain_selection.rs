@@ -0,0 +1,181 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+//! Offline access to the unfinalized block tree kept by chain selection, for finding out why
+//! blocks are not built upon and for overriding their state while the node is stopped.
+//!
+//! Overrides are recorded in the override log of the chain selection column, just like the ones
+//! applied via RPC.
+
+use super::{inspect::ParachainsDb, other_io_error, REAL_COLUMNS};
+use polkadot_node_core_chain_selection::{
+	ChainSelectionSubsystem, Config, StagnantCheckInterval, StagnantCheckMode,
+};
+use polkadot_node_subsystem::messages::{
+	ChainSelectionBlock, ChainSelectionOverride, ChainSelectionOverrideRecord,
+};
+use polkadot_primitives::v2::{BlockNumber, Hash};
+use std::io;
+
+/// An unfinalized block as seen by chain selection.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ExportedBlock {
+	/// The hash of the block.
+	pub hash: String,
+	/// The block number.
+	pub number: BlockNumber,
+	/// The hash of the parent block.
+	pub parent_hash: String,
+	/// The weight of the block.
+	pub weight: u32,
+	/// `approved`, `unapproved` or `stagnant`.
+	pub approval: &'static str,
+	/// Seconds since the UNIX epoch at which the block is checked for stagnation, if pending.
+	pub stagnant_at: Option<u64>,
+	/// Whether the block was reverted, either by a descendant or by the operator.
+	pub reverted: bool,
+	/// The earliest ancestor which is reverted or stagnant, making this block unviable.
+	pub earliest_unviable_ancestor: Option<String>,
+	/// Whether the block and all its ancestors are viable.
+	pub viable: bool,
+	/// Whether new blocks may be built upon the block.
+	pub viable_leaf: bool,
+}
+
+impl From<ChainSelectionBlock> for ExportedBlock {
+	fn from(block: ChainSelectionBlock) -> Self {
+		ExportedBlock {
+			hash: format!("{:?}", block.hash),
+			number: block.number,
+			parent_hash: format!("{:?}", block.parent_hash),
+			weight: block.weight,
+			approval: block.approval.as_str(),
+			stagnant_at: block.stagnant_at,
+			reverted: block.explicitly_reverted,
+			earliest_unviable_ancestor: block
+				.earliest_unviable_ancestor
+				.map(|h| format!("{:?}", h)),
+			viable: block.viable,
+			viable_leaf: block.viable_leaf,
+		}
+	}
+}
+
+/// An entry of the chain selection override log.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ExportedOverride {
+	/// Seconds since the UNIX epoch at which the override was applied.
+	pub timestamp: u64,
+	/// The hash of the overridden block.
+	pub block_hash: String,
+	/// The number of the overridden block.
+	pub block_number: BlockNumber,
+	/// `revert` or `unstagnate`.
+	pub kind: &'static str,
+}
+
+impl From<ChainSelectionOverrideRecord> for ExportedOverride {
+	fn from(record: ChainSelectionOverrideRecord) -> Self {
+		ExportedOverride {
+			timestamp: record.timestamp,
+			block_hash: format!("{:?}", record.block_hash),
+			block_number: record.block_number,
+			kind: record.kind.as_str(),
+		}
+	}
+}
+
+/// Parse a hex encoded block hash, with or without `0x` prefix.
+pub fn parse_block_hash(s: &str) -> Result<Hash, String> {
+	let raw = sp_core::bytes::from_hex(s).map_err(|e| format!("{:?}: {:?}", s, e))?;
+	let raw: [u8; 32] = raw.try_into().map_err(|_| format!("{:?}: expected 32 bytes", s))?;
+	Ok(Hash::from(raw))
+}
+
+/// Parse the name of a chain selection override, `revert` or `unstagnate`.
+pub fn parse_override(s: &str) -> Result<ChainSelectionOverride, String> {
+	ChainSelectionOverride::ALL
+		.into_iter()
+		.find(|kind| kind.as_str() == s)
+		.ok_or_else(|| format!("Unknown chain selection override {:?}", s))
+}
+
+impl ParachainsDb {
+	fn into_chain_selection(self) -> ChainSelectionSubsystem {
+		let config = Config {
+			col_data: REAL_COLUMNS.col_chain_selection_data,
+			stagnant_check_interval: StagnantCheckInterval::never(),
+			stagnant_check_mode: StagnantCheckMode::PruneOnly,
+		};
+		ChainSelectionSubsystem::new(config, self.into_database())
+	}
+
+	/// Export all unfinalized blocks in ascending order by number.
+	pub fn chain_selection_tree(self) -> io::Result<Vec<ExportedBlock>> {
+		let blocks = self
+			.into_chain_selection()
+			.block_tree()
+			.map_err(|err| other_io_error(err.to_string()))?;
+
+		Ok(blocks.into_iter().map(Into::into).collect())
+	}
+
+	/// Apply an override to the given block and record it in the override log.
+	///
+	/// Returns the updated block, or `None` if the block is unknown.
+	pub fn override_chain_selection(
+		self,
+		block_hash: Hash,
+		kind: ChainSelectionOverride,
+	) -> io::Result<Option<ExportedBlock>> {
+		let block = self
+			.into_chain_selection()
+			.apply_override(block_hash, kind)
+			.map_err(|err| other_io_error(err.to_string()))?;
+
+		Ok(block.map(Into::into))
+	}
+
+	/// Export the override log, oldest first.
+	pub fn chain_selection_overrides(self) -> io::Result<Vec<ExportedOverride>> {
+		let records = self
+			.into_chain_selection()
+			.override_log()
+			.map_err(|err| other_io_error(err.to_string()))?;
+
+		Ok(records.into_iter().map(Into::into).collect())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_block_hash_with_and_without_prefix() {
+		let hash = Hash::repeat_byte(0xAB);
+		let hex = format!("{:?}", hash);
+
+		assert_eq!(parse_block_hash(&hex), Ok(hash));
+		assert_eq!(parse_block_hash(&hex[2..]), Ok(hash));
+		assert!(parse_block_hash("0xabcd").is_err());
+		assert!(parse_block_hash("not a hash").is_err());
+	}
+
+	#[test]
+	fn parses_override() {
+		assert_eq!(parse_override("revert"), Ok(ChainSelectionOverride::Revert));
+		assert_eq!(parse_override("unstagnate"), Ok(ChainSelectionOverride::Unstagnate));
+		assert!(parse_override("approve").is_err());
+	}
+}
@@ -0,0 +1,390 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+//! Export of the disputes stored by the dispute coordinator, for post-mortems and slashing
+//! reviews.
+//!
+//! Validator indices are resolved to public keys with the session window persisted in the
+//! parachains DB. Disputes are only kept for the sessions of that window, so keys are known for
+//! all of them unless the window was pruned manually.
+
+use super::{inspect::ParachainsDb, other_io_error, REAL_COLUMNS};
+use polkadot_node_core_dispute_coordinator::{
+	Config, DisputeCoordinatorSubsystem, DisputeStatus, StoredDispute,
+};
+use polkadot_node_primitives::DISPUTE_WINDOW;
+use polkadot_node_subsystem_util::rolling_session_window::{DatabaseParams, RollingSessionWindow};
+use polkadot_primitives::v2::{SessionIndex, ValidatorId, ValidatorIndex};
+use sp_core::{crypto::Ss58Codec, hexdisplay::HexDisplay};
+use std::{io, ops::RangeInclusive, sync::Arc};
+
+/// A single vote on a disputed candidate.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ExportedVote {
+	/// The index of the validator in the session.
+	pub validator_index: u32,
+	/// The SS58 encoded public key of the validator, if the session is known.
+	pub validator: Option<String>,
+	/// Whether the validator voted for the validity of the candidate.
+	pub valid: bool,
+	/// The kind of statement the vote was derived from.
+	pub kind: String,
+	/// The hex encoded signature.
+	pub signature: String,
+}
+
+/// A dispute with all votes on the candidate.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ExportedDispute {
+	/// The session the candidate was disputed in.
+	pub session: SessionIndex,
+	/// The disputed candidate.
+	pub candidate_hash: String,
+	/// The parachain of the candidate.
+	pub para_id: u32,
+	/// `active`, `confirmed`, `concluded_for` or `concluded_against`.
+	pub status: &'static str,
+	/// Seconds since the UNIX epoch at which the dispute concluded.
+	pub concluded_at: Option<u64>,
+	/// Whether one of the local validators voted, `None` if the session is unknown.
+	pub local_participated: Option<bool>,
+	/// All votes, valid ones first.
+	pub votes: Vec<ExportedVote>,
+}
+
+/// A vote against the supermajority of a concluded dispute.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct AuditEntry {
+	/// The session the candidate was disputed in.
+	pub session: SessionIndex,
+	/// The disputed candidate.
+	pub candidate_hash: String,
+	/// The parachain of the candidate.
+	pub para_id: u32,
+	/// Whether the supermajority found the candidate valid.
+	pub concluded_valid: bool,
+	/// The index of the validator in the session.
+	pub validator_index: u32,
+	/// The SS58 encoded public key of the validator, if the session is known.
+	pub validator: Option<String>,
+	/// The kind of statement the vote was derived from.
+	pub kind: String,
+}
+
+/// Parse a validator public key, either SS58 or hex encoded.
+pub fn parse_validator(s: &str) -> Result<ValidatorId, String> {
+	let public = if s.starts_with("0x") {
+		let raw = sp_core::bytes::from_hex(s).map_err(|e| format!("{:?}: {:?}", s, e))?;
+		let raw: [u8; 32] = raw.try_into().map_err(|_| format!("{:?}: expected 32 bytes", s))?;
+		sp_core::sr25519::Public::from_raw(raw)
+	} else {
+		sp_core::sr25519::Public::from_ss58check(s).map_err(|e| format!("{:?}: {:?}", s, e))?
+	};
+	Ok(public.into())
+}
+
+impl ParachainsDb {
+	/// Export all disputes of the given sessions.
+	///
+	/// Votes of `local_validators` are noted in `local_participated`.
+	pub fn export_disputes(
+		self,
+		sessions: RangeInclusive<SessionIndex>,
+		local_validators: &[ValidatorId],
+	) -> io::Result<Vec<ExportedDispute>> {
+		let db = self.into_database();
+		let session_window = RollingSessionWindow::load_persisted(
+			DISPUTE_WINDOW,
+			DatabaseParams { db: db.clone(), db_column: REAL_COLUMNS.col_session_window_data },
+		);
+		let config = Config {
+			col_data: REAL_COLUMNS.col_dispute_coordinator_data,
+			col_session_data: REAL_COLUMNS.col_session_window_data,
+			participation_queue: Default::default(),
+		};
+		let disputes = DisputeCoordinatorSubsystem::new(
+			db,
+			config,
+			Arc::new(sc_keystore::LocalKeystore::in_memory()),
+			Default::default(),
+		)
+		.load_disputes(sessions)
+		.map_err(|err| other_io_error(err.to_string()))?;
+
+		Ok(disputes
+			.into_iter()
+			.map(|dispute| {
+				let validators = session_window
+					.as_ref()
+					.and_then(|w| w.session_info(dispute.session))
+					.map(|info| &info.validators[..]);
+				export(dispute, validators, local_validators)
+			})
+			.collect())
+	}
+}
+
+fn export(
+	dispute: StoredDispute,
+	validators: Option<&[ValidatorId]>,
+	local_validators: &[ValidatorId],
+) -> ExportedDispute {
+	let validator = |index: ValidatorIndex| validators.and_then(|v| v.get(index.0 as usize));
+
+	let (status, concluded_at) = match dispute.status {
+		DisputeStatus::Active => ("active", None),
+		DisputeStatus::Confirmed => ("confirmed", None),
+		DisputeStatus::ConcludedFor(at) => ("concluded_for", Some(at)),
+		DisputeStatus::ConcludedAgainst(at) => ("concluded_against", Some(at)),
+	};
+
+	let local_participated = validators.map(|_| {
+		dispute
+			.votes
+			.valid
+			.keys()
+			.chain(dispute.votes.invalid.keys())
+			.filter_map(|index| validator(*index))
+			.any(|v| local_validators.contains(v))
+	});
+
+	let valid = dispute
+		.votes
+		.valid
+		.iter()
+		.map(|(index, (kind, signature))| (*index, true, format!("{:?}", kind), signature));
+	let invalid = dispute
+		.votes
+		.invalid
+		.iter()
+		.map(|(index, (kind, signature))| (*index, false, format!("{:?}", kind), signature));
+	let votes = valid
+		.chain(invalid)
+		.map(|(index, valid, kind, signature)| ExportedVote {
+			validator_index: index.0,
+			validator: validator(index).map(|v| v.to_ss58check()),
+			valid,
+			kind,
+			signature: format!("0x{}", HexDisplay::from(&signature.as_ref())),
+		})
+		.collect();
+
+	ExportedDispute {
+		session: dispute.session,
+		candidate_hash: format!("{:?}", dispute.candidate_hash.0),
+		para_id: dispute.votes.candidate_receipt.descriptor.para_id.into(),
+		status,
+		concluded_at,
+		local_participated,
+		votes,
+	}
+}
+
+/// Collect the votes against the supermajority of all concluded disputes.
+///
+/// Only votes of `validators` are reported, or all of them if it is empty.
+pub fn audit(disputes: &[ExportedDispute], validators: &[ValidatorId]) -> Vec<AuditEntry> {
+	let validators = validators.iter().map(|v| v.to_ss58check()).collect::<Vec<_>>();
+
+	disputes
+		.iter()
+		.filter_map(|dispute| {
+			let concluded_valid = match dispute.status {
+				"concluded_for" => true,
+				"concluded_against" => false,
+				_ => return None,
+			};
+			Some(dispute.votes.iter().filter(move |vote| vote.valid != concluded_valid).map(
+				move |vote| AuditEntry {
+					session: dispute.session,
+					candidate_hash: dispute.candidate_hash.clone(),
+					para_id: dispute.para_id,
+					concluded_valid,
+					validator_index: vote.validator_index,
+					validator: vote.validator.clone(),
+					kind: vote.kind.clone(),
+				},
+			))
+		})
+		.flatten()
+		.filter(|entry| {
+			validators.is_empty()
+				|| entry.validator.as_ref().map_or(false, |v| validators.contains(v))
+		})
+		.collect()
+}
+
+/// Render the disputes as CSV, one line per vote.
+pub fn disputes_to_csv(disputes: &[ExportedDispute]) -> String {
+	let mut csv = String::from(
+		"session,candidate_hash,para_id,status,concluded_at,local_participated,\
+		validator_index,validator,valid,kind,signature\n",
+	);
+	for dispute in disputes {
+		for vote in &dispute.votes {
+			let row = [
+				dispute.session.to_string(),
+				dispute.candidate_hash.clone(),
+				dispute.para_id.to_string(),
+				dispute.status.to_string(),
+				optional(&dispute.concluded_at),
+				optional(&dispute.local_participated),
+				vote.validator_index.to_string(),
+				optional(&vote.validator),
+				vote.valid.to_string(),
+				vote.kind.clone(),
+				vote.signature.clone(),
+			];
+			push_row(&mut csv, &row);
+		}
+	}
+	csv
+}
+
+/// Render the audit entries as CSV.
+pub fn audit_to_csv(entries: &[AuditEntry]) -> String {
+	let mut csv = String::from(
+		"session,candidate_hash,para_id,concluded_valid,validator_index,validator,kind\n",
+	);
+	for entry in entries {
+		let row = [
+			entry.session.to_string(),
+			entry.candidate_hash.clone(),
+			entry.para_id.to_string(),
+			entry.concluded_valid.to_string(),
+			entry.validator_index.to_string(),
+			optional(&entry.validator),
+			entry.kind.clone(),
+		];
+		push_row(&mut csv, &row);
+	}
+	csv
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+	value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+fn push_row(csv: &mut String, fields: &[String]) {
+	let fields = fields
+		.iter()
+		.map(|field| {
+			if field.contains(|c| matches!(c, ',' | '"' | '\n')) {
+				format!("\"{}\"", field.replace('"', "\"\""))
+			} else {
+				field.clone()
+			}
+		})
+		.collect::<Vec<_>>();
+	csv.push_str(&fields.join(","));
+	csv.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use polkadot_node_primitives::CandidateVotes;
+	use polkadot_primitives::v2::{
+		CandidateHash, InvalidDisputeStatementKind, ValidDisputeStatementKind,
+	};
+	use sp_core::crypto::UncheckedFrom;
+	use sp_keyring::Sr25519Keyring;
+	use test_helpers::dummy_candidate_receipt_bad_sig;
+
+	fn validators() -> Vec<ValidatorId> {
+		[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie]
+			.iter()
+			.map(|k| k.public().into())
+			.collect()
+	}
+
+	fn dispute(status: DisputeStatus) -> StoredDispute {
+		let signature = || UncheckedFrom::unchecked_from([1u8; 64]);
+		let mut votes = CandidateVotes {
+			candidate_receipt: dummy_candidate_receipt_bad_sig(Default::default(), None),
+			valid: Default::default(),
+			invalid: Default::default(),
+		};
+		votes
+			.valid
+			.insert(ValidatorIndex(0), (ValidDisputeStatementKind::Explicit, signature()));
+		votes
+			.valid
+			.insert(ValidatorIndex(1), (ValidDisputeStatementKind::Explicit, signature()));
+		votes
+			.invalid
+			.insert(ValidatorIndex(2), (InvalidDisputeStatementKind::Explicit, signature()));
+
+		StoredDispute {
+			session: 3,
+			candidate_hash: CandidateHash(Default::default()),
+			status,
+			votes,
+		}
+	}
+
+	#[test]
+	fn export_resolves_validators_and_local_participation() {
+		let validators = validators();
+		let exported =
+			export(dispute(DisputeStatus::ConcludedFor(42)), Some(&validators), &validators[2..]);
+
+		assert_eq!(exported.status, "concluded_for");
+		assert_eq!(exported.concluded_at, Some(42));
+		assert_eq!(exported.local_participated, Some(true));
+		assert_eq!(exported.votes.len(), 3);
+		assert_eq!(exported.votes[2].validator, Some(validators[2].to_ss58check()));
+		assert!(!exported.votes[2].valid);
+
+		let unknown_session = export(dispute(DisputeStatus::Active), None, &validators);
+		assert_eq!(unknown_session.local_participated, None);
+		assert_eq!(unknown_session.votes[0].validator, None);
+	}
+
+	#[test]
+	fn audit_reports_votes_against_supermajority() {
+		let validators = validators();
+		let disputes = vec![
+			export(dispute(DisputeStatus::ConcludedFor(1)), Some(&validators), &[]),
+			export(dispute(DisputeStatus::ConcludedAgainst(2)), Some(&validators), &[]),
+			export(dispute(DisputeStatus::Active), Some(&validators), &[]),
+		];
+
+		let all = audit(&disputes, &[]);
+		assert_eq!(
+			all.iter().map(|e| (e.concluded_valid, e.validator_index)).collect::<Vec<_>>(),
+			vec![(true, 2), (false, 0), (false, 1)],
+		);
+
+		let bob = audit(&disputes, &validators[1..2]);
+		assert_eq!(bob.len(), 1);
+		assert_eq!(bob[0].validator_index, 1);
+	}
+
+	#[test]
+	fn csv_has_a_line_per_vote() {
+		let disputes = vec![export(dispute(DisputeStatus::Active), Some(&validators()), &[])];
+		let csv = disputes_to_csv(&disputes);
+		assert_eq!(csv.lines().count(), 4);
+		assert!(csv.lines().nth(1).unwrap().starts_with("3,0x0000"));
+	}
+
+	#[test]
+	fn parses_hex_and_ss58_validators() {
+		let alice: ValidatorId = Sr25519Keyring::Alice.public().into();
+		assert_eq!(parse_validator(&alice.to_ss58check()).unwrap(), alice);
+		let hex = format!("0x{}", HexDisplay::from(&Sr25519Keyring::Alice.public().0));
+		assert_eq!(parse_validator(&hex).unwrap(), alice);
+		assert!(parse_validator("0x1234").is_err());
+	}
+}
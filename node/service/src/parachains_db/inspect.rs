@@ -0,0 +1,367 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+//! Offline inspection and repair of the parachains DB.
+//!
+//! The database is opened directly instead of through the subsystem adapters, so that columns
+//! which do not support ordered iteration can still be walked for statistics.
+
+#![cfg(feature = "full-node")]
+
+use super::{columns, other_io_error, upgrade, DatabaseKind, LOG_TARGET, REAL_COLUMNS};
+use kvdb::KeyValueDB;
+use polkadot_node_core_approval_voting::ApprovalVotingSubsystem;
+use polkadot_node_core_av_store::AvailabilityStoreSubsystem;
+use polkadot_node_primitives::AvailableData;
+use polkadot_node_subsystem_util::database::{Database, DecodedEntry};
+use polkadot_primitives::v2::CandidateHash;
+use sp_core::hexdisplay::HexDisplay;
+use std::{io, path::PathBuf, sync::Arc};
+
+/// The columns of the parachains DB, with the names used to refer to them on the command line.
+pub const COLUMN_NAMES: &[(u32, &str)] = &[
+	(columns::v3::COL_AVAILABILITY_DATA, "availability-data"),
+	(columns::v3::COL_AVAILABILITY_META, "availability-meta"),
+	(columns::v3::COL_APPROVAL_DATA, "approval-data"),
+	(columns::v3::COL_CHAIN_SELECTION_DATA, "chain-selection"),
+	(columns::v3::COL_DISPUTE_COORDINATOR_DATA, "dispute-coordinator"),
+	(columns::v3::COL_SESSION_WINDOW_DATA, "session-window"),
+	(columns::v3::COL_COLLATOR_REPUTATION_DATA, "collator-reputation"),
+];
+
+/// Resolve a column name to the column index.
+pub fn column_by_name(name: &str) -> io::Result<u32> {
+	COLUMN_NAMES
+		.iter()
+		.find(|(_, n)| *n == name)
+		.map(|(col, _)| *col)
+		.ok_or_else(|| {
+			let known = COLUMN_NAMES.iter().map(|(_, n)| *n).collect::<Vec<_>>();
+			other_io_error(format!("Unknown column {:?}, expected one of {:?}", name, known))
+		})
+}
+
+fn column_name(col: u32) -> &'static str {
+	COLUMN_NAMES.iter().find(|(c, _)| *c == col).map_or("unknown", |(_, n)| *n)
+}
+
+/// Number of keys and their total size for a single column.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ColumnStats {
+	/// The name of the column.
+	pub name: &'static str,
+	/// The number of keys stored in the column.
+	pub keys: u64,
+	/// The total size of all keys, in bytes.
+	pub key_bytes: u64,
+	/// The total size of all values, in bytes.
+	pub value_bytes: u64,
+	/// Whether the keys are only available as hashes (`ParityDB` columns without a btree index).
+	pub hashed_keys: bool,
+}
+
+impl ColumnStats {
+	fn new(col: u32, hashed_keys: bool) -> Self {
+		ColumnStats { name: column_name(col), keys: 0, key_bytes: 0, value_bytes: 0, hashed_keys }
+	}
+
+	fn note(&mut self, key_len: usize, value_len: usize) {
+		self.keys += 1;
+		self.key_bytes += key_len as u64;
+		self.value_bytes += value_len as u64;
+	}
+}
+
+/// A decoded entry of a column, ready to be serialized.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Record {
+	/// The hex encoded key.
+	pub key: String,
+	/// The type of record stored under the key.
+	pub kind: &'static str,
+	/// The decoded value.
+	pub value: serde_json::Value,
+}
+
+impl From<DecodedEntry> for Record {
+	fn from(entry: DecodedEntry) -> Self {
+		Record {
+			key: format!("0x{}", HexDisplay::from(&entry.key)),
+			kind: entry.kind,
+			value: entry.value,
+		}
+	}
+}
+
+enum Backend {
+	RocksDB(kvdb_rocksdb::Database),
+	ParityDB(parity_db::Db),
+}
+
+/// An existing parachains DB, opened for offline inspection.
+pub struct ParachainsDb {
+	path: PathBuf,
+	backend: Backend,
+}
+
+impl ParachainsDb {
+	/// Open the parachains DB at the location used by the node for the given `root` directory.
+	///
+	/// Fails if there is no database at that location. A database of an older version is upgraded
+	/// first, just like the node does on startup.
+	pub(crate) fn open(root: PathBuf, kind: DatabaseKind) -> io::Result<Self> {
+		let path = match kind {
+			DatabaseKind::RocksDB => super::rocksdb_path(&root),
+			DatabaseKind::ParityDB => super::paritydb_path(&root),
+		};
+
+		if !path.is_dir() {
+			return Err(other_io_error(format!("No parachains database found at {:?}", path)))
+		}
+
+		upgrade::try_upgrade_db(&path, kind)?;
+
+		let backend = match kind {
+			DatabaseKind::RocksDB => {
+				let path_str = path
+					.to_str()
+					.ok_or_else(|| other_io_error(format!("Bad database path: {:?}", path)))?;
+				let db_config =
+					kvdb_rocksdb::DatabaseConfig::with_columns(columns::v3::NUM_COLUMNS);
+				Backend::RocksDB(kvdb_rocksdb::Database::open(&db_config, path_str)?)
+			},
+			DatabaseKind::ParityDB => Backend::ParityDB(
+				parity_db::Db::open(&upgrade::paritydb_version_3_config(&path))
+					.map_err(|err| other_io_error(format!("{:?}", err)))?,
+			),
+		};
+
+		Ok(ParachainsDb { path, backend })
+	}
+
+	/// Count the keys and their sizes in every column.
+	pub fn column_stats(&self) -> io::Result<Vec<ColumnStats>> {
+		COLUMN_NAMES.iter().map(|(col, _)| self.stats_for(*col)).collect()
+	}
+
+	fn stats_for(&self, col: u32) -> io::Result<ColumnStats> {
+		match &self.backend {
+			Backend::RocksDB(db) => {
+				let mut stats = ColumnStats::new(col, false);
+				for (key, value) in db.iter(col) {
+					stats.note(key.len(), value.len());
+				}
+				Ok(stats)
+			},
+			Backend::ParityDB(db) if columns::v3::ORDERED_COL.contains(&col) => {
+				let mut stats = ColumnStats::new(col, false);
+				let mut iter =
+					db.iter(col as u8).map_err(|err| other_io_error(format!("{:?}", err)))?;
+				while let Some((key, value)) =
+					iter.next().map_err(|err| other_io_error(format!("{:?}", err)))?
+				{
+					stats.note(key.len(), value.len());
+				}
+				Ok(stats)
+			},
+			Backend::ParityDB(db) => {
+				let mut stats = ColumnStats::new(col, true);
+				db.iter_column_while(col as u8, |state| {
+					stats.note(32, state.value.len());
+					true
+				})
+				.map_err(|err| other_io_error(format!("{:?}", err)))?;
+				Ok(stats)
+			},
+		}
+	}
+
+	/// Decode all entries of the given column into readable records.
+	///
+	/// Availability data is not decoded, as it consists of raw erasure chunks and PoVs.
+	pub fn decode_column(self, col: u32) -> io::Result<Vec<Record>> {
+		let db = self.into_database();
+		let decoded = if col == REAL_COLUMNS.col_availability_meta {
+			av_store(db).inspect_meta().map_err(|err| other_io_error(err.to_string()))?
+		} else if col == REAL_COLUMNS.col_approval_data {
+			approval_voting(db)
+				.inspect_db()
+				.map_err(|err| other_io_error(err.to_string()))?
+		} else if col == REAL_COLUMNS.col_chain_selection_data {
+			let config = polkadot_node_core_chain_selection::Config {
+				col_data: REAL_COLUMNS.col_chain_selection_data,
+				stagnant_check_interval:
+					polkadot_node_core_chain_selection::StagnantCheckInterval::never(),
+				stagnant_check_mode:
+					polkadot_node_core_chain_selection::StagnantCheckMode::PruneOnly,
+			};
+			polkadot_node_core_chain_selection::ChainSelectionSubsystem::new(config, db)
+				.inspect_db()
+				.map_err(|err| other_io_error(err.to_string()))?
+		} else if col == REAL_COLUMNS.col_dispute_coordinator_data {
+			let config = polkadot_node_core_dispute_coordinator::Config {
+				col_data: REAL_COLUMNS.col_dispute_coordinator_data,
+				col_session_data: REAL_COLUMNS.col_session_window_data,
+				participation_queue: Default::default(),
+			};
+			polkadot_node_core_dispute_coordinator::DisputeCoordinatorSubsystem::new(
+				db,
+				config,
+				Arc::new(sc_keystore::LocalKeystore::in_memory()),
+				Default::default(),
+			)
+			.inspect_db()
+			.map_err(|err| other_io_error(err.to_string()))?
+		} else if col == REAL_COLUMNS.col_collator_reputation_data {
+			polkadot_collator_protocol::inspect_collator_reputations(&*db, col)
+		} else {
+			return Err(other_io_error(format!("Column {:?} can not be decoded", column_name(col))))
+		};
+
+		Ok(decoded.into_iter().map(Into::into).collect())
+	}
+
+	/// Load the PoV and the persisted validation data the av-store kept for the given candidate.
+	pub fn available_data(
+		self,
+		candidate_hash: &CandidateHash,
+	) -> io::Result<Option<AvailableData>> {
+		av_store(self.into_database())
+			.available_data(candidate_hash)
+			.map_err(|err| other_io_error(err.to_string()))
+	}
+
+	/// Remove every entry of the given column.
+	///
+	/// The other columns are left untouched, so subsystems have to cope with references into the
+	/// cleared column not being resolvable anymore.
+	pub fn clear_column(self, col: u32) -> io::Result<()> {
+		gum::info!(target: LOG_TARGET, column = column_name(col), "Clearing parachains db column");
+
+		match self.backend {
+			Backend::RocksDB(db) => {
+				let mut tx = db.transaction();
+				tx.delete_prefix(col, &[]);
+				db.write(tx)
+			},
+			Backend::ParityDB(db) => {
+				// The column can only be cleared while the database is closed.
+				drop(db);
+				parity_db::clear_column(&self.path, col as u8)
+					.map_err(|err| other_io_error(format!("Error clearing column {:?}", err)))
+			},
+		}
+	}
+
+	pub(super) fn into_database(self) -> Arc<dyn Database> {
+		use polkadot_node_subsystem_util::database::{kvdb_impl, paritydb_impl};
+
+		match self.backend {
+			Backend::RocksDB(db) =>
+				Arc::new(kvdb_impl::DbAdapter::new(db, columns::v3::ORDERED_COL)),
+			Backend::ParityDB(db) =>
+				Arc::new(paritydb_impl::DbAdapter::new(db, columns::v3::ORDERED_COL)),
+		}
+	}
+}
+
+/// The av-store, backed by the given parachains DB.
+pub(super) fn av_store(db: Arc<dyn Database>) -> AvailabilityStoreSubsystem {
+	let config = polkadot_node_core_av_store::Config {
+		col_data: REAL_COLUMNS.col_availability_data,
+		col_meta: REAL_COLUMNS.col_availability_meta,
+	};
+	AvailabilityStoreSubsystem::new(db, config, Default::default())
+}
+
+/// Approval voting, backed by the given parachains DB.
+pub(super) fn approval_voting(db: Arc<dyn Database>) -> ApprovalVotingSubsystem {
+	let config = polkadot_node_core_approval_voting::Config {
+		col_data: REAL_COLUMNS.col_approval_data,
+		col_session_data: REAL_COLUMNS.col_session_window_data,
+		slot_duration_millis: Default::default(),
+	};
+	ApprovalVotingSubsystem::with_config(
+		config,
+		db,
+		Arc::new(sc_keystore::LocalKeystore::in_memory()),
+		Box::new(consensus_common::NoNetwork),
+		Default::default(),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn stats_and_clear_paritydb_column() {
+		let root = tempfile::tempdir().unwrap();
+		let path = root.path().join("parachains");
+		std::fs::create_dir_all(&path).unwrap();
+		{
+			let db =
+				parity_db::Db::open_or_create(&upgrade::paritydb_version_3_config(&path)).unwrap();
+			db.commit(vec![
+				(columns::v3::COL_APPROVAL_DATA as u8, b"1234".to_vec(), Some(b"value".to_vec())),
+				(columns::v3::COL_AVAILABILITY_META as u8, b"5678".to_vec(), Some(b"v".to_vec())),
+			])
+			.unwrap();
+		}
+		upgrade::update_version(&path).unwrap();
+
+		let db = ParachainsDb::open(root.path().to_owned(), DatabaseKind::ParityDB).unwrap();
+		let stats = db.column_stats().unwrap();
+		let approval = stats.iter().find(|s| s.name == "approval-data").unwrap();
+		assert_eq!((approval.keys, approval.value_bytes, approval.hashed_keys), (1, 5, true));
+		let meta = stats.iter().find(|s| s.name == "availability-meta").unwrap();
+		assert_eq!((meta.keys, meta.key_bytes, meta.hashed_keys), (1, 4, false));
+
+		db.clear_column(column_by_name("availability-meta").unwrap()).unwrap();
+
+		let db = ParachainsDb::open(root.path().to_owned(), DatabaseKind::ParityDB).unwrap();
+		let stats = db.column_stats().unwrap();
+		assert_eq!(stats.iter().find(|s| s.name == "availability-meta").unwrap().keys, 0);
+		assert_eq!(stats.iter().find(|s| s.name == "approval-data").unwrap().keys, 1);
+	}
+
+	#[test]
+	fn open_upgrades_older_database() {
+		let root = tempfile::tempdir().unwrap();
+		let path = root.path().join("parachains");
+		std::fs::create_dir_all(&path).unwrap();
+		{
+			let db =
+				parity_db::Db::open_or_create(&upgrade::paritydb_version_2_config(&path)).unwrap();
+			db.commit(vec![(
+				columns::v2::COL_AVAILABILITY_META as u8,
+				b"5678".to_vec(),
+				Some(b"v".to_vec()),
+			)])
+			.unwrap();
+		}
+		std::fs::write(path.join("parachain_db_version"), "2").unwrap();
+
+		// The collator reputation column only exists from version 3 on.
+		let db = ParachainsDb::open(root.path().to_owned(), DatabaseKind::ParityDB).unwrap();
+		let stats = db.column_stats().unwrap();
+		assert_eq!(stats.iter().find(|s| s.name == "availability-meta").unwrap().keys, 1);
+		assert_eq!(stats.iter().find(|s| s.name == "collator-reputation").unwrap().keys, 0);
+	}
+
+	#[test]
+	fn open_fails_without_database() {
+		let root = tempfile::tempdir().unwrap();
+		assert!(ParachainsDb::open(root.path().to_owned(), DatabaseKind::RocksDB).is_err());
+	}
+}
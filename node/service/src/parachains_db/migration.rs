@@ -0,0 +1,622 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+//! Migration of the parachain's DB between `RocksDB` and `ParityDB`.
+//!
+//! Every column is copied in batches. The progress is written to a file in the target database
+//! directory after each batch, so an interrupted migration continues where it left off on the
+//! next start. The file is only removed once all columns were copied and the key counts of
+//! both databases were found to match.
+//!
+//! A completed migration leaves a marker file in the source database directory, since the data
+//! in there goes stale as soon as the node runs on the target. If the node is switched back, the
+//! stale database is replaced by migrating the data back again. If the data is gone, the node
+//! refuses to start on the stale database.
+//!
+//! `ParityDB` only stores hashes of the keys in columns without a btree index. When migrating
+//! back to `RocksDB`, the keys of those columns are reconstructed from what the subsystems owning
+//! them reference: the av-store metadata, the block range of approval voting and the session
+//! window. Entries which are not referenced anymore can not be recovered, but they would never
+//! be read by the subsystems either.
+
+#![cfg(feature = "full-node")]
+
+use super::{
+	columns,
+	inspect::{self, ParachainsDb},
+	other_io_error,
+	upgrade::{self, Error},
+	DatabaseKind, LOG_TARGET,
+};
+use kvdb::KeyValueDB;
+use polkadot_node_subsystem_util::{
+	database::Database, rolling_session_window::SESSION_WINDOW_KEY,
+};
+use sp_core::bytes::{from_hex, to_hex};
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+/// Migration progress file name.
+const PROGRESS_FILE_NAME: &'static str = "parachain_db_migration_progress";
+
+/// Name of the file marking a database as migrated, holding the path of the target database.
+const MIGRATED_TO_FILE_NAME: &'static str = "parachain_db_migrated_to";
+
+/// Maximum number of entries written to the target database in a single commit.
+const BATCH_SIZE: usize = 10_000;
+
+/// How far a single column has been copied.
+#[derive(Debug, Clone, PartialEq)]
+enum ColumnProgress {
+	/// All keys up to and including the given one have been copied.
+	CopiedUpTo(Vec<u8>),
+	/// The column has been copied completely.
+	Done,
+}
+
+type Progress = BTreeMap<u32, ColumnProgress>;
+
+/// Whether a database exists at `source` whose data has not yet been (completely) copied into
+/// the one at `target`, or copied into `target` before `source` was migrated away from.
+fn is_needed(source: &Path, target: &Path) -> bool {
+	let source_exists = source.read_dir().map_or(false, |mut d| d.next().is_some());
+	let target_is_empty = target.read_dir().map_or(true, |mut d| d.next().is_none());
+
+	let target_is_stale = target_is_empty || migrated_to_file_path(target).exists();
+
+	// A source which was migrated away from is stale itself, unless the migration got
+	// interrupted right after marking it.
+	source_exists &&
+		(progress_file_path(target).exists() ||
+			(target_is_stale && !migrated_to_file_path(source).exists()))
+}
+
+/// Whether a `RocksDB` parachains DB exists below `rocksdb_root` whose data has not yet been
+/// (completely) copied into the `ParityDB` one below `paritydb_root`.
+pub(super) fn is_needed_to_paritydb(rocksdb_root: &Path, paritydb_root: &Path) -> bool {
+	is_needed(&super::rocksdb_path(rocksdb_root), &super::paritydb_path(paritydb_root))
+}
+
+/// Whether a `ParityDB` parachains DB exists below `paritydb_root` whose data has not yet been
+/// (completely) copied into the `RocksDB` one below `rocksdb_root`.
+pub(super) fn is_needed_to_rocksdb(paritydb_root: &Path, rocksdb_root: &Path) -> bool {
+	is_needed(&super::paritydb_path(paritydb_root), &super::rocksdb_path(rocksdb_root))
+}
+
+/// Fail if the database at `path` was migrated away from, as its data is stale.
+///
+/// Meant for databases which are not the target of a migration, i.e. whose data can't be
+/// brought up to date.
+pub(super) fn ensure_not_migrated(path: &Path) -> Result<(), Error> {
+	match fs::read_to_string(migrated_to_file_path(path)) {
+		Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+		Err(err) => Err(err.into()),
+		Ok(target) => Err(Error::MigratedAway { target }),
+	}
+}
+
+/// Copy the `RocksDB` parachains DB below `rocksdb_root` into a `ParityDB` one below
+/// `paritydb_root`, resuming a previously interrupted migration.
+///
+/// The `RocksDB` database is left untouched apart from being upgraded to the current version.
+pub(super) fn rocksdb_to_paritydb(rocksdb_root: &Path, paritydb_root: &Path) -> Result<(), Error> {
+	let source_path = super::rocksdb_path(rocksdb_root);
+	let target_path = super::paritydb_path(paritydb_root);
+
+	gum::info!(
+		target: LOG_TARGET,
+		?source_path,
+		?target_path,
+		"Migrating parachains db from RocksDB to ParityDB ...",
+	);
+
+	upgrade::try_upgrade_db(&source_path, DatabaseKind::RocksDB)?;
+	discard_if_migrated(&target_path)?;
+	let mut progress = start_progress(&target_path)?;
+
+	{
+		let source_path_str = source_path
+			.to_str()
+			.ok_or_else(|| other_io_error(format!("Bad database path: {:?}", source_path)))?;
+		let source = kvdb_rocksdb::Database::open(
+			&kvdb_rocksdb::DatabaseConfig::with_columns(columns::v3::NUM_COLUMNS),
+			source_path_str,
+		)?;
+		let target =
+			parity_db::Db::open_or_create(&upgrade::paritydb_version_3_config(&target_path))
+				.map_err(|err| other_io_error(format!("{:?}", err)))?;
+
+		for column in 0..columns::v3::NUM_COLUMNS {
+			// `RocksDB` iterates the keys in ascending order, as required by `copy_column`.
+			let entries =
+				source.iter(column).map(|(key, value)| Ok((key.into_vec(), value.into_vec())));
+			copy_column(column, entries, &mut progress, &target_path, |batch| {
+				target
+					.commit(batch.into_iter().map(|(key, value)| (column as u8, key, Some(value))))
+					.map_err(|err| other_io_error(format!("{:?}", err)).into())
+			})?;
+		}
+	}
+
+	// Opening the target for verification must not mistake it for an old database. Until the
+	// progress file is removed, the migration is still considered incomplete.
+	upgrade::update_version(&target_path)?;
+	verify_key_counts(
+		ParachainsDb::open(rocksdb_root.to_owned(), DatabaseKind::RocksDB)?,
+		ParachainsDb::open(paritydb_root.to_owned(), DatabaseKind::ParityDB)?,
+	)?;
+
+	finish(&source_path, &target_path, "RocksDB")
+}
+
+/// Copy the `ParityDB` parachains DB below `paritydb_root` into a `RocksDB` one below
+/// `rocksdb_root`, resuming a previously interrupted migration.
+///
+/// The `ParityDB` database is left untouched apart from being upgraded to the current version.
+pub(super) fn paritydb_to_rocksdb(paritydb_root: &Path, rocksdb_root: &Path) -> Result<(), Error> {
+	let source_path = super::paritydb_path(paritydb_root);
+	let target_path = super::rocksdb_path(rocksdb_root);
+
+	gum::info!(
+		target: LOG_TARGET,
+		?source_path,
+		?target_path,
+		"Migrating parachains db from ParityDB to RocksDB ...",
+	);
+
+	let source =
+		ParachainsDb::open(paritydb_root.to_owned(), DatabaseKind::ParityDB)?.into_database();
+	discard_if_migrated(&target_path)?;
+	let mut progress = start_progress(&target_path)?;
+
+	{
+		let target_path_str = target_path
+			.to_str()
+			.ok_or_else(|| other_io_error(format!("Bad database path: {:?}", target_path)))?;
+		let target = kvdb_rocksdb::Database::open(
+			&kvdb_rocksdb::DatabaseConfig::with_columns(columns::v3::NUM_COLUMNS),
+			target_path_str,
+		)?;
+
+		for column in 0..columns::v3::NUM_COLUMNS {
+			if progress.get(&column) == Some(&ColumnProgress::Done) {
+				continue
+			}
+
+			let mut keys = paritydb_column_keys(&source, column)?;
+			keys.sort();
+			let entries = keys.into_iter().filter_map(|key| match source.get(column, &key) {
+				Ok(value) => value.map(|value| Ok((key, value))),
+				Err(err) => Some(Err(err.into())),
+			});
+			copy_column(column, entries, &mut progress, &target_path, |batch| {
+				let mut tx = target.transaction();
+				for (key, value) in batch {
+					tx.put_vec(column, &key, value);
+				}
+				target.write(tx).map_err(Into::into)
+			})?;
+		}
+	}
+
+	drop(source);
+	upgrade::update_version(&target_path)?;
+	verify_key_counts(
+		ParachainsDb::open(paritydb_root.to_owned(), DatabaseKind::ParityDB)?,
+		ParachainsDb::open(rocksdb_root.to_owned(), DatabaseKind::RocksDB)?,
+	)?;
+
+	finish(&source_path, &target_path, "ParityDB")
+}
+
+/// The keys stored in the given column of a `ParityDB` parachains DB.
+///
+/// Columns without a btree index only store hashes of their keys, so the keys are reconstructed
+/// from the entries referencing them.
+fn paritydb_column_keys(db: &Arc<dyn Database>, column: u32) -> Result<Vec<Vec<u8>>, Error> {
+	if columns::v3::ORDERED_COL.contains(&column) {
+		return Ok(db.iter(column).map(|(key, _)| key.into_vec()).collect())
+	}
+
+	let keys = if column == columns::v3::COL_AVAILABILITY_DATA {
+		inspect::av_store(db.clone())
+			.data_keys()
+			.map_err(|err| other_io_error(err.to_string()))?
+	} else if column == columns::v3::COL_APPROVAL_DATA {
+		inspect::approval_voting(db.clone())
+			.inspect_db()
+			.map_err(|err| other_io_error(err.to_string()))?
+			.into_iter()
+			.map(|entry| entry.key)
+			.collect()
+	} else if column == columns::v3::COL_SESSION_WINDOW_DATA {
+		vec![SESSION_WINDOW_KEY.to_vec()]
+	} else {
+		return Err(other_io_error(format!("Unknown parachains db column {}", column)).into())
+	};
+
+	Ok(keys)
+}
+
+/// Remove the database at `path` if it was migrated away from, so it can be migrated into again.
+///
+/// The marker file is removed last, an interruption leaves a database which is still known to
+/// be stale.
+fn discard_if_migrated(path: &Path) -> Result<(), Error> {
+	let marker = migrated_to_file_path(path);
+	if !marker.exists() {
+		return Ok(())
+	}
+
+	gum::info!(target: LOG_TARGET, ?path, "Replacing stale parachains db which was migrated away");
+
+	for entry in fs::read_dir(path)? {
+		let entry_path = entry?.path();
+		if entry_path == marker {
+			continue
+		}
+		if entry_path.is_dir() {
+			fs::remove_dir_all(entry_path)?;
+		} else {
+			fs::remove_file(entry_path)?;
+		}
+	}
+	fs::remove_file(marker).map_err(Into::into)
+}
+
+/// Read the progress of a previous migration into the database at `target_path`, or start a new
+/// one.
+fn start_progress(target_path: &Path) -> Result<Progress, Error> {
+	// The progress file has to exist before the target database is created, otherwise an
+	// interruption right afterwards would leave a database which looks like it is in use.
+	fs::create_dir_all(target_path)?;
+	match read_progress(target_path)? {
+		Some(progress) => Ok(progress),
+		None => {
+			let progress = Progress::new();
+			write_progress(target_path, &progress)?;
+			Ok(progress)
+		},
+	}
+}
+
+/// Copy the entries of a single column, which have to be in ascending key order, skipping the
+/// ones copied before an interruption.
+///
+/// `commit` writes a batch of entries to the target database.
+fn copy_column(
+	column: u32,
+	entries: impl Iterator<Item = Result<(Vec<u8>, Vec<u8>), Error>>,
+	progress: &mut Progress,
+	target_path: &Path,
+	mut commit: impl FnMut(Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), Error>,
+) -> Result<(), Error> {
+	let resume_after = match progress.get(&column) {
+		Some(ColumnProgress::Done) => return Ok(()),
+		Some(ColumnProgress::CopiedUpTo(key)) => Some(key.clone()),
+		None => None,
+	};
+
+	gum::debug!(
+		target: LOG_TARGET,
+		column,
+		resuming = resume_after.is_some(),
+		"Copying parachains db column",
+	);
+
+	let mut batch = Vec::with_capacity(BATCH_SIZE);
+	// The entries are in ascending order, which makes the last copied key sufficient to resume
+	// from.
+	for entry in entries {
+		let (key, value) = entry?;
+		if resume_after.as_ref().map_or(false, |last| &key[..] <= &last[..]) {
+			continue
+		}
+
+		batch.push((key, value));
+
+		if batch.len() == BATCH_SIZE {
+			let last = batch.last().map(|(key, _)| key.clone()).unwrap_or_default();
+			commit(std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE)))?;
+			let _ = progress.insert(column, ColumnProgress::CopiedUpTo(last));
+			write_progress(target_path, progress)?;
+		}
+	}
+
+	commit(batch)?;
+	let _ = progress.insert(column, ColumnProgress::Done);
+	write_progress(target_path, progress)
+}
+
+/// Mark the database at `target_path` as complete and the one at `source_path` as stale.
+fn finish(source_path: &Path, target_path: &Path, source_kind: &str) -> Result<(), Error> {
+	// Marked first, an interruption right afterwards only repeats the final steps.
+	fs::write(migrated_to_file_path(source_path), target_path.to_string_lossy().as_bytes())?;
+	fs::remove_file(progress_file_path(target_path))?;
+
+	gum::info!(
+		target: LOG_TARGET,
+		?source_path,
+		"Migration complete! The {} parachains db is not used anymore and can be removed.",
+		source_kind,
+	);
+
+	Ok(())
+}
+
+/// Compare the number of keys in every column of both databases.
+///
+/// Columns whose keys are only stored as hashes in the source are allowed to lose entries which
+/// could not be recovered.
+fn verify_key_counts(source: ParachainsDb, target: ParachainsDb) -> Result<(), Error> {
+	for (expected, got) in source.column_stats()?.into_iter().zip(target.column_stats()?) {
+		if expected.hashed_keys && got.keys < expected.keys {
+			gum::warn!(
+				target: LOG_TARGET,
+				column = expected.name,
+				lost = expected.keys - got.keys,
+				"Parachains db entries which are not referenced anymore were not migrated",
+			);
+		} else if expected.keys != got.keys {
+			return Err(Error::MigrationKeyCountMismatch {
+				column: expected.name,
+				expected: expected.keys,
+				got: got.keys,
+			})
+		}
+	}
+
+	Ok(())
+}
+
+/// Returns the migration progress file path.
+fn progress_file_path(path: &Path) -> PathBuf {
+	path.join(PROGRESS_FILE_NAME)
+}
+
+/// Returns the path of the file marking a database as migrated.
+fn migrated_to_file_path(path: &Path) -> PathBuf {
+	path.join(MIGRATED_TO_FILE_NAME)
+}
+
+/// Reads the migration progress from the file at given path.
+/// If the file does not exist returns `None`.
+///
+/// Every line of the file holds a column index followed by either `done` or the hex encoded
+/// last key copied.
+fn read_progress(path: &Path) -> Result<Option<Progress>, Error> {
+	let content = match fs::read_to_string(progress_file_path(path)) {
+		Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+		Err(err) => return Err(err.into()),
+		Ok(content) => content,
+	};
+
+	content
+		.lines()
+		.map(|line| {
+			let (column, state) =
+				line.split_once(' ').ok_or(Error::CorruptedMigrationProgressFile)?;
+			let column = column.parse().map_err(|_| Error::CorruptedMigrationProgressFile)?;
+			let state = match state {
+				"done" => ColumnProgress::Done,
+				key => ColumnProgress::CopiedUpTo(
+					from_hex(key).map_err(|_| Error::CorruptedMigrationProgressFile)?,
+				),
+			};
+			Ok((column, state))
+		})
+		.collect::<Result<_, _>>()
+		.map(Some)
+}
+
+/// Writes the migration progress to the file at given path.
+///
+/// The file is replaced atomically, so an interruption never leaves it half written.
+fn write_progress(path: &Path, progress: &Progress) -> Result<(), Error> {
+	let content = progress
+		.iter()
+		.map(|(column, state)| match state {
+			ColumnProgress::Done => format!("{} done\n", column),
+			ColumnProgress::CopiedUpTo(key) => format!("{} {}\n", column, to_hex(key, false)),
+		})
+		.collect::<String>();
+
+	let file_path = progress_file_path(path);
+	let tmp_path = file_path.with_extension("tmp");
+	fs::write(&tmp_path, content)?;
+	fs::rename(tmp_path, file_path).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{columns::v1::*, *};
+
+	fn populate_rocksdb(root: &Path, entries: &[(u32, Vec<u8>, Vec<u8>)]) {
+		let path = super::super::rocksdb_path(root);
+		fs::create_dir_all(&path).unwrap();
+		let db = kvdb_rocksdb::Database::open(
+			&kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS),
+			path.to_str().unwrap(),
+		)
+		.unwrap();
+		let mut tx = db.transaction();
+		for (col, key, value) in entries {
+			tx.put_vec(*col, key, value.clone());
+		}
+		db.write(tx).unwrap();
+	}
+
+	fn test_entries() -> Vec<(u32, Vec<u8>, Vec<u8>)> {
+		(0..NUM_COLUMNS)
+			.flat_map(|col| {
+				(0u32..100).map(move |i| (col, i.to_be_bytes().to_vec(), vec![col as u8; 3]))
+			})
+			.collect()
+	}
+
+	fn assert_migrated(paritydb_root: &Path, entries: &[(u32, Vec<u8>, Vec<u8>)]) {
+		let path = super::super::paritydb_path(paritydb_root);
+		assert!(!progress_file_path(&path).exists());
+		let db = parity_db::Db::open(&upgrade::paritydb_version_3_config(&path)).unwrap();
+		for (col, key, value) in entries {
+			assert_eq!(db.get(*col as u8, key).unwrap().as_ref(), Some(value));
+		}
+	}
+
+	#[test]
+	fn migrates_all_columns() {
+		let rocksdb_root = tempfile::tempdir().unwrap();
+		let paritydb_root = tempfile::tempdir().unwrap();
+		let entries = test_entries();
+		populate_rocksdb(rocksdb_root.path(), &entries);
+
+		assert!(is_needed_to_paritydb(rocksdb_root.path(), paritydb_root.path()));
+		rocksdb_to_paritydb(rocksdb_root.path(), paritydb_root.path()).unwrap();
+		assert!(!is_needed_to_paritydb(rocksdb_root.path(), paritydb_root.path()));
+
+		assert_migrated(paritydb_root.path(), &entries);
+	}
+
+	#[test]
+	fn resumes_interrupted_migration() {
+		let rocksdb_root = tempfile::tempdir().unwrap();
+		let paritydb_root = tempfile::tempdir().unwrap();
+		let entries = test_entries();
+		populate_rocksdb(rocksdb_root.path(), &entries);
+
+		// Pretend the first column and half of the second were copied before an interruption.
+		let target_path = super::super::paritydb_path(paritydb_root.path());
+		fs::create_dir_all(&target_path).unwrap();
+		{
+			let db =
+				parity_db::Db::open_or_create(&upgrade::paritydb_version_3_config(&target_path))
+					.unwrap();
+			db.commit(
+				entries
+					.iter()
+					.filter(|(col, key, _)| {
+						*col == 0 || (*col == 1 && key[..] < 50u32.to_be_bytes())
+					})
+					.map(|(col, key, value)| (*col as u8, key.clone(), Some(value.clone()))),
+			)
+			.unwrap();
+		}
+		let progress = vec![
+			(0, ColumnProgress::Done),
+			(1, ColumnProgress::CopiedUpTo(49u32.to_be_bytes().to_vec())),
+		]
+		.into_iter()
+		.collect();
+		write_progress(&target_path, &progress).unwrap();
+		assert_eq!(read_progress(&target_path).unwrap(), Some(progress));
+
+		assert!(is_needed_to_paritydb(rocksdb_root.path(), paritydb_root.path()));
+		rocksdb_to_paritydb(rocksdb_root.path(), paritydb_root.path()).unwrap();
+
+		assert_migrated(paritydb_root.path(), &entries);
+	}
+
+	#[test]
+	fn switching_back_replaces_stale_database() {
+		let rocksdb_root = tempfile::tempdir().unwrap();
+		let paritydb_root = tempfile::tempdir().unwrap();
+		let entries = test_entries()
+			.into_iter()
+			.filter(|(col, _, _)| columns::v3::ORDERED_COL.contains(col))
+			.collect::<Vec<_>>();
+		populate_rocksdb(rocksdb_root.path(), &entries);
+		rocksdb_to_paritydb(rocksdb_root.path(), paritydb_root.path()).unwrap();
+
+		let rocksdb_path = super::super::rocksdb_path(rocksdb_root.path());
+		let paritydb_path = super::super::paritydb_path(paritydb_root.path());
+		assert!(matches!(ensure_not_migrated(&rocksdb_path), Err(Error::MigratedAway { .. })));
+		assert!(ensure_not_migrated(&paritydb_path).is_ok());
+
+		// The node runs on `ParityDB` for a while, leaving the `RocksDB` data stale.
+		let (col, key, _) = entries[0].clone();
+		{
+			let db =
+				parity_db::Db::open(&upgrade::paritydb_version_3_config(&paritydb_path)).unwrap();
+			db.commit(vec![(col as u8, key.clone(), Some(vec![42]))]).unwrap();
+		}
+
+		assert!(!is_needed_to_paritydb(rocksdb_root.path(), paritydb_root.path()));
+		assert!(is_needed_to_rocksdb(paritydb_root.path(), rocksdb_root.path()));
+		paritydb_to_rocksdb(paritydb_root.path(), rocksdb_root.path()).unwrap();
+		assert!(!is_needed_to_rocksdb(paritydb_root.path(), rocksdb_root.path()));
+
+		assert!(ensure_not_migrated(&rocksdb_path).is_ok());
+		assert!(matches!(ensure_not_migrated(&paritydb_path), Err(Error::MigratedAway { .. })));
+		let db = kvdb_rocksdb::Database::open(
+			&kvdb_rocksdb::DatabaseConfig::with_columns(columns::v3::NUM_COLUMNS),
+			rocksdb_path.to_str().unwrap(),
+		)
+		.unwrap();
+		assert_eq!(db.get(col, &key).unwrap(), Some(vec![42]));
+	}
+
+	#[test]
+	fn migrates_back_to_rocksdb() {
+		let rocksdb_root = tempfile::tempdir().unwrap();
+		let paritydb_root = tempfile::tempdir().unwrap();
+
+		// Ordered columns and the session window can be migrated, the approval entry is not
+		// referenced by the (missing) block range and gets lost.
+		let mut entries = test_entries()
+			.into_iter()
+			.filter(|(col, _, _)| columns::v3::ORDERED_COL.contains(col))
+			.collect::<Vec<_>>();
+		entries.push((
+			columns::v3::COL_SESSION_WINDOW_DATA,
+			SESSION_WINDOW_KEY.to_vec(),
+			vec![1, 2, 3],
+		));
+		let lost = (columns::v3::COL_APPROVAL_DATA, b"unreferenced".to_vec(), vec![4]);
+
+		let source_path = super::super::paritydb_path(paritydb_root.path());
+		fs::create_dir_all(&source_path).unwrap();
+		{
+			let db =
+				parity_db::Db::open_or_create(&upgrade::paritydb_version_3_config(&source_path))
+					.unwrap();
+			db.commit(
+				entries
+					.iter()
+					.chain(Some(&lost))
+					.map(|(col, key, value)| (*col as u8, key.clone(), Some(value.clone()))),
+			)
+			.unwrap();
+		}
+		upgrade::update_version(&source_path).unwrap();
+
+		assert!(is_needed_to_rocksdb(paritydb_root.path(), rocksdb_root.path()));
+		paritydb_to_rocksdb(paritydb_root.path(), rocksdb_root.path()).unwrap();
+		assert!(!is_needed_to_rocksdb(paritydb_root.path(), rocksdb_root.path()));
+
+		let path = super::super::rocksdb_path(rocksdb_root.path());
+		assert!(!progress_file_path(&path).exists());
+		let db = kvdb_rocksdb::Database::open(
+			&kvdb_rocksdb::DatabaseConfig::with_columns(columns::v3::NUM_COLUMNS),
+			path.to_str().unwrap(),
+		)
+		.unwrap();
+		for (col, key, value) in &entries {
+			assert_eq!(db.get(*col, key).unwrap().as_ref(), Some(value));
+		}
+		assert_eq!(db.get(lost.0, &lost.1).unwrap(), None);
+	}
+}
@@ -15,9 +15,21 @@
 
 #[cfg(feature = "full-node")]
 use {
-	polkadot_node_subsystem_util::database::Database, std::io, std::path::PathBuf, std::sync::Arc,
+	polkadot_node_subsystem_util::database::Database,
+	service::config::DatabaseSource,
+	std::io,
+	std::path::{Path, PathBuf},
+	std::sync::Arc,
 };
 
+#[cfg(feature = "full-node")]
+pub mod chain_selection;
+#[cfg(feature = "full-node")]
+pub mod disputes;
+#[cfg(feature = "full-node")]
+pub mod inspect;
+#[cfg(feature = "full-node")]
+mod migration;
 #[cfg(feature = "full-node")]
 mod upgrade;
 
@@ -40,6 +52,34 @@ pub(crate) mod columns {
 		pub const ORDERED_COL: &[u32] =
 			&[COL_AVAILABILITY_META, COL_CHAIN_SELECTION_DATA, COL_DISPUTE_COORDINATOR_DATA];
 	}
+
+	pub mod v2 {
+		pub use super::v1::{
+			COL_APPROVAL_DATA, COL_AVAILABILITY_DATA, COL_AVAILABILITY_META,
+			COL_CHAIN_SELECTION_DATA, COL_DISPUTE_COORDINATOR_DATA, ORDERED_COL,
+		};
+
+		pub const NUM_COLUMNS: u32 = 6;
+
+		pub const COL_SESSION_WINDOW_DATA: u32 = 5;
+	}
+
+	pub mod v3 {
+		pub use super::v2::{
+			COL_APPROVAL_DATA, COL_AVAILABILITY_DATA, COL_AVAILABILITY_META,
+			COL_CHAIN_SELECTION_DATA, COL_DISPUTE_COORDINATOR_DATA, COL_SESSION_WINDOW_DATA,
+		};
+
+		pub const NUM_COLUMNS: u32 = 7;
+
+		pub const COL_COLLATOR_REPUTATION_DATA: u32 = 6;
+		pub const ORDERED_COL: &[u32] = &[
+			COL_AVAILABILITY_META,
+			COL_CHAIN_SELECTION_DATA,
+			COL_DISPUTE_COORDINATOR_DATA,
+			COL_COLLATOR_REPUTATION_DATA,
+		];
+	}
 }
 
 /// Columns used by different subsystems.
@@ -56,19 +96,25 @@ pub struct ColumnsConfig {
 	pub col_chain_selection_data: u32,
 	/// The column used by dispute coordinator for data.
 	pub col_dispute_coordinator_data: u32,
+	/// The column used to persist the rolling session window.
+	pub col_session_window_data: u32,
+	/// The column used by the collator protocol to persist collator reputations.
+	pub col_collator_reputation_data: u32,
 }
 
 /// The real columns used by the parachains DB.
 #[cfg(any(test, feature = "full-node"))]
 pub const REAL_COLUMNS: ColumnsConfig = ColumnsConfig {
-	col_availability_data: columns::v1::COL_AVAILABILITY_DATA,
-	col_availability_meta: columns::v1::COL_AVAILABILITY_META,
-	col_approval_data: columns::v1::COL_APPROVAL_DATA,
-	col_chain_selection_data: columns::v1::COL_CHAIN_SELECTION_DATA,
-	col_dispute_coordinator_data: columns::v1::COL_DISPUTE_COORDINATOR_DATA,
+	col_availability_data: columns::v3::COL_AVAILABILITY_DATA,
+	col_availability_meta: columns::v3::COL_AVAILABILITY_META,
+	col_approval_data: columns::v3::COL_APPROVAL_DATA,
+	col_chain_selection_data: columns::v3::COL_CHAIN_SELECTION_DATA,
+	col_dispute_coordinator_data: columns::v3::COL_DISPUTE_COORDINATOR_DATA,
+	col_session_window_data: columns::v3::COL_SESSION_WINDOW_DATA,
+	col_collator_reputation_data: columns::v3::COL_COLLATOR_REPUTATION_DATA,
 };
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub(crate) enum DatabaseKind {
 	ParityDB,
 	RocksDB,
@@ -96,6 +142,120 @@ pub(crate) fn other_io_error(err: String) -> io::Error {
 	io::Error::new(io::ErrorKind::Other, err)
 }
 
+/// The directory of a `RocksDB` parachains DB, relative to the database root.
+#[cfg(feature = "full-node")]
+pub(crate) fn rocksdb_path(root: &Path) -> PathBuf {
+	root.join("parachains").join("db")
+}
+
+/// The directory of a `ParityDB` parachains DB, relative to the database root.
+#[cfg(feature = "full-node")]
+pub(crate) fn paritydb_path(root: &Path) -> PathBuf {
+	root.join("parachains")
+}
+
+/// Determine the root directory and the backend of the parachains DB for the given
+/// client database source.
+#[cfg(feature = "full-node")]
+pub(crate) fn locate(db_source: &DatabaseSource) -> Result<(PathBuf, DatabaseKind), crate::Error> {
+	match db_source {
+		DatabaseSource::RocksDb { path, .. } => Ok((path.clone(), DatabaseKind::RocksDB)),
+		DatabaseSource::ParityDb { path, .. } => Ok((
+			path.parent().ok_or(crate::Error::DatabasePathRequired)?.into(),
+			DatabaseKind::ParityDB,
+		)),
+		DatabaseSource::Auto { paritydb_path, rocksdb_path, .. } =>
+			if paritydb_path.is_dir() && paritydb_path.exists() {
+				Ok((
+					paritydb_path.parent().ok_or(crate::Error::DatabasePathRequired)?.into(),
+					DatabaseKind::ParityDB,
+				))
+			} else {
+				Ok((rocksdb_path.clone(), DatabaseKind::RocksDB))
+			},
+		DatabaseSource::Custom { .. } => Err(crate::Error::DatabasePathRequired),
+	}
+}
+
+/// The root directory of a `RocksDB` parachains DB whose data should be moved into the
+/// `ParityDB` one used by the node.
+///
+/// This follows the layout of the client database, where the `RocksDB` and `ParityDB` databases
+/// live in the sibling `db` and `paritydb` directories of the chain directory.
+#[cfg(feature = "full-node")]
+fn rocksdb_migration_source(db_source: &DatabaseSource) -> Option<PathBuf> {
+	match db_source {
+		DatabaseSource::Auto { rocksdb_path, .. } => Some(rocksdb_path.clone()),
+		DatabaseSource::ParityDb { path, .. } => {
+			let chain_dir = path.parent()?.parent()?;
+			Some(chain_dir.join("db").join(path.file_name()?))
+		},
+		_ => None,
+	}
+}
+
+/// Move the data of a previously used `RocksDB` parachains DB into the `ParityDB` one at
+/// `paritydb_root`, if the node was switched over to `ParityDB`.
+///
+/// Does nothing if there is no `RocksDB` parachains DB or the `ParityDB` one is already in use.
+/// An interrupted migration is resumed. Fails if the `ParityDB` one was migrated away from and
+/// there is no data to migrate back.
+#[cfg(feature = "full-node")]
+pub(crate) fn migrate_from_rocksdb_if_needed(
+	db_source: &DatabaseSource,
+	paritydb_root: &Path,
+) -> io::Result<()> {
+	match rocksdb_migration_source(db_source) {
+		Some(rocksdb_root) if migration::is_needed_to_paritydb(&rocksdb_root, paritydb_root) =>
+			migration::rocksdb_to_paritydb(&rocksdb_root, paritydb_root).map_err(Into::into),
+		_ => migration::ensure_not_migrated(&paritydb_path(paritydb_root)).map_err(Into::into),
+	}
+}
+
+/// The root directory of a `ParityDB` parachains DB whose data should be moved into the
+/// `RocksDB` one used by the node.
+///
+/// The counterpart of [`rocksdb_migration_source`].
+#[cfg(feature = "full-node")]
+fn paritydb_migration_source(db_source: &DatabaseSource) -> Option<PathBuf> {
+	match db_source {
+		DatabaseSource::RocksDb { path, .. } => {
+			let chain_dir = path.parent()?.parent()?;
+			Some(chain_dir.join("paritydb"))
+		},
+		_ => None,
+	}
+}
+
+/// Move the data of a previously used `ParityDB` parachains DB into the `RocksDB` one at
+/// `rocksdb_root`, if the node was switched back to `RocksDB`.
+///
+/// Does nothing if there is no `ParityDB` parachains DB or the `RocksDB` one is already in use.
+/// An interrupted migration is resumed. Fails if the `RocksDB` one was migrated away from and
+/// there is no data to migrate back.
+#[cfg(feature = "full-node")]
+pub(crate) fn migrate_from_paritydb_if_needed(
+	db_source: &DatabaseSource,
+	rocksdb_root: &Path,
+) -> io::Result<()> {
+	match paritydb_migration_source(db_source) {
+		Some(paritydb_root) if migration::is_needed_to_rocksdb(&paritydb_root, rocksdb_root) =>
+			migration::paritydb_to_rocksdb(&paritydb_root, rocksdb_root).map_err(Into::into),
+		_ => migration::ensure_not_migrated(&rocksdb_path(rocksdb_root)).map_err(Into::into),
+	}
+}
+
+/// Open an existing parachains DB for offline inspection and repair.
+///
+/// The node must not be running while the database is open.
+#[cfg(feature = "full-node")]
+pub fn open_for_inspection(
+	db_source: &DatabaseSource,
+) -> Result<inspect::ParachainsDb, crate::Error> {
+	let (root, kind) = locate(db_source)?;
+	inspect::ParachainsDb::open(root, kind).map_err(Into::into)
+}
+
 /// Open the database on disk, creating it if it doesn't exist.
 #[cfg(feature = "full-node")]
 pub fn open_creating_rocksdb(
@@ -104,19 +264,19 @@ pub fn open_creating_rocksdb(
 ) -> io::Result<Arc<dyn Database>> {
 	use kvdb_rocksdb::{Database, DatabaseConfig};
 
-	let path = root.join("parachains").join("db");
+	let path = rocksdb_path(&root);
 
-	let mut db_config = DatabaseConfig::with_columns(columns::v1::NUM_COLUMNS);
+	let mut db_config = DatabaseConfig::with_columns(columns::v3::NUM_COLUMNS);
 
 	let _ = db_config
 		.memory_budget
-		.insert(columns::v1::COL_AVAILABILITY_DATA, cache_sizes.availability_data);
+		.insert(columns::v3::COL_AVAILABILITY_DATA, cache_sizes.availability_data);
 	let _ = db_config
 		.memory_budget
-		.insert(columns::v1::COL_AVAILABILITY_META, cache_sizes.availability_meta);
+		.insert(columns::v3::COL_AVAILABILITY_META, cache_sizes.availability_meta);
 	let _ = db_config
 		.memory_budget
-		.insert(columns::v1::COL_APPROVAL_DATA, cache_sizes.approval_data);
+		.insert(columns::v3::COL_APPROVAL_DATA, cache_sizes.approval_data);
 
 	let path_str = path
 		.to_str()
@@ -127,7 +287,7 @@ pub fn open_creating_rocksdb(
 	let db = Database::open(&db_config, &path_str)?;
 	let db = polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter::new(
 		db,
-		columns::v1::ORDERED_COL,
+		columns::v3::ORDERED_COL,
 	);
 
 	Ok(Arc::new(db))
@@ -139,7 +299,7 @@ pub fn open_creating_paritydb(
 	root: PathBuf,
 	_cache_sizes: CacheSizes,
 ) -> io::Result<Arc<dyn Database>> {
-	let path = root.join("parachains");
+	let path = paritydb_path(&root);
 	let path_str = path
 		.to_str()
 		.ok_or_else(|| other_io_error(format!("Bad database path: {:?}", path)))?;
@@ -147,12 +307,12 @@ pub fn open_creating_paritydb(
 	std::fs::create_dir_all(&path_str)?;
 	upgrade::try_upgrade_db(&path, DatabaseKind::ParityDB)?;
 
-	let db = parity_db::Db::open_or_create(&upgrade::paritydb_version_1_config(&path))
+	let db = parity_db::Db::open_or_create(&upgrade::paritydb_version_3_config(&path))
 		.map_err(|err| io::Error::new(io::ErrorKind::Other, format!("{:?}", err)))?;
 
 	let db = polkadot_node_subsystem_util::database::paritydb_impl::DbAdapter::new(
 		db,
-		columns::v1::ORDERED_COL,
+		columns::v3::ORDERED_COL,
 	);
 	Ok(Arc::new(db))
 }
@@ -28,7 +28,7 @@ type Version = u32;
 const VERSION_FILE_NAME: &'static str = "parachain_db_version";
 
 /// Current db version.
-const CURRENT_VERSION: Version = 1;
+const CURRENT_VERSION: Version = 3;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -38,6 +38,12 @@ pub enum Error {
 	CorruptedVersionFile,
 	#[error("Future version (expected {current:?}, found {got:?})")]
 	FutureVersion { current: Version, got: Version },
+	#[error("The migration progress file format is incorrect")]
+	CorruptedMigrationProgressFile,
+	#[error("Column {column:?} has {got:?} keys after migration, expected {expected:?}")]
+	MigrationKeyCountMismatch { column: &'static str, expected: u64, got: u64 },
+	#[error("The parachains db is stale, it was migrated to {target:?}, which is gone")]
+	MigratedAway { target: String },
 }
 
 impl From<Error> for io::Error {
@@ -54,18 +60,35 @@ pub(crate) fn try_upgrade_db(db_path: &Path, db_kind: DatabaseKind) -> Result<()
 	let is_empty = db_path.read_dir().map_or(true, |mut d| d.next().is_none());
 	if !is_empty {
 		match get_db_version(db_path)? {
-			// 0 -> 1 migration
-			Some(0) => migrate_from_version_0_to_1(db_path, db_kind)?,
+			// 0 -> 1 -> 2 -> 3 migration
+			Some(0) => {
+				migrate_from_version_0_to_1(db_path, db_kind)?;
+				migrate_from_version_1_to_2(db_path, db_kind)?;
+				migrate_from_version_2_to_3(db_path, db_kind)?;
+			},
+			// 1 -> 2 -> 3 migration
+			Some(1) => {
+				migrate_from_version_1_to_2(db_path, db_kind)?;
+				migrate_from_version_2_to_3(db_path, db_kind)?;
+			},
+			// 2 -> 3 migration
+			Some(2) => migrate_from_version_2_to_3(db_path, db_kind)?,
 			// Already at current version, do nothing.
 			Some(CURRENT_VERSION) => (),
 			// This is an arbitrary future version, we don't handle it.
 			Some(v) => return Err(Error::FutureVersion { current: CURRENT_VERSION, got: v }),
-			// No version file. For `RocksDB` we dont need to do anything.
-			None if db_kind == DatabaseKind::RocksDB => (),
+			// No version file. `RocksDB` databases without one have the version 1 layout.
+			None if db_kind == DatabaseKind::RocksDB => {
+				migrate_from_version_1_to_2(db_path, db_kind)?;
+				migrate_from_version_2_to_3(db_path, db_kind)?;
+			},
 			// No version file. `ParityDB` did not previously have a version defined.
-			// We handle this as a `0 -> 1` migration.
-			None if db_kind == DatabaseKind::ParityDB =>
-				migrate_from_version_0_to_1(db_path, db_kind)?,
+			// We handle this as a `0 -> 1 -> 2 -> 3` migration.
+			None if db_kind == DatabaseKind::ParityDB => {
+				migrate_from_version_0_to_1(db_path, db_kind)?;
+				migrate_from_version_1_to_2(db_path, db_kind)?;
+				migrate_from_version_2_to_3(db_path, db_kind)?;
+			},
 			None => unreachable!(),
 		}
 	}
@@ -87,7 +110,7 @@ fn get_db_version(path: &Path) -> Result<Option<Version>, Error> {
 
 /// Writes current database version to the file.
 /// Creates a new file if the version file does not exist yet.
-fn update_version(path: &Path) -> Result<(), Error> {
+pub(super) fn update_version(path: &Path) -> Result<(), Error> {
 	fs::create_dir_all(path)?;
 	fs::write(version_file_path(path), CURRENT_VERSION.to_string()).map_err(Into::into)
 }
@@ -112,6 +135,32 @@ fn migrate_from_version_0_to_1(path: &Path, db_kind: DatabaseKind) -> Result<(),
 	})
 }
 
+fn migrate_from_version_1_to_2(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+	gum::info!(target: LOG_TARGET, "Migrating parachains db from version 1 to version 2 ...");
+
+	match db_kind {
+		DatabaseKind::ParityDB => paritydb_migrate_from_version_1_to_2(path),
+		DatabaseKind::RocksDB => rocksdb_migrate_from_version_1_to_2(path),
+	}
+	.and_then(|result| {
+		gum::info!(target: LOG_TARGET, "Migration complete! ");
+		Ok(result)
+	})
+}
+
+fn migrate_from_version_2_to_3(path: &Path, db_kind: DatabaseKind) -> Result<(), Error> {
+	gum::info!(target: LOG_TARGET, "Migrating parachains db from version 2 to version 3 ...");
+
+	match db_kind {
+		DatabaseKind::ParityDB => paritydb_migrate_from_version_2_to_3(path),
+		DatabaseKind::RocksDB => rocksdb_migrate_from_version_2_to_3(path),
+	}
+	.and_then(|result| {
+		gum::info!(target: LOG_TARGET, "Migration complete! ");
+		Ok(result)
+	})
+}
+
 /// Migration from version 0 to version 1:
 /// * the number of columns has changed from 3 to 5;
 fn rocksdb_migrate_from_version_0_to_1(path: &Path) -> Result<(), Error> {
@@ -129,6 +178,38 @@ fn rocksdb_migrate_from_version_0_to_1(path: &Path) -> Result<(), Error> {
 	Ok(())
 }
 
+/// Migration from version 1 to version 2:
+/// * the session window column was added.
+fn rocksdb_migrate_from_version_1_to_2(path: &Path) -> Result<(), Error> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+
+	let db_path = path
+		.to_str()
+		.ok_or_else(|| super::other_io_error("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(super::columns::v1::NUM_COLUMNS);
+	let db = Database::open(&db_cfg, db_path)?;
+
+	db.add_column()?;
+
+	Ok(())
+}
+
+/// Migration from version 2 to version 3:
+/// * the collator reputation column was added.
+fn rocksdb_migrate_from_version_2_to_3(path: &Path) -> Result<(), Error> {
+	use kvdb_rocksdb::{Database, DatabaseConfig};
+
+	let db_path = path
+		.to_str()
+		.ok_or_else(|| super::other_io_error("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(super::columns::v2::NUM_COLUMNS);
+	let db = Database::open(&db_cfg, db_path)?;
+
+	db.add_column()?;
+
+	Ok(())
+}
+
 // This currently clears columns which had their configs altered between versions.
 // The columns to be changed are constrained by the `allowed_columns` vector.
 fn paritydb_fix_columns(
@@ -197,6 +278,28 @@ pub(crate) fn paritydb_version_1_config(path: &Path) -> parity_db::Options {
 	options
 }
 
+/// Database configuration for version 2.
+pub(crate) fn paritydb_version_2_config(path: &Path) -> parity_db::Options {
+	let mut options =
+		parity_db::Options::with_columns(&path, super::columns::v2::NUM_COLUMNS as u8);
+	for i in columns::v2::ORDERED_COL {
+		options.columns[*i as usize].btree_index = true;
+	}
+
+	options
+}
+
+/// Database configuration for version 3.
+pub(crate) fn paritydb_version_3_config(path: &Path) -> parity_db::Options {
+	let mut options =
+		parity_db::Options::with_columns(&path, super::columns::v3::NUM_COLUMNS as u8);
+	for i in columns::v3::ORDERED_COL {
+		options.columns[*i as usize].btree_index = true;
+	}
+
+	options
+}
+
 /// Database configuration for version 0. This is useful just for testing.
 #[cfg(test)]
 pub(crate) fn paritydb_version_0_config(path: &Path) -> parity_db::Options {
@@ -224,6 +327,22 @@ fn paritydb_migrate_from_version_0_to_1(path: &Path) -> Result<(), Error> {
 	Ok(())
 }
 
+/// Migration from version 1 to version 2.
+/// The session window column is appended, no existing column is changed.
+fn paritydb_migrate_from_version_1_to_2(path: &Path) -> Result<(), Error> {
+	paritydb_fix_columns(path, paritydb_version_2_config(path), vec![])?;
+
+	Ok(())
+}
+
+/// Migration from version 2 to version 3.
+/// The ordered collator reputation column is appended, no existing column is changed.
+fn paritydb_migrate_from_version_2_to_3(path: &Path) -> Result<(), Error> {
+	paritydb_fix_columns(path, paritydb_version_3_config(path), vec![])?;
+
+	Ok(())
+}
+
 #[cfg(test)]
 mod tests {
 	#[test]
@@ -245,7 +364,7 @@ mod tests {
 
 		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
 
-		let db = Db::open(&paritydb_version_1_config(&path)).unwrap();
+		let db = Db::open(&paritydb_version_3_config(&path)).unwrap();
 		assert_eq!(
 			db.get(super::columns::v1::COL_DISPUTE_COORDINATOR_DATA as u8, b"1234").unwrap(),
 			None
@@ -255,4 +374,127 @@ mod tests {
 			Some("somevalue".as_bytes().to_vec())
 		);
 	}
+
+	#[test]
+	fn test_paritydb_migrate_1_2() {
+		use super::{columns::v3::*, *};
+		use parity_db::Db;
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+		{
+			let db = Db::open_or_create(&paritydb_version_1_config(&path)).unwrap();
+
+			db.commit(vec![(
+				COL_DISPUTE_COORDINATOR_DATA as u8,
+				b"1234".to_vec(),
+				Some(b"somevalue".to_vec()),
+			)])
+			.unwrap();
+		}
+		fs::write(version_file_path(path), "1").unwrap();
+
+		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		assert_eq!(get_db_version(path).unwrap(), Some(CURRENT_VERSION));
+
+		let db = Db::open(&paritydb_version_3_config(&path)).unwrap();
+		assert_eq!(
+			db.get(COL_DISPUTE_COORDINATOR_DATA as u8, b"1234").unwrap(),
+			Some("somevalue".as_bytes().to_vec())
+		);
+		db.commit(vec![(COL_SESSION_WINDOW_DATA as u8, b"5678".to_vec(), Some(b"v".to_vec()))])
+			.unwrap();
+		assert_eq!(db.get(COL_SESSION_WINDOW_DATA as u8, b"5678").unwrap(), Some(b"v".to_vec()));
+	}
+
+	#[test]
+	fn test_rocksdb_migrate_1_2() {
+		use super::{columns::v3::*, *};
+		use kvdb::KeyValueDB;
+		use kvdb_rocksdb::{Database, DatabaseConfig};
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+		let db_path = path.to_str().unwrap();
+		{
+			let db =
+				Database::open(&DatabaseConfig::with_columns(columns::v1::NUM_COLUMNS), db_path)
+					.unwrap();
+			let mut tx = db.transaction();
+			tx.put_vec(COL_APPROVAL_DATA, b"1234", b"somevalue".to_vec());
+			db.write(tx).unwrap();
+		}
+		fs::write(version_file_path(path), "1").unwrap();
+
+		try_upgrade_db(&path, DatabaseKind::RocksDB).unwrap();
+		assert_eq!(get_db_version(path).unwrap(), Some(CURRENT_VERSION));
+
+		let db = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), db_path).unwrap();
+		assert_eq!(db.get(COL_APPROVAL_DATA, b"1234").unwrap(), Some(b"somevalue".to_vec()));
+		assert_eq!(db.get(COL_SESSION_WINDOW_DATA, b"5678").unwrap(), None);
+	}
+
+	#[test]
+	fn test_paritydb_migrate_2_3() {
+		use super::{columns::v3::*, *};
+		use parity_db::Db;
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+		{
+			let db = Db::open_or_create(&paritydb_version_2_config(&path)).unwrap();
+
+			db.commit(vec![(COL_SESSION_WINDOW_DATA as u8, b"1234".to_vec(), Some(b"v".to_vec()))])
+				.unwrap();
+		}
+		fs::write(version_file_path(path), "2").unwrap();
+
+		try_upgrade_db(&path, DatabaseKind::ParityDB).unwrap();
+		assert_eq!(get_db_version(path).unwrap(), Some(CURRENT_VERSION));
+
+		let db = Db::open(&paritydb_version_3_config(&path)).unwrap();
+		assert_eq!(db.get(COL_SESSION_WINDOW_DATA as u8, b"1234").unwrap(), Some(b"v".to_vec()));
+		db.commit(vec![(
+			COL_COLLATOR_REPUTATION_DATA as u8,
+			b"5678".to_vec(),
+			Some(b"v".to_vec()),
+		)])
+		.unwrap();
+		assert_eq!(
+			db.get(COL_COLLATOR_REPUTATION_DATA as u8, b"5678").unwrap(),
+			Some(b"v".to_vec())
+		);
+	}
+
+	#[test]
+	fn test_rocksdb_migrate_2_3() {
+		use super::{columns::v3::*, *};
+		use kvdb::KeyValueDB;
+		use kvdb_rocksdb::{Database, DatabaseConfig};
+
+		let db_dir = tempfile::tempdir().unwrap();
+		let path = db_dir.path();
+		let db_path = path.to_str().unwrap();
+		{
+			let db =
+				Database::open(&DatabaseConfig::with_columns(columns::v2::NUM_COLUMNS), db_path)
+					.unwrap();
+			let mut tx = db.transaction();
+			tx.put_vec(COL_SESSION_WINDOW_DATA, b"1234", b"somevalue".to_vec());
+			db.write(tx).unwrap();
+		}
+		fs::write(version_file_path(path), "2").unwrap();
+
+		try_upgrade_db(&path, DatabaseKind::RocksDB).unwrap();
+		assert_eq!(get_db_version(path).unwrap(), Some(CURRENT_VERSION));
+
+		let db = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), db_path).unwrap();
+		assert_eq!(db.get(COL_SESSION_WINDOW_DATA, b"1234").unwrap(), Some(b"somevalue".to_vec()));
+		assert_eq!(db.get(COL_COLLATOR_REPUTATION_DATA, b"5678").unwrap(), None);
+
+		let mut tx = db.transaction();
+		tx.put_vec(COL_COLLATOR_REPUTATION_DATA, b"5678", b"v".to_vec());
+		db.write(tx).unwrap();
+		assert_eq!(db.get(COL_COLLATOR_REPUTATION_DATA, b"5678").unwrap(), Some(b"v".to_vec()));
+	}
 }
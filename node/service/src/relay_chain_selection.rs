@@ -31,6 +31,10 @@
 //! leaf returned from the chain selection subsystem by calling into other
 //! subsystems which yield information about approvals and disputes.
 //!
+//! How far these constraints may hold back finality is determined by the
+//! [`FinalityPolicy`]. Every vote is reported along with the constraint
+//! which limited it.
+//!
 //! [chain-selection-guide]: https://w3f.github.io/parachain-implementers-guide/protocol-chain-selection.html
 
 #![cfg(feature = "full-node")]
@@ -43,12 +47,15 @@ use polkadot_node_subsystem::messages::{
 	ApprovalVotingMessage, ChainSelectionMessage, DisputeCoordinatorMessage,
 	HighestApprovedAncestorBlock,
 };
-use polkadot_node_subsystem_util::metrics::{self, prometheus};
+use polkadot_node_subsystem_util::{
+	metrics::{self, prometheus},
+	TimeoutExt,
+};
 use polkadot_overseer::{AllMessages, Handle};
 use polkadot_primitives::v2::{
 	Block as PolkadotBlock, BlockNumber, Hash, Header as PolkadotHeader,
 };
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 /// The maximum amount of unfinalized blocks we are willing to allow due to approval checking
 /// or disputes.
@@ -60,6 +67,110 @@ const MAX_FINALITY_LAG: polkadot_primitives::v2::BlockNumber = PRIMITIVES_MAX_FI
 
 const LOG_TARGET: &str = "parachain::chain-selection";
 
+/// The valid range of [`FinalityPolicy::hold_back_depth`].
+///
+/// Approval checking regularly lags a few blocks behind the best leaf, holding back votes at
+/// lower depths would stall finality during normal operation. Beyond `MAX_FINALITY_LAG`, the
+/// safeguard forces finality regardless.
+pub const HOLD_BACK_DEPTH_RANGE: std::ops::RangeInclusive<BlockNumber> = 10..=MAX_FINALITY_LAG;
+
+/// What to vote for if the dispute coordinator does not determine the undisputed chain in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeCoordinatorFallback {
+	/// Do not vote beyond the block GRANDPA asked to vote on top of.
+	Target,
+	/// Vote for the highest approved ancestor without accounting for disputes.
+	///
+	/// Keeps finality going while the dispute coordinator is overloaded, at the risk
+	/// of finalizing a block which includes a disputed candidate.
+	ApprovedAncestor,
+}
+
+impl Default for DisputeCoordinatorFallback {
+	fn default() -> Self {
+		DisputeCoordinatorFallback::Target
+	}
+}
+
+/// The safety policy applied to finality votes on top of approval checking and disputes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinalityPolicy {
+	/// Do not vote beyond the block GRANDPA asked to vote on top of once the approved and
+	/// undisputed chain lags more than this number of blocks behind the best leaf.
+	///
+	/// Must be within [`HOLD_BACK_DEPTH_RANGE`], `None` never holds votes back.
+	pub hold_back_depth: Option<BlockNumber>,
+	/// How long to wait for the dispute coordinator, `None` waits until it responds.
+	pub dispute_coordinator_timeout: Option<Duration>,
+	/// What to vote for if the dispute coordinator did not respond in time or at all.
+	pub dispute_coordinator_fallback: DisputeCoordinatorFallback,
+	/// Warn if the best leaf is more than this number of blocks ahead of the block GRANDPA
+	/// asked to vote on top of.
+	pub finality_lag_alert: Option<BlockNumber>,
+}
+
+impl Default for FinalityPolicy {
+	fn default() -> Self {
+		FinalityPolicy {
+			hold_back_depth: None,
+			dispute_coordinator_timeout: None,
+			dispute_coordinator_fallback: DisputeCoordinatorFallback::default(),
+			finality_lag_alert: None,
+		}
+	}
+}
+
+/// The constraint which limited a finality vote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FinalityConstraint {
+	/// Nothing, the vote is for the best leaf containing the target.
+	BestLeaf,
+	/// No viable leaf contains the target.
+	NoViableLeaf,
+	/// The maximum block number passed in by GRANDPA.
+	MaxNumber,
+	/// The highest approved ancestor of the best leaf.
+	Approvals,
+	/// Approval voting returned block descriptions not matching the block numbers.
+	InconsistentApprovals,
+	/// The highest ancestor without disputed candidates.
+	Disputes,
+	/// The dispute coordinator did not respond in time or at all.
+	DisputeCoordinatorUnavailable,
+	/// The approved and undisputed chain lags beyond `MAX_FINALITY_LAG`, so we voted beyond it.
+	LagSafeguard,
+	/// The approved and undisputed chain lags beyond the hold back depth, so we did not vote
+	/// beyond the target.
+	HoldBack,
+}
+
+impl FinalityConstraint {
+	fn as_str(&self) -> &'static str {
+		match self {
+			FinalityConstraint::BestLeaf => "best-leaf",
+			FinalityConstraint::NoViableLeaf => "no-viable-leaf",
+			FinalityConstraint::MaxNumber => "max-number",
+			FinalityConstraint::Approvals => "approvals",
+			FinalityConstraint::InconsistentApprovals => "inconsistent-approvals",
+			FinalityConstraint::Disputes => "disputes",
+			FinalityConstraint::DisputeCoordinatorUnavailable => "dispute-coordinator-unavailable",
+			FinalityConstraint::LagSafeguard => "lag-safeguard",
+			FinalityConstraint::HoldBack => "hold-back",
+		}
+	}
+}
+
+/// A finality vote along with the constraint which limited it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FinalityVote {
+	/// The block to vote for.
+	pub hash: Hash,
+	/// The constraint which limited the vote.
+	pub constraint: FinalityConstraint,
+	/// The number of blocks the best leaf is ahead of the target, if known.
+	pub finality_lag: Option<BlockNumber>,
+}
+
 /// Prometheus metrics for chain-selection.
 #[derive(Debug, Default, Clone)]
 pub struct Metrics(Option<MetricsInner>);
@@ -68,6 +179,9 @@ pub struct Metrics(Option<MetricsInner>);
 struct MetricsInner {
 	approval_checking_finality_lag: prometheus::Gauge<prometheus::U64>,
 	disputes_finality_lag: prometheus::Gauge<prometheus::U64>,
+	finality_lag: prometheus::Gauge<prometheus::U64>,
+	finality_lag_alerts: prometheus::Counter<prometheus::U64>,
+	finality_vote_constraints: prometheus::CounterVec<prometheus::U64>,
 }
 
 impl metrics::Metrics for Metrics {
@@ -91,6 +205,30 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			finality_lag: prometheus::register(
+				prometheus::Gauge::with_opts(prometheus::Opts::new(
+					"polkadot_parachain_finality_lag",
+					"How far the head of the chain is ahead of the block GRANDPA votes on top of",
+				))?,
+				registry,
+			)?,
+			finality_lag_alerts: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_finality_lag_alerts_total",
+					"Number of finality votes with a finality lag above the alert threshold",
+				)?,
+				registry,
+			)?,
+			finality_vote_constraints: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_finality_vote_constraints_total",
+						"Number of finality votes by the constraint which limited them",
+					),
+					&["constraint"],
+				)?,
+				registry,
+			)?,
 		};
 
 		Ok(Metrics(Some(metrics)))
@@ -109,6 +247,27 @@ impl Metrics {
 			metrics.disputes_finality_lag.set(lag as _);
 		}
 	}
+
+	fn note_finality_lag(&self, lag: BlockNumber) {
+		if let Some(ref metrics) = self.0 {
+			metrics.finality_lag.set(lag as _);
+		}
+	}
+
+	fn on_finality_lag_alert(&self) {
+		if let Some(ref metrics) = self.0 {
+			metrics.finality_lag_alerts.inc();
+		}
+	}
+
+	fn on_finality_vote(&self, constraint: FinalityConstraint) {
+		if let Some(ref metrics) = self.0 {
+			metrics
+				.finality_vote_constraints
+				.with_label_values(&[constraint.as_str()])
+				.inc();
+		}
+	}
 }
 
 /// Determines whether the chain is a relay chain
@@ -164,13 +323,22 @@ where
 
 	/// Create a new [`SelectRelayChain`] wrapping the given chain backend
 	/// and a handle to the overseer.
-	pub fn new_with_overseer(backend: Arc<B>, overseer: Handle, metrics: Metrics) -> Self {
-		gum::debug!(target: LOG_TARGET, "Using dispute aware relay-chain selection algorithm",);
+	pub fn new_with_overseer(
+		backend: Arc<B>,
+		overseer: Handle,
+		metrics: Metrics,
+		policy: FinalityPolicy,
+	) -> Self {
+		gum::debug!(
+			target: LOG_TARGET,
+			?policy,
+			"Using dispute aware relay-chain selection algorithm",
+		);
 
 		SelectRelayChain {
 			longest_chain: sc_consensus::LongestChain::new(backend.clone()),
 			selection: IsDisputesAwareWithOverseer::Yes(SelectRelayChainInner::new(
-				backend, overseer, metrics,
+				backend, overseer, metrics, policy,
 			)),
 		}
 	}
@@ -228,6 +396,7 @@ pub struct SelectRelayChainInner<B, OH> {
 	backend: Arc<B>,
 	overseer: OH,
 	metrics: Metrics,
+	policy: FinalityPolicy,
 }
 
 impl<B, OH> SelectRelayChainInner<B, OH>
@@ -237,8 +406,8 @@ where
 {
 	/// Create a new [`SelectRelayChainInner`] wrapping the given chain backend
 	/// and a handle to the overseer.
-	pub fn new(backend: Arc<B>, overseer: OH, metrics: Metrics) -> Self {
-		SelectRelayChainInner { backend, overseer, metrics }
+	pub fn new(backend: Arc<B>, overseer: OH, metrics: Metrics, policy: FinalityPolicy) -> Self {
+		SelectRelayChainInner { backend, overseer, metrics, policy }
 	}
 
 	fn block_header(&self, hash: Hash) -> Result<PolkadotHeader, ConsensusError> {
@@ -276,6 +445,7 @@ where
 			backend: self.backend.clone(),
 			overseer: self.overseer.clone(),
 			metrics: self.metrics.clone(),
+			policy: self.policy.clone(),
 		}
 	}
 }
@@ -363,13 +533,53 @@ where
 	/// given target hash, and then constrain to the given block number.
 	///
 	/// It will also constrain the chain to only chains which are fully
-	/// approved, and chains which contain no disputes.
+	/// approved, and chains which contain no disputes, as far as the
+	/// [`FinalityPolicy`] allows.
 	pub(crate) async fn finality_target_with_longest_chain(
 		&self,
 		target_hash: Hash,
 		best_leaf: Hash,
 		maybe_max_number: Option<BlockNumber>,
 	) -> Result<Hash, ConsensusError> {
+		let vote = self.finality_vote(target_hash, best_leaf, maybe_max_number).await?;
+
+		self.metrics.on_finality_vote(vote.constraint);
+		if let Some(finality_lag) = vote.finality_lag {
+			self.metrics.note_finality_lag(finality_lag);
+
+			if self.policy.finality_lag_alert.map_or(false, |alert| finality_lag > alert) {
+				self.metrics.on_finality_lag_alert();
+				gum::warn!(
+					target: LOG_TARGET,
+					?target_hash,
+					vote = ?vote.hash,
+					finality_lag,
+					constraint = vote.constraint.as_str(),
+					"Finality is lagging behind the best leaf",
+				);
+			}
+		}
+
+		gum::debug!(
+			target: LOG_TARGET,
+			?target_hash,
+			vote = ?vote.hash,
+			finality_lag = ?vote.finality_lag,
+			constraint = vote.constraint.as_str(),
+			"Finality vote",
+		);
+
+		Ok(vote.hash)
+	}
+
+	/// Get the block to vote for as in `finality_target_with_longest_chain`,
+	/// along with the constraint which limited the vote.
+	pub(crate) async fn finality_vote(
+		&self,
+		target_hash: Hash,
+		best_leaf: Hash,
+		maybe_max_number: Option<BlockNumber>,
+	) -> Result<FinalityVote, ConsensusError> {
 		let mut overseer = self.overseer.clone();
 		gum::trace!(target: LOG_TARGET, ?best_leaf, "Longest chain");
 
@@ -391,12 +601,21 @@ where
 
 			match best {
 				// No viable leaves containing the block.
-				None => return Ok(target_hash),
+				None =>
+					return Ok(FinalityVote {
+						hash: target_hash,
+						constraint: FinalityConstraint::NoViableLeaf,
+						finality_lag: None,
+					}),
 				Some(best) => best,
 			}
 		};
 
 		let target_number = self.block_number(target_hash)?;
+		let finality_lag = self.block_number(subchain_head)?.saturating_sub(target_number);
+		let vote =
+			|hash, constraint| FinalityVote { hash, constraint, finality_lag: Some(finality_lag) };
+		let mut constraint = FinalityConstraint::BestLeaf;
 
 		// 1. Constrain the leaf according to `maybe_max_number`.
 		let subchain_head = match maybe_max_number {
@@ -411,7 +630,7 @@ where
 							"`finality_target` max number is less than target number",
 						);
 					}
-					return Ok(target_hash)
+					return Ok(vote(target_hash, FinalityConstraint::MaxNumber))
 				}
 				// find the current number.
 				let subchain_header = self.block_header(subchain_head)?;
@@ -432,6 +651,7 @@ where
 						?ancestor_hash,
 						"Grandpa walk backwards sub-chain head"
 					);
+					constraint = FinalityConstraint::MaxNumber;
 					ancestor_hash
 				}
 			},
@@ -464,6 +684,10 @@ where
 
 		gum::trace!(target: LOG_TARGET, ?subchain_head, "Ancestor approval restriction applied",);
 
+		if subchain_number < initial_leaf_number {
+			constraint = FinalityConstraint::Approvals;
+		}
+
 		let lag = initial_leaf_number.saturating_sub(subchain_number);
 		self.metrics.note_approval_checking_finality_lag(lag);
 
@@ -479,7 +703,7 @@ where
 					subchain_number,
 					"Mismatch of anticipated block descriptions and block number difference.",
 				);
-				return Ok(target_hash)
+				return Ok(vote(target_hash, FinalityConstraint::InconsistentApprovals))
 			}
 			// 3. Constrain according to disputes:
 			let (tx, rx) = oneshot::channel();
@@ -494,31 +718,66 @@ where
 				)
 				.await;
 
-			// Try to fetch response from `dispute-coordinator`. If an error occurs we just log it
-			// and return `target_hash` as maximal vote. It is safer to contain this error here
-			// and not push it up the stack to cause additional issues in GRANDPA/BABE.
-			let (lag, subchain_head) =
-				match rx.await.map_err(Error::DetermineUndisputedChainCanceled) {
-					// If request succeded we will receive (block number, block hash).
-					Ok((subchain_number, subchain_head)) => {
-						// The total lag accounting for disputes.
-						let lag_disputes = initial_leaf_number.saturating_sub(subchain_number);
-						self.metrics.note_disputes_finality_lag(lag_disputes);
-						(lag_disputes, subchain_head)
-					},
-					Err(e) => {
-						gum::error!(
-							target: LOG_TARGET,
-							error = ?e,
-							"Call to `DetermineUndisputedChain` failed",
-						);
-						// We need to return a sane finality target. But, we are unable to ensure we are not
-						// finalizing something that is being disputed or has been concluded as invalid. We will be
-						// conservative here and not vote for finality above the ancestor passed in.
-						return Ok(target_hash)
-					},
-				};
-			(lag, subchain_head)
+			let response = match self.policy.dispute_coordinator_timeout {
+				Some(timeout) => rx.timeout(timeout).await,
+				None => Some(rx.await),
+			};
+
+			// Try to fetch response from `dispute-coordinator`. If an error occurs or it does not
+			// respond in time, we just log it and fall back according to the policy. It is safer
+			// to contain this error here and not push it up the stack to cause additional issues
+			// in GRANDPA/BABE.
+			let undisputed = match response {
+				Some(Ok(undisputed)) => Some(undisputed),
+				Some(Err(e)) => {
+					gum::error!(
+						target: LOG_TARGET,
+						error = ?Error::DetermineUndisputedChainCanceled(e),
+						"Call to `DetermineUndisputedChain` failed",
+					);
+					None
+				},
+				None => {
+					gum::warn!(
+						target: LOG_TARGET,
+						timeout = ?self.policy.dispute_coordinator_timeout,
+						"Call to `DetermineUndisputedChain` timed out",
+					);
+					None
+				},
+			};
+
+			match undisputed {
+				// If request succeded we will receive (block number, block hash).
+				Some((undisputed_number, undisputed_head)) => {
+					// The total lag accounting for disputes.
+					let lag_disputes = initial_leaf_number.saturating_sub(undisputed_number);
+					self.metrics.note_disputes_finality_lag(lag_disputes);
+					if undisputed_number < subchain_number {
+						constraint = FinalityConstraint::Disputes;
+					}
+					(lag_disputes, undisputed_head)
+				},
+				None => {
+					constraint = FinalityConstraint::DisputeCoordinatorUnavailable;
+					match self.policy.dispute_coordinator_fallback {
+						// We need to return a sane finality target. But, we are unable to ensure
+						// we are not finalizing something that is being disputed or has been
+						// concluded as invalid. We will be conservative here and not vote for
+						// finality above the ancestor passed in.
+						DisputeCoordinatorFallback::Target =>
+							return Ok(vote(target_hash, constraint)),
+						DisputeCoordinatorFallback::ApprovedAncestor => {
+							gum::warn!(
+								target: LOG_TARGET,
+								?subchain_head,
+								"Voting for the highest approved ancestor regardless of disputes",
+							);
+							(lag, subchain_head)
+						},
+					}
+				},
+			}
 		};
 
 		gum::trace!(
@@ -532,11 +791,12 @@ where
 			// We need to constrain our vote as a safety net to
 			// ensure the network continues to finalize.
 			let safe_target = initial_leaf_number - MAX_FINALITY_LAG;
+			constraint = FinalityConstraint::LagSafeguard;
 
 			if safe_target <= target_number {
 				gum::warn!(target: LOG_TARGET, ?target_hash, "Safeguard enforced finalization");
 				// Minimal vote needs to be on the target number.
-				Ok(target_hash)
+				Ok(vote(target_hash, constraint))
 			} else {
 				// Otherwise we're looking for a descendant.
 				let initial_leaf_header = self.block_header(initial_leaf)?;
@@ -553,10 +813,14 @@ where
 					"Safeguard enforced finalization of child"
 				);
 
-				Ok(forced_target)
+				Ok(vote(forced_target, constraint))
 			}
+		} else if self.policy.hold_back_depth.map_or(false, |depth| lag > depth) {
+			// 5. The operator rather waits for approvals and disputes than to keep finalizing.
+			gum::warn!(target: LOG_TARGET, ?target_hash, lag, "Finality vote held back");
+			Ok(vote(target_hash, FinalityConstraint::HoldBack))
 		} else {
-			Ok(subchain_head)
+			Ok(vote(subchain_head, constraint))
 		}
 	}
 }
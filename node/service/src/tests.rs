@@ -57,9 +57,9 @@ impl OverseerHandleT for TestSubsystemSender {
 struct TestHarness {
 	virtual_overseer: VirtualOverseer,
 	case_vars: CaseVars,
-	/// The result of `fn finality_target` will be injected into the
+	/// The result of `fn finality_vote` will be injected into the
 	/// harness scope via this channel.
-	finality_target_rx: Receiver<Option<Hash>>,
+	finality_target_rx: Receiver<Option<FinalityVote>>,
 }
 
 #[derive(Default)]
@@ -67,6 +67,7 @@ struct HarnessConfig;
 
 fn test_harness<T: Future<Output = VirtualOverseer>>(
 	case_vars: CaseVars,
+	policy: FinalityPolicy,
 	test: impl FnOnce(TestHarness) -> T,
 ) {
 	let _ = env_logger::builder()
@@ -77,20 +78,18 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 	let pool = sp_core::testing::TaskExecutor::new();
 	let (mut context, virtual_overseer) = test_helpers::make_subsystem_context(pool);
 
-	let (finality_target_tx, finality_target_rx) = oneshot::channel::<Option<Hash>>();
+	let (finality_target_tx, finality_target_rx) = oneshot::channel::<Option<FinalityVote>>();
 
 	let select_relay_chain = SelectRelayChainInner::<TestChainStorage, TestSubsystemSender>::new(
 		Arc::new(case_vars.chain.clone()),
 		context.sender().clone(),
 		Default::default(),
+		policy,
 	);
 
 	let target_hash = case_vars.target_block.clone();
 	let selection_process = async move {
-		let best = select_relay_chain
-			.finality_target_with_longest_chain(target_hash, target_hash, None)
-			.await
-			.unwrap();
+		let best = select_relay_chain.finality_vote(target_hash, target_hash, None).await.unwrap();
 		finality_target_tx.send(Some(best)).unwrap();
 		()
 	};
@@ -403,7 +402,11 @@ async fn test_skeleton(
 /// Straight forward test case, where the test is not
 /// for integrity, but for different block relation structures.
 fn run_specialized_test_w_harness<F: FnOnce() -> CaseVars>(case_var_provider: F) {
-	test_harness(case_var_provider(), |test_harness| async move {
+	run_specialized_test_w_policy(case_var_provider(), Default::default())
+}
+
+fn run_specialized_test_w_policy(case_vars: CaseVars, policy: FinalityPolicy) {
+	test_harness(case_vars, policy, |test_harness| async move {
 		let TestHarness {
 			mut virtual_overseer,
 			finality_target_rx,
@@ -415,6 +418,7 @@ fn run_specialized_test_w_harness<F: FnOnce() -> CaseVars>(case_var_provider: F)
 					highest_approved_ancestor_block,
 					undisputed_chain,
 					expected_finality_target_result,
+					expected_constraint,
 				},
 			..
 		} = test_harness;
@@ -465,9 +469,18 @@ fn run_specialized_test_w_harness<F: FnOnce() -> CaseVars>(case_var_provider: F)
 		.await;
 
 		assert_matches!(finality_target_rx.await,
-		 	Ok(
-		 		finality_target_val,
-			) => assert_eq!(expected_finality_target_result, finality_target_val));
+		 Ok(
+			 finality_target_val,
+		) => {
+			assert_eq!(
+				expected_finality_target_result,
+				finality_target_val.as_ref().map(|vote| vote.hash),
+			);
+			assert_eq!(
+				Some(expected_constraint),
+				finality_target_val.map(|vote| vote.constraint),
+			);
+		});
 
 		virtual_overseer
 	});
@@ -497,6 +510,9 @@ struct CaseVars {
 
 	/// The returned value by `fn finality_target`.
 	expected_finality_target_result: Option<Hash>,
+
+	/// The constraint which limited the returned value.
+	expected_constraint: FinalityConstraint,
 }
 
 /// ```raw
@@ -527,6 +543,7 @@ fn chain_undisputed() -> CaseVars {
 		highest_approved_ancestor_block: Some(a3),
 		undisputed_chain: Some(a3),
 		expected_finality_target_result: Some(a3),
+		expected_constraint: FinalityConstraint::Approvals,
 	}
 }
 
@@ -558,6 +575,7 @@ fn chain_0() -> CaseVars {
 		highest_approved_ancestor_block: Some(a3),
 		undisputed_chain: Some(a2),
 		expected_finality_target_result: Some(a2),
+		expected_constraint: FinalityConstraint::Disputes,
 	}
 }
 
@@ -586,6 +604,7 @@ fn chain_1() -> CaseVars {
 		highest_approved_ancestor_block: Some(b2),
 		undisputed_chain: Some(b2),
 		expected_finality_target_result: Some(b2),
+		expected_constraint: FinalityConstraint::Approvals,
 	}
 }
 
@@ -614,6 +633,7 @@ fn chain_2() -> CaseVars {
 		highest_approved_ancestor_block: Some(a3),
 		undisputed_chain: Some(a1),
 		expected_finality_target_result: Some(a1),
+		expected_constraint: FinalityConstraint::Disputes,
 	}
 }
 
@@ -642,6 +662,7 @@ fn chain_3() -> CaseVars {
 		highest_approved_ancestor_block: Some(a3),
 		undisputed_chain: Some(a2),
 		expected_finality_target_result: Some(a2),
+		expected_constraint: FinalityConstraint::Disputes,
 	}
 }
 
@@ -673,6 +694,7 @@ fn chain_4() -> CaseVars {
 		highest_approved_ancestor_block: None,
 		undisputed_chain: None,
 		expected_finality_target_result: Some(does_not_exist),
+		expected_constraint: FinalityConstraint::NoViableLeaf,
 	}
 }
 
@@ -695,6 +717,7 @@ fn chain_5() -> CaseVars {
 		highest_approved_ancestor_block: Some(a2),
 		undisputed_chain: Some(a2),
 		expected_finality_target_result: Some(a2),
+		expected_constraint: FinalityConstraint::BestLeaf,
 	}
 }
 
@@ -732,6 +755,7 @@ fn chain_6() -> CaseVars {
 		highest_approved_ancestor_block: Some(approved),
 		undisputed_chain: Some(approved),
 		expected_finality_target_result: Some(approved),
+		expected_constraint: FinalityConstraint::Approvals,
 	}
 }
 
@@ -774,3 +798,91 @@ fn chain_sel_5_best_is_target_hash() {
 fn chain_sel_6_approval_lag() {
 	run_specialized_test_w_harness(chain_6);
 }
+
+#[test]
+fn chain_sel_6_approval_lag_beyond_hold_back_depth() {
+	// The highest approved block is 7 blocks behind the leaf, so the vote is held back at the
+	// target.
+	let policy = FinalityPolicy { hold_back_depth: Some(4), ..Default::default() };
+	let case_vars = chain_6();
+	let case_vars = CaseVars {
+		expected_finality_target_result: Some(case_vars.target_block),
+		expected_constraint: FinalityConstraint::HoldBack,
+		..case_vars
+	};
+
+	run_specialized_test_w_policy(case_vars, policy);
+}
+
+#[test]
+fn chain_sel_6_approval_lag_within_hold_back_depth() {
+	let policy = FinalityPolicy { hold_back_depth: Some(7), ..Default::default() };
+
+	run_specialized_test_w_policy(chain_6(), policy);
+}
+
+/// Runs the given case without the dispute coordinator ever responding.
+fn run_dispute_coordinator_timeout_test(
+	case_vars: CaseVars,
+	fallback: DisputeCoordinatorFallback,
+	expected_finality_target_result: Hash,
+) {
+	let policy = FinalityPolicy {
+		dispute_coordinator_timeout: Some(Duration::from_millis(10)),
+		dispute_coordinator_fallback: fallback,
+		..Default::default()
+	};
+
+	test_harness(case_vars, policy, |test_harness| async move {
+		let TestHarness { mut virtual_overseer, finality_target_rx, case_vars } = test_harness;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ChainSelection(ChainSelectionMessage::BestLeafContaining(_, tx)) => {
+				tx.send(case_vars.best_chain_containing_block).unwrap();
+			}
+		);
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::ApprovalVoting(
+				ApprovalVotingMessage::ApprovedAncestor(leaf, number, tx)
+			) => {
+				tx.send(case_vars.chain.highest_approved_ancestors(number, leaf)).unwrap();
+			}
+		);
+		// Keep the sender alive, so the request times out instead of being canceled.
+		let _tx = assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::DisputeCoordinator(
+				DisputeCoordinatorMessage::DetermineUndisputedChain { tx, .. }
+			) => tx
+		);
+
+		assert_matches!(finality_target_rx.await, Ok(Some(vote)) => {
+			assert_eq!(vote.hash, expected_finality_target_result);
+			assert_eq!(vote.constraint, FinalityConstraint::DisputeCoordinatorUnavailable);
+		});
+
+		virtual_overseer
+	});
+}
+
+#[test]
+fn dispute_coordinator_timeout_votes_for_target() {
+	let case_vars = chain_undisputed();
+	let target = case_vars.target_block;
+
+	run_dispute_coordinator_timeout_test(case_vars, DisputeCoordinatorFallback::Target, target);
+}
+
+#[test]
+fn dispute_coordinator_timeout_votes_for_approved_ancestor() {
+	let case_vars = chain_undisputed();
+	let approved = case_vars.highest_approved_ancestor_block.unwrap();
+
+	run_dispute_coordinator_timeout_test(
+		case_vars,
+		DisputeCoordinatorFallback::ApprovedAncestor,
+		approved,
+	);
+}
@@ -19,8 +19,12 @@
 #![warn(missing_docs)]
 
 use polkadot_node_subsystem::{
-	messages::AllMessages, overseer, FromOrchestra, OverseerSignal, SpawnGlue, SpawnedSubsystem,
-	SubsystemError, SubsystemResult,
+	messages::AllMessages,
+	overseer::{
+		self,
+		recorder::{Record, RecordedEvent},
+	},
+	FromOrchestra, OverseerSignal, SpawnGlue, SpawnedSubsystem, SubsystemError, SubsystemResult,
 };
 use polkadot_node_subsystem_util::TimeoutExt;
 
@@ -325,6 +329,44 @@ pub fn subsystem_test_harness<M, OverseerFactory, Overseer, TestFactory, Test>(
 	});
 }
 
+/// Replay the messages and signals an overseer
+/// [`MessageRecorder`](overseer::MessageRecorder) recorded for `destination`.
+///
+/// Records are fed to the subsystem in order, each one once the subsystem has read the
+/// previous one. Messages whose payload was not captured, or which `extract` does not map
+/// to the subsystem's message type, are skipped. Requests the subsystem sends while
+/// replaying must be served concurrently via [`TestSubsystemContextHandle::rx`].
+///
+/// Returns the number of messages and signals replayed.
+pub async fn replay_records<M>(
+	tx: &mut SingleItemSink<FromOrchestra<M>>,
+	records: impl IntoIterator<Item = Record>,
+	destination: &str,
+	extract: impl Fn(AllMessages) -> Option<M>,
+) -> usize {
+	let mut replayed = 0;
+	for record in records {
+		let from_overseer = match record.event {
+			RecordedEvent::Signal(signal) => FromOrchestra::Signal(signal.into_signal()),
+			RecordedEvent::Message { destination: to, payload, .. } if to == destination =>
+				match payload.into_message().and_then(&extract) {
+					Some(msg) => FromOrchestra::Communication { msg },
+					None => continue,
+				},
+			RecordedEvent::Message { .. } => continue,
+		};
+
+		tx.send(from_overseer)
+			.timeout(TestSubsystemContextHandle::<M>::TIMEOUT)
+			.await
+			.expect("`fn replay_records` does not timeout")
+			.expect("Test subsystem no longer live");
+		replayed += 1;
+	}
+
+	replayed
+}
+
 /// A forward subsystem that implements [`Subsystem`].
 ///
 /// It forwards all communication from the overseer to the internal message
@@ -395,8 +437,15 @@ macro_rules! arbitrary_order {
 mod tests {
 	use super::*;
 	use futures::executor::block_on;
-	use polkadot_node_subsystem::messages::CollatorProtocolMessage;
-	use polkadot_overseer::{dummy::dummy_overseer_builder, Handle, HeadSupportsParachains};
+	use polkadot_node_subsystem::{
+		messages::{ChainSelectionMessage, CollatorProtocolMessage},
+		SubsystemContext,
+	};
+	use polkadot_overseer::{
+		dummy::dummy_overseer_builder,
+		recorder::{RecordedPayload, RecordedSignal},
+		Handle, HeadSupportsParachains,
+	};
 	use polkadot_primitives::v2::Hash;
 	use sp_core::traits::SpawnNamed;
 
@@ -447,4 +496,48 @@ mod tests {
 		assert_eq!(first, 11_usize);
 		assert_eq!(second, 0);
 	}
+
+	#[test]
+	fn replay_feeds_recorded_messages_for_destination() {
+		let approved = Hash::repeat_byte(1);
+		let message = |destination: &str, payload| Record {
+			at: 0,
+			event: RecordedEvent::Message {
+				origin: "approval-voting".to_owned(),
+				destination: destination.to_owned(),
+				payload,
+			},
+		};
+		let records = vec![
+			Record {
+				at: 0,
+				event: RecordedEvent::Signal(RecordedSignal::BlockFinalized(approved, 1)),
+			},
+			message("chain-selection", RecordedPayload::Opaque),
+			message("approval-distribution", RecordedPayload::ChainSelectionApproved(approved)),
+			message("chain-selection", RecordedPayload::ChainSelectionApproved(approved)),
+		];
+
+		let (mut ctx, mut handle) =
+			make_subsystem_context::<ChainSelectionMessage, _>(TaskExecutor::new());
+
+		let replay = replay_records(&mut handle.tx, records, "chain-selection", |msg| match msg {
+			AllMessages::ChainSelection(msg) => Some(msg),
+			_ => None,
+		});
+		let receive = async move {
+			assert!(matches!(
+				ctx.recv().await.unwrap(),
+				FromOrchestra::Signal(OverseerSignal::BlockFinalized(hash, 1)) if hash == approved
+			));
+			assert!(matches!(
+				ctx.recv().await.unwrap(),
+				FromOrchestra::Communication { msg: ChainSelectionMessage::Approved(hash) }
+					if hash == approved
+			));
+		};
+
+		let (replayed, ()) = block_on(future::join(replay, receive));
+		assert_eq!(replayed, 2);
+	}
 }
@@ -210,6 +210,35 @@ pub enum CollatorProtocolMessage {
 	///
 	/// The hash is the relay parent.
 	Seconded(Hash, SignedFullStatement),
+	/// Query the reputations of collators, either of all paras or only the given one.
+	///
+	/// Collators known only from the operator's allow and deny lists are included as well.
+	/// Only answered with any reputations on the validator side of the protocol.
+	CollatorReputations(Option<ParaId>, oneshot::Sender<Vec<CollatorReputation>>),
+}
+
+/// Reputation of a collator for a para, see `CollatorProtocolMessage::CollatorReputations`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollatorReputation {
+	/// The para the collator collates for.
+	pub para_id: ParaId,
+	/// The collator.
+	pub collator_id: CollatorId,
+	/// The score accumulated from the behavior of the collator, persisted across restarts.
+	pub score: i32,
+	/// How the collator is treated by the operator's allow and deny lists.
+	pub listing: CollatorListing,
+}
+
+/// How a collator is treated by the operator's allow and deny lists of a para.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollatorListing {
+	/// The para has no allow list and the collator is not denied.
+	Unlisted,
+	/// The collator is on the allow list of the para.
+	Allowed,
+	/// The collator is denied, either explicitly or by not being on the allow list of the para.
+	Denied,
 }
 
 impl Default for CollatorProtocolMessage {
@@ -297,6 +326,27 @@ pub enum DisputeCoordinatorMessage {
 		/// The block to vote on, might be base in case there is no better.
 		tx: oneshot::Sender<(BlockNumber, Hash)>,
 	},
+	/// Fetch the spam slot use of all validators with unconfirmed disputes.
+	SpamSlots(oneshot::Sender<Vec<SpamSlotUsage>>),
+}
+
+/// Spam slot use of a validator in a session, see `DisputeCoordinatorMessage::SpamSlots`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpamSlotUsage {
+	/// The session of the unconfirmed disputes.
+	pub session: SessionIndex,
+	/// The index of the validator in the session.
+	pub validator_index: ValidatorIndex,
+	/// The public key of the validator.
+	pub validator: ValidatorId,
+	/// Number of unconfirmed disputes the validator raised.
+	pub used: u32,
+	/// Number of unconfirmed disputes the validator may raise.
+	pub limit: u32,
+	/// Decayed number of the validator's disputes which never got confirmed, in thousandths.
+	pub unconfirmed_score: u32,
+	/// Decayed number of the validator's disputes which concluded invalid, in thousandths.
+	pub confirmed_score: u32,
 }
 
 /// The result of `DisputeCoordinatorMessage::ImportStatements`.
@@ -474,6 +524,20 @@ impl BitfieldDistributionMessage {
 	}
 }
 
+// Peers re-gossip bitfields for every relay parent, so a lagging subsystem may skip some.
+// Our own bitfields go out first, they are what peers wait for. Neither they nor updates
+// of the network state must ever be dropped.
+impl orchestra::PickLane for BitfieldDistributionMessage {
+	fn lane(&self) -> orchestra::metered::Lane {
+		use orchestra::metered::Lane;
+		match self {
+			Self::DistributeBitfield(..) => Lane::Urgent,
+			Self::NetworkBridgeUpdate(NetworkBridgeEvent::PeerMessage(..)) => Lane::Droppable,
+			Self::NetworkBridgeUpdate(_) => Lane::Normal,
+		}
+	}
+}
+
 /// Bitfield signing message.
 ///
 /// Currently non-instantiable.
@@ -607,6 +671,98 @@ pub enum ChainSelectionMessage {
 	/// Request the best leaf containing the given block in its ancestry. Return `None` if
 	/// there is no such leaf.
 	BestLeafContaining(Hash, oneshot::Sender<Option<Hash>>),
+	/// Request all unfinalized blocks known to chain selection in ascending order by number,
+	/// with their viability, approval and stagnation state.
+	BlockTree(oneshot::Sender<Vec<ChainSelectionBlock>>),
+	/// Apply an operator override to the given unfinalized block, which is recorded in the
+	/// override log. Returns the updated block, or `None` if the block is unknown.
+	Override(Hash, ChainSelectionOverride, oneshot::Sender<Option<ChainSelectionBlock>>),
+	/// Request all operator overrides applied so far, oldest first.
+	OverrideLog(oneshot::Sender<Vec<ChainSelectionOverrideRecord>>),
+}
+
+/// The approval state of a block as tracked by chain selection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainSelectionApproval {
+	/// All candidates of the block are approved.
+	Approved,
+	/// The block is not approved yet, but not stagnant either.
+	Unapproved,
+	/// The block was not approved in time and is not built upon anymore.
+	Stagnant,
+}
+
+impl ChainSelectionApproval {
+	/// The name of the approval state, as exposed to operators.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ChainSelectionApproval::Approved => "approved",
+			ChainSelectionApproval::Unapproved => "unapproved",
+			ChainSelectionApproval::Stagnant => "stagnant",
+		}
+	}
+}
+
+/// An unfinalized block as seen by chain selection, see `ChainSelectionMessage::BlockTree`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainSelectionBlock {
+	/// The hash of the block.
+	pub hash: Hash,
+	/// The number of the block.
+	pub number: BlockNumber,
+	/// The hash of the parent block.
+	pub parent_hash: Hash,
+	/// The weight of the block.
+	pub weight: BlockWeight,
+	/// The approval state of the block.
+	pub approval: ChainSelectionApproval,
+	/// Unix timestamp in seconds at which the block is checked for stagnation, if pending.
+	pub stagnant_at: Option<u64>,
+	/// Whether the block was reverted, either by a descendant or by the operator.
+	pub explicitly_reverted: bool,
+	/// The earliest ancestor which is reverted or stagnant, making this block unviable.
+	pub earliest_unviable_ancestor: Option<Hash>,
+	/// Whether the block and all its ancestors are viable.
+	pub viable: bool,
+	/// Whether the block is a viable leaf, i.e. considered for building new blocks.
+	pub viable_leaf: bool,
+}
+
+/// An operator override of the chain selection state of a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainSelectionOverride {
+	/// Mark the block as reverted, making it and its descendants unviable.
+	Revert,
+	/// Undo the stagnation of the block, giving it another full stagnation timeout to get
+	/// approved.
+	Unstagnate,
+}
+
+impl ChainSelectionOverride {
+	/// All overrides, in declaration order.
+	pub const ALL: [ChainSelectionOverride; 2] =
+		[ChainSelectionOverride::Revert, ChainSelectionOverride::Unstagnate];
+
+	/// The name of the override, as exposed to operators.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ChainSelectionOverride::Revert => "revert",
+			ChainSelectionOverride::Unstagnate => "unstagnate",
+		}
+	}
+}
+
+/// An entry of the chain selection override log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainSelectionOverrideRecord {
+	/// Unix timestamp in seconds at which the override was applied.
+	pub timestamp: u64,
+	/// The hash of the block the override was applied to.
+	pub block_hash: Hash,
+	/// The number of the block the override was applied to.
+	pub block_number: BlockNumber,
+	/// The applied override.
+	pub kind: ChainSelectionOverride,
 }
 
 impl ChainSelectionMessage {
@@ -619,6 +775,9 @@ impl ChainSelectionMessage {
 			ChainSelectionMessage::Approved(_) => None,
 			ChainSelectionMessage::Leaves(_) => None,
 			ChainSelectionMessage::BestLeafContaining(..) => None,
+			ChainSelectionMessage::BlockTree(_) => None,
+			ChainSelectionMessage::Override(..) => None,
+			ChainSelectionMessage::OverrideLog(_) => None,
 		}
 	}
 }
@@ -898,6 +1057,22 @@ pub enum ApprovalVotingMessage {
 	///
 	/// Should not be sent unless the block hash within the indirect vote is known.
 	CheckAndImportApproval(IndirectSignedApprovalVote, oneshot::Sender<ApprovalCheckResult>),
+	/// Check and import a batch of assignments, verifying their certificates together.
+	///
+	/// The assignments are imported in order and the results are returned in the same order.
+	/// Should not be sent unless the block hashes are known.
+	CheckAndImportAssignments(
+		Vec<(IndirectAssignmentCert, CandidateIndex)>,
+		oneshot::Sender<Vec<AssignmentCheckResult>>,
+	),
+	/// Check and import a batch of approval votes, verifying their signatures together.
+	///
+	/// The votes are imported in order and the results are returned in the same order.
+	/// Should not be sent unless the block hashes within the votes are known.
+	CheckAndImportApprovals(
+		Vec<IndirectSignedApprovalVote>,
+		oneshot::Sender<Vec<ApprovalCheckResult>>,
+	),
 	/// Returns the highest possible ancestor hash of the provided block hash which is
 	/// acceptable to vote on finality for.
 	/// The `BlockNumber` provided is the number of the block's ancestor which is the
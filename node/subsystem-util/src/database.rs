@@ -18,6 +18,10 @@
 
 pub use kvdb::{DBTransaction, DBValue, KeyValueDB};
 
+mod debug_json;
+
+pub use debug_json::debug_to_json;
+
 /// Database trait with ordered key capacity.
 pub trait Database: KeyValueDB {
 	/// Check if column allows content iteration
@@ -25,6 +29,26 @@ pub trait Database: KeyValueDB {
 	fn is_indexed_column(&self, col: u32) -> bool;
 }
 
+/// A database entry decoded into a human readable form.
+///
+/// Used by offline tooling to inspect the on-disk state of a subsystem.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedEntry {
+	/// The raw key of the entry.
+	pub key: Vec<u8>,
+	/// A short name of the record type stored under the key.
+	pub kind: &'static str,
+	/// The decoded value.
+	pub value: serde_json::Value,
+}
+
+impl DecodedEntry {
+	/// Create a new entry, converting the `Debug` rendering of the value into JSON.
+	pub fn new(key: impl Into<Vec<u8>>, kind: &'static str, value: impl std::fmt::Debug) -> Self {
+		DecodedEntry { key: key.into(), kind, value: debug_to_json(&format!("{:?}", value)) }
+	}
+}
+
 /// Implementation for database supporting `KeyValueDB` already.
 pub mod kvdb_impl {
 	use super::{DBTransaction, DBValue, Database, KeyValueDB};
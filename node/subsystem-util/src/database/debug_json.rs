@@ -0,0 +1,368 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Conversion of `Debug` renderings into JSON.
+//!
+//! Most of the types persisted by subsystems do not implement `serde::Serialize`, but all of them
+//! implement `Debug`, whose output has a regular enough structure to be turned into JSON.
+
+use serde_json::{Map, Number, Value};
+
+/// Convert the `Debug` rendering of a value into JSON.
+///
+/// Structs become objects, sequences, tuples and sets become arrays and maps become objects keyed
+/// by the rendered keys. Tuple structs and tuple variants become objects with a single entry keyed
+/// by their name, `Some` is unwrapped and `None` is `null`. Renderings which can not be
+/// parsed are kept as a string.
+pub fn debug_to_json(debug: &str) -> Value {
+	let mut parser = Parser { input: debug, pos: 0 };
+	match parser.value() {
+		Some(value) if parser.at_end() => value,
+		_ => Value::String(debug.into()),
+	}
+}
+
+struct Parser<'a> {
+	input: &'a str,
+	pos: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn rest(&self) -> &'a str {
+		let input = self.input;
+		&input[self.pos..]
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.rest().chars().next()
+	}
+
+	fn advance(&mut self) {
+		self.pos += self.peek().map_or(0, char::len_utf8);
+	}
+
+	fn skip_whitespace(&mut self) {
+		while self.peek().map_or(false, char::is_whitespace) {
+			self.advance();
+		}
+	}
+
+	fn at_end(&mut self) -> bool {
+		self.skip_whitespace();
+		self.rest().is_empty()
+	}
+
+	/// Consume `token` after optional whitespace, if it is next.
+	fn eat(&mut self, token: &str) -> bool {
+		self.skip_whitespace();
+		if self.rest().starts_with(token) {
+			self.pos += token.len();
+			true
+		} else {
+			false
+		}
+	}
+
+	fn expect(&mut self, token: &str) -> Option<()> {
+		self.eat(token).then(|| ())
+	}
+
+	fn value(&mut self) -> Option<Value> {
+		self.skip_whitespace();
+		match self.peek()? {
+			'"' => self.string('"').map(Value::String),
+			'\'' => self.string('\'').map(Value::String),
+			'[' => {
+				self.advance();
+				self.sequence("]").map(Value::Array)
+			},
+			'(' => {
+				self.advance();
+				let items = self.sequence(")")?;
+				Some(if items.is_empty() { Value::Null } else { Value::Array(items) })
+			},
+			'{' => {
+				self.advance();
+				self.map_or_set()
+			},
+			c if c.is_ascii_digit() || c == '-' => Some(self.number()),
+			c if c.is_alphabetic() || c == '_' => self.named(),
+			_ => None,
+		}
+	}
+
+	/// Comma separated values up to the closing delimiter.
+	fn sequence(&mut self, close: &str) -> Option<Vec<Value>> {
+		let mut items = Vec::new();
+		loop {
+			if self.eat(close) {
+				return Some(items)
+			}
+			items.push(self.value()?);
+			if !self.eat(",") {
+				self.expect(close)?;
+				return Some(items)
+			}
+		}
+	}
+
+	fn map_or_set(&mut self) -> Option<Value> {
+		let mut map = Map::new();
+		let mut set = Vec::new();
+		loop {
+			if self.eat("}") {
+				break
+			}
+			let item = self.value()?;
+			if self.eat(":") {
+				let key = match item {
+					Value::String(key) => key,
+					key => key.to_string(),
+				};
+				map.insert(key, self.value()?);
+			} else {
+				set.push(item);
+			}
+			if !self.eat(",") {
+				self.expect("}")?;
+				break
+			}
+		}
+
+		match (map.is_empty(), set.is_empty()) {
+			(_, true) => Some(Value::Object(map)),
+			(true, false) => Some(Value::Array(set)),
+			(false, false) => None,
+		}
+	}
+
+	fn identifier(&mut self) -> Option<&'a str> {
+		self.skip_whitespace();
+		let start = self.pos;
+		while let Some(c) = self.peek() {
+			if c.is_alphanumeric() || c == '_' {
+				self.advance();
+			} else if self.rest().starts_with("::") {
+				self.pos += 2;
+			} else {
+				break
+			}
+		}
+		let input = self.input;
+		(self.pos > start).then(|| &input[start..self.pos])
+	}
+
+	/// A struct, tuple struct, enum variant or unit value.
+	fn named(&mut self) -> Option<Value> {
+		let name = self.identifier()?;
+		// Generic arguments, as rendered by `BitVec`, carry no information.
+		if self.rest().starts_with('<') {
+			self.skip_generics()?;
+		}
+
+		if self.eat("{") {
+			let fields = self.fields()?;
+			// `BitVec` renders its bits after the struct holding the internal representation.
+			if self.eat("[") {
+				return self.sequence("]").map(Value::Array)
+			}
+			Some(Value::Object(fields))
+		} else if self.eat("(") {
+			let mut items = self.sequence(")")?;
+			let inner = if items.len() == 1 { items.remove(0) } else { Value::Array(items) };
+			if name == "Some" {
+				Some(inner)
+			} else {
+				let mut map = Map::new();
+				map.insert(name.into(), inner);
+				Some(Value::Object(map))
+			}
+		} else {
+			Some(match name {
+				"None" => Value::Null,
+				"true" => Value::Bool(true),
+				"false" => Value::Bool(false),
+				name => Value::String(name.into()),
+			})
+		}
+	}
+
+	fn fields(&mut self) -> Option<Map<String, Value>> {
+		let mut fields = Map::new();
+		loop {
+			if self.eat("}") {
+				return Some(fields)
+			}
+			// Non-exhaustive structs end with `..`.
+			if self.eat("..") {
+				self.expect("}")?;
+				return Some(fields)
+			}
+			let name = self.identifier()?;
+			self.expect(":")?;
+			fields.insert(name.into(), self.value()?);
+			if !self.eat(",") {
+				self.expect("}")?;
+				return Some(fields)
+			}
+		}
+	}
+
+	fn skip_generics(&mut self) -> Option<()> {
+		let mut depth = 0;
+		loop {
+			match self.peek()? {
+				'<' => depth += 1,
+				'>' => depth -= 1,
+				_ => {},
+			}
+			self.advance();
+			if depth == 0 {
+				return Some(())
+			}
+		}
+	}
+
+	/// Numbers, but also hex strings like hashes and units like durations, which are kept as
+	/// strings.
+	fn number(&mut self) -> Value {
+		let start = self.pos;
+		self.advance();
+		while self.peek().map_or(false, |c| c.is_alphanumeric() || c == '.' || c == '_') {
+			self.advance();
+		}
+
+		let token = &self.input[start..self.pos];
+		if let Ok(n) = token.parse::<u64>() {
+			Value::from(n)
+		} else if let Ok(n) = token.parse::<i64>() {
+			Value::from(n)
+		} else if let Some(n) = token
+			.contains('.')
+			.then(|| token.parse::<f64>().ok())
+			.flatten()
+			.and_then(Number::from_f64)
+		{
+			Value::Number(n)
+		} else {
+			Value::String(token.into())
+		}
+	}
+
+	fn string(&mut self, quote: char) -> Option<String> {
+		self.advance();
+		let mut out = String::new();
+		let mut chars = self.rest().char_indices();
+		while let Some((i, c)) = chars.next() {
+			match c {
+				c if c == quote => {
+					self.pos += i + 1;
+					return Some(out)
+				},
+				'\\' => match chars.next()?.1 {
+					'n' => out.push('\n'),
+					'r' => out.push('\r'),
+					't' => out.push('\t'),
+					'0' => out.push('\0'),
+					'u' => {
+						if chars.next()?.1 != '{' {
+							return None
+						}
+						let mut code = String::new();
+						loop {
+							match chars.next()?.1 {
+								'}' => break,
+								c => code.push(c),
+							}
+						}
+						out.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+					},
+					c => out.push(c),
+				},
+				c => out.push(c),
+			}
+		}
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+	use std::collections::BTreeMap;
+
+	#[derive(Debug)]
+	struct Meta {
+		available: bool,
+		state: State,
+		chunks: Vec<u8>,
+		hash: &'static str,
+	}
+
+	#[allow(dead_code)]
+	#[derive(Debug)]
+	enum State {
+		Unavailable(u64),
+		Unfinalized { at: (u32, i32), descendants: Option<()> },
+	}
+
+	#[test]
+	fn structs_and_enums_become_objects() {
+		let meta = Meta {
+			available: false,
+			state: State::Unavailable(5),
+			chunks: vec![1, 2],
+			hash: "0x01\"02",
+		};
+
+		assert_eq!(
+			debug_to_json(&format!("{:?}", meta)),
+			json!({
+				"available": false,
+				"state": { "Unavailable": 5 },
+				"chunks": [1, 2],
+				"hash": "0x01\"02",
+			}),
+		);
+
+		let state = State::Unfinalized { at: (10, -1), descendants: None };
+		assert_eq!(
+			debug_to_json(&format!("{:?}", state)),
+			json!({ "at": [10, -1], "descendants": null })
+		);
+	}
+
+	#[test]
+	fn maps_options_and_numbers() {
+		let map = vec![((1u32, 2u32), Some(1.5f64)), ((3, 4), None)]
+			.into_iter()
+			.collect::<BTreeMap<_, _>>();
+		assert_eq!(debug_to_json(&format!("{:?}", map)), json!({ "[1,2]": 1.5, "[3,4]": null }));
+
+		assert_eq!(debug_to_json(&format!("{:?}", u128::MAX)), json!(u128::MAX.to_string()));
+		assert_eq!(
+			debug_to_json(&format!("{:?}", std::time::Duration::from_millis(1500))),
+			json!("1.5s"),
+		);
+	}
+
+	#[test]
+	fn unparsable_renderings_are_kept() {
+		assert_eq!(debug_to_json("Foo { a: 1"), json!("Foo { a: 1"));
+		assert_eq!(debug_to_json("<unknown>"), json!("<unknown>"));
+	}
+}
@@ -18,16 +18,29 @@
 //!
 //! This is useful for consensus components which need to stay up-to-date about recent sessions but don't
 //! care about the state of particular blocks.
+//!
+//! The window is persisted to the database, so it survives restarts and does not depend on the
+//! state of old blocks still being available.
 
 pub use polkadot_node_primitives::{new_session_window_size, SessionWindowSize};
 use polkadot_primitives::v2::{Hash, SessionIndex, SessionInfo};
 
 use futures::channel::oneshot;
+use kvdb::DBTransaction;
+use parity_scale_codec::{Decode, Encode};
 use polkadot_node_subsystem::{
 	errors::RuntimeApiError,
 	messages::{RuntimeApiMessage, RuntimeApiRequest},
 	overseer,
 };
+use std::sync::Arc;
+
+use crate::database::Database;
+
+const LOG_TARGET: &str = "parachain::rolling-session-window";
+
+/// The key the window is stored under.
+pub const SESSION_WINDOW_KEY: &[u8] = b"session_window";
 
 /// Sessions unavailable in state to cache.
 #[derive(Debug, Clone, thiserror::Error)]
@@ -83,19 +96,39 @@ pub enum SessionWindowUpdate {
 	Unchanged,
 }
 
+/// The database and column the window is persisted to.
+#[derive(Clone)]
+pub struct DatabaseParams {
+	/// The database.
+	pub db: Arc<dyn Database>,
+	/// The column of the database to store the window in.
+	pub db_column: u32,
+}
+
+/// The window as stored in the database.
+#[derive(Encode, Decode)]
+struct StoredWindow {
+	earliest_session: SessionIndex,
+	session_info: Vec<SessionInfo>,
+}
+
 /// A rolling window of sessions and cached session info.
 pub struct RollingSessionWindow {
 	earliest_session: SessionIndex,
 	session_info: Vec<SessionInfo>,
 	window_size: SessionWindowSize,
+	db_params: Option<DatabaseParams>,
 }
 
 impl RollingSessionWindow {
 	/// Initialize a new session info cache with the given window size.
+	///
+	/// Sessions of the window found in the database are not requested from the runtime again.
 	pub async fn new<Sender>(
 		mut sender: Sender,
 		window_size: SessionWindowSize,
 		block_hash: Hash,
+		db_params: DatabaseParams,
 	) -> Result<Self, SessionsUnavailable>
 	where
 		Sender: overseer::SubsystemSender<RuntimeApiMessage>,
@@ -104,27 +137,58 @@ impl RollingSessionWindow {
 
 		let window_start = session_index.saturating_sub(window_size.get() - 1);
 
-		match load_all_sessions(&mut sender, block_hash, window_start, session_index).await {
+		let mut session_info = load_stored_sessions(&db_params, window_start, session_index);
+		let fetch_start = window_start + session_info.len() as SessionIndex;
+
+		match load_all_sessions(&mut sender, block_hash, fetch_start, session_index).await {
 			Err(kind) => Err(SessionsUnavailable {
 				kind,
 				info: Some(SessionsUnavailableInfo {
-					window_start,
+					window_start: fetch_start,
 					window_end: session_index,
 					block_hash,
 				}),
 			}),
-			Ok(s) => Ok(Self { earliest_session: window_start, session_info: s, window_size }),
+			Ok(s) => {
+				session_info.extend(s);
+				let window = Self {
+					earliest_session: window_start,
+					session_info,
+					window_size,
+					db_params: Some(db_params),
+				};
+				window.persist();
+				Ok(window)
+			},
 		}
 	}
 
 	/// Initialize a new session info cache with the given window size and
 	/// initial data.
+	///
+	/// The window is not persisted.
 	pub fn with_session_info(
 		window_size: SessionWindowSize,
 		earliest_session: SessionIndex,
 		session_info: Vec<SessionInfo>,
 	) -> Self {
-		RollingSessionWindow { earliest_session, session_info, window_size }
+		RollingSessionWindow { earliest_session, session_info, window_size, db_params: None }
+	}
+
+	/// Load the window persisted in the database, without consulting the runtime.
+	///
+	/// Useful for offline tooling. Returns `None` if no window is stored.
+	pub fn load_persisted(
+		window_size: SessionWindowSize,
+		db_params: DatabaseParams,
+	) -> Option<Self> {
+		let stored = load_stored_window(&db_params)?;
+		Some(RollingSessionWindow {
+			earliest_session: stored.earliest_session,
+			session_info: stored.session_info,
+			window_size,
+			db_params: Some(db_params),
+		})
 	}
 
 	/// Access the session info for the given session index, if stored within the window.
@@ -203,11 +267,74 @@ impl RollingSessionWindow {
 				//              old_window_start ........... latest
 				let new_earliest = std::cmp::max(window_start, old_window_start);
 				self.earliest_session = new_earliest;
+				self.persist();
 
 				Ok(update)
 			},
 		}
 	}
+
+	/// Write the window to the database, if any.
+	///
+	/// Failing to do so is not fatal, the sessions will be requested from the runtime again on
+	/// the next start.
+	fn persist(&self) {
+		let db_params = match self.db_params {
+			Some(ref db_params) => db_params,
+			None => return,
+		};
+
+		let stored = StoredWindow {
+			earliest_session: self.earliest_session,
+			session_info: self.session_info.clone(),
+		};
+		let mut tx = DBTransaction::new();
+		tx.put_vec(db_params.db_column, SESSION_WINDOW_KEY, stored.encode());
+
+		if let Err(err) = db_params.db.write(tx) {
+			gum::warn!(target: LOG_TARGET, ?err, "Failed to persist the session window");
+		}
+	}
+}
+
+fn load_stored_window(db_params: &DatabaseParams) -> Option<StoredWindow> {
+	match db_params.db.get(db_params.db_column, SESSION_WINDOW_KEY) {
+		Ok(Some(raw)) => match StoredWindow::decode(&mut &raw[..]) {
+			Ok(stored) => Some(stored),
+			Err(err) => {
+				gum::warn!(target: LOG_TARGET, ?err, "Failed to decode the stored session window");
+				None
+			},
+		},
+		Ok(None) => None,
+		Err(err) => {
+			gum::warn!(target: LOG_TARGET, ?err, "Failed to load the stored session window");
+			None
+		},
+	}
+}
+
+// Returns the consecutive sessions from `start` on, up to `end_inclusive`, found in the database.
+fn load_stored_sessions(
+	db_params: &DatabaseParams,
+	start: SessionIndex,
+	end_inclusive: SessionIndex,
+) -> Vec<SessionInfo> {
+	let stored = match load_stored_window(db_params) {
+		Some(stored) => stored,
+		None => return Vec::new(),
+	};
+
+	if stored.earliest_session > start {
+		return Vec::new()
+	}
+
+	stored
+		.session_info
+		.into_iter()
+		.skip((start - stored.earliest_session) as usize)
+		.take(end_inclusive.saturating_sub(start) as usize + 1)
+		.collect()
 }
 
 // Returns the session index expected at any child of the `parent` block.
@@ -287,6 +414,12 @@ mod tests {
 
 	pub const TEST_WINDOW_SIZE: SessionWindowSize = new_session_window_size!(6);
 
+	fn test_db_params() -> DatabaseParams {
+		let db = kvdb_memorydb::create(1);
+		let db = crate::database::kvdb_impl::DbAdapter::new(db, &[]);
+		DatabaseParams { db: Arc::new(db), db_column: 0 }
+	}
+
 	fn dummy_session_info(index: SessionIndex) -> SessionInfo {
 		SessionInfo {
 			validators: Vec::new(),
@@ -310,6 +443,22 @@ mod tests {
 		session: SessionIndex,
 		window: Option<RollingSessionWindow>,
 		expect_requests_from: SessionIndex,
+	) {
+		cache_session_info_test_with_db(
+			expected_start_session,
+			session,
+			window,
+			expect_requests_from,
+			test_db_params(),
+		)
+	}
+
+	fn cache_session_info_test_with_db(
+		expected_start_session: SessionIndex,
+		session: SessionIndex,
+		window: Option<RollingSessionWindow>,
+		expect_requests_from: SessionIndex,
+		db_params: DatabaseParams,
 	) {
 		let header = Header {
 			digest: Default::default(),
@@ -330,9 +479,10 @@ mod tests {
 		let test_fut = {
 			Box::pin(async move {
 				let window = match window {
-					None => RollingSessionWindow::new(sender.clone(), TEST_WINDOW_SIZE, hash)
-						.await
-						.unwrap(),
+					None =>
+						RollingSessionWindow::new(sender.clone(), TEST_WINDOW_SIZE, hash, db_params)
+							.await
+							.unwrap(),
 					Some(mut window) => {
 						window.cache_session_info_for_head(sender, hash).await.unwrap();
 						window
@@ -387,6 +537,7 @@ mod tests {
 			earliest_session: 1,
 			session_info: vec![dummy_session_info(1)],
 			window_size: TEST_WINDOW_SIZE,
+			db_params: None,
 		};
 
 		cache_session_info_test(1, 2, Some(window), 2);
@@ -412,6 +563,7 @@ mod tests {
 				dummy_session_info(52),
 			],
 			window_size: TEST_WINDOW_SIZE,
+			db_params: None,
 		};
 
 		cache_session_info_test(
@@ -429,6 +581,7 @@ mod tests {
 			earliest_session: start,
 			session_info: (start..=99).map(dummy_session_info).collect(),
 			window_size: TEST_WINDOW_SIZE,
+			db_params: None,
 		};
 
 		cache_session_info_test(
@@ -446,6 +599,7 @@ mod tests {
 			earliest_session: start,
 			session_info: (start..=97).map(dummy_session_info).collect(),
 			window_size: TEST_WINDOW_SIZE,
+			db_params: None,
 		};
 
 		cache_session_info_test(
@@ -463,6 +617,7 @@ mod tests {
 			earliest_session: start,
 			session_info: (0..=1).map(dummy_session_info).collect(),
 			window_size: TEST_WINDOW_SIZE,
+			db_params: None,
 		};
 
 		cache_session_info_test(
@@ -480,11 +635,51 @@ mod tests {
 			earliest_session: start,
 			session_info: (0..=1).map(dummy_session_info).collect(),
 			window_size: TEST_WINDOW_SIZE,
+			db_params: None,
 		};
 
 		cache_session_info_test(0, 3, Some(window), 2);
 	}
 
+	#[test]
+	fn cache_session_info_loads_stored_sessions() {
+		let db_params = test_db_params();
+		let start = 97 - (TEST_WINDOW_SIZE.get() - 1);
+		let window = RollingSessionWindow {
+			earliest_session: start,
+			session_info: (start..=97).map(dummy_session_info).collect(),
+			window_size: TEST_WINDOW_SIZE,
+			db_params: Some(db_params.clone()),
+		};
+		window.persist();
+
+		cache_session_info_test_with_db(
+			(100 as SessionIndex).saturating_sub(TEST_WINDOW_SIZE.get() - 1),
+			100,
+			None,
+			98, // only sessions missing in the db are requested.
+			db_params,
+		);
+	}
+
+	#[test]
+	fn cache_session_info_persists_window() {
+		let db_params = test_db_params();
+		cache_session_info_test_with_db(
+			(100 as SessionIndex).saturating_sub(TEST_WINDOW_SIZE.get() - 1),
+			100,
+			None,
+			(100 as SessionIndex).saturating_sub(TEST_WINDOW_SIZE.get() - 1),
+			db_params.clone(),
+		);
+
+		let start = 101 - (TEST_WINDOW_SIZE.get() - 1);
+		assert_eq!(
+			load_stored_sessions(&db_params, start, 101),
+			(start..=100).map(dummy_session_info).collect::<Vec<_>>(),
+		);
+	}
+
 	#[test]
 	fn any_session_unavailable_for_caching_means_no_change() {
 		let session: SessionIndex = 6;
@@ -506,7 +701,9 @@ mod tests {
 		let test_fut = {
 			let sender = ctx.sender().clone();
 			Box::pin(async move {
-				let res = RollingSessionWindow::new(sender, TEST_WINDOW_SIZE, hash).await;
+				let res =
+					RollingSessionWindow::new(sender, TEST_WINDOW_SIZE, hash, test_db_params())
+						.await;
 				assert!(res.is_err());
 			})
 		};
@@ -567,7 +764,9 @@ mod tests {
 			Box::pin(async move {
 				let sender = ctx.sender().clone();
 				let window =
-					RollingSessionWindow::new(sender, TEST_WINDOW_SIZE, hash).await.unwrap();
+					RollingSessionWindow::new(sender, TEST_WINDOW_SIZE, hash, test_db_params())
+						.await
+						.unwrap();
 
 				assert_eq!(window.earliest_session, session);
 				assert_eq!(window.session_info, vec![dummy_session_info(session)]);
@@ -104,6 +104,7 @@ pub fn new_full(
 		None,
 		None,
 		None,
+		Default::default(),
 	)
 }
 
@@ -68,6 +68,7 @@ fn main() -> Result<()> {
 					None,
 					None,
 					None,
+					Default::default(),
 				)
 				.map_err(|e| e.to_string())?;
 				let mut overseer_handle = full_node
@@ -21,8 +21,9 @@
 use std::sync::Arc;
 
 use jsonrpsee::RpcModule;
+use polkadot_overseer::Handle;
 use polkadot_primitives::v2::{AccountId, Balance, Block, BlockNumber, Hash, Nonce};
-use sc_client_api::AuxStore;
+use sc_client_api::{AuxStore, BlockchainEvents};
 use sc_consensus_babe::Epoch;
 use sc_finality_grandpa::FinalityProofProvider;
 pub use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
@@ -34,6 +35,8 @@ use sp_consensus_babe::BabeApi;
 use sp_keystore::SyncCryptoStorePtr;
 use txpool_api::TransactionPool;
 
+pub mod parachain;
+
 /// A type representing all RPC extensions.
 pub type RpcExtension = RpcModule<()>;
 
@@ -72,6 +75,14 @@ pub struct BeefyDeps {
 	pub subscription_executor: sc_rpc::SubscriptionTaskExecutor,
 }
 
+/// Dependencies for the `parachain_*` RPC namespace.
+pub struct ParachainDeps {
+	/// Handle to the overseer, used to query the subsystems.
+	pub overseer_handle: Handle,
+	/// Executor to drive the parachain RPC subscriptions.
+	pub subscription_executor: sc_rpc::SubscriptionTaskExecutor,
+}
+
 /// Full client dependencies
 pub struct FullDeps<C, P, SC, B> {
 	/// The client instance to use.
@@ -90,6 +101,8 @@ pub struct FullDeps<C, P, SC, B> {
 	pub grandpa: GrandpaDeps<B>,
 	/// BEEFY specific dependencies.
 	pub beefy: BeefyDeps,
+	/// Parachain specific dependencies, `None` if the node does not run the subsystems.
+	pub parachain: Option<ParachainDeps>,
 }
 
 /// Instantiate all RPC extensions.
@@ -101,6 +114,7 @@ where
 	C: ProvideRuntimeApi<Block>
 		+ HeaderBackend<Block>
 		+ AuxStore
+		+ BlockchainEvents<Block>
 		+ HeaderMetadata<Block, Error = BlockChainError>
 		+ Send
 		+ Sync
@@ -110,6 +124,7 @@ where
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BabeApi<Block>,
 	C::Api: BlockBuilder<Block>,
+	C::Api: polkadot_primitives::runtime_api::ParachainHost<Block>,
 	P: TransactionPool + Sync + Send + 'static,
 	SC: SelectChain<Block> + 'static,
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
@@ -119,14 +134,24 @@ where
 	use frame_rpc_system::{System, SystemApiServer};
 	use pallet_mmr_rpc::{Mmr, MmrApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
+	use parachain::{Parachain, ParachainApiServer};
 	use sc_consensus_babe_rpc::{Babe, BabeApiServer};
 	use sc_finality_grandpa_rpc::{Grandpa, GrandpaApiServer};
 	use sc_sync_state_rpc::{SyncState, SyncStateApiServer};
 	use substrate_state_trie_migration_rpc::{StateMigration, StateMigrationApiServer};
 
 	let mut io = RpcModule::new(());
-	let FullDeps { client, pool, select_chain, chain_spec, deny_unsafe, babe, grandpa, beefy } =
-		deps;
+	let FullDeps {
+		client,
+		pool,
+		select_chain,
+		chain_spec,
+		deny_unsafe,
+		babe,
+		grandpa,
+		beefy,
+		parachain,
+	} = deps;
 	let BabeDeps { keystore, babe_config, shared_epoch_changes } = babe;
 	let GrandpaDeps {
 		shared_voter_state,
@@ -161,6 +186,12 @@ where
 		)
 		.into_rpc(),
 	)?;
+	if let Some(ParachainDeps { overseer_handle, subscription_executor }) = parachain {
+		io.merge(
+			Parachain::new(client.clone(), overseer_handle, subscription_executor, deny_unsafe)
+				.into_rpc(),
+		)?;
+	}
 	io.merge(
 		SyncState::new(chain_spec, client, shared_authority_set, shared_epoch_changes)?.into_rpc(),
 	)?;
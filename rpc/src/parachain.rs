@@ -0,0 +1,942 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The `parachain_*` RPC namespace, giving insight into parachain consensus.
+//!
+//! Disputes, approvals, chain selection and the availability store are queried from the respective
+//! subsystems via the overseer [`Handle`], everything else is read from the runtime.
+//!
+//! All methods and subscriptions expose the node's view of consensus and are unsafe, chain
+//! selection overrides also change the state of the node.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use futures::{
+	channel::{mpsc, oneshot},
+	future::Either,
+	FutureExt, Stream, StreamExt,
+};
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::{
+		error::{CallError, ErrorObject},
+		SubscriptionResult,
+	},
+	SubscriptionSink,
+};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use polkadot_node_subsystem_types::messages::{
+	ApprovalVotingMessage, AvailabilityStoreMessage, ChainSelectionBlock, ChainSelectionMessage,
+	ChainSelectionOverride, CollatorListing, CollatorProtocolMessage, DisputeCoordinatorMessage,
+};
+use polkadot_overseer::{AllMessages, Handle};
+use polkadot_primitives::{
+	runtime_api::ParachainHost,
+	v2::{
+		Block, BlockNumber, CandidateEvent, CandidateHash, CollatorId, CoreState, Hash,
+		Id as ParaId, SessionIndex, ValidatorId,
+	},
+};
+use sc_client_api::BlockchainEvents;
+use sc_rpc::{DenyUnsafe, SubscriptionTaskExecutor};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::generic::BlockId;
+
+/// How long to wait for a subsystem to answer a query.
+const SUBSYSTEM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Origin of the messages sent to the overseer.
+const ORIGIN: &str = "parachain-rpc";
+
+/// Error code of all errors of this namespace.
+const PARACHAIN_RPC_ERROR: i32 = 9000;
+
+/// Errors of the parachain RPC.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// A subsystem did not answer in time.
+	#[error("Subsystem did not answer within {0:?}")]
+	Timeout(Duration),
+	/// A subsystem dropped the request.
+	#[error("Subsystem dropped the request")]
+	Canceled,
+	/// Calling the runtime failed.
+	#[error("Runtime API error: {0}")]
+	RuntimeApi(String),
+	/// Accessing the blockchain failed.
+	#[error("Blockchain error: {0}")]
+	Blockchain(#[from] sp_blockchain::Error),
+}
+
+impl From<Error> for JsonRpseeError {
+	fn from(error: Error) -> Self {
+		CallError::Custom(ErrorObject::owned(PARACHAIN_RPC_ERROR, error.to_string(), None::<()>))
+			.into()
+	}
+}
+
+/// A dispute, identified by the session it was raised in and the disputed candidate.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dispute {
+	/// The session the candidate was included in.
+	pub session: SessionIndex,
+	/// The disputed candidate.
+	pub candidate_hash: CandidateHash,
+}
+
+/// A dispute with the votes known so far.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisputeTally {
+	/// The session the candidate was included in.
+	pub session: SessionIndex,
+	/// The disputed candidate.
+	pub candidate_hash: CandidateHash,
+	/// The parachain of the disputed candidate.
+	pub para_id: u32,
+	/// Number of votes for validity.
+	pub valid_votes: u32,
+	/// Number of votes against validity.
+	pub invalid_votes: u32,
+}
+
+/// Dispute spam slot use of a validator in a session.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpamSlots {
+	/// The session of the unconfirmed disputes.
+	pub session: SessionIndex,
+	/// The index of the validator in the session.
+	pub validator_index: u32,
+	/// The public key of the validator.
+	pub validator: ValidatorId,
+	/// Number of unconfirmed disputes the validator raised.
+	pub used: u32,
+	/// Number of unconfirmed disputes the validator may raise.
+	pub limit: u32,
+	/// Decayed number of the validator's disputes which never got confirmed, in thousandths.
+	pub unconfirmed_score: u32,
+	/// Decayed number of the validator's disputes which concluded invalid, in thousandths.
+	pub confirmed_score: u32,
+}
+
+/// Approval status of a relay chain block.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockApprovalStatus {
+	/// The block number.
+	pub number: BlockNumber,
+	/// Whether the block is finalized.
+	pub finalized: bool,
+	/// Whether all candidates of the block and its unfinalized ancestors are approved.
+	pub approved: bool,
+	/// The highest ancestor, which is approved together with all its unfinalized ancestors.
+	pub highest_approved_ancestor: Option<Hash>,
+}
+
+/// A candidate occupying an availability core.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingAvailability {
+	/// The index of the occupied core.
+	pub core: u32,
+	/// The parachain of the candidate.
+	pub para_id: u32,
+	/// The candidate pending availability.
+	pub candidate_hash: CandidateHash,
+	/// Block number at which the candidate occupied the core.
+	pub occupied_since: BlockNumber,
+	/// Block number at which the candidate times out.
+	pub time_out_at: BlockNumber,
+	/// Number of validators which have signed availability of the candidate.
+	pub available_votes: u32,
+	/// Number of validators in the current session.
+	pub n_validators: u32,
+}
+
+/// What the local availability store holds for a candidate.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailabilityStorePresence {
+	/// Whether the full available data is stored.
+	pub available_data: bool,
+	/// Indices of the stored chunks.
+	pub chunks: Vec<u32>,
+}
+
+/// A candidate included in a relay chain block.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateInclusion {
+	/// The block the candidate got included in.
+	pub block_hash: Hash,
+	/// The parachain of the candidate.
+	pub para_id: u32,
+	/// The included candidate.
+	pub candidate_hash: CandidateHash,
+	/// The core the candidate occupied.
+	pub core: u32,
+}
+
+/// The reputation a validator keeps of a collator for a parachain.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollatorReputation {
+	/// The parachain the collator collates for.
+	pub para_id: u32,
+	/// The collator.
+	pub collator: CollatorId,
+	/// The score, collations of collators with higher scores are fetched first.
+	pub score: i32,
+	/// How the operator lists the collator, one of `unlisted`, `allowed` or `denied`.
+	pub listing: &'static str,
+}
+
+/// An unfinalized block as seen by chain selection.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSelectionBlockInfo {
+	/// The hash of the block.
+	pub hash: Hash,
+	/// The block number.
+	pub number: BlockNumber,
+	/// The hash of the parent block.
+	pub parent_hash: Hash,
+	/// The weight of the block.
+	pub weight: u32,
+	/// The approval state of the block, one of `approved`, `unapproved` or `stagnant`.
+	pub approval: &'static str,
+	/// Unix timestamp in seconds at which the block is checked for stagnation, if pending.
+	pub stagnant_at: Option<u64>,
+	/// Whether the block was reverted, either by a descendant or by the operator.
+	pub reverted: bool,
+	/// The earliest ancestor which is reverted or stagnant, making this block unviable.
+	pub earliest_unviable_ancestor: Option<Hash>,
+	/// Whether the block and all its ancestors are viable.
+	pub viable: bool,
+	/// Whether new blocks may be built upon the block.
+	pub viable_leaf: bool,
+}
+
+impl From<ChainSelectionBlock> for ChainSelectionBlockInfo {
+	fn from(block: ChainSelectionBlock) -> Self {
+		ChainSelectionBlockInfo {
+			hash: block.hash,
+			number: block.number,
+			parent_hash: block.parent_hash,
+			weight: block.weight,
+			approval: block.approval.as_str(),
+			stagnant_at: block.stagnant_at,
+			reverted: block.explicitly_reverted,
+			earliest_unviable_ancestor: block.earliest_unviable_ancestor,
+			viable: block.viable,
+			viable_leaf: block.viable_leaf,
+		}
+	}
+}
+
+/// An operator override of the chain selection state of a block.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSelectionOverrideInfo {
+	/// Unix timestamp in seconds at which the override was applied.
+	pub timestamp: u64,
+	/// The hash of the overridden block.
+	pub block_hash: Hash,
+	/// The number of the overridden block.
+	pub block_number: BlockNumber,
+	/// The applied override, either `revert` or `unstagnate`.
+	pub kind: &'static str,
+}
+
+/// Parachain consensus introspection.
+#[rpc(client, server)]
+pub trait ParachainApi {
+	/// Disputes which are not yet concluded or concluded recently, with their votes.
+	#[method(name = "parachain_activeDisputes")]
+	async fn active_disputes(&self) -> RpcResult<Vec<DisputeTally>>;
+
+	/// Disputes of the sessions within the dispute window, with their votes.
+	#[method(name = "parachain_recentDisputes")]
+	async fn recent_disputes(&self) -> RpcResult<Vec<DisputeTally>>;
+
+	/// Spam slot use of all validators which raised unconfirmed disputes.
+	#[method(name = "parachain_disputeSpamSlots")]
+	async fn dispute_spam_slots(&self) -> RpcResult<Vec<SpamSlots>>;
+
+	/// Approval status of the given relay chain block.
+	///
+	/// Returns `None` if the block is unknown.
+	#[method(name = "parachain_approvalStatus")]
+	async fn approval_status(&self, block_hash: Hash) -> RpcResult<Option<BlockApprovalStatus>>;
+
+	/// Candidates pending availability per core, at the given or the best block.
+	#[method(name = "parachain_pendingAvailability")]
+	fn pending_availability(&self, at: Option<Hash>) -> RpcResult<Vec<PendingAvailability>>;
+
+	/// What the local availability store holds for a candidate.
+	#[method(name = "parachain_availabilityStorePresence")]
+	async fn availability_store_presence(
+		&self,
+		candidate_hash: CandidateHash,
+	) -> RpcResult<AvailabilityStorePresence>;
+
+	/// Reputation of the collators of the given or all parachains, best first.
+	///
+	/// Empty on collators, only validators keep collator reputations.
+	#[method(name = "parachain_collatorReputation")]
+	async fn collator_reputation(&self, para_id: Option<u32>)
+		-> RpcResult<Vec<CollatorReputation>>;
+
+	/// The unfinalized blocks known to chain selection in ascending order by number.
+	#[method(name = "parachain_chainSelectionTree")]
+	async fn chain_selection_tree(&self) -> RpcResult<Vec<ChainSelectionBlockInfo>>;
+
+	/// Mark an unfinalized block as reverted, so neither it nor its descendants are built upon.
+	///
+	/// Returns the updated block, or `None` if the block is unknown. This is an unsafe method.
+	#[method(name = "parachain_revertBlock")]
+	async fn revert_block(&self, block_hash: Hash) -> RpcResult<Option<ChainSelectionBlockInfo>>;
+
+	/// Undo the stagnation of a block, giving it another stagnation timeout to get approved.
+	///
+	/// Returns the updated block, or `None` if the block is unknown. This is an unsafe method.
+	#[method(name = "parachain_unstagnateBlock")]
+	async fn unstagnate_block(
+		&self,
+		block_hash: Hash,
+	) -> RpcResult<Option<ChainSelectionBlockInfo>>;
+
+	/// The chain selection overrides applied by operators, oldest first.
+	#[method(name = "parachain_chainSelectionOverrides")]
+	async fn chain_selection_overrides(&self) -> RpcResult<Vec<ChainSelectionOverrideInfo>>;
+
+	/// Subscribe to disputes as they get raised.
+	#[subscription(
+		name = "parachain_subscribeNewDisputes" => "parachain_newDispute",
+		unsubscribe = "parachain_unsubscribeNewDisputes",
+		item = Dispute
+	)]
+	fn subscribe_new_disputes(&self);
+
+	/// Subscribe to candidates getting included in imported blocks.
+	#[subscription(
+		name = "parachain_subscribeCandidateInclusion" => "parachain_candidateIncluded",
+		unsubscribe = "parachain_unsubscribeCandidateInclusion",
+		item = CandidateInclusion
+	)]
+	fn subscribe_candidate_inclusion(&self);
+}
+
+/// The subscribers to new disputes, which are served by a single poller.
+///
+/// `None` while the poller is not running.
+type DisputeSubscribers = Arc<Mutex<Option<Vec<mpsc::UnboundedSender<Dispute>>>>>;
+
+/// Implements the [`ParachainApiServer`] RPC trait.
+pub struct Parachain<C> {
+	client: Arc<C>,
+	overseer_handle: Handle,
+	executor: SubscriptionTaskExecutor,
+	deny_unsafe: DenyUnsafe,
+	dispute_subscribers: DisputeSubscribers,
+}
+
+impl<C> Parachain<C> {
+	/// Create a new instance, querying subsystems via the given handle.
+	pub fn new(
+		client: Arc<C>,
+		overseer_handle: Handle,
+		executor: SubscriptionTaskExecutor,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Self {
+			client,
+			overseer_handle,
+			executor,
+			deny_unsafe,
+			dispute_subscribers: Default::default(),
+		}
+	}
+
+	/// Apply a chain selection override to the given block.
+	async fn override_block(
+		&self,
+		block_hash: Hash,
+		kind: ChainSelectionOverride,
+	) -> RpcResult<Option<ChainSelectionBlockInfo>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let block = request(self.overseer_handle.clone(), |tx| {
+			ChainSelectionMessage::Override(block_hash, kind, tx).into()
+		})
+		.await?;
+		Ok(block.map(Into::into))
+	}
+}
+
+/// Send a message to a subsystem and wait for its answer.
+async fn request<T>(
+	mut handle: Handle,
+	make_msg: impl FnOnce(oneshot::Sender<T>) -> AllMessages,
+) -> Result<T, Error> {
+	let (tx, rx) = oneshot::channel();
+	let timeout = futures_timer::Delay::new(SUBSYSTEM_TIMEOUT);
+	let request = async move {
+		handle.send_msg(make_msg(tx), ORIGIN).await;
+		rx.await
+	};
+	futures::pin_mut!(request);
+
+	match futures::future::select(request, timeout).await {
+		Either::Left((Ok(answer), _)) => Ok(answer),
+		Either::Left((Err(oneshot::Canceled), _)) => Err(Error::Canceled),
+		Either::Right(_) => Err(Error::Timeout(SUBSYSTEM_TIMEOUT)),
+	}
+}
+
+/// Fetch the votes of the given disputes.
+async fn tallies(
+	handle: Handle,
+	disputes: Vec<(SessionIndex, CandidateHash)>,
+) -> Result<Vec<DisputeTally>, Error> {
+	let votes =
+		request(handle, |tx| DisputeCoordinatorMessage::QueryCandidateVotes(disputes, tx).into())
+			.await?;
+
+	Ok(votes
+		.into_iter()
+		.map(|(session, candidate_hash, votes)| DisputeTally {
+			session,
+			candidate_hash,
+			para_id: votes.candidate_receipt.descriptor.para_id.into(),
+			valid_votes: votes.valid.len() as u32,
+			invalid_votes: votes.invalid.len() as u32,
+		})
+		.collect())
+}
+
+/// The disputes which are currently active.
+async fn active_disputes(handle: Handle) -> Result<HashSet<Dispute>, Error> {
+	let disputes =
+		request(handle, |tx| DisputeCoordinatorMessage::ActiveDisputes(tx).into()).await?;
+	Ok(disputes
+		.into_iter()
+		.map(|(session, candidate_hash)| Dispute { session, candidate_hash })
+		.collect())
+}
+
+/// Announce disputes to all subscribers as they get raised, checking for them on every import.
+///
+/// Checking on block import is frequent enough for monitoring, disputes raised via the network
+/// will be picked up with the next block. Disputes which are already active when the poller starts
+/// are not announced. Stops once all subscribers are gone.
+async fn poll_new_disputes(
+	handle: Handle,
+	mut imports: impl Stream<Item = ()> + Unpin,
+	subscribers: DisputeSubscribers,
+) {
+	let mut known = active_disputes(handle.clone()).await.ok();
+
+	while imports.next().await.is_some() {
+		let active = match active_disputes(handle.clone()).await {
+			Ok(active) => active,
+			Err(_) => continue,
+		};
+		// If the known disputes could not be queried before, all active ones count as known.
+		let new = known
+			.map(|known| active.difference(&known).cloned().collect::<Vec<_>>())
+			.unwrap_or_default();
+		known = Some(active);
+
+		let mut subscribers = subscribers.lock();
+		let remaining = subscribers.get_or_insert_with(Vec::new);
+		remaining.retain(|tx| {
+			!tx.is_closed() && new.iter().all(|dispute| tx.unbounded_send(dispute.clone()).is_ok())
+		});
+		if remaining.is_empty() {
+			*subscribers = None;
+			return
+		}
+	}
+
+	*subscribers.lock() = None;
+}
+
+fn runtime_error(error: sp_api::ApiError) -> Error {
+	Error::RuntimeApi(error.to_string())
+}
+
+#[async_trait]
+impl<C> ParachainApiServer for Parachain<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync + 'static,
+	C::Api: ParachainHost<Block>,
+{
+	async fn active_disputes(&self) -> RpcResult<Vec<DisputeTally>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let disputes = request(self.overseer_handle.clone(), |tx| {
+			DisputeCoordinatorMessage::ActiveDisputes(tx).into()
+		})
+		.await?;
+		Ok(tallies(self.overseer_handle.clone(), disputes).await?)
+	}
+
+	async fn recent_disputes(&self) -> RpcResult<Vec<DisputeTally>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let disputes = request(self.overseer_handle.clone(), |tx| {
+			DisputeCoordinatorMessage::RecentDisputes(tx).into()
+		})
+		.await?;
+		Ok(tallies(self.overseer_handle.clone(), disputes).await?)
+	}
+
+	async fn dispute_spam_slots(&self) -> RpcResult<Vec<SpamSlots>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let usage = request(self.overseer_handle.clone(), |tx| {
+			DisputeCoordinatorMessage::SpamSlots(tx).into()
+		})
+		.await?;
+		Ok(usage
+			.into_iter()
+			.map(|u| SpamSlots {
+				session: u.session,
+				validator_index: u.validator_index.0,
+				validator: u.validator,
+				used: u.used,
+				limit: u.limit,
+				unconfirmed_score: u.unconfirmed_score,
+				confirmed_score: u.confirmed_score,
+			})
+			.collect())
+	}
+
+	async fn approval_status(&self, block_hash: Hash) -> RpcResult<Option<BlockApprovalStatus>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let number = match self.client.number(block_hash).map_err(Error::from)? {
+			Some(number) => number,
+			None => return Ok(None),
+		};
+		let finalized_number = self.client.info().finalized_number;
+		if number <= finalized_number {
+			if self.client.hash(number).map_err(Error::from)? != Some(block_hash) {
+				// Abandoned fork, won't ever be approved.
+				return Ok(Some(BlockApprovalStatus {
+					number,
+					finalized: false,
+					approved: false,
+					highest_approved_ancestor: None,
+				}));
+			}
+
+			// Finalized blocks are approved, approval voting might have forgotten about them.
+			return Ok(Some(BlockApprovalStatus {
+				number,
+				finalized: true,
+				approved: true,
+				highest_approved_ancestor: Some(block_hash),
+			}));
+		}
+
+		let ancestor = request(self.overseer_handle.clone(), |tx| {
+			ApprovalVotingMessage::ApprovedAncestor(block_hash, finalized_number, tx).into()
+		})
+		.await?;
+
+		Ok(Some(BlockApprovalStatus {
+			number,
+			finalized: false,
+			approved: ancestor.as_ref().map_or(false, |ancestor| ancestor.hash == block_hash),
+			highest_approved_ancestor: ancestor.map(|ancestor| ancestor.hash),
+		}))
+	}
+
+	fn pending_availability(&self, at: Option<Hash>) -> RpcResult<Vec<PendingAvailability>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let cores = self
+			.client
+			.runtime_api()
+			.availability_cores(&BlockId::Hash(at))
+			.map_err(runtime_error)?;
+
+		Ok(cores
+			.into_iter()
+			.enumerate()
+			.filter_map(|(core, state)| match state {
+				CoreState::Occupied(occupied) => Some(PendingAvailability {
+					core: core as u32,
+					para_id: occupied.para_id().into(),
+					candidate_hash: occupied.candidate_hash,
+					occupied_since: occupied.occupied_since,
+					time_out_at: occupied.time_out_at,
+					available_votes: occupied.availability.count_ones() as u32,
+					n_validators: occupied.availability.len() as u32,
+				}),
+				_ => None,
+			})
+			.collect())
+	}
+
+	async fn availability_store_presence(
+		&self,
+		candidate_hash: CandidateHash,
+	) -> RpcResult<AvailabilityStorePresence> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let available_data = request(self.overseer_handle.clone(), |tx| {
+			AvailabilityStoreMessage::QueryDataAvailability(candidate_hash, tx).into()
+		})
+		.await?;
+		let chunks = request(self.overseer_handle.clone(), |tx| {
+			AvailabilityStoreMessage::QueryAllChunks(candidate_hash, tx).into()
+		})
+		.await?;
+
+		Ok(AvailabilityStorePresence {
+			available_data,
+			chunks: chunks.into_iter().map(|chunk| chunk.index.0).collect(),
+		})
+	}
+
+	async fn collator_reputation(
+		&self,
+		para_id: Option<u32>,
+	) -> RpcResult<Vec<CollatorReputation>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let reputations = request(self.overseer_handle.clone(), |tx| {
+			CollatorProtocolMessage::CollatorReputations(para_id.map(ParaId::from), tx).into()
+		})
+		.await?;
+
+		Ok(reputations
+			.into_iter()
+			.map(|reputation| CollatorReputation {
+				para_id: reputation.para_id.into(),
+				collator: reputation.collator_id,
+				score: reputation.score,
+				listing: match reputation.listing {
+					CollatorListing::Unlisted => "unlisted",
+					CollatorListing::Allowed => "allowed",
+					CollatorListing::Denied => "denied",
+				},
+			})
+			.collect())
+	}
+
+	async fn chain_selection_tree(&self) -> RpcResult<Vec<ChainSelectionBlockInfo>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let blocks =
+			request(self.overseer_handle.clone(), |tx| ChainSelectionMessage::BlockTree(tx).into())
+				.await?;
+		Ok(blocks.into_iter().map(Into::into).collect())
+	}
+
+	async fn revert_block(&self, block_hash: Hash) -> RpcResult<Option<ChainSelectionBlockInfo>> {
+		self.override_block(block_hash, ChainSelectionOverride::Revert).await
+	}
+
+	async fn unstagnate_block(
+		&self,
+		block_hash: Hash,
+	) -> RpcResult<Option<ChainSelectionBlockInfo>> {
+		self.override_block(block_hash, ChainSelectionOverride::Unstagnate).await
+	}
+
+	async fn chain_selection_overrides(&self) -> RpcResult<Vec<ChainSelectionOverrideInfo>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let records = request(self.overseer_handle.clone(), |tx| {
+			ChainSelectionMessage::OverrideLog(tx).into()
+		})
+		.await?;
+		Ok(records
+			.into_iter()
+			.map(|record| ChainSelectionOverrideInfo {
+				timestamp: record.timestamp,
+				block_hash: record.block_hash,
+				block_number: record.block_number,
+				kind: record.kind.as_str(),
+			})
+			.collect())
+	}
+
+	fn subscribe_new_disputes(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			let _ = sink.reject(JsonRpseeError::from(err));
+			return Ok(())
+		}
+
+		let (tx, rx) = mpsc::unbounded();
+		{
+			let mut subscribers = self.dispute_subscribers.lock();
+			match subscribers.as_mut() {
+				Some(subscribers) => subscribers.push(tx),
+				None => {
+					*subscribers = Some(vec![tx]);
+					let imports = self.client.import_notification_stream().map(|_| ());
+					let poller = poll_new_disputes(
+						self.overseer_handle.clone(),
+						imports,
+						self.dispute_subscribers.clone(),
+					);
+					self.executor.spawn("polkadot-rpc-dispute-poller", Some("rpc"), poller.boxed());
+				},
+			}
+		}
+
+		let fut = async move {
+			sink.pipe_from_stream(rx).await;
+		};
+		self.executor.spawn("polkadot-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+
+	fn subscribe_candidate_inclusion(&self, mut sink: SubscriptionSink) -> SubscriptionResult {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			let _ = sink.reject(JsonRpseeError::from(err));
+			return Ok(())
+		}
+
+		let client = self.client.clone();
+		let stream = self
+			.client
+			.import_notification_stream()
+			.map(move |notification| {
+				let events = client
+					.runtime_api()
+					.candidate_events(&BlockId::Hash(notification.hash))
+					.unwrap_or_default();
+				let included = events
+					.into_iter()
+					.filter_map(|event| match event {
+						CandidateEvent::CandidateIncluded(receipt, _, core, _) =>
+							Some(CandidateInclusion {
+								block_hash: notification.hash,
+								para_id: receipt.descriptor.para_id.into(),
+								candidate_hash: receipt.hash(),
+								core: core.0,
+							}),
+						_ => None,
+					})
+					.collect::<Vec<_>>();
+				futures::stream::iter(included)
+			})
+			.flatten();
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+		self.executor.spawn("polkadot-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::collections::BTreeMap;
+
+	use jsonrpsee::{types::EmptyParams, RpcModule};
+	use polkadot_node_primitives::CandidateVotes;
+	use polkadot_node_subsystem_test_helpers::ForwardSubsystem;
+	use polkadot_overseer::{dummy::dummy_overseer_builder, HeadSupportsParachains};
+	use polkadot_primitives_test_helpers::dummy_candidate_receipt;
+	use polkadot_test_client::{
+		Client, DefaultTestClientBuilderExt, TestClientBuilder, TestClientBuilderExt,
+	};
+	use sp_core::{testing::TaskExecutor, traits::SpawnNamed};
+
+	struct AlwaysSupportsParachains;
+
+	#[async_trait]
+	impl HeadSupportsParachains for AlwaysSupportsParachains {
+		async fn head_supports_parachains(&self, _head: &Hash) -> bool {
+			true
+		}
+	}
+
+	/// Start an overseer, forwarding the messages to the dispute coordinator to the receiver.
+	fn dispute_coordinator(
+		spawner: &TaskExecutor,
+	) -> (Handle, mpsc::Receiver<DisputeCoordinatorMessage>) {
+		let (tx, rx) = mpsc::channel(16);
+		let (overseer, handle) =
+			dummy_overseer_builder(spawner.clone(), AlwaysSupportsParachains, None)
+				.unwrap()
+				.replace_dispute_coordinator(|_| ForwardSubsystem(tx))
+				.leaves(vec![])
+				.build()
+				.unwrap();
+		spawner.spawn("overseer", None, overseer.run().map(|_| ()).boxed());
+
+		(Handle::new(handle), rx)
+	}
+
+	fn parachain_rpc(
+		spawner: &TaskExecutor,
+		handle: Handle,
+		deny_unsafe: DenyUnsafe,
+	) -> RpcModule<Parachain<Client>> {
+		let client = Arc::new(TestClientBuilder::new().build());
+		Parachain::new(client, handle, Arc::new(spawner.clone()), deny_unsafe).into_rpc()
+	}
+
+	async fn answer_active_disputes(
+		rx: &mut mpsc::Receiver<DisputeCoordinatorMessage>,
+		active: Vec<Dispute>,
+	) {
+		match rx.next().await.expect("Overseer is alive") {
+			DisputeCoordinatorMessage::ActiveDisputes(tx) => tx
+				.send(active.into_iter().map(|d| (d.session, d.candidate_hash)).collect())
+				.unwrap(),
+			msg => panic!("Unexpected message: {:?}", msg),
+		}
+	}
+
+	fn dispute(n: u8) -> Dispute {
+		Dispute { session: 1, candidate_hash: CandidateHash(Hash::repeat_byte(n)) }
+	}
+
+	#[test]
+	fn unsafe_calls_are_denied() {
+		let spawner = TaskExecutor::new();
+		let (handle, _rx) = dispute_coordinator(&spawner);
+		let api = parachain_rpc(&spawner, handle, DenyUnsafe::Yes);
+
+		futures::executor::block_on(async move {
+			let err = api
+				.call::<_, serde_json::Value>("parachain_activeDisputes", EmptyParams::new())
+				.await
+				.unwrap_err();
+			assert!(err.to_string().contains("unsafe"), "{}", err);
+
+			assert!(api
+				.call::<_, serde_json::Value>("parachain_pendingAvailability", EmptyParams::new())
+				.await
+				.is_err());
+			assert!(api
+				.call::<_, serde_json::Value>("parachain_chainSelectionTree", EmptyParams::new())
+				.await
+				.is_err());
+			assert!(api
+				.call::<_, serde_json::Value>("parachain_collatorReputation", EmptyParams::new())
+				.await
+				.is_err());
+			assert!(api
+				.subscribe("parachain_subscribeNewDisputes", EmptyParams::new())
+				.await
+				.is_err());
+			assert!(api
+				.subscribe("parachain_subscribeCandidateInclusion", EmptyParams::new())
+				.await
+				.is_err());
+		});
+	}
+
+	#[test]
+	fn active_disputes_are_tallied() {
+		let spawner = TaskExecutor::new();
+		let (handle, mut rx) = dispute_coordinator(&spawner);
+		let api = parachain_rpc(&spawner, handle, DenyUnsafe::No);
+
+		let candidate_receipt = dummy_candidate_receipt(Hash::repeat_byte(1));
+		let candidate_hash = candidate_receipt.hash();
+
+		futures::executor::block_on(async move {
+			let coordinator = async {
+				answer_active_disputes(&mut rx, vec![Dispute { session: 2, candidate_hash }]).await;
+				match rx.next().await.expect("Overseer is alive") {
+					DisputeCoordinatorMessage::QueryCandidateVotes(query, tx) => {
+						assert_eq!(query, vec![(2, candidate_hash)]);
+						let votes = CandidateVotes {
+							candidate_receipt,
+							valid: BTreeMap::new(),
+							invalid: BTreeMap::new(),
+						};
+						tx.send(vec![(2, candidate_hash, votes)]).unwrap();
+					},
+					msg => panic!("Unexpected message: {:?}", msg),
+				}
+			};
+
+			let (tallies, ()) = futures::join!(
+				api.call::<_, serde_json::Value>("parachain_activeDisputes", EmptyParams::new()),
+				coordinator,
+			);
+
+			let tallies = tallies.unwrap();
+			assert_eq!(tallies.as_array().map(Vec::len), Some(1));
+			assert_eq!(tallies[0]["session"], 2);
+			assert_eq!(tallies[0]["candidateHash"], serde_json::to_value(candidate_hash).unwrap());
+			assert_eq!(tallies[0]["validVotes"], 0);
+			assert_eq!(tallies[0]["invalidVotes"], 0);
+		});
+	}
+
+	#[test]
+	fn new_disputes_are_announced_to_all_subscribers() {
+		let spawner = TaskExecutor::new();
+		let (handle, mut rx) = dispute_coordinator(&spawner);
+		let (imports_tx, imports) = mpsc::unbounded();
+		let (tx_a, mut sub_a) = mpsc::unbounded();
+		let (tx_b, mut sub_b) = mpsc::unbounded();
+		let subscribers: DisputeSubscribers = Arc::new(Mutex::new(Some(vec![tx_a, tx_b])));
+
+		let test = async {
+			// Disputes raised before the poller started are not announced.
+			answer_active_disputes(&mut rx, vec![dispute(1)]).await;
+
+			imports_tx.unbounded_send(()).unwrap();
+			answer_active_disputes(&mut rx, vec![dispute(1), dispute(2)]).await;
+			assert_eq!(sub_a.next().await, Some(dispute(2)));
+			assert_eq!(sub_b.next().await, Some(dispute(2)));
+
+			// A single query serves the remaining subscribers.
+			drop(sub_b);
+			imports_tx.unbounded_send(()).unwrap();
+			answer_active_disputes(&mut rx, vec![dispute(2), dispute(3)]).await;
+			assert_eq!(sub_a.next().await, Some(dispute(3)));
+
+			// The poller stops once all subscribers are gone.
+			drop(sub_a);
+			imports_tx.unbounded_send(()).unwrap();
+			answer_active_disputes(&mut rx, vec![dispute(3)]).await;
+		};
+
+		futures::executor::block_on(futures::future::join(
+			poll_new_disputes(handle, imports, subscribers.clone()),
+			test,
+		));
+
+		assert!(subscribers.lock().is_none());
+		assert!(rx.try_next().is_err());
+	}
+}
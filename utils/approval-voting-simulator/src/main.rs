@@ -0,0 +1,146 @@
+// Copyright 2022 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Simulate approval checking for a set of assignment parameters.
+//!
+//! Reports the distribution of the time it takes to approve candidates and of the load on
+//! validators, to judge proposals changing the approval related fields of the host
+//! configuration. Run it with the current and the proposed values and compare the reports.
+
+use clap::Parser;
+use polkadot_node_core_approval_voting::simulation::{
+	simulate, Distribution, Report, SimulationParams,
+};
+
+#[derive(Debug, Parser)]
+struct Opt {
+	/// Number of validators.
+	#[clap(long, default_value = "300")]
+	validators: u32,
+
+	/// Number of availability cores, all of them are occupied in every block.
+	#[clap(long, default_value = "60")]
+	cores: u32,
+
+	/// `n_delay_tranches` of the host configuration.
+	#[clap(long, default_value = "89")]
+	n_delay_tranches: u32,
+
+	/// `zeroth_delay_tranche_width` of the host configuration.
+	#[clap(long, default_value = "0")]
+	zeroth_delay_tranche_width: u32,
+
+	/// `needed_approvals` of the host configuration.
+	#[clap(long, default_value = "30")]
+	needed_approvals: u32,
+
+	/// `relay_vrf_modulo_samples` of the host configuration.
+	#[clap(long, default_value = "6")]
+	relay_vrf_modulo_samples: u32,
+
+	/// `no_show_slots` of the host configuration.
+	#[clap(long, default_value = "2")]
+	no_show_slots: u32,
+
+	/// Duration of a relay chain slot in milliseconds.
+	#[clap(long, default_value = "6000")]
+	slot_duration_millis: u64,
+
+	/// Number of relay chain blocks to simulate.
+	#[clap(long, default_value = "10")]
+	blocks: u32,
+
+	/// Probability of a validator not approving after triggering its assignment.
+	#[clap(long, default_value = "0.01")]
+	no_show_probability: f64,
+
+	/// Minimum time in milliseconds for an assignment or an approval to arrive.
+	#[clap(long, default_value = "100")]
+	min_network_delay_millis: u64,
+
+	/// Maximum time in milliseconds for an assignment or an approval to arrive.
+	#[clap(long, default_value = "1000")]
+	max_network_delay_millis: u64,
+
+	/// Time in milliseconds it takes to recover and check a candidate.
+	#[clap(long, default_value = "2000")]
+	approval_check_millis: u64,
+
+	/// Report candidates not approved within this many milliseconds as unapproved.
+	#[clap(long, default_value = "300000")]
+	timeout_millis: u64,
+
+	/// Seed of the validator keys and of all random choices.
+	#[clap(long, default_value = "0")]
+	seed: u64,
+
+	/// Print the report as JSON.
+	#[clap(long)]
+	json: bool,
+}
+
+fn main() -> Result<(), String> {
+	let opt = Opt::parse();
+	let params = SimulationParams {
+		n_validators: opt.validators,
+		n_cores: opt.cores,
+		n_delay_tranches: opt.n_delay_tranches,
+		zeroth_delay_tranche_width: opt.zeroth_delay_tranche_width,
+		needed_approvals: opt.needed_approvals,
+		relay_vrf_modulo_samples: opt.relay_vrf_modulo_samples,
+		no_show_slots: opt.no_show_slots,
+		slot_duration_millis: opt.slot_duration_millis,
+		n_blocks: opt.blocks,
+		no_show_probability: opt.no_show_probability,
+		min_network_delay_millis: opt.min_network_delay_millis,
+		max_network_delay_millis: opt.max_network_delay_millis,
+		approval_check_millis: opt.approval_check_millis,
+		timeout_millis: opt.timeout_millis,
+		seed: opt.seed,
+	};
+
+	let report = simulate(&params).map_err(|err| err.to_string())?;
+	if opt.json {
+		println!("{}", serde_json::to_string_pretty(&report).map_err(|err| err.to_string())?);
+	} else {
+		print_report(&report);
+	}
+	Ok(())
+}
+
+fn print_report(report: &Report) {
+	println!("candidates: {}, unapproved: {}", report.candidates, report.unapproved);
+	println!(
+		"{:<28}{:>10}{:>10}{:>10}{:>10}{:>10}{:>10}",
+		"", "min", "mean", "p50", "p90", "p99", "max"
+	);
+	let rows = [
+		("time to approval (ms)", &report.time_to_approval_millis),
+		("checks per candidate", &report.checks_per_candidate),
+		("no-shows per candidate", &report.no_shows_per_candidate),
+		("highest tranche", &report.highest_tranche),
+		("checks per validator", &report.validator_load),
+	];
+	for (name, distribution) in rows {
+		match distribution {
+			Some(Distribution { min, mean, p50, p90, p99, max }) => println!(
+				"{:<28}{:>10}{:>10.1}{:>10}{:>10}{:>10}{:>10}",
+				name, min, mean, p50, p90, p99, max,
+			),
+			None => println!("{:<28}{:>10}", name, "-"),
+		}
+	}
+}